@@ -39,6 +39,16 @@ fn find_eval_static_fn(block: &ItemImpl) -> Result<&ImplItemFn> {
         .ok_or_else(|| syn::Error::new(block.span(), "expected `eval_static` function inside `impl` block"))
 }
 
+/// Returns true if the parameter is the context the function is called in, written as
+/// `ctxt: &Ctxt`.
+fn is_ctxt_param(arg: &FnArg) -> bool {
+    match arg {
+        FnArg::Typed(pat) => matches!(&*pat.pat, syn::Pat::Ident(pat) if pat.ident == "ctxt")
+            && matches!(&*pat.ty, syn::Type::Reference(_)),
+        FnArg::Receiver(_) => false,
+    }
+}
+
 /// Get the identifier of a `Type`.
 fn path_ident(path: &syn::Type) -> Result<&Ident> {
     match path {
@@ -73,32 +83,42 @@ pub enum TypeKind {
     /// A complex number.
     Complex,
 
+    /// An interval of real numbers.
+    Interval,
+
     /// A boolean.
     Bool,
 
     /// The unit type, akin to `()` in Rust.
     Unit,
 
+    /// A list of values of any type.
+    List,
+
+    /// A matrix of numbers.
+    Matrix,
+
     /// Any type.
     Value,
 }
 
-/// Helper function to match the first segment of a path in a type.
-fn match_first_segment(ty: &syn::Type) -> Result<Type> {
+/// Helper function to match the last segment of a path in a type, so that types can also be
+/// written with their full path, such as `crate::interval::Interval`.
+fn match_last_segment(ty: &syn::Type) -> Result<Type> {
     let path = match ty {
         syn::Type::Path(ty) => &ty.path,
         _ => return Err(syn::Error::new(ty.span(), "expected path")),
     };
 
-    let Some(first) = path.segments.first() else {
+    let Some(last) = path.segments.last() else {
         return Err(syn::Error::new(path.span(), "expected path"));
     };
 
-    let ident_str = first.ident.to_string();
+    let ident_str = last.ident.to_string();
     if ident_str == "Option" {
-        let args = match &first.arguments {
+        let args = match &last.arguments {
             syn::PathArguments::AngleBracketed(bracketed) if bracketed.args.len() == 1 => &bracketed.args,
-            _ => return Err(syn::Error::new(first.ident.span(), "expected one angle-bracketed argument")),
+            _ => return Err(syn::Error::new(last.ident.span(), "expected one angle-bracketed argument")),
         };
 
         let first_arg = args.first().unwrap();
@@ -109,7 +129,7 @@ fn match_first_segment(ty: &syn::Type) -> Result<Type> {
         Ok(Type {
             optional: true,
             is_ref: false,
-            kind: match_first_segment(ty)?.kind,
+            kind: match_last_segment(ty)?.kind,
         })
     } else {
         Ok(Type {
@@ -119,9 +139,12 @@ fn match_first_segment(ty: &syn::Type) -> Result<Type> {
                 "Float" => TypeKind::Float,
                 "Integer" => TypeKind::Integer,
                 "Complex" => TypeKind::Complex,
+                "Interval" => TypeKind::Interval,
                 "bool" => TypeKind::Bool,
+                "Vec" => TypeKind::List,
+                "Matrix" => TypeKind::Matrix,
                 "Value" => TypeKind::Value,
-                _ => return Err(syn::Error::new(first.ident.span(), format!("expected `Float`, `Integer`, `Complex`, `bool`, or `Value`, found `{}`", ident_str))),
+                _ => return Err(syn::Error::new(last.ident.span(), format!("expected `Float`, `Integer`, `Complex`, `Interval`, `bool`, `Vec<Value>`, `Matrix`, or `Value`, found `{}`", ident_str))),
             },
         })
     }
@@ -133,7 +156,7 @@ impl TryFrom<ReturnType> for Type {
     fn try_from(ty: ReturnType) -> Result<Self> {
         match ty {
             ReturnType::Default => Ok(Type { optional: false, is_ref: false, kind: TypeKind::Unit }),
-            ReturnType::Type(_, ty) => match_first_segment(&ty),
+            ReturnType::Type(_, ty) => match_last_segment(&ty),
         }
     }
 }
@@ -143,8 +166,8 @@ impl TryFrom<syn::Type> for Type {
 
     fn try_from(ty: syn::Type) -> Result<Self> {
         match ty {
-            syn::Type::Reference(ty) => Ok(Type { optional: false, is_ref: true, kind: match_first_segment(&ty.elem)?.kind }),
-            _ => match_first_segment(&ty),
+            syn::Type::Reference(ty) => Ok(Type { optional: false, is_ref: true, kind: match_last_segment(&ty.elem)?.kind }),
+            _ => match_last_segment(&ty),
         }
     }
 }
@@ -156,8 +179,11 @@ impl Type {
             TypeKind::Float => quote! { Float },
             TypeKind::Integer => quote! { Integer },
             TypeKind::Complex => quote! { Complex },
+            TypeKind::Interval => quote! { Interval },
             TypeKind::Bool => quote! { bool },
             TypeKind::Unit => quote! { () },
+            TypeKind::List => quote! { Vec<Value> },
+            TypeKind::Matrix => quote! { Matrix },
             TypeKind::Value => quote! { Value },
         };
 
@@ -180,8 +206,11 @@ impl Type {
             TypeKind::Float => "Float",
             TypeKind::Integer => "Integer",
             TypeKind::Complex => "Complex",
+            TypeKind::Interval => "Interval",
             TypeKind::Bool => "Boolean",
             TypeKind::Unit => "Unit",
+            TypeKind::List => "List",
+            TypeKind::Matrix => "Matrix",
             TypeKind::Value => "Value",
         }
     }
@@ -192,8 +221,11 @@ impl Type {
             TypeKind::Float => quote! { crate::numerical::value::Value::Float },
             TypeKind::Integer => quote! { crate::numerical::value::Value::Integer },
             TypeKind::Complex => quote! { crate::numerical::value::Value::Complex },
+            TypeKind::Interval => quote! { crate::numerical::value::Value::Interval },
             TypeKind::Bool => quote! { crate::numerical::value::Value::Bool },
             TypeKind::Unit => quote! { crate::numerical::value::Value::Unit },
+            TypeKind::List => quote! { crate::numerical::value::Value::List },
+            TypeKind::Matrix => quote! { crate::numerical::value::Value::Matrix },
             TypeKind::Value => quote! { crate::numerical::value::Value::Value },
         }
     }
@@ -286,6 +318,12 @@ pub struct Builtin {
 
     /// The parameters of the function.
     params: Vec<Param>,
+
+    /// Whether the function returns a [`Result`], whose error is propagated to the caller.
+    fallible: bool,
+
+    /// Whether the function takes the context it is called in as its first parameter.
+    takes_ctxt: bool,
 }
 
 impl Builtin {
@@ -325,6 +363,10 @@ impl Builtin {
         // - the argument given is an `Integer` and the parameter is a `Float`
         // - the argument given is a `Float` with a fractional part of 0 and the parameter is an
         // `Integer`
+        // - the argument given is a real number and the parameter is an `Interval`
+        //
+        // similarly, a list of rows coerces into a `Matrix` if the rows are lists of numbers with
+        // the same length, and a range coerces into a `List` of its integers
         //
         // TODO: arguments are cloned, which may or may not be ideal
         let type_checkers = self.params
@@ -354,6 +396,9 @@ impl Builtin {
                     TypeKind::Float => Some(quote! { .map(|arg| arg.coerce_float()) }),
                     TypeKind::Integer => Some(quote! { .map(|arg| arg.coerce_integer()) }),
                     TypeKind::Complex => Some(quote! { .map(|arg| arg.coerce_complex()) }),
+                    TypeKind::Interval => Some(quote! { .map(|arg| arg.coerce_interval()) }),
+                    TypeKind::List => Some(quote! { .map(|arg| arg.coerce_list()) }),
+                    TypeKind::Matrix => Some(quote! { .map(|arg| arg.coerce_matrix()) }),
                     _ => None,
                 };
 
@@ -438,9 +483,16 @@ impl Builtin {
                 quote! { #ident }
             }
         });
-        let make_value = quote! { crate::numerical::value::Value::from(
-            #pascal_name::eval_static(#(#param_idents),*)
-        ) };
+        let ctxt = self.takes_ctxt.then(|| quote! { ctxt, });
+        let make_value = if self.fallible {
+            quote! { crate::numerical::value::Value::from(
+                #pascal_name::eval_static(#ctxt #(#param_idents),*)?
+            ) }
+        } else {
+            quote! { crate::numerical::value::Value::from(
+                #pascal_name::eval_static(#ctxt #(#param_idents),*)
+            ) }
+        };
 
         if radian == Radian::Output {
             quote! {
@@ -460,10 +512,39 @@ impl Builtin {
         quote! { #item }
     }
 
+    /// Generates the statements that apply the function element-wise if it is given lists in place
+    /// of single values, such as `sin([0, pi / 2])`.
+    ///
+    /// Parameters that accept lists, matrices, or values of any type are passed through as they
+    /// are. If the function has no other parameters, nothing is generated.
+    pub fn generate_broadcast_stmts(&self) -> TokenStream2 {
+        let Self { name, params, .. } = self;
+        let broadcast = params.iter()
+            .map(|param| !matches!(param.ty.kind, TypeKind::List | TypeKind::Matrix | TypeKind::Value))
+            .collect::<Vec<_>>();
+        if !broadcast.contains(&true) {
+            return quote! {};
+        }
+
+        quote! {
+            let args = args.collect::<Vec<_>>();
+            if let Some(calls) = crate::funcs::helper::broadcast_args(stringify!(#name), &args, &[#(#broadcast),*])? {
+                return calls.into_iter()
+                    .map(|args| self.eval(ctxt, &mut args.into_iter()))
+                    .collect::<Result<_, _>>()
+                    .map(crate::numerical::value::Value::List);
+            }
+            let args = &mut args.into_iter();
+        }
+    }
+
     /// Generate the implementation of the `Builtin` trait for the function.
     fn impl_builtin(&self, radian: Radian) -> TokenStream2 {
         let Self { pascal_name, params, .. } = self;
         let arg_count = params.len();
+        let param_names = params.iter().map(|param| param.ident.to_string());
+        let signature = self.signature();
+        let broadcast = self.generate_broadcast_stmts();
         let type_checkers = self.generate_check_stmts(radian);
         let call = self.generate_call(radian);
 
@@ -471,14 +552,26 @@ impl Builtin {
             impl crate::numerical::builtin::Builtin for #pascal_name {
                 fn num_args(&self) -> usize { #arg_count }
 
+                fn params(&self) -> Option<crate::numerical::builtin::Params> {
+                    Some(crate::numerical::builtin::Params {
+                        names: &[#(#param_names),*],
+                        signature: #signature,
+                    })
+                }
+
                 fn eval(
                     &self,
                     ctxt: &crate::numerical::ctxt::Ctxt,
                     args: &mut dyn Iterator<Item = crate::numerical::value::Value>,
                 ) -> Result<crate::numerical::value::Value, crate::numerical::builtin::error::BuiltinError> {
-                    let mut arg_count = 0;
-                    #type_checkers
-                    #call
+                    #broadcast
+
+                    // compute with the precision of the context the function is called in
+                    crate::primitive::with_precision(ctxt.precision(), || {
+                        let mut arg_count = 0;
+                        #type_checkers
+                        #call
+                    })
                 }
             }
         }
@@ -501,12 +594,29 @@ impl Parse for Builtin {
         let pascal_name = path_ident(&item.self_ty)?.clone();
         let name = pascal_to_snake_case(&pascal_name);
         let eval_static_fn = find_eval_static_fn(&item)?.clone();
+        let fallible = match &eval_static_fn.sig.output {
+            ReturnType::Type(_, ty) => match &**ty {
+                syn::Type::Path(ty) => ty.path.segments.first()
+                    .is_some_and(|first| first.ident == "Result"),
+                _ => false,
+            },
+            ReturnType::Default => false,
+        };
+
+        // the first parameter can be the context, which is not an argument of the function
+        let mut inputs = eval_static_fn.sig.inputs.into_iter().peekable();
+        let takes_ctxt = inputs.peek().is_some_and(is_ctxt_param);
+        if takes_ctxt {
+            inputs.next();
+        }
 
         let builtin = Builtin {
             item,
             pascal_name,
             name,
-            params: eval_static_fn.sig.inputs.into_iter().map(Param::try_from).collect::<Result<_>>()?,
+            params: inputs.map(Param::try_from).collect::<Result<_>>()?,
+            fallible,
+            takes_ctxt,
         };
 
         // optional parameters must be at the end
@@ -24,11 +24,11 @@ use syn::parse_macro_input;
 ///
 /// The following tags are available:
 ///
-/// | Tag         | Description                                                                                                                                                      |
-/// | ----------- | ---------------------------------------------------------------------------------------------------------------------------------------------------------------- |
-/// | `message`   | The message displayed at the top of the error when it is displayed.                                                                                              |
-/// | `labels`    | A list of labels that point to the spans of the error. The first label will be associated with the first span, the second label with the second span, and so on. |
-/// | `help`      | Optional help text for the error, describing what the user can do to fix it.                                                                                     |
+/// | Tag        | Description                                                                                                              |
+/// | ---------- | ------------------------------------------------------------------------------------------------------------------------ |
+/// | `message`  | The message displayed at the top of the error when it is displayed.                                                      |
+/// | `labels`   | A list of labels that point to the spans of the error. The first label will be associated with the first span, the second label with the second span, and so on. |
+/// | `help`     | Optional help text for the error, describing what the user can do to fix it.                                             |
 ///
 /// The `message` and `help` tags accept an expression that can be converted to a [`String`], and
 /// the `labels` tag accepts an expression that can be converted to a [`Vec`] of [`String`]s. Each
@@ -55,19 +55,36 @@ pub fn error_kind(item: TokenStream) -> TokenStream {
 /// the types of its parameters, to match the types that [`Value`] provides. These are the accepted
 /// types:
 ///
-/// | Type      | Description                                                                                                              |
-/// | --------- | ------------------------------------------------------------------------------------------------------------------------ |
-/// | `Float`   | [`rug::Float`]: A floating-point value. Floats can freely coerce to `Complex`.                                           |
-/// | `Integer` | [`rug::Integer`]: An integer value. Integers can freely coerce to `Complex` or `Float`.                                  |
-/// | `Complex` | [`rug::Complex`]: A complex number value. Complex numbers can coerce to `Float` or `Integer` if the imaginary part is 0. |
-/// | `bool`    | [`bool`]: A boolean value.                                                                                               |
-/// | `()`      | [`()`]: The unit type, analogous to `()` in Rust.                                                                        |
-/// | `Value`   | Any value, regardless of type. The value will be left as a [`Value`] for the function to handle.                         |
+/// | Type         | Description                                                                                                              |
+/// | ------------ | ------------------------------------------------------------------------------------------------------------------------ |
+/// | `Float`      | [`rug::Float`]: A floating-point value. Floats can freely coerce to `Complex`.                                           |
+/// | `Integer`    | [`rug::Integer`]: An integer value. Integers can freely coerce to `Complex` or `Float`.                                  |
+/// | `Complex`    | [`rug::Complex`]: A complex number value. Complex numbers can coerce to `Float` or `Integer` if the imaginary part is 0. |
+/// | `Interval`   | [`Interval`]: An interval of real numbers. Real numbers coerce to the smallest interval containing them.                 |
+/// | `bool`       | [`bool`]: A boolean value.                                                                                               |
+/// | `()`         | [`()`]: The unit type, analogous to `()` in Rust.                                                                        |
+/// | `Vec<Value>` | [`Vec`]: A list of values of any type.                                                                                   |
+/// | `Matrix`     | [`Matrix`]: A matrix of numbers. A list of rows coerces to `Matrix` if the rows have the same length.                    |
+/// | `Value`      | Any value, regardless of type. The value will be left as a [`Value`] for the function to handle.                         |
 ///
 /// In addition, any of these types can be wrapped in an [`Option`] to make the argument optional.
 /// Optional arguments should be placed at the end of the list of parameters, though the attribute
 /// does not enforce this.
 ///
+/// The function can return any type that converts into a [`Value`]. If the function can fail, it
+/// can instead return a `Result` whose error type is `BuiltinError`; the error is then returned
+/// from the implementation of the `Builtin` trait.
+///
+/// The function can also take the context it is called in as its first parameter, written as
+/// `ctxt: &Ctxt`. This parameter is not an argument of the function; it gives the function access
+/// to the settings of the context, and lets it call `Ctxt::builtin_step` on every iteration of a
+/// long-running loop.
+///
+/// If a list is given to a parameter that is not a `Vec<Value>`, `Matrix`, or `Value`, the
+/// function is applied element-wise, and returns a list of the results. For example,
+/// `sin([0, pi / 2])` is `[0, 1]`. Lists given to several such parameters must have the same
+/// length, and are paired up element by element.
+///
 /// For trigonometric functions, the attribute can be used to indicate that the function takes
 /// input in radians, or returns an output in radians. This is done by adding the `radian` tag to
 /// the attribute, with the value `input` or `output`. If the user's trigonometric mode does not
@@ -125,6 +142,8 @@ pub fn error_kind(item: TokenStream) -> TokenStream {
 /// ```
 ///
 /// [`Value`]: cas_compute::numerical::value::Value
+/// [`Interval`]: cas_compute::interval::Interval
+/// [`Matrix`]: cas_compute::numerical::matrix::Matrix
  // NOTE: this cannot be a derive macro, since we need to know information about the function
  // signature; applying #[derive(Builtin)] to the marker struct does not provide that information
 #[proc_macro_attribute]
@@ -1,59 +1,195 @@
 use rug::{Float, Integer, Rational};
-use std::cmp::Ordering;
-use super::primitive::float;
+use super::primitive::{float, int};
+
+/// The error bound used by [`approximate_rational`].
+pub const TOLERANCE: f64 = 1e-60;
+
+/// Computes the convergents of the continued fraction with the given partial quotients, that is,
+/// the value of the continued fraction truncated after each partial quotient.
+///
+/// Every partial quotient after the first must be positive.
+pub fn convergents(continued_fraction_form: &[Integer]) -> Vec<Rational> {
+    let (mut h, mut h_prev) = (int(1), int(0));
+    let (mut k, mut k_prev) = (int(0), int(1));
+    continued_fraction_form.iter()
+        .map(|a| {
+            (h, h_prev) = (int(a * &h) + &h_prev, h.clone());
+            (k, k_prev) = (int(a * &k) + &k_prev, k.clone());
+            Rational::from((&h, &k))
+        })
+        .collect()
+}
 
 /// Computes the [`Rational`] from the continued fraction form of a float.
-fn rational_from_continued_fraction(continued_fraction_form: &[Integer]) -> Rational {
-    let mut rational = Rational::new();
-    for (i, integer) in continued_fraction_form.iter().rev().enumerate() {
-        if i == 0 {
-            if integer.cmp0() == Ordering::Equal {
-                continue;
-            }
-            rational += Rational::from(Rational::ONE / integer);
-        } else {
-            rational = (rational + integer).recip();
-        }
+///
+/// Every partial quotient after the first must be positive. The value of an empty continued
+/// fraction is zero.
+pub fn rational_from_continued_fraction(continued_fraction_form: &[Integer]) -> Rational {
+    convergents(continued_fraction_form).pop().unwrap_or_default()
+}
+
+/// Computes the partial quotients of the continued fraction of the given float.
+///
+/// The continued fraction algorithm is applied until the number is represented exactly, the
+/// error of the last convergent is less than `tolerance`, or `max_terms` partial quotients have
+/// been computed. Since the partial quotients are computed with floating point arithmetic, the
+/// algorithm also stops once the last convergent agrees with the number to its full precision, as
+/// any further partial quotients would be meaningless. The first partial quotient is the floor of the number, and the rest are
+/// positive. Non-finite numbers have no partial quotients.
+///
+/// See
+/// [Wikipedia](https://en.wikipedia.org/wiki/Continued_fraction#Calculating_continued_fraction_representations)
+/// for more information.
+pub fn continued_fraction(n: &Float, max_terms: Option<usize>, tolerance: &Float) -> Vec<Integer> {
+    let mut continued_fraction_form = Vec::new();
+    if !n.is_finite() {
+        return continued_fraction_form;
     }
 
-    if rational.cmp0() == Ordering::Equal {
-        rational
-    } else {
-        rational.recip()
+    let (mut h, mut h_prev) = (int(1), int(0));
+    let (mut k, mut k_prev) = (int(0), int(1));
+    let mut x = n.clone();
+    while max_terms.is_none_or(|max| continued_fraction_form.len() < max) {
+        // if `x` is within rounding error of an integer, such as `2.999...` when `x` should be
+        // exactly `3`, round it to that integer and stop
+        let rounded = float(x.round_ref());
+        let difference = float(&x - &rounded);
+        let (integer, fractional) = match (difference.get_exp(), x.get_exp()) {
+            (Some(difference_exp), Some(x_exp)) if difference_exp > x_exp - (x.prec() / 2) as i32 => {
+                let integer = x.clone().floor();
+                let fractional = float(&x - &integer);
+                (integer, fractional)
+            },
+            _ => (rounded, float(0)),
+        };
+        let a = integer.to_integer().unwrap();
+
+        // update the convergent `h / k`
+        (h, h_prev) = (int(&a * &h) + &h_prev, h);
+        (k, k_prev) = (int(&a * &k) + &k_prev, k);
+        continued_fraction_form.push(a);
+
+        // check how close we are to the original number
+        let error = float(n - Rational::from((&h, &k))).abs();
+        let exact = match (error.get_exp(), n.get_exp()) {
+            (Some(error_exp), Some(n_exp)) => error_exp < n_exp - n.prec() as i32,
+            _ => true,
+        };
+        if fractional.is_zero() || exact || error < *tolerance {
+            break;
+        }
+
+        x = fractional.recip();
     }
+
+    continued_fraction_form
 }
 
 /// Approximates the given float as a rational fraction.
 ///
 /// This function applies the continued fraction algorithm to the given float until the error is
-/// less than `1e-60`.
+/// less than [`TOLERANCE`].
 ///
 /// We don't use [`Float::to_rational`] because it can produce bad / useless results due to
 /// floating point arithmetic errors. Rather, we use the continued fraction algorithm to compute
 /// the rational approximation ourselves.
-///
-/// See
-/// [Wikipedia](https://en.wikipedia.org/wiki/Continued_fraction#Calculating_continued_fraction_representations)
-/// for more information.
 pub fn approximate_rational(n: &Float) -> Rational {
-    let orig = n;
+    approximate_rational_with(n, &float(TOLERANCE))
+}
 
-    let mut continued_fraction_form = Vec::new();
-    let mut n = n.clone();
-    loop {
-        let (integer, fractional) = n.trunc_fract(float(0));
-        continued_fraction_form.push(integer.to_integer().unwrap());
+/// Approximates the given float as a rational fraction, applying the continued fraction algorithm
+/// until the error is less than `tolerance`.
+pub fn approximate_rational_with(n: &Float, tolerance: &Float) -> Rational {
+    // approximate the magnitude, so that negative numbers are approximated by the negation of the
+    // approximation of their magnitude
+    let rational = rational_from_continued_fraction(
+        &continued_fraction(&float(n.abs_ref()), None, tolerance),
+    );
+    if n.is_sign_negative() {
+        -rational
+    } else {
+        rational
+    }
+}
 
-        // check how close we are to the original number
-        let rational = rational_from_continued_fraction(&continued_fraction_form);
-        let error = float(orig - rational).abs();
+/// Finds the best rational approximation of the given float with a denominator of at most
+/// `max_denominator`, which must be positive.
+///
+/// The best approximation is either a convergent or a semiconvergent of the continued fraction of
+/// the number. If a convergent with an error less than `tolerance` is found first, that convergent
+/// is returned instead.
+pub fn best_rational(n: &Float, max_denominator: &Integer, tolerance: &Float) -> Rational {
+    let error = |r: &Rational| float(n - r).abs();
 
-        if fractional.is_zero() || error < 1e-60 {
-            break;
+    let (mut h, mut h_prev) = (int(1), int(0));
+    let (mut k, mut k_prev) = (int(0), int(1));
+    for a in continued_fraction(n, None, tolerance) {
+        let k_next = int(&a * &k) + &k_prev;
+        if k_next > *max_denominator {
+            // the largest semiconvergent with a small enough denominator may be closer than the
+            // last convergent
+            let t = int(max_denominator - &k_prev) / &k;
+            let semiconvergent = Rational::from((int(&t * &h) + &h_prev, int(&t * &k) + &k_prev));
+            let convergent = Rational::from((h, k));
+            return if error(&semiconvergent) < error(&convergent) {
+                semiconvergent
+            } else {
+                convergent
+            };
         }
 
-        n = fractional.recip();
+        (h, h_prev) = (int(&a * &h) + &h_prev, h);
+        (k, k_prev) = (k_next, k);
     }
 
-    rational_from_continued_fraction(&continued_fraction_form)
+    if k == 0 {
+        Rational::new()
+    } else {
+        Rational::from((h, k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{consts::pi, primitive::float_from_str};
+
+    /// Converts the given integers into a list of [`Integer`]s.
+    fn ints(ns: &[i32]) -> Vec<Integer> {
+        ns.iter().map(|&n| int(n)).collect()
+    }
+
+    #[test]
+    fn pi_continued_fraction() {
+        let pi = pi();
+        assert_eq!(continued_fraction(&pi, Some(5), &float(0)), ints(&[3, 7, 15, 1, 292]));
+        assert_eq!(
+            convergents(&ints(&[3, 7, 15, 1])),
+            vec![Rational::from(3), Rational::from((22, 7)), Rational::from((333, 106)), Rational::from((355, 113))],
+        );
+    }
+
+    #[test]
+    fn negative_continued_fraction() {
+        let n = float_from_str("-0.3");
+        assert_eq!(continued_fraction(&n, None, &float(TOLERANCE)), ints(&[-1, 1, 2, 3]));
+        assert_eq!(approximate_rational(&n), Rational::from((-3, 10)));
+    }
+
+    #[test]
+    fn bounded_denominator() {
+        let pi = pi();
+        let tolerance = float(0);
+        assert_eq!(best_rational(&pi, &int(1), &tolerance), Rational::from(3));
+        assert_eq!(best_rational(&pi, &int(10), &tolerance), Rational::from((22, 7)));
+        assert_eq!(best_rational(&pi, &int(1000), &tolerance), Rational::from((355, 113)));
+
+        assert_eq!(best_rational(&pi, &int(110), &tolerance), Rational::from((333, 106)));
+
+        // semiconvergent between 22/7 and 333/106
+        assert_eq!(best_rational(&pi, &int(57), &tolerance), Rational::from((179, 57)));
+
+        // stops at the first convergent within the tolerance
+        assert_eq!(best_rational(&pi, &int(1000), &float(0.01)), Rational::from((22, 7)));
+    }
 }
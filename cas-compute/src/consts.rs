@@ -1,29 +1,77 @@
-//! Additional constants used in the library. This module consists of static constants that return
+//! Additional constants used in the library. This module consists of functions that return
 //! [`Float`]s with the given value.
+//!
+//! Each constant is computed with the current precision of the thread (see
+//! [`precision`](crate::primitive::precision)). Constants that are expensive to compute are
+//! cached for each precision they are requested with.
 
-use once_cell::sync::Lazy;
 use rug::{Complex, Float};
-use super::primitive::{complex, float};
+use std::{cell::RefCell, collections::HashMap};
+use super::primitive::{complex, float, precision};
 
-pub static ZERO: Lazy<Float> = Lazy::new(|| float(0));
+thread_local! {
+    /// Cache of the constants that have been computed on this thread, keyed by their name and
+    /// precision.
+    static CACHE: RefCell<HashMap<(&'static str, u32), Float>> = RefCell::new(HashMap::new());
+}
 
-pub static ONE: Lazy<Float> = Lazy::new(|| float(1));
+/// Returns the cached value of the constant with the given name at the current precision, or
+/// computes and caches it if it has not been computed yet.
+fn cached(name: &'static str, compute: impl FnOnce() -> Float) -> Float {
+    let key = (name, precision());
+    if let Some(value) = CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return value;
+    }
 
-pub static ONE_HALF: Lazy<Float> = Lazy::new(|| float(1) / float(2));
+    let value = compute();
+    CACHE.with(|cache| cache.borrow_mut().insert(key, value.clone()));
+    value
+}
 
-pub static TWO: Lazy<Float> = Lazy::new(|| float(2));
+pub fn zero() -> Float {
+    float(0)
+}
 
-pub static TEN: Lazy<Float> = Lazy::new(|| float(10));
+pub fn one() -> Float {
+    float(1)
+}
+
+pub fn one_half() -> Float {
+    float(1) / 2u8
+}
+
+pub fn two() -> Float {
+    float(2)
+}
+
+pub fn ten() -> Float {
+    float(10)
+}
 
 /// The imaginary unit.
-pub static I: Lazy<Complex> = Lazy::new(|| complex((0, 1)));
+pub fn i() -> Complex {
+    complex((0, 1))
+}
 
 /// Euler's number.
-pub static E: Lazy<Float> = Lazy::new(|| float(1).exp());
+pub fn e() -> Float {
+    cached("e", || float(1).exp())
+}
 
 /// The golden ratio.
-pub static PHI: Lazy<Float> = Lazy::new(|| (float(1) + float(5).sqrt()) / float(2));
+pub fn phi() -> Float {
+    cached("phi", || (float(1) + float(5).sqrt()) / float(2))
+}
+
+/// Positive infinity.
+pub fn inf() -> Float {
+    float(f64::INFINITY)
+}
 
-pub static PI: Lazy<Float> = Lazy::new(|| float(-1).acos());
+pub fn pi() -> Float {
+    cached("pi", || float(-1).acos())
+}
 
-pub static TAU: Lazy<Float> = Lazy::new(|| float(2) * &*PI);
+pub fn tau() -> Float {
+    pi() * 2u8
+}
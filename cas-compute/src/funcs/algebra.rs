@@ -0,0 +1,430 @@
+//! Symbolic manipulation of expressions.
+//!
+//! These functions make the [`crate::symbolic`] module available to CalcScript, using
+//! [`Value::Expr`] to hold unevaluated expressions:
+//!
+//! - `quote(expr)` returns `expr` without evaluating it.
+//! - `simplify(expr)` and `expand(expr)` simplify and expand `expr`.
+//! - `diff(expr, x)` and `integrate(expr, x)` differentiate and integrate `expr` with respect to
+//!   the variable `x`.
+//! - `subs(expr, x, value)` replaces every occurrence of `x` in `expr` with `value`.
+//! - `apart(expr, x)` decomposes the rational function `expr` in `x` into partial fractions.
+//! - `dsolve(ode)` solves an ordinary differential equation written in prime notation, such as
+//!   `dsolve(y'(x) == y(x))`, returning the right-hand side of its solution `y(x) = ...`. Up to two
+//!   initial conditions can follow the equation, as in `dsolve(y'(x) == y(x), y(0) == 2)`.
+//!
+//! Arguments to these functions are **quoted** rather than evaluated: each argument is converted
+//! into a symbolic expression, in which variables that have been assigned a number or an
+//! expression are replaced by their values. Undefined variables, the variable named by `x`, and the
+//! builtin constants such as `pi` and `e` are kept as symbols. For example, `simplify(x + x)` is
+//! `2x` if `x` is undefined, and `f = quote(x^2)` followed by `diff(f, x)` is `2x`.
+//!
+//! If the result of a function is a single number, it is returned as a number instead of an
+//! expression.
+
+use cas_parser::parser::{
+    ast::{call::Call, expr::Expr, literal::Literal},
+    token::op::BinOpKind,
+};
+use crate::numerical::{
+    builtin::{
+        error::BuiltinError,
+        func_specific::{SymbolicError, SymbolicErrorKind},
+        Builtin,
+    },
+    ctxt::Ctxt,
+    error::{kind::{MissingArgument, TooManyArguments, TypeMismatch}, Error},
+    value::Value,
+};
+use crate::primitive::int;
+use crate::symbolic::{
+    apart,
+    calculus::{diff, integrate},
+    dsolve::{dsolve, Equation, Solution},
+    expand,
+    expr::{is_convertible, Expr as SymExpr, Primary},
+    simplify,
+};
+use std::sync::Arc;
+
+/// Describes one of the functions in this module.
+struct Signature {
+    /// The name of the function.
+    name: &'static str,
+
+    /// The signature of the function, used in error messages.
+    signature: &'static str,
+
+    /// The number of arguments the function takes.
+    num_args: usize,
+
+    /// The number of arguments that must be given; the rest are optional.
+    required: usize,
+
+    /// The indices of the arguments that name a variable, rather than hold an expression.
+    vars: &'static [usize],
+}
+
+impl Signature {
+    /// Creates an error of the given kind.
+    fn error(&self, kind: SymbolicErrorKind) -> BuiltinError {
+        SymbolicError::new(self.name, kind).into()
+    }
+
+    /// Checks that the function was given the correct number of arguments.
+    fn check_count(&self, given: usize) -> Result<(), BuiltinError> {
+        if given > self.num_args {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: self.name.to_owned(),
+                expected: self.num_args,
+                given,
+                signature: self.signature.to_owned(),
+            }));
+        }
+        if given < self.required {
+            return Err(BuiltinError::MissingArgument(MissingArgument {
+                name: self.name.to_owned(),
+                index: given,
+                expected: self.num_args,
+                given,
+                signature: self.signature.to_owned(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Replaces the variables in the expression given as argument `index` with their values from
+    /// the context, except for the `bound` variables and the builtin constants. Nested calls to the
+    /// functions in this module are applied as well, so that e.g. `subs(diff(f, x), x, 2)`
+    /// differentiates `f` before substituting.
+    fn resolve<'a>(
+        &self,
+        ctxt: &Ctxt,
+        expr: &'a SymExpr,
+        bound: &[&'a str],
+        index: usize,
+    ) -> Result<SymExpr, BuiltinError> {
+        let map = |exprs: &'a [SymExpr], bound: &[&'a str]| exprs.iter()
+            .map(|expr| self.resolve(ctxt, expr, bound, index))
+            .collect::<Result<Vec<_>, _>>();
+        Ok(match expr {
+            SymExpr::Primary(Primary::Symbol(name)) => {
+                if bound.contains(&name.as_str()) || ctxt.is_constant(name) {
+                    return Ok(expr.clone());
+                }
+                match ctxt.get_var(name) {
+                    Some(value) => {
+                        let given = value.typename();
+                        value.into_expr()
+                            .ok_or_else(|| self.error(SymbolicErrorKind::InvalidVariable {
+                                index,
+                                name: name.to_string(),
+                                given,
+                            }))?
+                    },
+                    None => expr.clone(),
+                }
+            },
+            SymExpr::Primary(Primary::Call(name, args)) => match function(name) {
+                Some((signature, apply)) => {
+                    signature.check_count(args.len())?;
+                    let mut bound = bound.to_vec();
+                    for &var in signature.vars {
+                        match args[var].as_symbol() {
+                            Some(symbol) => bound.push(symbol),
+                            None => return Err(signature.error(SymbolicErrorKind::NotAVariable { index })),
+                        }
+                    }
+                    apply(map(args, &bound)?)?
+                },
+                None => SymExpr::Primary(Primary::Call(name.clone(), map(args, bound)?)),
+            },
+            SymExpr::Primary(_) => expr.clone(),
+            SymExpr::Add(terms) => SymExpr::Add(map(terms, bound)?.into()),
+            SymExpr::Mul(factors) => SymExpr::Mul(map(factors, bound)?.into()),
+            SymExpr::Exp(base, exp) => SymExpr::Exp(
+                Arc::new(self.resolve(ctxt, base, bound, index)?),
+                Arc::new(self.resolve(ctxt, exp, bound, index)?),
+            ),
+        })
+    }
+
+    /// Quotes the arguments of the call, converting each of them into a [`Value::Expr`].
+    fn quote(&self, ctxt: &Ctxt, call: &Call) -> Result<Vec<Value>, BuiltinError> {
+        self.check_count(call.args.len())?;
+
+        // variables named by the call are never replaced
+        let mut bound = Vec::new();
+        for &index in self.vars {
+            match &call.args[index] {
+                Expr::Literal(Literal::Symbol(symbol)) => bound.push(symbol.name.as_str()),
+                _ => return Err(self.error(SymbolicErrorKind::NotAVariable { index })),
+            }
+        }
+
+        call.args.iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                if !is_convertible(arg) {
+                    return Err(self.error(SymbolicErrorKind::Unsupported { index }));
+                }
+                let expr = SymExpr::from(arg.clone());
+                if self.vars.contains(&index) {
+                    return Ok(Value::Expr(expr));
+                }
+                self.resolve(ctxt, &expr, &bound, index).map(Value::Expr)
+            })
+            .collect()
+    }
+
+    /// Converts the given evaluated arguments into expressions, checking that the variable
+    /// arguments are symbols.
+    fn exprs(&self, args: &mut dyn Iterator<Item = Value>) -> Result<Vec<SymExpr>, BuiltinError> {
+        let args = args.collect::<Vec<_>>();
+        self.check_count(args.len())?;
+
+        args.into_iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                let given = arg.typename();
+                match arg.into_expr() {
+                    Some(expr) if self.vars.contains(&index) && expr.as_symbol().is_none() => {
+                        Err(self.error(SymbolicErrorKind::NotAVariable { index }))
+                    },
+                    Some(expr) => Ok(expr),
+                    None => Err(BuiltinError::TypeMismatch(TypeMismatch {
+                        name: self.name.to_owned(),
+                        index,
+                        expected: "Expr",
+                        given,
+                        signature: self.signature.to_owned(),
+                    })),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A function that applies one of the builtins in this module to its arguments.
+type Apply = fn(Vec<SymExpr>) -> Result<SymExpr, BuiltinError>;
+
+/// Declares builtins that operate on symbolic expressions. Each builtin quotes its arguments, then
+/// passes them to the given function as [`SymExpr`]s.
+macro_rules! symbolic {
+    ($(
+        $(#[$attr:meta])*
+        $upname:ident $name:literal($first:literal $(, $arg:literal)*), vars = [$($var:literal),*],
+        |$args:ident| $body:expr;
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug)]
+            pub struct $upname;
+
+            impl $upname {
+                /// The signature of the function.
+                const SIGNATURE: Signature = Signature {
+                    name: $name,
+                    signature: concat!($name, "(", $first, $(", ", $arg,)* ")"),
+                    num_args: [$first $(, $arg)*].len(),
+                    required: [$first $(, $arg)*].len(),
+                    vars: &[$($var),*],
+                };
+
+                /// Applies the function to the given expressions, returning [`None`] if there is no
+                /// result.
+                fn compute($args: Vec<SymExpr>) -> Option<SymExpr> {
+                    $body
+                }
+
+                /// Applies the function to the given expressions.
+                fn apply(args: Vec<SymExpr>) -> Result<SymExpr, BuiltinError> {
+                    Self::compute(args).ok_or_else(|| Self::SIGNATURE.error(SymbolicErrorKind::NoResult))
+                }
+            }
+
+            impl Builtin for $upname {
+                fn num_args(&self) -> usize {
+                    Self::SIGNATURE.num_args
+                }
+
+                fn eval(&self, _: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+                    Self::apply(Self::SIGNATURE.exprs(args)?).map(Value::from)
+                }
+
+                fn takes_call(&self) -> bool {
+                    true
+                }
+
+                fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+                    Some(
+                        Self::SIGNATURE.quote(ctxt, call)
+                            .and_then(|args| self.eval(ctxt, &mut args.into_iter()))
+                            .map_err(|err| err.into_error(call))
+                    )
+                }
+            }
+        )*
+
+        /// Returns the signature of the function in this module with the given name, and a
+        /// function that applies it.
+        fn function(name: &str) -> Option<(&'static Signature, Apply)> {
+            match name {
+                $($name => Some((&$upname::SIGNATURE, $upname::apply)),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+symbolic! {
+    /// Returns the expression without evaluating it.
+    Quote "quote"("expr"), vars = [], |args| args.into_iter().next();
+
+    /// Simplifies the expression.
+    Simplify "simplify"("expr"), vars = [], |args| Some(simplify(&args[0]));
+
+    /// Expands products and powers of sums in the expression.
+    Expand "expand"("expr"), vars = [], |args| Some(expand(&args[0]));
+
+    /// Differentiates the expression with respect to a variable.
+    Diff "diff"("expr", "var"), vars = [1], |args| diff(&args[0], args[1].as_symbol()?);
+
+    /// Integrates the expression with respect to a variable.
+    Integrate "integrate"("expr", "var"), vars = [1], |args| integrate(&args[0], args[1].as_symbol()?);
+
+    /// Replaces a variable in the expression with a value.
+    Subs "subs"("expr", "var", "value"), vars = [1], |args| Some(simplify(&args[0].substitute(&args[1], &args[2])));
+
+    /// Decomposes a rational function of a variable into partial fractions.
+    Apart "apart"("expr", "var"), vars = [1], |args| apart(&args[0], args[1].as_symbol()?);
+}
+
+/// Solves an ordinary differential equation, optionally applying initial conditions.
+///
+/// An implicit solution is returned as the expression `lhs - rhs`, which is zero when the
+/// equation `lhs = rhs` holds.
+#[derive(Debug)]
+pub struct Dsolve;
+
+impl Dsolve {
+    /// The signature of the function.
+    const SIGNATURE: Signature = Signature {
+        name: "dsolve",
+        signature: "dsolve(ode, condition1 (optional), condition2 (optional))",
+        num_args: 3,
+        required: 1,
+        vars: &[],
+    };
+
+    /// Quotes the arguments of the call. An argument of the form `lhs == rhs` is the equation
+    /// between its two sides; any other argument `expr` is the equation `expr = 0`.
+    ///
+    /// The variable that the unknown function of the ODE depends on, such as `x` in `y'(x)`, is
+    /// never replaced.
+    fn quote(ctxt: &Ctxt, call: &Call) -> Result<Vec<Equation>, BuiltinError> {
+        let signature = &Self::SIGNATURE;
+        signature.check_count(call.args.len())?;
+
+        let equations = call.args.iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                let convertible = match innermost(arg) {
+                    Expr::Binary(bin) if bin.op.kind == BinOpKind::Eq => {
+                        is_convertible(&bin.lhs) && is_convertible(&bin.rhs)
+                    },
+                    arg => is_convertible(arg),
+                };
+                if convertible {
+                    Ok(Equation::from(arg.clone()))
+                } else {
+                    Err(signature.error(SymbolicErrorKind::Unsupported { index }))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let bound = independent_var(&equations[0]).into_iter().collect::<Vec<_>>();
+        equations.iter()
+            .enumerate()
+            .map(|(index, equation)| Ok(Equation::new(
+                signature.resolve(ctxt, &equation.lhs, &bound, index)?,
+                signature.resolve(ctxt, &equation.rhs, &bound, index)?,
+            )))
+            .collect()
+    }
+
+    /// Solves the ODE given by the first equation, with the remaining equations as initial
+    /// conditions.
+    fn apply(equations: Vec<Equation>) -> Result<Value, BuiltinError> {
+        let solution = dsolve(&equations[0], &equations[1..])
+            .map_err(|err| Self::SIGNATURE.error(SymbolicErrorKind::NoSolution {
+                reason: err.to_string(),
+            }))?;
+        Ok(match solution {
+            Solution::Explicit { expr, .. } => Value::from(expr),
+            Solution::Implicit { equation, .. } => Value::from(equation.difference()),
+        })
+    }
+}
+
+impl Builtin for Dsolve {
+    fn num_args(&self) -> usize {
+        Self::SIGNATURE.num_args
+    }
+
+    fn eval(&self, _: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        // expressions given as values cannot contain `==`, so each is the equation `expr = 0`
+        let equations = args.collect::<Vec<_>>();
+        Self::SIGNATURE.check_count(equations.len())?;
+        let equations = equations.into_iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                let given = arg.typename();
+                arg.into_expr()
+                    .map(|expr| Equation::new(expr, SymExpr::Primary(Primary::Integer(int(0)))))
+                    .ok_or_else(|| BuiltinError::TypeMismatch(TypeMismatch {
+                        name: Self::SIGNATURE.name.to_owned(),
+                        index,
+                        expected: "Expr",
+                        given,
+                        signature: Self::SIGNATURE.signature.to_owned(),
+                    }))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::apply(equations)
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(
+            Self::quote(ctxt, call)
+                .and_then(Self::apply)
+                .map_err(|err| err.into_error(call))
+        )
+    }
+}
+
+/// Returns the expression inside any number of parentheses.
+fn innermost(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(paren) => paren.innermost(),
+        expr => expr,
+    }
+}
+
+/// Returns the variable that the derivatives in the equation are taken with respect to, such as
+/// `x` in `y'(x)`.
+fn independent_var(equation: &Equation) -> Option<&str> {
+    [&equation.lhs, &equation.rhs].into_iter()
+        .flat_map(SymExpr::post_order_iter)
+        .find_map(|expr| match expr {
+            SymExpr::Primary(Primary::Call(name, args)) if name.ends_with('\'') => match args.as_slice() {
+                [arg] => arg.as_symbol(),
+                _ => None,
+            },
+            _ => None,
+        })
+}
@@ -1,7 +1,7 @@
 //! Utilities for converting between degrees and radians.
 
 use cas_attrs::builtin;
-use crate::consts::{PI, TAU};
+use crate::consts::{pi, tau};
 use rug::Float;
 
 /// Converts the given value from degrees to radians.
@@ -11,7 +11,7 @@ pub struct Dtr;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Dtr {
     pub fn eval_static(n: Float) -> Float {
-        n * &*PI / 180.0
+        n * &pi() / 180.0
     }
 }
 
@@ -22,7 +22,7 @@ pub struct Rtd;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Rtd {
     pub fn eval_static(n: Float) -> Float {
-        n * 180.0 / &*PI
+        n * 180.0 / &pi()
     }
 }
 
@@ -36,6 +36,6 @@ pub struct Circle;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Circle {
     pub fn eval_static(n: Float) -> Float {
-        n * &*TAU
+        n * &tau()
     }
 }
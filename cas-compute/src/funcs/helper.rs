@@ -26,3 +26,50 @@ pub(crate) fn count_all_args(
 
     *arg_count
 }
+
+/// Private helper function used by the `builtin` macro to apply a function element-wise when it is
+/// given lists in place of single values.
+///
+/// `broadcast` has an entry for each parameter of the function, which is `true` if the parameter
+/// accepts a single value, rather than a list or a value of any type. If any such parameter is
+/// given a list, the arguments for each element-wise call of the function are returned: the `i`-th
+/// call is given the `i`-th element of each of these lists, and the other arguments as they are.
+/// Otherwise, [`None`] is returned.
+pub(crate) fn broadcast_args(
+    name: &'static str,
+    args: &[crate::numerical::value::Value],
+    broadcast: &[bool],
+) -> Result<Option<Vec<Vec<crate::numerical::value::Value>>>, crate::numerical::builtin::error::BuiltinError> {
+    use crate::numerical::{builtin::func_specific::{ListError, ListErrorKind}, value::Value};
+
+    let mut len = None;
+    for (index, arg) in args.iter().enumerate() {
+        let Value::List(list) = arg else {
+            continue;
+        };
+        if !broadcast.get(index).copied().unwrap_or(false) {
+            continue;
+        }
+
+        match len {
+            Some(expected) if expected != list.len() => {
+                return Err(ListError::new(name, ListErrorKind::LengthMismatch {
+                    index,
+                    expected,
+                    given: list.len(),
+                }).into());
+            },
+            _ => len = Some(list.len()),
+        }
+    }
+
+    Ok(len.map(|len| (0..len)
+        .map(|i| args.iter()
+            .enumerate()
+            .map(|(index, arg)| match arg {
+                Value::List(list) if broadcast.get(index).copied().unwrap_or(false) => list[i].clone(),
+                arg => arg.clone(),
+            })
+            .collect())
+        .collect()))
+}
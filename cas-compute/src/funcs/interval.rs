@@ -0,0 +1,262 @@
+//! Functions for interval arithmetic.
+//!
+//! The [`Interval`](crate::interval::Interval) type itself is defined in [`crate::interval`].
+//! This module provides the builtin functions that create and inspect intervals, and the interval
+//! implementations of elementary functions used in [`NumberMode::Interval`].
+
+use cas_attrs::builtin;
+use cas_parser::parser::ast::call::Call;
+use crate::interval;
+use crate::numerical::{
+    builtin::{error::BuiltinError, Builtin},
+    ctxt::{Ctxt, NumberMode, TrigMode},
+    error::{kind::{MissingArgument, TooManyArguments, TypeMismatch}, Error},
+    eval::Eval,
+    value::Value,
+};
+use crate::primitive::float;
+use rug::Float;
+
+/// The builtin functions with an interval implementation, which are used instead of the regular
+/// implementation when any argument is an interval, or the context is in
+/// [`NumberMode::Interval`].
+pub const SUPPORTED: [&str; 8] = ["abs", "atan", "cos", "exp", "ln", "sin", "sqrt", "tan"];
+
+/// Returns the smallest interval containing `pi / 180`, used to convert degrees to radians.
+fn degree() -> interval::Interval {
+    interval::Interval::pi().div(&interval::Interval::from_integer(&180.into()))
+}
+
+/// Evaluates the builtin function with the given name with interval arithmetic, if it is one of
+/// the [`SUPPORTED`] functions and it is given a single real number or interval.
+///
+/// A list of real numbers or intervals is also accepted, in which case the function is applied to
+/// each element.
+///
+/// Returns [`None`] if the function has no interval implementation, or the arguments are not
+/// suitable for it.
+pub(crate) fn eval_builtin(name: &str, ctxt: &Ctxt, args: &[Value]) -> Option<Value> {
+    let [arg] = args else {
+        return None;
+    };
+    if let Value::List(values) = arg {
+        return values.iter()
+            .map(|value| eval_builtin(name, ctxt, std::slice::from_ref(value)))
+            .collect::<Option<_>>()
+            .map(Value::List);
+    }
+    let Value::Interval(x) = arg.clone().coerce_interval() else {
+        return None;
+    };
+
+    let degrees = ctxt.trig_mode == TrigMode::Degrees;
+    let angle = || if degrees { x.mul(&degree()) } else { x.clone() };
+    Some(Value::Interval(match name {
+        "abs" => x.abs(),
+        "atan" if degrees => x.atan().div(&degree()),
+        "atan" => x.atan(),
+        "cos" => angle().cos(),
+        "exp" => x.exp(),
+        "ln" => x.ln(),
+        "sin" => angle().sin(),
+        "sqrt" => x.sqrt(),
+        "tan" => angle().tan(),
+        _ => return None,
+    }))
+}
+
+/// Returns the smallest interval containing both of the given numbers or intervals. If only one
+/// argument is given, returns the smallest interval containing it.
+#[derive(Debug)]
+pub struct Interval;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Interval {
+    pub fn eval_static(a: interval::Interval, b: Option<interval::Interval>) -> interval::Interval {
+        match b {
+            Some(b) => a.hull(&b),
+            None => a,
+        }
+    }
+}
+
+/// Returns the lower bound of an interval.
+#[derive(Debug)]
+pub struct Lower;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Lower {
+    pub fn eval_static(n: interval::Interval) -> Float {
+        n.lo().clone()
+    }
+}
+
+/// Returns the upper bound of an interval.
+#[derive(Debug)]
+pub struct Upper;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Upper {
+    pub fn eval_static(n: interval::Interval) -> Float {
+        n.hi().clone()
+    }
+}
+
+/// Returns the midpoint of an interval.
+#[derive(Debug)]
+pub struct Mid;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Mid {
+    pub fn eval_static(n: interval::Interval) -> Float {
+        n.mid()
+    }
+}
+
+/// Returns an upper bound on the width of an interval, which bounds the error of the computation
+/// that produced it.
+#[derive(Debug)]
+pub struct Width;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Width {
+    pub fn eval_static(n: interval::Interval) -> Float {
+        if n.is_empty() {
+            float(0)
+        } else {
+            n.width()
+        }
+    }
+}
+
+/// Evaluates its argument in interval arithmetic, returning an interval that is guaranteed to
+/// contain the exact result.
+///
+/// Float literals and the builtin constants in the argument are replaced by intervals that
+/// enclose their exact values (see [`NumberMode::Interval`]). For example, `enclose(e^pi - pi)`
+/// shows that `e^pi - pi` is close to, but not exactly, `20`.
+#[derive(Debug)]
+pub struct Enclose;
+
+impl Enclose {
+    const SIGNATURE: &'static str = "enclose(expr: Value)";
+
+    /// Converts the value into an interval, or each element of a list into an interval.
+    ///
+    /// Returns the type of the first value that cannot be converted if this fails.
+    fn enclose(value: Value) -> Result<Value, &'static str> {
+        match value.coerce_interval() {
+            Value::Interval(n) => Ok(Value::Interval(n)),
+            Value::List(values) => values.into_iter()
+                .map(Self::enclose)
+                .collect::<Result<_, _>>()
+                .map(Value::List),
+            value => Err(value.typename()),
+        }
+    }
+}
+
+impl Builtin for Enclose {
+    fn num_args(&self) -> usize {
+        1
+    }
+
+    fn eval(&self, _: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        let Some(value) = args.next() else {
+            return Err(BuiltinError::MissingArgument(MissingArgument {
+                name: "enclose".to_owned(),
+                index: 0,
+                expected: 1,
+                given: 0,
+                signature: Self::SIGNATURE.to_owned(),
+            }));
+        };
+
+        let given = 1 + args.count();
+        if given > 1 {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: "enclose".to_owned(),
+                expected: 1,
+                given,
+                signature: Self::SIGNATURE.to_owned(),
+            }));
+        }
+
+        Self::enclose(value).map_err(|given| BuiltinError::TypeMismatch(TypeMismatch {
+            name: "enclose".to_owned(),
+            index: 0,
+            expected: "Interval",
+            given,
+            signature: Self::SIGNATURE.to_owned(),
+        }))
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        ctxt.number_mode = NumberMode::Interval;
+        let args = match call.args.iter().map(|arg| arg.eval(ctxt)).collect::<Result<Vec<_>, _>>() {
+            Ok(args) => args,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(self.eval(ctxt, &mut args.into_iter()).map_err(|err| err.into_error(call)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::Expr, Parser};
+    use crate::primitive::float_from_str;
+    use super::*;
+
+    /// Evaluates the given expression, which must evaluate to an interval.
+    fn enclose(source: &str) -> interval::Interval {
+        let expr = Parser::new(source).try_parse_full::<Expr>().unwrap();
+        match expr.eval_default().unwrap() {
+            Value::Interval(n) => n,
+            value => panic!("expected an interval, got {:?}", value),
+        }
+    }
+
+    #[test]
+    fn e_pi_minus_pi() {
+        let result = enclose("enclose(e^pi - pi)");
+        assert!(!result.contains(&float(20)));
+        assert!(*result.lo() > float_from_str("19.9990999791894757672664429846690444960689368432251061724701018172165259444042437848889371717254321"));
+        assert!(*result.hi() < float_from_str("19.9990999791894757672664429846690444960689368432251061724701018172165259444042437848889371717254322"));
+        assert!(result.width() < 1e-140);
+    }
+
+    #[test]
+    fn literals_and_functions() {
+        // 0.1 is not exactly representable, so its enclosure is not a single point
+        let tenth = enclose("enclose(0.1)");
+        assert!(tenth.lo() < tenth.hi());
+
+        // exact arithmetic is still used where possible
+        assert_eq!(enclose("enclose(1 / 4)"), interval::Interval::point(float(0.25)));
+
+        let sqrt = enclose("enclose(sqrt(2))");
+        assert!(sqrt.lo() < sqrt.hi());
+        assert!(sqrt.contains(&float(2).sqrt()));
+
+        let sin = enclose("enclose(sin(pi))");
+        assert!(sin.contains_zero() && sin.width() < 1e-150);
+    }
+
+    #[test]
+    fn construct_and_inspect() {
+        let n = enclose("interval(3, 1) + interval(2)");
+        assert_eq!(n, interval::Interval::new(float(3), float(5)));
+
+        let eval = |source: &str| Parser::new(source).try_parse_full::<Expr>().unwrap().eval_default();
+        assert_eq!(eval("lower(interval(1, 2) * 3)").unwrap(), Value::Float(float(3)));
+        assert_eq!(eval("width(interval(1, 2) ^ 2)").unwrap(), Value::Float(float(3)));
+        assert_eq!(eval("interval(1, 2) < interval(3, 4)").unwrap(), Value::Boolean(true));
+        assert_eq!(eval("interval(1, 3) ~== interval(2, 4)").unwrap(), Value::Boolean(true));
+        assert!(eval("interval(1, 3) < interval(2, 4)").is_err());
+        assert!(eval("enclose(gamma(0.5))").is_err());
+    }
+}
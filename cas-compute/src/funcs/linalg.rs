@@ -0,0 +1,341 @@
+//! Linear algebra functions on matrices and vectors.
+//!
+//! Matrices are created from a list of rows with the `matrix` function, and any function that
+//! takes a matrix also accepts a list of rows directly, such as `det([[1, 2], [3, 4]])`. Vectors are
+//! lists of numbers. See [`crate::numerical::matrix`] for the algorithms used, and the precision
+//! of their results.
+
+use cas_attrs::builtin;
+use cas_parser::parser::token::op::BinOpKind;
+use crate::numerical::{
+    builtin::{
+        error::BuiltinError,
+        func_specific::{MatrixError, MatrixErrorKind},
+    },
+    error::kind::TypeMismatch,
+    eval::eval_operands,
+    matrix,
+    value::Value,
+};
+use crate::primitive::float;
+use rug::{Float, Integer};
+
+/// Creates an error for the function with the given name.
+fn error(name: &'static str, kind: MatrixErrorKind) -> BuiltinError {
+    MatrixError::new(name, kind).into()
+}
+
+/// Returns an error if argument `index` of the function is not a square matrix.
+fn check_square(name: &'static str, a: &matrix::Matrix, index: usize) -> Result<(), BuiltinError> {
+    if a.is_square() {
+        Ok(())
+    } else {
+        Err(error(name, MatrixErrorKind::NotSquare { index, rows: a.rows(), cols: a.cols() }))
+    }
+}
+
+/// Returns an error if argument `index` of the function is not a vector of numbers with `len`
+/// elements.
+fn check_vector(
+    name: &'static str,
+    v: &[Value],
+    len: usize,
+    index: usize,
+) -> Result<(), BuiltinError> {
+    if let Some(entry) = v.iter().find(|entry| !entry.is_complex()) {
+        return Err(error(name, MatrixErrorKind::InvalidEntry { index, given: entry.typename() }));
+    }
+    if v.len() != len {
+        return Err(error(name, MatrixErrorKind::LengthMismatch { index, expected: len, given: v.len() }));
+    }
+    Ok(())
+}
+
+/// Returns the magnitude of a number.
+fn magnitude(n: &Value) -> Float {
+    match n.clone().coerce_complex() {
+        Value::Complex(c) => float(c.abs_ref()),
+        _ => unreachable!("only numbers have a magnitude"),
+    }
+}
+
+/// Applies a binary operator to two numbers.
+fn apply(op: BinOpKind, left: &Value, right: &Value) -> Value {
+    eval_operands(op, false, left.clone(), right.clone())
+        .expect("arithmetic on numbers cannot fail")
+}
+
+/// Creates a matrix from a list of rows, which must be non-empty lists of numbers with the same
+/// length.
+#[derive(Debug)]
+pub struct Matrix;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Matrix {
+    pub fn eval_static(rows: matrix::Matrix) -> matrix::Matrix {
+        rows
+    }
+}
+
+/// Returns the transpose of a matrix.
+#[derive(Debug)]
+pub struct Transpose;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Transpose {
+    pub fn eval_static(a: matrix::Matrix) -> matrix::Matrix {
+        a.transpose()
+    }
+}
+
+/// Returns the determinant of a square matrix.
+#[derive(Debug)]
+pub struct Det;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Det {
+    pub fn eval_static(a: matrix::Matrix) -> Result<Value, BuiltinError> {
+        check_square("det", &a, 0)?;
+        Ok(a.det())
+    }
+}
+
+/// Returns the inverse of a square matrix.
+#[derive(Debug)]
+pub struct Inv;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Inv {
+    pub fn eval_static(a: matrix::Matrix) -> Result<matrix::Matrix, BuiltinError> {
+        check_square("inv", &a, 0)?;
+        a.inverse().ok_or_else(|| error("inv", MatrixErrorKind::Singular { index: 0 }))
+    }
+}
+
+/// Returns the reduced row echelon form of a matrix.
+#[derive(Debug)]
+pub struct Rref;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Rref {
+    pub fn eval_static(a: matrix::Matrix) -> matrix::Matrix {
+        a.rref()
+    }
+}
+
+/// Returns the rank of a matrix.
+#[derive(Debug)]
+pub struct Rank;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Rank {
+    pub fn eval_static(a: matrix::Matrix) -> Integer {
+        a.rank().into()
+    }
+}
+
+/// Returns the sum of the entries on the main diagonal of a square matrix.
+#[derive(Debug)]
+pub struct Trace;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Trace {
+    pub fn eval_static(a: matrix::Matrix) -> Result<Value, BuiltinError> {
+        check_square("trace", &a, 0)?;
+        Ok(a.trace())
+    }
+}
+
+/// Returns the dot product of two vectors. Complex entries are not conjugated.
+#[derive(Debug)]
+pub struct Dot;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Dot {
+    pub fn eval_static(u: Vec<Value>, v: Vec<Value>) -> Result<Value, BuiltinError> {
+        check_vector("dot", &u, u.len(), 0)?;
+        check_vector("dot", &v, u.len(), 1)?;
+        Ok(u.iter()
+            .zip(&v)
+            .fold(Value::Integer(Integer::new()), |sum, (a, b)| {
+                apply(BinOpKind::Add, &sum, &apply(BinOpKind::Mul, a, b))
+            }))
+    }
+}
+
+/// Returns the cross product of two vectors with 3 elements.
+#[derive(Debug)]
+pub struct Cross;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Cross {
+    pub fn eval_static(u: Vec<Value>, v: Vec<Value>) -> Result<Vec<Value>, BuiltinError> {
+        check_vector("cross", &u, 3, 0)?;
+        check_vector("cross", &v, 3, 1)?;
+        let term = |i: usize, j: usize| apply(
+            BinOpKind::Sub,
+            &apply(BinOpKind::Mul, &u[i], &v[j]),
+            &apply(BinOpKind::Mul, &u[j], &v[i]),
+        );
+        Ok(vec![term(1, 2), term(2, 0), term(0, 1)])
+    }
+}
+
+/// Returns the Euclidean norm of a vector, or the Frobenius norm of a matrix, which is the square
+/// root of the sum of the squared magnitudes of its entries.
+#[derive(Debug)]
+pub struct Norm;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Norm {
+    pub fn eval_static(v: Value) -> Result<Float, BuiltinError> {
+        let entries = match v.coerce_matrix() {
+            Value::List(entries) if entries.iter().all(Value::is_complex) => entries,
+            Value::Matrix(matrix) => matrix.into_entries(),
+            value => return Err(BuiltinError::TypeMismatch(TypeMismatch {
+                name: "norm".to_owned(),
+                index: 0,
+                expected: "List",
+                given: value.typename(),
+                signature: "norm(v: Value)".to_owned(),
+            })),
+        };
+        Ok(entries.iter()
+            .map(|entry| magnitude(entry).square())
+            .fold(float(0), |sum, n| sum + n)
+            .sqrt())
+    }
+}
+
+/// Solves the linear system `A * x = b` for `x`, where `A` is an invertible square matrix.
+///
+/// If `b` is a vector, the solution is a vector. If `b` is a matrix, each of its columns is
+/// treated as a separate right-hand side, and the solution is a matrix.
+#[derive(Debug)]
+pub struct Solve;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Solve {
+    pub fn eval_static(a: matrix::Matrix, b: Value) -> Result<Value, BuiltinError> {
+        check_square("solve", &a, 0)?;
+        let (b, vector) = match b.coerce_matrix() {
+            Value::Matrix(b) => (b, false),
+            Value::List(b) => {
+                check_vector("solve", &b, a.rows(), 1)?;
+                (matrix::Matrix::column(b).unwrap(), true)
+            },
+            value => return Err(BuiltinError::TypeMismatch(TypeMismatch {
+                name: "solve".to_owned(),
+                index: 1,
+                expected: "List",
+                given: value.typename(),
+                signature: "solve(a: Matrix, b: Value)".to_owned(),
+            })),
+        };
+        if b.rows() != a.rows() {
+            return Err(error("solve", MatrixErrorKind::LengthMismatch {
+                index: 1,
+                expected: a.rows(),
+                given: b.rows(),
+            }));
+        }
+
+        let x = a.solve(&b).ok_or_else(|| error("solve", MatrixErrorKind::Singular { index: 0 }))?;
+        if vector {
+            Ok(Value::List(x.into_entries()))
+        } else {
+            Ok(Value::Matrix(x))
+        }
+    }
+}
+
+/// Returns the eigenvalues of a square matrix, repeated according to their multiplicity.
+#[derive(Debug)]
+pub struct Eig;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Eig {
+    pub fn eval_static(a: matrix::Matrix) -> Result<Vec<Value>, BuiltinError> {
+        check_square("eig", &a, 0)?;
+        Ok(a.eigenvalues())
+    }
+}
+
+/// Returns the LU decomposition of a square matrix `A` with partial pivoting, as the list of
+/// matrices `[L, U, P]` such that `P * A = L * U`.
+#[derive(Debug)]
+pub struct Lu;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Lu {
+    pub fn eval_static(a: matrix::Matrix) -> Result<Vec<Value>, BuiltinError> {
+        check_square("lu", &a, 0)?;
+        let (lower, upper, permutation) = a.lu();
+        Ok(vec![lower.into(), upper.into(), permutation.into()])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::numerical::test_util::eval;
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(eval("a = matrix([[1, 2], [3, 4]]); a * a").unwrap().to_string(), "matrix([[7, 10], [15, 22]])");
+        assert_eq!(eval("a = matrix([[1, 2], [3, 4]]); a - 2a").unwrap().to_string(), "matrix([[-1, -2], [-3, -4]])");
+        assert_eq!(eval("matrix([[1, 2], [3, 4]]) * [1, 1]").unwrap().to_string(), "[3, 7]");
+        assert_eq!(eval("[1, 1] * matrix([[1, 2], [3, 4]])").unwrap().to_string(), "[4, 6]");
+        assert_eq!(eval("matrix([[1, 1], [0, 1]]) ^ 10").unwrap().to_string(), "matrix([[1, 10], [0, 1]])");
+        assert_eq!(eval("a = matrix([[2, 1], [1, 1]]); a ^ -1 * a == matrix([[1, 0], [0, 1]])").unwrap(), Value::Boolean(true));
+        assert_eq!(eval("matrix([[1, 2], [3, 4]])[1][0]").unwrap(), Value::Integer(3.into()));
+        assert!(eval("matrix([[1, 2]]) + matrix([[1], [2]])").is_err());
+        assert!(eval("matrix([[1, 2]]) ^ 2").is_err());
+        assert!(eval("matrix([[1, 2], [2, 4]]) ^ -1").is_err());
+        assert!(eval("matrix([[1, 2], [3]])").is_err());
+    }
+
+    #[test]
+    fn exact_elimination() {
+        assert_eq!(eval("det([[1, 2], [3, 4]])").unwrap(), Value::Integer((-2).into()));
+        assert_eq!(eval("det([[0, 1, 2], [1, 0, 3], [4, -3, 8]])").unwrap(), Value::Integer((-2).into()));
+        assert_eq!(eval("inv([[1, 2], [3, 4]])").unwrap().to_string(), "matrix([[-2, 1], [3 / 2, -1 / 2]])");
+        assert_eq!(eval("rref([[1, 2, 3], [2, 4, 7]])").unwrap().to_string(), "matrix([[1, 2, 0], [0, 0, 1]])");
+        assert_eq!(eval("rank([[1, 2], [2, 4], [3, 6]])").unwrap(), Value::Integer(1.into()));
+        assert_eq!(eval("solve([[2, 1], [1, 3]], [3, 5])").unwrap().to_string(), "[4 / 5, 7 / 5]");
+        assert_eq!(eval("a = [[0, 2], [1, 1]]; r = lu(a); r[2] * a == r[0] * r[1]").unwrap(), Value::Boolean(true));
+        assert!(eval("inv([[1, 2], [2, 4]])").is_err());
+        assert!(eval("det([[1, 2, 3]])").is_err());
+    }
+
+    #[test]
+    fn inexact_elimination() {
+        assert_eq!(eval("rank([[0.1, 0.2], [0.3, 0.6]])").unwrap(), Value::Integer(1.into()));
+        assert_eq!(eval("det([[0.1, 0.2], [0.3, 0.6]])").unwrap(), Value::Integer(0.into()));
+        assert!(eval("inv([[0.1, 0.2], [0.3, 0.6]])").is_err());
+    }
+
+    #[test]
+    fn vectors() {
+        assert_eq!(eval("dot([1, 2, 3], [4, 5, 6])").unwrap(), Value::Integer(32.into()));
+        assert_eq!(eval("cross([1, 0, 0], [0, 1, 0])").unwrap().to_string(), "[0, 0, 1]");
+        assert_eq!(eval("norm([3, 4])").unwrap().to_string(), "5");
+        assert_eq!(eval("trace([[1, 2], [3, 4]])").unwrap(), Value::Integer(5.into()));
+        assert!(eval("cross([1, 2], [3, 4])").is_err());
+        assert!(eval("dot([1, 2], [3])").is_err());
+    }
+
+    #[test]
+    fn eigenvalues() {
+        assert_eq!(eval("eig([[2, 0], [0, 3]])").unwrap().to_string(), "[2, 3]");
+        assert_eq!(eval("eig([[1, 1], [0, 1]])").unwrap().to_string(), "[1, 1]");
+        assert_eq!(eval("eig([[4, 1], [2, 3]])").unwrap().to_string(), "[2, 5]");
+        assert_eq!(eval("eig([[0, -1], [1, 0]])").unwrap().to_string(), "[-i, i]");
+
+        let Value::List(values) = eval("eig([[1, 1], [1, 0]])").unwrap() else {
+            panic!("expected a list");
+        };
+        let phi = (float(5).sqrt() + 1) / 2;
+        assert!(values[1].approx_eq(&Value::Float(phi)));
+    }
+}
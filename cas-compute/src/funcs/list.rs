@@ -0,0 +1,540 @@
+//! Functions for creating and manipulating lists.
+//!
+//! Lists are written with square brackets, such as `[1, 2, 3]`. Their elements can be read and
+//! assigned with indices and slices, such as `l[0]`, `l[-1]`, and `l[1:3]`.
+//!
+//! The higher-order functions `map`, `filter`, and `reduce` accept the name of a user-defined or
+//! builtin function as their first argument, such as `map(sqrt, [1, 4, 9])`. They are function
+//! values themselves, so they can also be assigned to variables or passed to each other.
+
+use cas_attrs::builtin;
+use cas_parser::parser::{
+    ast::{call::Call, expr::Expr, literal::Literal},
+    token::op::BinOpKind,
+};
+use crate::numerical::{
+    builtin::{
+        error::BuiltinError,
+        func_specific::{ListError, ListErrorKind},
+        Builtin,
+    },
+    ctxt::{Ctxt, Function},
+    error::{kind::{MissingArgument, TooManyArguments, TypeMismatch}, Error},
+    eval::{call_with_values, eval_operands, Eval},
+    value::Value,
+};
+use rug::{ops::DivRounding, Integer};
+use std::cmp::Ordering;
+
+/// The maximum number of elements in a list created by [`Range`].
+pub const MAX_LEN: usize = 1 << 24;
+
+/// Combines the elements of a list from left to right with the given binary operator, starting
+/// with `identity`. This is used to implement `sum` and `prod`.
+pub(crate) fn fold(
+    name: &'static str,
+    op: BinOpKind,
+    identity: Value,
+    values: Vec<Value>,
+) -> Result<Value, BuiltinError> {
+    values.into_iter().try_fold(identity, |result, value| {
+        let given = value.typename();
+        eval_operands(op, false, result, value)
+            .map_err(|_| ListError::new(name, ListErrorKind::InvalidElement { index: 0, given }).into())
+    })
+}
+
+/// Compares two real numbers.
+pub(crate) fn compare(a: &Value, b: &Value) -> Ordering {
+    let less = |a: &Value, b: &Value| matches!(
+        eval_operands(BinOpKind::Less, false, a.clone().coerce_number(), b.clone().coerce_number()),
+        Ok(Value::Boolean(true)),
+    );
+    if less(a, b) {
+        Ordering::Less
+    } else if less(b, a) {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// Returns the number of elements in a list.
+#[derive(Debug)]
+pub struct Len;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Len {
+    pub fn eval_static(l: Vec<Value>) -> Integer {
+        l.len().into()
+    }
+}
+
+/// Returns the product of the elements of a list. The product of an empty list is `1`.
+#[derive(Debug)]
+pub struct Prod;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Prod {
+    pub fn eval_static(l: Vec<Value>) -> Result<Value, BuiltinError> {
+        fold("prod", BinOpKind::Mul, Value::Integer(Integer::from(1)), l)
+    }
+}
+
+/// Returns the elements of a list of real numbers in ascending order.
+#[derive(Debug)]
+pub struct Sort;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Sort {
+    pub fn eval_static(mut l: Vec<Value>) -> Result<Vec<Value>, BuiltinError> {
+        if let Some(value) = l.iter().find(|value| !value.is_real()) {
+            return Err(ListError::new("sort", ListErrorKind::InvalidElement {
+                index: 0,
+                given: value.typename(),
+            }).into());
+        }
+
+        // the sort is stable, so equal elements keep their order
+        l.sort_by(compare);
+        Ok(l)
+    }
+}
+
+/// Returns the elements of a list in reverse order.
+#[derive(Debug)]
+pub struct Reverse;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Reverse {
+    pub fn eval_static(mut l: Vec<Value>) -> Vec<Value> {
+        l.reverse();
+        l
+    }
+}
+
+/// Returns the elements of the first list, followed by the elements of the second list.
+#[derive(Debug)]
+pub struct Concat;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Concat {
+    pub fn eval_static(mut a: Vec<Value>, b: Vec<Value>) -> Vec<Value> {
+        a.extend(b);
+        a
+    }
+}
+
+/// Returns a list of pairs of corresponding elements of two lists, such as `[[1, 3], [2, 4]]` for
+/// `zip([1, 2], [3, 4])`. If one list is longer, its extra elements are ignored.
+#[derive(Debug)]
+pub struct Zip;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Zip {
+    pub fn eval_static(a: Vec<Value>, b: Vec<Value>) -> Vec<Value> {
+        a.into_iter()
+            .zip(b)
+            .map(|(a, b)| Value::List(vec![a, b]))
+            .collect()
+    }
+}
+
+/// Returns a list of the integers from `start` (inclusive) to `end` (exclusive), counting by
+/// `step`. If only one argument is given, it is the end, and the list starts at `0`.
+///
+/// For example, `range(4)` is `[0, 1, 2, 3]`, and `range(10, 0, -3)` is `[10, 7, 4, 1]`.
+#[derive(Debug)]
+pub struct Range;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Range {
+    pub fn eval_static(
+        start: Integer,
+        end: Option<Integer>,
+        step: Option<Integer>,
+    ) -> Result<Vec<Value>, BuiltinError> {
+        let (start, end) = match end {
+            Some(end) => (start, end),
+            None => (Integer::new(), start),
+        };
+        let step = step.unwrap_or_else(|| Integer::from(1));
+        if step == 0 {
+            return Err(ListError::new("range", ListErrorKind::ZeroStep).into());
+        }
+
+        // number of elements is ceil((end - start) / step), if positive
+        let len = Integer::from(&end - &start).div_ceil(step.clone());
+        if len <= 0 {
+            return Ok(Vec::new());
+        }
+        let len = len.to_usize()
+            .filter(|&len| len <= MAX_LEN)
+            .ok_or_else(|| ListError::new("range", ListErrorKind::TooManyElements))?;
+
+        let mut values = Vec::with_capacity(len);
+        let mut n = start;
+        for _ in 0..len {
+            values.push(Value::Integer(n.clone()));
+            n += &step;
+        }
+        Ok(values)
+    }
+}
+
+/// The arguments of a call to a higher-order list function, after evaluation.
+struct HigherOrderArgs {
+    /// The function passed as the first argument.
+    func: Function,
+
+    /// The list passed as the second argument.
+    list: Vec<Value>,
+
+    /// The remaining arguments.
+    rest: Vec<Value>,
+}
+
+/// The signature of a higher-order list function, used to check its arguments.
+struct Signature {
+    /// The name of the function.
+    name: &'static str,
+
+    /// The signature of the function, used in error messages.
+    signature: &'static str,
+
+    /// The number of parameters of the function.
+    num_params: usize,
+
+    /// The number of trailing parameters that are optional.
+    num_optional: usize,
+}
+
+impl Signature {
+    /// Checks that the given number of arguments is accepted by the function.
+    fn check_count(&self, call: &Call, given: usize) -> Result<(), Error> {
+        if given > self.num_params {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: self.name.to_owned(),
+                expected: self.num_params,
+                given,
+                signature: self.signature.to_owned(),
+            }).into_error(call));
+        }
+        if given < self.num_params - self.num_optional {
+            return Err(BuiltinError::MissingArgument(MissingArgument {
+                name: self.name.to_owned(),
+                index: given,
+                expected: self.num_params,
+                given,
+                signature: self.signature.to_owned(),
+            }).into_error(call));
+        }
+        Ok(())
+    }
+}
+
+impl HigherOrderArgs {
+    /// Resolves the function given as the first argument of the call, and evaluates the remaining
+    /// arguments, checking them against the given signature.
+    fn new(ctxt: &mut Ctxt, call: &Call, signature: &Signature) -> Result<Self, Error> {
+        signature.check_count(call, call.args.len())?;
+
+        let mut args = Vec::with_capacity(call.args.len());
+        args.push(match &call.args[0] {
+            // a function passed by name is used even if a variable of the same name shadows it
+            Expr::Literal(Literal::Symbol(symbol)) => ctxt.get_function(&symbol.name)
+                .map_or(Value::Unit, Value::Function),
+            arg => arg.eval(ctxt)?,
+        });
+        for arg in &call.args[1..] {
+            args.push(arg.eval(ctxt)?);
+        }
+        Self::from_values(ctxt, call, signature, args)
+    }
+
+    /// Checks arguments that have already been evaluated against the given signature, such as
+    /// when the function is itself passed to a higher-order function.
+    fn from_values(
+        ctxt: &Ctxt,
+        call: &Call,
+        signature: &Signature,
+        args: Vec<Value>,
+    ) -> Result<Self, Error> {
+        signature.check_count(call, args.len())?;
+
+        let mut args = args.into_iter();
+        let Some(Value::Function(func)) = args.next() else {
+            return Err(BuiltinError::from(ListError::new(
+                signature.name,
+                ListErrorKind::NotAFunction { index: 0 },
+            )).into_error(call));
+        };
+
+        let list = args.next().unwrap();
+        let span = || call.args.get(1).map_or_else(|| call.span(), Expr::span);
+        ctxt.budget.check_coerced_list(&list, span)?;
+        let list = match list.coerce_list() {
+            Value::List(list) => list,
+            value => return Err(BuiltinError::TypeMismatch(TypeMismatch {
+                name: signature.name.to_owned(),
+                index: 1,
+                expected: "List",
+                given: value.typename(),
+                signature: signature.signature.to_owned(),
+            }).into_error(call)),
+        };
+        Ok(Self { func, list, rest: args.collect() })
+    }
+}
+
+/// Returns the error for calling a higher-order list function without a context, in which case
+/// the function it is given cannot be called.
+fn values_only(name: &'static str) -> BuiltinError {
+    ListError::new(name, ListErrorKind::NotAFunction { index: 0 }).into()
+}
+
+/// Applies a function to each element of a list, returning a list of the results.
+///
+/// For example, `map(sqrt, [1, 4, 9])` is `[1, 2, 3]`.
+#[derive(Debug)]
+pub struct Map;
+
+impl Map {
+    const SIGNATURE: Signature = Signature {
+        name: "map",
+        signature: "map(f: Function, l: List)",
+        num_params: 2,
+        num_optional: 0,
+    };
+
+    /// Applies the function to each element of the list.
+    fn apply(ctxt: &mut Ctxt, call: &Call, args: HigherOrderArgs) -> Result<Value, Error> {
+        args.list.into_iter()
+            .map(|value| call_with_values(ctxt, call, &args.func, vec![value]))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::List)
+    }
+}
+
+impl Builtin for Map {
+    fn num_args(&self) -> usize {
+        2
+    }
+
+    fn eval(&self, _: &Ctxt, _: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        Err(values_only("map"))
+    }
+
+    fn eval_values(&self, ctxt: &mut Ctxt, call: &Call, args: Vec<Value>) -> Result<Value, Error> {
+        HigherOrderArgs::from_values(ctxt, call, &Self::SIGNATURE, args)
+            .and_then(|args| Self::apply(ctxt, call, args))
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(HigherOrderArgs::new(ctxt, call, &Self::SIGNATURE)
+            .and_then(|args| Self::apply(ctxt, call, args)))
+    }
+}
+
+/// Returns the elements of a list for which a function returns a truthy value.
+///
+/// For example, `filter(isprime, range(10))` is `[2, 3, 5, 7]`.
+#[derive(Debug)]
+pub struct Filter;
+
+impl Filter {
+    const SIGNATURE: Signature = Signature {
+        name: "filter",
+        signature: "filter(f: Function, l: List)",
+        num_params: 2,
+        num_optional: 0,
+    };
+
+    /// Keeps the elements of the list for which the function returns a truthy value.
+    fn apply(ctxt: &mut Ctxt, call: &Call, args: HigherOrderArgs) -> Result<Value, Error> {
+        let mut kept = Vec::new();
+        for value in args.list {
+            if call_with_values(ctxt, call, &args.func, vec![value.clone()])?.is_truthy() {
+                kept.push(value);
+            }
+        }
+        Ok(Value::List(kept))
+    }
+}
+
+impl Builtin for Filter {
+    fn num_args(&self) -> usize {
+        2
+    }
+
+    fn eval(&self, _: &Ctxt, _: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        Err(values_only("filter"))
+    }
+
+    fn eval_values(&self, ctxt: &mut Ctxt, call: &Call, args: Vec<Value>) -> Result<Value, Error> {
+        HigherOrderArgs::from_values(ctxt, call, &Self::SIGNATURE, args)
+            .and_then(|args| Self::apply(ctxt, call, args))
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(HigherOrderArgs::new(ctxt, call, &Self::SIGNATURE)
+            .and_then(|args| Self::apply(ctxt, call, args)))
+    }
+}
+
+/// Combines the elements of a list from left to right with a function of two arguments, the
+/// result so far and the next element. If an initial value is given, it is the first result;
+/// otherwise, the first element is.
+///
+/// For example, `reduce(max, [3, 1, 4])` is `4`.
+#[derive(Debug)]
+pub struct Reduce;
+
+impl Reduce {
+    const SIGNATURE: Signature = Signature {
+        name: "reduce",
+        signature: "reduce(f: Function, l: List, init: Value (optional))",
+        num_params: 3,
+        num_optional: 1,
+    };
+
+    /// Combines the elements of the list with the function.
+    fn apply(ctxt: &mut Ctxt, call: &Call, args: HigherOrderArgs) -> Result<Value, Error> {
+        let mut values = args.rest.into_iter().chain(args.list);
+        let Some(mut result) = values.next() else {
+            return Err(BuiltinError::from(ListError::new(
+                "reduce",
+                ListErrorKind::EmptyList { index: 1 },
+            )).into_error(call));
+        };
+        for value in values {
+            result = call_with_values(ctxt, call, &args.func, vec![result, value])?;
+        }
+        Ok(result)
+    }
+}
+
+impl Builtin for Reduce {
+    fn num_args(&self) -> usize {
+        3
+    }
+
+    fn eval(&self, _: &Ctxt, _: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        Err(values_only("reduce"))
+    }
+
+    fn eval_values(&self, ctxt: &mut Ctxt, call: &Call, args: Vec<Value>) -> Result<Value, Error> {
+        HigherOrderArgs::from_values(ctxt, call, &Self::SIGNATURE, args)
+            .and_then(|args| Self::apply(ctxt, call, args))
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(HigherOrderArgs::new(ctxt, call, &Self::SIGNATURE)
+            .and_then(|args| Self::apply(ctxt, call, args)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::numerical::test_util::eval;
+    use super::*;
+
+    /// Creates a list of integers.
+    fn list(ns: &[i32]) -> Value {
+        Value::List(ns.iter().map(|&n| Value::Integer(Integer::from(n))).collect())
+    }
+
+    #[test]
+    fn index_and_slice() {
+        assert_eq!(eval("l = [1, 2, 3, 4]; l[0] + l[-1]").unwrap(), Value::Integer(Integer::from(5)));
+        assert_eq!(eval("l = [1, 2, 3, 4]; l[1:3]").unwrap(), list(&[2, 3]));
+        assert_eq!(eval("l = [1, 2, 3, 4]; l[-2:]").unwrap(), list(&[3, 4]));
+        assert_eq!(eval("l = [1, 2, 3, 4]; l[:10]").unwrap(), list(&[1, 2, 3, 4]));
+        assert_eq!(eval("[[1, 2], [3, 4]][1][0]").unwrap(), Value::Integer(Integer::from(3)));
+        assert!(eval("l = [1, 2]; l[2]").is_err());
+        assert!(eval("l = [1, 2]; l[0.5]").is_err());
+        assert!(eval("x = 5; x[0]").is_err());
+    }
+
+    #[test]
+    fn index_assignment() {
+        assert_eq!(eval("l = [1, 2, 3]; l[-1] = 5; l").unwrap(), list(&[1, 2, 5]));
+        assert_eq!(eval("l = [[1, 2], [3, 4]]; l[1][0] *= 10; l[1]").unwrap(), list(&[30, 4]));
+        assert_eq!(eval("l = [1, 2, 3, 4]; l[1:3] = [0]; l").unwrap(), list(&[1, 0, 4]));
+        assert!(eval("l = [1, 2, 3]; l[1:] = 0").is_err());
+        assert!(eval("l = [1, 2, 3]; l[1:] += [0]").is_err());
+    }
+
+    #[test]
+    fn higher_order() {
+        assert_eq!(eval("f(x) = x^2; map(f, [1, 2, 3])").unwrap(), list(&[1, 4, 9]));
+        assert_eq!(eval("filter(isprime, range(10))").unwrap(), list(&[2, 3, 5, 7]));
+        assert_eq!(eval("add(a, b) = a + b; reduce(add, [1, 2, 3], 10)").unwrap(), Value::Integer(Integer::from(16)));
+        assert_eq!(eval("reduce(max, [3, 1, 4])").unwrap(), Value::Float(crate::primitive::float(4)));
+        assert!(eval("reduce(max, [])").is_err());
+        assert!(eval("map(5, [1, 2])").is_err());
+    }
+
+    #[test]
+    fn higher_order_lambdas() {
+        assert_eq!(eval("map(x -> x^2, [1, 2, 3])").unwrap(), list(&[1, 4, 9]));
+        assert_eq!(eval("filter(n -> n % 2 == 0, range(6))").unwrap(), list(&[0, 2, 4]));
+        assert_eq!(eval("reduce((a, b) -> 10a + b, [1, 2, 3])").unwrap(), Value::Integer(Integer::from(123)));
+        assert_eq!(eval("k = 3; map(x -> k * x, [1, 2])").unwrap(), list(&[3, 6]));
+
+        // a variable that does not hold a function does not shadow a function passed by name
+        assert_eq!(eval("f(x) = -x; f = 2; map(f, [1, 2])").unwrap(), list(&[-1, -2]));
+    }
+
+    #[test]
+    fn higher_order_values() {
+        // the higher-order functions are themselves function values
+        assert_eq!(eval("g = map; g(x -> x + 1, [1, 2])").unwrap(), list(&[2, 3]));
+        assert_eq!(eval("apply(h, f, l) = h(f, l); apply(filter, isprime, [1, 2, 3])").unwrap(), list(&[2, 3]));
+        assert_eq!(eval("f(x) = 2x; reduce(map, [[1, 2]], f)").unwrap(), list(&[2, 4]));
+        assert_eq!(eval("map(l -> reduce((a, b) -> a + b, l), [[1, 2], [3]])").unwrap(), list(&[3, 3]));
+        assert_eq!(eval("map(g -> g(x -> -x, [1]), [map, filter])").unwrap().to_string(), "[[-1], [1]]");
+        assert!(eval("map(map, [1, 2])").is_err());
+        assert!(eval("reduce(filter, [1, 2])").is_err());
+    }
+
+    #[test]
+    fn builtins() {
+        assert_eq!(eval("sum([1, 2, 3])").unwrap(), Value::Integer(Integer::from(6)));
+        assert_eq!(eval("prod([])").unwrap(), Value::Integer(Integer::from(1)));
+        assert_eq!(eval("sort([3, 1.5, 2])").unwrap().to_string(), "[1.5, 2, 3]");
+        assert_eq!(eval("reverse(range(3))").unwrap(), list(&[2, 1, 0]));
+        assert_eq!(eval("concat([1], [2, 3])").unwrap(), list(&[1, 2, 3]));
+        assert_eq!(eval("range(10, 0, -3)").unwrap(), list(&[10, 7, 4, 1]));
+        assert_eq!(eval("len(zip([1, 2, 3], [4, 5]))").unwrap(), Value::Integer(Integer::from(2)));
+        assert!(eval("range(0, 5, 0)").is_err());
+        assert!(eval("sort([1, [2]])").is_err());
+    }
+
+    #[test]
+    fn broadcasting() {
+        assert_eq!(eval("[1, 2, 3] * 2").unwrap(), list(&[2, 4, 6]));
+        assert_eq!(eval("[1, 2] + [3, 4]").unwrap(), list(&[4, 6]));
+        assert_eq!(eval("10 - [[1], [2, 3]]").unwrap().to_string(), "[[9], [8, 7]]");
+        assert_eq!(eval("-range(3)").unwrap(), list(&[0, -1, -2]));
+        assert_eq!(eval("[1, 2] < [2, 1]").unwrap().to_string(), "[true, false]");
+        assert_eq!(eval("sin([0, pi / 2])").unwrap().to_string(), "[0, 1]");
+        assert_eq!(eval("ncr([4, 5], 2)").unwrap(), list(&[6, 10]));
+        assert_eq!(eval("sum([[1, 2], [3, 4]])").unwrap(), list(&[4, 6]));
+        assert_eq!(eval("len([1, 2])").unwrap(), Value::Integer(Integer::from(2)));
+        assert!(eval("[1, 2] + [1, 2, 3]").is_err());
+        assert!(eval("atan2([1, 2], [1, 2, 3])").is_err());
+    }
+}
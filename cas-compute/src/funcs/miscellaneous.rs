@@ -1,13 +1,25 @@
 //! Uncategorized functions.
 
 use cas_attrs::builtin;
-use crate::consts::TAU;
+use crate::consts::tau;
 use crate::numerical::value::Value;
 use crate::primitive::{complex, float_from_str, float, int};
 use once_cell::sync::Lazy;
 use rand::Rng;
 use rug::{integer::Order, ops::Pow, rand::RandState, Complex, Float, Integer};
 
+#[cfg(feature = "numerical")]
+use {
+    cas_parser::parser::ast::call::Call,
+    crate::numerical::{
+        builtin::{error::BuiltinError, func_specific::PrecisionError, Builtin},
+        ctxt::Ctxt,
+        error::{kind::{TooManyArguments, TypeMismatch}, Error},
+        eval::Eval,
+    },
+    crate::primitive::{MAX_PRECISION, MIN_PRECISION},
+};
+
 /// Returns the absolute value.
 #[derive(Debug)]
 pub struct Abs;
@@ -127,7 +139,7 @@ impl Gamma {
 
         let t = complex(complex(&z + &*GAMMA_G) + 0.5);
 
-        let tau_sqrt = float(&*TAU).sqrt();
+        let tau_sqrt = tau().sqrt();
         let t_pow = (&t).pow(z + 0.5);
         let exp_t = complex(t.as_neg().exp_ref());
 
@@ -237,3 +249,86 @@ impl Size {
         v.significant_bits().into()
     }
 }
+
+/// Returns the number of bits of precision used to compute values, or sets it when given an
+/// argument.
+///
+/// Setting the precision affects the rest of the scope the function is called in. The builtin
+/// constants, such as `pi`, are recomputed with the new precision, while other variables keep the
+/// precision they were computed with.
+#[derive(Debug)]
+pub struct Precision;
+
+#[cfg(feature = "numerical")]
+impl Precision {
+    const SIGNATURE: &'static str = "precision(bits: Integer?)";
+
+    /// Checks the arguments given to the function, returning the new precision, if one is given.
+    fn check(args: &mut dyn Iterator<Item = Value>) -> Result<Option<u32>, BuiltinError> {
+        let Some(bits) = args.next() else {
+            return Ok(None);
+        };
+
+        let given = 1 + args.count();
+        if given > 1 {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: "precision".to_owned(),
+                expected: 1,
+                given,
+                signature: Self::SIGNATURE.to_owned(),
+            }));
+        }
+
+        match bits.coerce_integer() {
+            Value::Integer(n) => n.to_u32()
+                .filter(|n| (MIN_PRECISION..=MAX_PRECISION).contains(n))
+                .map(Some)
+                .ok_or_else(|| PrecisionError.into()),
+            value => Err(BuiltinError::TypeMismatch(TypeMismatch {
+                name: "precision".to_owned(),
+                index: 0,
+                expected: "Integer",
+                given: value.typename(),
+                signature: Self::SIGNATURE.to_owned(),
+            })),
+        }
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Builtin for Precision {
+    fn num_args(&self) -> usize {
+        1
+    }
+
+    fn eval(&self, ctxt: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        // the context cannot be modified here, so a new precision is only checked
+        Ok(match Self::check(args)? {
+            Some(_) => Value::Unit,
+            None => Value::Integer(int(ctxt.precision())),
+        })
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        let args = match call.args.iter().map(|arg| arg.eval(ctxt)).collect::<Result<Vec<_>, _>>() {
+            Ok(args) => args,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(match Self::check(&mut args.into_iter()) {
+            Ok(Some(bits)) => {
+                ctxt.set_precision(bits);
+                Ok(Value::Unit)
+            },
+            Ok(None) => Ok(Value::Integer(int(ctxt.precision()))),
+            Err(err) => Err(err.into_error(call)),
+        })
+    }
+
+    fn mutates_ctxt(&self) -> bool {
+        true
+    }
+}
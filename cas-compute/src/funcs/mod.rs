@@ -11,12 +11,12 @@
 //! # Example
 //!
 //! ```
-//! use cas_compute::consts::PI;
+//! use cas_compute::consts::pi;
 //! use cas_compute::funcs::trigonometry::Sin;
 //! use cas_compute::primitive::complex;
 //!
 //! // evaluate sin(pi / 2) using `eval_static`
-//! let result = Sin::eval_static(complex(&*PI) / 2.0);
+//! let result = Sin::eval_static(complex(&pi()) / 2.0);
 //! println!("sin(pi / 2) = {}", result);
 //!
 //! // evaluate sin(pi / 2) using `Builtin` trait
@@ -33,16 +33,32 @@
 //! }
 //! ```
 
+#[cfg(feature = "numerical")]
+pub mod algebra;
 pub mod angle;
 pub mod complex;
 pub mod combinatoric;
 mod helper;
+#[cfg(feature = "numerical")]
+pub mod interval;
+#[cfg(feature = "numerical")]
+pub mod linalg;
+#[cfg(feature = "numerical")]
+pub mod list;
 pub mod miscellaneous;
+#[cfg(feature = "numerical")]
+pub mod number_theory;
+pub mod ode;
 pub mod power;
 pub mod print;
 pub mod probability; // TODO: add poison distribution
+#[cfg(feature = "numerical")]
+pub mod regression;
 pub mod round;
 pub mod sequence;
+pub mod series;
+#[cfg(feature = "numerical")]
+pub mod statistics;
 pub mod trigonometry;
 
 #[cfg(feature = "numerical")]
@@ -54,15 +70,24 @@ use std::collections::HashMap;
 /// Returns a list of all builtin functions that can be numerically evaluated.
 #[cfg(feature = "numerical")]
 pub fn all() -> HashMap<&'static str, Box<dyn Builtin>> {
+    use algebra::*;
     use angle::*;
     use complex::*;
     use combinatoric::*;
+    use interval::{Enclose, Interval, Lower, Mid, Upper, Width};
+    use linalg::*;
+    use list::*;
     use miscellaneous::*;
+    use number_theory::*;
+    use ode::*;
     use power::*;
     use print::*;
     use probability::*;
+    use regression::*;
     use round::*;
     use sequence::*;
+    use series::{Product, Sum};
+    use statistics::*;
     use trigonometry::*;
 
     macro_rules! build {
@@ -154,7 +179,82 @@ pub fn all() -> HashMap<&'static str, Box<dyn Builtin>> {
         "clamp" Clamp,
         "gcf" Gcf,
         "lcm" Lcm,
+        "isprime" Isprime,
+        "factorint" Factorint,
+        "nextprime" Nextprime,
+        "prevprime" Prevprime,
+        "totient" Totient,
+        "divisors" Divisors,
+        "sigma" Sigma,
+        "modpow" Modpow,
+        "modinv" Modinv,
+        "crt" Crt,
+        "jacobi" Jacobi,
+        "cf" Cf,
+        "fromcf" Fromcf,
+        "convergents" Convergents,
+        "rationalize" Rationalize,
         "sign" Sign,
         "size" Size,
+        "precision" Precision,
+        "interval" Interval,
+        "lower" Lower,
+        "upper" Upper,
+        "mid" Mid,
+        "width" Width,
+        "enclose" Enclose,
+        "len" Len,
+        "map" Map,
+        "filter" Filter,
+        "reduce" Reduce,
+        "prod" Prod,
+        "sort" Sort,
+        "reverse" Reverse,
+        "concat" Concat,
+        "range" Range,
+        "zip" Zip,
+        "matrix" Matrix,
+        "transpose" Transpose,
+        "det" Det,
+        "inv" Inv,
+        "rref" Rref,
+        "rank" Rank,
+        "trace" Trace,
+        "dot" Dot,
+        "cross" Cross,
+        "norm" Norm,
+        "solve" Solve,
+        "eig" Eig,
+        "lu" Lu,
+        "mean" Mean,
+        "median" Median,
+        "mode" Mode,
+        "var" Var,
+        "pvar" Pvar,
+        "stdev" Stdev,
+        "pstdev" Pstdev,
+        "quantile" Quantile,
+        "iqr" Iqr,
+        "cov" Cov,
+        "corr" Corr,
+        "zscore" Zscore,
+        "summary" Summary,
+        "linreg" Linreg,
+        "polyfit" Polyfit,
+        "expfit" Expfit,
+        "logfit" Logfit,
+        "powfit" Powfit,
+        "odesolve" Odesolve,
+        "odelist" Odelist,
+        "sum" Sum,
+        "product" Product,
+        "quote" Quote,
+        "simplify" Simplify,
+        "expand" Expand,
+        "diff" Diff,
+        "integrate" Integrate,
+        "subs" Subs,
+        "apart" Apart,
+        "dsolve" Dsolve,
     }
 }
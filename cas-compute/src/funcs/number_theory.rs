@@ -0,0 +1,547 @@
+//! Number-theoretic functions on integers, and rational approximations of real numbers.
+//!
+//! Primality is tested with [`Integer::is_probably_prime`], which runs the Baillie-PSW test
+//! followed by rounds of the Miller-Rabin test. Integers are factored by trial division by small
+//! primes, then by Pollard's rho algorithm.
+//!
+//! Continued fractions and rational approximations are computed with the functions in
+//! [`crate::approx`]. Fractions are returned as symbolic expressions, such as `355 / 113`.
+
+use cas_attrs::builtin;
+use crate::numerical::{
+    builtin::{
+        error::BuiltinError,
+        func_specific::{NumberTheoryError, NumberTheoryErrorKind},
+    },
+    ctxt::Ctxt,
+    value::Value,
+};
+use crate::approx::{self, approximate_rational_with, best_rational, continued_fraction, TOLERANCE};
+use crate::primitive::{float, int};
+use rug::{integer::IsPrime, ops::{Pow, RemRounding}, Float, Integer};
+
+/// The number of Miller-Rabin rounds used to test primality.
+const PRIME_REPS: u32 = 30;
+
+/// Primes below this bound are found by trial division before Pollard's rho algorithm is used.
+const TRIAL_DIVISION_BOUND: u32 = 1000;
+
+/// Creates an error for the function with the given name.
+fn error(name: &'static str, kind: NumberTheoryErrorKind) -> BuiltinError {
+    NumberTheoryError::new(name, kind).into()
+}
+
+/// Returns an error if argument `index` of the function is not positive.
+fn check_positive(name: &'static str, n: &Integer, index: usize) -> Result<(), BuiltinError> {
+    if *n <= 0 {
+        Err(error(name, NumberTheoryErrorKind::NotPositive { index }))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns an error if argument `index` of the function is not finite.
+fn check_finite(name: &'static str, n: &Float, index: usize) -> Result<(), BuiltinError> {
+    if n.is_finite() {
+        Ok(())
+    } else {
+        Err(error(name, NumberTheoryErrorKind::NotFinite { index }))
+    }
+}
+
+/// Converts the optional number of terms given as argument `index` into a [`usize`], returning
+/// an error if it is not positive.
+fn max_terms(name: &'static str, n: Option<Integer>, index: usize) -> Result<Option<usize>, BuiltinError> {
+    n.map(|n| match n.to_usize() {
+        Some(n) if n > 0 => Ok(n),
+        _ => Err(error(name, NumberTheoryErrorKind::NotPositive { index })),
+    })
+        .transpose()
+}
+
+/// Returns true if the integer is probably prime. Integers less than `2`, including negative
+/// integers, are not prime.
+pub fn is_prime(n: &Integer) -> bool {
+    // GMP tests the absolute value of `n`
+    *n >= 2 && n.is_probably_prime(PRIME_REPS) != IsPrime::No
+}
+
+/// Finds a non-trivial factor of `n`, which must be an odd composite number, using Pollard's rho
+/// algorithm. Each iteration takes a step of the evaluation using `ctxt`.
+fn pollard_rho(ctxt: &Ctxt, n: &Integer) -> Result<Integer, BuiltinError> {
+    let mut c = int(1);
+    loop {
+        let f = |x: &Integer| (int(x.square_ref()) + &c) % n;
+        let (mut x, mut y, mut d) = (int(2), int(2), int(1));
+        while d == 1 {
+            ctxt.builtin_step()?;
+            x = f(&x);
+            y = f(&f(&y));
+            d = int(&x - &y).abs().gcd(n);
+        }
+        if d != *n {
+            return Ok(d);
+        }
+        c += 1;
+    }
+}
+
+/// Returns the prime factorization of `|n|` as a list of primes and their exponents, sorted by
+/// prime. The factorization of `0` and `1` is empty.
+///
+/// Factoring can take a long time, so it takes steps of the evaluation using `ctxt`, and returns
+/// an error if the evaluation is interrupted.
+pub fn factor(ctxt: &Ctxt, n: &Integer) -> Result<Vec<(Integer, u32)>, BuiltinError> {
+    let mut factors = Vec::new();
+    let mut n = n.clone().abs();
+    if n <= 1 {
+        return Ok(factors);
+    }
+
+    let mut p = int(2);
+    while p < TRIAL_DIVISION_BOUND {
+        let (rest, count) = n.remove_factor(&p);
+        n = rest;
+        if count > 0 {
+            factors.push((p.clone(), count));
+        }
+        p.next_prime_mut();
+    }
+
+    let mut stack = vec![n];
+    let mut large = Vec::new();
+    while let Some(n) = stack.pop() {
+        if n == 1 {
+            continue;
+        } else if is_prime(&n) {
+            large.push(n);
+        } else {
+            let d = pollard_rho(ctxt, &n)?;
+            stack.push(int(&n / &d));
+            stack.push(d);
+        }
+    }
+
+    large.sort();
+    for p in large {
+        match factors.last_mut() {
+            Some((last, count)) if *last == p => *count += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    Ok(factors)
+}
+
+/// Returns true if the given integer is prime.
+#[derive(Debug)]
+pub struct Isprime;
+
+#[builtin]
+impl Isprime {
+    pub fn eval_static(n: Integer) -> bool {
+        is_prime(&n)
+    }
+}
+
+/// Returns the prime factorization of a positive integer, as a list of `[prime, exponent]`
+/// pairs.
+#[derive(Debug)]
+pub struct Factorint;
+
+#[builtin]
+impl Factorint {
+    pub fn eval_static(ctxt: &Ctxt, n: Integer) -> Result<Value, BuiltinError> {
+        check_positive("factorint", &n, 0)?;
+        Ok(Value::List(
+            factor(ctxt, &n)?
+                .into_iter()
+                .map(|(p, k)| Value::List(vec![Value::Integer(p), Value::Integer(int(k))]))
+                .collect()
+        ))
+    }
+}
+
+/// Returns the smallest prime greater than the given integer.
+#[derive(Debug)]
+pub struct Nextprime;
+
+#[builtin]
+impl Nextprime {
+    pub fn eval_static(ctxt: &Ctxt, n: Integer) -> Result<Integer, BuiltinError> {
+        if n < 2 {
+            return Ok(int(2));
+        }
+
+        // start from the smallest odd number greater than `n`
+        let mut p = if n.is_even() { n + 1 } else { n + 2 };
+        while !is_prime(&p) {
+            ctxt.builtin_step()?;
+            p += 2;
+        }
+        Ok(p)
+    }
+}
+
+/// Returns the largest prime less than the given integer.
+#[derive(Debug)]
+pub struct Prevprime;
+
+#[builtin]
+impl Prevprime {
+    pub fn eval_static(ctxt: &Ctxt, n: Integer) -> Result<Integer, BuiltinError> {
+        if n <= 2 {
+            return Err(error("prevprime", NumberTheoryErrorKind::NoSmallerPrime));
+        } else if n == 3 {
+            return Ok(int(2));
+        }
+
+        // start from the largest odd number less than `n`
+        let mut p = if n.is_even() { n - 1 } else { n - 2 };
+        while !is_prime(&p) {
+            ctxt.builtin_step()?;
+            p -= 2;
+        }
+        Ok(p)
+    }
+}
+
+/// Euler's totient function. Returns the number of integers in `[1, n]` that are coprime to `n`.
+#[derive(Debug)]
+pub struct Totient;
+
+#[builtin]
+impl Totient {
+    pub fn eval_static(ctxt: &Ctxt, n: Integer) -> Result<Integer, BuiltinError> {
+        check_positive("totient", &n, 0)?;
+        Ok(factor(ctxt, &n)?
+            .into_iter()
+            .fold(int(1), |acc, (p, k)| acc * int(&p - 1) * p.pow(k - 1)))
+    }
+}
+
+/// Returns a sorted list of the positive divisors of a positive integer.
+#[derive(Debug)]
+pub struct Divisors;
+
+#[builtin]
+impl Divisors {
+    pub fn eval_static(ctxt: &Ctxt, n: Integer) -> Result<Value, BuiltinError> {
+        check_positive("divisors", &n, 0)?;
+        let mut divisors = vec![int(1)];
+        for (p, k) in factor(ctxt, &n)? {
+            let mut next = Vec::with_capacity(divisors.len() * (k as usize + 1));
+            let mut power = int(1);
+            for _ in 0..=k {
+                next.extend(divisors.iter().map(|d| int(d * &power)));
+                power *= &p;
+            }
+            divisors = next;
+        }
+        divisors.sort();
+        Ok(Value::List(divisors.into_iter().map(Value::Integer).collect()))
+    }
+}
+
+/// Divisor function. Returns the sum of the `k`th powers of the positive divisors of `n`. `k`
+/// defaults to `1`, giving the sum of the divisors.
+#[derive(Debug)]
+pub struct Sigma;
+
+#[builtin]
+impl Sigma {
+    pub fn eval_static(ctxt: &Ctxt, n: Integer, k: Option<Integer>) -> Result<Integer, BuiltinError> {
+        check_positive("sigma", &n, 0)?;
+        let k = match k.map(|k| k.to_u32()) {
+            None => 1,
+            Some(Some(k)) => k,
+            Some(None) => return Err(error("sigma", NumberTheoryErrorKind::Negative { index: 1 })),
+        };
+
+        // sigma is multiplicative: sigma_k(p^e) = 1 + p^k + p^2k + ... + p^ek
+        Ok(factor(ctxt, &n)?
+            .into_iter()
+            .fold(int(1), |acc, (p, e)| {
+                let pk = p.pow(k);
+                let mut term = int(1);
+                let mut sum = int(1);
+                for _ in 0..e {
+                    term *= &pk;
+                    sum += &term;
+                }
+                acc * sum
+            }))
+    }
+}
+
+/// Modular exponentiation. Returns `a^b mod m`, where `m` is positive. If `b` is negative, the
+/// inverse of `a` modulo `m` is raised to the power `-b`.
+#[derive(Debug)]
+pub struct Modpow;
+
+#[builtin]
+impl Modpow {
+    pub fn eval_static(a: Integer, b: Integer, m: Integer) -> Result<Integer, BuiltinError> {
+        check_positive("modpow", &m, 2)?;
+        a.pow_mod(&b, &m)
+            .map_err(|_| error("modpow", NumberTheoryErrorKind::NoInverse))
+    }
+}
+
+/// Modular inverse. Returns the integer `x` in `[0, m)` such that `a * x = 1 (mod m)`.
+#[derive(Debug)]
+pub struct Modinv;
+
+#[builtin]
+impl Modinv {
+    pub fn eval_static(a: Integer, m: Integer) -> Result<Integer, BuiltinError> {
+        check_positive("modinv", &m, 1)?;
+        a.invert(&m)
+            .map_err(|_| error("modinv", NumberTheoryErrorKind::NoInverse))
+    }
+}
+
+/// Chinese remainder theorem. Returns the smallest non-negative integer `x` such that
+/// `x = r_i (mod m_i)` for every residue `r_i` and modulus `m_i` in the given lists. The moduli do
+/// not need to be coprime.
+#[derive(Debug)]
+pub struct Crt;
+
+#[builtin]
+impl Crt {
+    pub fn eval_static(residues: Value, moduli: Value) -> Result<Integer, BuiltinError> {
+        let integers = |list: Value, index: usize| {
+            let invalid = || error("crt", NumberTheoryErrorKind::NotAnIntegerList { index });
+            match list {
+                Value::List(values) => values.into_iter()
+                    .map(|value| match value.coerce_integer() {
+                        Value::Integer(n) => Ok(n),
+                        _ => Err(invalid()),
+                    })
+                    .collect::<Result<Vec<_>, _>>(),
+                _ => Err(invalid()),
+            }
+        };
+        let residues = integers(residues, 0)?;
+        let moduli = integers(moduli, 1)?;
+        if residues.len() != moduli.len() {
+            return Err(error("crt", NumberTheoryErrorKind::LengthMismatch));
+        }
+        if moduli.iter().any(|m| *m <= 0) {
+            return Err(error("crt", NumberTheoryErrorKind::NotPositive { index: 1 }));
+        }
+
+        // merge the congruences one at a time: x = r (mod m) and x = r2 (mod m2)
+        let (mut r, mut m) = (int(0), int(1));
+        for (r2, m2) in residues.into_iter().zip(moduli) {
+            let g = int(m.gcd_ref(&m2));
+            let diff = int(&r2 - &r);
+            if !diff.is_divisible(&g) {
+                return Err(error("crt", NumberTheoryErrorKind::NoSolution));
+            }
+
+            // m * t = r2 - r (mod m2), so t = (r2 - r) / g * (m / g)^-1 (mod m2 / g)
+            let m2g = int(&m2 / &g);
+            let t = int(&m / &g).invert(&m2g).unwrap() * (diff / &g) % &m2g;
+            r += int(&m * t);
+            m *= m2g;
+            r = r.rem_euc(&m);
+        }
+        Ok(r)
+    }
+}
+
+/// Jacobi symbol. Returns `jacobi(a, n)` for a positive odd integer `n`, which is `0`, `1` or
+/// `-1`. If `n` is prime, this is the Legendre symbol.
+#[derive(Debug)]
+pub struct Jacobi;
+
+#[builtin]
+impl Jacobi {
+    pub fn eval_static(a: Integer, n: Integer) -> Result<Integer, BuiltinError> {
+        if n <= 0 || n.is_even() {
+            return Err(error("jacobi", NumberTheoryErrorKind::NotPositiveOdd { index: 1 }));
+        }
+        Ok(int(a.jacobi(&n)))
+    }
+}
+
+/// Returns the partial quotients of the continued fraction of a number, as a list of integers.
+/// If `n` is given, at most `n` partial quotients are returned.
+#[derive(Debug)]
+pub struct Cf;
+
+#[builtin]
+impl Cf {
+    pub fn eval_static(x: Float, n: Option<Integer>) -> Result<Value, BuiltinError> {
+        check_finite("cf", &x, 0)?;
+        let n = max_terms("cf", n, 1)?;
+        Ok(Value::List(
+            continued_fraction(&x, n, &float(TOLERANCE))
+                .into_iter()
+                .map(Value::Integer)
+                .collect()
+        ))
+    }
+}
+
+/// Returns the value of the continued fraction with the given list of partial quotients.
+#[derive(Debug)]
+pub struct Fromcf;
+
+#[builtin]
+impl Fromcf {
+    pub fn eval_static(quotients: Value) -> Result<Value, BuiltinError> {
+        let invalid = || error("fromcf", NumberTheoryErrorKind::NotAnIntegerList { index: 0 });
+        let quotients = match quotients {
+            Value::List(values) => values.into_iter()
+                .map(|value| match value.coerce_integer() {
+                    Value::Integer(n) => Ok(n),
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(invalid()),
+        };
+        if quotients.iter().skip(1).any(|a| *a <= 0) {
+            return Err(error("fromcf", NumberTheoryErrorKind::InvalidContinuedFraction));
+        }
+        Ok(Value::from(approx::rational_from_continued_fraction(&quotients)))
+    }
+}
+
+/// Returns the convergents of the continued fraction of a number, which are increasingly accurate
+/// rational approximations of the number. If `n` is given, at most `n` convergents are returned.
+#[derive(Debug)]
+pub struct Convergents;
+
+#[builtin]
+impl Convergents {
+    pub fn eval_static(x: Float, n: Option<Integer>) -> Result<Value, BuiltinError> {
+        check_finite("convergents", &x, 0)?;
+        let n = max_terms("convergents", n, 1)?;
+        Ok(Value::List(
+            approx::convergents(&continued_fraction(&x, n, &float(TOLERANCE)))
+                .into_iter()
+                .map(Value::from)
+                .collect()
+        ))
+    }
+}
+
+/// Returns the best rational approximation of a number.
+///
+/// If `maxden` is given, the approximation is the closest fraction with a denominator of at most
+/// `maxden`. The approximation stops at the first convergent whose error is less than `tol`,
+/// which defaults to `10^-60`.
+#[derive(Debug)]
+pub struct Rationalize;
+
+#[builtin]
+impl Rationalize {
+    pub fn eval_static(x: Float, maxden: Option<Integer>, tol: Option<Float>) -> Result<Value, BuiltinError> {
+        check_finite("rationalize", &x, 0)?;
+        let tol = match tol {
+            Some(tol) if tol < 0 => {
+                return Err(error("rationalize", NumberTheoryErrorKind::Negative { index: 2 }));
+            },
+            Some(tol) => tol,
+            None => float(TOLERANCE),
+        };
+        let rational = match maxden {
+            Some(maxden) => {
+                check_positive("rationalize", &maxden, 1)?;
+                best_rational(&x, &maxden, &tol)
+            },
+            None => approximate_rational_with(&x, &tol),
+        };
+        Ok(Value::from(rational))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rug::Rational;
+
+    /// Returns the factorization of `n` as a list of pairs of integers.
+    fn factor_u64(n: u64) -> Vec<(u64, u32)> {
+        factor(&Ctxt::new(), &int(n))
+            .unwrap()
+            .into_iter()
+            .map(|(p, k)| (p.to_u64().unwrap(), k))
+            .collect()
+    }
+
+    #[test]
+    fn factor_small() {
+        assert!(factor_u64(1).is_empty());
+        assert_eq!(factor_u64(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factor_u64(997), vec![(997, 1)]);
+    }
+
+    #[test]
+    fn factor_large() {
+        // two primes above the trial division bound
+        assert_eq!(factor_u64(1_000_003 * 1_000_033), vec![(1_000_003, 1), (1_000_033, 1)]);
+        assert_eq!(factor_u64(1_009 * 1_009 * 1_013), vec![(1_009, 2), (1_013, 1)]);
+    }
+
+    #[test]
+    fn primality() {
+        assert!(Isprime::eval_static(int(2)));
+        assert!(Isprime::eval_static(int(997)));
+        assert!(!Isprime::eval_static(int(1_009 * 1_013)));
+        assert!(!Isprime::eval_static(int(1)));
+        assert!(!Isprime::eval_static(int(0)));
+        assert!(!Isprime::eval_static(int(-2)));
+        assert!(!Isprime::eval_static(int(-7)));
+    }
+
+    #[test]
+    fn prime_neighbors() {
+        let ctxt = Ctxt::new();
+        assert_eq!(Prevprime::eval_static(&ctxt, int(100)).unwrap(), 97);
+        assert_eq!(Prevprime::eval_static(&ctxt, int(3)).unwrap(), 2);
+        assert!(Prevprime::eval_static(&ctxt, int(2)).is_err());
+        assert_eq!(Nextprime::eval_static(&ctxt, int(97)).unwrap(), 101);
+        assert_eq!(Nextprime::eval_static(&ctxt, int(2)).unwrap(), 3);
+        assert_eq!(Nextprime::eval_static(&ctxt, int(-5)).unwrap(), 2);
+    }
+
+    #[test]
+    fn multiplicative() {
+        let ctxt = Ctxt::new();
+        assert_eq!(Totient::eval_static(&ctxt, int(36)).unwrap(), 12);
+        assert_eq!(Sigma::eval_static(&ctxt, int(12), None).unwrap(), 28);
+        assert_eq!(Sigma::eval_static(&ctxt, int(12), Some(int(0))).unwrap(), 6);
+        assert_eq!(Sigma::eval_static(&ctxt, int(12), Some(int(2))).unwrap(), 210);
+    }
+
+    #[test]
+    fn modular() {
+        assert_eq!(Modpow::eval_static(int(3), int(200), int(13)).unwrap(), 9);
+        assert_eq!(Modpow::eval_static(int(3), int(-1), int(7)).unwrap(), 5);
+        assert_eq!(Modinv::eval_static(int(3), int(7)).unwrap(), 5);
+        assert!(Modinv::eval_static(int(4), int(8)).is_err());
+        assert_eq!(Jacobi::eval_static(int(2), int(15)).unwrap(), 1);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        let list = |ns: &[i32]| Value::List(ns.iter().map(|&n| Value::Integer(int(n))).collect());
+        assert_eq!(Crt::eval_static(list(&[2, 3, 2]), list(&[3, 5, 7])).unwrap(), 23);
+        assert_eq!(Crt::eval_static(list(&[3, 5]), list(&[4, 6])).unwrap(), 11);
+        assert!(Crt::eval_static(list(&[1, 2]), list(&[4, 6])).is_err());
+    }
+
+    #[test]
+    fn continued_fractions() {
+        let list = |ns: &[i32]| Value::List(ns.iter().map(|&n| Value::Integer(int(n))).collect());
+        assert_eq!(Cf::eval_static(float(0.75), None).unwrap(), list(&[0, 1, 3]));
+        assert_eq!(Fromcf::eval_static(list(&[0, 1, 3])).unwrap(), Value::Rational(Rational::from((3, 4))));
+        assert!(Fromcf::eval_static(list(&[1, 0])).is_err());
+        assert_eq!(
+            Rationalize::eval_static(float(0.333), Some(int(10)), None).unwrap(),
+            Value::Rational(Rational::from((1, 3))),
+        );
+    }
+}
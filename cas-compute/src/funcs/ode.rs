@@ -0,0 +1,637 @@
+//! Numerical solutions of ordinary differential equations.
+//!
+//! The [`rkf45`] function integrates an initial value problem `y' = f(t, y)`, `y(t0) = y0`, using
+//! the adaptive Runge-Kutta-Fehlberg method, where `y` is a vector of one or more components. All
+//! arithmetic is done with [`Float`]s at the precision of the current thread, which is the
+//! precision of the context when called from CalcScript. If no tolerance is given, it is derived
+//! from that precision with [`default_tolerance`].
+//!
+//! With the `numerical` feature enabled, the method is available to users through the
+//! `odesolve(f, t0, y0, t1)` and `odelist(f, t0, y0, t1, n)` builtins, where `f` is the name of a
+//! user-defined function. For a system of `n` equations, `y0` is a list of `n` values, and `f` is
+//! called with `n + 1` arguments, `f(t, y1, y2, ..., yn)`, returning a list of `n` derivatives.
+
+use crate::primitive::{float, precision};
+use rug::Float;
+
+#[cfg(feature = "numerical")]
+use {
+    cas_parser::parser::ast::{call::Call, expr::Expr, literal::Literal},
+    crate::numerical::{
+        builtin::{
+            error::BuiltinError,
+            func_specific::{OdeError, OdeErrorKind},
+            Builtin,
+        },
+        ctxt::Ctxt,
+        error::{kind::{MissingArgument, TooManyArguments, TypeMismatch}, Error},
+        eval::{call_with_values, Eval},
+        value::Value,
+    },
+};
+
+/// The maximum number of steps (accepted or rejected) that [`rkf45`] will take before giving up.
+const MAX_STEPS: usize = 1 << 16;
+
+/// The smallest tolerance returned by [`default_tolerance`]. A fifth-order method needs too many
+/// steps to reach smaller tolerances, so they must be asked for explicitly.
+const MIN_DEFAULT_TOLERANCE: f64 = 1e-12;
+
+/// Returns the default tolerance for the local error of each step, when computing with the given
+/// number of bits of precision.
+///
+/// The tolerance is `2^10` times the machine epsilon, so that rounding errors stay well below the
+/// error estimates, but it is never smaller than `1e-12`.
+pub fn default_tolerance(precision: u32) -> Float {
+    let tol = float(1) >> (precision as i32 - 10);
+    tol.max(&float(MIN_DEFAULT_TOLERANCE))
+}
+
+/// The nodes `c_i` of the Runge-Kutta-Fehlberg method, as fractions.
+const C: [(i32, i32); 6] = [(0, 1), (1, 4), (3, 8), (12, 13), (1, 1), (1, 2)];
+
+/// The Runge-Kutta matrix `a_ij` of the Runge-Kutta-Fehlberg method, as fractions.
+const A: [&[(i32, i32)]; 6] = [
+    &[],
+    &[(1, 4)],
+    &[(3, 32), (9, 32)],
+    &[(1932, 2197), (-7200, 2197), (7296, 2197)],
+    &[(439, 216), (-8, 1), (3680, 513), (-845, 4104)],
+    &[(-8, 27), (2, 1), (-3544, 2565), (1859, 4104), (-11, 40)],
+];
+
+/// The weights of the fifth-order solution, as fractions.
+const B5: [(i32, i32); 6] = [(16, 135), (0, 1), (6656, 12825), (28561, 56430), (-9, 50), (2, 55)];
+
+/// The weights of the fourth-order solution, as fractions.
+const B4: [(i32, i32); 6] = [(25, 216), (0, 1), (1408, 2565), (2197, 4104), (-1, 5), (0, 1)];
+
+/// An error that can occur while integrating with [`rkf45`].
+#[derive(Debug)]
+pub enum Rkf45Error<E> {
+    /// The derivative function returned an error.
+    Eval(E),
+
+    /// The step size became too small to make progress, usually because the solution has a
+    /// singularity. Contains the value of `t` where integration stopped.
+    StepSizeUnderflow(Float),
+}
+
+/// Creates a [`Float`] from the given fraction.
+fn ratio((numer, denom): (i32, i32)) -> Float {
+    float(numer) / denom
+}
+
+/// Returns `y + h * sum(weights[j] * k[j])`.
+fn combine(y: &[Float], h: &Float, weights: &[(i32, i32)], k: &[Vec<Float>]) -> Vec<Float> {
+    y.iter()
+        .enumerate()
+        .map(|(i, y_i)| {
+            let sum = weights.iter()
+                .zip(k)
+                .fold(float(0), |sum, (&weight, k_j)| sum + ratio(weight) * &k_j[i]);
+            float(y_i + sum * h)
+        })
+        .collect()
+}
+
+/// Integrates the system `y' = f(t, y)` from `t0` to `t1`, with the initial value `y(t0) = y0`,
+/// using the adaptive Runge-Kutta-Fehlberg (RKF45) method. Returns the value of `y(t1)`.
+///
+/// The step size is chosen so that the estimated local error of each step, relative to
+/// `1 + |y|`, stays below `tol` in every component. `t1` may be less than `t0`, in which case the
+/// system is integrated backwards.
+///
+/// The function `f` must return a vector with the same length as `y0`.
+pub fn rkf45<E>(
+    mut f: impl FnMut(&Float, &[Float]) -> Result<Vec<Float>, E>,
+    t0: &Float,
+    y0: &[Float],
+    t1: &Float,
+    tol: &Float,
+) -> Result<Vec<Float>, Rkf45Error<E>> {
+    let span = float(t1 - t0);
+    let mut t = float(t0);
+    let mut y = y0.to_vec();
+    if span.is_zero() {
+        return Ok(y);
+    }
+
+    let min_step = float(span.abs_ref()) * float(1e-30);
+    let mut h = float(&span / 64);
+    for _ in 0..MAX_STEPS {
+        // do not step past the end of the interval
+        let remaining = float(t1 - &t);
+        let last = h.cmp_abs(&remaining).is_some_and(|ord| ord.is_ge());
+        if last {
+            h = remaining;
+        }
+
+        let mut k: Vec<Vec<Float>> = Vec::with_capacity(6);
+        for (&c, a) in C.iter().zip(A) {
+            let t_stage = float(&t + ratio(c) * &h);
+            let y_stage = combine(&y, &h, a, &k);
+            k.push(f(&t_stage, &y_stage).map_err(Rkf45Error::Eval)?);
+        }
+
+        let y5 = combine(&y, &h, &B5, &k);
+        let y4 = combine(&y, &h, &B4, &k);
+        let error = y5.iter()
+            .zip(&y4)
+            .zip(&y)
+            .map(|((a, b), y_i)| float(a - b).abs() / (float(y_i).abs() + 1))
+            .fold(float(0), |max, err| max.max(&err));
+
+        if error <= *tol {
+            // use the fifth-order solution (local extrapolation)
+            y = y5;
+            if last {
+                return Ok(y);
+            }
+            t += &h;
+        }
+
+        // scale the step size by 0.9 * (tol / error)^(1/5), within [0.2, 5]
+        let factor = if error.is_zero() {
+            float(5)
+        } else {
+            (float(tol / error).root(5) * float(0.9)).clamp(&0.2, &5)
+        };
+        h *= factor;
+
+        if *h.as_abs() < min_step {
+            return Err(Rkf45Error::StepSizeUnderflow(t));
+        }
+    }
+
+    Err(Rkf45Error::StepSizeUnderflow(t))
+}
+
+/// Integrates an initial value problem, `y' = f(t, y)`, `y(t0) = y0`, and returns `y(t1)`.
+///
+/// Evaluating `odesolve(f, t0, y0, t1, tol)` calls the user function `f` to compute derivatives.
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct Odesolve;
+
+impl Odesolve {
+    pub fn eval_static<E>(
+        f: impl FnMut(&Float, &[Float]) -> Result<Vec<Float>, E>,
+        t0: &Float,
+        y0: &[Float],
+        t1: &Float,
+        tol: Option<&Float>,
+    ) -> Result<Vec<Float>, Rkf45Error<E>> {
+        match tol {
+            Some(tol) => rkf45(f, t0, y0, t1, tol),
+            None => rkf45(f, t0, y0, t1, &default_tolerance(precision())),
+        }
+    }
+}
+
+/// Integrates an initial value problem, `y' = f(t, y)`, `y(t0) = y0`, and returns a list of `n + 1`
+/// samples `[t, y(t)]`, evenly spaced from `t0` to `t1`.
+///
+/// Evaluating `odelist(f, t0, y0, t1, n, tol)` calls the user function `f` to compute derivatives.
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug)]
+pub struct Odelist;
+
+impl Odelist {
+    pub fn eval_static<E>(
+        mut f: impl FnMut(&Float, &[Float]) -> Result<Vec<Float>, E>,
+        t0: &Float,
+        y0: &[Float],
+        t1: &Float,
+        n: usize,
+        tol: Option<&Float>,
+    ) -> Result<Vec<(Float, Vec<Float>)>, Rkf45Error<E>> {
+        let tol = tol.cloned().unwrap_or_else(|| default_tolerance(precision()));
+        let step = float(t1 - t0) / n as u64;
+
+        let mut samples = Vec::with_capacity(n + 1);
+        samples.push((float(t0), y0.to_vec()));
+        for i in 1..=n {
+            let (start, y) = &samples[i - 1];
+            let end = if i == n {
+                float(t1)
+            } else {
+                float(t0 + float(&step * i as u64))
+            };
+            let y = rkf45(&mut f, start, y, &end, &tol)?;
+            samples.push((end, y));
+        }
+
+        Ok(samples)
+    }
+}
+
+/// The arguments of the `odesolve` and `odelist` builtins, after evaluation.
+#[cfg(feature = "numerical")]
+struct OdeArgs<'a> {
+    /// The call to the builtin, used for error spans.
+    call: &'a Call,
+
+    /// The name of the builtin.
+    name: &'static str,
+
+    /// The function computing the derivatives.
+    func: crate::numerical::ctxt::Function,
+
+    /// Whether `y` is a single value, rather than a list.
+    scalar: bool,
+
+    /// The remaining arguments, evaluated.
+    values: Vec<Value>,
+}
+
+#[cfg(feature = "numerical")]
+impl<'a> OdeArgs<'a> {
+    /// Resolves the function argument and evaluates the remaining arguments of the call, checking
+    /// that the number of arguments matches the given signature. The last parameter is optional.
+    fn new(
+        ctxt: &mut Ctxt,
+        call: &'a Call,
+        name: &'static str,
+        signature: &'static str,
+        num_params: usize,
+    ) -> Result<Self, Error> {
+        Self::check_count(call, name, signature, num_params, call.args.len())?;
+
+        let mut args = Vec::with_capacity(call.args.len());
+        args.push(match &call.args[0] {
+            // a function passed by name is used even if a variable of the same name shadows it
+            Expr::Literal(Literal::Symbol(symbol)) => ctxt.get_function(&symbol.name)
+                .map_or(Value::Unit, Value::Function),
+            arg => arg.eval(ctxt)?,
+        });
+        for arg in &call.args[1..] {
+            args.push(arg.eval(ctxt)?);
+        }
+        Self::from_values(call, name, signature, num_params, args)
+    }
+
+    /// Checks arguments that have already been evaluated against the given signature, such as
+    /// when the builtin is itself passed to a higher-order function. The last parameter is
+    /// optional.
+    fn from_values(
+        call: &'a Call,
+        name: &'static str,
+        signature: &'static str,
+        num_params: usize,
+        args: Vec<Value>,
+    ) -> Result<Self, Error> {
+        Self::check_count(call, name, signature, num_params, args.len())?;
+
+        let mut values = args.into_iter();
+        let Some(Value::Function(func)) = values.next() else {
+            return Err(BuiltinError::from(OdeError::new(name, OdeErrorKind::NotAFunction))
+                .into_error(call));
+        };
+        let values = values.collect::<Vec<_>>();
+        let scalar = !matches!(values[1], Value::List(_));
+        Ok(Self { call, name, func, scalar, values })
+    }
+
+    /// Checks that the given number of arguments matches the given signature.
+    fn check_count(
+        call: &Call,
+        name: &'static str,
+        signature: &'static str,
+        num_params: usize,
+        given: usize,
+    ) -> Result<(), Error> {
+        if given > num_params {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: name.to_owned(),
+                expected: num_params,
+                given,
+                signature: signature.to_owned(),
+            }).into_error(call));
+        }
+        if given < num_params - 1 {
+            return Err(BuiltinError::MissingArgument(MissingArgument {
+                name: name.to_owned(),
+                index: given,
+                expected: num_params,
+                given,
+                signature: signature.to_owned(),
+            }).into_error(call));
+        }
+        Ok(())
+    }
+
+    /// Returns a [`TypeMismatch`] error for the argument at the given index.
+    fn type_mismatch(&self, index: usize, expected: &'static str, signature: &'static str) -> Error {
+        BuiltinError::TypeMismatch(TypeMismatch {
+            name: self.name.to_owned(),
+            index,
+            expected,
+            given: self.values[index - 1].typename(),
+            signature: signature.to_owned(),
+        }).into_error(self.call)
+    }
+
+    /// Returns the argument at the given index as a real number.
+    fn float(&self, index: usize, signature: &'static str) -> Result<Float, Error> {
+        match self.values[index - 1].clone().coerce_float() {
+            Value::Float(n) => Ok(n),
+            _ => Err(self.type_mismatch(index, "Float", signature)),
+        }
+    }
+
+    /// Returns the optional tolerance argument at the given index, which must be a positive,
+    /// finite number.
+    fn tol(&self, index: usize, signature: &'static str) -> Result<Option<Float>, Error> {
+        if self.values.len() < index {
+            return Ok(None);
+        }
+
+        let tol = self.float(index, signature)?;
+        if tol.is_finite() && tol > 0 {
+            Ok(Some(tol))
+        } else {
+            Err(BuiltinError::from(OdeError::new(self.name, OdeErrorKind::InvalidTolerance { index }))
+                .into_error(self.call))
+        }
+    }
+
+    /// Returns the initial value `y0` as a vector of components.
+    fn y0(&self, signature: &'static str) -> Result<Vec<Float>, Error> {
+        let components = match &self.values[1] {
+            Value::List(values) => values.clone(),
+            value => vec![value.clone()],
+        };
+        components.into_iter()
+            .map(|value| match value.coerce_float() {
+                Value::Float(n) => Ok(n),
+                _ => Err(self.type_mismatch(2, "Float or List of Floats", signature)),
+            })
+            .collect()
+    }
+
+    /// Calls the derivative function with the given `t` and `y`. Each call takes a step of the
+    /// evaluation, even if the function is a builtin.
+    fn derivative(&self, ctxt: &mut Ctxt, t: &Float, y: &[Float]) -> Result<Vec<Float>, Error> {
+        ctxt.step(|| self.call.span())?;
+        let args = std::iter::once(t)
+            .chain(y)
+            .map(|n| Value::Float(n.clone()))
+            .collect();
+        let value = call_with_values(ctxt, self.call, &self.func, args)?;
+
+        let invalid = |given: &'static str| {
+            let expected = if self.scalar {
+                "a number".to_string()
+            } else {
+                format!("a list of {} numbers", y.len())
+            };
+            BuiltinError::from(OdeError::new(
+                self.name,
+                OdeErrorKind::InvalidDerivative { expected, given },
+            )).into_error(self.call)
+        };
+        let to_float = |value: Value| match value.coerce_float() {
+            Value::Float(n) => Ok(n),
+            value => Err(invalid(value.typename())),
+        };
+
+        match value {
+            Value::List(values) if !self.scalar && values.len() == y.len() => {
+                values.into_iter().map(to_float).collect()
+            },
+            Value::List(_) => Err(invalid("List")),
+            value if self.scalar => Ok(vec![to_float(value)?]),
+            value => Err(invalid(value.typename())),
+        }
+    }
+
+    /// Converts a vector of components back into a value, matching the type of `y0`.
+    fn to_value(&self, y: Vec<Float>) -> Value {
+        if self.scalar {
+            Value::Float(y.into_iter().next().unwrap())
+        } else {
+            Value::List(y.into_iter().map(Value::Float).collect())
+        }
+    }
+
+    /// Converts an [`Rkf45Error`] into an [`Error`].
+    fn map_error(&self, err: Rkf45Error<Error>) -> Error {
+        match err {
+            Rkf45Error::Eval(err) => err,
+            Rkf45Error::StepSizeUnderflow(t) => BuiltinError::from(OdeError::new(
+                self.name,
+                OdeErrorKind::StepSizeUnderflow { t: t.to_f64().to_string() },
+            )).into_error(self.call),
+        }
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Odesolve {
+    const SIGNATURE: &'static str =
+        "odesolve(f: Function, t0: Float, y0: Value, t1: Float, tol: Float (optional))";
+
+    /// Integrates the initial value problem given by the arguments.
+    fn apply(ctxt: &mut Ctxt, args: OdeArgs) -> Result<Value, Error> {
+        let t0 = args.float(1, Self::SIGNATURE)?;
+        let y0 = args.y0(Self::SIGNATURE)?;
+        let t1 = args.float(3, Self::SIGNATURE)?;
+        let tol = args.tol(4, Self::SIGNATURE)?;
+
+        let f = |t: &Float, y: &[Float]| args.derivative(ctxt, t, y);
+        Odesolve::eval_static(f, &t0, &y0, &t1, tol.as_ref())
+            .map(|y| args.to_value(y))
+            .map_err(|err| args.map_error(err))
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Builtin for Odesolve {
+    fn num_args(&self) -> usize {
+        5
+    }
+
+    fn eval(&self, _: &Ctxt, _: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        // the function argument cannot be called without a context
+        Err(OdeError::new("odesolve", OdeErrorKind::NotAFunction).into())
+    }
+
+    fn eval_values(&self, ctxt: &mut Ctxt, call: &Call, args: Vec<Value>) -> Result<Value, Error> {
+        OdeArgs::from_values(call, "odesolve", Self::SIGNATURE, 5, args)
+            .and_then(|args| Self::apply(ctxt, args))
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(OdeArgs::new(ctxt, call, "odesolve", Self::SIGNATURE, 5)
+            .and_then(|args| Self::apply(ctxt, args)))
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Odelist {
+    const SIGNATURE: &'static str =
+        "odelist(f: Function, t0: Float, y0: Value, t1: Float, n: Integer, tol: Float (optional))";
+
+    /// Integrates the initial value problem given by the arguments, sampling the solution.
+    fn apply(ctxt: &mut Ctxt, args: OdeArgs) -> Result<Value, Error> {
+        let t0 = args.float(1, Self::SIGNATURE)?;
+        let y0 = args.y0(Self::SIGNATURE)?;
+        let t1 = args.float(3, Self::SIGNATURE)?;
+        let n = match args.values[3].clone().coerce_integer() {
+            Value::Integer(n) => n.to_usize()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| {
+                    BuiltinError::from(OdeError::new("odelist", OdeErrorKind::InvalidSampleCount))
+                        .into_error(args.call)
+                })?,
+            _ => return Err(args.type_mismatch(4, "Integer", Self::SIGNATURE)),
+        };
+        let tol = args.tol(5, Self::SIGNATURE)?;
+
+        let f = |t: &Float, y: &[Float]| args.derivative(ctxt, t, y);
+        Odelist::eval_static(f, &t0, &y0, &t1, n, tol.as_ref())
+            .map(|samples| Value::List(
+                samples.into_iter()
+                    .map(|(t, y)| Value::List(vec![Value::Float(t), args.to_value(y)]))
+                    .collect()
+            ))
+            .map_err(|err| args.map_error(err))
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Builtin for Odelist {
+    fn num_args(&self) -> usize {
+        6
+    }
+
+    fn eval(&self, _: &Ctxt, _: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        // the function argument cannot be called without a context
+        Err(OdeError::new("odelist", OdeErrorKind::NotAFunction).into())
+    }
+
+    fn eval_values(&self, ctxt: &mut Ctxt, call: &Call, args: Vec<Value>) -> Result<Value, Error> {
+        OdeArgs::from_values(call, "odelist", Self::SIGNATURE, 6, args)
+            .and_then(|args| Self::apply(ctxt, args))
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(OdeArgs::new(ctxt, call, "odelist", Self::SIGNATURE, 6)
+            .and_then(|args| Self::apply(ctxt, args)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential() {
+        // y' = y, y(0) = 1 => y(1) = e
+        let y = rkf45(
+            |_, y| Ok::<_, ()>(vec![y[0].clone()]),
+            &float(0),
+            &[float(1)],
+            &float(1),
+            &float(1e-12),
+        ).unwrap();
+        assert!(float(&y[0] - &crate::consts::e()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn backwards() {
+        // y' = -2ty, y(1) = e^-1 => y(0) = 1
+        let y = rkf45(
+            |t, y| Ok::<_, ()>(vec![float(t * &y[0]) * -2]),
+            &float(1),
+            &[float(-1).exp()],
+            &float(0),
+            &float(1e-12),
+        ).unwrap();
+        assert!(float(&y[0] - 1).abs() < 1e-10);
+    }
+
+    #[test]
+    fn harmonic_oscillator() {
+        // x'' = -x, x(0) = 1, x'(0) = 0 => x(t) = cos(t)
+        let samples = Odelist::eval_static(
+            |_, y| Ok::<_, ()>(vec![y[1].clone(), -y[0].clone()]),
+            &float(0),
+            &[float(1), float(0)],
+            &float(3),
+            3,
+            None,
+        ).unwrap();
+        assert_eq!(samples.len(), 4);
+        for (t, y) in samples {
+            assert!(float(&y[0] - t.clone().cos()).abs() < 1e-9);
+            assert!(float(&y[1] + t.sin()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn singularity() {
+        // y' = y^2, y(0) = 1 => y = 1 / (1 - t), which blows up at t = 1
+        let result = rkf45(
+            |_, y| Ok::<_, ()>(vec![float(y[0].square_ref())]),
+            &float(0),
+            &[float(1)],
+            &float(2),
+            &float(1e-12),
+        );
+        assert!(matches!(result, Err(Rkf45Error::StepSizeUnderflow(_))));
+    }
+
+    #[test]
+    fn default_tolerance_follows_precision() {
+        assert_eq!(default_tolerance(24), float(1) >> 14);
+        assert_eq!(default_tolerance(53), float(1e-12));
+        assert_eq!(default_tolerance(512), float(1e-12));
+    }
+
+    #[cfg(feature = "numerical")]
+    #[test]
+    fn invalid_tolerance() {
+        use crate::numerical::test_util::eval;
+
+        for tol in ["0", "-10^-6", "1 / 0"] {
+            let err = eval(&format!("f(t, y) = y; odesolve(f, 0, 1, 1, {})", tol)).unwrap_err();
+            assert!(format!("{:?}", err.kind).contains("InvalidTolerance"), "{}", tol);
+        }
+        let err = eval("f(t, y) = y; odelist(f, 0, 1, 1, 2, 0)").unwrap_err();
+        assert!(format!("{:?}", err.kind).contains("InvalidTolerance"));
+    }
+
+    #[cfg(feature = "numerical")]
+    #[test]
+    fn low_precision() {
+        use crate::numerical::test_util::eval_str;
+
+        // the default tolerance is looser at low precision, but still accurate to a few digits
+        assert_eq!(eval_str("precision(24); f(t, y) = y; abs(odesolve(f, 0, 1, 1) - e) < 10^-4"), "true");
+    }
+
+    #[cfg(feature = "numerical")]
+    #[test]
+    fn function_values() {
+        use crate::numerical::test_util::{eval, eval_str};
+
+        // the builtins can be called through function values
+        assert_eq!(eval_str("f(t, y) = y; g = odesolve; g(f, 0, 1, 1) ~== e"), "true");
+        assert_eq!(eval_str("f(t, y) = y; map(g -> g(f, 0, 1, 1) ~== e, [odesolve])"), "[true]");
+        assert_eq!(eval_str("f(t, y) = 2; g = [odelist][0]; g(f, 0, 0, 2, 2) ~== [[0, 0], [1, 2], [2, 4]]"), "[[true, true], [true, true], [true, true]]");
+
+        // passing the builtin to `map` calls it with too few arguments
+        let err = eval("map(odesolve, [1])").unwrap_err();
+        assert!(format!("{:?}", err.kind).starts_with("MissingArgument"));
+    }
+}
@@ -1,7 +1,7 @@
 //! Functions related to powers, exponentiation, and roots.
 
 use cas_attrs::builtin;
-use crate::consts::{I, TAU, TEN};
+use crate::consts::{i, tau, ten};
 use crate::primitive::{complex, float};
 use rug::{ops::Pow as _, Complex, Float};
 
@@ -31,7 +31,7 @@ pub struct Scientific;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Scientific {
     pub fn eval_static(a: Complex, b: Complex) -> Complex {
-        a * complex(&*TEN).pow(b)
+        a * complex(&ten()).pow(b)
     }
 }
 
@@ -42,7 +42,7 @@ pub struct Log;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Log {
     pub fn eval_static(n: Complex, base: Option<Complex>) -> Complex {
-        let base = base.unwrap_or(complex(&*TEN));
+        let base = base.unwrap_or(complex(&ten()));
         n.ln() / base.ln()
     }
 }
@@ -77,7 +77,7 @@ impl Cbrt {
                 n.arg().into_real_imag().0,
             );
             let lhs = abs.cbrt();
-            let rhs = complex(one_third * (arg + &*TAU) * &*I).exp();
+            let rhs = complex(one_third * (arg + &tau()) * &i()).exp();
             lhs * rhs
         }
     }
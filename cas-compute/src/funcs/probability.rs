@@ -1,7 +1,7 @@
 //! Probability density and distribution functions.
 
 use cas_attrs::builtin;
-use crate::consts::{E, ONE, PI, TAU, TWO, ZERO};
+use crate::consts::{e, one, pi, tau, two, zero};
 use crate::primitive::{float, float_from_str, int};
 use once_cell::sync::Lazy;
 use rug::{float::Special, ops::Pow, Float, Integer};
@@ -38,7 +38,7 @@ static INV_ERF_A: Lazy<[Float; 4]> = Lazy::new(|| [
 ]);
 
 static INV_ERF_B: Lazy<[Float; 5]> = Lazy::new(|| [
-    float(&*ONE),
+    one(),
     float_from_str("-2.118377725"),
     float_from_str("1.442710462"),
     float_from_str("-0.329097515"),
@@ -53,7 +53,7 @@ static INV_ERF_C: Lazy<[Float; 4]> = Lazy::new(|| [
 ]);
 
 static INV_ERF_D: Lazy<[Float; 3]> = Lazy::new(|| [
-    float(&*ONE),
+    one(),
     float_from_str("3.543889200"),
     float_from_str("1.637067800"),
 ]);
@@ -89,7 +89,7 @@ impl Inverf {
             num / den
         } else {
             let y = {
-                let inner_log = (&*ONE - float(&z)) / &*TWO;
+                let inner_log = (one() - float(&z)) / two();
                 (-inner_log.ln()).sqrt()
             };
             let num = fold_arr(&*INV_ERF_C, &y);
@@ -101,7 +101,7 @@ impl Inverf {
         z = if x.is_sign_negative() { -z } else { z };
 
         // double newton's method
-        let f = 2 / float(&*PI).sqrt();
+        let f = 2 / pi().sqrt();
         r -= (float(r.erf_ref()) - &z) / (&f * float((-float(r.square_ref())).exp_ref()));
         r -= (float(r.erf_ref()) - &z) / (&f * float((-float(r.square_ref())).exp_ref()));
 
@@ -119,10 +119,10 @@ pub struct Normpdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Normpdf {
     pub fn eval_static(x: Float, m: Option<Float>, s: Option<Float>) -> Float {
-        let m = m.unwrap_or_else(|| float(&*ZERO));
-        let s = s.unwrap_or_else(|| float(&*ONE));
+        let m = m.unwrap_or_else(zero);
+        let s = s.unwrap_or_else(one);
         let exp_arg = Float::exp(float(x - m).square() / (-2 * float(s.square_ref())));
-        let bot = s * float(TAU.sqrt_ref());
+        let bot = s * tau().sqrt();
         exp_arg / bot
     }
 }
@@ -137,12 +137,12 @@ pub struct Normcdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Normcdf {
     pub fn eval_static(a: Float, b: Float, m: Option<Float>, s: Option<Float>) -> Float {
-        let m = m.unwrap_or_else(|| float(&*ZERO));
-        let s = s.unwrap_or_else(|| float(&*ONE));
-        let sqrt_two = float(TWO.sqrt_ref());
+        let m = m.unwrap_or_else(zero);
+        let s = s.unwrap_or_else(one);
+        let sqrt_two = two().sqrt();
         let z_a = (a - &m) / float(&s * &sqrt_two);
         let z_b = (b - &m) / float(&s * &sqrt_two);
-        (z_b.erf() - z_a.erf()) / &*TWO
+        (z_b.erf() - z_a.erf()) / two()
     }
 }
 
@@ -157,9 +157,9 @@ pub struct Invnorm;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Invnorm {
     pub fn eval_static(p: Float, m: Option<Float>, s: Option<Float>) -> Float {
-        let m = m.unwrap_or_else(|| float(&*ZERO));
-        let s = s.unwrap_or_else(|| float(&*ONE));
-        let sqrt_two = float(TWO.sqrt_ref());
+        let m = m.unwrap_or_else(zero);
+        let s = s.unwrap_or_else(one);
+        let sqrt_two = two().sqrt();
         let z = sqrt_two * Inverf::eval_static(2 * p - 1);
         m + s * z
     }
@@ -175,11 +175,11 @@ pub struct Geompdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Geompdf {
     pub fn eval_static(p: Float, n: Integer) -> Float {
-        if n <= *ZERO {
-            return float(&*ZERO);
+        if n <= zero() {
+            return zero();
         }
 
-        let q = float(&*ONE - &p);
+        let q = one() - &p;
         p * q.pow(n - 1)
     }
 }
@@ -194,12 +194,12 @@ pub struct Geomcdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Geomcdf {
     pub fn eval_static(p: Float, n: Integer) -> Float {
-        if n <= *ZERO {
-            return float(&*ZERO);
+        if n <= zero() {
+            return zero();
         }
 
-        let q = float(&*ONE - &p);
-        &*ONE - q.pow(n)
+        let q = one() - &p;
+        one() - q.pow(n)
     }
 }
 
@@ -213,11 +213,11 @@ pub struct Binompdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Binompdf {
     pub fn eval_static(n: Integer, p: Float, x: Integer) -> Float {
-        if x < *ZERO || x > n {
-            return float(&*ZERO);
+        if x < zero() || x > n {
+            return zero();
         }
 
-        let q = float(&*ONE - &p);
+        let q = one() - &p;
 
         let c = q.pow(n.clone() - &x);
         let b = p.pow(&x);
@@ -236,14 +236,14 @@ pub struct Binomcdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Binomcdf {
     pub fn eval_static(n: Integer, p: Float, mut x: Integer) -> Float {
-        if x < *ZERO {
-            return float(&*ZERO);
+        if x < zero() {
+            return zero();
         } else if x >= n {
-            return float(&*ONE);
+            return one();
         }
 
-        let mut sum = float(&*ZERO);
-        while x >= *ZERO {
+        let mut sum = zero();
+        while x >= zero() {
             sum += Binompdf::eval_static(n.clone(), p.clone(), x.clone());
             x -= 1;
         }
@@ -261,11 +261,11 @@ pub struct Poisspdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Poisspdf {
     pub fn eval_static(k: Integer, l: Float) -> Float {
-        if k < *ZERO {
-            return float(&*ZERO);
+        if k < zero() {
+            return zero();
         }
 
-        let b = float(&*E).pow(&*l.as_neg());
+        let b = e().pow(&*l.as_neg());
         let a = l.pow(&k);
         let c = partial_factorial(k, int(1));
         a * b / c
@@ -282,8 +282,8 @@ pub struct Poisscdf;
 #[cfg_attr(feature = "numerical", builtin)]
 impl Poisscdf {
     pub fn eval_static(k: Integer, l: Float) -> Float {
-        if k < *ZERO {
-            return float(&*ZERO);
+        if k < zero() {
+            return zero();
         }
 
         fn reg_gamma(s: Float, x: Float) -> Float {
@@ -0,0 +1,525 @@
+//! Least-squares regression on lists of data points.
+//!
+//! Each function takes a list of x-values and a list of y-values, and fits a model to them:
+//!
+//! - `linreg(xs, ys)` fits a line `a * x + b`, returning the coefficients `[a, b]`.
+//! - `polyfit(xs, ys, n)` fits a polynomial of degree `n`, returning its coefficients from the
+//!   highest power of `x` to the constant term.
+//! - `expfit(xs, ys)` fits `a * exp(b * x)`, returning `[a, b]`.
+//! - `logfit(xs, ys)` fits `a + b * ln(x)`, returning `[a, b]`.
+//! - `powfit(xs, ys)` fits `a * x^b`, returning `[a, b]`.
+//!
+//! The result is the list `[coefficients, r2]`, where `r2` is the coefficient of determination of
+//! the fit. In addition, the fitted model is defined as a function of `x` in the context, named
+//! `fit` by default, or the name given as the last argument, such as `g` in `linreg(xs, ys, g)`.
+//! The function can then be called and graphed like any other function.
+//!
+//! Polynomial fits of exact data, such as integers, have exact coefficients. The other models are
+//! fit by taking the logarithm of the x-values, y-values, or both, and fitting a line to the
+//! result; their R² is that of the line, which is what most calculators and spreadsheets report.
+//!
+//! The same fits are available in Rust with [`Fit`]. [`Fit::expr`] returns the fitted model as an
+//! expression that can be passed to other crates, such as a graphing calculator.
+
+use cas_parser::parser::{
+    ast::{
+        Binary,
+        Call,
+        Expr,
+        FuncHeader,
+        LitFloat,
+        LitInt,
+        LitSym,
+        Literal,
+        Param,
+        Paren,
+        Unary,
+    },
+    token::op::{BinOp, BinOpKind, UnaryOp, UnaryOpKind},
+};
+use crate::funcs::{
+    list::compare,
+    statistics::{apply, mean, real_data, sum},
+};
+use crate::numerical::{
+    builtin::{
+        error::BuiltinError,
+        func_specific::{StatisticsError, StatisticsErrorKind},
+        Builtin,
+    },
+    ctxt::Ctxt,
+    error::{kind::{MissingArgument, TooManyArguments, TypeMismatch}, Error},
+    eval::Eval,
+    matrix::Matrix,
+    value::Value,
+};
+use rug::{Float, Integer};
+use std::cmp::Ordering;
+
+/// The name of the fitted function if none is given.
+pub const DEFAULT_NAME: &str = "fit";
+
+/// A model that can be fit to data points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// A polynomial of the given degree, `c_n * x^n + ... + c_1 * x + c_0`.
+    Polynomial(usize),
+
+    /// An exponential function, `a * exp(b * x)`.
+    Exponential,
+
+    /// A logarithmic function, `a + b * ln(x)`.
+    Logarithmic,
+
+    /// A power function, `a * x^b`.
+    Power,
+}
+
+/// A model fit to data points with least-squares regression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    /// The model that was fit.
+    pub model: Model,
+
+    /// The coefficients of the model. For a polynomial, these are ordered from the highest power
+    /// of `x` to the constant term. For the other models, these are `[a, b]`.
+    pub coefficients: Vec<Value>,
+
+    /// The coefficient of determination of the fit.
+    pub r_squared: Value,
+}
+
+impl Fit {
+    /// Fits the model to the data points with the given x-values and y-values.
+    ///
+    /// Errors refer to the x-values as argument 0, and the y-values as argument 1.
+    pub fn new(model: Model, xs: Vec<Value>, ys: Vec<Value>) -> Result<Self, StatisticsErrorKind> {
+        let (degree, log_x, log_y) = match model {
+            Model::Polynomial(degree) => (degree, false, false),
+            Model::Exponential => (1, false, true),
+            Model::Logarithmic => (1, true, false),
+            Model::Power => (1, true, true),
+        };
+        let required = degree.saturating_add(1);
+        let xs = real_data(xs, 0, required)?;
+        let ys = real_data(ys, 1, required)?;
+        if xs.len() != ys.len() {
+            return Err(StatisticsErrorKind::LengthMismatch { index: 1, expected: xs.len(), given: ys.len() });
+        }
+
+        let xs = if log_x { ln_all(xs, 0)? } else { xs };
+        let ys = if log_y { ln_all(ys, 1)? } else { ys };
+        let (mut coefficients, r_squared) = least_squares(&xs, &ys, degree)
+            .ok_or(StatisticsErrorKind::TooFewDistinct { index: 0, required })?;
+
+        // the line is fit as `b * x + c`, which is rearranged into `[a, b]`
+        if !matches!(model, Model::Polynomial(_)) {
+            let intercept = coefficients.pop().unwrap();
+            let a = if log_y { Value::Float(real(&intercept).exp()) } else { intercept };
+            coefficients.insert(0, a);
+        }
+        Ok(Self { model, coefficients, r_squared })
+    }
+
+    /// Returns the fitted model as an expression in the variable `x`.
+    pub fn expr(&self) -> Expr {
+        let x = || symbol("x");
+        match self.model {
+            Model::Polynomial(degree) => linear_combination(self.coefficients.iter()
+                .enumerate()
+                .map(|(i, coefficient)| {
+                    let power = degree - i;
+                    let basis = match power {
+                        0 => None,
+                        1 => Some(x()),
+                        _ => Some(binary(x(), BinOpKind::Exp, number(&Value::Integer(power.into())))),
+                    };
+                    (coefficient.clone(), basis)
+                })),
+            Model::Exponential => {
+                let exponent = linear_combination([(self.coefficients[1].clone(), Some(x()))]);
+                linear_combination([(self.coefficients[0].clone(), Some(call("exp", exponent)))])
+            },
+            Model::Logarithmic => linear_combination([
+                (self.coefficients[0].clone(), None),
+                (self.coefficients[1].clone(), Some(call("ln", x()))),
+            ]),
+            Model::Power => {
+                let exponent = linear_combination([(self.coefficients[1].clone(), None)]);
+                linear_combination([(self.coefficients[0].clone(), Some(binary(x(), BinOpKind::Exp, exponent)))])
+            },
+        }
+    }
+
+    /// Defines the fitted model as a function of `x` with the given name in the context.
+    pub fn define(&self, ctxt: &mut Ctxt, name: &str) {
+        let header = FuncHeader {
+            name: LitSym { name: name.to_owned(), span: 0..0 },
+            params: vec![Param::Symbol(LitSym { name: "x".to_owned(), span: 0..0 })],
+            span: 0..0,
+        };
+        ctxt.add_func(header, self.expr(), false);
+    }
+}
+
+impl From<Fit> for Value {
+    fn from(fit: Fit) -> Self {
+        Value::List(vec![Value::List(fit.coefficients), fit.r_squared])
+    }
+}
+
+/// Converts a real number to a [`Float`].
+fn real(n: &Value) -> Float {
+    match n.clone().coerce_float() {
+        Value::Float(n) => n,
+        _ => unreachable!("only real numbers are converted"),
+    }
+}
+
+/// Returns the natural logarithms of the numbers of argument `index`, if they are all positive.
+fn ln_all(values: Vec<Value>, index: usize) -> Result<Vec<Value>, StatisticsErrorKind> {
+    let zero = Value::Integer(Integer::new());
+    values.into_iter()
+        .map(|value| if compare(&value, &zero) == Ordering::Greater {
+            Ok(Value::Float(real(&value).ln()))
+        } else {
+            Err(StatisticsErrorKind::NonPositive { index })
+        })
+        .collect()
+}
+
+/// Evaluates the polynomial with the given coefficients, ordered from the highest power of `x`.
+fn eval_polynomial(coefficients: &[Value], x: &Value) -> Value {
+    coefficients.iter()
+        .fold(Value::Integer(Integer::new()), |result, coefficient| {
+            apply(BinOpKind::Add, &apply(BinOpKind::Mul, &result, x), coefficient)
+        })
+}
+
+/// Fits a polynomial of the given degree to the data points by solving the normal equations,
+/// returning its coefficients and the coefficient of determination.
+///
+/// Returns [`None`] if there are too few distinct x-values to determine the polynomial.
+fn least_squares(xs: &[Value], ys: &[Value], degree: usize) -> Option<(Vec<Value>, Value)> {
+    let rows = xs.iter()
+        .map(|x| (0..=degree)
+            .rev()
+            .map(|power| apply(BinOpKind::Exp, x, &Value::Integer(power.into())))
+            .collect())
+        .collect();
+    let a = Matrix::from_rows(rows)?;
+    let a_t = a.transpose();
+    let coefficients = a_t.mul(&a)?
+        .solve(&a_t.mul(&Matrix::column(ys.to_vec())?)?)?
+        .into_entries();
+
+    // R² = 1 - (residual sum of squares) / (total sum of squares)
+    let square = |n: Value| apply(BinOpKind::Mul, &n, &n);
+    let mean = mean(ys);
+    let total = sum(&ys.iter().map(|y| square(apply(BinOpKind::Sub, y, &mean))).collect::<Vec<_>>());
+    let residual = sum(&xs.iter()
+        .zip(ys)
+        .map(|(x, y)| square(apply(BinOpKind::Sub, y, &eval_polynomial(&coefficients, x))))
+        .collect::<Vec<_>>());
+    let one = Value::Integer(Integer::from(1));
+    let r_squared = if compare(&total, &Value::Integer(Integer::new())) == Ordering::Equal {
+        // every y-value is the same, and the fit is exact
+        one
+    } else {
+        apply(BinOpKind::Sub, &one, &apply(BinOpKind::Div, &residual, &total))
+    };
+    Some((coefficients, r_squared))
+}
+
+/// Creates a symbol.
+fn symbol(name: &str) -> Expr {
+    Expr::Literal(Literal::Symbol(LitSym { name: name.to_owned(), span: 0..0 }))
+}
+
+/// Creates a call to a function with one argument.
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::Call(Call {
+        name: LitSym { name: name.to_owned(), span: 0..0 },
+        derivatives: 0,
+        args: vec![arg],
+        named_args: Vec::new(),
+        span: 0..0,
+        paren_span: 0..0,
+    })
+}
+
+/// Creates a binary expression.
+fn binary(lhs: Expr, kind: BinOpKind, rhs: Expr) -> Expr {
+    Expr::Binary(Binary {
+        lhs: Box::new(lhs),
+        op: BinOp { kind, implicit: false, span: 0..0 },
+        rhs: Box::new(rhs),
+        span: 0..0,
+    })
+}
+
+/// Creates a literal for a non-negative real number. Fractions are parenthesized.
+fn number(n: &Value) -> Expr {
+    match n {
+        Value::Integer(n) => Expr::Literal(Literal::Integer(LitInt { value: n.to_string(), span: 0..0 })),
+        Value::Rational(n) => Expr::Paren(Paren {
+            expr: Box::new(binary(
+                number(&Value::Integer(n.numer().clone())),
+                BinOpKind::Div,
+                number(&Value::Integer(n.denom().clone())),
+            )),
+            span: 0..0,
+        }),
+        n => Expr::Literal(Literal::Float(LitFloat { value: real(n).to_string(), span: 0..0 })),
+    }
+}
+
+/// Creates the sum of the given terms, each of which is a coefficient multiplied by an expression,
+/// or a constant if there is no expression. Terms with a coefficient of zero are omitted, and
+/// negative coefficients are subtracted.
+fn linear_combination(terms: impl IntoIterator<Item = (Value, Option<Expr>)>) -> Expr {
+    let zero = Value::Integer(Integer::new());
+    let one = Value::Integer(Integer::from(1));
+    let mut result: Option<Expr> = None;
+    for (coefficient, basis) in terms {
+        let sign = compare(&coefficient, &zero);
+        if sign == Ordering::Equal {
+            continue;
+        }
+
+        let magnitude = if sign == Ordering::Less {
+            apply(BinOpKind::Sub, &zero, &coefficient)
+        } else {
+            coefficient
+        };
+        let term = match basis {
+            Some(basis) if compare(&magnitude, &one) == Ordering::Equal => basis,
+            Some(basis) => binary(number(&magnitude), BinOpKind::Mul, basis),
+            None => number(&magnitude),
+        };
+        result = Some(match (result, sign) {
+            (Some(sum), Ordering::Less) => binary(sum, BinOpKind::Sub, term),
+            (Some(sum), _) => binary(sum, BinOpKind::Add, term),
+            (None, Ordering::Less) => Expr::Unary(Unary {
+                operand: Box::new(term),
+                op: UnaryOp { kind: UnaryOpKind::Neg, span: 0..0 },
+                span: 0..0,
+            }),
+            (None, _) => term,
+        });
+    }
+    result.unwrap_or_else(|| number(&zero))
+}
+
+/// Describes one of the functions in this module.
+struct Signature {
+    /// The name of the function.
+    name: &'static str,
+
+    /// The signature of the function, used in error messages.
+    signature: &'static str,
+
+    /// The number of arguments the function takes, not including the optional name of the fitted
+    /// function.
+    num_args: usize,
+
+    /// Returns the model to fit, given the arguments of the function.
+    model: fn(&Signature, &[Value]) -> Result<Model, BuiltinError>,
+}
+
+impl Signature {
+    /// Creates an error of the given kind.
+    fn error(&self, kind: StatisticsErrorKind) -> BuiltinError {
+        StatisticsError::new(self.name, kind).into()
+    }
+
+    /// Checks that the function was given the correct number of arguments.
+    fn check_count(&self, given: usize) -> Result<(), BuiltinError> {
+        if given > self.num_args + 1 {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: self.name.to_owned(),
+                expected: self.num_args + 1,
+                given,
+                signature: self.signature.to_owned(),
+            }));
+        }
+        if given < self.num_args {
+            return Err(BuiltinError::MissingArgument(MissingArgument {
+                name: self.name.to_owned(),
+                index: given,
+                expected: self.num_args + 1,
+                given,
+                signature: self.signature.to_owned(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Returns a type mismatch error for argument `index`.
+    fn type_mismatch(&self, index: usize, expected: &'static str, given: &Value) -> BuiltinError {
+        BuiltinError::TypeMismatch(TypeMismatch {
+            name: self.name.to_owned(),
+            index,
+            expected,
+            given: given.typename(),
+            signature: self.signature.to_owned(),
+        })
+    }
+
+    /// Fits the model to the data points given as the first two arguments.
+    fn fit(&self, args: Vec<Value>) -> Result<Fit, BuiltinError> {
+        let list = |index: usize| match &args[index] {
+            Value::List(list) => Ok(list.clone()),
+            value => Err(self.type_mismatch(index, "List", value)),
+        };
+        let (xs, ys) = (list(0)?, list(1)?);
+        let model = (self.model)(self, &args)?;
+        Fit::new(model, xs, ys).map_err(|kind| self.error(kind))
+    }
+}
+
+/// Declares builtins that fit a model to data points. The function evaluating the call defines the
+/// fitted function in the context.
+macro_rules! regression {
+    ($(
+        $(#[$attr:meta])*
+        $upname:ident $name:literal($($arg:literal),*), |$signature:pat_param, $args:pat_param| $model:expr;
+    )*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug)]
+            pub struct $upname;
+
+            impl $upname {
+                /// The signature of the function.
+                const SIGNATURE: Signature = Signature {
+                    name: $name,
+                    signature: concat!($name, "(", $($arg, ", ",)* "f: Function (optional))"),
+                    num_args: [$($arg),*].len(),
+                    model: |$signature, $args| $model,
+                };
+            }
+
+            impl Builtin for $upname {
+                fn num_args(&self) -> usize {
+                    Self::SIGNATURE.num_args + 1
+                }
+
+                fn eval(&self, _: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+                    // the context cannot be modified here, so the fitted function is not defined,
+                    // and it cannot be named with a value
+                    let args = args.collect::<Vec<_>>();
+                    Self::SIGNATURE.check_count(args.len())?;
+                    if args.len() > Self::SIGNATURE.num_args {
+                        return Err(Self::SIGNATURE.error(StatisticsErrorKind::NotAName {
+                            index: Self::SIGNATURE.num_args,
+                        }));
+                    }
+                    Self::SIGNATURE.fit(args).map(Value::from)
+                }
+
+                fn takes_call(&self) -> bool {
+                    true
+                }
+
+                fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+                    let signature = &Self::SIGNATURE;
+                    let eval = |ctxt: &mut Ctxt| {
+                        signature.check_count(call.args.len()).map_err(|err| err.into_error(call))?;
+                        let name = match call.args.get(signature.num_args) {
+                            Some(Expr::Literal(Literal::Symbol(symbol))) => symbol.name.as_str(),
+                            Some(_) => return Err(signature.error(StatisticsErrorKind::NotAName {
+                                index: signature.num_args,
+                            }).into_error(call)),
+                            None => DEFAULT_NAME,
+                        };
+                        let args = call.args[..signature.num_args].iter()
+                            .map(|arg| arg.eval(ctxt))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        let fit = signature.fit(args).map_err(|err| err.into_error(call))?;
+                        fit.define(ctxt, name);
+                        Ok(Value::from(fit))
+                    };
+                    Some(eval(ctxt))
+                }
+
+                fn mutates_ctxt(&self) -> bool {
+                    true
+                }
+            }
+        )*
+    };
+}
+
+regression! {
+    /// Fits a line to data points.
+    Linreg "linreg"("xs: List", "ys: List"), |_, _| Ok(Model::Polynomial(1));
+
+    /// Fits a polynomial of the given degree to data points.
+    Polyfit "polyfit"("xs: List", "ys: List", "degree: Integer"), |signature, args| {
+        match args[2].clone().coerce_integer() {
+            Value::Integer(degree) => degree.to_usize()
+                .map(Model::Polynomial)
+                .ok_or_else(|| signature.error(StatisticsErrorKind::InvalidDegree)),
+            value => Err(signature.type_mismatch(2, "Integer", &value)),
+        }
+    };
+
+    /// Fits an exponential function to data points with positive y-values.
+    Expfit "expfit"("xs: List", "ys: List"), |_, _| Ok(Model::Exponential);
+
+    /// Fits a logarithmic function to data points with positive x-values.
+    Logfit "logfit"("xs: List", "ys: List"), |_, _| Ok(Model::Logarithmic);
+
+    /// Fits a power function to data points with positive x-values and y-values.
+    Powfit "powfit"("xs: List", "ys: List"), |_, _| Ok(Model::Power);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::numerical::test_util::{eval, eval_str};
+    use super::*;
+
+    #[test]
+    fn polynomial() {
+        assert_eq!(eval_str("linreg([1, 2, 3], [3, 5, 7])"), "[[2, 1], 1]");
+        assert_eq!(eval_str("linreg([1, 2, 3], [3, 5, 7]); fit(10)"), "21");
+        assert_eq!(eval_str("linreg([0, 1, 2, 3], [0, 1, 1, 2])"), "[[3 / 5, 1 / 10], 9 / 10]");
+        assert_eq!(eval_str("polyfit([-5, -4, -3, -2, -1], [5, 4, 1, 1 / 2, 4], 2)[0]"), "[23 / 28, 613 / 140, 7]");
+        assert_eq!(
+            eval_str("polyfit([-5, -4, -3, -2, -1], [5, 4, 1, 0.5, 4], 2)[0] ~== [23 / 28, 613 / 140, 7]"),
+            "[true, true, true]",
+        );
+        assert_eq!(eval_str("polyfit([0, 1, 2], [1, 2, 5], 2, g); g(3)"), "10");
+        assert_eq!(eval_str("polyfit([1, 2], [4, 4], 0)"), "[[4], 1]");
+    }
+
+    #[test]
+    fn linearized() {
+        assert_eq!(eval_str("expfit([0, 1, 2], [2, 2e, 2e^2])[0] ~== [2, 1]"), "[true, true]");
+        assert_eq!(eval_str("logfit([1, e, e^2], [1, 3, 5])[0] ~== [1, 2]"), "[true, true]");
+        assert!(eval("powfit([1, 2, 4], [3, 12, 48], h); h(3) ~== 27").unwrap().is_truthy());
+        assert!(eval("expfit([0, 1, 2], [1, 2, 5])[1] < 1").unwrap().is_truthy());
+    }
+
+    #[test]
+    fn expr() {
+        let fit = Fit::new(
+            Model::Polynomial(2),
+            vec![Value::from(0.0), Value::from(1.0), Value::from(2.0)],
+            vec![Value::from(1.0), Value::from(0.0), Value::from(-1.0)],
+        ).unwrap();
+        assert_eq!(fit.expr().to_string(), "-x+1");
+    }
+
+    #[test]
+    fn errors() {
+        assert!(eval("linreg([1, 1], [1, 2])").is_err());
+        assert!(eval("linreg([1, 2], [1, 2, 3])").is_err());
+        assert!(eval("linreg([1, 2], [1, 2], 3)").is_err());
+        assert!(eval("polyfit([1, 2], [1, 2], -1)").is_err());
+        assert!(eval("polyfit([1, 2], [1, 2], 2)").is_err());
+        assert!(eval("logfit([0, 1], [1, 2])").is_err());
+        assert!(eval("expfit([0, 1], [1, -2])").is_err());
+    }
+}
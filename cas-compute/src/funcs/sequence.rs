@@ -1,7 +1,7 @@
 //! Functions to determine the specified term of particular sequences.
 
 use cas_attrs::builtin;
-use crate::consts::PHI;
+use crate::consts::phi;
 use crate::primitive::float;
 use rug::{ops::Pow, Float};
 
@@ -21,8 +21,8 @@ impl Fib {
             false
         };
 
-        let a = float(&*PHI).pow(&*n.as_abs());
-        let b = float(1.0 - &*PHI).pow(&*n.as_abs());
+        let a = phi().pow(&*n.as_abs());
+        let b = float(1.0 - &phi()).pow(&*n.as_abs());
         let five_sqrt = float(5.0).sqrt();
         let raw = ((a - b) / five_sqrt).round();
 
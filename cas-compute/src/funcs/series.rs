@@ -0,0 +1,503 @@
+//! Sums and products of sequences.
+//!
+//! The `sum(expr, k, a, b)` and `product(expr, k, a, b)` functions combine the values of `expr` for
+//! every integer `k` from `a` to `b` inclusive. The upper bound can be `inf` to compute an infinite
+//! series. `sum` can also be given a single list, such as `sum([1, 2, 3])`, to add its elements.
+//!
+//! Small finite ranges are evaluated exactly, one term at a time. Otherwise, a closed form is
+//! looked for with [`crate::symbolic::series`]. If there is none, finite ranges are evaluated one
+//! term at a time in floating point, and infinite series are estimated with [`accelerate`].
+
+use crate::primitive::float;
+use rug::{Float, Integer};
+
+#[cfg(feature = "numerical")]
+use {
+    cas_parser::parser::{
+        ast::{call::Call, expr::Expr, literal::Literal},
+        token::op::BinOpKind,
+    },
+    crate::numerical::{
+        builtin::{
+            error::BuiltinError,
+            func_specific::{SeriesError, SeriesErrorKind},
+            Builtin,
+        },
+        ctxt::Ctxt,
+        error::{kind::{MissingArgument, TooManyArguments, TypeMismatch}, Error},
+        eval::{eval_operands, Eval},
+        value::Value,
+    },
+    crate::symbolic::{
+        expr::{is_convertible, Expr as SymExpr, Primary},
+        series::{self, INFINITY},
+    },
+};
+
+/// The maximum number of terms of a finite series that are evaluated exactly before looking for a
+/// closed form.
+const MAX_EXACT_TERMS: u64 = 1 << 16;
+
+/// The maximum number of terms of a finite series that are evaluated one at a time.
+const MAX_TERMS: u64 = 1 << 24;
+
+/// The number of terms in the first partial result used to estimate an infinite series.
+const INITIAL_TERMS: u64 = 16;
+
+/// The number of times the number of terms is doubled when estimating an infinite series.
+const DOUBLINGS: usize = 10;
+
+/// The default relative tolerance used to decide if an infinite series converges.
+pub const DEFAULT_TOLERANCE: f64 = 1e-10;
+
+/// The operation used to combine the terms of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeriesKind {
+    /// The terms are added together.
+    Sum,
+
+    /// The terms are multiplied together.
+    Product,
+}
+
+impl SeriesKind {
+    /// Returns the result of combining no terms.
+    fn identity(self) -> Float {
+        match self {
+            SeriesKind::Sum => float(0),
+            SeriesKind::Product => float(1),
+        }
+    }
+
+    /// Combines a partial result with the next term.
+    fn combine(self, partial: Float, term: Float) -> Float {
+        match self {
+            SeriesKind::Sum => partial + term,
+            SeriesKind::Product => partial * term,
+        }
+    }
+}
+
+/// An error that can occur while evaluating a series numerically.
+#[derive(Debug)]
+pub enum SeriesEvalError<E> {
+    /// An error occurred while evaluating a term.
+    Eval(E),
+
+    /// The finite range has too many terms to evaluate.
+    TooManyTerms,
+
+    /// The partial results of the infinite series did not approach a limit.
+    NoConvergence,
+}
+
+/// Combines the terms `f(k)` for `k` from `lower` to `upper` inclusive, one at a time.
+pub fn direct<E>(
+    kind: SeriesKind,
+    mut f: impl FnMut(&Integer) -> Result<Float, E>,
+    lower: &Integer,
+    upper: &Integer,
+) -> Result<Float, SeriesEvalError<E>> {
+    if Integer::from(upper - lower) >= MAX_TERMS {
+        return Err(SeriesEvalError::TooManyTerms);
+    }
+
+    let mut partial = kind.identity();
+    let mut k = lower.clone();
+    while k <= *upper {
+        partial = kind.combine(partial, f(&k).map_err(SeriesEvalError::Eval)?);
+        k += 1;
+    }
+    Ok(partial)
+}
+
+/// Estimates the limit of the infinite series with terms `f(k)`, starting at `k = lower`.
+///
+/// The partial results with `16, 32, 64, ...` terms are extrapolated with Richardson
+/// extrapolation, which assumes that the partial result with `n` terms approaches the limit as a
+/// power series in `1 / n`. This holds for most series whose terms are rational functions of `k`,
+/// and for alternating series, since the number of terms is always even. The series is considered
+/// convergent when two successive estimates agree to within the relative tolerance `tol`.
+pub fn accelerate<E>(
+    kind: SeriesKind,
+    mut f: impl FnMut(&Integer) -> Result<Float, E>,
+    lower: &Integer,
+    tol: &Float,
+) -> Result<Float, SeriesEvalError<E>> {
+    let mut partial = kind.identity();
+    let mut k = lower.clone();
+    let mut count = 0;
+    let mut previous_row: Vec<Float> = Vec::new();
+    for j in 0..=DOUBLINGS {
+        while count < INITIAL_TERMS << j {
+            partial = kind.combine(partial, f(&k).map_err(SeriesEvalError::Eval)?);
+            k += 1;
+            count += 1;
+        }
+        if !partial.is_finite() {
+            return Err(SeriesEvalError::NoConvergence);
+        }
+
+        // R(j, m) = R(j, m - 1) + (R(j, m - 1) - R(j - 1, m - 1)) / (2^m - 1)
+        let mut row = vec![partial.clone()];
+        for m in 1..=j {
+            let correction = float(&row[m - 1] - &previous_row[m - 1]) / ((1u64 << m) - 1);
+            row.push(float(&row[m - 1] + correction));
+        }
+
+        if let Some(previous) = previous_row.last() {
+            let estimate = &row[j];
+            let error = float(estimate - previous).abs();
+            let scale = float(estimate.abs_ref()).max(&float(1));
+            if j >= 2 && error <= float(tol * scale) {
+                return Ok(estimate.clone());
+            }
+        }
+        previous_row = row;
+    }
+
+    Err(SeriesEvalError::NoConvergence)
+}
+
+/// Combines the terms `f(k)` for `k` from `lower` to `upper` inclusive, or estimates the infinite
+/// series if `upper` is [`None`].
+fn evaluate<E>(
+    kind: SeriesKind,
+    f: impl FnMut(&Integer) -> Result<Float, E>,
+    lower: &Integer,
+    upper: Option<&Integer>,
+    tol: Option<&Float>,
+) -> Result<Float, SeriesEvalError<E>> {
+    match upper {
+        Some(upper) => direct(kind, f, lower, upper),
+        None => accelerate(kind, f, lower, tol.unwrap_or(&float(DEFAULT_TOLERANCE))),
+    }
+}
+
+/// Computes the sum `Σ_{k=a}^{b} f(k)` numerically.
+#[derive(Debug)]
+pub struct Sum;
+
+impl Sum {
+    /// Sums the terms `f(k)` for `k` from `lower` to `upper` inclusive. If `upper` is [`None`],
+    /// the infinite series is estimated with [`accelerate`] instead.
+    pub fn eval_static<E>(
+        f: impl FnMut(&Integer) -> Result<Float, E>,
+        lower: &Integer,
+        upper: Option<&Integer>,
+        tol: Option<&Float>,
+    ) -> Result<Float, SeriesEvalError<E>> {
+        evaluate(SeriesKind::Sum, f, lower, upper, tol)
+    }
+}
+
+/// Computes the product `Π_{k=a}^{b} f(k)` numerically.
+#[derive(Debug)]
+pub struct Product;
+
+impl Product {
+    /// Multiplies the terms `f(k)` for `k` from `lower` to `upper` inclusive. If `upper` is
+    /// [`None`], the infinite product is estimated with [`accelerate`] instead.
+    pub fn eval_static<E>(
+        f: impl FnMut(&Integer) -> Result<Float, E>,
+        lower: &Integer,
+        upper: Option<&Integer>,
+        tol: Option<&Float>,
+    ) -> Result<Float, SeriesEvalError<E>> {
+        evaluate(SeriesKind::Product, f, lower, upper, tol)
+    }
+}
+
+/// The parsed arguments of a call to `sum` or `product`.
+#[cfg(feature = "numerical")]
+struct SeriesArgs<'a> {
+    /// The call to the function.
+    call: &'a Call,
+
+    /// Whether this is a sum or a product.
+    kind: SeriesKind,
+
+    /// The name of the index variable.
+    var: &'a str,
+
+    /// The lower bound.
+    lower: Integer,
+
+    /// The upper bound, or [`None`] if the series is infinite.
+    upper: Option<Integer>,
+}
+
+#[cfg(feature = "numerical")]
+impl<'a> SeriesArgs<'a> {
+    /// Returns the name and signature of the function.
+    fn name_signature(kind: SeriesKind) -> (&'static str, &'static str) {
+        match kind {
+            SeriesKind::Sum => ("sum", "sum(expr: Value, k: Symbol, a: Integer, b: Integer)"),
+            SeriesKind::Product => ("product", "product(expr: Value, k: Symbol, a: Integer, b: Integer)"),
+        }
+    }
+
+    /// Checks the arguments of the call and evaluates the bounds.
+    fn new(ctxt: &mut Ctxt, call: &'a Call, kind: SeriesKind) -> Result<Self, Error> {
+        let (name, signature) = Self::name_signature(kind);
+        if call.args.len() > 4 {
+            return Err(BuiltinError::TooManyArguments(TooManyArguments {
+                name: name.to_owned(),
+                expected: 4,
+                given: call.args.len(),
+                signature: signature.to_owned(),
+            }).into_error(call));
+        }
+        if call.args.len() < 4 {
+            return Err(BuiltinError::MissingArgument(MissingArgument {
+                name: name.to_owned(),
+                index: call.args.len(),
+                expected: 4,
+                given: call.args.len(),
+                signature: signature.to_owned(),
+            }).into_error(call));
+        }
+
+        let error = |kind| BuiltinError::from(SeriesError::new(name, kind)).into_error(call);
+        let var = match &call.args[1] {
+            Expr::Literal(Literal::Symbol(symbol)) => symbol.name.as_str(),
+            _ => return Err(error(SeriesErrorKind::NotAVariable)),
+        };
+
+        let lower = match call.args[2].eval(ctxt)?.coerce_integer() {
+            Value::Integer(n) => n,
+            _ => return Err(error(SeriesErrorKind::InvalidBound { index: 2 })),
+        };
+        let upper = match call.args[3].eval(ctxt)?.coerce_integer() {
+            Value::Integer(n) => Some(n),
+            Value::Float(n) if n.is_infinite() && n.is_sign_positive() => None,
+            _ => return Err(error(SeriesErrorKind::InvalidBound { index: 3 })),
+        };
+
+        Ok(Self { call, kind, var, lower, upper })
+    }
+
+    /// Creates an error of the given kind.
+    fn error(&self, kind: SeriesErrorKind) -> Error {
+        let (name, _) = Self::name_signature(self.kind);
+        BuiltinError::from(SeriesError::new(name, kind)).into_error(self.call)
+    }
+
+    /// Evaluates the term at the given index. Each term takes a step of the evaluation, even if
+    /// the expression is a constant.
+    fn term(&self, ctxt: &mut Ctxt, k: &Integer) -> Result<Value, Error> {
+        ctxt.step(|| self.call.span())?;
+        ctxt.add_var(self.var, Value::Integer(k.clone()));
+        match self.call.args[0].eval(ctxt)? {
+            value if value.is_complex() => Ok(value),
+            value => Err(self.error(SeriesErrorKind::InvalidTerm { given: value.typename() })),
+        }
+    }
+
+    /// Evaluates the term at the given index as a real number.
+    fn real_term(&self, ctxt: &mut Ctxt, k: &Integer) -> Result<Float, Error> {
+        match self.term(ctxt, k)?.coerce_float() {
+            Value::Float(n) => Ok(n),
+            value => Err(self.error(SeriesErrorKind::InvalidTerm { given: value.typename() })),
+        }
+    }
+
+    /// Evaluates a finite series exactly, one term at a time.
+    fn exact(&self, ctxt: &mut Ctxt, upper: &Integer) -> Result<Value, Error> {
+        let (op, mut result) = match self.kind {
+            SeriesKind::Sum => (BinOpKind::Add, Value::Integer(Integer::from(0))),
+            SeriesKind::Product => (BinOpKind::Mul, Value::Integer(Integer::from(1))),
+        };
+        let mut k = self.lower.clone();
+        while k <= *upper {
+            let term = self.term(ctxt, &k)?;
+            let given = term.typename();
+            result = eval_operands(op, false, result, term)
+                .map_err(|_| self.error(SeriesErrorKind::InvalidTerm { given }))?;
+            k += 1;
+        }
+        Ok(result)
+    }
+
+    /// Looks for a closed form of the series, and evaluates it.
+    fn closed_form(&self, ctxt: &mut Ctxt) -> Option<Value> {
+        if !is_convertible(&self.call.args[0]) {
+            return None;
+        }
+
+        let expr = SymExpr::from(self.call.args[0].clone());
+        let lower = SymExpr::Primary(Primary::Integer(self.lower.clone()));
+        let upper = match &self.upper {
+            Some(upper) => SymExpr::Primary(Primary::Integer(upper.clone())),
+            None => SymExpr::Primary(Primary::Symbol(INFINITY.to_string())),
+        };
+        let result = match self.kind {
+            SeriesKind::Sum => series::sum(&expr, self.var, &lower, &upper),
+            SeriesKind::Product => series::product(&expr, self.var, &lower, &upper),
+        }?;
+
+        match Expr::from(result).eval(ctxt).ok()? {
+            Value::Float(n) if !n.is_finite() => None,
+            Value::Complex(c) if !c.real().is_finite() || !c.imag().is_finite() => None,
+            value => Some(value),
+        }
+    }
+
+    /// Evaluates the series.
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        // the index variable must not leak out of the call
+        let mut inner = ctxt.clone();
+
+        if let Some(upper) = &self.upper {
+            if Integer::from(upper - &self.lower) < MAX_EXACT_TERMS {
+                return self.exact(&mut inner, upper);
+            }
+        }
+
+        if let Some(value) = self.closed_form(ctxt) {
+            return Ok(value);
+        }
+
+        let f = |k: &Integer| self.real_term(&mut inner, k);
+        evaluate(self.kind, f, &self.lower, self.upper.as_ref(), None)
+            .map(Value::Float)
+            .map_err(|err| match err {
+                SeriesEvalError::Eval(err) => err,
+                SeriesEvalError::TooManyTerms => self.error(SeriesErrorKind::TooManyTerms),
+                SeriesEvalError::NoConvergence => self.error(SeriesErrorKind::NoConvergence),
+            })
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Builtin for Sum {
+    fn num_args(&self) -> usize {
+        4
+    }
+
+    fn eval(&self, _: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        // only the sum of a list can be computed from values; the expression and index variable
+        // of a series cannot be passed as values
+        match (args.next().map(Value::coerce_list), args.next()) {
+            (Some(Value::List(values)), None) => {
+                crate::funcs::list::fold("sum", BinOpKind::Add, Value::Integer(Integer::from(0)), values)
+            },
+            (Some(value), None) => Err(BuiltinError::TypeMismatch(TypeMismatch {
+                name: "sum".to_owned(),
+                index: 0,
+                expected: "List",
+                given: value.typename(),
+                signature: "sum(l: List)".to_owned(),
+            })),
+            _ => Err(SeriesError::new("sum", SeriesErrorKind::NotAVariable).into()),
+        }
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        if let [list] = &call.args[..] {
+            return Some(list.eval(ctxt).and_then(|value| {
+                ctxt.budget.check_coerced_list(&value, || list.span())?;
+                self.eval(ctxt, &mut std::iter::once(value)).map_err(|err| err.into_error(call))
+            }));
+        }
+        Some(SeriesArgs::new(ctxt, call, SeriesKind::Sum).and_then(|args| args.eval(ctxt)))
+    }
+}
+
+#[cfg(feature = "numerical")]
+impl Builtin for Product {
+    fn num_args(&self) -> usize {
+        4
+    }
+
+    fn eval(&self, _: &Ctxt, _: &mut dyn Iterator<Item = Value>) -> Result<Value, BuiltinError> {
+        // the expression and index variable cannot be passed as values
+        Err(SeriesError::new("product", SeriesErrorKind::NotAVariable).into())
+    }
+
+    fn takes_call(&self) -> bool {
+        true
+    }
+
+    fn eval_call(&self, ctxt: &mut Ctxt, call: &Call) -> Option<Result<Value, Error>> {
+        Some(SeriesArgs::new(ctxt, call, SeriesKind::Product).and_then(|args| args.eval(ctxt)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that two floating-point numbers are close.
+    fn assert_close(a: &Float, b: f64) {
+        assert!((a.to_f64() - b).abs() < 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn direct_sum() {
+        let result = Sum::eval_static(
+            |k| Ok::<_, ()>(float(k) * float(k)),
+            &Integer::from(1),
+            Some(&Integer::from(100)),
+            None,
+        ).unwrap();
+        assert_close(&result, 338_350.0);
+    }
+
+    #[test]
+    fn accelerate_inverse_cubes() {
+        // ζ(3)
+        let result = Sum::eval_static(
+            |k| Ok::<_, ()>(float(1) / float(k).square() / float(k)),
+            &Integer::from(1),
+            None,
+            None,
+        ).unwrap();
+        assert_close(&result, 1.202_056_903_159_594);
+    }
+
+    #[test]
+    fn accelerate_alternating() {
+        // Σ (-1)^k / (k + 1) = ln(2)
+        let result = Sum::eval_static(
+            |k| {
+                let sign = if k.is_even() { 1 } else { -1 };
+                Ok::<_, ()>(float(sign) / (float(k) + 1))
+            },
+            &Integer::from(0),
+            None,
+            None,
+        ).unwrap();
+        assert_close(&result, std::f64::consts::LN_2);
+    }
+
+    #[test]
+    fn accelerate_wallis() {
+        // Π 4k^2 / (4k^2 - 1) = π / 2
+        let result = Product::eval_static(
+            |k| {
+                let square = float(k).square() * 4;
+                Ok::<_, ()>(float(&square) / (square - 1))
+            },
+            &Integer::from(1),
+            None,
+            None,
+        ).unwrap();
+        assert_close(&result, std::f64::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn harmonic_diverges() {
+        let result = Sum::eval_static(
+            |k| Ok::<_, ()>(float(1) / float(k)),
+            &Integer::from(1),
+            None,
+            None,
+        );
+        assert!(matches!(result, Err(SeriesEvalError::NoConvergence)));
+    }
+}
@@ -0,0 +1,399 @@
+//! Descriptive statistics of lists of real numbers.
+//!
+//! Statistics are computed exactly when the data are integers or rational numbers, such as
+//! `mean([1, 2, 4])`, which is `7 / 3`. Statistics involving a square root, such as `stdev`, are
+//! computed with floating-point numbers.
+//!
+//! Functions for a sample of a population, such as `var` and `stdev`, divide by one less than the
+//! number of elements (Bessel's correction). The `p` variants, such as `pvar` and `pstdev`, treat
+//! the list as the entire population.
+
+use cas_attrs::builtin;
+use cas_parser::parser::token::op::BinOpKind;
+use crate::funcs::list::compare;
+use crate::numerical::{
+    builtin::{
+        error::BuiltinError,
+        func_specific::{StatisticsError, StatisticsErrorKind},
+    },
+    eval::eval_operands,
+    value::Value,
+};
+use crate::primitive::float;
+use rug::{Float, Integer};
+use std::cmp::Ordering;
+
+/// Creates an error for the function with the given name.
+fn error(name: &'static str, kind: StatisticsErrorKind) -> BuiltinError {
+    StatisticsError::new(name, kind).into()
+}
+
+/// Returns the elements of argument `index` of a function, if they are all real numbers and there
+/// are at least `required` of them.
+pub(crate) fn real_data(
+    l: Vec<Value>,
+    index: usize,
+    required: usize,
+) -> Result<Vec<Value>, StatisticsErrorKind> {
+    if let Some(value) = l.iter().find(|value| !value.is_real()) {
+        return Err(StatisticsErrorKind::InvalidElement { index, given: value.typename() });
+    }
+    if l.len() < required {
+        return Err(StatisticsErrorKind::TooFewElements { index, required, given: l.len() });
+    }
+    Ok(l.into_iter().map(Value::coerce_number).collect())
+}
+
+/// Returns the elements of argument `index` of the function, if they are all real numbers and
+/// there are at least `required` of them.
+fn data(
+    name: &'static str,
+    l: Vec<Value>,
+    index: usize,
+    required: usize,
+) -> Result<Vec<Value>, BuiltinError> {
+    real_data(l, index, required).map_err(|kind| error(name, kind))
+}
+
+/// Returns the elements of two lists that are paired up element by element.
+fn pair(
+    name: &'static str,
+    x: Vec<Value>,
+    y: Vec<Value>,
+    required: usize,
+) -> Result<(Vec<Value>, Vec<Value>), BuiltinError> {
+    let x = data(name, x, 0, required)?;
+    let y = data(name, y, 1, required)?;
+    if x.len() != y.len() {
+        return Err(error(name, StatisticsErrorKind::LengthMismatch {
+            index: 1,
+            expected: x.len(),
+            given: y.len(),
+        }));
+    }
+    Ok((x, y))
+}
+
+/// Returns an error if every element of argument `index` of the function is the same.
+fn check_varies(name: &'static str, data: &[Value], index: usize) -> Result<(), BuiltinError> {
+    if data.iter().all(|value| compare(value, &data[0]) == Ordering::Equal) {
+        Err(error(name, StatisticsErrorKind::ConstantData { index }))
+    } else {
+        Ok(())
+    }
+}
+
+/// Applies a binary operator to two real numbers.
+pub(crate) fn apply(op: BinOpKind, left: &Value, right: &Value) -> Value {
+    eval_operands(op, false, left.clone(), right.clone())
+        .expect("arithmetic on real numbers cannot fail")
+}
+
+/// Returns the square root of a non-negative real number.
+fn sqrt(value: Value) -> Float {
+    match value.coerce_float() {
+        Value::Float(n) => n.sqrt(),
+        _ => unreachable!("only real numbers are passed to `sqrt`"),
+    }
+}
+
+/// Returns the sum of the given numbers.
+pub(crate) fn sum<'a>(values: impl IntoIterator<Item = &'a Value>) -> Value {
+    values.into_iter()
+        .fold(Value::Integer(Integer::new()), |sum, value| apply(BinOpKind::Add, &sum, value))
+}
+
+/// Returns the arithmetic mean of a non-empty list of numbers.
+pub(crate) fn mean(data: &[Value]) -> Value {
+    apply(BinOpKind::Div, &sum(data), &Value::Integer(data.len().into()))
+}
+
+/// Returns the deviations of the numbers from their mean.
+fn deviations(data: &[Value]) -> Vec<Value> {
+    let mean = mean(data);
+    data.iter().map(|value| apply(BinOpKind::Sub, value, &mean)).collect()
+}
+
+/// Returns the sum of the products of corresponding deviations of two lists of numbers from their
+/// means. If the lists are the same, this is the sum of the squared deviations.
+fn sum_of_products(x: &[Value], y: &[Value]) -> Value {
+    let products = deviations(x)
+        .iter()
+        .zip(&deviations(y))
+        .map(|(dx, dy)| apply(BinOpKind::Mul, dx, dy))
+        .collect::<Vec<_>>();
+    sum(&products)
+}
+
+/// Returns the variance of a list of numbers, as a sample of a population or as the entire
+/// population.
+fn variance(name: &'static str, l: Vec<Value>, population: bool) -> Result<Value, BuiltinError> {
+    let data = data(name, l, 0, if population { 1 } else { 2 })?;
+    let n = if population { data.len() } else { data.len() - 1 };
+    Ok(apply(BinOpKind::Div, &sum_of_products(&data, &data), &Value::Integer(n.into())))
+}
+
+/// Returns the quantile of a sorted, non-empty list of numbers for the probability `p`, which
+/// must be between 0 and 1.
+///
+/// The quantile is interpolated linearly between the two closest elements, the same method used
+/// by default in R and NumPy. If the interpolation weight is a simple fraction, such as `1 / 4`,
+/// the interpolation is exact.
+fn quantile(sorted: &[Value], p: &Float) -> Value {
+    let position = float(sorted.len() - 1) * p;
+    let lower = position.clone().floor();
+    let weight = position - &lower;
+    let i = lower.to_integer().and_then(|i| i.to_usize()).unwrap();
+    if weight.is_zero() {
+        return sorted[i].clone();
+    }
+
+    let weight = weight.to_rational()
+        .filter(|weight| *weight.denom() <= 256)
+        .map(Value::from)
+        .unwrap_or(Value::Float(weight));
+    let difference = apply(BinOpKind::Sub, &sorted[i + 1], &sorted[i]);
+    apply(BinOpKind::Add, &sorted[i], &apply(BinOpKind::Mul, &weight, &difference))
+}
+
+/// Returns the elements of a list in ascending order.
+fn sorted(mut data: Vec<Value>) -> Vec<Value> {
+    data.sort_by(compare);
+    data
+}
+
+/// Returns the arithmetic mean of a list of real numbers.
+#[derive(Debug)]
+pub struct Mean;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Mean {
+    pub fn eval_static(l: Vec<Value>) -> Result<Value, BuiltinError> {
+        Ok(mean(&data("mean", l, 0, 1)?))
+    }
+}
+
+/// Returns the median of a list of real numbers. If the list has an even number of elements, this
+/// is the mean of the two middle elements.
+#[derive(Debug)]
+pub struct Median;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Median {
+    pub fn eval_static(l: Vec<Value>) -> Result<Value, BuiltinError> {
+        let data = sorted(data("median", l, 0, 1)?);
+        Ok(quantile(&data, &float(0.5)))
+    }
+}
+
+/// Returns the most common elements of a list of real numbers, in ascending order. The list has
+/// more than one element if several elements are equally common.
+#[derive(Debug)]
+pub struct Mode;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Mode {
+    pub fn eval_static(l: Vec<Value>) -> Result<Vec<Value>, BuiltinError> {
+        let data = sorted(data("mode", l, 0, 1)?);
+
+        // equal elements are adjacent in the sorted list
+        let mut runs: Vec<(Value, usize)> = Vec::new();
+        for value in data {
+            match runs.last_mut() {
+                Some((last, count)) if compare(last, &value) == Ordering::Equal => *count += 1,
+                _ => runs.push((value, 1)),
+            }
+        }
+
+        let max = runs.iter().map(|(_, count)| *count).max().unwrap();
+        Ok(runs.into_iter()
+            .filter(|(_, count)| *count == max)
+            .map(|(value, _)| value)
+            .collect())
+    }
+}
+
+/// Returns the sample variance of a list of real numbers.
+#[derive(Debug)]
+pub struct Var;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Var {
+    pub fn eval_static(l: Vec<Value>) -> Result<Value, BuiltinError> {
+        variance("var", l, false)
+    }
+}
+
+/// Returns the population variance of a list of real numbers.
+#[derive(Debug)]
+pub struct Pvar;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Pvar {
+    pub fn eval_static(l: Vec<Value>) -> Result<Value, BuiltinError> {
+        variance("pvar", l, true)
+    }
+}
+
+/// Returns the sample standard deviation of a list of real numbers.
+#[derive(Debug)]
+pub struct Stdev;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Stdev {
+    pub fn eval_static(l: Vec<Value>) -> Result<Float, BuiltinError> {
+        variance("stdev", l, false).map(sqrt)
+    }
+}
+
+/// Returns the population standard deviation of a list of real numbers.
+#[derive(Debug)]
+pub struct Pstdev;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Pstdev {
+    pub fn eval_static(l: Vec<Value>) -> Result<Float, BuiltinError> {
+        variance("pstdev", l, true).map(sqrt)
+    }
+}
+
+/// Returns the quantile of a list of real numbers for the probability `p`, such as `0.25` for the
+/// first quartile. Values between elements of the list are interpolated linearly.
+#[derive(Debug)]
+pub struct Quantile;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Quantile {
+    pub fn eval_static(l: Vec<Value>, p: Float) -> Result<Value, BuiltinError> {
+        let data = sorted(data("quantile", l, 0, 1)?);
+        if !(0..=1).contains(&p) {
+            return Err(error("quantile", StatisticsErrorKind::InvalidProbability));
+        }
+        Ok(quantile(&data, &p))
+    }
+}
+
+/// Returns the interquartile range of a list of real numbers, which is the difference between its
+/// third and first quartiles.
+#[derive(Debug)]
+pub struct Iqr;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Iqr {
+    pub fn eval_static(l: Vec<Value>) -> Result<Value, BuiltinError> {
+        let data = sorted(data("iqr", l, 0, 1)?);
+        Ok(apply(
+            BinOpKind::Sub,
+            &quantile(&data, &float(0.75)),
+            &quantile(&data, &float(0.25)),
+        ))
+    }
+}
+
+/// Returns the sample covariance of two lists of real numbers with the same length.
+#[derive(Debug)]
+pub struct Cov;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Cov {
+    pub fn eval_static(x: Vec<Value>, y: Vec<Value>) -> Result<Value, BuiltinError> {
+        let (x, y) = pair("cov", x, y, 2)?;
+        Ok(apply(BinOpKind::Div, &sum_of_products(&x, &y), &Value::Integer((x.len() - 1).into())))
+    }
+}
+
+/// Returns the Pearson correlation coefficient of two lists of real numbers with the same length.
+#[derive(Debug)]
+pub struct Corr;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Corr {
+    pub fn eval_static(x: Vec<Value>, y: Vec<Value>) -> Result<Float, BuiltinError> {
+        let (x, y) = pair("corr", x, y, 2)?;
+        check_varies("corr", &x, 0)?;
+        check_varies("corr", &y, 1)?;
+        let denominator = sqrt(apply(BinOpKind::Mul, &sum_of_products(&x, &x), &sum_of_products(&y, &y)));
+        match sum_of_products(&x, &y).coerce_float() {
+            Value::Float(numerator) => Ok(numerator / denominator),
+            _ => unreachable!("only real numbers are passed to `coerce_float`"),
+        }
+    }
+}
+
+/// Returns the z-score of each element of a list of real numbers, which is its distance from the
+/// mean of the list in sample standard deviations.
+#[derive(Debug)]
+pub struct Zscore;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Zscore {
+    pub fn eval_static(l: Vec<Value>) -> Result<Vec<Value>, BuiltinError> {
+        let data = data("zscore", l, 0, 2)?;
+        check_varies("zscore", &data, 0)?;
+        let stdev = Value::Float(sqrt(variance("zscore", data.clone(), false)?));
+        Ok(deviations(&data)
+            .iter()
+            .map(|deviation| apply(BinOpKind::Div, deviation, &stdev))
+            .collect())
+    }
+}
+
+/// Returns a summary of a list of real numbers, as the list `[min, q1, median, mean, q3, max]`,
+/// where `q1` and `q3` are the first and third quartiles.
+#[derive(Debug)]
+pub struct Summary;
+
+#[cfg_attr(feature = "numerical", builtin)]
+impl Summary {
+    pub fn eval_static(l: Vec<Value>) -> Result<Vec<Value>, BuiltinError> {
+        let data = sorted(data("summary", l, 0, 1)?);
+        Ok(vec![
+            data[0].clone(),
+            quantile(&data, &float(0.25)),
+            quantile(&data, &float(0.5)),
+            mean(&data),
+            quantile(&data, &float(0.75)),
+            data[data.len() - 1].clone(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::numerical::test_util::{eval, eval_str};
+
+    #[test]
+    fn exact() {
+        assert_eq!(eval_str("mean([1, 2, 4])"), "7 / 3");
+        assert_eq!(eval_str("median([3, 1, 2])"), "2");
+        assert_eq!(eval_str("median([4, 1, 2, 3])"), "5 / 2");
+        assert_eq!(eval_str("mode([1, 2, 2, 3, 3])"), "[2, 3]");
+        assert_eq!(eval_str("var([2, 4, 4, 4, 5, 5, 7, 9])"), "32 / 7");
+        assert_eq!(eval_str("pvar([2, 4, 4, 4, 5, 5, 7, 9])"), "4");
+        assert_eq!(eval_str("pstdev([2, 4, 4, 4, 5, 5, 7, 9])"), "2");
+        assert_eq!(eval_str("quantile([1, 2, 3, 4], 0.25)"), "7 / 4");
+        assert_eq!(eval_str("quantile([1, 2, 3, 4], [0, 1])"), "[1, 4]");
+        assert_eq!(eval_str("iqr([1, 2, 3, 4, 5])"), "2");
+        assert_eq!(eval_str("cov([1, 2, 3], [2, 4, 7])"), "5 / 2");
+        assert_eq!(eval_str("summary([5, 1, 4, 2, 3])"), "[1, 2, 3, 3, 4, 5]");
+    }
+
+    #[test]
+    fn inexact() {
+        assert!(eval("corr([1, 2, 3], [2, 4, 6]) == 1").unwrap().is_truthy());
+        assert!(eval("corr([1, 2, 3], [3, 2, 1]) == -1").unwrap().is_truthy());
+        assert!(eval("stdev([1, 2, 3, 4]) ~== sqrt(5 / 3)").unwrap().is_truthy());
+        assert_eq!(eval_str("zscore([1, 2, 3]) == [-1, 0, 1]"), "[true, true, true]");
+        assert!(eval("mean([0.5, 1.5]) == 1").unwrap().is_truthy());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(eval("mean([])").is_err());
+        assert!(eval("var([1])").is_err());
+        assert!(eval("median([1, i])").is_err());
+        assert!(eval("quantile([1, 2], 1.5)").is_err());
+        assert!(eval("cov([1, 2], [1, 2, 3])").is_err());
+        assert!(eval("corr([1, 1], [1, 2])").is_err());
+    }
+}
@@ -0,0 +1,491 @@
+//! Interval arithmetic with guaranteed error bounds.
+//!
+//! An [`Interval`] is a closed range of real numbers `[lo, hi]` that is known to contain some
+//! exact result. Every operation on intervals rounds its lower bound down and its upper bound up
+//! (outward rounding), so the resulting interval always contains the exact result of the operation
+//! applied to any numbers in the input intervals, no matter how much rounding error occurs along
+//! the way. The width of the final interval bounds the error of the computation.
+//!
+//! ```
+//! use cas_compute::interval::Interval;
+//!
+//! // e^pi - pi is famously close to 20, but not equal to it
+//! let result = Interval::e().pow(&Interval::pi()).sub(&Interval::pi());
+//! assert!(!result.contains(&rug::Float::with_val(64, 20)));
+//! assert!(result.lo() > &19.999 && result.hi() < &20.0);
+//! ```
+//!
+//! Bounds are computed with the current precision of the thread (see
+//! [`precision`](crate::primitive::precision)). Operations that are undefined for every number in
+//! an interval, such as the square root of a negative interval, produce an empty interval, whose
+//! bounds are both NaN.
+
+use crate::primitive::{float, precision};
+use rug::{
+    float::{Constant, Round},
+    ops::{AssignRound, Pow},
+    Float,
+    Integer,
+    Rational,
+};
+use std::cmp::Ordering;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Computes the given value, rounded down.
+fn down<T>(src: T) -> Float
+where
+    Float: AssignRound<T, Round = Round, Ordering = Ordering>,
+{
+    Float::with_val_round(precision(), src, Round::Down).0
+}
+
+/// Computes the given value, rounded up.
+fn up<T>(src: T) -> Float
+where
+    Float: AssignRound<T, Round = Round, Ordering = Ordering>,
+{
+    Float::with_val_round(precision(), src, Round::Up).0
+}
+
+/// Returns the smallest of the given numbers. NaNs, which arise from products such as `0 * inf`,
+/// are treated as zero.
+fn min(values: impl IntoIterator<Item = Float>) -> Float {
+    values.into_iter()
+        .map(|n| if n.is_nan() { float(0) } else { n })
+        .reduce(|a, b| if b < a { b } else { a })
+        .unwrap()
+}
+
+/// Returns the largest of the given numbers. NaNs are treated as zero.
+fn max(values: impl IntoIterator<Item = Float>) -> Float {
+    values.into_iter()
+        .map(|n| if n.is_nan() { float(0) } else { n })
+        .reduce(|a, b| if b > a { b } else { a })
+        .unwrap()
+}
+
+/// A closed interval of real numbers, `[lo, hi]`. See the [module-level documentation](self) for
+/// more information.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Interval {
+    /// The lower bound of the interval.
+    lo: Float,
+
+    /// The upper bound of the interval.
+    hi: Float,
+}
+
+impl Interval {
+    /// Creates an interval with the given bounds, which can be given in any order.
+    pub fn new(a: Float, b: Float) -> Self {
+        if a.is_nan() || b.is_nan() {
+            Self::empty()
+        } else if a <= b {
+            Self { lo: a, hi: b }
+        } else {
+            Self { lo: b, hi: a }
+        }
+    }
+
+    /// Creates an interval containing exactly the given number.
+    pub fn point(n: Float) -> Self {
+        Self::new(n.clone(), n)
+    }
+
+    /// Creates the smallest interval containing the given integer.
+    pub fn from_integer(n: &Integer) -> Self {
+        Self::new(down(n), up(n))
+    }
+
+    /// Creates the smallest interval containing the given rational number.
+    pub fn from_rational(n: &Rational) -> Self {
+        Self::new(down(n), up(n))
+    }
+
+    /// Creates the smallest interval containing the number written in decimal in the given
+    /// string, such as `0.1`, which cannot be represented exactly as a [`Float`].
+    ///
+    /// Returns [`None`] if the string is not a valid number.
+    pub fn parse(s: &str) -> Option<Self> {
+        let lo = down(Float::parse(s).ok()?);
+        let hi = up(Float::parse(s).ok()?);
+        Some(Self::new(lo, hi))
+    }
+
+    /// Returns the empty interval, which is the result of operations that are undefined for every
+    /// number in their input intervals.
+    pub fn empty() -> Self {
+        Self { lo: float(f64::NAN), hi: float(f64::NAN) }
+    }
+
+    /// Returns the interval containing every real number, `[-inf, inf]`.
+    pub fn entire() -> Self {
+        Self { lo: float(f64::NEG_INFINITY), hi: float(f64::INFINITY) }
+    }
+
+    /// Returns the smallest interval containing `pi`.
+    pub fn pi() -> Self {
+        Self::new(down(Constant::Pi), up(Constant::Pi))
+    }
+
+    /// Returns the smallest interval containing Euler's number.
+    pub fn e() -> Self {
+        Self::point(float(1)).exp()
+    }
+
+    /// Returns the lower bound of the interval.
+    pub fn lo(&self) -> &Float {
+        &self.lo
+    }
+
+    /// Returns the upper bound of the interval.
+    pub fn hi(&self) -> &Float {
+        &self.hi
+    }
+
+    /// Returns the midpoint of the interval, rounded to the nearest representable number.
+    pub fn mid(&self) -> Float {
+        if self.lo.is_infinite() || self.hi.is_infinite() {
+            float(&self.lo + &self.hi)
+        } else {
+            float(&self.lo + &self.hi) / 2u8
+        }
+    }
+
+    /// Returns an upper bound on the width of the interval, `hi - lo`.
+    pub fn width(&self) -> Float {
+        up(&self.hi - &self.lo)
+    }
+
+    /// Returns true if the interval is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lo.is_nan()
+    }
+
+    /// Returns true if the interval contains the given number.
+    pub fn contains(&self, n: &Float) -> bool {
+        self.lo <= *n && *n <= self.hi
+    }
+
+    /// Returns true if the interval contains zero.
+    pub fn contains_zero(&self) -> bool {
+        self.contains(&float(0))
+    }
+
+    /// Returns true if the two intervals have at least one number in common.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.lo <= other.hi && other.lo <= self.hi
+    }
+
+    /// Compares the two intervals. Returns [`None`] if the intervals overlap, since the numbers
+    /// they enclose could then compare either way. Two intervals containing the same single number
+    /// are equal.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        if self.hi < other.lo {
+            Some(Ordering::Less)
+        } else if self.lo > other.hi {
+            Some(Ordering::Greater)
+        } else if self.lo == self.hi && self == other {
+            Some(Ordering::Equal)
+        } else {
+            None
+        }
+    }
+
+    /// Applies an increasing function to the interval, given functions that compute it with
+    /// downward and upward rounding.
+    fn increasing(&self, lo: impl FnOnce(&Float) -> Float, hi: impl FnOnce(&Float) -> Float) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+        Self::new(lo(&self.lo), hi(&self.hi))
+    }
+
+    /// Returns the smallest interval containing both intervals.
+    pub fn hull(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            other.clone()
+        } else if other.is_empty() {
+            self.clone()
+        } else {
+            Self::new(min([self.lo.clone(), other.lo.clone()]), max([self.hi.clone(), other.hi.clone()]))
+        }
+    }
+
+    /// Returns the interval with both bounds negated.
+    pub fn neg(&self) -> Self {
+        Self::new(float(-&self.hi), float(-&self.lo))
+    }
+
+    /// Adds two intervals.
+    pub fn add(&self, other: &Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return Self::empty();
+        }
+        Self::new(down(&self.lo + &other.lo), up(&self.hi + &other.hi))
+    }
+
+    /// Subtracts two intervals.
+    pub fn sub(&self, other: &Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return Self::empty();
+        }
+        Self::new(down(&self.lo - &other.hi), up(&self.hi - &other.lo))
+    }
+
+    /// Multiplies two intervals.
+    pub fn mul(&self, other: &Self) -> Self {
+        if self.is_empty() || other.is_empty() {
+            return Self::empty();
+        }
+        let pairs = [
+            (&self.lo, &other.lo),
+            (&self.lo, &other.hi),
+            (&self.hi, &other.lo),
+            (&self.hi, &other.hi),
+        ];
+        Self::new(
+            min(pairs.iter().map(|(a, b)| down(*a * *b))),
+            max(pairs.iter().map(|(a, b)| up(*a * *b))),
+        )
+    }
+
+    /// Returns the reciprocal of the interval. If the interval contains zero, the result is the
+    /// entire real line.
+    pub fn recip(&self) -> Self {
+        if self.is_empty() || self.lo.is_zero() && self.hi.is_zero() {
+            Self::empty()
+        } else if self.contains_zero() {
+            Self::entire()
+        } else {
+            Self::new(down(1 / &self.hi), up(1 / &self.lo))
+        }
+    }
+
+    /// Divides two intervals. If the divisor contains zero, the result is the entire real line.
+    pub fn div(&self, other: &Self) -> Self {
+        self.mul(&other.recip())
+    }
+
+    /// Raises the interval to an integer power.
+    pub fn pow_int(&self, n: i32) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+
+        match n.cmp(&0) {
+            Ordering::Less => self.pow_int(-n).recip(),
+            Ordering::Equal => Self::point(float(1)),
+            Ordering::Greater if n % 2 == 1 || self.lo >= 0 => {
+                Self::new(down((&self.lo).pow(n)), up((&self.hi).pow(n)))
+            },
+            Ordering::Greater if self.hi <= 0 => {
+                Self::new(down((&self.hi).pow(n)), up((&self.lo).pow(n)))
+            },
+            Ordering::Greater => {
+                // even power of an interval containing zero
+                let magnitude = max([float(-&self.lo), self.hi.clone()]);
+                Self::new(float(0), up(magnitude.pow(n)))
+            },
+        }
+    }
+
+    /// Raises the interval to the power of another interval. If the exponent is a single integer,
+    /// this is the same as [`Interval::pow_int`]; otherwise, only the non-negative part of the
+    /// base is considered.
+    pub fn pow(&self, exponent: &Self) -> Self {
+        if exponent.lo == exponent.hi && exponent.lo.is_integer() {
+            if let Some(n) = exponent.lo.to_i32_saturating().filter(|n| n.unsigned_abs() < 1 << 16) {
+                return self.pow_int(n);
+            }
+        }
+        exponent.mul(&self.ln()).exp()
+    }
+
+    /// Returns the absolute value of the interval.
+    pub fn abs(&self) -> Self {
+        if self.is_empty() || self.lo >= 0 {
+            self.clone()
+        } else if self.hi <= 0 {
+            self.neg()
+        } else {
+            Self::new(float(0), max([float(-&self.lo), self.hi.clone()]))
+        }
+    }
+
+    /// Returns the square root of the non-negative part of the interval.
+    pub fn sqrt(&self) -> Self {
+        if self.is_empty() || self.hi < 0 {
+            return Self::empty();
+        }
+        let lo = max([self.lo.clone(), float(0)]);
+        Self::new(down(lo.sqrt_ref()), up(self.hi.sqrt_ref()))
+    }
+
+    /// Returns `e` raised to the power of the interval.
+    pub fn exp(&self) -> Self {
+        self.increasing(|lo| down(lo.exp_ref()), |hi| up(hi.exp_ref()))
+    }
+
+    /// Returns the natural logarithm of the non-negative part of the interval.
+    pub fn ln(&self) -> Self {
+        if self.is_empty() || self.hi < 0 {
+            return Self::empty();
+        }
+        let lo = max([self.lo.clone(), float(0)]);
+        Self::new(down(lo.ln_ref()), up(self.hi.ln_ref()))
+    }
+
+    /// Returns the inverse tangent of the interval.
+    pub fn atan(&self) -> Self {
+        self.increasing(|lo| down(lo.atan_ref()), |hi| up(hi.atan_ref()))
+    }
+
+    /// Returns true if the interval might contain a number of the form `offset * pi + k * period *
+    /// pi` for some integer `k`. This can return true when it does not, but never the other way
+    /// around.
+    fn may_contain_periodic(&self, offset: &Rational, period: u8) -> bool {
+        // solve for the interval of `k`; it contains an integer if its bounds are on either side
+        // of one
+        let k = self.sub(&Self::pi().mul(&Self::from_rational(offset)))
+            .div(&Self::pi().mul(&Self::from_integer(&Integer::from(period))));
+        k.lo.clone().ceil() <= k.hi.clone().floor()
+    }
+
+    /// Returns the sine of the interval.
+    pub fn sin(&self) -> Self {
+        self.sin_shifted(Rational::new())
+    }
+
+    /// Returns the cosine of the interval.
+    pub fn cos(&self) -> Self {
+        // cos(x) = sin(x + pi/2), so cos has the extrema of sin shifted left by pi/2
+        self.sin_shifted(Rational::from((1, 2)))
+    }
+
+    /// Returns the sine of the interval, with extrema shifted left by `shift * pi`, where `shift`
+    /// is `0` for sine and `1/2` for cosine. The endpoints are evaluated with `sin` or `cos`
+    /// directly, so that the shift does not add rounding error.
+    fn sin_shifted(&self, shift: Rational) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+        if !self.lo.is_finite() || !self.hi.is_finite() {
+            return Self::new(float(-1), float(1));
+        }
+
+        let (lo, hi) = if shift == 0 {
+            (
+                min([down(self.lo.sin_ref()), down(self.hi.sin_ref())]),
+                max([up(self.lo.sin_ref()), up(self.hi.sin_ref())]),
+            )
+        } else {
+            (
+                min([down(self.lo.cos_ref()), down(self.hi.cos_ref())]),
+                max([up(self.lo.cos_ref()), up(self.hi.cos_ref())]),
+            )
+        };
+
+        // the maxima of sin are at pi/2 + 2k pi, and the minima at -pi/2 + 2k pi
+        let lo = if self.may_contain_periodic(&(Rational::from((-1, 2)) - &shift), 2) {
+            float(-1)
+        } else {
+            max([lo, float(-1)])
+        };
+        let hi = if self.may_contain_periodic(&(Rational::from((1, 2)) - &shift), 2) {
+            float(1)
+        } else {
+            min([hi, float(1)])
+        };
+        Self::new(lo, hi)
+    }
+
+    /// Returns the tangent of the interval. If the interval contains a pole of the tangent, the
+    /// result is the entire real line.
+    pub fn tan(&self) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
+        if !self.lo.is_finite() || !self.hi.is_finite()
+            || self.may_contain_periodic(&Rational::from((1, 2)), 1) {
+            return Self::entire();
+        }
+        Self::new(down(self.lo.tan_ref()), up(self.hi.tan_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitive::{float_from_str, with_precision};
+
+    /// Creates an interval from two `f64` bounds.
+    fn interval(lo: f64, hi: f64) -> Interval {
+        Interval::new(float(lo), float(hi))
+    }
+
+    #[test]
+    fn outward_rounding() {
+        let tenth = Interval::parse("0.1").unwrap();
+        assert!(tenth.lo() < tenth.hi());
+        assert!(*tenth.lo() < Rational::from((1, 10)) && *tenth.hi() > Rational::from((1, 10)));
+
+        // the sum of ten enclosures of 0.1 still contains 1
+        let sum = (0..10).fold(Interval::point(float(0)), |sum, _| sum.add(&tenth));
+        assert!(sum.contains(&float(1)));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = interval(1.0, 2.0);
+        let b = interval(-3.0, 4.0);
+        assert_eq!(a.add(&b), interval(-2.0, 6.0));
+        assert_eq!(a.sub(&b), interval(-3.0, 5.0));
+        assert_eq!(a.mul(&b), interval(-6.0, 8.0));
+        assert_eq!(a.div(&interval(2.0, 4.0)), interval(0.25, 1.0));
+        assert_eq!(a.div(&b), Interval::entire());
+        assert_eq!(b.pow_int(2), interval(0.0, 16.0));
+        assert_eq!(b.pow_int(3), interval(-27.0, 64.0));
+        assert_eq!(b.abs(), interval(0.0, 4.0));
+    }
+
+    #[test]
+    fn elementary_functions() {
+        assert_eq!(interval(4.0, 9.0).sqrt(), interval(2.0, 3.0));
+        assert!(interval(-2.0, -1.0).sqrt().is_empty());
+
+        // sin is not monotonic on [0, 3], so its maximum of 1 is included
+        let sin = interval(0.0, 3.0).sin();
+        assert_eq!(sin.lo(), &0.0);
+        assert_eq!(sin.hi(), &1.0);
+
+        let cos = interval(3.0, 4.0).cos();
+        assert_eq!(cos.lo(), &-1.0);
+        assert!(cos.contains(&float(4.0).cos()) && cos.contains(&float(3.0).cos()));
+
+        let pi = Interval::pi();
+        assert!(pi.sin().contains_zero());
+        assert_eq!(interval(1.0, 2.0).tan(), Interval::entire());
+    }
+
+    #[test]
+    fn e_pi_minus_pi() {
+        with_precision(64, || {
+            let result = Interval::e().pow(&Interval::pi()).sub(&Interval::pi());
+            assert!(!result.contains(&float(20)));
+            assert!(*result.lo() > float_from_str("19.999099979189475"));
+            assert!(*result.hi() < float_from_str("19.999099979189476"));
+            assert!(result.width() < 1e-15);
+        });
+    }
+
+    #[test]
+    fn comparison() {
+        assert_eq!(interval(1.0, 2.0).compare(&interval(3.0, 4.0)), Some(Ordering::Less));
+        assert_eq!(interval(1.0, 3.0).compare(&interval(2.0, 4.0)), None);
+        assert_eq!(interval(2.0, 2.0).compare(&interval(2.0, 2.0)), Some(Ordering::Equal));
+    }
+}
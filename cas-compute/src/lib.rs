@@ -1,6 +1,7 @@
 pub mod approx;
 pub mod consts;
 pub mod funcs;
+pub mod interval;
 pub mod numerical;
 pub mod primitive;
 pub mod symbolic;
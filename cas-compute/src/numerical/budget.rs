@@ -0,0 +1,307 @@
+//! Limits on the resources used to evaluate an expression, and cancellation of an evaluation in
+//! progress.
+//!
+//! A [`Budget`] is set on a [`Ctxt`](super::ctxt::Ctxt), and applies to each evaluation using the
+//! context separately. An evaluation starts when an expression or list of statements is evaluated
+//! with the context, and ends when that expression or those statements have been evaluated. This
+//! makes it possible to evaluate arbitrary user input without it hanging or exhausting memory.
+//!
+//! ```
+//! use cas_compute::numerical::{budget::Budget, ctxt::Ctxt, eval::Eval};
+//! use cas_parser::parser::{ast::expr::Expr, Parser};
+//!
+//! let mut ctxt = Ctxt::default();
+//! ctxt.budget = Budget {
+//!     max_steps: Some(10_000),
+//!     ..Default::default()
+//! };
+//!
+//! let expr = Parser::new("loop {}").try_parse_full::<Expr>().unwrap();
+//! assert!(expr.eval(&mut ctxt).is_err());
+//! ```
+
+use rug::{Integer, Rational};
+use std::sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc};
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use super::{
+    error::{
+        kind::{
+            Cancelled,
+            IntegerLimitExceeded,
+            ListLimitExceeded,
+            StepLimitExceeded,
+            TimeLimitExceeded,
+        },
+        Error,
+    },
+    value::Value,
+};
+
+/// The number of steps taken between each check of the deadline of an evaluation, since reading
+/// the clock is much slower than counting a step.
+const CLOCK_INTERVAL: u64 = 64;
+
+/// Limits on the resources a single evaluation can use. A limit of [`None`] disables that limit,
+/// which is the default for every limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    /// The maximum number of steps an evaluation can take. A step is taken for each expression
+    /// evaluated, so every iteration of a loop and every function call takes at least one step.
+    /// Builtins that loop, such as `factorint` and `odesolve`, also take a step on every iteration.
+    ///
+    /// Code compiled by the [`vm`](super::vm) takes fewer steps, since it only takes a step at the
+    /// start of the program, and for every iteration of a loop and every function call.
+    pub max_steps: Option<u64>,
+
+    /// The maximum wall-clock time an evaluation can take.
+    pub timeout: Option<Duration>,
+
+    /// The maximum number of bits in the magnitude of an integer, or in the numerator or
+    /// denominator of a rational number.
+    ///
+    /// Operations whose result can be much larger than their operands, such as `2^60000` or
+    /// `1 << 10^9`, are checked before they are computed.
+    pub max_integer_bits: Option<u64>,
+
+    /// The maximum number of elements in a list, including the list a range is converted to when
+    /// it is passed to a function, such as `sum(0..10)`.
+    pub max_list_len: Option<usize>,
+}
+
+/// A handle to cancel an evaluation from another thread, obtained with
+/// [`Ctxt::cancel_handle`](super::ctxt::Ctxt::cancel_handle).
+///
+/// The evaluation stops with a [`Cancelled`] error soon after [`CancelHandle::cancel`] is called.
+/// The handle stays cancelled, and every evaluation using the context will be cancelled, until
+/// [`CancelHandle::reset`] is called.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    /// Creates a new handle that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the evaluations using this handle.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the handle has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Resets the handle, so that new evaluations using it are no longer cancelled.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The resources used by an evaluation in progress.
+///
+/// The meter is shared between the copies of a context made during the evaluation, such as the
+/// copy given to a builtin function, so that the work done with the copies counts towards the
+/// same budget.
+#[derive(Debug, Default)]
+pub(crate) struct Meter {
+    /// The number of steps taken so far.
+    steps: AtomicU64,
+
+    /// The time at which the evaluation runs out of time, if it has a timeout.
+    deadline: Option<Instant>,
+}
+
+impl Meter {
+    /// Starts measuring a new evaluation with the given budget.
+    pub(crate) fn start(budget: &Budget) -> Self {
+        Self {
+            steps: AtomicU64::new(0),
+            deadline: budget.timeout.and_then(|timeout| Instant::now().checked_add(timeout)),
+        }
+    }
+
+    /// Takes a step of the evaluation, returning an error if the evaluation was cancelled or it
+    /// has run out of steps or time. `span` is the span of the expression being evaluated.
+    pub(crate) fn step(
+        &self,
+        budget: &Budget,
+        cancel: &CancelHandle,
+        span: impl FnOnce() -> Range<usize>,
+    ) -> Result<(), Error> {
+        if cancel.is_cancelled() {
+            return Err(Error::new(vec![span()], Cancelled));
+        }
+
+        let steps = self.steps.fetch_add(1, Ordering::Relaxed);
+        if let Some(limit) = budget.max_steps.filter(|&limit| steps >= limit) {
+            return Err(Error::new(vec![span()], StepLimitExceeded { limit }));
+        }
+
+        if let Some(deadline) = self.deadline {
+            if steps.is_multiple_of(CLOCK_INTERVAL) && Instant::now() >= deadline {
+                return Err(Error::new(vec![span()], TimeLimitExceeded {
+                    timeout: budget.timeout.unwrap_or_default(),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Budget {
+    /// Checks that an integer with the given number of bits is within the budget.
+    pub(crate) fn check_integer_bits(
+        &self,
+        bits: u64,
+        span: impl FnOnce() -> Range<usize>,
+    ) -> Result<(), Error> {
+        match self.max_integer_bits {
+            Some(limit) if bits > limit => Err(Error::new(vec![span()], IntegerLimitExceeded { limit })),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a list with the given number of elements is within the budget.
+    pub(crate) fn check_list_len(
+        &self,
+        len: &Integer,
+        span: impl FnOnce() -> Range<usize>,
+    ) -> Result<(), Error> {
+        match self.max_list_len {
+            Some(limit) if *len > limit => Err(Error::new(vec![span()], ListLimitExceeded {
+                len: len.clone(),
+                limit,
+            })),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that a value produced by an expression is within the budget. Only the value itself
+    /// is checked, not the elements of a list, since they are checked when they are produced.
+    pub(crate) fn check_value(
+        &self,
+        value: &Value,
+        span: impl FnOnce() -> Range<usize>,
+    ) -> Result<(), Error> {
+        match value {
+            Value::Integer(int) => self.check_integer_bits(int.significant_bits().into(), span),
+            Value::Rational(rational) => self.check_integer_bits(rational_bits(rational), span),
+            Value::List(list) => self.check_list_len(&Integer::from(list.len()), span),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the list a value would be converted to by [`Value::coerce_list`] is within the
+    /// budget. This is used before a range is converted to a list.
+    pub(crate) fn check_coerced_list(
+        &self,
+        value: &Value,
+        span: impl FnOnce() -> Range<usize>,
+    ) -> Result<(), Error> {
+        match value {
+            Value::Range(range) => self.check_list_len(&range.len(), span),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns the number of bits in the larger of the numerator and denominator of a rational number.
+pub(crate) fn rational_bits(rational: &Rational) -> u64 {
+    rational.numer().significant_bits().max(rational.denom().significant_bits()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::Parser;
+    use crate::numerical::{ctxt::Ctxt, eval::eval_stmts};
+    use std::thread;
+    use super::*;
+
+    /// Evaluates the given source code with the given budget, returning the name of the kind of
+    /// error if evaluation fails.
+    fn eval(source: &str, budget: Budget) -> Result<String, String> {
+        let mut ctxt = Ctxt::default();
+        ctxt.budget = budget;
+        let stmts = Parser::new(source).try_parse_full_many().unwrap();
+        eval_stmts(&stmts, &mut ctxt)
+            .map(|value| value.to_string())
+            .map_err(|err| format!("{:?}", err.kind))
+    }
+
+    #[test]
+    fn max_steps() {
+        let budget = Budget { max_steps: Some(1000), ..Default::default() };
+        assert_eq!(eval("s = 0; for i in 0..10 { s += i }; s", budget).unwrap(), "45");
+        assert_eq!(eval("loop {}", budget).unwrap_err(), "StepLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("f(n) = f(n + 1); f(0)", budget).unwrap_err(), "StepLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("map(x -> loop {}, [1])", budget).unwrap_err(), "StepLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("odesolve(atan2, 0, 1, 10000)", budget).unwrap_err(), "StepLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("factorint((2^61 - 1) * (2^89 - 1))", budget).unwrap_err(), "StepLimitExceeded { limit: 1000 }");
+
+        // each evaluation has its own budget
+        let mut ctxt = Ctxt::default();
+        ctxt.budget = budget;
+        let stmts = Parser::new("s = 0; for i in 0..100 { s += i }").try_parse_full_many().unwrap();
+        for _ in 0..10 {
+            eval_stmts(&stmts, &mut ctxt).unwrap();
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let budget = Budget { timeout: Some(Duration::from_millis(50)), ..Default::default() };
+        assert_eq!(eval("1 + 2", budget).unwrap(), "3");
+        assert!(eval("loop {}", budget).unwrap_err().starts_with("TimeLimitExceeded"));
+    }
+
+    #[test]
+    fn max_integer_bits() {
+        let budget = Budget { max_integer_bits: Some(1000), ..Default::default() };
+        assert_eq!(eval("2^999 > 0", budget).unwrap(), "true");
+        assert_eq!(eval("(1/2)^999 > 0", budget).unwrap(), "true");
+        assert_eq!(eval("2^1000", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("3^60000", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("(1/3)^-30000", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("1 << 1000000000", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("[2, 3]^5000", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("x = 2; x ^= 5000", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("x = 3; loop { x *= x }", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+        assert_eq!(eval("500!", budget).unwrap_err(), "IntegerLimitExceeded { limit: 1000 }");
+    }
+
+    #[test]
+    fn max_list_len() {
+        let budget = Budget { max_list_len: Some(100), ..Default::default() };
+        assert_eq!(eval("sum(1..=100)", budget).unwrap(), "5050");
+        assert_eq!(eval("s = 0; for i in 0..1000 { s += 1 }; s", budget).unwrap(), "1000");
+        assert!(eval("sum(0..1000000000000)", budget).unwrap_err().starts_with("ListLimitExceeded"));
+        assert!(eval("map(x -> x, 0..101)", budget).unwrap_err().starts_with("ListLimitExceeded"));
+        assert!(eval("max(0..101)", budget).unwrap_err().starts_with("ListLimitExceeded"));
+        assert!(eval("l = [1]; loop { l = concat(l, l) }", budget).unwrap_err().starts_with("ListLimitExceeded"));
+    }
+
+    #[test]
+    fn cancel() {
+        let mut ctxt = Ctxt::default();
+        let handle = ctxt.cancel_handle();
+        let stmts = Parser::new("loop {}").try_parse_full_many().unwrap();
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handle.cancel();
+        });
+        let err = eval_stmts(&stmts, &mut ctxt).unwrap_err();
+        assert_eq!(format!("{:?}", err.kind), "Cancelled");
+        canceller.join().unwrap();
+
+        // the handle stays cancelled until it is reset
+        let stmts = Parser::new("1 + 2").try_parse_full_many().unwrap();
+        assert!(eval_stmts(&stmts, &mut ctxt).is_err());
+        ctxt.cancel_handle().reset();
+        assert_eq!(eval_stmts(&stmts, &mut ctxt).unwrap(), 3.into());
+    }
+}
@@ -19,6 +19,10 @@ pub enum BuiltinError {
 
     /// A function specific error.
     FunctionSpecific(FunctionSpecific),
+
+    /// The evaluation was cancelled, or ran out of steps or time, while the function was running.
+    /// See [`Ctxt::builtin_step`](crate::numerical::ctxt::Ctxt::builtin_step).
+    Interrupted(Box<dyn ErrorKind>),
 }
 
 impl BuiltinError {
@@ -46,6 +50,10 @@ impl BuiltinError {
                 spans: e.spans(call),
                 kind: e.into_kind(),
             },
+            BuiltinError::Interrupted(kind) => Error {
+                spans: call.outer_span().to_vec(),
+                kind,
+            },
         }
     }
 }
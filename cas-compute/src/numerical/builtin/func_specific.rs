@@ -2,28 +2,308 @@ use cas_attrs::ErrorKind;
 use cas_error::ErrorKind;
 use cas_parser::parser::ast::call::Call;
 use crate::numerical::builtin::BuiltinError;
+use crate::primitive::{MAX_PRECISION, MIN_PRECISION};
 use std::ops::Range;
 
 /// Represents an error specific to a builtin function.
 #[derive(Debug)]
 pub enum FunctionSpecific {
+    /// Errors for the builtin functions that operate on lists.
+    List(ListError),
+
+    /// Errors for the linear algebra builtin functions.
+    Matrix(MatrixError),
+
     /// Errors for the `ncr` and `npr` builtin function.
     Ncpr(NcprError),
+
+    /// Errors for the number-theoretic builtin functions.
+    NumberTheory(NumberTheoryError),
+
+    /// Errors for the `odesolve` and `odelist` builtin functions.
+    Ode(OdeError),
+
+    /// Errors for the `precision` builtin function.
+    Precision(PrecisionError),
+
+    /// Errors for the `sum` and `product` builtin functions.
+    Series(SeriesError),
+
+    /// Errors for the statistics and regression builtin functions.
+    Statistics(StatisticsError),
+
+    /// Errors for the builtin functions that manipulate symbolic expressions.
+    Symbolic(SymbolicError),
 }
 
 impl FunctionSpecific {
     /// Get the spans for the error.
     pub fn spans(&self, call: &Call) -> Vec<Range<usize>> {
         match self {
+            FunctionSpecific::List(e) => e.spans(call),
+            FunctionSpecific::Matrix(e) => e.spans(call),
             FunctionSpecific::Ncpr(e) => e.spans(call),
+            FunctionSpecific::NumberTheory(e) => e.spans(call),
+            FunctionSpecific::Ode(e) => e.spans(call),
+            FunctionSpecific::Precision(e) => e.spans(call),
+            FunctionSpecific::Series(e) => e.spans(call),
+            FunctionSpecific::Statistics(e) => e.spans(call),
+            FunctionSpecific::Symbolic(e) => e.spans(call),
         }
     }
 
     /// Convert the [`FunctionSpecific`] into an [`ErrorKind`].
     pub fn into_kind(self) -> Box<dyn ErrorKind> {
         match self {
+            FunctionSpecific::List(e) => Box::new(e),
+            FunctionSpecific::Matrix(e) => Box::new(e),
             FunctionSpecific::Ncpr(e) => Box::new(e),
+            FunctionSpecific::NumberTheory(e) => Box::new(e),
+            FunctionSpecific::Ode(e) => Box::new(e),
+            FunctionSpecific::Precision(e) => Box::new(e),
+            FunctionSpecific::Series(e) => Box::new(e),
+            FunctionSpecific::Statistics(e) => Box::new(e),
+            FunctionSpecific::Symbolic(e) => Box::new(e),
+        }
+    }
+}
+
+/// Errors for the builtin functions that operate on lists, such as `map` and `sort`.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = match &self.error {
+        ListErrorKind::NotAFunction { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a function".to_string(),
+        ],
+        ListErrorKind::InvalidElement { given, .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("this list contains a `{}`, which cannot be {}", given, match self.function_name {
+                "sum" => "added",
+                "prod" => "multiplied",
+                _ => "compared",
+            }),
+        ],
+        ListErrorKind::EmptyList { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this list is empty".to_string(),
+        ],
+        ListErrorKind::ZeroStep => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this step must not be zero".to_string(),
+        ],
+        ListErrorKind::TooManyElements => vec![
+            "this function call".to_string(),
+            "".to_string(),
+        ],
+        ListErrorKind::LengthMismatch { expected, given, .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("this list has {} element{}, but an earlier list has {}", given, if *given == 1 { "" } else { "s" }, expected),
+        ],
+    },
+    help = match &self.error {
+        ListErrorKind::NotAFunction { .. } => "pass a function by name or as a lambda, such as `sqrt` or `x -> x^2` in `map(sqrt, [1, 4, 9])`".to_string(),
+        ListErrorKind::InvalidElement { .. } => match self.function_name {
+            "sum" | "prod" => "every element of the list must be a number".to_string(),
+            _ => "every element of the list must be a real number".to_string(),
+        },
+        ListErrorKind::EmptyList { .. } => "pass an initial value as the third argument, such as `reduce(f, l, 0)`".to_string(),
+        ListErrorKind::ZeroStep => "the step between consecutive elements must be a positive or negative integer".to_string(),
+        ListErrorKind::TooManyElements => format!("a list can have at most {} elements", crate::funcs::list::MAX_LEN),
+        ListErrorKind::LengthMismatch { .. } => "the function is applied element-wise to the lists given in place of single values, so they must have the same length".to_string(),
+    }
+)]
+pub struct ListError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: ListErrorKind,
+}
+
+impl ListError {
+    pub fn new(function_name: &'static str, error: ListErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+
+        // these errors can be returned when the function is called by a higher-order function, in
+        // which case the argument may not exist in the call
+        let arg = match self.error {
+            ListErrorKind::NotAFunction { index }
+                | ListErrorKind::InvalidElement { index, .. }
+                | ListErrorKind::EmptyList { index }
+                | ListErrorKind::LengthMismatch { index, .. } => call.args.get(index),
+            ListErrorKind::ZeroStep => call.args.get(2),
+            ListErrorKind::TooManyElements => None,
+        };
+        if let Some(arg) = arg {
+            this_function_call.push(arg.span());
+        }
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListErrorKind {
+    /// An argument that should be a function is not a function.
+    NotAFunction {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// An element of a list cannot be combined with or compared to the other elements.
+    InvalidElement {
+        /// The index of the argument containing the list.
+        index: usize,
+
+        /// The type of the element.
+        given: &'static str,
+    },
+
+    /// A list is empty, and there is no value to return.
+    EmptyList {
+        /// The index of the argument containing the list.
+        index: usize,
+    },
+
+    /// The step of a range is zero.
+    ZeroStep,
+
+    /// The resulting list would have too many elements.
+    TooManyElements,
+
+    /// Lists given in place of single values, to apply a function element-wise, have different
+    /// lengths.
+    LengthMismatch {
+        /// The index of the argument containing the list.
+        index: usize,
+
+        /// The length of the earlier lists.
+        expected: usize,
+
+        /// The length of this list.
+        given: usize,
+    },
+}
+
+impl From<ListError> for BuiltinError {
+    fn from(e: ListError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::List(e))
+    }
+}
+
+/// Errors for the linear algebra builtin functions, such as `det` and `solve`.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = vec![
+        "this function call".to_string(),
+        "".to_string(),
+        match &self.error {
+            MatrixErrorKind::NotSquare { rows, cols, .. } => format!("this matrix is {}x{}", rows, cols),
+            MatrixErrorKind::Singular { .. } => "this matrix is singular".to_string(),
+            MatrixErrorKind::LengthMismatch { expected, given, .. } => format!(
+                "this has {} element{}, but should have {}",
+                given,
+                if *given == 1 { "" } else { "s" },
+                expected,
+            ),
+            MatrixErrorKind::InvalidEntry { given, .. } => format!("this contains a `{}`", given),
+        },
+    ],
+    help = match &self.error {
+        MatrixErrorKind::NotSquare { .. } => "this function is only defined for square matrices".to_string(),
+        MatrixErrorKind::Singular { .. } => "a singular matrix has a determinant of zero, and no inverse".to_string(),
+        MatrixErrorKind::LengthMismatch { .. } => match self.function_name {
+            "cross" => "the cross product is only defined for vectors with 3 elements".to_string(),
+            "solve" => "the right-hand side must have one element or row for each row of the matrix".to_string(),
+            _ => "both vectors must have the same length".to_string(),
+        },
+        MatrixErrorKind::InvalidEntry { .. } => "every element of a vector must be a number".to_string(),
+    }
+)]
+pub struct MatrixError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: MatrixErrorKind,
+}
+
+impl MatrixError {
+    pub fn new(function_name: &'static str, error: MatrixErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        let index = match self.error {
+            MatrixErrorKind::NotSquare { index, .. }
+                | MatrixErrorKind::Singular { index }
+                | MatrixErrorKind::LengthMismatch { index, .. }
+                | MatrixErrorKind::InvalidEntry { index, .. } => index,
+        };
+        if let Some(arg) = call.args.get(index) {
+            this_function_call.push(arg.span());
         }
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixErrorKind {
+    /// The matrix must be square.
+    NotSquare {
+        /// The index of the argument containing the matrix.
+        index: usize,
+
+        /// The number of rows of the matrix.
+        rows: usize,
+
+        /// The number of columns of the matrix.
+        cols: usize,
+    },
+
+    /// The matrix must be invertible.
+    Singular {
+        /// The index of the argument containing the matrix.
+        index: usize,
+    },
+
+    /// A vector or matrix has the wrong number of elements or rows.
+    LengthMismatch {
+        /// The index of the argument.
+        index: usize,
+
+        /// The expected number of elements or rows.
+        expected: usize,
+
+        /// The given number of elements or rows.
+        given: usize,
+    },
+
+    /// An element of a vector is not a number.
+    InvalidEntry {
+        /// The index of the argument containing the vector.
+        index: usize,
+
+        /// The type of the element.
+        given: &'static str,
+    },
+}
+
+impl From<MatrixError> for BuiltinError {
+    fn from(e: MatrixError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::Matrix(e))
     }
 }
 
@@ -89,3 +369,667 @@ impl From<NcprError> for BuiltinError {
         BuiltinError::FunctionSpecific(FunctionSpecific::Ncpr(e))
     }
 }
+
+/// Errors for the number-theoretic builtin functions, such as `modinv` and `crt`.
+#[derive(Debug, Clone, Copy, ErrorKind, PartialEq, Eq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = match self.error {
+        NumberTheoryErrorKind::NotPositive { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a positive integer".to_string(),
+        ],
+        NumberTheoryErrorKind::Negative { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must not be negative".to_string(),
+        ],
+        NumberTheoryErrorKind::NotPositiveOdd { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a positive odd integer".to_string(),
+        ],
+        NumberTheoryErrorKind::NoSmallerPrime => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "there is no prime less than this number".to_string(),
+        ],
+        NumberTheoryErrorKind::NoInverse => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "(1) this number has no inverse...".to_string(),
+            "(2) ...modulo this number".to_string(),
+        ],
+        NumberTheoryErrorKind::NotAnIntegerList { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a list of integers".to_string(),
+        ],
+        NumberTheoryErrorKind::LengthMismatch => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "(1) this list...".to_string(),
+            "(2) ...must have the same length as this list".to_string(),
+        ],
+        NumberTheoryErrorKind::NoSolution => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "these congruences have no common solution".to_string(),
+        ],
+        NumberTheoryErrorKind::NotFinite { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a finite number".to_string(),
+        ],
+        NumberTheoryErrorKind::InvalidContinuedFraction => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this is not a valid continued fraction".to_string(),
+        ],
+    },
+    help = match self.error {
+        NumberTheoryErrorKind::NotPositive { .. } => "pass an integer greater than zero",
+        NumberTheoryErrorKind::Negative { .. } => "pass an integer greater than or equal to zero",
+        NumberTheoryErrorKind::NotPositiveOdd { .. } => "the Jacobi symbol `jacobi(a, n)` is only defined for odd `n > 0`",
+        NumberTheoryErrorKind::NoSmallerPrime => "the smallest prime is `2`",
+        NumberTheoryErrorKind::NoInverse => "a number has an inverse modulo `m` only if it is coprime to `m`",
+        NumberTheoryErrorKind::NotAnIntegerList { .. } => "pass the residues and moduli as lists, such as `crt([2, 3], [3, 5])`",
+        NumberTheoryErrorKind::LengthMismatch => "each residue must have a matching modulus",
+        NumberTheoryErrorKind::NoSolution => "two congruences with moduli that are not coprime must agree modulo the gcd of their moduli",
+        NumberTheoryErrorKind::NotFinite { .. } => "only finite numbers can be approximated by fractions",
+        NumberTheoryErrorKind::InvalidContinuedFraction => "every partial quotient after the first must be a positive integer, such as in `fromcf([3, 7, 15, 1])`",
+    }
+)]
+pub struct NumberTheoryError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: NumberTheoryErrorKind,
+}
+
+impl NumberTheoryError {
+    pub fn new(function_name: &'static str, error: NumberTheoryErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        match self.error {
+            NumberTheoryErrorKind::NotPositive { index }
+                | NumberTheoryErrorKind::Negative { index }
+                | NumberTheoryErrorKind::NotPositiveOdd { index }
+                | NumberTheoryErrorKind::NotAnIntegerList { index }
+                | NumberTheoryErrorKind::NotFinite { index } => {
+                this_function_call.push(call.args[index].span());
+            },
+            NumberTheoryErrorKind::NoSmallerPrime | NumberTheoryErrorKind::InvalidContinuedFraction => {
+                this_function_call.push(call.args[0].span());
+            },
+            NumberTheoryErrorKind::NoInverse => {
+                this_function_call.push(call.args[0].span());
+                this_function_call.push(call.args[call.args.len() - 1].span());
+            },
+            NumberTheoryErrorKind::LengthMismatch => {
+                this_function_call.extend(call.args.iter().map(|arg| arg.span()));
+            },
+            NumberTheoryErrorKind::NoSolution => {
+                this_function_call.push(call.args[0].span().start..call.args[1].span().end);
+            },
+        };
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberTheoryErrorKind {
+    /// An argument is not a positive integer.
+    NotPositive {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// An argument is a negative integer.
+    Negative {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// An argument is not a positive odd integer.
+    NotPositiveOdd {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// There is no prime less than the given number.
+    NoSmallerPrime,
+
+    /// The number is not invertible modulo the given modulus.
+    NoInverse,
+
+    /// An argument is not a list of integers.
+    NotAnIntegerList {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// Two lists that should have the same length do not.
+    LengthMismatch,
+
+    /// A system of congruences has no solution.
+    NoSolution,
+
+    /// An argument is infinite or NaN.
+    NotFinite {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// A list of partial quotients has a partial quotient after the first that is not positive.
+    InvalidContinuedFraction,
+}
+
+impl From<NumberTheoryError> for BuiltinError {
+    fn from(e: NumberTheoryError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::NumberTheory(e))
+    }
+}
+
+/// Errors for the `odesolve` and `odelist` builtin functions.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = match &self.error {
+        OdeErrorKind::NotAFunction => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a function".to_string(),
+        ],
+        OdeErrorKind::InvalidDerivative { expected, given } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("this function returned `{}` instead of {}", given, expected),
+        ],
+        OdeErrorKind::InvalidSampleCount => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a positive integer".to_string(),
+        ],
+        OdeErrorKind::InvalidTolerance { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be a positive, finite number".to_string(),
+        ],
+        OdeErrorKind::StepSizeUnderflow { t } => vec![
+            format!("the step size became too small near t = {}", t),
+            "".to_string(),
+        ],
+    },
+    help = match &self.error {
+        OdeErrorKind::NotAFunction => "pass a function `f(t, y)` that returns the derivative of `y` as the first argument, either by name or as a lambda such as `(t, y) -> -y`",
+        OdeErrorKind::InvalidDerivative { .. } => "for a system of `n` equations, the function should take `n + 1` arguments and return a list of `n` numbers",
+        OdeErrorKind::InvalidSampleCount => "the number of samples to take must be at least `1`",
+        OdeErrorKind::InvalidTolerance { .. } => "the tolerance bounds the estimated error of each step, so it must be greater than `0`; omit it to use the default",
+        OdeErrorKind::StepSizeUnderflow { .. } => "the solution may have a singularity in the interval; try a larger tolerance or a shorter interval",
+    }
+)]
+pub struct OdeError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: OdeErrorKind,
+}
+
+impl OdeError {
+    pub fn new(function_name: &'static str, error: OdeErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        match self.error {
+            OdeErrorKind::NotAFunction | OdeErrorKind::InvalidDerivative { .. } => {
+                this_function_call.push(call.args[0].span());
+            },
+            OdeErrorKind::InvalidSampleCount => this_function_call.push(call.args[4].span()),
+            OdeErrorKind::InvalidTolerance { index } => this_function_call.push(call.args[index].span()),
+            OdeErrorKind::StepSizeUnderflow { .. } => (),
+        };
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OdeErrorKind {
+    /// The first argument is not a function.
+    NotAFunction,
+
+    /// The function did not return a number, or a list of numbers of the right length.
+    InvalidDerivative {
+        /// A description of the expected value.
+        expected: String,
+
+        /// The type of the value that was returned.
+        given: &'static str,
+    },
+
+    /// The number of samples is not a positive integer.
+    InvalidSampleCount,
+
+    /// The tolerance is not a positive, finite number.
+    InvalidTolerance {
+        /// The index of the tolerance argument, counting the function as argument `0`.
+        index: usize,
+    },
+
+    /// The adaptive step size became too small to make progress.
+    StepSizeUnderflow {
+        /// The value of the independent variable where integration stopped.
+        t: String,
+    },
+}
+
+impl From<OdeError> for BuiltinError {
+    fn from(e: OdeError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::Ode(e))
+    }
+}
+
+/// Errors for the `precision` builtin function.
+#[derive(Debug, Clone, Copy, ErrorKind, PartialEq, Eq)]
+#[error(
+    message = "incorrect arguments for the `precision` function",
+    labels = ["this function call", "", "this argument must be an integer number of bits"],
+    help = format!("the precision must be between {} and {} bits", MIN_PRECISION, MAX_PRECISION),
+)]
+pub struct PrecisionError;
+
+impl PrecisionError {
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        this_function_call.push(call.args[0].span());
+        this_function_call
+    }
+}
+
+impl From<PrecisionError> for BuiltinError {
+    fn from(e: PrecisionError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::Precision(e))
+    }
+}
+
+/// Errors for the `sum` and `product` builtin functions.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = match &self.error {
+        SeriesErrorKind::NotAVariable => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be the name of a variable".to_string(),
+        ],
+        SeriesErrorKind::InvalidBound { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this bound must be an integer".to_string(),
+        ],
+        SeriesErrorKind::InvalidTerm { given } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("this expression evaluated to `{}` instead of a number", given),
+        ],
+        SeriesErrorKind::TooManyTerms => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "there are too many terms in this range".to_string(),
+        ],
+        SeriesErrorKind::NoConvergence => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this series did not converge".to_string(),
+        ],
+    },
+    help = match &self.error {
+        SeriesErrorKind::NotAVariable => "the second argument is the index variable, such as `k` in `sum(1 / k^2, k, 1, inf)`",
+        SeriesErrorKind::InvalidBound { index: 3 } => "the upper bound must be an integer, or `inf` for an infinite series",
+        SeriesErrorKind::InvalidBound { .. } => "the lower bound must be an integer",
+        SeriesErrorKind::InvalidTerm { .. } => "every term of the series must be a number",
+        SeriesErrorKind::TooManyTerms => "no closed form was found for this series, and it has too many terms to evaluate one at a time",
+        SeriesErrorKind::NoConvergence => "no closed form was found for this series, and its partial results did not approach a limit",
+    }
+)]
+pub struct SeriesError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: SeriesErrorKind,
+}
+
+impl SeriesError {
+    pub fn new(function_name: &'static str, error: SeriesErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        match self.error {
+            SeriesErrorKind::NotAVariable => this_function_call.push(call.args[1].span()),
+            SeriesErrorKind::InvalidBound { index } => this_function_call.push(call.args[index].span()),
+            SeriesErrorKind::InvalidTerm { .. } | SeriesErrorKind::NoConvergence => {
+                this_function_call.push(call.args[0].span());
+            },
+            SeriesErrorKind::TooManyTerms => {
+                this_function_call.push(call.args[2].span().start..call.args[3].span().end);
+            },
+        };
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeriesErrorKind {
+    /// The index variable is not a symbol.
+    NotAVariable,
+
+    /// A bound is not an integer, or the lower bound is infinite.
+    InvalidBound {
+        /// The index of the argument containing the bound.
+        index: usize,
+    },
+
+    /// A term of the series is not a number.
+    InvalidTerm {
+        /// The type of the term.
+        given: &'static str,
+    },
+
+    /// The range is too large to evaluate directly, and no closed form was found.
+    TooManyTerms,
+
+    /// The infinite series did not converge numerically, and no closed form was found.
+    NoConvergence,
+}
+
+impl From<SeriesError> for BuiltinError {
+    fn from(e: SeriesError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::Series(e))
+    }
+}
+
+/// Errors for the statistics and regression builtin functions, such as `mean` and `linreg`.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = vec![
+        "this function call".to_string(),
+        "".to_string(),
+        match &self.error {
+            StatisticsErrorKind::InvalidElement { given, .. } => format!("this list contains a `{}`", given),
+            StatisticsErrorKind::TooFewElements { given, .. } => format!(
+                "this list has {} element{}",
+                given,
+                if *given == 1 { "" } else { "s" },
+            ),
+            StatisticsErrorKind::LengthMismatch { expected, given, .. } => format!(
+                "this list has {} element{}, but the first list has {}",
+                given,
+                if *given == 1 { "" } else { "s" },
+                expected,
+            ),
+            StatisticsErrorKind::ConstantData { .. } => "every element of this list is the same".to_string(),
+            StatisticsErrorKind::InvalidProbability => "this must be between 0 and 1".to_string(),
+            StatisticsErrorKind::NonPositive { .. } => "this list contains a number that is not positive".to_string(),
+            StatisticsErrorKind::TooFewDistinct { required, .. } => format!("this list has fewer than {} distinct elements", required),
+            StatisticsErrorKind::InvalidDegree => "this must be a non-negative integer".to_string(),
+            StatisticsErrorKind::NotAName { .. } => "this argument must be a name for the fitted function".to_string(),
+        },
+    ],
+    help = match &self.error {
+        StatisticsErrorKind::InvalidElement { .. } => "every element of the list must be a real number".to_string(),
+        StatisticsErrorKind::TooFewElements { required: 1, .. } => "the list must not be empty".to_string(),
+        StatisticsErrorKind::TooFewElements { required, .. } => match self.function_name {
+            "var" | "stdev" => format!(
+                "the list must have at least {} elements; for a population, use the `p` variant of this function, such as `pvar`",
+                required,
+            ),
+            _ => format!("the list must have at least {} elements", required),
+        },
+        StatisticsErrorKind::LengthMismatch { .. } => "the lists are paired up element by element, so they must have the same length".to_string(),
+        StatisticsErrorKind::ConstantData { .. } => "the standard deviation of the list is zero, so the result would be a division by zero".to_string(),
+        StatisticsErrorKind::InvalidProbability => "the quantile is given as a fraction of the data, such as `0.25` for the first quartile".to_string(),
+        StatisticsErrorKind::NonPositive { .. } => "this model takes the logarithm of these values, so they must all be positive".to_string(),
+        StatisticsErrorKind::TooFewDistinct { required, .. } => format!("the model has {} coefficients, so it is only determined by at least {} distinct x-values", required, required),
+        StatisticsErrorKind::InvalidDegree => "the degree is the highest power of `x` in the fitted polynomial, such as `2` for a quadratic".to_string(),
+        StatisticsErrorKind::NotAName { .. } => match self.function_name {
+            "polyfit" => "the fitted function is defined with the given name, such as `g` in `polyfit(xs, ys, 2, g)`".to_string(),
+            name => format!("the fitted function is defined with the given name, such as `g` in `{}(xs, ys, g)`", name),
+        },
+    }
+)]
+pub struct StatisticsError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: StatisticsErrorKind,
+}
+
+impl StatisticsError {
+    pub fn new(function_name: &'static str, error: StatisticsErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        let index = match self.error {
+            StatisticsErrorKind::InvalidElement { index, .. }
+                | StatisticsErrorKind::TooFewElements { index, .. }
+                | StatisticsErrorKind::LengthMismatch { index, .. }
+                | StatisticsErrorKind::ConstantData { index }
+                | StatisticsErrorKind::NonPositive { index }
+                | StatisticsErrorKind::TooFewDistinct { index, .. }
+                | StatisticsErrorKind::NotAName { index } => index,
+            StatisticsErrorKind::InvalidProbability => 1,
+            StatisticsErrorKind::InvalidDegree => 2,
+        };
+        if let Some(arg) = call.args.get(index) {
+            this_function_call.push(arg.span());
+        }
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatisticsErrorKind {
+    /// An element of a list is not a real number.
+    InvalidElement {
+        /// The index of the argument containing the list.
+        index: usize,
+
+        /// The type of the element.
+        given: &'static str,
+    },
+
+    /// A list has too few elements to compute the statistic.
+    TooFewElements {
+        /// The index of the argument containing the list.
+        index: usize,
+
+        /// The minimum number of elements.
+        required: usize,
+
+        /// The number of elements in the list.
+        given: usize,
+    },
+
+    /// Two lists that are paired up have different lengths.
+    LengthMismatch {
+        /// The index of the argument containing the list.
+        index: usize,
+
+        /// The length of the first list.
+        expected: usize,
+
+        /// The length of this list.
+        given: usize,
+    },
+
+    /// Every element of a list is the same, so its standard deviation is zero.
+    ConstantData {
+        /// The index of the argument containing the list.
+        index: usize,
+    },
+
+    /// The probability of a quantile is not between 0 and 1.
+    InvalidProbability,
+
+    /// A list contains a number that is not positive, but the regression model takes its
+    /// logarithm.
+    NonPositive {
+        /// The index of the argument containing the list.
+        index: usize,
+    },
+
+    /// A list has too few distinct elements to determine the coefficients of a regression model.
+    TooFewDistinct {
+        /// The index of the argument containing the list.
+        index: usize,
+
+        /// The number of coefficients of the model.
+        required: usize,
+    },
+
+    /// The degree of a polynomial fit is not a non-negative integer.
+    InvalidDegree,
+
+    /// The argument naming the fitted function of a regression is not a name.
+    NotAName {
+        /// The index of the argument.
+        index: usize,
+    },
+}
+
+impl From<StatisticsError> for BuiltinError {
+    fn from(e: StatisticsError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::Statistics(e))
+    }
+}
+
+/// Errors for the builtin functions that manipulate symbolic expressions, such as `simplify` and
+/// `diff`.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("incorrect arguments for the `{}` function", self.function_name),
+    labels = match &self.error {
+        SymbolicErrorKind::NotAVariable { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this argument must be the name of a variable".to_string(),
+        ],
+        SymbolicErrorKind::Unsupported { .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            "this expression cannot be manipulated symbolically".to_string(),
+        ],
+        SymbolicErrorKind::InvalidVariable { name, given, .. } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("the variable `{}` in this expression holds a `{}`, not a number or expression", name, given),
+        ],
+        SymbolicErrorKind::NoResult => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("`{}` could not be applied to this expression", self.function_name),
+        ],
+        SymbolicErrorKind::NoSolution { reason } => vec![
+            "this function call".to_string(),
+            "".to_string(),
+            format!("could not solve this equation: {}", reason),
+        ],
+    },
+    help = match &self.error {
+        SymbolicErrorKind::NotAVariable { .. } => "pass the variable by name, such as `x` in `diff(x^2, x)`",
+        SymbolicErrorKind::Unsupported { .. } => "symbolic expressions can only contain numbers, variables, arithmetic operators, and function calls",
+        SymbolicErrorKind::InvalidVariable { .. } => "variables that have been assigned a value are replaced by that value",
+        SymbolicErrorKind::NoResult => match self.function_name {
+            "integrate" => "no antiderivative was found for this expression",
+            "apart" => "the expression must be a rational function of the variable, with numeric coefficients",
+            _ => "this expression may contain a function that is not supported",
+        },
+        SymbolicErrorKind::NoSolution { .. } => "`dsolve` solves first-order linear, separable, and exact equations, and second-order linear equations with constant coefficients, given either no initial conditions or one for each constant of integration",
+    }
+)]
+pub struct SymbolicError {
+    /// The specific function name.
+    pub function_name: &'static str,
+
+    /// The error that occurred.
+    pub error: SymbolicErrorKind,
+}
+
+impl SymbolicError {
+    pub fn new(function_name: &'static str, error: SymbolicErrorKind) -> Self {
+        Self { function_name, error }
+    }
+
+    fn spans(&self, call: &Call) -> Vec<Range<usize>> {
+        let mut this_function_call = call.outer_span().to_vec();
+        match self.error {
+            SymbolicErrorKind::NotAVariable { index }
+                | SymbolicErrorKind::Unsupported { index }
+                | SymbolicErrorKind::InvalidVariable { index, .. } => {
+                this_function_call.push(call.args[index].span());
+            },
+            SymbolicErrorKind::NoResult | SymbolicErrorKind::NoSolution { .. } => {
+                this_function_call.push(call.args[0].span());
+            },
+        };
+        this_function_call
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolicErrorKind {
+    /// An argument that should name a variable is not a symbol.
+    NotAVariable {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// An argument cannot be converted into a symbolic expression.
+    Unsupported {
+        /// The index of the argument.
+        index: usize,
+    },
+
+    /// A variable in an argument holds a value that cannot be part of a symbolic expression.
+    InvalidVariable {
+        /// The index of the argument.
+        index: usize,
+
+        /// The name of the variable.
+        name: String,
+
+        /// The type of the variable's value.
+        given: &'static str,
+    },
+
+    /// The operation could not be carried out on the expression, such as when no antiderivative
+    /// could be found.
+    NoResult,
+
+    /// An ordinary differential equation could not be solved.
+    NoSolution {
+        /// The reason the equation could not be solved.
+        reason: String,
+    },
+}
+
+impl From<SymbolicError> for BuiltinError {
+    fn from(e: SymbolicError) -> Self {
+        BuiltinError::FunctionSpecific(FunctionSpecific::Symbolic(e))
+    }
+}
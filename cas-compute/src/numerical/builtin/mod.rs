@@ -1,17 +1,81 @@
 pub mod error;
 pub mod func_specific;
 
+use cas_parser::parser::ast::call::Call;
 use error::BuiltinError;
-use super::{ctxt::Ctxt, value::Value};
+use super::{ctxt::Ctxt, error::Error, value::Value};
 
 type Result = std::result::Result<Value, BuiltinError>;
 
+/// The parameters of a builtin function, which the named arguments of a call are matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    /// The names of the parameters, in order.
+    pub names: &'static [&'static str],
+
+    /// The signature of the function, used in error messages.
+    pub signature: &'static str,
+}
+
 /// A trait implemented by all builtin functions.
 pub trait Builtin: std::fmt::Debug + Send + Sync {
     /// Number of args the function takes.
     // NOTE: this is a `&self` method and not an associated constant to make the trait object-safe
     fn num_args(&self) -> usize;
 
+    /// Returns the parameters of the function, so that a call can give arguments by name, such as
+    /// `ncr(n = 5, k = 2)`.
+    ///
+    /// If this method returns [`None`] (the default), the function does not accept named
+    /// arguments.
+    fn params(&self) -> Option<Params> {
+        None
+    }
+
     /// Evaluates the function.
     fn eval(&self, ctxt: &Ctxt, args: &mut dyn Iterator<Item = Value>) -> Result;
+
+    /// Evaluates the function using the unevaluated arguments of the call.
+    ///
+    /// This is used by higher-order functions, such as `odesolve`, that accept the name of a
+    /// function as an argument, which cannot be evaluated to a [`Value`]. If this method returns
+    /// [`None`] (the default), the arguments are evaluated and passed to [`Builtin::eval`]
+    /// instead.
+    fn eval_call(&self, _ctxt: &mut Ctxt, _call: &Call) -> Option<std::result::Result<Value, Error>> {
+        None
+    }
+
+    /// Evaluates the function with arguments that have already been evaluated, as if it were
+    /// called at the site of `call`.
+    ///
+    /// This is used when the function is called by a higher-order function, such as when it is
+    /// passed to `map`. Functions implementing [`Builtin::eval_call`] override this method if they
+    /// can be called with values at all; the default passes the values to [`Builtin::eval`].
+    fn eval_values(
+        &self,
+        ctxt: &mut Ctxt,
+        call: &Call,
+        args: Vec<Value>,
+    ) -> std::result::Result<Value, Error> {
+        self.eval(ctxt, &mut args.into_iter())
+            .map_err(|err| err.into_error(call))
+    }
+
+    /// Returns true if the function implements [`Builtin::eval_call`], so it must be given the
+    /// unevaluated arguments of a call.
+    ///
+    /// [Compiled](crate::numerical::vm) code evaluates the arguments of a call before calling the
+    /// function, so calls to these functions are evaluated by the tree-walking evaluator instead.
+    fn takes_call(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the function modifies the context it is called in, such as `precision`.
+    ///
+    /// [`Builtin::eval_call`] is usually given a copy of the caller's context, so that changes to
+    /// it, such as variables bound by `sum`, do not leak out of the call. If this method returns
+    /// true, [`Builtin::eval_call`] is given the caller's context instead.
+    fn mutates_ctxt(&self) -> bool {
+        false
+    }
 }
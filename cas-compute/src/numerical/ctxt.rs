@@ -1,8 +1,16 @@
-use cas_parser::parser::ast::{assign::FuncHeader, expr::Expr};
+use cas_parser::parser::ast::{assign::{FuncHeader, Param}, expr::Expr};
 use crate::consts;
+use crate::interval::Interval;
+use crate::primitive::{with_precision, MAX_PRECISION, MIN_PRECISION, PRECISION};
 use levenshtein::levenshtein;
-use std::{collections::HashMap, sync::Arc};
-use super::{builtin::Builtin, value::Value};
+use std::{collections::HashMap, ops::Range, sync::Arc};
+use super::{
+    budget::{Budget, CancelHandle, Meter},
+    builtin::{error::BuiltinError, Builtin},
+    error::Error,
+    memo::Memo,
+    value::Value,
+};
 
 #[cfg(feature = "mysql")]
 use mysql_common::prelude::FromValue;
@@ -17,7 +25,7 @@ pub const MAX_RECURSION_DEPTH: usize = 1 << 11;
 
 /// The trigonometric mode of a context. This will affect the evaluation of input to trigonometric
 /// functions, and output from trigonometric functions.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "mysql", derive(FromValue))]
 #[cfg_attr(feature = "mysql", mysql(is_integer))]
 #[cfg_attr(feature = "serde", derive(Serialize_repr, Deserialize_repr))]
@@ -40,8 +48,36 @@ impl std::fmt::Display for TrigMode {
     }
 }
 
+/// The number mode of a context. This determines how numbers that cannot be represented exactly
+/// are computed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "mysql", derive(FromValue))]
+#[cfg_attr(feature = "mysql", mysql(is_integer))]
+#[cfg_attr(feature = "serde", derive(Serialize_repr, Deserialize_repr))]
+#[repr(u8)]
+pub enum NumberMode {
+    /// Use floating-point numbers, rounding to the nearest representable number.
+    #[default]
+    Standard,
+
+    /// Use interval arithmetic. Float literals and the builtin constants evaluate to intervals
+    /// that enclose their exact values, and any computation involving them produces an interval
+    /// that is guaranteed to contain the exact result. Computations on integers and rational
+    /// numbers are still exact.
+    Interval,
+}
+
+impl std::fmt::Display for NumberMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberMode::Standard => write!(f, "standard"),
+            NumberMode::Interval => write!(f, "interval"),
+        }
+    }
+}
+
 /// A user-defined function.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UserFunc {
     /// The header of the function.
@@ -53,6 +89,95 @@ pub struct UserFunc {
     /// Whether the function is recursive, used to report better errors if the stack overflows
     /// while evaluating the function.
     pub recursive: bool,
+
+    /// The table of remembered calls to the function, or [`None`] if the function is not pure.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub memo: Option<Memo>,
+}
+
+/// A scope of local variables, created for each call to a user-defined function or lambda.
+///
+/// A scope is linked to the scope it was created in, if any. Global variables are not part of any
+/// scope; they are stored in the [`Ctxt`] itself, and are visible from every scope.
+///
+/// Scopes are shared through an [`Arc`], so that a lambda can capture the current scope without
+/// copying it. A shared scope is only copied if a variable is then defined in it, in which case
+/// the enclosing scopes are still shared.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Scope {
+    /// The variables defined in this scope.
+    vars: HashMap<String, Value>,
+
+    /// The enclosing scope, captured when the lambda that created this scope was created.
+    parent: Option<Arc<Scope>>,
+}
+
+impl Scope {
+    /// Creates an empty scope enclosed by the given scope.
+    pub fn new(parent: Option<Arc<Scope>>) -> Self {
+        Self { vars: HashMap::new(), parent }
+    }
+
+    /// Add a variable to this scope.
+    pub fn add_var(&mut self, name: &str, value: Value) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    /// Returns the variables defined in the given scope, excluding those of any enclosing scope.
+    /// They are only copied if the scope is still shared.
+    pub(crate) fn into_vars(scope: Arc<Self>) -> HashMap<String, Value> {
+        Arc::try_unwrap(scope).map_or_else(|scope| scope.vars.clone(), |scope| scope.vars)
+    }
+
+    /// Get the value of a variable in this scope or any enclosing scope.
+    pub fn get_var(&self, name: &str) -> Option<&Value> {
+        let mut scope = self;
+        loop {
+            if let Some(value) = scope.vars.get(name) {
+                return Some(value);
+            }
+            scope = scope.parent.as_deref()?;
+        }
+    }
+}
+
+/// An anonymous function created by a lambda expression, such as `x -> x^2`.
+///
+/// A lambda captures the local scope it is created in, if any. When it is called, its parameters
+/// are bound in a new scope enclosed by the captured scope, so the body can refer to the local
+/// variables of the function that created it, even after that function has returned. Global
+/// variables are not captured, and are looked up when the lambda is called.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Lambda {
+    /// The parameters of the lambda.
+    pub params: Vec<Param>,
+
+    /// The body of the lambda.
+    pub body: Expr,
+
+    /// The local scope the lambda was created in, or [`None`] if it was created at the top level.
+    pub captures: Option<Arc<Scope>>,
+}
+
+impl std::fmt::Display for Lambda {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.params.as_slice() {
+            [Param::Symbol(symbol)] => write!(f, "{}", symbol)?,
+            params => {
+                write!(f, "(")?;
+                if let Some((last, rest)) = params.split_last() {
+                    for param in rest {
+                        write!(f, "{}, ", param)?;
+                    }
+                    write!(f, "{}", last)?;
+                }
+                write!(f, ")")?;
+            },
+        }
+        write!(f, " -> {}", self.body)
+    }
 }
 
 /// A function available for use in a context.
@@ -64,7 +189,43 @@ pub enum Func {
     Builtin(Arc<dyn Builtin>),
 
     /// A user-defined function.
-    UserFunc(UserFunc),
+    UserFunc(Arc<UserFunc>),
+
+    /// A lambda, which can only be stored in a variable as a [`Function`].
+    Lambda(Arc<Lambda>),
+}
+
+impl PartialEq for Func {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // builtins are compared by identity, ignoring the vtable
+            (Func::Builtin(a), Func::Builtin(b)) => std::ptr::addr_eq(Arc::as_ptr(a), Arc::as_ptr(b)),
+            (Func::UserFunc(a), Func::UserFunc(b)) => a == b,
+            (Func::Lambda(a), Func::Lambda(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// A function used as a value, such as `sqrt` in `map(sqrt, [1, 4, 9])`, or the lambda `x -> x^2`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Function {
+    /// The name of the function, or [`None`] if it is a lambda.
+    pub name: Option<String>,
+
+    /// The implementation of the function.
+    pub func: Func,
+}
+
+impl std::fmt::Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.name, &self.func) {
+            (_, Func::Lambda(lambda)) => write!(f, "{}", lambda),
+            (Some(name), _) => write!(f, "{}", name),
+            (None, _) => write!(f, "<function>"),
+        }
+    }
 }
 
 impl From<Box<dyn Builtin>> for Func {
@@ -75,29 +236,66 @@ impl From<Box<dyn Builtin>> for Func {
 
 impl From<UserFunc> for Func {
     fn from(user_func: UserFunc) -> Self {
-        Func::UserFunc(user_func)
+        Func::UserFunc(Arc::new(user_func))
     }
 }
 
 /// A context to use when evaluating an expression, containing variables and functions that can be
 /// used within the expression.
+///
+/// The global variables, functions and local [`Scope`] are shared between clones of a context
+/// until one of them is modified, so cloning a context is cheap. Calls to user-defined functions
+/// and lambdas do not clone the context at all; they push a new local scope, evaluate their body in
+/// it, then pop it.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ctxt {
-    /// The variables in the context.
-    vars: HashMap<String, Value>,
+    /// The global variables in the context.
+    vars: Arc<HashMap<String, Value>>,
+
+    /// The local scope of the function call being evaluated, or [`None`] at the top level.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    scope: Option<Arc<Scope>>,
 
     /// The functions in the context.
-    funcs: HashMap<String, Func>,
+    funcs: Arc<HashMap<String, Func>>,
 
     /// The trigonometric mode of the context.
     pub trig_mode: TrigMode,
 
+    /// The number mode of the context.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub number_mode: NumberMode,
+
+    /// The number of bits of precision used to compute values in the context.
+    #[cfg_attr(feature = "serde", serde(default = "default_precision"))]
+    precision: u32,
+
+    /// The builtin constants that have been redefined as global variables, as a set of bits
+    /// indexed by the position of their names in [`CONSTANTS`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    redefined: u8,
+
     /// When true, a `break` expression was evaluated in the current loop. The evaluator should
     /// stop and propogate the value of the `break` expression.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) break_loop: bool,
 
+    /// When true, a `return` expression was evaluated in the current function call.
+    ///
+    /// [`Ctxt::break_loop`] is also set, so that the evaluator stops in the same way as for a
+    /// `break` expression. However, loops continue to propogate the value of the `return`
+    /// expression, until it reaches the function call.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) return_call: bool,
+
+    /// When true, a `continue` expression was evaluated in the current loop.
+    ///
+    /// [`Ctxt::break_loop`] is also set, so that the evaluator stops evaluating the body of the
+    /// loop. The loop then resets both flags and continues with its next iteration.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) continue_loop: bool,
+
     /// The current depth of the stack. This is used to detect stack overflows.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) stack_depth: usize,
@@ -105,26 +303,92 @@ pub struct Ctxt {
     /// TODO: Whether the maximum recursion depth was reached while evaluating an expression.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) max_depth_reached: bool,
+
+    /// The limits on the resources used by each evaluation using the context.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub budget: Budget,
+
+    /// The handle used to cancel evaluations using the context.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cancel: CancelHandle,
+
+    /// The resources used by the evaluation in progress, or [`None`] if the context is not being
+    /// used to evaluate anything.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    meter: Option<Arc<Meter>>,
+}
+
+/// Returns the default precision of a context, used when deserializing a context without one.
+#[cfg(feature = "serde")]
+fn default_precision() -> u32 {
+    PRECISION
+}
+
+/// The names of the builtin constants, which are defined in every [`Ctxt::default`].
+pub const CONSTANTS: [&str; 6] = ["i", "e", "inf", "phi", "pi", "tau"];
+
+/// Returns the value of the builtin constant with the given name, such as `pi` or `e`, or
+/// [`None`] if there is no such constant.
+///
+/// The value is computed with the current precision of the thread.
+pub fn constant(name: &str) -> Option<Value> {
+    Some(match name {
+        "i" => consts::i().into(),
+        "e" => consts::e().into(),
+        "inf" => consts::inf().into(),
+        "phi" => consts::phi().into(),
+        "pi" => consts::pi().into(),
+        "tau" => consts::tau().into(),
+        _ => return None,
+    })
+}
+
+/// Returns the bit of the builtin constant with the given name in [`Ctxt::redefined`], or
+/// [`None`] if there is no such constant.
+fn constant_bit(name: &str) -> Option<u8> {
+    CONSTANTS.iter().position(|&constant| constant == name).map(|idx| 1 << idx)
+}
+
+/// Returns the smallest interval containing the value of the builtin constant with the given name,
+/// or [`None`] if there is no such constant, or it is not a real number.
+///
+/// The interval is computed with the current precision of the thread.
+pub fn interval_constant(name: &str) -> Option<Interval> {
+    let two = Interval::point(consts::two());
+    Some(match name {
+        "e" => Interval::e(),
+        "phi" => Interval::from_integer(&5.into()).sqrt().add(&Interval::point(consts::one())).div(&two),
+        "pi" => Interval::pi(),
+        "tau" => Interval::pi().mul(&two),
+        _ => return None,
+    })
 }
 
 impl Default for Ctxt {
     fn default() -> Self {
         Self {
-            vars: HashMap::from([
-                ("i".to_string(), consts::I.clone().into()),
-                ("e".to_string(), consts::E.clone().into()),
-                ("phi".to_string(), consts::PHI.clone().into()),
-                ("pi".to_string(), consts::PI.clone().into()),
-                ("tau".to_string(), consts::TAU.clone().into()),
-            ]),
-            funcs: crate::funcs::all()
+            vars: Arc::new(with_precision(PRECISION, || {
+                CONSTANTS.iter()
+                    .map(|&name| (name.to_string(), constant(name).unwrap()))
+                    .collect()
+            })),
+            scope: None,
+            funcs: Arc::new(crate::funcs::all()
                 .into_iter()
                 .map(|(name, func)| (name.to_string(), func.into()))
-                .collect(),
+                .collect()),
             trig_mode: TrigMode::default(),
+            number_mode: NumberMode::default(),
+            precision: PRECISION,
+            redefined: 0,
             break_loop: false,
+            return_call: false,
+            continue_loop: false,
             stack_depth: 0,
             max_depth_reached: false,
+            budget: Budget::default(),
+            cancel: CancelHandle::default(),
+            meter: None,
         }
     }
 }
@@ -136,32 +400,207 @@ impl Ctxt {
     /// functions. Consider using the [`Default`] implementation instead.
     pub fn new() -> Ctxt {
         Ctxt {
-            vars: HashMap::new(),
-            funcs: HashMap::new(),
+            vars: Arc::default(),
+            funcs: Arc::default(),
             ..Default::default()
         }
     }
 
-    /// Add a variable to the context.
+    /// Returns the number of bits of precision used to compute values in the context.
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    /// Sets the number of bits of precision used to compute values in the context, clamped to be
+    /// between [`MIN_PRECISION`] and [`MAX_PRECISION`].
+    ///
+    /// The builtin constants, such as `pi`, are recomputed with the new precision, unless they
+    /// have been redefined. Other variables keep the precision they were computed with.
+    pub fn set_precision(&mut self, precision: u32) {
+        let precision = precision.clamp(MIN_PRECISION, MAX_PRECISION);
+        let constants = CONSTANTS.iter()
+            .filter(|name| self.is_constant(name))
+            .collect::<Vec<_>>();
+
+        self.precision = precision;
+        with_precision(precision, || {
+            for name in constants {
+                Arc::make_mut(&mut self.vars).insert(name.to_string(), constant(name).unwrap());
+            }
+        });
+    }
+
+    /// Returns a handle that can be used to cancel evaluations using the context, including from
+    /// another thread. The handle is shared by clones of the context.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    /// Evaluates `f` as a single evaluation, which is subject to the [`Budget`] of the context.
+    ///
+    /// If the context is already being used for an evaluation, `f` is part of that evaluation
+    /// instead, and shares its budget.
+    pub(crate) fn metered<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        if self.meter.is_some() {
+            return f(self);
+        }
+
+        self.meter = Some(Arc::new(Meter::start(&self.budget)));
+        let result = f(self);
+        self.meter = None;
+        result
+    }
+
+    /// Takes a step of the evaluation in progress, returning an error if the evaluation was
+    /// cancelled, or it has run out of steps or time. `span` is the span of the expression that is
+    /// about to be evaluated.
+    pub(crate) fn step(&self, span: impl FnOnce() -> Range<usize>) -> Result<(), Error> {
+        match &self.meter {
+            Some(meter) => meter.step(&self.budget, &self.cancel, span),
+            None => Ok(()),
+        }
+    }
+
+    /// Takes a step of the evaluation in progress from within a builtin function, returning an
+    /// error if the evaluation was cancelled, or it has run out of steps or time.
+    ///
+    /// Builtins that loop for a long time, such as `factorint`, call this on every iteration, so
+    /// that the [`Budget`] and [`CancelHandle`] of the context can stop them.
+    pub fn builtin_step(&self) -> Result<(), BuiltinError> {
+        self.step(|| 0..0).map_err(|err| BuiltinError::Interrupted(err.kind))
+    }
+
+    /// Add a variable to the current scope of the context, which is the local scope of the
+    /// function call being evaluated, or the global scope at the top level.
     pub fn add_var(&mut self, name: &str, value: Value) {
-        self.vars.insert(name.to_string(), value);
+        match &mut self.scope {
+            Some(scope) => Arc::make_mut(scope).add_var(name, value),
+            None => self.insert_global(name, value),
+        }
+    }
+
+    /// Assigns a new value to an existing variable, in the innermost scope that defines it. This
+    /// allows compound assignments like `count += 1` to modify a global variable from within a
+    /// function.
+    ///
+    /// Scopes captured by a lambda cannot be modified, so if the variable is defined in one, the
+    /// new value is added to the current scope instead. If the variable is not defined at all, it
+    /// is added to the current scope.
+    pub fn set_var(&mut self, name: &str, value: Value) {
+        match &mut self.scope {
+            Some(scope) if scope.get_var(name).is_some() || !self.vars.contains_key(name) => {
+                Arc::make_mut(scope).add_var(name, value);
+            },
+            _ => self.insert_global(name, value),
+        }
+    }
+
+    /// Inserts a global variable into the context, marking it as redefined if it is a builtin
+    /// constant.
+    fn insert_global(&mut self, name: &str, value: Value) {
+        if let Some(bit) = constant_bit(name) {
+            self.redefined |= bit;
+        }
+        Arc::make_mut(&mut self.vars).insert(name.to_string(), value);
+    }
+
+    /// Returns a reference to the value of a variable in the context, looking in the current
+    /// scope first, then in the global scope.
+    pub(crate) fn lookup_var(&self, name: &str) -> Option<&Value> {
+        self.scope.as_ref()
+            .and_then(|scope| scope.get_var(name))
+            .or_else(|| self.vars.get(name))
     }
 
     /// Get the value of a variable in the context.
     pub fn get_var(&self, name: &str) -> Option<Value> {
-        self.vars.get(name).cloned()
+        self.lookup_var(name).cloned()
     }
 
-    /// Returns the variables in the context.
+    /// Returns true if the variable with the given name holds the value of the builtin constant
+    /// of the same name, i.e. it has not been redefined or shadowed by a local variable.
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.scope.as_ref().is_none_or(|scope| scope.get_var(name).is_none())
+            && self.is_global_constant(name)
+    }
+
+    /// Returns true if the global variable with the given name holds the value of the builtin
+    /// constant of the same name, ignoring the current scope.
+    pub(crate) fn is_global_constant(&self, name: &str) -> bool {
+        constant_bit(name)
+            .is_some_and(|bit| self.redefined & bit == 0 && self.vars.contains_key(name))
+    }
+
+    /// Returns the global variables in the context.
     pub fn get_vars(&self) -> &HashMap<String, Value> {
         &self.vars
     }
 
+    /// Returns the current local scope, shared, for a lambda to capture.
+    pub fn capture_scope(&self) -> Option<Arc<Scope>> {
+        self.scope.clone()
+    }
+
+    /// Pushes the given scope as the current local scope, evaluates `f`, then pops it, restoring
+    /// the previous scope. Variables defined while evaluating `f` are discarded with the scope.
+    pub fn with_scope<T>(&mut self, scope: Scope, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = self.scope.replace(Arc::new(scope));
+        let result = f(self);
+        self.scope = outer;
+        result
+    }
+
+    /// Replaces the current local scope with the given scope, returning the previous one.
+    pub(crate) fn replace_scope(&mut self, scope: Option<Arc<Scope>>) -> Option<Arc<Scope>> {
+        std::mem::replace(&mut self.scope, scope)
+    }
+
+    /// Get the value of a global variable in the context, ignoring the current scope.
+    pub(crate) fn get_global(&self, name: &str) -> Option<&Value> {
+        self.vars.get(name)
+    }
+
+    /// Assigns a value to a global variable in the context, ignoring the current scope.
+    pub(crate) fn set_global(&mut self, name: &str, value: Value) {
+        if let Some(bit) = constant_bit(name) {
+            self.redefined |= bit;
+        }
+        let vars = Arc::make_mut(&mut self.vars);
+        match vars.get_mut(name) {
+            Some(var) => *var = value,
+            None => {
+                vars.insert(name.to_string(), value);
+            },
+        }
+    }
+
     /// Add a function to the context.
+    ///
+    /// At the top level, the function is added to the global functions, and a variable of the same
+    /// name that holds a [`Function`] is removed, so that it does not shadow the new function.
+    /// Within a function call, the function is instead added to the local scope as a variable
+    /// holding a [`Lambda`], which captures the scope like any other lambda.
     pub fn add_func(&mut self, header: FuncHeader, body: Expr, recursive: bool) {
-        self.funcs.insert(
+        if self.scope.is_some() {
+            let function = Function {
+                name: Some(header.name.name.clone()),
+                func: Func::Lambda(Arc::new(Lambda {
+                    params: header.params,
+                    body,
+                    captures: self.capture_scope(),
+                })),
+            };
+            self.add_var(&header.name.name, Value::Function(function));
+            return;
+        }
+
+        if let Some(Value::Function(_)) = self.vars.get(&header.name.name) {
+            Arc::make_mut(&mut self.vars).remove(&header.name.name);
+        }
+        let memo = Memo::new(&header, &body, self);
+        Arc::make_mut(&mut self.funcs).insert(
             header.name.name.clone(),
-            Func::UserFunc(UserFunc { header, body, recursive }),
+            UserFunc { header, body, recursive, memo }.into(),
         );
     }
 
@@ -170,6 +609,27 @@ impl Ctxt {
         self.funcs.get(name)
     }
 
+    /// Returns the function with the given name as a value. A variable holding a [`Function`]
+    /// shadows a function of the same name.
+    pub fn get_function(&self, name: &str) -> Option<Function> {
+        match self.lookup_var(name) {
+            Some(Value::Function(function)) => Some(function.clone()),
+            _ => self.funcs.get(name).map(|func| Function {
+                name: Some(name.to_string()),
+                func: func.clone(),
+            }),
+        }
+    }
+
+    /// Returns the function with the given name at the top level, ignoring local variables. A
+    /// global variable holding a [`Function`] shadows a function of the same name.
+    pub(crate) fn get_global_func(&self, name: &str) -> Option<&Func> {
+        match self.vars.get(name) {
+            Some(Value::Function(function)) => Some(&function.func),
+            _ => self.funcs.get(name),
+        }
+    }
+
     /// Returns the functions in the context.
     pub fn get_funcs(&self) -> &HashMap<String, Func> {
         &self.funcs
@@ -2,6 +2,8 @@ use ariadne::Fmt;
 use cas_attrs::ErrorKind;
 use cas_error::EXPR;
 use cas_parser::parser::token::op::{BinOpKind, UnaryOpKind};
+use rug::Integer;
+use std::time::Duration;
 
 /// The given binary operation cannot be applied to the given operands.
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
@@ -53,6 +55,194 @@ pub struct InvalidUnaryOperation {
 )]
 pub struct BitshiftOverflow;
 
+/// Attempted to compare two intervals that overlap, so the result of the comparison is unknown.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("cannot determine the result of the `{:?}` operator", self.op),
+    labels = ["this interval", "", "overlaps with this interval"],
+    help = format!(
+        "the exact values could compare either way; increase the precision, or use {} to check if the intervals overlap",
+        "~==".fg(EXPR),
+    ),
+)]
+pub struct OverlappingIntervals {
+    /// The comparison operator that was used.
+    pub op: BinOpKind,
+}
+
+/// Attempted to apply a binary operator element-wise to two lists of different lengths.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("cannot apply the `{:?}` operator to lists of different lengths", self.op),
+    labels = [
+        format!("this list has {} element{}", self.left, if self.left == 1 { "" } else { "s" }),
+        "this operator".to_string(),
+        format!("this list has {} element{}", self.right, if self.right == 1 { "" } else { "s" }),
+    ],
+    help = "operators are applied element-wise to lists, so both lists must have the same length",
+)]
+pub struct ListLengthMismatch {
+    /// The operator that was used.
+    pub op: BinOpKind,
+
+    /// The length of the left list.
+    pub left: usize,
+
+    /// The length of the right list.
+    pub right: usize,
+}
+
+/// Attempted to apply a binary operator to matrices with incompatible dimensions.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("cannot apply the `{:?}` operator to matrices of these dimensions", self.op),
+    labels = [
+        format!("this matrix is {}x{}", self.left.0, self.left.1),
+        "this operator".to_string(),
+        format!("this matrix is {}x{}", self.right.0, self.right.1),
+    ],
+    help = match self.op {
+        BinOpKind::Mul => "to multiply two matrices, the first must have as many columns as the second has rows",
+        _ => "matrices can only be added or subtracted if they have the same dimensions",
+    },
+)]
+pub struct MatrixDimensionMismatch {
+    /// The operator that was used.
+    pub op: BinOpKind,
+
+    /// The number of rows and columns of the left matrix. A vector on the left is a row vector.
+    pub left: (usize, usize),
+
+    /// The number of rows and columns of the right matrix. A vector on the right is a column
+    /// vector.
+    pub right: (usize, usize),
+}
+
+/// Attempted to raise a matrix that is not square to a power, or a singular matrix to a negative
+/// power.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "cannot raise this matrix to this power",
+    labels = [
+        if self.square {
+            "this matrix is singular".to_string()
+        } else {
+            format!("this matrix is {}x{}", self.rows, self.cols)
+        },
+        "".to_string(),
+        "this power".to_string(),
+    ],
+    help = if self.square {
+        "a singular matrix has no inverse, so it cannot be raised to a negative power"
+    } else {
+        "only square matrices can be raised to a power"
+    },
+)]
+pub struct InvalidMatrixPower {
+    /// Whether the matrix is square; if so, it is singular and the power is negative.
+    pub square: bool,
+
+    /// The number of rows of the matrix.
+    pub rows: usize,
+
+    /// The number of columns of the matrix.
+    pub cols: usize,
+}
+
+/// Tried to index into a value that is not a list.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("cannot index into a value of type `{}`", self.expr_type),
+    labels = [
+        format!("this expression has type `{}`", self.expr_type),
+        "this index".to_string(),
+    ],
+    help = "only lists and matrices can be indexed",
+)]
+pub struct InvalidIndexTarget {
+    /// The type the indexed expression evaluated to.
+    pub expr_type: &'static str,
+}
+
+/// Tried to iterate over a value that is not a range or a list in a `for` loop.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("cannot iterate over a value of type `{}`", self.expr_type),
+    labels = [format!("this expression has type `{}`", self.expr_type)],
+    help = format!("only ranges and lists can be iterated over, such as {}", "0..10".fg(EXPR)),
+)]
+pub struct NotIterable {
+    /// The type the iterated expression evaluated to.
+    pub expr_type: &'static str,
+}
+
+/// An index or slice bound is not an integer.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "list indices must be integers",
+    labels = [format!("this index has type `{}`", self.expr_type)],
+    help = format!(
+        "indices start at `0`, and negative indices count from the end of the list, such as {}",
+        "l[-1]".fg(EXPR),
+    ),
+)]
+pub struct InvalidIndexType {
+    /// The type the index evaluated to.
+    pub expr_type: &'static str,
+}
+
+/// The index is outside the bounds of the list.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("index `{}` is out of bounds", self.index),
+    labels = [
+        format!("this list has {} element{}", self.len, if self.len == 1 { "" } else { "s" }),
+        "this index".to_string(),
+    ],
+    help = if self.len == 0 {
+        "the list is empty, so it has no valid indices".to_string()
+    } else {
+        format!("the valid indices for this list are `{}` to `{}`", -(self.len as i128), self.len - 1)
+    },
+)]
+pub struct IndexOutOfBounds {
+    /// The index that was given, before negative indices are counted from the end of the list.
+    pub index: String,
+
+    /// The length of the list.
+    pub len: usize,
+}
+
+/// Tried to assign a value that is not a list to a slice.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "only a list can be assigned to a slice",
+    labels = [
+        "this slice".to_string(),
+        format!("this expression has type `{}`", self.expr_type),
+    ],
+    help = format!(
+        "to replace the elements of a slice, assign a list to it, such as {}",
+        "l[1:3] = [0, 0]".fg(EXPR),
+    ),
+)]
+pub struct InvalidSliceAssignment {
+    /// The type the assigned expression evaluated to.
+    pub expr_type: &'static str,
+}
+
+/// Tried to use a compound assignment operator on a slice, or to assign to an element of a slice.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "cannot assign to this slice",
+    labels = ["this slice"],
+    help = format!(
+        "a slice can only be replaced as a whole with the standard assignment operator, such as {}",
+        "l[1:3] = [0, 0]".fg(EXPR),
+    ),
+)]
+pub struct UnsupportedSliceAssignment;
+
 /// The variable is undefined.
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
 #[error(
@@ -150,6 +340,67 @@ pub struct MissingArgument {
     pub signature: String,
 }
 
+/// A named argument of a function call does not match any parameter of the function.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("unknown argument `{}` for the `{}` function", self.argument, self.name),
+    labels = ["this function call", "", "this argument"],
+    help = if self.params.is_empty() {
+        format!("the `{}` function takes no arguments", (&self.name).fg(EXPR))
+    } else {
+        format!(
+            "the parameters of the `{}` function are named: {}",
+            (&self.name).fg(EXPR),
+            self.params.iter().map(|param| format!("`{}`", param)).collect::<Vec<_>>().join(", ")
+        )
+    },
+    note = format!("function signature: {}", self.signature),
+)]
+pub struct UnknownArgument {
+    /// The name of the function that was called.
+    pub name: String,
+
+    /// The name of the argument that was given.
+    pub argument: String,
+
+    /// The names of the parameters of the function.
+    pub params: Vec<String>,
+
+    /// The signature of the function.
+    pub signature: String,
+}
+
+/// Named arguments were given to a builtin function that does not accept them.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("the `{}` function does not accept named arguments", self.name),
+    labels = ["this function call", "", "this argument"],
+    help = format!("give the arguments of the `{}` function by position instead", (&self.name).fg(EXPR)),
+)]
+pub struct NamedArgumentsUnsupported {
+    /// The name of the function that was called.
+    pub name: String,
+}
+
+/// A parameter of a function was given more than one argument in a function call.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("argument `{}` was given more than once to the `{}` function", self.argument, self.name),
+    labels = ["this function call", "", "first given here", "given again here"],
+    help = format!("each parameter of the `{}` function can only be given one argument", (&self.name).fg(EXPR)),
+    note = format!("function signature: {}", self.signature),
+)]
+pub struct DuplicateArgument {
+    /// The name of the function that was called.
+    pub name: String,
+
+    /// The name of the parameter that was given more than one argument.
+    pub argument: String,
+
+    /// The signature of the function.
+    pub signature: String,
+}
+
 /// An argument to a function call has the wrong type.
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
 #[error(
@@ -192,6 +443,85 @@ pub struct TypeMismatch {
 )]
 pub struct StackOverflow;
 
+/// The evaluation took more steps than its budget allows.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "maximum number of evaluation steps exceeded",
+    labels = ["evaluation stopped here"],
+    help = format!("the maximum number of steps is: `{}`", self.limit),
+    note = "this can happen if a loop never ends, such as `loop {}`",
+)]
+pub struct StepLimitExceeded {
+    /// The maximum number of steps.
+    pub limit: u64,
+}
+
+/// The evaluation took longer than its budget allows.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "evaluation took too long",
+    labels = ["evaluation stopped here"],
+    help = format!("the time limit is: `{:?}`", self.timeout),
+)]
+pub struct TimeLimitExceeded {
+    /// The time limit of the evaluation.
+    pub timeout: Duration,
+}
+
+/// An integer larger than the budget of the evaluation allows was, or would have been, produced.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "integer is too large",
+    labels = ["this expression produces an integer that is too large"],
+    help = format!("integers can have at most `{}` bits", self.limit),
+)]
+pub struct IntegerLimitExceeded {
+    /// The maximum number of bits in an integer.
+    pub limit: u64,
+}
+
+/// A list longer than the budget of the evaluation allows was, or would have been, produced.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "list is too long",
+    labels = [format!("this expression produces a list of {} elements", self.len)],
+    help = format!("lists can have at most `{}` elements", self.limit),
+)]
+pub struct ListLimitExceeded {
+    /// The length of the list.
+    pub len: Integer,
+
+    /// The maximum length of a list.
+    pub limit: usize,
+}
+
+/// The evaluation was cancelled with its [`CancelHandle`](crate::numerical::budget::CancelHandle).
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "evaluation was cancelled",
+    labels = ["evaluation stopped here"],
+)]
+pub struct Cancelled;
+
+/// A builtin function without an interval implementation was called in interval mode, so its
+/// result cannot be enclosed in an interval.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = format!("the `{}` function does not support interval arithmetic", self.name),
+    labels = ["this function call", ""],
+    help = format!(
+        "these functions support interval arithmetic: {}",
+        crate::funcs::interval::SUPPORTED.iter()
+            .map(|name| format!("`{}`", name.fg(EXPR)))
+            .collect::<Vec<_>>()
+            .join(", "),
+    ),
+)]
+pub struct IntervalUnsupported {
+    /// The name of the function that was called.
+    pub name: String,
+}
+
 /// Tried to compute the derivative of a function that does not have a single parameter.
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
 #[error(
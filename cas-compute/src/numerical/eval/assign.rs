@@ -1,16 +1,28 @@
 use cas_parser::parser::{
-    ast::{assign::{Assign, AssignTarget}, literal::LitSym},
+    ast::{assign::{Assign, AssignTarget}, expr::Expr, index::Index, literal::{Literal, LitSym}},
     token::op::AssignOpKind,
 };
 use crate::eval_break;
 use crate::numerical::{
-    ctxt::Ctxt,
-    error::{kind::UndefinedVariable, Error},
-    eval::{binary::eval_operands, Eval},
+    budget::Budget,
+    ctxt::{Ctxt, Function, NumberMode},
+    error::{
+        kind::{InvalidSliceAssignment, UndefinedVariable, UnsupportedSliceAssignment},
+        Error,
+    },
+    eval::{
+        binary::{check_operands, eval_operands_in},
+        index::{invalid_target, position_with, Position},
+        Eval,
+    },
     value::Value,
 };
 
 /// Evaluate an assignment expression to a symbol.
+///
+/// A plain assignment defines the variable in the current scope, so assigning to a variable within
+/// a function creates a local variable. A compound assignment modifies the variable in the scope
+/// that defines it.
 fn assign_to_symbol(
     lit_sym: &LitSym,
     assign: &Assign,
@@ -20,14 +32,24 @@ fn assign_to_symbol(
     let rhs = eval_break!(assign.value, ctxt);
     match op {
         AssignOpKind::Assign => {
+            // a lambda assigned to a symbol takes its name, so that it can call itself by name
+            // from any scope
+            let rhs = match rhs {
+                Value::Function(Function { name: None, func }) => Value::Function(Function {
+                    name: Some(lit_sym.name.clone()),
+                    func,
+                }),
+                rhs => rhs,
+            };
             ctxt.add_var(&lit_sym.name, rhs.clone());
             Ok(rhs)
         },
         compound => {
             if let Some(lhs) = ctxt.get_var(&lit_sym.name) {
-                let new_lhs = eval_operands(compound.into(), false, lhs, rhs)
+                check_operands(&ctxt.budget, compound.into(), &lhs, &rhs, || assign.span.clone())?;
+                let new_lhs = eval_operands_in(ctxt.number_mode, compound.into(), false, lhs, rhs)
                     .map_err(|e| e.into_error(assign))?;
-                ctxt.add_var(&lit_sym.name, new_lhs.clone());
+                ctxt.set_var(&lit_sym.name, new_lhs.clone());
                 Ok(new_lhs)
             } else {
                 Err(Error::new(
@@ -39,6 +61,108 @@ fn assign_to_symbol(
     }
 }
 
+/// Returns the symbol holding the list that an index assignment target refers to, and the chain of
+/// indices of the target, starting from the one applied directly to the symbol.
+pub(crate) fn index_chain(index: &Index) -> (&LitSym, Vec<&Index>) {
+    let mut chain = vec![index];
+    while let Expr::Index(inner) = &*chain[chain.len() - 1].target {
+        chain.push(inner);
+    }
+    chain.reverse();
+
+    let Expr::Literal(Literal::Symbol(lit_sym)) = &*chain[0].target else {
+        unreachable!("the parser only accepts indices into symbols as assignment targets");
+    };
+    (lit_sym, chain)
+}
+
+/// Assigns `rhs` to the element or slice of `root` that the chain of indices refers to, using `eval`
+/// to evaluate the index expressions in order. Returns the value of the assignment.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn assign_to_element(
+    root: &mut Value,
+    chain: &[&Index],
+    assign: &Assign,
+    op: AssignOpKind,
+    rhs: Value,
+    budget: &Budget,
+    mode: NumberMode,
+    mut eval: impl FnMut(&Expr) -> Result<Value, Error>,
+) -> Result<Value, Error> {
+    let (last, rest) = chain.split_last().unwrap();
+    let mut target = root;
+    for index in rest {
+        let Value::List(values) = target else {
+            return Err(invalid_target(index, target));
+        };
+        match position_with(index, values.len(), &mut eval)? {
+            Position::Single(i) => target = &mut values[i],
+            Position::Slice(_) => return Err(Error::new(
+                vec![index.bracket_span.clone()],
+                UnsupportedSliceAssignment,
+            )),
+        }
+    }
+
+    let Value::List(values) = target else {
+        return Err(invalid_target(last, target));
+    };
+    let result = match (position_with(last, values.len(), &mut eval)?, op) {
+        (Position::Single(i), AssignOpKind::Assign) => {
+            values[i] = rhs.clone();
+            rhs
+        },
+        (Position::Single(i), compound) => {
+            check_operands(budget, compound.into(), &values[i], &rhs, || assign.span.clone())?;
+            let lhs = std::mem::replace(&mut values[i], Value::Unit);
+            let new_lhs = eval_operands_in(mode, compound.into(), false, lhs, rhs)
+                .map_err(|e| e.into_error(assign))?;
+            values[i] = new_lhs.clone();
+            new_lhs
+        },
+        (Position::Slice(range), AssignOpKind::Assign) => match rhs {
+            Value::List(new_values) => {
+                values.splice(range, new_values.iter().cloned());
+                Value::List(new_values)
+            },
+            rhs => return Err(Error::new(
+                vec![last.bracket_span.clone(), assign.value.span()],
+                InvalidSliceAssignment { expr_type: rhs.typename() },
+            )),
+        },
+        (Position::Slice(_), _) => return Err(Error::new(
+            vec![last.bracket_span.clone()],
+            UnsupportedSliceAssignment,
+        )),
+    };
+    Ok(result)
+}
+
+/// Evaluate an assignment expression to an element or slice of a list stored in a symbol. The list
+/// is modified in the scope that defines it.
+fn assign_to_index(
+    index: &Index,
+    assign: &Assign,
+    op: AssignOpKind,
+    ctxt: &mut Ctxt,
+) -> Result<Value, Error> {
+    let rhs = eval_break!(assign.value, ctxt);
+
+    let (lit_sym, chain) = index_chain(index);
+    let mut root = ctxt.get_var(&lit_sym.name).ok_or_else(|| Error::new(
+        vec![lit_sym.span.clone()],
+        UndefinedVariable { name: lit_sym.name.clone() },
+    ))?;
+
+    let (budget, mode) = (ctxt.budget, ctxt.number_mode);
+    let result = assign_to_element(&mut root, &chain, assign, op, rhs, &budget, mode, |expr| {
+        expr.eval(ctxt)
+    })?;
+
+    ctxt.set_var(&lit_sym.name, root);
+    Ok(result)
+}
+
 impl Eval for Assign {
     fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
         match &self.target {
@@ -46,6 +170,10 @@ impl Eval for Assign {
                 // variable assignment
                 assign_to_symbol(symbol, self, self.op.kind, ctxt)
             },
+            AssignTarget::Index(index) => {
+                // list element or slice assignment
+                assign_to_index(index, self, self.op.kind, ctxt)
+            },
             AssignTarget::Func(header) => {
                 // function assignment
                 ctxt.add_func(
@@ -1,13 +1,28 @@
 use cas_parser::parser::{ast::binary::Binary, token::op::BinOpKind};
-use rug::ops::Pow;
+use rug::{ops::Pow, Rational};
 use crate::eval_break;
 use crate::numerical::{
-    ctxt::Ctxt,
-    error::{kind::{BitshiftOverflow, InvalidBinaryOperation}, Error},
-    eval::{error::EvalError, Eval},
+    budget::{rational_bits, Budget},
+    ctxt::{Ctxt, NumberMode},
+    error::{
+        kind::{
+            BitshiftOverflow,
+            InvalidBinaryOperation,
+            InvalidMatrixPower,
+            ListLengthMismatch,
+            MatrixDimensionMismatch,
+            OverlappingIntervals,
+        },
+        Error,
+    },
+    eval::{check_operand, error::EvalError, Eval},
+    matrix::Matrix,
+    range::Range,
     value::Value,
 };
-use crate::primitive::{int_from_float, float};
+use crate::primitive::{int, int_from_float, float};
+use crate::symbolic::{expr::Expr as SymExpr, simplify, simplify::fraction::make_fraction};
+use std::{cmp::Ordering, sync::Arc};
 
 /// Evaluates a binary expression with two integer operands.
 fn eval_integer_operands(
@@ -29,6 +44,9 @@ fn eval_integer_operands(
 
                 // `right` is a positive integer
                 Value::Integer(left.pow(u32::from(right)))
+            } else if let Some(right) = right.to_i16().filter(|_| !left.is_zero()) {
+                // a negative exponent results in the exact reciprocal of the power
+                Value::from(Rational::from((int(1), left.pow(u32::from(right.unsigned_abs())))))
             } else {
                 // otherwise, use the `Float` implementation, which will be faster, but can lose
                 // precision
@@ -36,7 +54,11 @@ fn eval_integer_operands(
             }
         },
         BinOpKind::Mul => Value::Integer(left * right),
-        BinOpKind::Div => Value::Float(float(left) / float(right)),
+        BinOpKind::Div => if right.is_zero() {
+            Value::Float(float(left) / float(right))
+        } else {
+            Value::from(Rational::from((left, right)))
+        },
         BinOpKind::Mod => Value::Integer(left % right),
         BinOpKind::Add => Value::Integer(left + right),
         BinOpKind::Sub => Value::Integer(left - right),
@@ -52,6 +74,8 @@ fn eval_integer_operands(
         BinOpKind::NotEq => Value::Boolean(left != right),
         BinOpKind::ApproxEq => Value::Boolean((left - right).abs() < 1e-6),
         BinOpKind::ApproxNotEq => Value::Boolean((left - right).abs() >= 1e-6),
+        BinOpKind::RangeExclusive => Value::Range(Range::new(left, right, false)),
+        BinOpKind::RangeInclusive => Value::Range(Range::new(left, right, true)),
         BinOpKind::And | BinOpKind::Or => Err(InvalidBinaryOperation {
             op,
             implicit,
@@ -61,6 +85,72 @@ fn eval_integer_operands(
     })
 }
 
+/// Evaluates a binary expression with two rational operands.
+///
+/// Arithmetic is exact, except for powers with a non-integer exponent, which are computed with
+/// floating-point numbers. Bitwise operators are also computed with floating-point numbers, as
+/// they would be for [`Value::Float`] operands.
+fn eval_rational_operands(
+    op: BinOpKind,
+    implicit: bool,
+    left: Value,
+    right: Value,
+) -> Result<Value, EvalError> {
+    let typename = left.typename();
+    let (Value::Rational(left), Value::Rational(right)) = (left, right) else {
+        unreachable!()
+    };
+    let to_float = |left: Rational, right: Rational| {
+        eval_real_operands(op, implicit, Value::Float(float(left)), Value::Float(float(right)))
+    };
+    Ok(match op {
+        BinOpKind::Exp => {
+            // `right` is an integer if its denominator is `1`
+            let exponent = (*right.denom() == 1)
+                .then(|| right.numer().to_i16())
+                .flatten()
+                .filter(|exp| *exp >= 0 || left != 0);
+            match exponent {
+                Some(exp) => Value::from(left.pow(i32::from(exp))),
+                None => return to_float(left, right),
+            }
+        },
+        BinOpKind::Mul => Value::from(left * right),
+        BinOpKind::Div => if right == 0 {
+            return to_float(left, right);
+        } else {
+            Value::from(left / right)
+        },
+        BinOpKind::Mod => if right == 0 {
+            return to_float(left, right);
+        } else {
+            // truncated remainder, matching `%` for integers
+            let quotient = Rational::from(&left / &right).trunc();
+            Value::from(left - right * quotient)
+        },
+        BinOpKind::Add => Value::from(left + right),
+        BinOpKind::Sub => Value::from(left - right),
+        BinOpKind::Greater => Value::Boolean(left > right),
+        BinOpKind::GreaterEq => Value::Boolean(left >= right),
+        BinOpKind::Less => Value::Boolean(left < right),
+        BinOpKind::LessEq => Value::Boolean(left <= right),
+        BinOpKind::Eq => Value::Boolean(left == right),
+        BinOpKind::NotEq => Value::Boolean(left != right),
+        BinOpKind::ApproxEq => Value::Boolean((left - right).abs() < 1e-6),
+        BinOpKind::ApproxNotEq => Value::Boolean((left - right).abs() >= 1e-6),
+        BinOpKind::BitRight | BinOpKind::BitLeft | BinOpKind::BitAnd | BinOpKind::BitOr => {
+            return to_float(left, right);
+        },
+        BinOpKind::And | BinOpKind::Or
+            | BinOpKind::RangeExclusive | BinOpKind::RangeInclusive => Err(InvalidBinaryOperation {
+            op,
+            implicit,
+            left: typename,
+            right: typename,
+        })?
+    })
+}
+
 /// Evaluates a binary expression with two real operands.
 fn eval_real_operands(
     op: BinOpKind,
@@ -91,7 +181,8 @@ fn eval_real_operands(
         BinOpKind::NotEq => Value::Boolean(left != right),
         BinOpKind::ApproxEq => Value::Boolean((left - right).abs() < 1e-6),
         BinOpKind::ApproxNotEq => Value::Boolean((left - right).abs() >= 1e-6),
-        BinOpKind::And | BinOpKind::Or => Err(InvalidBinaryOperation {
+        BinOpKind::And | BinOpKind::Or
+            | BinOpKind::RangeExclusive | BinOpKind::RangeInclusive => Err(InvalidBinaryOperation {
             op,
             implicit,
             left: typename,
@@ -100,6 +191,50 @@ fn eval_real_operands(
     })
 }
 
+/// Evaluates a binary expression with two interval operands.
+///
+/// Ordering comparisons only succeed if the intervals do not overlap, since the exact values they
+/// enclose could otherwise compare either way. `~==` instead checks whether the intervals overlap.
+fn eval_interval_operands(
+    op: BinOpKind,
+    implicit: bool,
+    left: Value,
+    right: Value,
+) -> Result<Value, EvalError> {
+    let typename = left.typename();
+    let (Value::Interval(left), Value::Interval(right)) = (left, right) else {
+        unreachable!()
+    };
+    let compare = |expected: &[Ordering]| {
+        left.compare(&right)
+            .map(|ord| Value::Boolean(expected.contains(&ord)))
+            .ok_or(OverlappingIntervals { op })
+    };
+    Ok(match op {
+        BinOpKind::Exp => Value::Interval(left.pow(&right)),
+        BinOpKind::Mul => Value::Interval(left.mul(&right)),
+        BinOpKind::Div => Value::Interval(left.div(&right)),
+        BinOpKind::Add => Value::Interval(left.add(&right)),
+        BinOpKind::Sub => Value::Interval(left.sub(&right)),
+        BinOpKind::Greater => compare(&[Ordering::Greater])?,
+        BinOpKind::GreaterEq => compare(&[Ordering::Greater, Ordering::Equal])?,
+        BinOpKind::Less => compare(&[Ordering::Less])?,
+        BinOpKind::LessEq => compare(&[Ordering::Less, Ordering::Equal])?,
+        BinOpKind::Eq => compare(&[Ordering::Equal])?,
+        BinOpKind::NotEq => compare(&[Ordering::Less, Ordering::Greater])?,
+        BinOpKind::ApproxEq => Value::Boolean(left.overlaps(&right)),
+        BinOpKind::ApproxNotEq => Value::Boolean(!left.overlaps(&right)),
+        BinOpKind::And | BinOpKind::Or | BinOpKind::Mod
+            | BinOpKind::BitRight | BinOpKind::BitLeft | BinOpKind::BitAnd | BinOpKind::BitOr
+            | BinOpKind::RangeExclusive | BinOpKind::RangeInclusive => Err(InvalidBinaryOperation {
+                op,
+                implicit,
+                left: typename,
+                right: typename,
+            })?
+    })
+}
+
 /// Evaluates a binary expression with two complex operands.
 fn eval_complex_operands(
     op: BinOpKind,
@@ -135,7 +270,8 @@ fn eval_complex_operands(
         },
         BinOpKind::And | BinOpKind::Or | BinOpKind::Mod
             | BinOpKind::BitRight | BinOpKind::BitLeft | BinOpKind::BitAnd | BinOpKind::BitOr
-            | BinOpKind::Greater | BinOpKind::GreaterEq | BinOpKind::Less | BinOpKind::LessEq => Err(InvalidBinaryOperation {
+            | BinOpKind::Greater | BinOpKind::GreaterEq | BinOpKind::Less | BinOpKind::LessEq
+            | BinOpKind::RangeExclusive | BinOpKind::RangeInclusive => Err(InvalidBinaryOperation {
                 op,
                 implicit,
                 left: typename,
@@ -165,7 +301,7 @@ fn eval_bool_operands(
         BinOpKind::Exp | BinOpKind::Mul | BinOpKind::Div | BinOpKind::Mod | BinOpKind::Add
             | BinOpKind::Sub | BinOpKind::BitRight | BinOpKind::BitLeft | BinOpKind::BitAnd
             | BinOpKind::BitOr | BinOpKind::Greater | BinOpKind::GreaterEq | BinOpKind::Less
-            | BinOpKind::LessEq => Err(InvalidBinaryOperation {
+            | BinOpKind::LessEq | BinOpKind::RangeExclusive | BinOpKind::RangeInclusive => Err(InvalidBinaryOperation {
                 op,
                 implicit,
                 left: typename,
@@ -190,7 +326,8 @@ fn eval_unit_operands(
         BinOpKind::NotEq | BinOpKind::ApproxNotEq | BinOpKind::Greater | BinOpKind::Less => Value::Boolean(false),
         BinOpKind::Exp | BinOpKind::Mul | BinOpKind::Div | BinOpKind::Mod | BinOpKind::Add
             | BinOpKind::Sub | BinOpKind::BitRight | BinOpKind::BitLeft | BinOpKind::BitAnd
-            | BinOpKind::BitOr | BinOpKind::And | BinOpKind::Or => Err(InvalidBinaryOperation {
+            | BinOpKind::BitOr | BinOpKind::And | BinOpKind::Or
+            | BinOpKind::RangeExclusive | BinOpKind::RangeInclusive => Err(InvalidBinaryOperation {
                 op,
                 implicit,
                 left: typename,
@@ -199,21 +336,183 @@ fn eval_unit_operands(
     })
 }
 
+/// Evaluates a binary expression where at least one operand is a symbolic expression. The other
+/// operand must be a number or an expression.
+fn eval_expr_operands(
+    op: BinOpKind,
+    implicit: bool,
+    left: Value,
+    right: Value,
+) -> Result<Value, EvalError> {
+    let error = InvalidBinaryOperation {
+        op,
+        implicit,
+        left: left.typename(),
+        right: right.typename(),
+    };
+    let (Some(left), Some(right)) = (left.into_expr(), right.into_expr()) else {
+        return Err(error.into());
+    };
+    let result = match op {
+        BinOpKind::Exp => SymExpr::Exp(Arc::new(left), Arc::new(right)),
+        BinOpKind::Mul => left * right,
+        BinOpKind::Div => make_fraction(left, right),
+        BinOpKind::Add => left + right,
+        BinOpKind::Sub => left + -right,
+        BinOpKind::Eq => return Ok(Value::Boolean(simplify(&left) == simplify(&right))),
+        BinOpKind::NotEq => return Ok(Value::Boolean(simplify(&left) != simplify(&right))),
+        _ => return Err(error.into()),
+    };
+    Ok(Value::from(simplify(&result)))
+}
+
+/// Evaluates a binary expression with at least one matrix operand.
+///
+/// Matrices of the same dimensions can be added, subtracted, and compared for equality, and
+/// matrices are multiplied with the usual matrix product. A list of numbers is treated as a column
+/// vector when multiplied on the right of a matrix, and as a row vector on the left, and the
+/// product is returned as a list. A matrix can also be multiplied or divided by a number, and a
+/// square matrix can be raised to an integer power.
+fn eval_matrix_operands(
+    op: BinOpKind,
+    implicit: bool,
+    left: Value,
+    right: Value,
+) -> Result<Value, EvalError> {
+    let error = InvalidBinaryOperation {
+        op,
+        implicit,
+        left: left.typename(),
+        right: right.typename(),
+    };
+    let dimensions = |left: &Matrix, right: &Matrix| MatrixDimensionMismatch {
+        op,
+        left: (left.rows(), left.cols()),
+        right: (right.rows(), right.cols()),
+    };
+
+    // a list of rows next to a matrix is treated as a matrix, and any other list as a vector
+    let operands = match (left, right) {
+        (Value::Matrix(left), right @ Value::List(_)) => (Value::Matrix(left), right.coerce_matrix()),
+        (left @ Value::List(_), Value::Matrix(right)) => (left.coerce_matrix(), Value::Matrix(right)),
+        operands => operands,
+    };
+
+    match operands {
+        (Value::Matrix(left), Value::Matrix(right)) => match op {
+            BinOpKind::Add => left.add(&right).ok_or_else(|| dimensions(&left, &right)),
+            BinOpKind::Sub => left.sub(&right).ok_or_else(|| dimensions(&left, &right)),
+            BinOpKind::Mul => left.mul(&right).ok_or_else(|| dimensions(&left, &right)),
+            BinOpKind::Eq => return Ok(Value::Boolean(left.equals(&right))),
+            BinOpKind::NotEq => return Ok(Value::Boolean(!left.equals(&right))),
+            _ => return Err(error.into()),
+        }.map(Value::Matrix).map_err(EvalError::from),
+        (Value::Matrix(left), Value::List(right)) if op == BinOpKind::Mul => {
+            let right = Matrix::column(right).ok_or(error)?;
+            left.mul(&right)
+                .map(|product| Value::List(product.into_entries()))
+                .ok_or_else(|| dimensions(&left, &right).into())
+        },
+        (Value::List(left), Value::Matrix(right)) if op == BinOpKind::Mul => {
+            let left = Matrix::column(left).ok_or(error)?.transpose();
+            left.mul(&right)
+                .map(|product| Value::List(product.into_entries()))
+                .ok_or_else(|| dimensions(&left, &right).into())
+        },
+        (Value::Matrix(matrix), Value::Integer(exp)) if op == BinOpKind::Exp => {
+            matrix.pow(&exp).map(Value::Matrix).ok_or_else(|| InvalidMatrixPower {
+                square: matrix.is_square(),
+                rows: matrix.rows(),
+                cols: matrix.cols(),
+            }.into())
+        },
+        (Value::Matrix(matrix), scalar) if scalar.is_complex()
+            && matches!(op, BinOpKind::Mul | BinOpKind::Div) => {
+            matrix.try_map(|entry| eval_operands(op, implicit, entry.clone(), scalar.clone()))
+                .map(Value::Matrix)
+        },
+        (scalar, Value::Matrix(matrix)) if scalar.is_complex() && op == BinOpKind::Mul => {
+            matrix.try_map(|entry| eval_operands(op, implicit, scalar.clone(), entry.clone()))
+                .map(Value::Matrix)
+        },
+        _ => Err(error.into()),
+    }
+}
+
+/// Applies the binary operator element-wise to two operands, at least one of which is a list,
+/// using `eval` to apply it to each pair of elements.
+///
+/// Two lists must have the same length, and their elements are paired up in order. A list and
+/// any other value are combined by pairing the value with every element of the list, so
+/// `[1, 2, 3] * 2` is `[2, 4, 6]`. Nested lists are broadcast recursively by `eval`.
+fn eval_list_operands(
+    op: BinOpKind,
+    left: Value,
+    right: Value,
+    eval: impl Fn(Value, Value) -> Result<Value, EvalError>,
+) -> Result<Value, EvalError> {
+    let values = match (left, right) {
+        (Value::List(left), Value::List(right)) => {
+            if left.len() != right.len() {
+                return Err(ListLengthMismatch {
+                    op,
+                    left: left.len(),
+                    right: right.len(),
+                }.into());
+            }
+            left.into_iter()
+                .zip(right)
+                .map(|(left, right)| eval(left, right))
+                .collect::<Result<_, _>>()?
+        },
+        (Value::List(left), right) => left.into_iter()
+            .map(|left| eval(left, right.clone()))
+            .collect::<Result<_, _>>()?,
+        (left, Value::List(right)) => right.into_iter()
+            .map(|right| eval(left.clone(), right))
+            .collect::<Result<_, _>>()?,
+        _ => unreachable!(),
+    };
+    Ok(Value::List(values))
+}
+
 /// Evaluates the binary expression given the operator, and the left and right operands.
+///
+/// If either operand is a matrix, the operator is evaluated as described in
+/// [`eval_matrix_operands`]. Otherwise, if either operand is a list, the operator is applied
+/// element-wise (see [`eval_list_operands`]).
 pub(crate) fn eval_operands(
     op: BinOpKind,
     implicit: bool,
     left: Value,
     right: Value,
 ) -> Result<Value, EvalError> {
+    if left.is_matrix() || right.is_matrix() {
+        return eval_matrix_operands(op, implicit, left, right);
+    }
+
+    if left.is_list() || right.is_list() {
+        return eval_list_operands(op, left, right, |left, right| {
+            eval_operands(op, implicit, left, right)
+        });
+    }
+
     if left.is_integer() && right.is_integer() {
         return eval_integer_operands(op, implicit, left.coerce_integer(), right.coerce_integer());
     }
 
+    if left.is_rational() && right.is_rational() {
+        return eval_rational_operands(op, implicit, left.coerce_rational(), right.coerce_rational());
+    }
+
     if left.is_real() && right.is_real() {
         return eval_real_operands(op, implicit, left.coerce_float(), right.coerce_float());
     }
 
+    if left.is_interval() && right.is_interval() {
+        return eval_interval_operands(op, implicit, left.coerce_interval(), right.coerce_interval());
+    }
+
     if left.is_complex() && right.is_complex() {
         return eval_complex_operands(op, implicit, left.coerce_complex(), right.coerce_complex());
     }
@@ -226,6 +525,10 @@ pub(crate) fn eval_operands(
         return eval_unit_operands(op, implicit, left, right);
     }
 
+    if left.is_expr() || right.is_expr() {
+        return eval_expr_operands(op, implicit, left, right);
+    }
+
     Err(InvalidBinaryOperation {
         op,
         implicit,
@@ -234,11 +537,111 @@ pub(crate) fn eval_operands(
     }.into())
 }
 
+/// Evaluates the binary expression given the operator, and the left and right operands, in the
+/// given [`NumberMode`].
+///
+/// In [`NumberMode::Interval`], operations on integers and rational numbers are still exact, but
+/// operations on real numbers that would need rounding, such as `2 ^ (1 / 2)`, are computed with
+/// intervals instead.
+pub(crate) fn eval_operands_in(
+    mode: NumberMode,
+    op: BinOpKind,
+    implicit: bool,
+    left: Value,
+    right: Value,
+) -> Result<Value, EvalError> {
+    if left.is_matrix() || right.is_matrix() {
+        return eval_matrix_operands(op, implicit, left, right);
+    }
+
+    if left.is_list() || right.is_list() {
+        return eval_list_operands(op, left, right, |left, right| {
+            eval_operands_in(mode, op, implicit, left, right)
+        });
+    }
+
+    if mode == NumberMode::Standard || !left.is_interval() || !right.is_interval() {
+        return eval_operands(op, implicit, left, right);
+    }
+
+    if left.is_rational() && right.is_rational() {
+        match eval_operands(op, implicit, left.clone(), right.clone()) {
+            Ok(Value::Float(_)) => (),
+            result => return result,
+        }
+    }
+
+    eval_interval_operands(op, implicit, left.coerce_interval(), right.coerce_interval())
+}
+
+/// Returns the number of bits in an integer with `bits` bits raised to the power of `exp`, or the
+/// lowest number of bits it could have, since the exact number cannot be known without computing
+/// the power.
+fn power_bits(bits: u64, exp: u32) -> u64 {
+    bits.saturating_sub(1) * u64::from(exp) + 1
+}
+
+/// Returns the number of bits in the largest integer that the binary operator would produce given
+/// the operands, or the lowest number of bits it could have, for operators that can produce
+/// integers much larger than their operands. Returns [`None`] for other operators.
+fn result_bits(op: BinOpKind, left: &Value, right: &Value) -> Option<u64> {
+    match (left, right) {
+        (Value::List(left), Value::List(right)) => left.iter()
+            .zip(right)
+            .filter_map(|(left, right)| result_bits(op, left, right))
+            .max(),
+        (Value::List(left), right) => left.iter()
+            .filter_map(|left| result_bits(op, left, right))
+            .max(),
+        (left, Value::List(right)) => right.iter()
+            .filter_map(|right| result_bits(op, left, right))
+            .max(),
+        (Value::Integer(base), Value::Integer(exp)) if op == BinOpKind::Exp => {
+            // larger exponents are computed with floating-point numbers (see
+            // `eval_integer_operands`)
+            let exp = exp.to_u16()
+                .map(u32::from)
+                .or_else(|| exp.to_i16().map(|exp| u32::from(exp.unsigned_abs())))?;
+            Some(power_bits(base.significant_bits().into(), exp))
+        },
+        (Value::Rational(base), Value::Integer(exp)) if op == BinOpKind::Exp => {
+            let exp = exp.to_i16()?;
+            Some(power_bits(rational_bits(base), exp.unsigned_abs().into()))
+        },
+        (Value::Integer(int), Value::Integer(shift)) if op == BinOpKind::BitLeft && !int.is_zero() => {
+            let shift = shift.to_u64()?;
+            Some(u64::from(int.significant_bits()).saturating_add(shift))
+        },
+        _ => None,
+    }
+}
+
+/// Checks that the integers produced by applying the binary operator to the operands would not be
+/// larger than the budget allows, before they are computed.
+pub(crate) fn check_operands(
+    budget: &Budget,
+    op: BinOpKind,
+    left: &Value,
+    right: &Value,
+    span: impl FnOnce() -> std::ops::Range<usize>,
+) -> Result<(), Error> {
+    if budget.max_integer_bits.is_none() {
+        return Ok(());
+    }
+    match result_bits(op, left, right) {
+        Some(bits) => budget.check_integer_bits(bits, span),
+        None => Ok(()),
+    }
+}
+
 impl Eval for Binary {
     fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        check_operand(&self.lhs, ctxt)?;
         let left = eval_break!(self.lhs, ctxt);
+        check_operand(&self.rhs, ctxt)?;
         let right = eval_break!(self.rhs, ctxt);
-        eval_operands(self.op.kind, self.op.implicit, left, right)
+        check_operands(&ctxt.budget, self.op.kind, &left, &right, || self.span())?;
+        eval_operands_in(ctxt.number_mode, self.op.kind, self.op.implicit, left, right)
             .map_err(|e| e.into_error(self))
     }
 }
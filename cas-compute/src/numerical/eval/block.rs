@@ -3,18 +3,23 @@ use crate::eval_break;
 use crate::numerical::{ctxt::Ctxt, error::Error, eval::Eval, value::Value};
 
 /// Evaluates multiple statements, returning the value of the last one.
+///
+/// The statements are evaluated as a single evaluation, so they share the [`Budget`] of the
+/// context.
+///
+/// [`Budget`]: crate::numerical::budget::Budget
 pub fn eval_stmts(stmts: &[Stmt], ctxt: &mut Ctxt) -> Result<Value, Error> {
-    if stmts.is_empty() {
-        return Ok(Value::Unit);
-    }
+    ctxt.metered(|ctxt| {
+        if stmts.is_empty() {
+            return Ok(Value::Unit);
+        }
 
-    for stmt in stmts.iter().take(stmts.len() - 1) {
-        let mut ctxt_old = ctxt.clone();
-        eval_break!(stmt, &mut ctxt_old);
-        *ctxt = ctxt_old;
-    }
+        for stmt in stmts.iter().take(stmts.len() - 1) {
+            eval_break!(stmt, ctxt);
+        }
 
-    stmts.last().unwrap().eval(ctxt)
+        stmts.last().unwrap().eval(ctxt)
+    })
 }
 
 impl Eval for Block {
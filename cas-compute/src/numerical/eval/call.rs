@@ -1,24 +1,44 @@
-use cas_parser::parser::ast::{assign::Param, call::Call};
+use cas_parser::parser::ast::{assign::Param, call::Call, expr::Expr};
 use crate::eval_break;
-use crate::funcs::combinatoric::Ncr;
+use crate::funcs::{combinatoric::Ncr, interval};
 use crate::numerical::{
-    ctxt::{MAX_RECURSION_DEPTH, Ctxt, Func, UserFunc},
+    builtin::{error::BuiltinError, Builtin, Params},
+    ctxt::{MAX_RECURSION_DEPTH, Ctxt, Func, Function, NumberMode, Scope},
     error::{
         kind::{
+            DuplicateArgument,
+            IntervalUnsupported,
             InvalidDerivativeArguments,
             MissingArgument,
+            NamedArgumentsUnsupported,
             NonNumericDerivative,
             StackOverflow,
             TooManyArguments,
             UndefinedFunction,
+            UnknownArgument,
         },
         Error,
     },
     eval::Eval,
+    memo::Memoized,
     value::Value,
 };
 use crate::primitive::float;
+use crate::symbolic::expr::{Expr as SymExpr, Primary};
 use rug::{ops::Pow, Float};
+use std::{borrow::Cow, fmt::Display, sync::Arc};
+
+/// Evaluates the body of a user-defined function or lambda.
+///
+/// A `return` expression in the body stops evaluation with the returned value, in the same way as a
+/// `break` expression, so the flags it set are reset here, where the function returns.
+fn eval_function_body(body: &Expr, ctxt: &mut Ctxt) -> Result<Value, Error> {
+    let value = body.eval(ctxt)?;
+    ctxt.break_loop = false;
+    ctxt.return_call = false;
+    ctxt.continue_loop = false;
+    Ok(value)
+}
 
 /// Computes the numerical derivative of an expression, using the higher-order differentiation
 /// method found
@@ -51,10 +71,15 @@ fn compute_derivative(
                 builtin.eval(ctxt, &mut Some(Value::Float(location)).into_iter())
                     .map_err(|err| err.into_error(call))
             },
-            Func::UserFunc(UserFunc { header, body, .. }) => {
-                let symbol = &header.params[0].symbol().name;
+            Func::UserFunc(user_func) => {
+                let symbol = &user_func.header.params[0].symbol().name;
+                ctxt.add_var(symbol, Value::Float(location));
+                eval_function_body(&user_func.body, ctxt)
+            },
+            Func::Lambda(lambda) => {
+                let symbol = &lambda.params[0].symbol().name;
                 ctxt.add_var(symbol, Value::Float(location));
-                body.eval(ctxt)
+                eval_function_body(&lambda.body, ctxt)
             },
         }
     };
@@ -84,9 +109,341 @@ fn compute_derivative(
     Ok(Value::Float((result_left + result_right) / 2))
 }
 
+/// Returns true if the value is, or is a list containing, a number that could have been rounded.
+fn is_rounded(value: &Value) -> bool {
+    match value {
+        Value::Float(_) | Value::Complex(_) => true,
+        Value::List(values) => values.iter().any(is_rounded),
+        Value::Matrix(matrix) => matrix.entries().iter().any(is_rounded),
+        _ => false,
+    }
+}
+
+/// Evaluates a builtin function that is given an interval, or is called in
+/// [`NumberMode::Interval`].
+///
+/// Functions with an interval implementation use it. Other functions are evaluated as usual, but
+/// an error is returned if they do not accept the intervals they are given, or if they produce a
+/// rounded result in [`NumberMode::Interval`], since it could not be enclosed in an interval.
+fn eval_builtin_interval(
+    call: &Call,
+    builtin: &Arc<dyn Builtin>,
+    ctxt: &Ctxt,
+    args: Vec<Value>,
+) -> Result<Value, Error> {
+    if let Some(result) = interval::eval_builtin(&call.name.name, ctxt, &args) {
+        return Ok(result);
+    }
+
+    let unsupported = || Error::new(call.outer_span().to_vec(), IntervalUnsupported {
+        name: call.name.name.clone(),
+    });
+    let given_interval = args.iter().any(|arg| matches!(arg, Value::Interval(_)));
+    match builtin.eval(ctxt, &mut args.into_iter()) {
+        Ok(value) if !given_interval
+            && ctxt.number_mode == NumberMode::Interval
+            && is_rounded(&value) => Err(unsupported()),
+        Err(BuiltinError::TypeMismatch(err)) if err.given == "Interval" => Err(unsupported()),
+        result => result.map_err(|err| err.into_error(call)),
+    }
+}
+
+/// Matches the arguments of `call` to the parameters with the given names. Positional arguments
+/// are given to the parameters in order, and named arguments to the parameters they name.
+///
+/// For each parameter, the index of its argument in [`Call::arg_values`] is returned, or [`None`]
+/// if it was not given one.
+fn match_args(
+    call: &Call,
+    name: &str,
+    params: &[&str],
+    signature: &dyn Display,
+) -> Result<Vec<Option<usize>>, Error> {
+    let given = call.args.len() + call.named_args.len();
+    if given > params.len() {
+        return Err(Error::new(call.outer_span().to_vec(), TooManyArguments {
+            name: name.to_owned(),
+            expected: params.len(),
+            given,
+            signature: signature.to_string(),
+        }));
+    }
+
+    let mut slots = (0..params.len())
+        .map(|index| (index < call.args.len()).then_some(index))
+        .collect::<Vec<_>>();
+    for (index, arg) in call.named_args.iter().enumerate() {
+        let mut spans = call.outer_span().to_vec();
+        let Some(param) = params.iter().position(|param| *param == arg.name.name) else {
+            spans.push(arg.name.span.clone());
+            return Err(Error::new(spans, UnknownArgument {
+                name: name.to_owned(),
+                argument: arg.name.name.clone(),
+                params: params.iter().map(|param| param.to_string()).collect(),
+                signature: signature.to_string(),
+            }));
+        };
+
+        if let Some(first) = slots[param] {
+            spans.push(match call.args.get(first) {
+                Some(expr) => expr.span(),
+                None => call.named_args[first - call.args.len()].span(),
+            });
+            spans.push(arg.span());
+            return Err(Error::new(spans, DuplicateArgument {
+                name: name.to_owned(),
+                argument: arg.name.name.clone(),
+                signature: signature.to_string(),
+            }));
+        }
+        slots[param] = Some(call.args.len() + index);
+    }
+
+    Ok(slots)
+}
+
+/// Returns the given call with its named arguments moved to the positions of the parameters of the
+/// builtin function they name, since builtins are only given arguments by position.
+///
+/// A parameter that is not given an argument can only be followed by parameters that are not
+/// given one either, so that the builtin can treat them as missing.
+fn positional_call<'a>(call: &'a Call, builtin: &Arc<dyn Builtin>) -> Result<Cow<'a, Call>, Error> {
+    if call.named_args.is_empty() {
+        return Ok(Cow::Borrowed(call));
+    }
+
+    let Some(Params { names, signature }) = builtin.params() else {
+        let mut spans = call.outer_span().to_vec();
+        spans.push(call.named_args[0].span());
+        return Err(Error::new(spans, NamedArgumentsUnsupported {
+            name: call.name.name.clone(),
+        }));
+    };
+
+    let slots = match_args(call, &call.name.name, names, &signature)?;
+    let given = slots.iter().flatten().count();
+    let mut args = Vec::with_capacity(given);
+    for (index, slot) in slots.into_iter().enumerate() {
+        match slot {
+            Some(slot) => args.push(call.arg_values().nth(slot).unwrap().clone()),
+            None if args.len() < given => return Err(Error::new(
+                call.outer_span().to_vec(),
+                MissingArgument {
+                    name: call.name.name.clone(),
+                    index,
+                    expected: names.len(),
+                    given,
+                    signature: signature.to_owned(),
+                },
+            )),
+            None => (),
+        }
+    }
+
+    Ok(Cow::Owned(Call {
+        name: call.name.clone(),
+        derivatives: call.derivatives,
+        args,
+        named_args: Vec::new(),
+        span: call.span.clone(),
+        paren_span: call.paren_span.clone(),
+    }))
+}
+
+/// Binds already evaluated arguments to the parameters of a user-defined function or lambda,
+/// evaluating the default values of any parameters without a corresponding argument. An argument
+/// of [`None`] gives the parameter its default value, as if it were not given.
+fn bind_args(
+    ctxt: &mut Ctxt,
+    call: &Call,
+    name: &str,
+    params: &[Param],
+    signature: &dyn Display,
+    args: Vec<Option<Value>>,
+) -> Result<(), Error> {
+    if args.len() > params.len() {
+        return Err(Error::new(call.outer_span().to_vec(), TooManyArguments {
+            name: name.to_owned(),
+            expected: params.len(),
+            given: args.len(),
+            signature: signature.to_string(),
+        }));
+    }
+
+    let given = args.iter().flatten().count();
+    let mut args = args.into_iter();
+    for (index, param) in params.iter().enumerate() {
+        let value = match (args.next().flatten(), param) {
+            (Some(value), _) => value,
+            (None, Param::Default(_, expr)) => expr.eval(ctxt)?,
+            (None, Param::Symbol(_)) => return Err(Error::new(
+                call.outer_span().to_vec(),
+                MissingArgument {
+                    name: name.to_owned(),
+                    index,
+                    expected: params.len(),
+                    given,
+                    signature: signature.to_string(),
+                },
+            )),
+        };
+        ctxt.add_var(&param.symbol().name, value);
+    }
+
+    Ok(())
+}
+
+/// Calls a user-defined function or lambda with arguments that have already been evaluated in the
+/// calling scope, where an argument of [`None`] gives its parameter its default value. `name` is
+/// the name used in errors about the arguments.
+///
+/// The parameters are bound in a new local scope, in which `eval_body` is then called. The scope of
+/// a user-defined function is not enclosed by any other scope, while the scope of a lambda is
+/// enclosed by the scope it captured. A named lambda is also bound to its own name, so that it can
+/// call itself even if it was defined in a local scope.
+///
+/// Calls to pure user-defined functions are looked up in, and added to, their [`Memo`] table,
+/// unless they use prime notation, since the table only remembers the values of the function
+/// itself.
+///
+/// [`Memo`]: crate::numerical::memo::Memo
+fn call_user(
+    ctxt: &mut Ctxt,
+    call: &Call,
+    name: &str,
+    function: &Function,
+    args: Vec<Option<Value>>,
+    eval_body: impl FnOnce(&mut Ctxt) -> Result<Value, Error>,
+) -> Result<Value, Error> {
+    let (params, signature, scope, recursive): (&[Param], &dyn Display, _, _) =
+        match &function.func {
+            Func::Builtin(_) => unreachable!("builtin functions do not have a scope"),
+            Func::UserFunc(user_func) => {
+                (&user_func.header.params, &user_func.header, Scope::new(None), user_func.recursive)
+            },
+            Func::Lambda(lambda) => {
+                let mut scope = Scope::new(lambda.captures.clone());
+                if let Some(name) = &function.name {
+                    scope.add_var(name, Value::Function(function.clone()));
+                }
+
+                // a lambda can refer to itself in too many ways to tell whether it is recursive
+                (&lambda.params, &**lambda, scope, true)
+            },
+        };
+
+    let memoized = (call.derivatives == 0)
+        .then(|| Memoized::new(ctxt, &function.func, args.iter().map(Option::as_ref)))
+        .flatten();
+    if let Some(value) = memoized.as_ref().and_then(Memoized::get) {
+        return Ok(value);
+    }
+
+    let value = track_depth(ctxt, call, recursive, |ctxt| {
+        ctxt.with_scope(scope, |ctxt| {
+            bind_args(ctxt, call, name, params, signature, args)?;
+            eval_body(ctxt)
+        })
+    })?;
+    if let Some(memoized) = memoized {
+        memoized.insert(&value);
+    }
+    Ok(value)
+}
+
+/// Evaluates the body of a function called at `call` with `eval_body`, tracking the depth of the
+/// stack if the function is `recursive`, and returning a [`StackOverflow`] error if it is too deep.
+pub(crate) fn track_depth(
+    ctxt: &mut Ctxt,
+    call: &Call,
+    recursive: bool,
+    eval_body: impl FnOnce(&mut Ctxt) -> Result<Value, Error>,
+) -> Result<Value, Error> {
+    let result = enter_call(ctxt, recursive).and_then(|()| eval_body(ctxt));
+    leave_call(ctxt, call, recursive, result)
+}
+
+/// Enters a call to a function, increasing the depth of the stack if the function is `recursive`.
+/// Returns a [`StackOverflow`] error if the stack is too deep.
+///
+/// [`leave_call`] must be called once the function returns, even if this returns an error.
+pub(crate) fn enter_call(ctxt: &mut Ctxt, recursive: bool) -> Result<(), Error> {
+    if recursive {
+        ctxt.stack_depth += 1;
+    }
+    if ctxt.stack_depth > MAX_RECURSION_DEPTH {
+        ctxt.max_depth_reached = true;
+
+        // we do not include the call span
+        //
+        // this is because this call span is the span of the function call within the *function
+        // definition*, not the user's input
+        //
+        // this error will be propogated up to the top level of the call stack, where `call` is
+        // now representing the function call of the user's input
+        Err(Error::new(vec![], StackOverflow))
+    } else {
+        Ok(())
+    }
+}
+
+/// Leaves a call to a function at `call` that produced the given result, undoing
+/// [`enter_call`].
+pub(crate) fn leave_call<T>(
+    ctxt: &mut Ctxt,
+    call: &Call,
+    recursive: bool,
+    result: Result<T, Error>,
+) -> Result<T, Error> {
+    if recursive {
+        ctxt.stack_depth -= 1;
+    }
+
+    match result {
+        Err(mut err) if ctxt.max_depth_reached && ctxt.stack_depth == 0 => {
+            // we are now at the top level of a stack overflow error, so we can insert the span of
+            // the user's input
+            ctxt.max_depth_reached = false;
+            err.spans.extend(call.outer_span().to_vec());
+            Err(err)
+        },
+        result => result,
+    }
+}
+
+/// Calls the given function with arguments that have already been evaluated, as if it were
+/// called at the site of `call`. This is used by higher-order builtins to call the functions
+/// passed to them.
+///
+/// Unlike a regular function call, the arguments cannot break out of loops, and the function
+/// cannot be differentiated with prime notation.
+pub(crate) fn call_with_values(
+    ctxt: &mut Ctxt,
+    call: &Call,
+    function: &Function,
+    args: Vec<Value>,
+) -> Result<Value, Error> {
+    match &function.func {
+        Func::Builtin(builtin) => builtin.eval_values(ctxt, call, args),
+        Func::UserFunc(user_func) => {
+            let args = args.into_iter().map(Some).collect();
+            call_user(ctxt, call, &user_func.header.name.name, function, args, |ctxt| {
+                eval_function_body(&user_func.body, ctxt)
+            })
+        },
+        Func::Lambda(lambda) => {
+            let args = args.into_iter().map(Some).collect();
+            call_user(ctxt, call, &lambda.to_string(), function, args, |ctxt| {
+                eval_function_body(&lambda.body, ctxt)
+            })
+        },
+    }
+}
+
 impl Eval for Call {
     fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
-        let func = ctxt.get_func(&self.name.name)
+        // a variable holding a function shadows a function of the same name
+        let function = ctxt.get_function(&self.name.name)
             .ok_or_else(|| Error::new(vec![self.name.span.clone()], UndefinedFunction {
                 name: self.name.name.clone(),
                 suggestions: ctxt.get_similar_funcs(&self.name.name)
@@ -94,122 +451,129 @@ impl Eval for Call {
                     .map(|s| s.to_string())
                     .collect(),
             }))?;
-        let mut ctxt = ctxt.clone();
 
-        match func {
-            Func::Builtin(builtin) => {
-                let mut args = Vec::with_capacity(self.args.len());
-                for arg in self.args.iter() {
-                    args.push(eval_break!(arg, &mut ctxt));
-                }
-
-                if self.derivatives == 0 {
-                    // no eval_break!; cannot break out of loops from within a function
-                    builtin.eval(&ctxt, &mut args.into_iter())
-                        .map_err(|err| err.into_error(self))
-                } else {
-                    if builtin.num_args() != 1 {
-                        return Err(Error::new(self.outer_span().to_vec(), InvalidDerivativeArguments {
-                            name: self.name.name.clone(),
-                        }));
-                    }
-                    compute_derivative(self, func, &mut ctxt, args.swap_remove(0))
-                }
+        let (params, body, signature): (_, _, &dyn Display) = match &function.func {
+            Func::Builtin(builtin) => return eval_builtin(self, ctxt, &function.func, builtin),
+            Func::UserFunc(user_func) => {
+                (&user_func.header.params, &user_func.body, &user_func.header)
             },
-            Func::UserFunc(UserFunc { header, body, recursive }) => {
-                let mut ctxt = ctxt.clone();
-                if *recursive {
-                    ctxt.stack_depth += 1;
-                }
+            Func::Lambda(lambda) => (&lambda.params, &lambda.body, &**lambda),
+        };
 
-                if ctxt.stack_depth > MAX_RECURSION_DEPTH {
-                    // TODO
-                    ctxt.max_depth_reached = true;
-
-                    // we do not include the call span
-                    //
-                    // this is because this call span is the span of the function call within the *function
-                    // definition*, not the user's input
-                    //
-                    // this error will be propogated up to the top level of the call stack, where `self` is
-                    // now representing the function call of the user's input
-                    return Err(Error::new(vec![], StackOverflow));
-                }
+        // named arguments are matched to the parameters before any argument is evaluated
+        let slots = if self.named_args.is_empty() {
+            None
+        } else {
+            let names = params.iter().map(|param| param.symbol().name.as_str()).collect::<Vec<_>>();
+            Some(match_args(self, &self.name.name, &names, signature)?)
+        };
 
-                let mut index = 0;
-                let mut args = self.args.iter();
-                let mut params = header.params.iter();
-                loop {
-                    match (args.next(), params.next()) {
-                        // a positional argument
-                        // evaluate it and add it to the context for use in the function body
-                        (Some(arg), Some(param)) => {
-                            let value = eval_break!(arg, &mut ctxt);
-                            ctxt.add_var(&param.symbol().name, value);
-                        },
-
-                        // too many arguments were given
-                        (Some(_), None) => return Err(Error::new(self.outer_span().to_vec(), TooManyArguments {
-                            name: self.name.name.clone(),
-                            expected: header.params.len(),
-                            given: self.args.len(),
-                            signature: header.to_string(),
-                        })),
-
-                        // no argument was given for this parameter
-                        // use the default value if there is one
-                        (None, Some(param)) => {
-                            // if there is no default, that's an error
-                            let value = match param {
-                                Param::Symbol(_) => return Err(Error::new(
-                                    self.outer_span().to_vec(),
-                                    MissingArgument {
-                                        name: self.name.name.clone(),
-                                        index,
-                                        expected: header.params.len(),
-                                        given: self.args.len(),
-                                        signature: header.to_string(),
-                                    },
-                                )),
-                                Param::Default(_, expr) => expr.eval(&mut ctxt),
-                            }?;
-                            ctxt.add_var(&param.symbol().name, value);
-                        },
-
-                        // begin evaluation
-                        (None, None) => break,
-                    }
-
-                    index += 1;
-                }
+        // the arguments are evaluated in the calling scope, in the order they are written, before
+        // the parameters are bound
+        let mut args = Vec::with_capacity(self.args.len() + self.named_args.len());
+        for arg in self.arg_values() {
+            args.push(Some(eval_break!(arg, ctxt)));
+        }
+        if let Some(slots) = slots {
+            args = slots.into_iter()
+                .map(|slot| slot.and_then(|index| args[index].take()))
+                .collect();
+        }
 
-                let result = if self.derivatives == 0 {
-                    // no eval_break!; cannot break out of loops from within a function
-                    body.eval(&mut ctxt)
-                } else {
-                    if header.params.len() != 1 {
-                        return Err(Error::new(self.outer_span().to_vec(), InvalidDerivativeArguments {
-                            name: self.name.name.clone(),
-                        }));
-                    }
-                    let initial = ctxt.get_var(&header.params[0].symbol().name).unwrap();
-                    compute_derivative(self, func, &mut ctxt, initial)
-                };
-                if *recursive {
-                    ctxt.stack_depth -= 1;
-                }
+        if self.derivatives == 0 {
+            // no eval_break!; cannot break out of loops from within a function
+            call_user(ctxt, self, &self.name.name, &function, args, |ctxt| {
+                eval_function_body(body, ctxt)
+            })
+        } else {
+            if params.len() != 1 {
+                return Err(Error::new(self.outer_span().to_vec(), InvalidDerivativeArguments {
+                    name: self.name.name.clone(),
+                }));
+            }
+            call_user(ctxt, self, &self.name.name, &function, args, |ctxt| {
+                let initial = ctxt.get_var(&params[0].symbol().name).unwrap();
+                compute_derivative(self, &function.func, ctxt, initial)
+            })
+        }
+    }
+}
 
-                if result.is_err() && ctxt.max_depth_reached {
-                    // we are now at the top level of a stack overflow error, so we can insert the span of
-                    // the user's input
-                    result.map_err(|mut err| {
-                        err.spans.extend(self.outer_span().to_vec());
-                        err
-                    })
-                } else {
-                    result
-                }
-            },
+/// Evaluates a call to a builtin function.
+///
+/// Named arguments are first moved to the positions of the parameters they name.
+///
+/// Builtins that take the unevaluated arguments of the call and mutate the context are given the
+/// calling context. Other builtins taking the call are evaluated in a new local scope, so that any
+/// variables they define do not leak out of the call. The arguments of all other builtins are
+/// evaluated in the calling context, as they are for user-defined functions.
+fn eval_builtin(
+    call: &Call,
+    ctxt: &mut Ctxt,
+    func: &Func,
+    builtin: &Arc<dyn Builtin>,
+) -> Result<Value, Error> {
+    let call = &*positional_call(call, builtin)?;
+    if builtin.takes_call() {
+        let result = if builtin.mutates_ctxt() {
+            builtin.eval_call(ctxt, call)
+        } else {
+            let scope = Scope::new(ctxt.capture_scope());
+            ctxt.with_scope(scope, |ctxt| builtin.eval_call(ctxt, call))
+        };
+        if let Some(result) = result {
+            return result;
         }
     }
+
+    let mut args = Vec::with_capacity(call.args.len());
+    for arg in call.args.iter() {
+        let value = eval_break!(arg, ctxt);
+
+        // builtins accepting a list are given a range as a list
+        ctxt.budget.check_coerced_list(&value, || arg.span())?;
+        args.push(value);
+    }
+
+    if call.derivatives == 0 {
+        // no eval_break!; cannot break out of loops from within a function
+        apply_builtin(call, ctxt, builtin, args)
+    } else {
+        if builtin.num_args() != 1 {
+            return Err(Error::new(call.outer_span().to_vec(), InvalidDerivativeArguments {
+                name: call.name.name.clone(),
+            }));
+        }
+        compute_derivative(call, func, ctxt, args.swap_remove(0))
+    }
+}
+
+/// Calls a builtin function with arguments that have already been evaluated, as if it were called
+/// at the site of `call`, without prime notation.
+pub(crate) fn apply_builtin(
+    call: &Call,
+    ctxt: &Ctxt,
+    builtin: &Arc<dyn Builtin>,
+    args: Vec<Value>,
+) -> Result<Value, Error> {
+    if ctxt.number_mode == NumberMode::Interval
+        || args.iter().any(|arg| matches!(arg, Value::Interval(_))) {
+        return eval_builtin_interval(call, builtin, ctxt, args);
+    }
+
+    if !args.iter().any(Value::is_expr) {
+        return builtin.eval(ctxt, &mut args.into_iter())
+            .map_err(|err| err.into_error(call));
+    }
+
+    // a function that does not accept symbolic expressions is left unevaluated
+    // when given one, e.g. `sin(quote(x))` is the expression `sin(x)`
+    match builtin.eval(ctxt, &mut args.clone().into_iter()) {
+        Err(BuiltinError::TypeMismatch(err)) => args.into_iter()
+            .map(Value::into_expr)
+            .collect::<Option<Vec<_>>>()
+            .map(|args| Value::Expr(SymExpr::Primary(Primary::Call(call.name.name.clone(), args))))
+            .ok_or_else(|| BuiltinError::TypeMismatch(err).into_error(call)),
+        result => result.map_err(|err| err.into_error(call)),
+    }
 }
@@ -2,7 +2,11 @@ use cas_parser::parser::ast::loop_expr::Continue;
 use crate::numerical::{ctxt::Ctxt, error::Error, eval::Eval, value::Value};
 
 impl Eval for Continue {
-    fn eval(&self, _: &mut Ctxt) -> Result<Value, Error> {
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        // like `break`, stop evaluating the body of the loop; the loop will then set both flags
+        // back to false and continue with its next iteration
+        ctxt.break_loop = true;
+        ctxt.continue_loop = true;
         Ok(Value::Unit)
     }
 }
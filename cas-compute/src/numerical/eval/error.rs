@@ -1,6 +1,16 @@
 use cas_error::ErrorKind;
 use cas_parser::parser::ast::{assign::Assign, binary::Binary};
-use crate::numerical::error::{kind::{BitshiftOverflow, InvalidBinaryOperation}, Error};
+use crate::numerical::error::{
+    kind::{
+        BitshiftOverflow,
+        InvalidBinaryOperation,
+        InvalidMatrixPower,
+        ListLengthMismatch,
+        MatrixDimensionMismatch,
+        OverlappingIntervals,
+    },
+    Error,
+};
 use std::ops::Range;
 
 /// Trait implemented on [`Binary`] and [`Assign`] to extract the spans of the operands and the
@@ -30,6 +40,18 @@ pub enum EvalError {
 
     /// Attempted to bitshift by a value that is too large.
     BitshiftOverflow(BitshiftOverflow),
+
+    /// Attempted to compare two intervals that overlap.
+    OverlappingIntervals(OverlappingIntervals),
+
+    /// Attempted to apply an operator element-wise to lists of different lengths.
+    ListLengthMismatch(ListLengthMismatch),
+
+    /// Attempted to apply an operator to matrices with incompatible dimensions.
+    MatrixDimensionMismatch(MatrixDimensionMismatch),
+
+    /// Attempted to raise a matrix to a power it cannot be raised to.
+    InvalidMatrixPower(InvalidMatrixPower),
 }
 
 impl From<InvalidBinaryOperation> for EvalError {
@@ -44,10 +66,39 @@ impl From<BitshiftOverflow> for EvalError {
     }
 }
 
+impl From<OverlappingIntervals> for EvalError {
+    fn from(e: OverlappingIntervals) -> Self {
+        EvalError::OverlappingIntervals(e)
+    }
+}
+
+impl From<ListLengthMismatch> for EvalError {
+    fn from(e: ListLengthMismatch) -> Self {
+        EvalError::ListLengthMismatch(e)
+    }
+}
+
+impl From<MatrixDimensionMismatch> for EvalError {
+    fn from(e: MatrixDimensionMismatch) -> Self {
+        EvalError::MatrixDimensionMismatch(e)
+    }
+}
+
+impl From<InvalidMatrixPower> for EvalError {
+    fn from(e: InvalidMatrixPower) -> Self {
+        EvalError::InvalidMatrixPower(e)
+    }
+}
+
 impl EvalError {
     /// Convert the [`EvalError`] into an [`Error`], using the given syntax tree to provide spans.
     pub fn into_error(self, binary: &dyn BinaryLike) -> Error {
-        let spans = binary.spans();
+        self.into_error_at(binary.spans())
+    }
+
+    /// Convert the [`EvalError`] into an [`Error`] with the spans of the operands and the operator
+    /// of the expression it occurred in.
+    pub fn into_error_at(self, spans: Vec<Range<usize>>) -> Error {
         match self {
             EvalError::InvalidBinaryOperation(e) => Error {
                 spans,
@@ -57,6 +108,22 @@ impl EvalError {
                 spans,
                 kind: Box::new(e) as Box<dyn ErrorKind>,
             },
+            EvalError::OverlappingIntervals(e) => Error {
+                spans,
+                kind: Box::new(e) as Box<dyn ErrorKind>,
+            },
+            EvalError::ListLengthMismatch(e) => Error {
+                spans,
+                kind: Box::new(e) as Box<dyn ErrorKind>,
+            },
+            EvalError::MatrixDimensionMismatch(e) => Error {
+                spans,
+                kind: Box::new(e) as Box<dyn ErrorKind>,
+            },
+            EvalError::InvalidMatrixPower(e) => Error {
+                spans,
+                kind: Box::new(e) as Box<dyn ErrorKind>,
+            },
         }
     }
 }
@@ -1,21 +1,38 @@
 use cas_parser::parser::ast::expr::Expr;
 use crate::numerical::{ctxt::Ctxt, error::Error, eval::Eval, value::Value};
+use crate::primitive::with_precision;
 
 impl Eval for Expr {
     fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
-        match self {
-            Expr::Literal(literal) => literal.eval(ctxt),
-            Expr::Paren(paren) => paren.expr.eval(ctxt),
-            Expr::Block(block) => block.eval(ctxt),
-            Expr::If(if_expr) => if_expr.eval(ctxt),
-            Expr::Loop(loop_expr) => loop_expr.eval(ctxt),
-            Expr::While(while_expr) => while_expr.eval(ctxt),
-            Expr::Break(break_expr) => break_expr.eval(ctxt),
-            Expr::Continue(continue_expr) => continue_expr.eval(ctxt),
-            Expr::Call(call) => call.eval(ctxt),
-            Expr::Unary(unary) => unary.eval(ctxt),
-            Expr::Binary(binary) => binary.eval(ctxt),
-            Expr::Assign(assign) => assign.eval(ctxt),
-        }
+        ctxt.metered(|ctxt| {
+            ctxt.step(|| self.span())?;
+            let value = eval_expr(self, ctxt)?;
+            ctxt.budget.check_value(&value, || self.span())?;
+            Ok(value)
+        })
     }
 }
+
+/// Evaluates the expression, without checking it against the budget of the context.
+fn eval_expr(expr: &Expr, ctxt: &mut Ctxt) -> Result<Value, Error> {
+    // the precision of the context can change during evaluation, such as with a call to
+    // `precision`, so it is set again before evaluating each expression
+    with_precision(ctxt.precision(), || match expr {
+        Expr::Literal(literal) => literal.eval(ctxt),
+        Expr::Paren(paren) => paren.expr.eval(ctxt),
+        Expr::Block(block) => block.eval(ctxt),
+        Expr::If(if_expr) => if_expr.eval(ctxt),
+        Expr::Loop(loop_expr) => loop_expr.eval(ctxt),
+        Expr::While(while_expr) => while_expr.eval(ctxt),
+        Expr::For(for_expr) => for_expr.eval(ctxt),
+        Expr::Break(break_expr) => break_expr.eval(ctxt),
+        Expr::Continue(continue_expr) => continue_expr.eval(ctxt),
+        Expr::Return(return_expr) => return_expr.eval(ctxt),
+        Expr::Call(call) => call.eval(ctxt),
+        Expr::Index(index) => index.eval(ctxt),
+        Expr::Unary(unary) => unary.eval(ctxt),
+        Expr::Binary(binary) => binary.eval(ctxt),
+        Expr::Assign(assign) => assign.eval(ctxt),
+        Expr::Lambda(lambda) => lambda.eval(ctxt),
+    })
+}
@@ -0,0 +1,102 @@
+use cas_parser::parser::ast::{expr::Expr, index::{Index, IndexKind}};
+use crate::eval_break;
+use crate::numerical::{
+    ctxt::Ctxt,
+    error::{kind::{IndexOutOfBounds, InvalidIndexTarget, InvalidIndexType}, Error},
+    eval::Eval,
+    value::Value,
+};
+use rug::Integer;
+use std::ops::Range;
+
+/// The elements of a list that an [`Index`] refers to.
+pub(crate) enum Position {
+    /// A single element, at the given index.
+    Single(usize),
+
+    /// A range of elements.
+    Slice(Range<usize>),
+}
+
+/// Evaluates an index or slice bound with `eval`, counting negative indices from the end of a list
+/// of the given length.
+///
+/// Returns the adjusted index, and the index as it was given.
+fn eval_offset(
+    expr: &Expr,
+    len: usize,
+    eval: &mut impl FnMut(&Expr) -> Result<Value, Error>,
+) -> Result<(Integer, Integer), Error> {
+    match eval(expr)?.coerce_integer() {
+        Value::Integer(n) if n < 0 => Ok((Integer::from(&n + len), n)),
+        Value::Integer(n) => Ok((n.clone(), n)),
+        value => Err(Error::new(vec![expr.span()], InvalidIndexType {
+            expr_type: value.typename(),
+        })),
+    }
+}
+
+/// Evaluates the index of an [`Index`] expression into a list of the given length.
+///
+/// A single index must refer to an element of the list. The bounds of a slice are clamped to the
+/// bounds of the list instead, and a slice whose end is before its start is empty.
+pub(crate) fn eval_position(index: &Index, len: usize, ctxt: &mut Ctxt) -> Result<Position, Error> {
+    position_with(index, len, |expr| expr.eval(ctxt))
+}
+
+/// Computes the position that an [`Index`] expression refers to in a list of the given length, as
+/// in [`eval_position`], using `eval` to evaluate the index expressions in order.
+pub(crate) fn position_with(
+    index: &Index,
+    len: usize,
+    mut eval: impl FnMut(&Expr) -> Result<Value, Error>,
+) -> Result<Position, Error> {
+    match &index.index {
+        IndexKind::Single(expr) => {
+            let (n, given) = eval_offset(expr, len, &mut eval)?;
+            n.to_usize()
+                .filter(|&n| n < len)
+                .map(Position::Single)
+                .ok_or_else(|| Error::new(
+                    vec![index.target.span(), index.bracket_span.clone()],
+                    IndexOutOfBounds { index: given.to_string(), len },
+                ))
+        },
+        IndexKind::Slice { start, end } => {
+            let mut bound = |expr: &Option<Box<Expr>>, default: usize| match expr {
+                Some(expr) => {
+                    let (n, _) = eval_offset(expr, len, &mut eval)?;
+                    Ok::<_, Error>(n.clamp(&0, &len).to_usize().unwrap())
+                },
+                None => Ok(default),
+            };
+            let start = bound(start, 0)?;
+            let end = bound(end, len)?;
+            Ok(Position::Slice(start..end.max(start)))
+        },
+    }
+}
+
+/// Returns the error for indexing into a value that is not a list.
+pub(crate) fn invalid_target(index: &Index, value: &Value) -> Error {
+    Error::new(
+        vec![index.target.span(), index.bracket_span.clone()],
+        InvalidIndexTarget { expr_type: value.typename() },
+    )
+}
+
+impl Eval for Index {
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        // a matrix is indexed like the list of its rows
+        let mut values = match eval_break!(self.target, ctxt) {
+            Value::List(values) => values,
+            Value::Matrix(matrix) => matrix.into_rows().into_iter().map(Value::List).collect(),
+            target => return Err(invalid_target(self, &target)),
+        };
+
+        match eval_position(self, values.len(), ctxt)? {
+            Position::Single(i) => Ok(values.swap_remove(i)),
+            Position::Slice(range) => Ok(Value::List(values.drain(range).collect())),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use cas_parser::parser::ast::lambda::Lambda as AstLambda;
+use crate::numerical::{
+    ctxt::{Ctxt, Func, Function, Lambda},
+    error::Error,
+    eval::Eval,
+    value::Value,
+};
+use std::sync::Arc;
+
+impl Eval for AstLambda {
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        Ok(Value::Function(Function {
+            name: None,
+            func: Func::Lambda(Arc::new(Lambda {
+                params: self.params.clone(),
+                body: (*self.body).clone(),
+                captures: ctxt.capture_scope(),
+            })),
+        }))
+    }
+}
@@ -1,20 +1,48 @@
-use cas_parser::parser::ast::literal::Literal;
+use cas_parser::parser::ast::{expr::Expr, literal::Literal};
+use crate::interval::Interval;
 use crate::numerical::{
-    ctxt::Ctxt,
+    ctxt::{interval_constant, Ctxt, NumberMode},
     error::{kind::UndefinedVariable, Error},
     eval::Eval,
     value::Value,
 };
 use crate::primitive::{from_str_radix, float_from_str, int_from_str};
 
+/// Returns an error if the operand of an operator is a symbol that does not name a variable.
+///
+/// Elsewhere, a symbol that names a function but not a variable evaluates to that function.
+/// Functions cannot be operands, so a symbol operand such as `sin` in `sin + 1` is undefined
+/// instead.
+pub(crate) fn check_operand(expr: &Expr, ctxt: &Ctxt) -> Result<(), Error> {
+    match expr {
+        Expr::Literal(Literal::Symbol(sym)) if ctxt.lookup_var(&sym.name).is_none() => {
+            Err(Error::new(vec![sym.span.clone()], UndefinedVariable { name: sym.name.clone() }))
+        },
+        _ => Ok(()),
+    }
+}
+
 impl Eval for Literal {
     fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
         match self {
             Literal::Integer(int) => Ok(Value::Integer(int_from_str(&int.value))),
-            Literal::Float(float) => Ok(Value::Float(float_from_str(&float.value))),
+            Literal::Float(float) => Ok(match ctxt.number_mode {
+                NumberMode::Standard => Value::Float(float_from_str(&float.value)),
+                NumberMode::Interval => Value::Interval(Interval::parse(&float.value).unwrap()),
+            }),
             Literal::Radix(radix) => Ok(Value::Integer(from_str_radix(radix.value.as_str(), radix.base))),
             Literal::Boolean(boolean) => Ok(Value::Boolean(boolean.value)),
+            Literal::Symbol(sym) if ctxt.number_mode == NumberMode::Interval
+                && ctxt.is_constant(&sym.name) => {
+                // use an enclosure of the constant, unless it has been redefined
+                Ok(interval_constant(&sym.name)
+                    .map(Value::Interval)
+                    .unwrap_or_else(|| ctxt.get_var(&sym.name).unwrap()))
+            },
+            // a symbol that names a function, but not a variable, evaluates to that function, so
+            // that functions can be passed by name, as in `map(sqrt, [1, 4, 9])`
             Literal::Symbol(sym) => ctxt.get_var(sym.name.as_str())
+                .or_else(|| ctxt.get_function(&sym.name).map(Value::Function))
                 .ok_or_else(|| Error::new(vec![sym.span.clone()], UndefinedVariable { name: sym.name.clone() })),
             Literal::Unit(_) => Ok(Value::Unit),
             Literal::List(list) => {
@@ -1,5 +1,32 @@
-use cas_parser::parser::ast::{expr::Expr, loop_expr::Loop, while_expr::While};
-use crate::numerical::{ctxt::Ctxt, error::Error, eval::Eval, value::Value};
+use cas_parser::parser::ast::{expr::Expr, for_expr::For, loop_expr::Loop, while_expr::While};
+use crate::eval_break;
+use crate::numerical::{
+    ctxt::Ctxt,
+    error::{kind::NotIterable, Error},
+    eval::Eval,
+    value::Value,
+};
+
+/// Checks whether the body of a loop was stopped by a `break` or `return` expression, in which case
+/// the loop should stop and evaluate to the value of the body.
+///
+/// A `break` expression only stops the innermost loop, so the flag is reset here. A `return`
+/// expression must also stop any surrounding loops, until it reaches the function call. A
+/// `continue` expression only stops the current iteration of the loop.
+fn should_stop(ctxt: &mut Ctxt) -> bool {
+    if !ctxt.break_loop {
+        return false;
+    }
+    if ctxt.continue_loop {
+        ctxt.break_loop = false;
+        ctxt.continue_loop = false;
+        return false;
+    }
+    if !ctxt.return_call {
+        ctxt.break_loop = false;
+    }
+    true
+}
 
 /// Trait for loop expressions that utilize a boolean condition to determine whether they should
 /// terminate.
@@ -10,6 +37,8 @@ trait ConditionalLoop {
     /// Evaluates the loop expression.
     fn eval_loop(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
         while self.condition(ctxt)? {
+            // the body is not evaluated through `Expr::eval`, so the step is taken here
+            ctxt.step(|| self.body().span())?;
             let value = match self.body() {
                 Expr::Literal(literal) => literal.eval(ctxt),
                 Expr::Paren(paren) => paren.expr.eval(ctxt),
@@ -17,16 +46,19 @@ trait ConditionalLoop {
                 Expr::If(if_expr) => if_expr.eval(ctxt),
                 Expr::Loop(loop_expr) => loop_expr.eval(ctxt),
                 Expr::While(while_expr) => while_expr.eval(ctxt),
+                Expr::For(for_expr) => for_expr.eval(ctxt),
                 Expr::Break(break_expr) => break_expr.eval(ctxt),
                 Expr::Continue(_) => continue,
+                Expr::Return(return_expr) => return_expr.eval(ctxt),
                 Expr::Call(call) => call.eval(ctxt),
+                Expr::Index(index) => index.eval(ctxt),
                 Expr::Unary(unary) => unary.eval(ctxt),
                 Expr::Binary(binary) => binary.eval(ctxt),
                 Expr::Assign(assign) => assign.eval(ctxt),
+                Expr::Lambda(lambda) => lambda.eval(ctxt),
             }?;
 
-            if ctxt.break_loop {
-                ctxt.break_loop = false;
+            if should_stop(ctxt) {
                 return Ok(value);
             }
         }
@@ -66,3 +98,26 @@ impl Eval for While {
         self.eval_loop(ctxt)
     }
 }
+
+impl Eval for For {
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        // ranges are iterated over lazily
+        let values: Box<dyn Iterator<Item = Value>> = match eval_break!(self.iterable, ctxt) {
+            Value::Range(range) => Box::new(range.iter().map(Value::Integer)),
+            Value::List(list) => Box::new(list.into_iter()),
+            value => return Err(Error::new(vec![self.iterable.span()], NotIterable {
+                expr_type: value.typename(),
+            })),
+        };
+
+        for value in values {
+            ctxt.add_var(&self.variable.name, value);
+            let value = self.body.eval(ctxt)?;
+            if should_stop(ctxt) {
+                return Ok(value);
+            }
+        }
+
+        Ok(Value::Unit)
+    }
+}
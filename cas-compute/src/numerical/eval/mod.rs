@@ -7,15 +7,24 @@ mod continue_expr;
 mod error;
 mod expr;
 mod if_expr;
+mod index;
+mod lambda;
 mod literal;
 mod loops;
 mod primary;
+mod return_expr;
 mod stmt;
 mod unary;
 
 use super::{ctxt::Ctxt, error::Error, value::Value};
 
 pub use block::eval_stmts;
+pub(crate) use assign::{assign_to_element, index_chain};
+pub(crate) use binary::{check_operands, eval_operands, eval_operands_in};
+pub(crate) use call::{apply_builtin, call_with_values, enter_call, leave_call};
+pub(crate) use index::{invalid_target, position_with, Position};
+pub(crate) use literal::check_operand;
+pub(crate) use unary::eval_operand;
 
 /// Any type that can be evaluated to produce a value.
 pub trait Eval {
@@ -36,8 +45,9 @@ mod tests {
     use crate::consts;
     use crate::funcs::miscellaneous::{Abs, Factorial};
     use crate::numerical::builtin::Builtin;
+    use crate::numerical::test_util::{eval, eval_str};
     use crate::primitive::float;
-    use rug::ops::Pow;
+    use rug::{ops::Pow, Rational};
     use super::*;
 
     use cas_parser::parser::{ast::expr::Expr, Parser};
@@ -60,7 +70,7 @@ mod tests {
     fn binary_and_unary() {
         let mut parser = Parser::new("3 * -5 / 5! + 6");
         let expr = parser.try_parse_full::<Expr>().unwrap();
-        assert_eq!(expr.eval_default().unwrap(), 5.875.into());
+        assert_eq!(expr.eval_default().unwrap(), Rational::from((47, 8)).into());
     }
 
     #[test]
@@ -70,6 +80,22 @@ mod tests {
         assert_eq!(expr.eval_default().unwrap(), 6.into());
     }
 
+    #[test]
+    fn exact_rationals() {
+        let eval = |source: &str| Parser::new(source).try_parse_full::<Expr>().unwrap().eval_default().unwrap();
+        assert_eq!(eval("1/3 + 1/3 + 1/3"), 1.into());
+        assert_eq!(eval("1/3 - 1/2"), Rational::from((-1, 6)).into());
+        assert_eq!(eval("(2/3)^-2"), Rational::from((9, 4)).into());
+        assert_eq!(eval("2^-3"), Rational::from((1, 8)).into());
+        assert_eq!(eval("7/2 % 2"), Rational::from((3, 2)).into());
+        assert_eq!(eval("1/3 == 2/6"), true.into());
+        assert_eq!(eval("-(1/3)"), Rational::from((-1, 3)).into());
+
+        // mixing rationals with floats is inexact
+        assert_eq!(eval("1/4 + 0.5"), 0.75.into());
+        assert_eq!(eval("(1/4)^(1/2)"), 0.5.into());
+    }
+
     #[test]
     fn degree_to_radian() {
         let mut parser = Parser::new("90 * 2 * pi / 360");
@@ -77,7 +103,7 @@ mod tests {
 
         // assert approximate equality for floating point numbers
         let val1 = expr.eval_default().unwrap();
-        let val2 = (&*consts::PI / float(2)).into();
+        let val2 = (&consts::pi() / float(2)).into();
         assert!(val1.approx_eq(&val2));
     }
 
@@ -85,7 +111,7 @@ mod tests {
     fn precision() {
         let mut parser = Parser::new("e^2 - tau");
         let expr = parser.try_parse_full::<Expr>().unwrap();
-        assert_eq!(expr.eval_default().unwrap(), Value::Float(consts::E.clone().pow(2) - &*consts::TAU));
+        assert_eq!(expr.eval_default().unwrap(), Value::Float(consts::e().pow(2) - &consts::tau()));
     }
 
     #[test]
@@ -99,10 +125,31 @@ mod tests {
         } else {
             unreachable!("factorial of 17 is an integer")
         };
-        let val2 = Value::Float(consts::PI.clone().pow(2) * fac_17 / -float(4.9) + &*consts::E);
+        let val2 = Value::Float(consts::pi().pow(2) * fac_17 / -float(4.9) + &consts::e());
         assert!(val1.approx_eq(&val2));
     }
 
+    #[test]
+    fn context_precision() {
+        let mut ctxt = Ctxt::default();
+        let mut eval = |source: &str| Parser::new(source).try_parse_full_many().map(|stmts| eval_stmts(&stmts, &mut ctxt)).unwrap();
+
+        assert_eq!(eval("precision()").unwrap(), 512.into());
+        let Value::Float(pi) = eval("precision(2000); pi").unwrap() else { panic!("pi is a float") };
+        assert_eq!(pi.prec(), 2000);
+        let Value::Float(half) = eval("1.5 / 3").unwrap() else { panic!("1.5 / 3 is a float") };
+        assert_eq!(half.prec(), 2000);
+
+        // redefined constants are left alone
+        assert_eq!(eval("pi = 3; precision(64); pi").unwrap(), 3.into());
+        assert!(eval("precision(0)").is_err());
+
+        let mut ctxt = Ctxt::default();
+        ctxt.set_precision(100);
+        let value = Parser::new("e / 2").try_parse_full::<Expr>().unwrap().eval(&mut ctxt).unwrap();
+        assert!(matches!(value, Value::Float(n) if n.prec() == 100));
+    }
+
     #[test]
     fn func_call() {
         let mut ctxt = Ctxt::default();
@@ -130,16 +177,16 @@ mod tests {
         // call function
         let tries = [
             (None, None, 18),
-            // (None, Some(4.0), 12.0), // TODO: there is currently no way to pass only the second argument
+            (None, Some(4), 12),
             (Some(9), None, 54),
             (Some(8), Some(14), 112),
         ];
         for (n, k, expected_result) in tries {
-            let source = format!(
-                "f({}{})",
-                n.map_or("".to_string(), |n| n.to_string()),
-                k.map_or("".to_string(), |k| format!(", {}", k)),
-            );
+            let args = n.map(|n| n.to_string())
+                .into_iter()
+                .chain(k.map(|k| format!("k = {}", k)))
+                .collect::<Vec<_>>();
+            let source = format!("f({})", args.join(", "));
             let mut parser = Parser::new(&source);
             let expr = parser.try_parse_full::<Expr>().unwrap();
             assert_eq!(
@@ -151,6 +198,149 @@ mod tests {
         }
     }
 
+    #[test]
+    fn named_arguments() {
+        let error = |source: &str| {
+            let err = eval(source).unwrap_err();
+            (format!("{:?}", err.kind).split([' ', '{']).next().unwrap().to_owned(), err.spans)
+        };
+
+        assert_eq!(eval_str("f(a, b = 2, c = 3) = [a, b, c]; f(c = 1, a = 5)"), "[5, 2, 1]");
+        assert_eq!(eval_str("f(x, y = x + 1) = x * y; f(y = 10, x = 2)"), "20");
+        assert_eq!(eval_str("g = (x, y = 1) -> x - y; g(y = 5, x = 1)"), "-4");
+        assert_eq!(eval_str("ncr(k = 2, n = 5)"), "10");
+        assert_eq!(eval_str("round(s = 0.5, n = 2.7)"), "2.5");
+
+        // arguments are evaluated in the order they are written
+        assert_eq!(eval_str("l = [0]; f(a, b) = a - b; f(b = l[0] = 1, a = l[0] = 2); l"), "[2]");
+
+        let f = "f(n, k = 6) = n * k;";
+        assert_eq!(error(&format!("{f} f(3, m = 1)")), ("UnknownArgument".to_owned(), vec![21..23, 31..32, 26..27]));
+        assert_eq!(error(&format!("{f} f(3, n = 1)")), ("DuplicateArgument".to_owned(), vec![21..23, 31..32, 23..24, 26..31]));
+        assert_eq!(error(&format!("{f} f(k = 1, k = 2)")), ("DuplicateArgument".to_owned(), vec![21..23, 35..36, 23..28, 30..35]));
+        assert_eq!(error(&format!("{f} f(k = 1)")).0, "MissingArgument");
+
+        // positional and named arguments both count towards the number of arguments given
+        for source in [format!("{f} f(3, 4, k = 1)"), format!("{f} f(3, k = 1, m = 2)"), "ncr(5, 2, k = 1)".to_owned()] {
+            let kind = format!("{:?}", eval(&source).unwrap_err().kind);
+            assert!(kind.starts_with("TooManyArguments"), "{}", source);
+            assert!(kind.contains("expected: 2, given: 3"), "{}", source);
+        }
+        assert_eq!(error("ncr(k = 2)").0, "MissingArgument");
+        assert_eq!(error("ncr(5, m = 2)").0, "UnknownArgument");
+        assert_eq!(error("sum(l = [1, 2])").0, "NamedArgumentsUnsupported");
+    }
+
+    #[test]
+    fn function_values() {
+        assert_eq!(eval_str("f = abs; f(-3)"), "3");
+        assert_eq!(eval_str("f(x) = x + 1; g = f; g(1)"), "2");
+        assert_eq!(eval_str("f = x -> x^2; f(3)"), "9");
+        assert_eq!(eval_str("f = (x, y = 10) -> x + y; f(1)"), "11");
+        assert_eq!(eval_str("f = x -> x; f"), "x -> x");
+        assert_eq!(eval_str("abs"), "abs");
+        assert!(eval("f = x -> x; f(1, 2)").is_err());
+        assert!(eval("f = x -> x; -f").is_err());
+
+        // functions cannot be operands, so a name that is not a variable is undefined as an operand
+        for source in ["sn + 1", "sin + 1", "2 * sin", "-sin", "f(x) = x; f!"] {
+            let err = eval(source).unwrap_err();
+            assert!(format!("{:?}", err.kind).starts_with("UndefinedVariable"), "source: {}", source);
+        }
+
+        // a function definition replaces a variable holding a function of the same name
+        assert_eq!(eval_str("f = x -> x^2; f(x) = x + 1; f(2)"), "3");
+    }
+
+    #[test]
+    fn lambda_closures() {
+        // global variables are looked up when the lambda is called
+        assert_eq!(eval_str("a = 2; f = x -> a * x; a = 3; f(5)"), "15");
+        assert_eq!(eval_str("a = 2; f = x -> a * x; a = 3; f(a)"), "9");
+        assert_eq!(eval_str("adder = n -> (x -> x + n); inc = adder(1); inc(41)"), "42");
+        assert_eq!(eval_str("compose = (f, g) -> (x -> f(g(x))); h = compose(abs, x -> x - 5); h(1)"), "4");
+        assert_eq!(eval_str("fact = n -> if n <= 1 then 1 else n * fact(n - 1); fact(5)"), "120");
+    }
+
+    #[test]
+    fn lexical_scoping() {
+        // plain assignment in a function defines a local variable
+        assert_eq!(eval_str("x = 14; g() = { x = 9 }; g(); x"), "14");
+        assert!(eval("f(n) = { y = n; y }; f(3); y").is_err());
+
+        // compound assignment writes to the variable where it is defined
+        assert_eq!(eval_str("count = 0; inc() = count += 1; inc(); inc(); count"), "2");
+        assert_eq!(eval_str("list = [1, 2]; set() = list[0] = 5; set(); list"), "[5, 2]");
+
+        // lambdas capture the local variables of the function they are created in
+        assert_eq!(eval_str("f(n) = { k = n * 2; x -> x + k }; g = f(5); g(1)"), "11");
+
+        // functions defined in a function are local, and can be recursive
+        assert_eq!(eval_str("f(n) = { fact(k) = if k <= 1 then 1 else k * fact(k - 1); fact(n) }; f(5)"), "120");
+        assert!(eval("f() = { h(x) = x }; f(); h(1)").is_err());
+
+        // a break in any statement of a loop body ends the loop
+        assert_eq!(eval_str("i = 0; loop { i += 1; if i == 3 then break; 0 }; i"), "3");
+
+        // the arguments of a builtin are evaluated in the calling scope
+        assert_eq!(eval_str("f() = { x = 1; abs({ x = -5 }); x }; f()"), "-5");
+
+        // variables bound by a builtin, such as the index of `sum`, do not leak out of the call
+        assert_eq!(eval_str("f(n) = { k = 10; s = sum(k, k, 1, n); [k, s] }; f(3)"), "[10, 6]");
+        assert!(eval("sum(k, k, 1, 3); k").is_err());
+
+        // a lambda keeps the values of the variables it captured, even if they are later reassigned
+        assert_eq!(eval_str("f() = { k = 1; g = x -> x + k; k = 100; g(0) }; f()"), "1");
+    }
+
+    #[test]
+    fn captured_scope_is_shared() {
+        let stmts = Parser::new("f() = { l = [1, 2, 3]; x -> l }; f()").try_parse_full_many().unwrap();
+        let Value::Function(function) = eval_stmts(&stmts, &mut Ctxt::default()).unwrap() else {
+            panic!("expected a function");
+        };
+        let crate::numerical::ctxt::Func::Lambda(lambda) = function.func else {
+            panic!("expected a lambda");
+        };
+
+        // capturing the scope does not copy it, so it is only referenced by the lambda
+        let captures = lambda.captures.as_ref().unwrap();
+        assert_eq!(std::sync::Arc::strong_count(captures), 1);
+        assert_eq!(captures.get_var("l").unwrap().to_string(), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn ranges_and_for_loops() {
+        assert_eq!(eval_str("n = 4; 0..n + 1"), "0..5");
+        assert_eq!(eval_str("1..=3"), "1..=3");
+        assert_eq!(eval_str("sum(1..=100)"), "5050");
+        assert_eq!(eval_str("map(x -> x^2, 0..4)"), "[0, 1, 4, 9]");
+        assert!(eval("0..1.5").is_err());
+
+        assert_eq!(eval_str("s = 0; for i in 0..10 { s += i }; s"), "45");
+        assert_eq!(eval_str("s = 0; for i in 5..=1 { s += i }; s"), "0");
+        assert_eq!(eval_str("p = 1; for x in [2, 3, 7] { p *= x }; p"), "42");
+        assert_eq!(eval_str("for i in 0..1000000000 { if i^2 > 50 then break i }"), "8");
+        assert_eq!(eval_str("c = 0; for i in 0..3 { for j in 0..3 { if j > i then break; c += 1 } }; c"), "6");
+        assert!(eval("for i in 5 { i }").is_err());
+    }
+
+    #[test]
+    fn return_expr() {
+        assert_eq!(eval_str("f(x) = { if x < 0 then return 0; x }; [f(-3), f(3)]"), "[0, 3]");
+        assert_eq!(eval_str("f() = return; f()"), "()");
+
+        // `return` exits all loops in the function, but only the innermost function
+        let find = "find(l, v) = { i = 0; for x in l { while true then { if x == v then return i; break }; i += 1 }; -1 };";
+        assert_eq!(eval_str(&format!("{find} find([4, 5, 6], 6)")), "2");
+        assert_eq!(eval_str(&format!("{find} find([4, 5, 6], 7)")), "-1");
+        assert_eq!(eval_str(&format!("{find} c = 0; for i in 0..3 {{ c += find([0, 1], i) }}; c")), "0");
+        assert_eq!(eval_str("f(x) = { g = y -> { return y * 2 }; g(x) + 1 }; f(5)"), "11");
+
+        // a function returning early can be differentiated
+        assert_eq!(eval_str("f(x) = { return x^2; 0 }; round(f'(3))"), "6");
+    }
+
     #[test]
     fn builtin_func_arg_check() {
         assert_eq!(Abs.eval(&Ctxt::default(), &mut [Value::from(4.0)].into_iter()).unwrap().coerce_float(), 4.0.into());
@@ -0,0 +1,17 @@
+use cas_parser::parser::ast::return_expr::Return;
+use crate::numerical::{ctxt::Ctxt, error::Error, eval::Eval, value::Value};
+
+impl Eval for Return {
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        // like `break`, evaluate the entire expression first, then begin returning from the
+        // function
+        // the function call will set both flags back to false once we propogate up the stack
+        let value = match &self.value {
+            Some(value) => value.eval(ctxt)?,
+            None => Value::Unit,
+        };
+        ctxt.break_loop = true;
+        ctxt.return_call = true;
+        Ok(value)
+    }
+}
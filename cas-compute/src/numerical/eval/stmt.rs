@@ -3,11 +3,14 @@ use crate::numerical::{ctxt::Ctxt, error::Error, eval::Eval, value::Value};
 
 impl Eval for Stmt {
     fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
-        if self.semicolon.is_some() {
-            self.expr.eval(ctxt)?;
+        let value = self.expr.eval(ctxt)?;
+
+        // the value of a `break` or `return` expression is propogated even if the statement ends
+        // with a semicolon, as in `if x < 0 then return 0;`
+        if self.semicolon.is_some() && !ctxt.break_loop {
             Ok(Value::Unit)
         } else {
-            self.expr.eval(ctxt)
+            Ok(value)
         }
     }
 }
@@ -1,56 +1,101 @@
-use cas_parser::parser::{ast::unary::Unary, token::op::UnaryOpKind};
+use cas_parser::parser::{ast::unary::Unary, token::op::{UnaryOp, UnaryOpKind}};
 use crate::eval_break;
 use crate::funcs::miscellaneous::Factorial;
 use crate::numerical::{
     ctxt::Ctxt,
     error::{kind::InvalidUnaryOperation, Error},
-    eval::Eval,
+    eval::{check_operand, Eval},
     value::Value,
 };
 use crate::primitive::{complex, int_from_float, float};
+use crate::symbolic::{expr::{Expr as SymExpr, Primary}, simplify};
+use std::ops::Range;
 
-impl Eval for Unary {
-    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
-        let operand = eval_break!(self.operand, ctxt).coerce_number();
-        match operand {
-            Value::Float(num) => Ok(match self.op.kind {
-                UnaryOpKind::Not => Value::Boolean(num.is_zero()),
-                UnaryOpKind::BitNot => Value::Float(float(!int_from_float(num))),
-                UnaryOpKind::Factorial => Factorial::eval_static(num),
-                UnaryOpKind::Neg => Value::Float(-num),
-            }),
-            Value::Integer(num) => Ok(match self.op.kind {
-                UnaryOpKind::Not => Value::Boolean(num.is_zero()),
-                UnaryOpKind::BitNot => Value::Integer(!num),
-                UnaryOpKind::Factorial => Factorial::eval_static(float(num)),
-                UnaryOpKind::Neg => Value::Integer(-num),
-            }),
-            Value::Complex(ref comp) => Ok(match self.op.kind {
-                UnaryOpKind::Not => Value::Boolean(comp.is_zero()),
-                UnaryOpKind::Neg => Value::Complex(complex(&*comp.as_neg())),
-                _ => return Err(Error::new(vec![self.operand.span(), self.op.span.clone()], InvalidUnaryOperation {
-                    op: self.op.kind,
-                    expr_type: operand.typename(),
-                })),
-            }),
-            Value::Boolean(b) => {
-                if self.op.kind == UnaryOpKind::Not {
-                    Ok(Value::Boolean(!b))
-                } else {
-                    Err(Error::new(vec![self.operand.span(), self.op.span.clone()], InvalidUnaryOperation {
-                        op: self.op.kind,
-                        expr_type: operand.typename(),
-                    }))
-                }
-            },
-            Value::Unit => Err(Error::new(vec![self.operand.span(), self.op.span.clone()], InvalidUnaryOperation {
-                op: self.op.kind,
+/// Applies the unary operator to the value its operand evaluated to. `operand_span` is the span of
+/// the operand, used to report errors.
+///
+/// The operator is applied element-wise to lists, so `-[1, 2]` is `[-1, -2]`.
+pub(crate) fn eval_operand(
+    op: &UnaryOp,
+    operand_span: &Range<usize>,
+    operand: Value,
+) -> Result<Value, Error> {
+    let spans = || vec![operand_span.clone(), op.span.clone()];
+    let operand = operand.coerce_number();
+    match operand {
+        Value::Float(num) => Ok(match op.kind {
+            UnaryOpKind::Not => Value::Boolean(num.is_zero()),
+            UnaryOpKind::BitNot => Value::Float(float(!int_from_float(num))),
+            UnaryOpKind::Factorial => Factorial::eval_static(num),
+            UnaryOpKind::Neg => Value::Float(-num),
+        }),
+        Value::Integer(num) => Ok(match op.kind {
+            UnaryOpKind::Not => Value::Boolean(num.is_zero()),
+            UnaryOpKind::BitNot => Value::Integer(!num),
+            UnaryOpKind::Factorial => Factorial::eval_static(float(num)),
+            UnaryOpKind::Neg => Value::Integer(-num),
+        }),
+        Value::Rational(num) => Ok(match op.kind {
+            UnaryOpKind::Not => Value::Boolean(num == 0),
+            UnaryOpKind::BitNot => Value::Float(float(!int_from_float(float(num)))),
+            UnaryOpKind::Factorial => Factorial::eval_static(float(num)),
+            UnaryOpKind::Neg => Value::Rational(-num),
+        }),
+        Value::Complex(ref comp) => Ok(match op.kind {
+            UnaryOpKind::Not => Value::Boolean(comp.is_zero()),
+            UnaryOpKind::Neg => Value::Complex(complex(&*comp.as_neg())),
+            _ => return Err(Error::new(spans(), InvalidUnaryOperation {
+                op: op.kind,
                 expr_type: operand.typename(),
             })),
-            Value::List(_) => Err(Error::new(vec![self.operand.span(), self.op.span.clone()], InvalidUnaryOperation {
-                op: self.op.kind,
+        }),
+        Value::Interval(ref num) => Ok(match op.kind {
+            UnaryOpKind::Neg => Value::Interval(num.neg()),
+            _ => return Err(Error::new(spans(), InvalidUnaryOperation {
+                op: op.kind,
+                expr_type: operand.typename(),
+            })),
+        }),
+        Value::Boolean(b) => {
+            if op.kind == UnaryOpKind::Not {
+                Ok(Value::Boolean(!b))
+            } else {
+                Err(Error::new(spans(), InvalidUnaryOperation {
+                    op: op.kind,
+                    expr_type: operand.typename(),
+                }))
+            }
+        },
+        Value::Unit | Value::Range(_) | Value::Function(_) => Err(Error::new(spans(), InvalidUnaryOperation {
+            op: op.kind,
+            expr_type: operand.typename(),
+        })),
+        Value::List(values) => values.into_iter()
+            .map(|value| eval_operand(op, operand_span, value))
+            .collect::<Result<_, _>>()
+            .map(Value::List),
+        Value::Matrix(ref matrix) => match op.kind {
+            UnaryOpKind::Neg => Ok(Value::Matrix(matrix.neg())),
+            _ => Err(Error::new(spans(), InvalidUnaryOperation {
+                op: op.kind,
                 expr_type: operand.typename(),
             })),
-        }
+        },
+        Value::Expr(expr) => match op.kind {
+            UnaryOpKind::Neg => Ok(Value::from(simplify(&-expr))),
+            UnaryOpKind::Factorial => Ok(Value::Expr(SymExpr::Primary(Primary::Call("factorial".to_string(), vec![expr])))),
+            _ => Err(Error::new(spans(), InvalidUnaryOperation {
+                op: op.kind,
+                expr_type: "Expr",
+            })),
+        },
+    }
+}
+
+impl Eval for Unary {
+    fn eval(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        check_operand(&self.operand, ctxt)?;
+        let operand = eval_break!(self.operand, ctxt);
+        eval_operand(&self.op, &self.operand.span(), operand)
     }
 }
@@ -0,0 +1,580 @@
+//! Compilation of expressions to closures over `f64`s, for evaluating them at many points.
+//!
+//! Evaluating an expression with [`Eval`] is exact where possible, and otherwise uses [`Float`]s
+//! at high precision. This is too slow when the same expression must be evaluated at thousands
+//! of points, such as when it is graphed, and more precise than a graph needs. [`FastFn::compile`]
+//! instead compiles an expression into a tree of closures that evaluate it with `f64` arithmetic,
+//! taking the values of its parameters as a slice.
+//!
+//! Real arithmetic, comparisons, `if` expressions, the common math builtins, and calls to
+//! non-recursive user-defined functions can be compiled. Variables that are not parameters are
+//! read from the context once, when the expression is compiled. If the expression contains
+//! anything else, such as a list or a call to `sum`, the compiled function falls back to
+//! evaluating it with [`Eval`] at every point, so the result is the same, only slower.
+//!
+//! Results that are not real numbers, such as `sqrt(-1)`, are [`f64::NAN`].
+//!
+//! ```
+//! use cas_compute::numerical::{ctxt::Ctxt, fast::FastFn};
+//! use cas_parser::parser::{ast::expr::Expr, Parser};
+//!
+//! let expr = Parser::new("if x < 0 then -x else sqrt(x)").try_parse_full::<Expr>().unwrap();
+//! let f = FastFn::compile(&expr, &["x"], &Ctxt::default());
+//! assert!(f.is_native());
+//! assert_eq!(f.eval(&[-3.0]), 3.0);
+//! assert_eq!(f.eval(&[16.0]), 4.0);
+//! ```
+//!
+//! [`Float`]: rug::Float
+
+use cas_parser::parser::{
+    ast::{call::Call, expr::Expr, literal::Literal},
+    token::op::{BinOpKind, UnaryOpKind},
+};
+use crate::numerical::{
+    ctxt::{Ctxt, Func, NumberMode, TrigMode},
+    eval::Eval,
+    value::Value,
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Range,
+};
+
+/// The maximum depth of nested calls to user-defined functions that are compiled into the caller.
+const MAX_INLINE_DEPTH: usize = 16;
+
+/// A compiled expression with a real value.
+type Real = Box<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// A compiled expression with a boolean value.
+type Cond = Box<dyn Fn(&[f64]) -> bool + Send + Sync>;
+
+/// The `f64` implementation of a builtin function of one argument.
+type UnaryFn = fn(f64) -> f64;
+
+/// The `f64` implementation of a builtin function of two arguments.
+type BinaryFn = fn(f64, f64) -> f64;
+
+/// A compiled expression.
+enum Compiled {
+    /// An expression with a real value.
+    Real(Real),
+
+    /// An expression with a boolean value, such as a comparison.
+    Bool(Cond),
+}
+
+/// The part of an expression that cannot be compiled by [`FastFn::try_compile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    /// The span of the unsupported expression.
+    pub span: Range<usize>,
+}
+
+/// How a builtin function of real numbers treats angles, which are measured in degrees if the
+/// context is in [`TrigMode::Degrees`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Angle {
+    /// The function does not take or return an angle.
+    None,
+
+    /// The argument of the function is an angle.
+    Input,
+
+    /// The function returns an angle.
+    Output,
+}
+
+/// Returns the `f64` implementation of a builtin function of one argument.
+fn unary_builtin(name: &str) -> Option<(UnaryFn, Angle)> {
+    Some(match name {
+        "sin" => (f64::sin, Angle::Input),
+        "cos" => (f64::cos, Angle::Input),
+        "tan" => (f64::tan, Angle::Input),
+        "csc" => (|x: f64| x.sin().recip(), Angle::Input),
+        "sec" => (|x: f64| x.cos().recip(), Angle::Input),
+        "cot" => (|x: f64| x.tan().recip(), Angle::Input),
+        "asin" => (f64::asin, Angle::Output),
+        "acos" => (f64::acos, Angle::Output),
+        "atan" => (f64::atan, Angle::Output),
+        "acsc" => (|x: f64| x.recip().asin(), Angle::Output),
+        "asec" => (|x: f64| x.recip().acos(), Angle::Output),
+        "acot" => (|x: f64| x.recip().atan(), Angle::Output),
+        "sinh" => (f64::sinh, Angle::None),
+        "cosh" => (f64::cosh, Angle::None),
+        "tanh" => (f64::tanh, Angle::None),
+        "csch" => (|x: f64| x.sinh().recip(), Angle::None),
+        "sech" => (|x: f64| x.cosh().recip(), Angle::None),
+        "coth" => (|x: f64| x.tanh().recip(), Angle::None),
+        "asinh" => (f64::asinh, Angle::None),
+        "acosh" => (f64::acosh, Angle::None),
+        "atanh" => (f64::atanh, Angle::None),
+        "acsch" => (|x: f64| x.recip().asinh(), Angle::None),
+        "asech" => (|x: f64| x.recip().acosh(), Angle::None),
+        "acoth" => (|x: f64| x.recip().atanh(), Angle::None),
+        "exp" => (f64::exp, Angle::None),
+        "ln" => (f64::ln, Angle::None),
+        "log" => (f64::log10, Angle::None),
+        "sqrt" => (f64::sqrt, Angle::None),
+        // the real cube root, which `cbrt` is meant to return for negative numbers
+        "cbrt" => (f64::cbrt, Angle::None),
+        "abs" => (f64::abs, Angle::None),
+        "floor" => (f64::floor, Angle::None),
+        "ceil" => (f64::ceil, Angle::None),
+        "trunc" => (f64::trunc, Angle::None),
+        "sign" => (|x: f64| if x == 0.0 { x } else { x.signum() }, Angle::None),
+        _ => return None,
+    })
+}
+
+/// Returns the `f64` implementation of a builtin function of two arguments.
+fn binary_builtin(name: &str) -> Option<(BinaryFn, Angle)> {
+    Some(match name {
+        "atan2" => (f64::atan2, Angle::Output),
+        "log" => (|x: f64, base: f64| x.ln() / base.ln(), Angle::None),
+        "pow" => (f64::powf, Angle::None),
+        "root" => (|x: f64, n: f64| x.powf(n.recip()), Angle::None),
+        "hypot" => (f64::hypot, Angle::None),
+        "min" => (f64::min, Angle::None),
+        "max" => (f64::max, Angle::None),
+        _ => return None,
+    })
+}
+
+/// Compiles expressions into closures.
+struct Compiler<'a> {
+    /// The context that variables and functions are read from.
+    ctxt: &'a Ctxt,
+
+    /// The names of the parameters of the compiled function. When falling back to [`Eval`], they
+    /// are defined as variables in the context, so they hide any variable in the context with the
+    /// same name, even in the body of a user-defined function.
+    outer: &'a [String],
+
+    /// The names of the parameters of the expression being compiled, in the order their values
+    /// are given. These are the parameters of the user-defined function whose body is being
+    /// compiled, if any.
+    params: &'a [String],
+
+    /// The number of calls to user-defined functions being compiled into the expression.
+    depth: usize,
+}
+
+impl Compiler<'_> {
+    /// Compiles an expression with a real value.
+    fn real(&self, expr: &Expr) -> Result<Real, Unsupported> {
+        match self.expr(expr)? {
+            Compiled::Real(real) => Ok(real),
+            Compiled::Bool(_) => Err(Unsupported { span: expr.span() }),
+        }
+    }
+
+    /// Compiles an expression with a boolean value.
+    fn cond(&self, expr: &Expr) -> Result<Cond, Unsupported> {
+        match self.expr(expr)? {
+            Compiled::Bool(cond) => Ok(cond),
+            Compiled::Real(_) => Err(Unsupported { span: expr.span() }),
+        }
+    }
+
+    /// Compiles an expression.
+    fn expr(&self, expr: &Expr) -> Result<Compiled, Unsupported> {
+        let unsupported = || Unsupported { span: expr.span() };
+        match expr {
+            Expr::Literal(Literal::Symbol(symbol)) => {
+                if let Some(index) = self.params.iter().position(|param| *param == symbol.name) {
+                    return Ok(Compiled::Real(Box::new(move |args| args[index])));
+                }
+                if self.outer.contains(&symbol.name) {
+                    return Err(unsupported());
+                }
+                constant(self.ctxt.get_var(&symbol.name).ok_or_else(unsupported)?)
+                    .ok_or_else(unsupported)
+            },
+            Expr::Literal(Literal::Integer(_) | Literal::Float(_) | Literal::Radix(_) | Literal::Boolean(_)) => {
+                constant(expr.eval(&mut self.ctxt.clone()).map_err(|_| unsupported())?)
+                    .ok_or_else(unsupported)
+            },
+            Expr::Paren(paren) => self.expr(&paren.expr),
+            Expr::Block(block) => match block.stmts.as_slice() {
+                [stmt] if stmt.semicolon.is_none() && !matches!(stmt.expr, Expr::Assign(_)) => {
+                    self.expr(&stmt.expr)
+                },
+                _ => Err(unsupported()),
+            },
+            Expr::If(if_expr) => {
+                let else_expr = if_expr.else_expr.as_ref().ok_or_else(unsupported)?;
+                let condition = self.cond(&if_expr.condition)?;
+                Ok(match (self.expr(&if_expr.then_expr)?, self.expr(else_expr)?) {
+                    (Compiled::Real(then), Compiled::Real(otherwise)) => Compiled::Real(Box::new(
+                        move |args| if condition(args) { then(args) } else { otherwise(args) },
+                    )),
+                    (Compiled::Bool(then), Compiled::Bool(otherwise)) => Compiled::Bool(Box::new(
+                        move |args| if condition(args) { then(args) } else { otherwise(args) },
+                    )),
+                    _ => return Err(unsupported()),
+                })
+            },
+            Expr::Call(call) => self.call(call),
+            Expr::Unary(unary) => Ok(match unary.op.kind {
+                UnaryOpKind::Neg => {
+                    let operand = self.real(&unary.operand)?;
+                    Compiled::Real(Box::new(move |args| -operand(args)))
+                },
+                UnaryOpKind::Not => {
+                    let operand = self.cond(&unary.operand)?;
+                    Compiled::Bool(Box::new(move |args| !operand(args)))
+                },
+                UnaryOpKind::BitNot | UnaryOpKind::Factorial => return Err(unsupported()),
+            }),
+            Expr::Binary(binary) => {
+                if matches!(binary.op.kind, BinOpKind::And | BinOpKind::Or) {
+                    let (lhs, rhs) = (self.cond(&binary.lhs)?, self.cond(&binary.rhs)?);
+                    return Ok(Compiled::Bool(match binary.op.kind {
+                        BinOpKind::And => Box::new(move |args| lhs(args) && rhs(args)),
+                        _ => Box::new(move |args| lhs(args) || rhs(args)),
+                    }));
+                }
+
+                // an integer exponent is common, and exact with repeated multiplication
+                if let (BinOpKind::Exp, Expr::Literal(Literal::Integer(int))) = (binary.op.kind, &*binary.rhs) {
+                    if let Ok(exponent) = int.value.parse::<i32>() {
+                        let lhs = self.real(&binary.lhs)?;
+                        return Ok(Compiled::Real(Box::new(move |args| lhs(args).powi(exponent))));
+                    }
+                }
+
+                let (lhs, rhs) = (self.real(&binary.lhs)?, self.real(&binary.rhs)?);
+                let op: BinaryFn = match binary.op.kind {
+                    BinOpKind::Exp => f64::powf,
+                    BinOpKind::Mul => |a, b| a * b,
+                    BinOpKind::Div => |a, b| a / b,
+                    BinOpKind::Mod => |a, b| a % b,
+                    BinOpKind::Add => |a, b| a + b,
+                    BinOpKind::Sub => |a, b| a - b,
+                    kind => {
+                        let compare: fn(f64, f64) -> bool = match kind {
+                            BinOpKind::Greater => |a, b| a > b,
+                            BinOpKind::GreaterEq => |a, b| a >= b,
+                            BinOpKind::Less => |a, b| a < b,
+                            BinOpKind::LessEq => |a, b| a <= b,
+                            BinOpKind::Eq => |a, b| a == b,
+                            BinOpKind::NotEq => |a, b| a != b,
+                            _ => return Err(unsupported()),
+                        };
+                        return Ok(Compiled::Bool(Box::new(move |args| compare(lhs(args), rhs(args)))));
+                    },
+                };
+                Ok(Compiled::Real(Box::new(move |args| op(lhs(args), rhs(args)))))
+            },
+            _ => Err(unsupported()),
+        }
+    }
+
+    /// Compiles a call to a builtin function with an `f64` implementation, or to a user-defined
+    /// function, whose body is compiled into the caller.
+    fn call(&self, call: &Call) -> Result<Compiled, Unsupported> {
+        let unsupported = || Unsupported { span: call.span() };
+        if call.derivatives > 0
+            || !call.named_args.is_empty()
+            || self.params.contains(&call.name.name) {
+            return Err(unsupported());
+        }
+        let function = self.ctxt.get_function(&call.name.name).ok_or_else(unsupported)?;
+        let args = || call.args.iter()
+            .map(|arg| self.real(arg))
+            .collect::<Result<Vec<_>, _>>();
+
+        match function.func {
+            Func::Builtin(_) => {
+                let name = function.name.as_deref().unwrap_or(&call.name.name);
+                let degrees = self.ctxt.trig_mode == TrigMode::Degrees;
+                let mut args = args()?.into_iter();
+                let real: Real = match (args.next(), args.next(), args.next()) {
+                    (Some(x), None, None) => {
+                        let (f, angle) = unary_builtin(name).ok_or_else(unsupported)?;
+                        match (angle, degrees) {
+                            (Angle::Input, true) => Box::new(move |args| f(x(args).to_radians())),
+                            (Angle::Output, true) => Box::new(move |args| f(x(args)).to_degrees()),
+                            _ => Box::new(move |args| f(x(args))),
+                        }
+                    },
+                    (Some(x), Some(y), None) => {
+                        let (f, angle) = binary_builtin(name).ok_or_else(unsupported)?;
+                        match (angle, degrees) {
+                            (Angle::Output, true) => Box::new(move |args| f(x(args), y(args)).to_degrees()),
+                            _ => Box::new(move |args| f(x(args), y(args))),
+                        }
+                    },
+                    _ => return Err(unsupported()),
+                };
+                Ok(Compiled::Real(real))
+            },
+            Func::UserFunc(user_func) => {
+                let params = &user_func.header.params;
+                if user_func.recursive || params.len() != call.args.len() || self.depth >= MAX_INLINE_DEPTH {
+                    return Err(unsupported());
+                }
+
+                // the body only sees the parameters of the function, and the context
+                let args = args()?;
+                let names = params.iter()
+                    .map(|param| param.symbol().name.clone())
+                    .collect::<Vec<_>>();
+                let callee = Compiler { params: &names, depth: self.depth + 1, ..*self };
+                let values = move |outer: &[f64]| args.iter()
+                    .map(|arg| arg(outer))
+                    .collect::<Vec<_>>();
+                Ok(match callee.expr(&user_func.body)? {
+                    Compiled::Real(body) => Compiled::Real(Box::new(move |args| body(&values(args)))),
+                    Compiled::Bool(body) => Compiled::Bool(Box::new(move |args| body(&values(args)))),
+                })
+            },
+            Func::Lambda(_) => Err(unsupported()),
+        }
+    }
+}
+
+/// Compiles a value that is known when the expression is compiled.
+fn constant(value: Value) -> Option<Compiled> {
+    match value {
+        Value::Boolean(boolean) => Some(Compiled::Bool(Box::new(move |_| boolean))),
+        value if value.is_real() => {
+            let value = to_f64(value);
+            Some(Compiled::Real(Box::new(move |_| value)))
+        },
+        _ => None,
+    }
+}
+
+/// Converts a real value to an `f64`, or [`f64::NAN`] if it is not a real number.
+fn to_f64(value: Value) -> f64 {
+    match value.coerce_float() {
+        Value::Float(float) => float.to_f64(),
+        Value::Complex(complex) if complex.imag().is_zero() => complex.real().to_f64(),
+        _ => f64::NAN,
+    }
+}
+
+/// How a [`FastFn`] evaluates its expression.
+enum Kind {
+    /// The expression was compiled to a closure.
+    Native(Real),
+
+    /// The expression could not be compiled, so it is evaluated with [`Eval`].
+    Interpreted(Box<Interpreted>),
+}
+
+/// An expression that is evaluated with [`Eval`] in a copy of the context, with its parameters
+/// defined as variables.
+struct Interpreted {
+    /// The expression.
+    expr: Expr,
+
+    /// The names of the parameters.
+    params: Vec<String>,
+
+    /// The context to evaluate the expression in.
+    ctxt: Ctxt,
+}
+
+/// An expression compiled to a function of its parameters, which are `f64`s.
+///
+/// See the [module-level documentation](self) for more information.
+pub struct FastFn {
+    /// How the expression is evaluated.
+    kind: Kind,
+
+    /// The number of parameters of the function.
+    arity: usize,
+}
+
+impl FastFn {
+    /// Compiles an expression to a function of the given parameters. Other variables and functions
+    /// used by the expression are read from the context.
+    ///
+    /// If the expression cannot be compiled to a closure, the function evaluates it with [`Eval`]
+    /// instead.
+    pub fn compile(expr: &Expr, params: &[&str], ctxt: &Ctxt) -> Self {
+        Self::try_compile(expr, params, ctxt).unwrap_or_else(|_| Self {
+            kind: Kind::Interpreted(Box::new(Interpreted {
+                expr: expr.clone(),
+                params: params.iter().map(|param| param.to_string()).collect(),
+                ctxt: ctxt.clone(),
+            })),
+            arity: params.len(),
+        })
+    }
+
+    /// Compiles an expression to a closure of the given parameters, returning the part of the
+    /// expression that cannot be compiled if there is one.
+    pub fn try_compile(expr: &Expr, params: &[&str], ctxt: &Ctxt) -> Result<Self, Unsupported> {
+        // intervals are the point of the interval number mode, and cannot be approximated
+        if ctxt.number_mode != NumberMode::Standard {
+            return Err(Unsupported { span: expr.span() });
+        }
+
+        let params = params.iter().map(|param| param.to_string()).collect::<Vec<_>>();
+        let compiler = Compiler { ctxt, outer: &params, params: &params, depth: 0 };
+        Ok(Self { kind: Kind::Native(compiler.real(expr)?), arity: params.len() })
+    }
+
+    /// Returns true if the expression was compiled to a closure, rather than falling back to
+    /// [`Eval`].
+    pub fn is_native(&self) -> bool {
+        matches!(self.kind, Kind::Native(_))
+    }
+
+    /// Evaluates the function with the given values of its parameters. Returns [`f64::NAN`] if
+    /// the result is not a real number, or the expression cannot be evaluated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values is not the number of parameters.
+    pub fn eval(&self, args: &[f64]) -> f64 {
+        assert_eq!(args.len(), self.arity, "wrong number of arguments given to a `FastFn`");
+        match &self.kind {
+            Kind::Native(real) => real(args),
+            Kind::Interpreted(interpreted) => {
+                let Interpreted { expr, params, ctxt } = &**interpreted;
+                let mut ctxt = ctxt.clone();
+                for (param, &arg) in params.iter().zip(args) {
+                    ctxt.add_var(param, arg.into());
+                }
+                match expr.eval(&mut ctxt) {
+                    Ok(value) if value.is_real() => to_f64(value),
+                    _ => f64::NAN,
+                }
+            },
+        }
+    }
+}
+
+impl Debug for FastFn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FastFn")
+            .field("native", &self.is_native())
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::Parser;
+    use super::*;
+
+    /// Compiles the expression as a function of `x`, in a context where the given statements have
+    /// been evaluated.
+    fn compile(setup: &str, source: &str) -> (FastFn, Ctxt) {
+        let mut ctxt = Ctxt::default();
+        if !setup.is_empty() {
+            let stmts = Parser::new(setup).try_parse_full_many().unwrap();
+            crate::numerical::eval::eval_stmts(&stmts, &mut ctxt).unwrap();
+        }
+        let expr = Parser::new(source).try_parse_full::<Expr>().unwrap();
+        (FastFn::compile(&expr, &["x"], &ctxt), ctxt)
+    }
+
+    /// Checks that the expression compiles to a closure that agrees with [`Eval`].
+    fn assert_native(setup: &str, source: &str) {
+        let (f, ctxt) = compile(setup, source);
+        assert!(f.is_native(), "`{}` was not compiled", source);
+        let expr = Parser::new(source).try_parse_full::<Expr>().unwrap();
+        let interpreted = FastFn {
+            kind: Kind::Interpreted(Box::new(Interpreted {
+                expr,
+                params: vec!["x".to_string()],
+                ctxt,
+            })),
+            arity: 1,
+        };
+        for x in [-2.5, -1.0, 0.0, 0.5, 1.0, 3.0, 10.0] {
+            let (expected, value) = (interpreted.eval(&[x]), f.eval(&[x]));
+            assert!(
+                // at a singularity, one may be infinite where the other is not a number
+                !expected.is_finite() && !value.is_finite()
+                    || (expected - value).abs() <= 1e-9 * expected.abs().max(1.0),
+                "`{}` at x = {}: expected {}, got {}",
+                source,
+                x,
+                expected,
+                value,
+            );
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_native("", "3x^2 - 2x + 1");
+        assert_native("", "x / 4 + x % 3 - 2^x");
+        assert_native("", "x^0.5 + (x + 1)^-2");
+        assert_native("", "-pi * x + e");
+    }
+
+    #[test]
+    fn branches() {
+        assert_native("", "if x < 0 then -x else x");
+        assert_native("", "if x >= 1 && not (x == 3) then 1 else (if x < -2 || x == 0 then 2 else 3)");
+        assert_native("", "{ x + 1 }");
+    }
+
+    #[test]
+    fn builtins() {
+        assert_native("", "sin(x) + cos(2x) - atan2(x, 2)");
+        assert_native("", "sqrt(x) + ln(x) + log(x) + log(x, 2)");
+        assert_native("", "abs(x) + floor(x) + ceil(x) + sign(x) + min(x, 1) + max(x, 1)");
+        assert_native("", "exp(x) + hypot(x, 3) + sinh(x) + tanh(x)");
+
+        // the cube root of a negative number is real, unlike in the tree-walking evaluator, where
+        // rounding error gives it a tiny imaginary part
+        let (f, _) = compile("", "cbrt(x)");
+        assert!(f.is_native());
+        assert_eq!(f.eval(&[-8.0]), -2.0);
+    }
+
+    #[test]
+    fn degrees() {
+        let mut ctxt = Ctxt::default();
+        ctxt.trig_mode = TrigMode::Degrees;
+        let expr = Parser::new("sin(x) + asin(1)").try_parse_full::<Expr>().unwrap();
+        let f = FastFn::compile(&expr, &["x"], &ctxt);
+        assert!(f.is_native());
+        assert!((f.eval(&[30.0]) - 90.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn user_functions() {
+        assert_native("f(t) = t^2 + 1; a = 3", "a * f(x) + f(2)");
+        assert_native("g(t, u) = if t > u then t else u", "g(x, 1)");
+        assert_native("x = 5; h(t) = t + 1", "h(x)");
+    }
+
+    #[test]
+    fn fallback() {
+        // each of these falls back to the tree-walking evaluator, with the same results
+        for (setup, source, x, expected) in [
+            ("", "x!", 4.0, 24.0),
+            ("", "sum(x * n, n, 1, 3)", 2.0, 12.0),
+            ("fact(n) = if n < 2 then 1 else n * fact(n - 1)", "fact(x)", 5.0, 120.0),
+            ("h(t) = t + x", "h(1)", 2.0, 3.0),
+            ("", "[x, 2][1]", 0.0, 2.0),
+        ] {
+            let (f, _) = compile(setup, source);
+            assert!(!f.is_native(), "`{}` was compiled", source);
+            assert_eq!(f.eval(&[x]), expected, "`{}`", source);
+        }
+    }
+
+    #[test]
+    fn not_real() {
+        let (f, _) = compile("", "sqrt(x)");
+        assert!(f.eval(&[-1.0]).is_nan());
+        let (f, _) = compile("", "x + i");
+        assert!(!f.is_native());
+        assert!(f.eval(&[1.0]).is_nan());
+        let (f, _) = compile("", "x > 1");
+        assert!(f.eval(&[2.0]).is_nan());
+    }
+}
@@ -1,6 +1,6 @@
 //! Utility functions to format complex numbers.
 
-use rug::{Complex, Float};
+use rug::{float::Round, Complex, Float};
 use std::fmt::Formatter;
 use super::{float, FormatOptions, NumberFormat};
 
@@ -21,7 +21,7 @@ fn fmt_helper(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std::
         || options.number == NumberFormat::Auto && float::should_use_scientific(n)
     {
         write!(f, "(")?;
-        float::fmt_scientific(f, n, options)?;
+        float::fmt_scientific(f, n, options, Round::Nearest)?;
         write!(f, ")")?;
     } else {
         float::fmt(f, n, options)?;
@@ -0,0 +1,231 @@
+//! Utility functions to format symbolic expressions.
+//!
+//! Expressions are written in a form that can be parsed back into an equivalent expression:
+//! negative terms are subtracted, factors with negative exponents are moved into a denominator,
+//! and numeric coefficients are multiplied implicitly, as in `2x - 3 / (x + 1)`.
+
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    simplify::fraction::as_rational,
+};
+use rug::{Integer, Rational};
+use std::{cmp::Reverse, fmt::Formatter, sync::Arc};
+use super::{float, integer, FormatOptions, NumberFormat};
+
+/// Returns the formatted number or symbol, formatting numbers with the given options.
+fn primary(n: &Primary, options: FormatOptions) -> String {
+    /// Helper to call a formatting function with a [`Formatter`].
+    struct Number<'a>(&'a Primary, FormatOptions);
+
+    impl std::fmt::Display for Number<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            match self.0 {
+                Primary::Integer(n) => integer::fmt(f, n, self.1),
+                Primary::Float(n) => float::fmt(f, n, self.1),
+                primary => write!(f, "{}", primary),
+            }
+        }
+    }
+
+    // word form would read strangely next to symbols
+    let options = match options.number {
+        NumberFormat::Word => NumberFormat::Auto.inside(options),
+        _ => options,
+    };
+    Number(n, options).to_string()
+}
+
+/// If the expression is a term with a negative numeric coefficient, returns the negated term.
+fn negated(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::Primary(Primary::Integer(n)) if *n < 0 => Some(Expr::Primary(Primary::Integer(-n.clone()))),
+        Expr::Primary(Primary::Float(n)) if *n < 0 => Some(Expr::Primary(Primary::Float(-n.clone()))),
+        Expr::Mul(factors) => {
+            let index = factors.iter().position(|factor| matches!(
+                factor,
+                Expr::Primary(Primary::Integer(_) | Primary::Float(_)),
+            ))?;
+            let factor = negated(&factors[index])?;
+            let mut factors = factors.to_vec();
+            if factor.as_integer().is_some_and(|n| *n == 1) && factors.len() > 1 {
+                factors.remove(index);
+            } else {
+                factors[index] = factor;
+            }
+            Some(Expr::Mul(factors.into()).downgrade())
+        },
+        _ => None,
+    }
+}
+
+/// If the expression is a power with a negative numeric exponent, returns the reciprocal.
+fn reciprocal(expr: &Expr) -> Option<Expr> {
+    let Expr::Exp(base, exp) = expr else {
+        return None;
+    };
+    let exp = negated(exp)?;
+    if exp.as_integer().is_some_and(|n| *n == 1) {
+        Some(Expr::clone(base))
+    } else {
+        Some(Expr::Exp(base.clone(), Arc::new(exp)))
+    }
+}
+
+/// Formats a factor of a product or power, adding parentheses if needed.
+fn factor(expr: &Expr, options: FormatOptions) -> String {
+    match expr {
+        Expr::Add(_) | Expr::Mul(_) => format!("({})", format(expr, options)),
+        Expr::Exp(..) if reciprocal(expr).is_some() => format!("({})", format(expr, options)),
+        Expr::Primary(Primary::Integer(n)) if *n < 0 => format!("({})", format(expr, options)),
+        Expr::Primary(Primary::Float(n)) if *n < 0 => format!("({})", format(expr, options)),
+        _ => format(expr, options),
+    }
+}
+
+/// Returns true if the expression is a number.
+fn is_numeric(expr: &Expr) -> bool {
+    matches!(expr, Expr::Primary(Primary::Integer(_) | Primary::Float(_)))
+}
+
+/// Returns the total degree of the term in its symbols, counting only integer exponents.
+fn degree(expr: &Expr) -> i64 {
+    match expr {
+        Expr::Primary(Primary::Symbol(_)) => 1,
+        Expr::Mul(factors) => factors.iter().map(degree).sum(),
+        Expr::Exp(base, exp) => match exp.as_integer().and_then(|n| n.to_i64()) {
+            Some(n) => degree(base).saturating_mul(n),
+            None => 0,
+        },
+        _ => 0,
+    }
+}
+
+/// Formats the factors of a product, with no denominator. Numeric coefficients are written first.
+fn product(factors: &[Expr], options: FormatOptions) -> String {
+    let (mut factors, others): (Vec<_>, Vec<_>) = factors.iter().partition(|expr| is_numeric(expr));
+    factors.extend(others);
+
+    // a leading coefficient of `1` or `-1` is written as a sign
+    let mut out = String::new();
+    if factors.len() > 1 {
+        match factors[0].as_integer() {
+            Some(n) if *n == 1 => { factors.remove(0); },
+            Some(n) if *n == -1 => {
+                out.push('-');
+                factors.remove(0);
+            },
+            _ => (),
+        }
+    }
+
+    let mut prev_numeric = false;
+    for (i, expr) in factors.iter().enumerate() {
+        let numeric = is_numeric(expr);
+        let formatted = factor(expr, options);
+        if i == 0 {
+            out.push_str(&formatted);
+        } else if prev_numeric && !numeric && formatted.starts_with(|c: char| c.is_alphabetic() || c == '(') {
+            // implicit multiplication with a coefficient, e.g. `2x` or `3(x + 1)`
+            out.push_str(&formatted);
+        } else {
+            out.push_str(" * ");
+            out.push_str(&formatted);
+        }
+        prev_numeric = numeric;
+    }
+    out
+}
+
+/// Formats a product, moving factors with negative exponents into the denominator.
+fn fraction(factors: &[Expr], options: FormatOptions) -> String {
+    let mut num = Vec::new();
+    let mut den = Vec::new();
+    for expr in factors {
+        match reciprocal(expr) {
+            Some(expr) => den.push(expr),
+            None => num.push(expr.clone()),
+        }
+    }
+
+    // keep a negative coefficient in front of the whole fraction
+    let mut num = match num.as_slice() {
+        [] => String::from("1"),
+        [Expr::Primary(Primary::Integer(n))] if *n == -1 && !den.is_empty() => String::from("-1"),
+        _ => product(&num, options),
+    };
+    if den.is_empty() {
+        return num;
+    }
+
+    if num.contains(" * ") {
+        num = format!("({})", num);
+    }
+    match den.as_slice() {
+        [expr] if !matches!(expr, Expr::Mul(_)) => format!("{} / {}", num, factor(expr, options)),
+        _ => format!("{} / ({})", num, product(&den, options)),
+    }
+}
+
+/// Formats the exponent of a power.
+fn exponent(exp: &Expr, options: FormatOptions) -> String {
+    match exp {
+        Expr::Primary(Primary::Integer(n)) if *n >= 0 => n.to_string(),
+        Expr::Primary(Primary::Symbol(_)) => format(exp, options),
+        _ => format!("({})", format(exp, options)),
+    }
+}
+
+/// Formats the expression.
+fn format(expr: &Expr, options: FormatOptions) -> String {
+    match expr {
+        Expr::Primary(Primary::Call(name, args)) => format!(
+            "{}({})",
+            name,
+            args.iter().map(|arg| format(arg, options)).collect::<Vec<_>>().join(", "),
+        ),
+        Expr::Primary(n) => primary(n, options),
+        Expr::Add(terms) => {
+            // terms are written in order of decreasing degree, with constants last, as in
+            // `x^2 + 2x + 1`
+            let mut terms = terms.iter().collect::<Vec<_>>();
+            terms.sort_by_key(|term| (is_numeric(term), Reverse(degree(term))));
+
+            let mut out = String::new();
+            for (i, term) in terms.iter().enumerate() {
+                match (i, negated(term)) {
+                    (0, _) => out.push_str(&format(term, options)),
+                    (_, Some(term)) => {
+                        out.push_str(" - ");
+                        out.push_str(&format(&term, options));
+                    },
+                    (_, None) => {
+                        out.push_str(" + ");
+                        out.push_str(&format(term, options));
+                    },
+                }
+            }
+            out
+        },
+        Expr::Mul(factors) => fraction(factors, options),
+        Expr::Exp(..) if reciprocal(expr).is_some() => fraction(std::slice::from_ref(expr), options),
+        Expr::Exp(base, exp) => match as_rational(exp) {
+            Some(n) if n == Rational::from((1, 2)) => format!("sqrt({})", format(base, options)),
+            Some(n) if *n.denom() != 1 => {
+                let rational = Expr::Mul(vec![
+                    Expr::Primary(Primary::Integer(n.numer().clone())),
+                    Expr::Exp(
+                        Arc::new(Expr::Primary(Primary::Integer(n.denom().clone()))),
+                        Arc::new(Expr::Primary(Primary::Integer(Integer::from(-1)))),
+                    ),
+                ].into());
+                format!("{}^({})", factor(base, options), format(&rational, options))
+            },
+            _ => format!("{}^{}", factor(base, options), exponent(exp, options)),
+        },
+    }
+}
+
+/// Formats a symbolic expression.
+pub fn fmt(f: &mut Formatter<'_>, expr: &Expr, options: FormatOptions) -> std::fmt::Result {
+    write!(f, "{}", format(expr, options))
+}
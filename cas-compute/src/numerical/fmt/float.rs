@@ -1,7 +1,7 @@
 //! Utility functions to format floating-point numbers.
 
 use crate::approx::approximate_rational;
-use rug::{float::Round, Float};
+use rug::{float::Round, Float, Integer};
 use std::{cmp::Ordering, fmt::Formatter};
 use super::{integer, FormatOptions, NumberFormat, Scientific, Separator};
 
@@ -49,13 +49,19 @@ fn fmt_non_normal_decimal<F: std::fmt::Write>(f: &mut F, n: &Float) -> std::fmt:
     unreachable!()
 }
 
-/// Formats a float as a standard number.
-fn fmt_decimal<F: std::fmt::Write>(f: &mut F, n: &Float, options: FormatOptions) -> std::fmt::Result {
+/// Formats a float as a standard number, rounding the last displayed digit in the given
+/// direction.
+fn fmt_decimal<F: std::fmt::Write>(
+    f: &mut F,
+    n: &Float,
+    options: FormatOptions,
+    round: Round,
+) -> std::fmt::Result {
     if !n.is_normal() {
         return fmt_non_normal_decimal(f, n);
     }
 
-    let (sign, mut s, exponent) = n.to_sign_string_exp_round(10, options.precision, Round::Nearest);
+    let (sign, mut s, exponent) = n.to_sign_string_exp_round(10, options.precision, round);
     let exponent = exponent.unwrap(); // exponent is Some() if the number is normal
 
     // add decimal point
@@ -84,13 +90,19 @@ fn fmt_decimal<F: std::fmt::Write>(f: &mut F, n: &Float, options: FormatOptions)
     write!(f, "{}{}", if sign { "-" } else { "" }, trim_trailing(&s))
 }
 
-/// Formats a float in scientific notation.
-pub fn fmt_scientific(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std::fmt::Result {
+/// Formats a float in scientific notation, rounding the last displayed digit in the given
+/// direction.
+pub fn fmt_scientific(
+    f: &mut Formatter<'_>,
+    n: &Float,
+    options: FormatOptions,
+    round: Round,
+) -> std::fmt::Result {
     if !n.is_normal() {
         return fmt_non_normal_decimal(f, n);
     }
 
-    let (sign, mut s, exponent) = n.to_sign_string_exp_round(10, options.precision, Round::Nearest);
+    let (sign, mut s, exponent) = n.to_sign_string_exp_round(10, options.precision, round);
     let mut exponent = exponent.unwrap(); // exponent is Some() if the number is normal
 
     // add decimal point
@@ -128,9 +140,23 @@ fn fmt_fraction(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std
     }
 
     let (numerator, denominator) = approximate_rational(n).into_numer_denom();
+    fmt_numer_denom(f, &numerator, &denominator, options)
+}
+
+/// Formats a fraction with the given numerator and denominator, using [`NumberFormat::Auto`] for
+/// both.
+pub fn fmt_numer_denom(
+    f: &mut Formatter<'_>,
+    numerator: &Integer,
+    denominator: &Integer,
+    options: FormatOptions,
+) -> std::fmt::Result {
+    let options = options.into_builder()
+        .number(NumberFormat::Auto)
+        .build();
 
     // write numerator
-    integer::fmt(f, &numerator, options)?;
+    integer::fmt(f, numerator, options)?;
 
     // write fraction bar
     write!(f, " / ")?;
@@ -138,7 +164,7 @@ fn fmt_fraction(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std
     // write denominator using `NumberFormat::Auto`
     // the functionality of `integer::fmt` is copied here because we need to manually add
     // parentheses in edge case where the denominator is rendered in scientific notation
-    let expected_format = if integer::should_use_scientific(&denominator) {
+    let expected_format = if integer::should_use_scientific(denominator) {
         NumberFormat::Scientific
     } else {
         NumberFormat::Decimal
@@ -147,10 +173,10 @@ fn fmt_fraction(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std
     if expected_format == NumberFormat::Scientific {
         // put the denominator in parentheses to avoid ambiguity
         write!(f, "(")?;
-        integer::fmt_scientific(f, &denominator, options)?;
+        integer::fmt_scientific(f, denominator, options)?;
         write!(f, ")")?;
     } else {
-        integer::fmt_decimal(f, &denominator, options)?;
+        integer::fmt_decimal(f, denominator, options)?;
     }
 
     Ok(())
@@ -198,7 +224,7 @@ fn fmt_word(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std::fm
     }
 
     let mut s = String::new();
-    fmt_decimal(&mut s, n, Separator::Never.inside(options))?;
+    fmt_decimal(&mut s, n, Separator::Never.inside(options), Round::Nearest)?;
 
     let mut parts = s.split('.');
     if let Some(integer) = parts.next() {
@@ -230,14 +256,38 @@ pub fn fmt(f: &mut Formatter<'_>, n: &Float, options: FormatOptions) -> std::fmt
     match options.number {
         NumberFormat::Auto => {
             if should_use_scientific(n) {
-                fmt_scientific(f, n, options)
+                fmt_scientific(f, n, options, Round::Nearest)
             } else {
-                fmt_decimal(f, n, options)
+                fmt_decimal(f, n, options, Round::Nearest)
             }
         }
-        NumberFormat::Decimal => fmt_decimal(f, n, options),
-        NumberFormat::Scientific => fmt_scientific(f, n, options),
+        NumberFormat::Decimal => fmt_decimal(f, n, options, Round::Nearest),
+        NumberFormat::Scientific => fmt_scientific(f, n, options, Round::Nearest),
         NumberFormat::Fraction => fmt_fraction(f, n, options),
         NumberFormat::Word => fmt_word(f, n, options),
     }
 }
+
+/// Format a floating-point number using the given formatting options, rounding the displayed
+/// digits in the given direction instead of to the nearest digit.
+///
+/// [`NumberFormat::Fraction`] and [`NumberFormat::Word`] are treated as [`NumberFormat::Auto`],
+/// since neither can be rounded in a specific direction.
+pub fn fmt_directed(
+    f: &mut Formatter<'_>,
+    n: &Float,
+    options: FormatOptions,
+    round: Round,
+) -> std::fmt::Result {
+    match options.number {
+        NumberFormat::Decimal => fmt_decimal(f, n, options, round),
+        NumberFormat::Scientific => fmt_scientific(f, n, options, round),
+        NumberFormat::Auto | NumberFormat::Fraction | NumberFormat::Word => {
+            if should_use_scientific(n) {
+                fmt_scientific(f, n, options, round)
+            } else {
+                fmt_decimal(f, n, options, round)
+            }
+        },
+    }
+}
@@ -0,0 +1,23 @@
+//! Utility functions to format intervals.
+
+use crate::interval::Interval;
+use rug::float::Round;
+use std::fmt::Formatter;
+use super::{float, FormatOptions};
+
+/// Format an interval using the given formatting options.
+///
+/// The interval is written as `[lo, hi]`. The lower bound is rounded down and the upper bound is
+/// rounded up, so that the displayed interval still contains the exact result, no matter how many
+/// digits are displayed. The empty interval is written as `∅`.
+pub fn fmt(f: &mut Formatter<'_>, n: &Interval, options: FormatOptions) -> std::fmt::Result {
+    if n.is_empty() {
+        return write!(f, "∅");
+    }
+
+    write!(f, "[")?;
+    float::fmt_directed(f, n.lo(), options, Round::Down)?;
+    write!(f, ", ")?;
+    float::fmt_directed(f, n.hi(), options, Round::Up)?;
+    write!(f, "]")
+}
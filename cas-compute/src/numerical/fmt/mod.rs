@@ -1,6 +1,9 @@
 mod complex;
+mod expr;
 mod float;
 mod integer;
+mod interval;
+mod rational;
 
 use crate::primitive::float;
 use std::fmt::{Display, Formatter};
@@ -206,7 +209,9 @@ impl Display for ValueFormatter<'_> {
         match self.value {
             Value::Float(n) => float::fmt(f, n, self.options),
             Value::Integer(n) => integer::fmt(f, n, self.options),
+            Value::Rational(n) => rational::fmt(f, n, self.options),
             Value::Complex(c) => complex::fmt(f, c, self.options),
+            Value::Interval(n) => interval::fmt(f, n, self.options),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Unit => write!(f, "()"),
             Value::List(l) => {
@@ -222,6 +227,29 @@ impl Display for ValueFormatter<'_> {
                 }
                 write!(f, "]")
             },
+            Value::Matrix(matrix) => {
+                write!(f, "matrix([")?;
+                for (i, row) in matrix.entries().chunks(matrix.cols()).enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "[")?;
+                    for (j, item) in row.iter().enumerate() {
+                        if j != 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", ValueFormatter {
+                            value: item,
+                            options: self.options,
+                        })?;
+                    }
+                    write!(f, "]")?;
+                }
+                write!(f, "])")
+            },
+            Value::Expr(expr) => expr::fmt(f, expr, self.options),
+            Value::Range(range) => write!(f, "{}", range),
+            Value::Function(function) => write!(f, "{}", function),
         }
     }
 }
@@ -338,7 +366,7 @@ mod tests {
 
         assert_eq!(
             formatted,
-            "2.59322324860261966289150489187302066591294650436810102067533652743955265303530160852333720511812211606173647225958129384019175298482864208811149268313933175 × 10 ^ -216 - (1.16574875077673880591679077396436952491792279821826898772971150811137017094408687014393063851764516093648992883092191087289215932890555684983700537703343422 × 10 ^ -507)i"
+            "2.59322324860261966289150489187302066591294650436810102067533652743955265303530160852333720511812211606173647225958129384019175298482864208811149268313933197 × 10 ^ -216 - (1.16574875077673880591679077396436952491792279821826898772971150811137017094408687014393063851764516093648992883092191087289215932890555684983700537703343422 × 10 ^ -507)i"
         );
     }
 
@@ -404,4 +432,53 @@ mod tests {
 
         assert_eq!(formatted, "1,400.001");
     }
+
+    #[test]
+    fn exact_rational() {
+        let rational = eval("1/3 + 1/6 - 1");
+        assert_eq!(format!("{}", rational.fmt(FormatOptions::default())), "-1 / 2");
+
+        let opts = FormatOptionsBuilder::new()
+            .number(NumberFormat::Decimal)
+            .build();
+        assert_eq!(format!("{}", rational.fmt(opts)), "-0.5");
+    }
+
+    #[test]
+    fn rational_one_third() {
+        let rational = eval("1/3");
+        assert_eq!(format!("{}", rational.fmt(FormatOptions::default())), "1 / 3");
+
+        let opts = FormatOptionsBuilder::new()
+            .number(NumberFormat::Decimal)
+            .precision(Some(10))
+            .build();
+        assert_eq!(format!("{}", rational.fmt(opts)), "0.3333333333");
+    }
+
+    #[test]
+    fn rational_negative() {
+        let rational = eval("-22/6");
+        let opts = FormatOptionsBuilder::new()
+            .number(NumberFormat::Fraction)
+            .build();
+        assert_eq!(format!("{}", rational.fmt(opts)), "-11 / 3");
+    }
+
+    #[test]
+    fn rational_scientific_denominator() {
+        let rational = eval("2/3^40");
+        let formatted = format!("{}", rational.fmt(FormatOptions::default()));
+
+        // the denominator is put in parentheses, so that the scientific notation is unambiguous
+        assert_eq!(formatted, "2 / (1.2157665459056928801 × 10 ^ 19)");
+    }
+
+    #[test]
+    fn symbolic_expr() {
+        let expr = eval("expand((x - 1)^2 / 2)");
+        let formatted = format!("{}", expr.fmt(FormatOptions::default()));
+
+        assert_eq!(formatted, "x^2 / 2 - x + 1 / 2");
+    }
 }
@@ -0,0 +1,22 @@
+//! Utility functions to format rational numbers.
+
+use crate::primitive::float;
+use rug::Rational;
+use std::fmt::Formatter;
+use super::{float as fmt_float, FormatOptions, NumberFormat};
+
+/// Format a rational number using the given formatting options.
+///
+/// With [`NumberFormat::Auto`] and [`NumberFormat::Fraction`], the exact numerator and
+/// denominator are written. All other formats write the rational number as it would be written as
+/// a float.
+pub fn fmt(f: &mut Formatter<'_>, n: &Rational, options: FormatOptions) -> std::fmt::Result {
+    match options.number {
+        NumberFormat::Auto | NumberFormat::Fraction => {
+            fmt_float::fmt_numer_denom(f, n.numer(), n.denom(), options)
+        },
+        NumberFormat::Decimal | NumberFormat::Scientific | NumberFormat::Word => {
+            fmt_float::fmt(f, &float(n), options)
+        },
+    }
+}
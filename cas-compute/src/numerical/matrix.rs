@@ -0,0 +1,617 @@
+//! Matrices of numbers, and the linear algebra algorithms used by the matrix builtins.
+//!
+//! A [`Matrix`] stores its entries as [`Value`]s, which are always integers, rational numbers,
+//! floats, or complex numbers. The entries are combined with the same rules as the binary
+//! operators, so a matrix of exact numbers stays exact: the determinant of an integer matrix is an
+//! integer, and its inverse has rational entries. Entries that are floats or complex numbers are
+//! computed with the current precision of the thread (see [`precision`]).
+//!
+//! The elimination algorithms use partial pivoting, choosing the entry with the largest magnitude
+//! as the pivot of each column. When the entries are not exact, an entry that is negligible
+//! compared to the largest entry of the matrix is treated as zero, so that rounding errors do not
+//! turn a singular matrix into an invertible one.
+
+use cas_parser::parser::token::op::BinOpKind;
+use crate::approx::approximate_rational_with;
+use crate::numerical::{eval::eval_operands, value::Value};
+use crate::primitive::{complex, float, int, precision};
+use rug::{float::Constant, Complex, Float, Integer};
+use std::{cmp::Ordering, ops::{Index, IndexMut}};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of iterations used to find the eigenvalues of a matrix.
+const MAX_EIGENVALUE_ITERATIONS: usize = 1000;
+
+/// Applies a binary operator to two numbers.
+fn apply(op: BinOpKind, left: &Value, right: &Value) -> Value {
+    eval_operands(op, false, left.clone(), right.clone())
+        .expect("arithmetic on numbers cannot fail")
+}
+
+/// Returns the number `0`.
+fn zero() -> Value {
+    Value::Integer(int(0))
+}
+
+/// Returns the number `1`.
+fn one() -> Value {
+    Value::Integer(int(1))
+}
+
+/// Returns the magnitude of a number.
+fn magnitude(value: &Value) -> Float {
+    match value {
+        Value::Integer(n) => float(n).abs(),
+        Value::Rational(n) => float(n).abs(),
+        Value::Float(n) => float(n).abs(),
+        Value::Complex(c) => float(c.abs_ref()),
+        _ => float(0),
+    }
+}
+
+/// Converts a number to a complex number.
+fn to_complex(value: &Value) -> Complex {
+    match value {
+        Value::Integer(n) => complex(n),
+        Value::Rational(n) => complex(n),
+        Value::Float(n) => complex(n),
+        Value::Complex(c) => c.clone(),
+        _ => complex(0),
+    }
+}
+
+/// Returns true if the number is zero, or if it is not exact and its magnitude is at most
+/// `tolerance`.
+fn is_zero(value: &Value, tolerance: &Float) -> bool {
+    match value {
+        Value::Integer(n) => n.is_zero(),
+        Value::Rational(n) => *n == 0,
+        value => magnitude(value) <= *tolerance,
+    }
+}
+
+/// Evaluates a polynomial with the given coefficients, from the leading coefficient to the
+/// constant term, at `x`.
+fn eval_polynomial(coeffs: &[Value], x: &Value) -> Value {
+    coeffs.iter().fold(zero(), |result, coeff| apply(BinOpKind::Add, &apply(BinOpKind::Mul, &result, x), coeff))
+}
+
+/// Finds the complex roots of a monic polynomial with the given coefficients, from the leading
+/// coefficient to the constant term, using the
+/// [Aberth method](https://en.wikipedia.org/wiki/Aberth_method).
+///
+/// The roots are refined until they stop changing at a quarter of the current precision. Multiple
+/// roots converge more slowly than simple roots, and can only be found to a fraction of the
+/// current precision.
+fn polynomial_roots(coeffs: &[Complex]) -> Vec<Complex> {
+    let degree = coeffs.len() - 1;
+
+    // every root is within Cauchy's bound of the origin; start from points spread around a circle
+    // of that radius, offset so that the points are not symmetric about the real axis
+    let bound = coeffs[1..].iter()
+        .map(|coeff| float(coeff.abs_ref()))
+        .fold(float(0), |a, b| a.max(&b)) + 1;
+    let tau = float(Constant::Pi) * 2;
+    let mut roots = (0..degree)
+        .map(|k| {
+            let angle: Float = float(&tau) * k as u32 / degree as u32 + 0.4;
+            complex((float(angle.cos_ref()) * &bound, float(angle.sin_ref()) * &bound))
+        })
+        .collect::<Vec<_>>();
+
+    let eval = |z: &Complex| {
+        let (mut p, mut dp) = (complex(0), complex(0));
+        for coeff in coeffs {
+            dp = dp * z + &p;
+            p = p * z + coeff;
+        }
+        (p, dp)
+    };
+
+    let tolerance = float(1) >> (precision() - precision() / 4);
+    for _ in 0..MAX_EIGENVALUE_ITERATIONS {
+        let mut converged = true;
+        for k in 0..degree {
+            let (p, dp) = eval(&roots[k]);
+            if p.is_zero() {
+                continue;
+            }
+
+            let ratio = p / dp;
+            let repulsion = (0..degree)
+                .filter(|&j| j != k)
+                .fold(complex(0), |sum, j| sum + complex(1) / complex(&roots[k] - &roots[j]));
+            let correction = complex(&ratio) / (complex(1) - ratio * repulsion);
+            if !correction.real().is_finite() || !correction.imag().is_finite() {
+                continue;
+            }
+
+            let scale = float(roots[k].abs_ref()).max(&float(1));
+            if float(correction.abs_ref()) > tolerance.clone() * scale {
+                converged = false;
+            }
+            roots[k] -= correction;
+        }
+
+        if converged {
+            break;
+        }
+    }
+
+    roots
+}
+
+/// The result of row reducing a matrix with [`Matrix::row_reduce`].
+struct Reduction {
+    /// The columns containing a pivot, in the order of the rows containing them.
+    pivots: Vec<usize>,
+
+    /// The original indices of the rows, in the order they were moved to by row swaps.
+    order: Vec<usize>,
+
+    /// Whether an odd number of row swaps was made.
+    odd: bool,
+}
+
+/// A matrix of numbers, with at least one row and one column.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Matrix {
+    /// The number of rows.
+    rows: usize,
+
+    /// The number of columns.
+    cols: usize,
+
+    /// The entries of the matrix, in row-major order.
+    entries: Vec<Value>,
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = Value;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Value {
+        &self.entries[row * self.cols + col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Value {
+        &mut self.entries[row * self.cols + col]
+    }
+}
+
+impl Matrix {
+    /// Creates a matrix from its rows.
+    ///
+    /// Returns [`None`] if there are no rows or columns, the rows have different lengths, or any
+    /// entry is not a number.
+    pub fn from_rows(rows: Vec<Vec<Value>>) -> Option<Self> {
+        let cols = rows.first()?.len();
+        if cols == 0 || rows.iter().any(|row| row.len() != cols) {
+            return None;
+        }
+
+        let entries = rows.into_iter().flatten().collect::<Vec<_>>();
+        if !entries.iter().all(Value::is_complex) {
+            return None;
+        }
+
+        Some(Self { rows: entries.len() / cols, cols, entries })
+    }
+
+    /// Creates a matrix with a single column from the entries of a vector.
+    ///
+    /// Returns [`None`] if the vector is empty, or any entry is not a number.
+    pub fn column(entries: Vec<Value>) -> Option<Self> {
+        Self::from_rows(entries.into_iter().map(|entry| vec![entry]).collect())
+    }
+
+    /// Creates a matrix with the given dimensions, computing each entry from its row and column.
+    fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> Value) -> Self {
+        let entries = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| f(row, col))
+            .collect();
+        Self { rows, cols, entries }
+    }
+
+    /// Creates the `n` by `n` identity matrix.
+    pub fn identity(n: usize) -> Self {
+        Self::from_fn(n, n, |row, col| if row == col { one() } else { zero() })
+    }
+
+    /// Returns the number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns true if the matrix has as many rows as columns.
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Returns the entries of the matrix, in row-major order.
+    pub fn entries(&self) -> &[Value] {
+        &self.entries
+    }
+
+    /// Consumes the matrix and returns its entries, in row-major order.
+    pub fn into_entries(self) -> Vec<Value> {
+        self.entries
+    }
+
+    /// Consumes the matrix and returns its rows.
+    pub fn into_rows(self) -> Vec<Vec<Value>> {
+        let mut entries = self.entries.into_iter();
+        (0..self.rows)
+            .map(|_| entries.by_ref().take(self.cols).collect())
+            .collect()
+    }
+
+    /// Applies a function to each entry of the matrix.
+    ///
+    /// The function must return a number.
+    pub(crate) fn try_map<E>(&self, f: impl FnMut(&Value) -> Result<Value, E>) -> Result<Self, E> {
+        Ok(Self {
+            rows: self.rows,
+            cols: self.cols,
+            entries: self.entries.iter().map(f).collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Applies a binary operator to the corresponding entries of two matrices, returning [`None`]
+    /// if they have different dimensions.
+    fn zip_with(&self, other: &Self, op: BinOpKind) -> Option<Self> {
+        if self.rows != other.rows || self.cols != other.cols {
+            return None;
+        }
+        Some(Self::from_fn(self.rows, self.cols, |row, col| apply(op, &self[(row, col)], &other[(row, col)])))
+    }
+
+    /// Returns the negation of the matrix.
+    pub fn neg(&self) -> Self {
+        Self::from_fn(self.rows, self.cols, |row, col| apply(BinOpKind::Sub, &zero(), &self[(row, col)]))
+    }
+
+    /// Adds two matrices, returning [`None`] if they have different dimensions.
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        self.zip_with(other, BinOpKind::Add)
+    }
+
+    /// Subtracts two matrices, returning [`None`] if they have different dimensions.
+    pub fn sub(&self, other: &Self) -> Option<Self> {
+        self.zip_with(other, BinOpKind::Sub)
+    }
+
+    /// Multiplies two matrices, returning [`None`] if the number of columns of `self` is not the
+    /// number of rows of `other`.
+    pub fn mul(&self, other: &Self) -> Option<Self> {
+        if self.cols != other.rows {
+            return None;
+        }
+        Some(Self::from_fn(self.rows, other.cols, |row, col| {
+            (0..self.cols).fold(zero(), |sum, k| {
+                apply(BinOpKind::Add, &sum, &apply(BinOpKind::Mul, &self[(row, k)], &other[(k, col)]))
+            })
+        }))
+    }
+
+    /// Raises a square matrix to an integer power. A negative power is a power of the inverse of
+    /// the matrix.
+    ///
+    /// Returns [`None`] if the matrix is not square, or the power is negative and the matrix is
+    /// singular.
+    pub fn pow(&self, exp: &Integer) -> Option<Self> {
+        if !self.is_square() {
+            return None;
+        }
+
+        let (mut base, mut exp) = if *exp < 0 {
+            (self.inverse()?, int(-exp))
+        } else {
+            (self.clone(), exp.clone())
+        };
+        let mut result = Self::identity(self.rows);
+        while exp > 0 {
+            if exp.is_odd() {
+                result = result.mul(&base).unwrap();
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base).unwrap();
+            }
+        }
+        Some(result)
+    }
+
+    /// Returns true if the two matrices have the same dimensions and equal entries.
+    pub fn equals(&self, other: &Self) -> bool {
+        self.rows == other.rows
+            && self.cols == other.cols
+            && self.entries.iter()
+                .zip(&other.entries)
+                .all(|(a, b)| apply(BinOpKind::Eq, a, b) == Value::Boolean(true))
+    }
+
+    /// Returns the transpose of the matrix.
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.cols, self.rows, |row, col| self[(col, row)].clone())
+    }
+
+    /// Returns the sum of the entries on the main diagonal of a square matrix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn trace(&self) -> Value {
+        assert!(self.is_square(), "the trace is only defined for square matrices");
+        (0..self.rows).fold(zero(), |sum, i| apply(BinOpKind::Add, &sum, &self[(i, i)]))
+    }
+
+    /// Returns the magnitude below which an entry is treated as zero during elimination.
+    fn tolerance(&self) -> Float {
+        let largest = self.entries.iter()
+            .map(magnitude)
+            .fold(float(0), |a, b| a.max(&b));
+        largest >> (precision() - precision() / 8)
+    }
+
+    /// Swaps two rows of the matrix.
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for col in 0..self.cols {
+            self.entries.swap(a * self.cols + col, b * self.cols + col);
+        }
+    }
+
+    /// Returns a matrix with the given columns of this matrix.
+    fn columns(&self, cols: std::ops::Range<usize>) -> Self {
+        Self::from_fn(self.rows, cols.len(), |row, col| self[(row, cols.start + col)].clone())
+    }
+
+    /// Returns the matrix formed by placing the columns of `other` to the right of this matrix,
+    /// which must have the same number of rows.
+    fn augment(&self, other: &Self) -> Self {
+        Self::from_fn(self.rows, self.cols + other.cols, |row, col| {
+            if col < self.cols {
+                self[(row, col)].clone()
+            } else {
+                other[(row, col - self.cols)].clone()
+            }
+        })
+    }
+
+    /// Transforms the matrix into row echelon form with Gaussian elimination, using only the
+    /// first `pivot_cols` columns as pivot columns.
+    ///
+    /// If `reduced` is true, the result is in reduced row echelon form instead: every pivot is `1`,
+    /// and is the only non-zero entry in its column. If `lower` is given, the multiple of each
+    /// pivot row that is subtracted from a row below it is stored in `lower`, which is used to
+    /// compute the LU decomposition.
+    fn row_reduce(&mut self, pivot_cols: usize, reduced: bool, mut lower: Option<&mut Self>) -> Reduction {
+        let tolerance = self.tolerance();
+        let mut reduction = Reduction {
+            pivots: Vec::new(),
+            order: (0..self.rows).collect(),
+            odd: false,
+        };
+
+        for col in 0..pivot_cols {
+            let row = reduction.pivots.len();
+            if row == self.rows {
+                break;
+            }
+
+            let pivot_row = (row..self.rows)
+                .filter(|&r| !is_zero(&self[(r, col)], &tolerance))
+                .max_by(|&a, &b| magnitude(&self[(a, col)])
+                    .partial_cmp(&magnitude(&self[(b, col)]))
+                    .unwrap_or(Ordering::Equal));
+            let Some(pivot_row) = pivot_row else {
+                // the column is already zero below the pivots found so far
+                for r in row..self.rows {
+                    self[(r, col)] = zero();
+                }
+                continue;
+            };
+
+            if pivot_row != row {
+                self.swap_rows(row, pivot_row);
+                reduction.order.swap(row, pivot_row);
+                reduction.odd = !reduction.odd;
+                if let Some(lower) = lower.as_deref_mut() {
+                    for c in 0..row {
+                        lower.entries.swap(row * lower.cols + c, pivot_row * lower.cols + c);
+                    }
+                }
+            }
+
+            if reduced {
+                let pivot = self[(row, col)].clone();
+                for c in col + 1..self.cols {
+                    self[(row, c)] = apply(BinOpKind::Div, &self[(row, c)], &pivot);
+                }
+                self[(row, col)] = one();
+            }
+
+            let targets = if reduced { 0..self.rows } else { row + 1..self.rows };
+            for r in targets {
+                if r == row || is_zero(&self[(r, col)], &float(0)) {
+                    continue;
+                }
+
+                let factor = apply(BinOpKind::Div, &self[(r, col)], &self[(row, col)]);
+                for c in col + 1..self.cols {
+                    let product = apply(BinOpKind::Mul, &factor, &self[(row, c)]);
+                    self[(r, c)] = apply(BinOpKind::Sub, &self[(r, c)], &product);
+                }
+                self[(r, col)] = zero();
+                if let Some(lower) = lower.as_deref_mut() {
+                    lower[(r, row)] = factor;
+                }
+            }
+
+            reduction.pivots.push(col);
+        }
+
+        reduction
+    }
+
+    /// Returns the determinant of a square matrix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn det(&self) -> Value {
+        assert!(self.is_square(), "the determinant is only defined for square matrices");
+        let mut echelon = self.clone();
+        let reduction = echelon.row_reduce(self.cols, false, None);
+        if reduction.pivots.len() < self.rows {
+            return zero();
+        }
+
+        let det = (0..self.rows).fold(one(), |det, i| apply(BinOpKind::Mul, &det, &echelon[(i, i)]));
+        if reduction.odd {
+            apply(BinOpKind::Sub, &zero(), &det)
+        } else {
+            det
+        }
+    }
+
+    /// Returns the rank of the matrix, which is the number of linearly independent rows.
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce(self.cols, false, None).pivots.len()
+    }
+
+    /// Returns the reduced row echelon form of the matrix.
+    pub fn rref(&self) -> Self {
+        let mut rref = self.clone();
+        rref.row_reduce(self.cols, true, None);
+        rref
+    }
+
+    /// Returns the inverse of a square matrix, or [`None`] if the matrix is singular.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn inverse(&self) -> Option<Self> {
+        self.solve(&Self::identity(self.rows))
+    }
+
+    /// Solves the linear system `self * x = b` for `x`, where `self` is a square matrix and `b`
+    /// has the same number of rows. Returns [`None`] if the matrix is singular, in which case the
+    /// system has either no solution or infinitely many.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not square, or `b` has a different number of rows.
+    pub fn solve(&self, b: &Self) -> Option<Self> {
+        assert!(self.is_square(), "only systems with a square matrix can be solved");
+        assert_eq!(self.rows, b.rows, "the right-hand side must have one row per equation");
+        let mut augmented = self.augment(b);
+        let reduction = augmented.row_reduce(self.cols, true, None);
+        if reduction.pivots.len() < self.rows {
+            return None;
+        }
+        Some(augmented.columns(self.cols..self.cols + b.cols))
+    }
+
+    /// Returns the LU decomposition of a square matrix with partial pivoting, as the matrices
+    /// `(L, U, P)` such that `P * self = L * U`.
+    ///
+    /// `L` is lower triangular with ones on its diagonal, `U` is upper triangular, and `P` is a
+    /// permutation matrix. The decomposition exists even if the matrix is singular.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn lu(&self) -> (Self, Self, Self) {
+        assert!(self.is_square(), "the LU decomposition is only computed for square matrices");
+        let n = self.rows;
+        let mut upper = self.clone();
+        let mut lower = Self::from_fn(n, n, |_, _| zero());
+        let reduction = upper.row_reduce(n, false, Some(&mut lower));
+        for i in 0..n {
+            lower[(i, i)] = one();
+        }
+        let permutation = Self::from_fn(n, n, |row, col| {
+            if reduction.order[row] == col { one() } else { zero() }
+        });
+        (lower, upper, permutation)
+    }
+
+    /// Returns the coefficients of the characteristic polynomial `det(xI - A)` of a square matrix
+    /// `A`, from the leading coefficient, which is `1`, to the constant term.
+    ///
+    /// The coefficients are computed with the
+    /// [Faddeev-LeVerrier algorithm](https://en.wikipedia.org/wiki/Faddeev%E2%80%93LeVerrier_algorithm),
+    /// so they are exact if the entries of the matrix are exact.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn characteristic_polynomial(&self) -> Vec<Value> {
+        assert!(self.is_square(), "the characteristic polynomial is only defined for square matrices");
+        let n = self.rows;
+        let mut coeffs = vec![one()];
+        let mut m = Self::from_fn(n, n, |_, _| zero());
+        for k in 1..=n {
+            let prev = coeffs[k - 1].clone();
+            m = self.mul(&m).unwrap();
+            for i in 0..n {
+                m[(i, i)] = apply(BinOpKind::Add, &m[(i, i)], &prev);
+            }
+            let trace = self.mul(&m).unwrap().trace();
+            coeffs.push(apply(BinOpKind::Div, &trace, &Value::Integer(-int(k))));
+        }
+        coeffs
+    }
+
+    /// Returns the eigenvalues of a square matrix, repeated according to their algebraic
+    /// multiplicity, and sorted by their real parts, then their imaginary parts.
+    ///
+    /// The eigenvalues are the roots of the [characteristic
+    /// polynomial](Self::characteristic_polynomial), which are found numerically. If the entries
+    /// of the matrix are exact, real eigenvalues that are exactly rational are returned as exact
+    /// numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn eigenvalues(&self) -> Vec<Value> {
+        let coeffs = self.characteristic_polynomial();
+        let exact = coeffs.iter().all(|coeff| matches!(coeff, Value::Integer(_) | Value::Rational(_)));
+
+        let mut roots = polynomial_roots(&coeffs.iter().map(to_complex).collect::<Vec<_>>());
+        roots.sort_by(|a, b| a.real().partial_cmp(b.real())
+            .unwrap_or(Ordering::Equal)
+            .then(a.imag().partial_cmp(b.imag()).unwrap_or(Ordering::Equal)));
+
+        roots.into_iter()
+            .map(|root| {
+                let tolerance = float(root.abs_ref()).max(&float(1)) >> (precision() / 4);
+                let (re, im) = root.into_real_imag();
+                if float(im.abs_ref()) > tolerance {
+                    let re = if float(re.abs_ref()) > tolerance { re } else { float(0) };
+                    return Value::Complex(complex((re, im)));
+                }
+
+                if exact {
+                    let candidate = Value::from(approximate_rational_with(&re, &tolerance));
+                    if eval_polynomial(&coeffs, &candidate) == zero() {
+                        return candidate;
+                    }
+                }
+                Value::Float(re)
+            })
+            .collect()
+    }
+}
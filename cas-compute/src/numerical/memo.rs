@@ -0,0 +1,443 @@
+//! Memoization of calls to pure user-defined functions.
+//!
+//! A user-defined function is pure if its result only depends on its arguments. When such a
+//! function is defined, it is given a [`Memo`] table, which remembers the value returned for each
+//! combination of arguments it is called with, so that calling it again with the same arguments
+//! returns the remembered value instead of evaluating its body. This makes naive recursive
+//! definitions, such as `fib(n) = if n < 2 then n else fib(n - 1) + fib(n - 2)`, take linear
+//! instead of exponential time.
+//!
+//! A function is considered pure if its body, and the default values of its parameters:
+//!
+//! - only read its parameters, the local variables it defines, and the builtin constants, such
+//!   as `pi`,
+//! - only assign to its parameters and local variables,
+//! - only call itself, or builtins other than `print`, `rand` and those that evaluate the
+//!   unevaluated arguments of the call, such as `sum`, and
+//! - do not define functions or lambdas.
+//!
+//! Calls are only remembered if every argument is an integer, rational number or boolean, since
+//! those values can be compared exactly. The table is keyed on the precision, trigonometric mode
+//! and number mode of the context as well. It is bounded in size, and is discarded along with
+//! the function when the function is redefined. If a name called or constant read by the function
+//! is later redefined, such as by defining a user function named `sin` or assigning to `pi`, the
+//! table is no longer used.
+
+use cas_parser::parser::ast::{
+    assign::{AssignTarget, FuncHeader, Param},
+    expr::Expr,
+    literal::Literal,
+};
+use crate::numerical::{
+    builtin::Builtin,
+    ctxt::{Ctxt, Func, NumberMode, TrigMode, UserFunc},
+    value::Value,
+};
+use rug::{Integer, Rational};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+/// The maximum number of calls a [`Memo`] table remembers. Once it is full, it is cleared before
+/// another call is remembered.
+pub const MEMO_CAPACITY: usize = 1 << 12;
+
+/// Builtins that are not pure, even though they are given evaluated arguments.
+const IMPURE_BUILTINS: [&str; 2] = ["print", "rand"];
+
+/// An argument of a remembered call.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Arg {
+    Integer(Integer),
+    Rational(Rational),
+    Boolean(bool),
+}
+
+impl Arg {
+    /// Returns the argument for the given value, if calls with it can be remembered.
+    fn new(value: &Value) -> Option<Self> {
+        match value {
+            Value::Integer(n) => Some(Arg::Integer(n.clone())),
+            Value::Rational(n) => Some(Arg::Rational(n.clone())),
+            Value::Boolean(b) => Some(Arg::Boolean(*b)),
+            _ => None,
+        }
+    }
+}
+
+/// The key of a remembered call in a [`Memo`] table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    /// The arguments of the call, where [`None`] stands for a parameter given its default value.
+    args: Vec<Option<Arg>>,
+
+    /// The precision of the context.
+    precision: u32,
+
+    /// The trigonometric mode of the context.
+    trig_mode: TrigMode,
+
+    /// The number mode of the context.
+    number_mode: NumberMode,
+}
+
+/// The table of remembered calls to a pure user-defined function.
+///
+/// See the [module-level documentation](self) for more information.
+#[derive(Debug, Default)]
+pub struct Memo {
+    /// The builtins called by the function, which must still be the functions with those names
+    /// for the table to be used.
+    builtins: Vec<(String, Arc<dyn Builtin>)>,
+
+    /// The builtin constants read by the function, which must not have been redefined for the
+    /// table to be used.
+    constants: Vec<String>,
+
+    /// The values returned by remembered calls.
+    table: Mutex<HashMap<Key, Value>>,
+}
+
+impl Memo {
+    /// Returns a table for the function with the given header and body if it is pure, or [`None`]
+    /// if it is not. Names called by the function are looked up in the given context.
+    pub fn new(header: &FuncHeader, body: &Expr, ctxt: &Ctxt) -> Option<Self> {
+        let mut purity = Purity {
+            name: &header.name.name,
+            ctxt,
+            builtins: Vec::new(),
+            constants: Vec::new(),
+        };
+        let mut locals = header.params.iter()
+            .map(|param| param.symbol().name.clone())
+            .collect::<Vec<_>>();
+        for param in &header.params {
+            if let Param::Default(_, expr) = param {
+                purity.check(expr, &mut locals.clone())?;
+            }
+        }
+        purity.check(body, &mut locals)?;
+
+        Some(Self {
+            builtins: purity.builtins,
+            constants: purity.constants,
+            table: Mutex::default(),
+        })
+    }
+
+    /// Returns true if the names called by the function `this` still refer to the same functions
+    /// in the given context, and the constants it reads have not been redefined.
+    fn is_valid(&self, ctxt: &Ctxt, this: &Arc<UserFunc>) -> bool {
+        let is_this = matches!(
+            ctxt.get_global_func(&this.header.name.name),
+            Some(Func::UserFunc(user_func)) if Arc::ptr_eq(user_func, this)
+        );
+        is_this && self.builtins.iter().all(|(name, builtin)| matches!(
+            ctxt.get_global_func(name),
+            Some(Func::Builtin(current)) if std::ptr::addr_eq(Arc::as_ptr(current), Arc::as_ptr(builtin))
+        )) && self.constants.iter().all(|name| ctxt.is_global_constant(name))
+    }
+}
+
+impl Clone for Memo {
+    /// Clones the table, without the calls it remembers.
+    fn clone(&self) -> Self {
+        Self {
+            builtins: self.builtins.clone(),
+            constants: self.constants.clone(),
+            table: Mutex::default(),
+        }
+    }
+}
+
+impl PartialEq for Memo {
+    /// Tables are always equal, since they only hold values that can be computed again.
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Checks whether the body of a function is pure.
+struct Purity<'a> {
+    /// The name of the function.
+    name: &'a str,
+
+    /// The context that called names are looked up in.
+    ctxt: &'a Ctxt,
+
+    /// The builtins called by the function.
+    builtins: Vec<(String, Arc<dyn Builtin>)>,
+
+    /// The builtin constants read by the function.
+    constants: Vec<String>,
+}
+
+impl Purity<'_> {
+    /// Checks that the given expression is pure, given the local variables defined before it is
+    /// evaluated. Variables it defines are added to `locals`.
+    ///
+    /// Returns [`None`] if it is not pure.
+    fn check(&mut self, expr: &Expr, locals: &mut Vec<String>) -> Option<()> {
+        match expr {
+            Expr::Literal(Literal::Symbol(symbol)) => {
+                if locals.contains(&symbol.name) {
+                    Some(())
+                } else if self.ctxt.is_global_constant(&symbol.name) {
+                    self.constants.push(symbol.name.clone());
+                    Some(())
+                } else {
+                    None
+                }
+            },
+            Expr::Literal(Literal::List(list)) => list.values.iter()
+                .try_for_each(|value| self.check(value, locals)),
+            Expr::Literal(_) | Expr::Continue(_) => Some(()),
+            Expr::Paren(paren) => self.check(&paren.expr, locals),
+            Expr::Block(block) => block.stmts.iter()
+                .try_for_each(|stmt| self.check(&stmt.expr, locals)),
+
+            // variables defined in a branch or loop may not be defined after it
+            Expr::If(if_expr) => {
+                self.check(&if_expr.condition, locals)?;
+                self.check(&if_expr.then_expr, &mut locals.clone())?;
+                if_expr.else_expr.as_ref()
+                    .map_or(Some(()), |else_expr| self.check(else_expr, &mut locals.clone()))
+            },
+            Expr::Loop(loop_expr) => self.check(&loop_expr.body, &mut locals.clone()),
+            Expr::While(while_expr) => {
+                let mut inner = locals.clone();
+                self.check(&while_expr.condition, &mut inner)?;
+                self.check(&while_expr.body, &mut inner)
+            },
+            Expr::For(for_expr) => {
+                self.check(&for_expr.iterable, locals)?;
+                let mut inner = locals.clone();
+                inner.push(for_expr.variable.name.clone());
+                self.check(&for_expr.body, &mut inner)
+            },
+            Expr::Break(break_expr) => break_expr.value.as_ref()
+                .map_or(Some(()), |value| self.check(value, locals)),
+            Expr::Return(return_expr) => return_expr.value.as_ref()
+                .map_or(Some(()), |value| self.check(value, locals)),
+            Expr::Call(call) => {
+                if call.derivatives > 0 || locals.contains(&call.name.name) {
+                    return None;
+                }
+                if call.name.name != self.name {
+                    match self.ctxt.get_global_func(&call.name.name)? {
+                        Func::Builtin(builtin)
+                            if !builtin.takes_call()
+                                && !builtin.mutates_ctxt()
+                                && !IMPURE_BUILTINS.contains(&call.name.name.as_str()) => {
+                            self.builtins.push((call.name.name.clone(), Arc::clone(builtin)));
+                        },
+                        _ => return None,
+                    }
+                }
+                call.arg_values().try_for_each(|arg| self.check(arg, locals))
+            },
+            Expr::Index(index) => {
+                self.check(&index.target, locals)?;
+                index.index.exprs().into_iter().try_for_each(|expr| self.check(expr, locals))
+            },
+            Expr::Unary(unary) => self.check(&unary.operand, locals),
+            Expr::Binary(binary) => {
+                self.check(&binary.lhs, locals)?;
+                self.check(&binary.rhs, locals)
+            },
+            Expr::Assign(assign) => {
+                self.check(&assign.value, locals)?;
+                match &assign.target {
+                    // a plain assignment defines a local variable, while a compound assignment
+                    // assigns to a global variable if there is no local variable
+                    AssignTarget::Symbol(symbol) if !assign.op.is_compound() => {
+                        locals.push(symbol.name.clone());
+                        Some(())
+                    },
+                    AssignTarget::Symbol(symbol) => locals.contains(&symbol.name).then_some(()),
+                    AssignTarget::Index(index) => self.check(&Expr::Index(index.clone()), locals),
+                    AssignTarget::Func(_) => None,
+                }
+            },
+            Expr::Lambda(_) => None,
+        }
+    }
+}
+
+/// Locks the table of a pure user-defined function.
+fn lock(func: &UserFunc) -> MutexGuard<'_, HashMap<Key, Value>> {
+    let memo = func.memo.as_ref().expect("the function is pure");
+
+    // the table is never left in an inconsistent state, so it can be used after a panic
+    memo.table.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A call to a pure user-defined function, which can be looked up in, and added to, its [`Memo`]
+/// table.
+pub(crate) struct Memoized {
+    /// The function being called.
+    func: Arc<UserFunc>,
+
+    /// The key of the call.
+    key: Key,
+}
+
+impl Memoized {
+    /// Returns the call to the given function with the given arguments, if the function is pure
+    /// and calls with these arguments can be remembered. An argument of [`None`] gives its
+    /// parameter its default value.
+    pub(crate) fn new<'a>(
+        ctxt: &Ctxt,
+        func: &Func,
+        args: impl IntoIterator<Item = Option<&'a Value>>,
+    ) -> Option<Self> {
+        let Func::UserFunc(user_func) = func else {
+            return None;
+        };
+        let memo = user_func.memo.as_ref()?;
+        let args = args.into_iter()
+            .map(|arg| match arg {
+                Some(value) => Arg::new(value).map(Some),
+                None => Some(None),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if !memo.is_valid(ctxt, user_func) {
+            return None;
+        }
+
+        Some(Self {
+            func: Arc::clone(user_func),
+            key: Key {
+                args,
+                precision: ctxt.precision(),
+                trig_mode: ctxt.trig_mode,
+                number_mode: ctxt.number_mode,
+            },
+        })
+    }
+
+    /// Returns the value remembered for the call, if any.
+    pub(crate) fn get(&self) -> Option<Value> {
+        lock(&self.func).get(&self.key).cloned()
+    }
+
+    /// Remembers the value returned by the call.
+    pub(crate) fn insert(self, value: &Value) {
+        let mut table = lock(&self.func);
+        if table.len() >= MEMO_CAPACITY {
+            table.clear();
+        }
+        table.insert(self.key, value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::Parser;
+    use crate::numerical::{eval::eval_stmts, vm::run_stmts};
+    use super::*;
+
+    /// Evaluates the given statements in the context with both evaluators, returning the results.
+    fn eval(source: &str, ctxt: &mut Ctxt) -> (Value, Value) {
+        let stmts = Parser::new(source).try_parse_full_many().unwrap();
+        let expected = eval_stmts(&stmts, &mut ctxt.clone()).unwrap();
+        (expected, run_stmts(&stmts, ctxt).unwrap())
+    }
+
+    /// Returns true if the function defined by the given statement is pure.
+    fn is_pure(source: &str) -> bool {
+        let mut ctxt = Ctxt::default();
+        eval(source, &mut ctxt);
+        let name = source.split('(').next().unwrap();
+        match ctxt.get_func(name) {
+            Some(Func::UserFunc(user_func)) => user_func.memo.is_some(),
+            _ => panic!("`{}` is not a function", name),
+        }
+    }
+
+    #[test]
+    fn purity() {
+        assert!(is_pure("fib(n) = if n < 2 then n else fib(n - 1) + fib(n - 2)"));
+        assert!(is_pure("f(x, y = 2) = sqrt(x) + y"));
+        assert!(is_pure("f(n) = { total = 0; for i in 1..=n { total += i }; total }"));
+        assert!(is_pure("f(l) = { l[0] = 5; l }"));
+        assert!(is_pure("f(n) = sin(n * pi / 2)"));
+
+        assert!(!is_pure("f(n) = n + a"));
+        assert!(!is_pure("f(n) = rand() * n"));
+        assert!(!is_pure("f(n) = print(n)"));
+        assert!(!is_pure("f(n) = { count += 1; n }"));
+        assert!(!is_pure("f(n) = sum(k * n, k, 1, 3)"));
+        assert!(!is_pure("f(n) = g(n)"));
+        assert!(!is_pure("f(n) = x -> x + n"));
+        assert!(!is_pure("f(n) = { if n > 0 then m = 1; m }"));
+        assert!(!is_pure("f(n) = { m; m = 1 }"));
+    }
+
+    #[test]
+    fn exponential_recursion() {
+        // without memoization, these would take far too long
+        let mut ctxt = Ctxt::default();
+        let (expected, value) = eval("fib(n) = if n < 2 then n else fib(n - 1) + fib(n - 2); fib(60)", &mut ctxt);
+        assert_eq!(expected, value);
+        assert_eq!(value, Integer::from(1548008755920_u64).into());
+
+        let (expected, value) = eval("c(n, k) = if k == 0 || k == n then 1 else c(n - 1, k - 1) + c(n - 1, k); c(60, 30)", &mut ctxt);
+        assert_eq!(expected, value);
+        assert_eq!(value, Integer::from(118264581564861424_u64).into());
+    }
+
+    #[test]
+    fn redefinition() {
+        let mut ctxt = Ctxt::default();
+        assert_eq!(eval("f(n) = n + 1; f(1)", &mut ctxt).1, 2.into());
+        assert_eq!(eval("f(n) = n + 2; f(1)", &mut ctxt).1, 3.into());
+
+        // redefining a builtin called by the function stops the table from being used
+        assert_eq!(eval("g(n) = abs(n); g(-2)", &mut ctxt).1.to_string(), "2");
+        assert_eq!(eval("abs(n) = 7; g(-2)", &mut ctxt).1, 7.into());
+
+        // so does redefining a constant read by the function
+        assert_eq!(eval("p(n) = n * pi; p(2) > 6", &mut ctxt).1, true.into());
+        let (expected, value) = eval("pi = 3; p(2)", &mut ctxt);
+        assert_eq!(expected, value);
+        assert_eq!(value, 6.into());
+
+        // the function itself can be shadowed by a variable
+        assert_eq!(eval("h(n) = if n > 0 then h(n - 1) + 1 else 0; h(3)", &mut ctxt).1, 3.into());
+        let (expected, value) = eval("k(n) = 10; old = h; h = k; old(3)", &mut ctxt);
+        assert_eq!(expected, value);
+        assert_eq!(value, 11.into());
+    }
+
+    #[test]
+    fn derivatives() {
+        // calls using prime notation are not remembered as calls to the function itself
+        let mut ctxt = Ctxt::default();
+        let (expected, value) = eval("f(x) = x^2; [f(3), f'(3), f''(3)]", &mut ctxt);
+        assert_eq!(expected, value);
+        assert_eq!(value.to_string(), "[9, 6, 2]");
+
+        let mut ctxt = Ctxt::default();
+        let (expected, value) = eval("f(x) = x^2; [f''(3), f'(3), f(3)]", &mut ctxt);
+        assert_eq!(expected, value);
+        assert_eq!(value.to_string(), "[2, 6, 9]");
+    }
+
+    #[test]
+    fn modes() {
+        let mut ctxt = Ctxt::default();
+        let (_, radians) = eval("f(n) = sin(n); f(1)", &mut ctxt);
+        match ctxt.get_func("f") {
+            Some(Func::UserFunc(user_func)) => assert_eq!(lock(user_func).len(), 1),
+            _ => panic!("`f` is not a function"),
+        }
+
+        ctxt.trig_mode = TrigMode::Degrees;
+        let (expected, degrees) = eval("f(1)", &mut ctxt);
+        assert_eq!(expected, degrees);
+        assert_ne!(degrees, radians);
+        assert_eq!(degrees, eval("sin(1)", &mut ctxt).1);
+    }
+}
@@ -4,12 +4,21 @@
 
 #![cfg(feature = "numerical")]
 
+pub mod budget;
 pub mod builtin;
 pub mod ctxt;
 pub mod error;
 pub mod eval;
+pub mod fast;
 pub mod fmt;
+pub mod matrix;
+pub mod memo;
+pub mod range;
 pub mod value;
+pub mod vm;
+
+#[cfg(test)]
+pub(crate) mod test_util;
 
 #[cfg(test)]
 mod tests {
@@ -52,4 +61,22 @@ mod tests {
         let source = include_str!("../../../examples/manual_abs.calc");
         assert_eq!(eval(source), 4.into());
     }
+
+    #[test]
+    fn radioactive_decay() {
+        let source = include_str!("../../../examples/radioactive_decay.calc");
+        assert_eq!(eval(source), true.into());
+    }
+
+    #[test]
+    fn basel_problem() {
+        let source = include_str!("../../../examples/basel_problem.calc");
+        assert_eq!(eval(source), true.into());
+    }
+
+    #[test]
+    fn symbolic() {
+        let source = include_str!("../../../examples/symbolic.calc");
+        assert_eq!(eval(source), true.into());
+    }
 }
@@ -0,0 +1,139 @@
+//! Ranges of consecutive integers, produced by the `..` and `..=` operators.
+
+use rug::Integer;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A range of consecutive integers, such as `0..10` or `1..=n`.
+///
+/// A range is lazy: its integers are only produced as it is iterated over, so looping over a large
+/// range does not build a list of every integer in it. A range whose end is not after its start is
+/// empty.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Range {
+    /// The first integer in the range.
+    start: Integer,
+
+    /// The end of the range.
+    end: Integer,
+
+    /// Whether the end of the range is included in the range.
+    inclusive: bool,
+}
+
+impl Range {
+    /// Creates a range from `start` to `end`, which includes `end` if `inclusive` is true.
+    pub fn new(start: Integer, end: Integer, inclusive: bool) -> Self {
+        Self { start, end, inclusive }
+    }
+
+    /// Returns the first integer in the range.
+    pub fn start(&self) -> &Integer {
+        &self.start
+    }
+
+    /// Returns the end of the range.
+    pub fn end(&self) -> &Integer {
+        &self.end
+    }
+
+    /// Returns true if the end of the range is included in the range.
+    pub fn is_inclusive(&self) -> bool {
+        self.inclusive
+    }
+
+    /// Returns the number of integers in the range.
+    pub fn len(&self) -> Integer {
+        let len = Integer::from(&self.end - &self.start) + u32::from(self.inclusive);
+        len.max(Integer::ZERO)
+    }
+
+    /// Returns true if the range contains no integers.
+    pub fn is_empty(&self) -> bool {
+        if self.inclusive {
+            self.start > self.end
+        } else {
+            self.start >= self.end
+        }
+    }
+
+    /// Returns an iterator over the integers in the range, in increasing order.
+    pub fn iter(&self) -> Iter {
+        Iter {
+            next: self.start.clone(),
+            end: self.end.clone(),
+            inclusive: self.inclusive,
+        }
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.inclusive {
+            write!(f, "{}..={}", self.start, self.end)
+        } else {
+            write!(f, "{}..{}", self.start, self.end)
+        }
+    }
+}
+
+/// An iterator over the integers in a [`Range`].
+#[derive(Debug, Clone)]
+pub struct Iter {
+    /// The next integer to produce.
+    next: Integer,
+
+    /// The end of the range.
+    end: Integer,
+
+    /// Whether the end of the range is included in the range.
+    inclusive: bool,
+}
+
+impl Iterator for Iter {
+    type Item = Integer;
+
+    fn next(&mut self) -> Option<Integer> {
+        let in_range = if self.inclusive {
+            self.next <= self.end
+        } else {
+            self.next < self.end
+        };
+        in_range.then(|| {
+            let value = self.next.clone();
+            self.next += 1;
+            value
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterate() {
+        let range = Range::new(Integer::from(-1), Integer::from(3), false);
+        assert_eq!(range.iter().collect::<Vec<_>>(), [-1, 0, 1, 2]);
+        assert_eq!(range.len(), 4);
+
+        let range = Range::new(Integer::from(1), Integer::from(3), true);
+        assert_eq!(range.iter().collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(range.len(), 3);
+    }
+
+    #[test]
+    fn empty() {
+        let range = Range::new(Integer::from(5), Integer::from(5), false);
+        assert!(range.is_empty());
+        assert_eq!(range.iter().count(), 0);
+
+        let range = Range::new(Integer::from(5), Integer::from(2), true);
+        assert!(range.is_empty());
+        assert_eq!(range.len(), 0);
+        assert_eq!(range.iter().count(), 0);
+    }
+}
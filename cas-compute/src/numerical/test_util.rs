@@ -0,0 +1,15 @@
+//! Helpers shared by the tests of the evaluator and the builtin function modules.
+
+use cas_parser::parser::Parser;
+use super::{error::Error, eval::eval_stmts, value::Value};
+
+/// Evaluates the given source code in a default context.
+pub fn eval(source: &str) -> Result<Value, Error> {
+    let stmts = Parser::new(source).try_parse_full_many().unwrap();
+    eval_stmts(&stmts, &mut Default::default())
+}
+
+/// Evaluates the given source code in a default context and formats the result.
+pub fn eval_str(source: &str) -> String {
+    eval(source).unwrap().to_string()
+}
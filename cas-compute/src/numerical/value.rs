@@ -1,8 +1,10 @@
-use crate::consts::PI;
+use crate::consts::pi;
+use crate::interval::Interval;
 use crate::primitive::{complex, float};
-use rug::{Complex, Float, Integer};
+use crate::symbolic::{expr::{Expr, Primary}, simplify::fraction::rational_expr};
+use rug::{Complex, Float, Integer, Rational};
 use std::fmt::{Display, Formatter};
-use super::fmt::{FormatOptions, ValueFormatter};
+use super::{ctxt::Function, fmt::{FormatOptions, ValueFormatter}, matrix::Matrix, range::Range};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -17,9 +19,18 @@ pub enum Value {
     /// An integer value.
     Integer(Integer),
 
+    /// An exact rational number, such as `1/3`. Its denominator is never `1`; rationals with an
+    /// integer value are stored as [`Value::Integer`] instead, which converting a [`Rational`]
+    /// with `Value::from` takes care of.
+    Rational(Rational),
+
     /// A complex number value.
     Complex(Complex),
 
+    /// A closed interval of real numbers that is guaranteed to contain the result of a
+    /// computation. See [`Interval`] for more information.
+    Interval(Interval),
+
     /// A boolean.
     Boolean(bool),
 
@@ -28,6 +39,19 @@ pub enum Value {
 
     /// A list of values.
     List(Vec<Value>),
+
+    /// A range of consecutive integers, such as `0..10`. See [`Range`] for more information.
+    Range(Range),
+
+    /// A matrix of numbers. See [`Matrix`] for more information.
+    Matrix(Matrix),
+
+    /// An unevaluated symbolic expression, such as `2x + 1`.
+    Expr(Expr),
+
+    /// A function, such as a builtin or user-defined function referred to by name, or a lambda.
+    /// See [`Function`] for more information.
+    Function(Function),
 }
 
 #[cfg(test)]
@@ -47,10 +71,16 @@ impl Value {
         match self {
             Value::Float(_) => "Float",
             Value::Integer(_) => "Integer",
+            Value::Rational(_) => "Rational",
             Value::Complex(_) => "Complex",
+            Value::Interval(_) => "Interval",
             Value::Boolean(_) => "Boolean",
             Value::Unit => "Unit",
             Value::List(_) => "List",
+            Value::Range(_) => "Range",
+            Value::Matrix(_) => "Matrix",
+            Value::Expr(_) => "Expr",
+            Value::Function(_) => "Function",
         }
     }
 
@@ -60,7 +90,7 @@ impl Value {
     ///
     /// This conversion only occurs if one of the following is true:
     ///
-    /// - The value is an integer.
+    /// - The value is an integer or a rational number.
     /// - The value is a complex number with a zero imaginary part.
     ///
     /// This is useful for when evaluation of an expression results in a [`Value::Complex`] with a
@@ -70,6 +100,7 @@ impl Value {
     pub fn coerce_float(self) -> Self {
         match self {
             Value::Integer(n) => Value::Float(float(n)),
+            Value::Rational(n) => Value::Float(float(n)),
             Value::Complex(c) if c.imag().is_zero() => Value::Float(c.into_real_imag().0),
             _ => self,
         }
@@ -92,12 +123,25 @@ impl Value {
         }
     }
 
+    /// Consumes and attempts to coerce the value to a rational number. **This coercion is
+    /// lossless**, and is used to perform exact arithmetic on integers and rational numbers.
+    ///
+    /// Only integers are converted to [`Value::Rational`]; all other values are returned as-is.
+    /// Note that the result breaks the invariant that a [`Value::Rational`] never has a
+    /// denominator of `1`, so it should only be used for intermediate computations.
+    pub fn coerce_rational(self) -> Self {
+        match self {
+            Value::Integer(n) => Value::Rational(Rational::from(n)),
+            _ => self,
+        }
+    }
+
     /// Consumes and attempts to coerce the value to a real number or an integer, preferring
     /// integers if possible. **This coercion is lossless**.
     ///
     /// This conversion follows these rules:
     ///
-    /// - If the value is an integer, it is returned as-is.
+    /// - If the value is an integer or a rational number, it is returned as-is.
     /// - If the value is a float with a zero fractional part, it is converted to an integer.
     /// Otherwise, it is returned as-is.
     /// - If the value is a complex number with a zero imaginary part, either an integer or float
@@ -123,6 +167,57 @@ impl Value {
         match self {
             Value::Float(n) => Value::Complex(complex(n)),
             Value::Integer(n) => Value::Complex(complex(n)),
+            Value::Rational(n) => Value::Complex(complex(n)),
+            _ => self,
+        }
+    }
+
+    /// Consumes and attempts to coerce the value to an interval. The resulting interval is the
+    /// smallest one that contains the exact value of the number.
+    ///
+    /// This conversion only occurs if the value is a real number, or a complex number with a zero
+    /// imaginary part.
+    pub fn coerce_interval(self) -> Self {
+        match self {
+            Value::Float(n) => Value::Interval(Interval::point(n)),
+            Value::Integer(n) => Value::Interval(Interval::from_integer(&n)),
+            Value::Rational(n) => Value::Interval(Interval::from_rational(&n)),
+            Value::Complex(c) if c.imag().is_zero() => {
+                Value::Interval(Interval::point(c.into_real_imag().0))
+            },
+            _ => self,
+        }
+    }
+
+    /// Consumes and attempts to coerce the value to a list. This coercion is lossless.
+    ///
+    /// This conversion only occurs if the value is a range, which is converted to the list of the
+    /// integers in it.
+    pub fn coerce_list(self) -> Self {
+        match self {
+            Value::Range(range) => Value::List(range.iter().map(Value::Integer).collect()),
+            _ => self,
+        }
+    }
+
+    /// Consumes and attempts to coerce the value to a matrix. This coercion is lossless.
+    ///
+    /// This conversion only occurs if the value is a non-empty list of rows, which are non-empty
+    /// lists of numbers with the same length.
+    pub fn coerce_matrix(self) -> Self {
+        match self {
+            Value::List(rows) if rows.iter().all(Value::is_list) => {
+                let rows = rows.into_iter()
+                    .map(|row| match row {
+                        Value::List(row) => row,
+                        _ => unreachable!(),
+                    })
+                    .collect::<Vec<_>>();
+                match Matrix::from_rows(rows.clone()) {
+                    Some(matrix) => Value::Matrix(matrix),
+                    None => Value::List(rows.into_iter().map(Value::List).collect()),
+                }
+            },
             _ => self,
         }
     }
@@ -130,10 +225,11 @@ impl Value {
     /// Converts this value from radians to degrees. If it is a real number, it is converted as
     /// usual. If it is a complex number, the real and imaginary parts are converted separately.
     pub fn into_degrees(self) -> Self {
-        let convert = |n: Float| n * 180.0 / &*PI;
+        let convert = |n: Float| n * 180.0 / &pi();
         match self {
             Value::Float(n) => Value::Float(convert(n)),
             Value::Integer(n) => Value::Float(convert(float(n))),
+            Value::Rational(n) => Value::Float(convert(float(n))),
             Value::Complex(c) => Value::Complex({
                 let (real, imag) = c.into_real_imag();
                 complex((convert(real), convert(imag)))
@@ -145,10 +241,11 @@ impl Value {
     /// Converts this value from degrees to radians. If it is a real number, it is converted as
     /// usual. If it is a complex number, the real and imaginary parts are converted separately.
     pub fn into_radians(self) -> Self {
-        let convert = |n: Float| n * &*PI / 180.0;
+        let convert = |n: Float| n * &pi() / 180.0;
         match self {
             Value::Float(n) => Value::Float(convert(n)),
             Value::Integer(n) => Value::Float(convert(float(n))),
+            Value::Rational(n) => Value::Float(convert(float(n))),
             Value::Complex(c) => Value::Complex({
                 let (real, imag) = c.into_real_imag();
                 complex((convert(real), convert(imag)))
@@ -162,6 +259,7 @@ impl Value {
         match self {
             Value::Float(_) => true,
             Value::Integer(_) => true,
+            Value::Rational(_) => true,
             Value::Complex(c) => c.imag().is_zero(),
             _ => false,
         }
@@ -177,9 +275,20 @@ impl Value {
         }
     }
 
+    /// Returns true if this value is a rational number, or can be coerced to one without loss of
+    /// precision.
+    pub fn is_rational(&self) -> bool {
+        matches!(self, Value::Rational(_) | Value::Integer(_))
+    }
+
     /// Returns true if this value is a complex number, or can be coerced to one.
     pub fn is_complex(&self) -> bool {
-        matches!(self, Value::Complex(_) | Value::Float(_) | Value::Integer(_))
+        matches!(self, Value::Complex(_) | Value::Float(_) | Value::Integer(_) | Value::Rational(_))
+    }
+
+    /// Returns true if this value is an interval, or can be coerced to one.
+    pub fn is_interval(&self) -> bool {
+        matches!(self, Value::Interval(_)) || self.is_real()
     }
 
     /// Returns true if this value is a boolean.
@@ -187,6 +296,31 @@ impl Value {
         matches!(self, Value::Boolean(_))
     }
 
+    /// Returns true if this value is a list.
+    pub fn is_list(&self) -> bool {
+        matches!(self, Value::List(_))
+    }
+
+    /// Returns true if this value is a range.
+    pub fn is_range(&self) -> bool {
+        matches!(self, Value::Range(_))
+    }
+
+    /// Returns true if this value is a matrix.
+    pub fn is_matrix(&self) -> bool {
+        matches!(self, Value::Matrix(_))
+    }
+
+    /// Returns true if this value is a symbolic expression.
+    pub fn is_expr(&self) -> bool {
+        matches!(self, Value::Expr(_))
+    }
+
+    /// Returns true if this value is a function.
+    pub fn is_function(&self) -> bool {
+        matches!(self, Value::Function(_))
+    }
+
     /// Returns true if this value is a unit type.
     pub fn is_unit(&self) -> bool {
         matches!(self, Value::Unit)
@@ -197,10 +331,41 @@ impl Value {
         match self {
             Value::Float(n) => !n.is_zero(),
             Value::Integer(n) => !n.is_zero(),
+            Value::Rational(n) => *n != 0,
             Value::Complex(c) => !c.is_zero(),
+            Value::Interval(n) => !(n.lo().is_zero() && n.hi().is_zero()),
             Value::Boolean(b) => *b,
             Value::Unit => false,
             Value::List(l) => !l.is_empty(),
+            Value::Range(r) => !r.is_empty(),
+            Value::Matrix(_) => true,
+            Value::Expr(_) => true,
+            Value::Function(_) => true,
+        }
+    }
+
+    /// Converts this value into a symbolic expression, if it is a finite number or an expression.
+    ///
+    /// Complex numbers are written as `a + b * i`, where `i` is the imaginary unit.
+    pub fn into_expr(self) -> Option<Expr> {
+        let real = |n: Float| if n.is_integer() {
+            n.to_integer().map(|n| Expr::Primary(Primary::Integer(n)))
+        } else if n.is_finite() {
+            Some(Expr::Primary(Primary::Float(n)))
+        } else {
+            None
+        };
+        match self {
+            Value::Float(n) => real(n),
+            Value::Integer(n) => Some(Expr::Primary(Primary::Integer(n))),
+            Value::Rational(n) => Some(rational_expr(&n)),
+            Value::Complex(c) => {
+                let (re, im) = c.into_real_imag();
+                let i = Expr::Primary(Primary::Symbol("i".to_string()));
+                Some(real(re)? + real(im)? * i)
+            },
+            Value::Expr(expr) => Some(expr),
+            _ => None,
         }
     }
 
@@ -237,12 +402,30 @@ impl From<Integer> for Value {
     }
 }
 
+/// Converts a rational number into a value. Rational numbers with a denominator of `1` are
+/// converted into [`Value::Integer`].
+impl From<Rational> for Value {
+    fn from(n: Rational) -> Self {
+        if *n.denom() == 1 {
+            Value::Integer(n.into_numer_denom().0)
+        } else {
+            Value::Rational(n)
+        }
+    }
+}
+
 impl From<Complex> for Value {
     fn from(c: Complex) -> Self {
         Value::Complex(c)
     }
 }
 
+impl From<Interval> for Value {
+    fn from(n: Interval) -> Self {
+        Value::Interval(n)
+    }
+}
+
 impl From<bool> for Value {
     fn from(b: bool) -> Self {
         Value::Boolean(b)
@@ -255,6 +438,36 @@ impl From<()> for Value {
     }
 }
 
+impl From<Matrix> for Value {
+    fn from(matrix: Matrix) -> Self {
+        Value::Matrix(matrix)
+    }
+}
+
+impl From<Range> for Value {
+    fn from(range: Range) -> Self {
+        Value::Range(range)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Self {
+        Value::List(values)
+    }
+}
+
+/// Converts a symbolic expression into a value. Expressions that are a single number are converted
+/// into that number, and all other expressions are kept as [`Value::Expr`].
+impl From<Expr> for Value {
+    fn from(expr: Expr) -> Self {
+        match expr {
+            Expr::Primary(Primary::Integer(n)) => Value::Integer(n),
+            Expr::Primary(Primary::Float(n)) => Value::Float(n),
+            expr => Value::Expr(expr),
+        }
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         self.fmt(Default::default()).fmt(f)
@@ -0,0 +1,764 @@
+//! Compilation of CalcScript to [`Instruction`]s.
+
+use cas_parser::parser::{
+    ast::{
+        assign::{Assign, AssignTarget, Param},
+        call::Call,
+        expr::Expr,
+        index::{Index, IndexKind},
+        lambda::Lambda,
+        literal::{Literal, LitSym, LitUnit},
+        stmt::Stmt,
+    },
+    token::op::{AssignOpKind, BinOpKind, UnaryOp},
+};
+use crate::numerical::{eval::index_chain, value::Value};
+use crate::primitive::{from_str_radix, int_from_str};
+use std::{collections::HashMap, ops::Range};
+use super::instruction::{Instruction, LoopTarget};
+
+/// The information about a binary expression needed to evaluate it.
+#[derive(Debug)]
+pub(super) struct BinarySite {
+    /// The operator.
+    pub op: BinOpKind,
+
+    /// Whether the operator is an implicit multiplication.
+    pub implicit: bool,
+
+    /// The span of the whole expression.
+    pub span: Range<usize>,
+
+    /// The spans of the left operand, the operator, and the right operand.
+    pub spans: Vec<Range<usize>>,
+}
+
+/// The information about a unary expression needed to evaluate it.
+#[derive(Debug)]
+pub(super) struct UnarySite {
+    /// The operator.
+    pub op: UnaryOp,
+
+    /// The span of the operand.
+    pub operand: Range<usize>,
+
+    /// The span of the whole expression.
+    pub span: Range<usize>,
+}
+
+/// The information about a compound assignment, or an assignment to a list element or slice,
+/// needed to evaluate it.
+#[derive(Debug)]
+pub(super) struct AssignSite {
+    /// The assignment, without its right-hand side.
+    pub assign: Assign,
+
+    /// The symbol holding the variable or list that is assigned to.
+    pub symbol: LitSym,
+
+    /// The chain of indices of the target, starting from the one applied directly to the symbol.
+    /// This is empty if the target is a symbol.
+    pub chain: Vec<Index>,
+}
+
+/// The information about a function call needed to evaluate it.
+#[derive(Debug)]
+pub(super) struct CallSite {
+    /// The call, without its arguments.
+    pub call: Call,
+
+    /// The slot of the local variable with the same name as the function, if any, which can hold
+    /// a function that shadows it.
+    pub slot: Option<usize>,
+
+    /// Whether the arguments can be evaluated by compiled code before calling a builtin function.
+    ///
+    /// Builtin functions are called with a copy of the context, so arguments that assign to
+    /// variables, or exit a loop or function, are evaluated by the tree-walking evaluator instead.
+    pub compiled_args: bool,
+}
+
+/// An expression evaluated by the tree-walking evaluator.
+#[derive(Debug)]
+pub(super) struct Fallback {
+    /// The expression.
+    pub expr: Expr,
+
+    /// The innermost loop surrounding the expression, if any.
+    pub target: Option<LoopTarget>,
+}
+
+/// A compiled list of statements, or body of a function.
+#[derive(Debug, Default)]
+pub struct Chunk {
+    /// The instructions.
+    pub(super) code: Vec<Instruction>,
+
+    /// The constants used by [`Instruction::Const`].
+    pub(super) consts: Vec<Value>,
+
+    /// The float literals used by [`Instruction::Float`].
+    pub(super) floats: Vec<String>,
+
+    /// The symbols used by instructions that look up or define variables by name.
+    pub(super) symbols: Vec<LitSym>,
+
+    /// The spans used by instructions that report errors.
+    pub(super) spans: Vec<Range<usize>>,
+
+    /// The unary expressions used by [`Instruction::Unary`].
+    pub(super) unaries: Vec<UnarySite>,
+
+    /// The binary expressions used by [`Instruction::Binary`].
+    pub(super) binaries: Vec<BinarySite>,
+
+    /// The index expressions used by [`Instruction::IndexTarget`] and [`Instruction::Index`],
+    /// without their targets.
+    pub(super) indices: Vec<Index>,
+
+    /// The assignments used by [`Instruction::Compound`] and [`Instruction::AssignIndex`].
+    pub(super) assigns: Vec<AssignSite>,
+
+    /// The calls used by [`Instruction::ResolveCall`] and [`Instruction::Call`].
+    pub(super) calls: Vec<CallSite>,
+
+    /// The lambda expressions used by [`Instruction::MakeLambda`].
+    pub(super) lambdas: Vec<Lambda>,
+
+    /// The expressions used by [`Instruction::Eval`] and [`Instruction::ResolveCall`].
+    pub(super) fallbacks: Vec<Fallback>,
+
+    /// The names of the local variables stored in slots. This is empty for a list of statements,
+    /// whose variables are stored in the context.
+    pub(super) locals: Vec<String>,
+
+    /// The slot of each local variable, by name.
+    pub(super) slots: HashMap<String, usize>,
+
+    /// The slot each parameter of a function is bound to.
+    pub(super) params: Vec<usize>,
+}
+
+/// A loop being compiled.
+struct LoopScope {
+    /// The height of the stack when the loop was entered.
+    depth: usize,
+
+    /// The instruction that starts the next iteration of the loop.
+    next: usize,
+
+    /// The instructions and fallback expressions that exit the loop, whose exit is not known
+    /// until the loop has been compiled.
+    exits: Vec<Exit>,
+}
+
+/// An instruction or fallback expression that can exit a loop.
+enum Exit {
+    Instruction(usize),
+    Fallback(usize),
+}
+
+/// Returns an expression with the same span as the given expression, that is stored instead of it
+/// where only its span is needed.
+fn placeholder(expr: &Expr) -> Expr {
+    Expr::Literal(Literal::Unit(LitUnit { span: expr.span() }))
+}
+
+/// Returns a copy of the index expression, without its target.
+fn shallow_index(index: &Index) -> Index {
+    Index {
+        target: Box::new(placeholder(&index.target)),
+        index: match &index.index {
+            IndexKind::Single(expr) => IndexKind::Single(Box::new(placeholder(expr))),
+            IndexKind::Slice { start, end } => IndexKind::Slice {
+                start: start.as_deref().map(placeholder).map(Box::new),
+                end: end.as_deref().map(placeholder).map(Box::new),
+            },
+        },
+        span: index.span.clone(),
+        bracket_span: index.bracket_span.clone(),
+    }
+}
+
+/// Calls `f` with the expression and each of its subexpressions that are evaluated in the same
+/// scope, which excludes the bodies of lambdas and function definitions.
+fn visit(expr: &Expr, f: &mut impl FnMut(&Expr)) {
+    f(expr);
+    match expr {
+        Expr::Literal(Literal::List(list)) => list.values.iter().for_each(|value| visit(value, f)),
+        Expr::Literal(_) | Expr::Continue(_) | Expr::Lambda(_) => (),
+        Expr::Paren(paren) => visit(&paren.expr, f),
+        Expr::Block(block) => block.stmts.iter().for_each(|stmt| visit(&stmt.expr, f)),
+        Expr::If(if_expr) => {
+            visit(&if_expr.condition, f);
+            visit(&if_expr.then_expr, f);
+            if let Some(else_expr) = &if_expr.else_expr {
+                visit(else_expr, f);
+            }
+        },
+        Expr::Loop(loop_expr) => visit(&loop_expr.body, f),
+        Expr::While(while_expr) => {
+            visit(&while_expr.condition, f);
+            visit(&while_expr.body, f);
+        },
+        Expr::For(for_expr) => {
+            visit(&for_expr.iterable, f);
+            visit(&for_expr.body, f);
+        },
+        Expr::Break(break_expr) => {
+            if let Some(value) = &break_expr.value {
+                visit(value, f);
+            }
+        },
+        Expr::Return(return_expr) => {
+            if let Some(value) = &return_expr.value {
+                visit(value, f);
+            }
+        },
+        Expr::Call(call) => call.arg_values().for_each(|arg| visit(arg, f)),
+        Expr::Index(index) => visit_index(index, f),
+        Expr::Unary(unary) => visit(&unary.operand, f),
+        Expr::Binary(binary) => {
+            visit(&binary.lhs, f);
+            visit(&binary.rhs, f);
+        },
+        Expr::Assign(assign) => match &assign.target {
+            AssignTarget::Func(_) => (),
+            AssignTarget::Symbol(_) => visit(&assign.value, f),
+            AssignTarget::Index(index) => {
+                visit(&assign.value, f);
+                visit_index(index, f);
+            },
+        },
+    }
+}
+
+/// Calls [`visit`] with the target and index expressions of an index expression.
+fn visit_index(index: &Index, f: &mut impl FnMut(&Expr)) {
+    visit(&index.target, f);
+    match &index.index {
+        IndexKind::Single(expr) => visit(expr, f),
+        IndexKind::Slice { start, end } => {
+            for expr in [start, end].into_iter().flatten() {
+                visit(expr, f);
+            }
+        },
+    }
+}
+
+/// Compiles expressions into a [`Chunk`].
+#[derive(Default)]
+struct Compiler {
+    /// The chunk being compiled.
+    chunk: Chunk,
+
+    /// The height of the stack at the current instruction, relative to the start of the chunk.
+    depth: usize,
+
+    /// The loops surrounding the current instruction, from outermost to innermost.
+    loops: Vec<LoopScope>,
+}
+
+/// Compiles a list of statements, whose variables are stored in the context.
+///
+/// A step is taken before the statements are run, so that a cancelled evaluation stops even if it
+/// does not loop or call any functions.
+pub(super) fn compile_stmts(stmts: &[Stmt]) -> Chunk {
+    let mut compiler = Compiler::default();
+    if let (Some(first), Some(last)) = (stmts.first(), stmts.last()) {
+        let span = compiler.span(first.span.start..last.span.end);
+        compiler.emit(Instruction::Step(span));
+    }
+    compiler.stmts(stmts);
+    compiler.emit(Instruction::Return);
+    compiler.chunk
+}
+
+/// Compiles the body of a user-defined function or lambda with the given parameters.
+///
+/// Every variable the function defines is stored in a slot, including its parameters. The default
+/// values of parameters that are not given an argument are evaluated when the function is called,
+/// before the body.
+pub(super) fn compile_function(params: &[Param], body: &Expr) -> Chunk {
+    let mut compiler = Compiler::default();
+    let param_slots = params.iter()
+        .map(|param| compiler.declare(&param.symbol().name))
+        .collect();
+    compiler.chunk.params = param_slots;
+
+    let mut declare = |expr: &Expr| match expr {
+        Expr::Assign(assign) => match &assign.target {
+            AssignTarget::Symbol(symbol) if assign.op.kind == AssignOpKind::Assign => {
+                compiler.declare(&symbol.name);
+            },
+            AssignTarget::Func(header) => {
+                compiler.declare(&header.name.name);
+            },
+            _ => (),
+        },
+        Expr::For(for_expr) => {
+            compiler.declare(&for_expr.variable.name);
+        },
+        _ => (),
+    };
+    for param in params {
+        if let Param::Default(_, expr) = param {
+            visit(expr, &mut declare);
+        }
+    }
+    visit(body, &mut declare);
+
+    for (index, param) in params.iter().enumerate() {
+        let jump = compiler.emit(Instruction::JumpIfBound { param: index, target: 0 });
+        match param {
+            Param::Symbol(_) => {
+                compiler.emit(Instruction::MissingArgument(index));
+            },
+            Param::Default(_, expr) => {
+                compiler.expr(expr);
+                compiler.emit(Instruction::StoreLocal {
+                    slot: compiler.chunk.params[index],
+                    assign: false,
+                });
+                compiler.emit(Instruction::Pop);
+            },
+        }
+        compiler.patch(jump);
+    }
+
+    compiler.expr(body);
+    compiler.emit(Instruction::Return);
+    compiler.chunk
+}
+
+impl Compiler {
+    /// Declares a local variable, returning its slot.
+    fn declare(&mut self, name: &str) -> usize {
+        if let Some(&slot) = self.chunk.slots.get(name) {
+            return slot;
+        }
+        let slot = self.chunk.locals.len();
+        self.chunk.locals.push(name.to_string());
+        self.chunk.slots.insert(name.to_string(), slot);
+        slot
+    }
+
+    /// Returns the slot of the local variable with the given name, if it is stored in one.
+    fn slot(&self, name: &str) -> Option<usize> {
+        self.chunk.slots.get(name).copied()
+    }
+
+    /// Adds an item to one of the tables of the chunk, returning its index.
+    fn add<T>(table: &mut Vec<T>, item: T) -> usize {
+        table.push(item);
+        table.len() - 1
+    }
+
+    /// Adds a span to the chunk, returning its index.
+    fn span(&mut self, span: Range<usize>) -> usize {
+        Self::add(&mut self.chunk.spans, span)
+    }
+
+    /// Adds a symbol to the chunk, returning its index.
+    fn symbol(&mut self, symbol: &LitSym) -> usize {
+        Self::add(&mut self.chunk.symbols, symbol.clone())
+    }
+
+    /// Adds an instruction to the chunk, returning its index.
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.depth = match instruction {
+            Instruction::Const(_)
+                | Instruction::Float(_)
+                | Instruction::PushUnit
+                | Instruction::LoadLocal { .. }
+                | Instruction::LoadName { .. }
+                | Instruction::MakeLambda(_)
+                | Instruction::ResolveCall { .. }
+                | Instruction::IterNext { .. }
+                | Instruction::Eval(_)
+                // `continue` does not produce a value, but the expression is treated as if it did
+                | Instruction::Continue(_) => self.depth + 1,
+            Instruction::Pop
+                | Instruction::Binary(_)
+                | Instruction::IterStart(_)
+                | Instruction::JumpIfFalse(_) => self.depth - 1,
+            Instruction::MakeList { count, .. } => self.depth + 1 - count,
+            Instruction::AssignIndex { count, .. } | Instruction::Index { count, .. } => {
+                self.depth - count
+            },
+            Instruction::Call { argc, .. } => self.depth - argc,
+            // `break` and `return` exit with the value on the stack, which is treated as the value
+            // of the expression
+            _ => self.depth,
+        };
+        Self::add(&mut self.chunk.code, instruction)
+    }
+
+    /// Sets the target of the jump instruction at the given index to the next instruction.
+    fn patch(&mut self, jump: usize) {
+        let next = self.chunk.code.len();
+        match &mut self.chunk.code[jump] {
+            Instruction::Jump(target)
+                | Instruction::JumpIfFalse(target)
+                | Instruction::JumpIfBound { target, .. }
+                | Instruction::IterNext { end: target }
+                | Instruction::ResolveCall { end: target, .. } => *target = next,
+            instruction => unreachable!("cannot patch {:?}", instruction),
+        }
+    }
+
+    /// Returns the target of a `break` or `continue` expression in the innermost loop, if any.
+    ///
+    /// The exit of the loop is not known until the loop has been compiled, so the instruction or
+    /// fallback expression that can exit the loop is recorded, to be patched then.
+    fn loop_target(&mut self, exit: Option<Exit>) -> Option<LoopTarget> {
+        let scope = self.loops.last_mut()?;
+        scope.exits.extend(exit);
+        Some(LoopTarget { depth: scope.depth, exit: usize::MAX, next: scope.next })
+    }
+
+    /// Adds an expression to be evaluated by the tree-walking evaluator, returning its index.
+    fn fallback(&mut self, expr: &Expr) -> usize {
+        let index = self.chunk.fallbacks.len();
+        let target = self.loop_target(Some(Exit::Fallback(index)));
+        Self::add(&mut self.chunk.fallbacks, Fallback { expr: expr.clone(), target })
+    }
+
+    /// Compiles the body of a loop that starts at the instruction `next`, with the height of the
+    /// stack `depth`, then patches the exits of the loop to jump to the instruction after it.
+    fn loop_body(&mut self, depth: usize, next: usize, body: impl FnOnce(&mut Self)) {
+        self.loops.push(LoopScope { depth, next, exits: Vec::new() });
+        body(self);
+        let scope = self.loops.pop().unwrap();
+        let exit = self.chunk.code.len();
+        for exit_from in scope.exits {
+            let target = match exit_from {
+                Exit::Instruction(index) => match &mut self.chunk.code[index] {
+                    Instruction::Break(target) => target,
+                    instruction => unreachable!("cannot exit a loop from {:?}", instruction),
+                },
+                Exit::Fallback(index) => self.chunk.fallbacks[index].target.as_mut().unwrap(),
+            };
+            target.exit = exit;
+        }
+
+        // a loop exited with `break` leaves its value on the stack
+        self.depth = depth + 1;
+    }
+
+    /// Compiles a list of statements, producing the value of the last one.
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        let Some((last, rest)) = stmts.split_last() else {
+            self.emit(Instruction::PushUnit);
+            return;
+        };
+        for stmt in rest {
+            self.expr(&stmt.expr);
+            self.emit(Instruction::Pop);
+        }
+        self.expr(&last.expr);
+        if last.semicolon.is_some() {
+            self.emit(Instruction::Pop);
+            self.emit(Instruction::PushUnit);
+        }
+    }
+
+    /// Compiles an expression, producing its value.
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(literal) => self.literal(literal),
+            Expr::Paren(paren) => self.expr(&paren.expr),
+            Expr::Block(block) => self.stmts(&block.stmts),
+            Expr::If(if_expr) => {
+                self.expr(&if_expr.condition);
+                let to_else = self.emit(Instruction::JumpIfFalse(0));
+                self.expr(&if_expr.then_expr);
+                let to_end = self.emit(Instruction::Jump(0));
+                self.patch(to_else);
+                self.depth -= 1;
+                match &if_expr.else_expr {
+                    Some(else_expr) => self.expr(else_expr),
+                    None => {
+                        self.emit(Instruction::PushUnit);
+                    },
+                }
+                self.patch(to_end);
+            },
+            Expr::Loop(loop_expr) => {
+                let (depth, next) = (self.depth, self.chunk.code.len());
+                self.loop_body(depth, next, |this| {
+                    let span = this.span(loop_expr.body.span());
+                    this.emit(Instruction::Step(span));
+                    this.expr(&loop_expr.body);
+                    this.emit(Instruction::Pop);
+                    this.emit(Instruction::Jump(next));
+                });
+            },
+            Expr::While(while_expr) => {
+                let (depth, next) = (self.depth, self.chunk.code.len());
+                self.loop_body(depth, next, |this| {
+                    this.expr(&while_expr.condition);
+                    let to_end = this.emit(Instruction::JumpIfFalse(0));
+                    let span = this.span(while_expr.body.span());
+                    this.emit(Instruction::Step(span));
+                    this.expr(&while_expr.body);
+                    this.emit(Instruction::Pop);
+                    this.emit(Instruction::Jump(next));
+                    this.patch(to_end);
+                    this.emit(Instruction::PushUnit);
+                });
+            },
+            Expr::For(for_expr) => {
+                self.expr(&for_expr.iterable);
+                let span = self.span(for_expr.iterable.span());
+                self.emit(Instruction::IterStart(span));
+
+                let (depth, next) = (self.depth, self.chunk.code.len());
+                self.loop_body(depth, next, |this| {
+                    let to_end = this.emit(Instruction::IterNext { end: 0 });
+                    this.store(&for_expr.variable, false);
+                    this.emit(Instruction::Pop);
+                    let span = this.span(for_expr.body.span());
+                    this.emit(Instruction::Step(span));
+                    this.expr(&for_expr.body);
+                    this.emit(Instruction::Pop);
+                    this.emit(Instruction::Jump(next));
+                    this.patch(to_end);
+                    this.emit(Instruction::PushUnit);
+                });
+                self.emit(Instruction::IterEnd);
+            },
+            Expr::Break(break_expr) => {
+                match &break_expr.value {
+                    Some(value) => self.expr(value),
+                    None => {
+                        self.emit(Instruction::PushUnit);
+                    },
+                }
+
+                // outside of a loop, `break` exits the function instead
+                match self.loop_target(Some(Exit::Instruction(self.chunk.code.len()))) {
+                    Some(target) => self.emit(Instruction::Break(target)),
+                    None => self.emit(Instruction::Return),
+                };
+            },
+            Expr::Continue(_) => match self.loop_target(None) {
+                Some(target) => {
+                    self.emit(Instruction::Continue(target));
+                },
+                None => {
+                    self.emit(Instruction::PushUnit);
+                    self.emit(Instruction::Return);
+                },
+            },
+            Expr::Return(return_expr) => {
+                match &return_expr.value {
+                    Some(value) => self.expr(value),
+                    None => {
+                        self.emit(Instruction::PushUnit);
+                    },
+                }
+                self.emit(Instruction::Return);
+            },
+            Expr::Call(call) => self.call(call, expr),
+            Expr::Index(index) => {
+                self.expr(&index.target);
+                let site = Self::add(&mut self.chunk.indices, shallow_index(index));
+                self.emit(Instruction::IndexTarget(site));
+                let count = self.index_exprs(index);
+                self.emit(Instruction::Index { index: site, count });
+            },
+            Expr::Unary(unary) => {
+                self.operand(&unary.operand);
+                let site = Self::add(&mut self.chunk.unaries, UnarySite {
+                    op: unary.op.clone(),
+                    operand: unary.operand.span(),
+                    span: unary.span.clone(),
+                });
+                self.emit(Instruction::Unary(site));
+            },
+            Expr::Binary(binary) => {
+                self.operand(&binary.lhs);
+                self.operand(&binary.rhs);
+                let site = Self::add(&mut self.chunk.binaries, BinarySite {
+                    op: binary.op.kind,
+                    implicit: binary.op.implicit,
+                    span: binary.span.clone(),
+                    spans: vec![binary.lhs.span(), binary.op.span.clone(), binary.rhs.span()],
+                });
+                self.emit(Instruction::Binary(site));
+            },
+            Expr::Assign(assign) => self.assign(assign, expr),
+            Expr::Lambda(lambda) => {
+                let site = Self::add(&mut self.chunk.lambdas, lambda.clone());
+                self.emit(Instruction::MakeLambda(site));
+            },
+        }
+    }
+
+    /// Compiles a load of the value of a symbol. If `function` is true, a symbol that names a
+    /// function but not a variable loads the function.
+    fn load(&mut self, symbol: &LitSym, function: bool) {
+        let index = self.symbol(symbol);
+        match self.slot(&symbol.name) {
+            Some(slot) => self.emit(Instruction::LoadLocal { slot, symbol: index, function }),
+            None => self.emit(Instruction::LoadName { symbol: index, function }),
+        };
+    }
+
+    /// Compiles the operand of an operator. Functions cannot be operands, so a symbol operand does
+    /// not load the function it names, and is undefined if it does not name a variable.
+    fn operand(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(Literal::Symbol(symbol)) => self.load(symbol, false),
+            expr => self.expr(expr),
+        }
+    }
+
+    /// Compiles a literal.
+    fn literal(&mut self, literal: &Literal) {
+        let value = match literal {
+            Literal::Integer(int) => Value::Integer(int_from_str(&int.value)),
+            Literal::Float(float) => {
+                let float = Self::add(&mut self.chunk.floats, float.value.clone());
+                self.emit(Instruction::Float(float));
+                return;
+            },
+            Literal::Radix(radix) => Value::Integer(from_str_radix(radix.value.as_str(), radix.base)),
+            Literal::Boolean(boolean) => Value::Boolean(boolean.value),
+            Literal::Symbol(symbol) => {
+                self.load(symbol, true);
+                return;
+            },
+            Literal::Unit(_) => {
+                self.emit(Instruction::PushUnit);
+                return;
+            },
+            Literal::List(list) => {
+                for value in &list.values {
+                    self.expr(value);
+                }
+                let span = self.span(list.span.clone());
+                self.emit(Instruction::MakeList { count: list.values.len(), span });
+                return;
+            },
+        };
+        let index = Self::add(&mut self.chunk.consts, value);
+        self.emit(Instruction::Const(index));
+    }
+
+    /// Compiles the definition of a variable with the value on top of the stack. `assign` is true
+    /// if the value is assigned to the variable with an assignment expression.
+    fn store(&mut self, symbol: &LitSym, assign: bool) {
+        match self.slot(&symbol.name) {
+            Some(slot) => self.emit(Instruction::StoreLocal { slot, assign }),
+            None => {
+                let symbol = self.symbol(symbol);
+                self.emit(Instruction::DefineName { symbol, assign })
+            },
+        };
+    }
+
+    /// Compiles the index expressions of an index expression, returning how many there are.
+    fn index_exprs(&mut self, index: &Index) -> usize {
+        match &index.index {
+            IndexKind::Single(expr) => {
+                self.expr(expr);
+                1
+            },
+            IndexKind::Slice { start, end } => {
+                let mut count = 0;
+                for expr in [start, end].into_iter().flatten() {
+                    self.expr(expr);
+                    count += 1;
+                }
+                count
+            },
+        }
+    }
+
+    /// Compiles an assignment expression.
+    fn assign(&mut self, assign: &Assign, expr: &Expr) {
+        let shallow = |target| Assign {
+            target,
+            op: assign.op.clone(),
+            value: Box::new(placeholder(&assign.value)),
+            span: assign.span.clone(),
+        };
+        match &assign.target {
+            AssignTarget::Symbol(symbol) => {
+                self.expr(&assign.value);
+                if assign.op.kind == AssignOpKind::Assign {
+                    self.store(symbol, true);
+                    return;
+                }
+
+                let site = Self::add(&mut self.chunk.assigns, AssignSite {
+                    assign: shallow(assign.target.clone()),
+                    symbol: symbol.clone(),
+                    chain: Vec::new(),
+                });
+                let slot = self.slot(&symbol.name);
+                self.emit(Instruction::Compound { assign: site, slot });
+            },
+            AssignTarget::Index(index) => {
+                self.expr(&assign.value);
+                let (symbol, chain) = index_chain(index);
+                let count = chain.iter().map(|index| self.index_exprs(index)).sum();
+
+                let site = Self::add(&mut self.chunk.assigns, AssignSite {
+                    assign: shallow(AssignTarget::Index(shallow_index(index))),
+                    symbol: symbol.clone(),
+                    chain: chain.into_iter().map(shallow_index).collect(),
+                });
+                let slot = self.slot(&symbol.name);
+                self.emit(Instruction::AssignIndex { assign: site, slot, count });
+            },
+            AssignTarget::Func(_) => {
+                let fallback = self.fallback(expr);
+                self.emit(Instruction::Eval(fallback));
+            },
+        }
+    }
+
+    /// Compiles a function call.
+    ///
+    /// Calls with prime notation or named arguments are evaluated by the tree-walking evaluator, as
+    /// are calls to builtin functions that must be given the unevaluated arguments of the call.
+    /// Since that is only known once the function is looked up, the call is compiled both ways.
+    fn call(&mut self, call: &Call, expr: &Expr) {
+        let fallback = self.fallback(expr);
+        if call.derivatives > 0 || !call.named_args.is_empty() {
+            self.emit(Instruction::Eval(fallback));
+            return;
+        }
+
+        let mut compiled_args = true;
+        for arg in &call.args {
+            visit(arg, &mut |expr| compiled_args &= !matches!(
+                expr,
+                Expr::Assign(_) | Expr::Break(_) | Expr::Continue(_) | Expr::Return(_)
+            ));
+        }
+        let slot = self.slot(&call.name.name);
+        let site = Self::add(&mut self.chunk.calls, CallSite {
+            call: Call {
+                name: call.name.clone(),
+                derivatives: call.derivatives,
+                args: call.args.iter().map(placeholder).collect(),
+                named_args: Vec::new(),
+                span: call.span.clone(),
+                paren_span: call.paren_span.clone(),
+            },
+            slot,
+            compiled_args,
+        });
+
+        let resolve = self.emit(Instruction::ResolveCall { call: site, fallback, end: 0 });
+        for arg in &call.args {
+            self.expr(arg);
+        }
+        self.emit(Instruction::Call { call: site, argc: call.args.len() });
+        self.patch(resolve);
+    }
+}
@@ -0,0 +1,147 @@
+//! The instructions of compiled CalcScript.
+//!
+//! Instructions operate on a stack of values. Each instruction pops its operands from the stack,
+//! and pushes its result, if any. Instructions that need more information than fits in an
+//! instruction, such as the spans used to report errors, refer to the tables of the [`Chunk`] they
+//! are part of by index.
+//!
+//! [`Chunk`]: super::compile::Chunk
+
+/// The location of a loop that a `break` or `continue` expression exits from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopTarget {
+    /// The height of the stack when the loop was entered.
+    pub depth: usize,
+
+    /// The instruction that a `break` expression jumps to, with its value on the stack.
+    pub exit: usize,
+
+    /// The instruction that a `continue` expression jumps to, which starts the next iteration.
+    pub next: usize,
+}
+
+/// A single instruction of compiled CalcScript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// Pushes the constant with the given index.
+    Const(usize),
+
+    /// Parses the float literal with the given index and pushes it. Float literals are parsed when
+    /// they are evaluated, since the result depends on the precision and number mode of the
+    /// context.
+    Float(usize),
+
+    /// Pushes the unit value.
+    PushUnit,
+
+    /// Pops a value and discards it.
+    Pop,
+
+    /// Pushes the value of the local variable in the given slot. If the slot is empty, the symbol
+    /// with the given index is looked up by name instead.
+    ///
+    /// If `function` is true and there is no variable with the name, the function with the name is
+    /// pushed instead. It is false for the operands of operators, which cannot be functions.
+    LoadLocal { slot: usize, symbol: usize, function: bool },
+
+    /// Pushes the value of the symbol with the given index, looked up by name.
+    ///
+    /// If `function` is true and there is no variable with the name, the function with the name is
+    /// pushed instead.
+    LoadName { symbol: usize, function: bool },
+
+    /// Stores the value on top of the stack in the given slot, without popping it.
+    ///
+    /// If `assign` is true, the value is being assigned to the variable, so a function without a
+    /// name takes the name of the variable.
+    StoreLocal { slot: usize, assign: bool },
+
+    /// Defines the variable named by the symbol with the given index in the current scope, with the
+    /// value on top of the stack, without popping it.
+    ///
+    /// If `assign` is true, the value is being assigned to the variable, so a function without a
+    /// name takes the name of the variable.
+    DefineName { symbol: usize, assign: bool },
+
+    /// Pops the right-hand side of the compound assignment with the given index, applies it to the
+    /// variable it assigns to, and pushes the new value of the variable. If `slot` is given, the
+    /// variable is a local variable that may be stored in that slot.
+    Compound { assign: usize, slot: Option<usize> },
+
+    /// Pops the index expressions of the assignment to a list element or slice with the given index,
+    /// then the right-hand side, assigns it, and pushes the value of the assignment.
+    AssignIndex { assign: usize, slot: Option<usize>, count: usize },
+
+    /// Pops the operand of the unary expression with the given index, and pushes the result.
+    Unary(usize),
+
+    /// Pops both operands of the binary expression with the given index, and pushes the result.
+    Binary(usize),
+
+    /// Pops the given number of values, and pushes a list containing them. The span with the given
+    /// index is the span of the list literal.
+    MakeList { count: usize, span: usize },
+
+    /// Checks that the value on top of the stack can be indexed by the index expression with the
+    /// given index, converting a matrix to the list of its rows.
+    IndexTarget(usize),
+
+    /// Pops the given number of values of the index expressions of the index expression with the
+    /// given index, then the list, and pushes the element or slice they refer to.
+    Index { index: usize, count: usize },
+
+    /// Pushes a lambda created by the lambda expression with the given index, capturing the
+    /// current local scope.
+    MakeLambda(usize),
+
+    /// Looks up the function called by the call with the given index, and pushes it.
+    ///
+    /// If the function must be given the unevaluated arguments of the call, the call is evaluated
+    /// as the fallback expression with index `fallback` instead, and evaluation continues at `end`.
+    ResolveCall { call: usize, fallback: usize, end: usize },
+
+    /// Pops the given number of arguments, then the function pushed by
+    /// [`Instruction::ResolveCall`], calls the function and pushes its result.
+    Call { call: usize, argc: usize },
+
+    /// Jumps to `target` if the parameter with the given index was given an argument, skipping the
+    /// instructions that evaluate its default value.
+    JumpIfBound { param: usize, target: usize },
+
+    /// Returns an error for the parameter with the given index, which was not given an argument.
+    MissingArgument(usize),
+
+    /// Pops a value, and starts iterating over it in a `for` loop. The span with the given index is
+    /// the span of the iterable, which is used if the value cannot be iterated over.
+    IterStart(usize),
+
+    /// Pushes the next value of the innermost `for` loop, or jumps to `end` if there are none.
+    IterNext { end: usize },
+
+    /// Ends the innermost `for` loop.
+    IterEnd,
+
+    /// Takes a step of the evaluation. The span with the given index is the span of the
+    /// expression that is about to be evaluated.
+    Step(usize),
+
+    /// Jumps to the given instruction.
+    Jump(usize),
+
+    /// Pops a value, and jumps to the given instruction if it is falsy.
+    JumpIfFalse(usize),
+
+    /// Pops the value of a `break` expression, removes the values pushed since the loop was
+    /// entered, pushes the value again, and exits the loop.
+    Break(LoopTarget),
+
+    /// Removes the values pushed since the loop was entered, and starts its next iteration.
+    Continue(LoopTarget),
+
+    /// Pops a value, and returns it from the function being called.
+    Return,
+
+    /// Evaluates the fallback expression with the given index with the tree-walking evaluator, and
+    /// pushes its value.
+    Eval(usize),
+}
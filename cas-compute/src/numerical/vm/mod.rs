@@ -0,0 +1,1010 @@
+//! A bytecode compiler and virtual machine for CalcScript.
+//!
+//! [`Program::compile`] lowers a list of statements to a compact list of instructions for a stack
+//! machine. The local variables of each user-defined function and lambda are resolved to numbered
+//! slots, so that they can be accessed without being looked up by name, and no scope is allocated
+//! for each call. [`Program::run`] then runs the instructions with a [`Ctxt`]. The body of each
+//! function is compiled the first time it is called during a run, and reused for later calls.
+//!
+//! Compiled code produces the same values and errors, with the same spans, as the tree-walking
+//! evaluator in [`eval`](super::eval), which remains the reference implementation of CalcScript.
+//! Function definitions, calls with prime notation, and calls to builtin functions that must be
+//! given the unevaluated arguments of the call, such as `sum`, are evaluated by the tree-walking
+//! evaluator from compiled code.
+//!
+//! ```
+//! use cas_compute::numerical::{ctxt::Ctxt, vm::Program};
+//! use cas_parser::parser::Parser;
+//!
+//! let source = "fib(n) = if n < 2 then n else fib(n - 1) + fib(n - 2); fib(20)";
+//! let stmts = Parser::new(source).try_parse_full_many().unwrap();
+//! let program = Program::compile(&stmts);
+//! assert_eq!(program.run(&mut Ctxt::default()).unwrap(), 6765.into());
+//! ```
+
+mod compile;
+mod instruction;
+
+use cas_parser::parser::ast::{assign::Param, call::Call, literal::LitSym, stmt::Stmt};
+use crate::interval::Interval;
+use crate::numerical::{
+    ctxt::{constant, interval_constant, Ctxt, Func, Function, Lambda, NumberMode, Scope},
+    error::{
+        kind::{MissingArgument, NotIterable, TooManyArguments, UndefinedFunction, UndefinedVariable},
+        Error,
+    },
+    eval::{
+        apply_builtin,
+        assign_to_element,
+        check_operands,
+        eval_operand,
+        eval_operands_in,
+        invalid_target,
+        enter_call,
+        leave_call,
+        position_with,
+        Eval,
+        Position,
+    },
+    memo::Memoized,
+    range,
+    value::Value,
+};
+use crate::primitive::{float_from_str, set_precision, with_precision};
+use cas_parser::parser::token::op::AssignOpKind;
+use compile::{compile_function, compile_stmts, Chunk};
+use instruction::{Instruction, LoopTarget};
+use std::{collections::HashMap, fmt::Display, rc::Rc, sync::Arc};
+
+/// A compiled list of statements, which can be run with a [`Ctxt`].
+#[derive(Debug)]
+pub struct Program {
+    /// The compiled statements.
+    chunk: Rc<Chunk>,
+}
+
+impl Program {
+    /// Compiles a list of statements.
+    pub fn compile(stmts: &[Stmt]) -> Self {
+        Self { chunk: Rc::new(compile_stmts(stmts)) }
+    }
+
+    /// Runs the program with the given context, returning the value of the last statement.
+    ///
+    /// Like [`eval_stmts`](super::eval::eval_stmts), the program is run as a single evaluation,
+    /// which is subject to the [`Budget`](super::budget::Budget) of the context.
+    pub fn run(&self, ctxt: &mut Ctxt) -> Result<Value, Error> {
+        ctxt.metered(|ctxt| with_precision(ctxt.precision(), || {
+            Vm::default().run(ctxt, Rc::clone(&self.chunk))
+        }))
+    }
+}
+
+/// Compiles and runs a list of statements, returning the value of the last one. This is the
+/// compiled equivalent of [`eval_stmts`](super::eval::eval_stmts).
+pub fn run_stmts(stmts: &[Stmt], ctxt: &mut Ctxt) -> Result<Value, Error> {
+    Program::compile(stmts).run(ctxt)
+}
+
+/// Returns the parameters of a user-defined function or lambda, and its signature, which is used
+/// in errors about its arguments.
+fn params_of(function: &Function) -> (&[Param], &dyn Display) {
+    match &function.func {
+        Func::Builtin(_) => unreachable!("builtin functions are not compiled"),
+        Func::UserFunc(user_func) => (&user_func.header.params, &user_func.header),
+        Func::Lambda(lambda) => (&lambda.params, &**lambda),
+    }
+}
+
+/// Gives a function without a name the given name, as it is assigned to a variable with that name.
+fn name_function(value: &mut Value, name: &str) {
+    if let Value::Function(function @ Function { name: None, .. }) = value {
+        function.name = Some(name.to_string());
+    }
+}
+
+/// Returns the value a symbol evaluates to, given the value of the variable it refers to.
+///
+/// In [`NumberMode::Interval`], a builtin constant that has not been redefined evaluates to an
+/// interval enclosing its exact value.
+fn symbol_value(ctxt: &Ctxt, name: &str, value: Value) -> Value {
+    if ctxt.number_mode == NumberMode::Interval && constant(name).as_ref() == Some(&value) {
+        interval_constant(name).map(Value::Interval).unwrap_or(value)
+    } else {
+        value
+    }
+}
+
+/// The values of a `for` loop that have not been iterated over yet.
+enum Iter {
+    /// The integers of a range.
+    Range(range::Iter),
+
+    /// The values of a list.
+    List(std::vec::IntoIter<Value>),
+}
+
+impl Iterator for Iter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        match self {
+            Iter::Range(iter) => iter.next().map(Value::Integer),
+            Iter::List(iter) => iter.next(),
+        }
+    }
+}
+
+/// The local variables of a call to a user-defined function or lambda.
+struct Locals {
+    /// The values of the local variables stored in slots, or [`None`] if they have not been
+    /// defined yet.
+    slots: Vec<Option<Value>>,
+
+    /// Local variables that are not stored in slots. These can only be defined by expressions
+    /// evaluated by the tree-walking evaluator.
+    dynamic: HashMap<String, Value>,
+
+    /// The scope captured by the lambda being called, if any.
+    captures: Option<Arc<Scope>>,
+
+    /// The function being called. A named lambda is bound to its own name, so that it can call
+    /// itself.
+    function: Function,
+
+    /// The chunk containing the call to the function.
+    caller: Rc<Chunk>,
+
+    /// The index of the call to the function in the chunk of the caller.
+    call: usize,
+
+    /// Whether the depth of the stack was increased by the call.
+    recursive: bool,
+
+    /// The call, if its value should be remembered once it returns.
+    memoized: Option<Memoized>,
+
+    /// The number of arguments given to the function.
+    argc: usize,
+}
+
+impl Locals {
+    /// Returns the call to the function.
+    fn call(&self) -> &Call {
+        &self.caller.calls[self.call].call
+    }
+
+    /// Returns the function being called if it is a lambda with the given name.
+    fn this(&self, name: &str) -> Option<&Function> {
+        match &self.function.func {
+            Func::Lambda(_) if self.function.name.as_deref() == Some(name) => Some(&self.function),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a variable that is not stored in a slot, looking in the scope of the
+    /// call, then the scope captured by the lambda being called.
+    fn lookup(&self, name: &str) -> Option<Value> {
+        self.dynamic.get(name).cloned()
+            .or_else(|| self.this(name).cloned().map(Value::Function))
+            .or_else(|| self.captures.as_ref()?.get_var(name).cloned())
+    }
+
+    /// Returns true if [`Locals::lookup`] would find a variable with the given name.
+    fn has(&self, name: &str) -> bool {
+        self.dynamic.contains_key(name)
+            || self.this(name).is_some()
+            || self.captures.as_ref().is_some_and(|scope| scope.get_var(name).is_some())
+    }
+
+    /// Defines a local variable, storing it in the given slot if it has one.
+    fn define(&mut self, slot: Option<usize>, name: &str, value: Value) {
+        match slot {
+            Some(slot) => self.slots[slot] = Some(value),
+            None => {
+                self.dynamic.insert(name.to_string(), value);
+            },
+        }
+    }
+
+    /// Returns a [`Scope`] containing the local variables, for the tree-walking evaluator to use,
+    /// or a lambda to capture.
+    fn scope(&self, chunk: &Chunk) -> Scope {
+        let mut scope = Scope::new(self.captures.clone());
+        if let Some(name) = &self.function.name {
+            if let Some(function) = self.this(name) {
+                scope.add_var(name, Value::Function(function.clone()));
+            }
+        }
+        for (name, value) in &self.dynamic {
+            scope.add_var(name, value.clone());
+        }
+        for (name, value) in chunk.locals.iter().zip(&self.slots) {
+            if let Some(value) = value {
+                scope.add_var(name, value.clone());
+            }
+        }
+        scope
+    }
+
+    /// Replaces the local variables with those of a [`Scope`] returned by [`Locals::scope`], after
+    /// the tree-walking evaluator has used it.
+    fn restore(&mut self, chunk: &Chunk, scope: Arc<Scope>) {
+        for (name, value) in Scope::into_vars(scope) {
+            let slot = chunk.slots.get(&name).copied();
+            self.define(slot, &name, value);
+        }
+    }
+}
+
+/// The state of a chunk being run.
+struct Frame {
+    /// The height of the stack when the chunk started running.
+    base: usize,
+
+    /// The `for` loops being run, from outermost to innermost.
+    iters: Vec<Iter>,
+
+    /// The local variables of the function being called, or [`None`] if the chunk is a list of
+    /// statements, whose variables are stored in the context.
+    locals: Option<Locals>,
+}
+
+impl Frame {
+    /// Creates a frame for a chunk that starts running with the stack at the given height.
+    fn new(base: usize, locals: Option<Locals>) -> Self {
+        Self { base, iters: Vec::new(), locals }
+    }
+
+    /// Returns the locals of the function being called.
+    fn locals(&mut self) -> &mut Locals {
+        self.locals.as_mut().expect("only functions have local variables")
+    }
+
+    /// Returns the value of a variable, as [`Ctxt::get_var`] would in the tree-walking evaluator.
+    /// `slot` is the slot of the variable, if it is stored in one.
+    fn get_var(&self, ctxt: &Ctxt, slot: Option<usize>, name: &str) -> Option<Value> {
+        match &self.locals {
+            None => ctxt.get_var(name),
+            Some(locals) => slot.and_then(|slot| locals.slots[slot].clone())
+                .or_else(|| locals.lookup(name))
+                .or_else(|| ctxt.get_global(name).cloned()),
+        }
+    }
+
+    /// Assigns a new value to a variable, as [`Ctxt::set_var`] would in the tree-walking
+    /// evaluator. `slot` is the slot of the variable, if it is stored in one.
+    fn set_var(&mut self, ctxt: &mut Ctxt, slot: Option<usize>, name: &str, value: Value) {
+        match &mut self.locals {
+            None => ctxt.set_var(name, value),
+            Some(locals) => {
+                let is_local = slot.is_some_and(|slot| locals.slots[slot].is_some())
+                    || locals.has(name)
+                    || ctxt.get_global(name).is_none();
+                if is_local {
+                    locals.define(slot, name, value);
+                } else {
+                    ctxt.set_global(name, value);
+                }
+            },
+        }
+    }
+
+    /// Returns the value of a symbol, which can also name a function if `function` is true.
+    fn load(
+        &self,
+        ctxt: &Ctxt,
+        slot: Option<usize>,
+        symbol: &LitSym,
+        function: bool,
+    ) -> Result<Value, Error> {
+        let value = self.get_var(ctxt, slot, &symbol.name)
+            .map(|value| symbol_value(ctxt, &symbol.name, value))
+            .or_else(|| ctxt.get_func(&symbol.name).filter(|_| function).map(|func| {
+                Value::Function(Function { name: Some(symbol.name.clone()), func: func.clone() })
+            }));
+        value.ok_or_else(|| Error::new(
+            vec![symbol.span.clone()],
+            UndefinedVariable { name: symbol.name.clone() },
+        ))
+    }
+
+    /// Returns the function with the given name, as [`Ctxt::get_function`] would in the
+    /// tree-walking evaluator.
+    fn get_function(&self, ctxt: &Ctxt, slot: Option<usize>, name: &str) -> Option<Function> {
+        if self.locals.is_none() {
+            return ctxt.get_function(name);
+        }
+        match self.get_var(ctxt, slot, name) {
+            Some(Value::Function(function)) => Some(function),
+            _ => ctxt.get_func(name).map(|func| Function {
+                name: Some(name.to_string()),
+                func: func.clone(),
+            }),
+        }
+    }
+
+    /// Returns the scope for a lambda created in this frame to capture.
+    fn capture(&self, ctxt: &Ctxt, chunk: &Chunk) -> Option<Arc<Scope>> {
+        match &self.locals {
+            None => ctxt.capture_scope(),
+            Some(locals) => Some(Arc::new(locals.scope(chunk))),
+        }
+    }
+}
+
+/// How a chunk continues running after an instruction that can exit a loop or function.
+enum Resume {
+    /// Push the value and continue with the next instruction.
+    Next(Value),
+
+    /// Exit the loop with the value.
+    Break(LoopTarget, Value),
+
+    /// Start the next iteration of the loop.
+    Continue(LoopTarget),
+
+    /// Return the value from the chunk.
+    Return(Value),
+}
+
+/// A chunk being run, with its frame.
+struct Activation {
+    /// The chunk being run.
+    chunk: Rc<Chunk>,
+
+    /// The index of the next instruction to run.
+    pc: usize,
+
+    /// The state of the chunk.
+    frame: Frame,
+}
+
+impl Activation {
+    /// Creates an activation that runs a chunk from its first instruction.
+    fn new(chunk: Rc<Chunk>, frame: Frame) -> Self {
+        Self { chunk, pc: 0, frame }
+    }
+}
+
+/// Why a chunk stopped running.
+enum Control {
+    /// The chunk called a user-defined function or lambda at the call with the given index, with
+    /// arguments that have already been evaluated. The chunk continues running once the function
+    /// returns, with its value pushed.
+    Call { call: usize, function: Function, args: Vec<Value> },
+
+    /// The chunk returned the value.
+    Return(Value),
+}
+
+/// The virtual machine that runs compiled code.
+///
+/// Calls to user-defined functions and lambdas do not recurse on the native stack. Instead, the
+/// virtual machine keeps the activations of the callers, so that deep recursion in CalcScript is
+/// reported as a [`StackOverflow`](crate::numerical::error::kind::StackOverflow) error.
+#[derive(Default)]
+struct Vm {
+    /// The stack of values, shared by all chunks being run.
+    stack: Vec<Value>,
+
+    /// The compiled bodies of the functions called so far, keyed by the address of their
+    /// implementation. The implementation is kept with its body, so that the address is not
+    /// reused by another function during the run.
+    chunks: HashMap<usize, (Func, Rc<Chunk>)>,
+}
+
+impl Vm {
+    /// Returns the compiled body of a user-defined function or lambda, compiling it if needed.
+    fn chunk(&mut self, func: &Func) -> Rc<Chunk> {
+        let (key, params, body) = match func {
+            Func::Builtin(_) => unreachable!("builtin functions are not compiled"),
+            Func::UserFunc(user_func) => {
+                (Arc::as_ptr(user_func) as usize, &user_func.header.params, &user_func.body)
+            },
+            Func::Lambda(lambda) => (Arc::as_ptr(lambda) as usize, &lambda.params, &lambda.body),
+        };
+        let (_, chunk) = self.chunks.entry(key)
+            .or_insert_with(|| (func.clone(), Rc::new(compile_function(params, body))));
+        Rc::clone(chunk)
+    }
+
+    /// Pops the given number of values from the stack.
+    fn pop_many(&mut self, count: usize) -> Vec<Value> {
+        self.stack.split_off(self.stack.len() - count)
+    }
+
+    /// Pops a value from the stack.
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("the stack is not empty")
+    }
+
+    /// Enters a call to a user-defined function or lambda at the call with the given index in the
+    /// chunk `caller`, with arguments that have already been evaluated, returning the activation
+    /// that runs its compiled body. `memoized` is the call, if its value should be remembered.
+    fn enter(
+        &mut self,
+        ctxt: &mut Ctxt,
+        caller: &Rc<Chunk>,
+        index: usize,
+        function: Function,
+        args: Vec<Value>,
+        memoized: Option<Memoized>,
+    ) -> Result<Activation, Error> {
+        let call = &caller.calls[index].call;
+        let (params, signature) = params_of(&function);
+        if args.len() > params.len() {
+            return Err(Error::new(call.outer_span().to_vec(), TooManyArguments {
+                name: call.name.name.clone(),
+                expected: params.len(),
+                given: args.len(),
+                signature: signature.to_string(),
+            }));
+        }
+
+        let (captures, recursive) = match &function.func {
+            Func::UserFunc(user_func) => (None, user_func.recursive),
+            // a lambda can refer to itself in too many ways to tell whether it is recursive
+            Func::Lambda(lambda) => (lambda.captures.clone(), true),
+            Func::Builtin(_) => unreachable!("builtin functions are not compiled"),
+        };
+        enter_call(ctxt, recursive)
+            .or_else(|err| leave_call(ctxt, call, recursive, Err(err)))?;
+
+        let chunk = self.chunk(&function.func);
+        let argc = args.len();
+        let mut slots = vec![None; chunk.locals.len()];
+        for (&slot, arg) in chunk.params.iter().zip(args) {
+            slots[slot] = Some(arg);
+        }
+        let locals = Locals {
+            slots,
+            dynamic: HashMap::new(),
+            captures,
+            function,
+            caller: Rc::clone(caller),
+            call: index,
+            recursive,
+            memoized,
+            argc,
+        };
+        Ok(Activation::new(chunk, Frame::new(self.stack.len(), Some(locals))))
+    }
+
+    /// Runs a chunk of statements, returning its value.
+    fn run(&mut self, ctxt: &mut Ctxt, chunk: Rc<Chunk>) -> Result<Value, Error> {
+        let mut current = Activation::new(chunk, Frame::new(0, None));
+        let mut callers = Vec::new();
+        loop {
+            let mut result = match self.execute(ctxt, &mut current) {
+                Ok(Control::Call { call, function, args }) => {
+                    let memoized = Memoized::new(ctxt, &function.func, args.iter().map(Some));
+                    if let Some(value) = memoized.as_ref().and_then(Memoized::get) {
+                        let call = &current.chunk.calls[call].call;
+                        match ctxt.budget.check_value(&value, || call.span()) {
+                            Ok(()) => {
+                                self.stack.push(value);
+                                continue;
+                            },
+                            Err(err) => Err(err),
+                        }
+                    } else {
+                        match self.enter(ctxt, &current.chunk, call, function, args, memoized) {
+                            Ok(callee) => {
+                                callers.push(std::mem::replace(&mut current, callee));
+                                continue;
+                            },
+                            Err(err) => Err(err),
+                        }
+                    }
+                },
+                Ok(Control::Return(value)) => Ok(value),
+                Err(err) => Err(err),
+            };
+
+            // return to the callers until one can continue running, propagating errors
+            loop {
+                let Some(caller) = callers.pop() else {
+                    return result;
+                };
+                let callee = std::mem::replace(&mut current, caller);
+                let mut locals = callee.frame.locals.expect("called chunks are functions");
+                let memoized = locals.memoized.take();
+                let call = locals.call();
+                result = leave_call(ctxt, call, locals.recursive, result).and_then(|value| {
+                    if let Some(memoized) = memoized {
+                        memoized.insert(&value);
+                    }
+                    ctxt.budget.check_value(&value, || call.span())?;
+                    Ok(value)
+                });
+                if let Ok(value) = result {
+                    self.stack.push(value);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Evaluates a fallback expression of the chunk with the tree-walking evaluator.
+    ///
+    /// In a function, the tree-walking evaluator is given a scope containing the local variables,
+    /// which are updated with any changes to it afterwards.
+    fn fallback(
+        &mut self,
+        ctxt: &mut Ctxt,
+        chunk: &Chunk,
+        frame: &mut Frame,
+        index: usize,
+    ) -> Result<Resume, Error> {
+        let fallback = &chunk.fallbacks[index];
+        let value = match &mut frame.locals {
+            None => fallback.expr.eval(ctxt),
+            Some(locals) => {
+                let outer = ctxt.replace_scope(Some(Arc::new(locals.scope(chunk))));
+                let value = fallback.expr.eval(ctxt);
+                let scope = ctxt.replace_scope(outer).expect("the scope is restored");
+                locals.restore(chunk, scope);
+                value
+            },
+        };
+
+        // builtins such as `precision` can change the precision of the context
+        set_precision(ctxt.precision());
+        let value = value?;
+
+        if !ctxt.break_loop {
+            return Ok(Resume::Next(value));
+        }
+        let (returning, continuing) = (ctxt.return_call, ctxt.continue_loop);
+        ctxt.break_loop = false;
+        ctxt.return_call = false;
+        ctxt.continue_loop = false;
+        Ok(match fallback.target {
+            Some(target) if !returning && continuing => Resume::Continue(target),
+            Some(target) if !returning => Resume::Break(target, value),
+            _ if continuing => Resume::Return(Value::Unit),
+            _ => Resume::Return(value),
+        })
+    }
+
+    /// Continues running a chunk as described by `resume`, returning the value of the chunk if it
+    /// should return.
+    fn resume(&mut self, frame: &Frame, resume: Resume, pc: &mut usize) -> Option<Value> {
+        match resume {
+            Resume::Next(value) => self.stack.push(value),
+            Resume::Break(target, value) => {
+                self.stack.truncate(frame.base + target.depth);
+                self.stack.push(value);
+                *pc = target.exit;
+            },
+            Resume::Continue(target) => {
+                self.stack.truncate(frame.base + target.depth);
+                *pc = target.next;
+            },
+            Resume::Return(value) => {
+                self.stack.truncate(frame.base);
+                return Some(value);
+            },
+        }
+        None
+    }
+
+    /// Runs an activation until it returns or calls a user-defined function or lambda.
+    fn execute(&mut self, ctxt: &mut Ctxt, activation: &mut Activation) -> Result<Control, Error> {
+        let Activation { chunk, pc, frame } = activation;
+        let chunk: &Chunk = chunk;
+        loop {
+            let instruction = chunk.code[*pc];
+            *pc += 1;
+            match instruction {
+                Instruction::Const(index) => self.stack.push(chunk.consts[index].clone()),
+                Instruction::Float(index) => {
+                    let float = &chunk.floats[index];
+                    self.stack.push(match ctxt.number_mode {
+                        NumberMode::Standard => Value::Float(float_from_str(float)),
+                        NumberMode::Interval => Value::Interval(Interval::parse(float).unwrap()),
+                    });
+                },
+                Instruction::PushUnit => self.stack.push(Value::Unit),
+                Instruction::Pop => {
+                    self.pop();
+                },
+                Instruction::LoadLocal { slot, symbol, function } => {
+                    let value = frame.load(ctxt, Some(slot), &chunk.symbols[symbol], function)?;
+                    self.stack.push(value);
+                },
+                Instruction::LoadName { symbol, function } => {
+                    let value = frame.load(ctxt, None, &chunk.symbols[symbol], function)?;
+                    self.stack.push(value);
+                },
+                Instruction::StoreLocal { slot, assign } => {
+                    let value = self.stack.last_mut().unwrap();
+                    if assign {
+                        name_function(value, &chunk.locals[slot]);
+                    }
+                    frame.locals().slots[slot] = Some(value.clone());
+                },
+                Instruction::DefineName { symbol, assign } => {
+                    let name = &chunk.symbols[symbol].name;
+                    let value = self.stack.last_mut().unwrap();
+                    if assign {
+                        name_function(value, name);
+                    }
+                    match &mut frame.locals {
+                        None => ctxt.add_var(name, value.clone()),
+                        Some(locals) => locals.define(None, name, value.clone()),
+                    }
+                },
+                Instruction::Compound { assign, slot } => {
+                    let site = &chunk.assigns[assign];
+                    let rhs = self.pop();
+                    let lhs = frame.get_var(ctxt, slot, &site.symbol.name)
+                        .ok_or_else(|| Error::new(
+                            vec![site.symbol.span.clone()],
+                            UndefinedVariable { name: site.symbol.name.clone() },
+                        ))?;
+                    let op = site.assign.op.kind.into();
+                    check_operands(&ctxt.budget, op, &lhs, &rhs, || site.assign.span.clone())?;
+                    let value = eval_operands_in(ctxt.number_mode, op, false, lhs, rhs)
+                        .map_err(|e| e.into_error(&site.assign))?;
+                    ctxt.budget.check_value(&value, || site.assign.span.clone())?;
+                    frame.set_var(ctxt, slot, &site.symbol.name, value.clone());
+                    self.stack.push(value);
+                },
+                Instruction::AssignIndex { assign, slot, count } => {
+                    let site = &chunk.assigns[assign];
+                    let mut indices = self.pop_many(count).into_iter();
+                    let rhs = self.pop();
+                    let op = site.assign.op.kind;
+                    let name = &site.symbol.name;
+
+                    // a plain assignment only modifies the list once it cannot fail, so a list
+                    // stored in a slot is modified in place, instead of a copy of it
+                    let taken = match (&mut frame.locals, slot) {
+                        (Some(locals), Some(slot)) if op == AssignOpKind::Assign => {
+                            locals.slots[slot].take()
+                        },
+                        _ => None,
+                    };
+                    let is_taken = taken.is_some();
+                    let mut root = taken.or_else(|| frame.get_var(ctxt, slot, name))
+                        .ok_or_else(|| Error::new(
+                            vec![site.symbol.span.clone()],
+                            UndefinedVariable { name: name.clone() },
+                        ))?;
+
+                    let chain = site.chain.iter().collect::<Vec<_>>();
+                    let result = assign_to_element(
+                        &mut root,
+                        &chain,
+                        &site.assign,
+                        op,
+                        rhs,
+                        &ctxt.budget,
+                        ctxt.number_mode,
+                        |_| Ok(indices.next().expect("the index was compiled")),
+                    );
+                    let value = match result {
+                        Ok(value) => value,
+                        Err(err) => {
+                            if is_taken {
+                                frame.locals().slots[slot.unwrap()] = Some(root);
+                            }
+                            return Err(err);
+                        },
+                    };
+                    ctxt.budget.check_value(&root, || site.assign.span.clone())?;
+                    frame.set_var(ctxt, slot, name, root);
+                    self.stack.push(value);
+                },
+                Instruction::Unary(index) => {
+                    let site = &chunk.unaries[index];
+                    let operand = self.pop();
+                    let value = eval_operand(&site.op, &site.operand, operand)?;
+                    ctxt.budget.check_value(&value, || site.span.clone())?;
+                    self.stack.push(value);
+                },
+                Instruction::Binary(index) => {
+                    let site = &chunk.binaries[index];
+                    let right = self.pop();
+                    let left = self.pop();
+                    check_operands(&ctxt.budget, site.op, &left, &right, || site.span.clone())?;
+                    let value = eval_operands_in(ctxt.number_mode, site.op, site.implicit, left, right)
+                        .map_err(|e| e.into_error_at(site.spans.clone()))?;
+                    ctxt.budget.check_value(&value, || site.span.clone())?;
+                    self.stack.push(value);
+                },
+                Instruction::MakeList { count, span } => {
+                    let value = Value::List(self.pop_many(count));
+                    ctxt.budget.check_value(&value, || chunk.spans[span].clone())?;
+                    self.stack.push(value);
+                },
+                Instruction::IndexTarget(index) => {
+                    // a matrix is indexed like the list of its rows
+                    let target = self.stack.last_mut().unwrap();
+                    match target {
+                        Value::List(_) => (),
+                        Value::Matrix(_) => {
+                            let Value::Matrix(matrix) = std::mem::replace(target, Value::Unit) else {
+                                unreachable!();
+                            };
+                            *target = Value::List(matrix.into_rows().into_iter().map(Value::List).collect());
+                        },
+                        target => return Err(invalid_target(&chunk.indices[index], target)),
+                    }
+                },
+                Instruction::Index { index, count } => {
+                    let mut indices = self.pop_many(count).into_iter();
+                    let Value::List(mut values) = self.pop() else {
+                        unreachable!("the target was checked by `IndexTarget`");
+                    };
+                    let position = position_with(&chunk.indices[index], values.len(), |_| {
+                        Ok(indices.next().expect("the index was compiled"))
+                    })?;
+                    self.stack.push(match position {
+                        Position::Single(i) => values.swap_remove(i),
+                        Position::Slice(range) => Value::List(values.drain(range).collect()),
+                    });
+                },
+                Instruction::MakeLambda(index) => {
+                    let lambda = &chunk.lambdas[index];
+                    self.stack.push(Value::Function(Function {
+                        name: None,
+                        func: Func::Lambda(Arc::new(Lambda {
+                            params: lambda.params.clone(),
+                            body: (*lambda.body).clone(),
+                            captures: frame.capture(ctxt, chunk),
+                        })),
+                    }));
+                },
+                Instruction::ResolveCall { call, fallback, end } => {
+                    let site = &chunk.calls[call];
+                    let name = &site.call.name;
+                    let function = frame.get_function(ctxt, site.slot, &name.name)
+                        .ok_or_else(|| Error::new(vec![name.span.clone()], UndefinedFunction {
+                            name: name.name.clone(),
+                            suggestions: ctxt.get_similar_funcs(&name.name)
+                                .into_iter()
+                                .map(|s| s.to_string())
+                                .collect(),
+                        }))?;
+
+                    if let Func::Builtin(builtin) = &function.func {
+                        if builtin.takes_call() || !site.compiled_args {
+                            let resume = self.fallback(ctxt, chunk, frame, fallback)?;
+                            *pc = end;
+                            if let Some(value) = self.resume(frame, resume, pc) {
+                                return Ok(Control::Return(value));
+                            }
+                            continue;
+                        }
+                    }
+                    self.stack.push(Value::Function(function));
+                },
+                Instruction::Call { call: index, argc } => {
+                    let call = &chunk.calls[index].call;
+                    ctxt.step(|| call.span())?;
+                    let args = self.pop_many(argc);
+                    let Value::Function(function) = self.pop() else {
+                        unreachable!("the function was pushed by `ResolveCall`");
+                    };
+                    let Func::Builtin(builtin) = &function.func else {
+                        return Ok(Control::Call { call: index, function, args });
+                    };
+
+                    // builtins accepting a list are given a range as a list
+                    for (arg, expr) in args.iter().zip(&call.args) {
+                        ctxt.budget.check_coerced_list(arg, || expr.span())?;
+                    }
+                    let value = apply_builtin(call, ctxt, builtin, args)?;
+                    ctxt.budget.check_value(&value, || call.span())?;
+                    self.stack.push(value);
+                },
+                Instruction::JumpIfBound { param, target } => {
+                    if param < frame.locals().argc {
+                        *pc = target;
+                    }
+                },
+                Instruction::MissingArgument(index) => {
+                    let locals = frame.locals();
+                    let (params, signature) = params_of(&locals.function);
+                    let call = locals.call();
+                    return Err(Error::new(call.outer_span().to_vec(), MissingArgument {
+                        name: call.name.name.clone(),
+                        index,
+                        expected: params.len(),
+                        given: locals.argc,
+                        signature: signature.to_string(),
+                    }));
+                },
+                Instruction::IterStart(span) => {
+                    // ranges are iterated over lazily
+                    let iter = match self.pop() {
+                        Value::Range(range) => Iter::Range(range.iter()),
+                        Value::List(list) => Iter::List(list.into_iter()),
+                        value => return Err(Error::new(vec![chunk.spans[span].clone()], NotIterable {
+                            expr_type: value.typename(),
+                        })),
+                    };
+                    frame.iters.push(iter);
+                },
+                Instruction::IterNext { end } => {
+                    match frame.iters.last_mut().expect("a loop is running").next() {
+                        Some(value) => self.stack.push(value),
+                        None => *pc = end,
+                    }
+                },
+                Instruction::IterEnd => {
+                    frame.iters.pop();
+                },
+                Instruction::Step(span) => ctxt.step(|| chunk.spans[span].clone())?,
+                Instruction::Jump(target) => *pc = target,
+                Instruction::JumpIfFalse(target) => {
+                    if !self.pop().is_truthy() {
+                        *pc = target;
+                    }
+                },
+                Instruction::Break(target) => {
+                    let value = self.pop();
+                    self.resume(frame, Resume::Break(target, value), pc);
+                },
+                Instruction::Continue(target) => {
+                    self.resume(frame, Resume::Continue(target), pc);
+                },
+                Instruction::Return => {
+                    let value = self.pop();
+                    self.stack.truncate(frame.base);
+                    return Ok(Control::Return(value));
+                },
+                Instruction::Eval(index) => {
+                    let resume = self.fallback(ctxt, chunk, frame, index)?;
+                    if let Some(value) = self.resume(frame, resume, pc) {
+                        return Ok(Control::Return(value));
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::Parser;
+    use crate::numerical::{error::kind::StackOverflow, eval::eval_stmts};
+    use super::*;
+
+    /// Runs the given statements with both the tree-walking evaluator and the virtual machine,
+    /// checking that they produce the same value or error, and define the same variables. Returns
+    /// the result of the virtual machine.
+    fn compare(source: &str) -> Result<Value, Error> {
+        let stmts = Parser::new(source).try_parse_full_many().unwrap();
+        let mut expected_ctxt = Ctxt::default();
+        let expected = eval_stmts(&stmts, &mut expected_ctxt);
+        let mut ctxt = Ctxt::default();
+        let result = run_stmts(&stmts, &mut ctxt);
+
+        match (&expected, &result) {
+            (Ok(expected), Ok(value)) => assert_eq!(expected, value, "value of `{}`", source),
+            (Err(expected), Err(err)) => {
+                assert_eq!(expected.spans, err.spans, "spans of `{}`", source);
+                assert_eq!(
+                    format!("{:?}", expected.kind),
+                    format!("{:?}", err.kind),
+                    "error of `{}`",
+                    source,
+                );
+            },
+            _ => panic!("`{}` evaluated to {:?}, but ran to {:?}", source, expected, result),
+        }
+
+        // functions are compared by address, so only compare the other variables
+        let vars = |ctxt: &Ctxt| ctxt.get_vars()
+            .iter()
+            .filter(|(_, value)| !matches!(value, Value::Function(_)))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(vars(&expected_ctxt), vars(&ctxt), "variables of `{}`", source);
+
+        result
+    }
+
+    #[test]
+    fn examples() {
+        compare(include_str!("../../../../examples/bad_lcm.calc")).unwrap();
+        compare(include_str!("../../../../examples/factorial.calc")).unwrap();
+        compare(include_str!("../../../../examples/function_scope.calc")).unwrap();
+        compare(include_str!("../../../../examples/if_branching.calc")).unwrap();
+        compare(include_str!("../../../../examples/manual_abs.calc")).unwrap();
+        compare(include_str!("../../../../examples/radioactive_decay.calc")).unwrap();
+        compare(include_str!("../../../../examples/basel_problem.calc")).unwrap();
+        compare(include_str!("../../../../examples/symbolic.calc")).unwrap();
+    }
+
+    #[test]
+    fn loops() {
+        assert_eq!(compare("i = 0; total = 0; while i < 100 then { i += 1; total += i }; total").unwrap(), 5050.into());
+        assert_eq!(compare("total = 0; for i in 1..=100 { total += i }; total").unwrap(), 5050.into());
+        assert_eq!(compare("i = 0; loop { i += 1; if i == 20 then break i * 2 }").unwrap(), 40.into());
+        assert_eq!(compare("total = 0; for i in 1..=10 { if i % 2 == 0 then continue; total += i }; total").unwrap(), 25.into());
+        assert_eq!(compare("total = 0; for x in [1, 2, 3] { for y in [10, 20] { total += x * y } }; total").unwrap(), 180.into());
+        compare("for i in 5 { i }").unwrap_err();
+    }
+
+    #[test]
+    fn recursion() {
+        assert_eq!(compare("fib(n) = if n < 2 then n else fib(n - 1) + fib(n - 2); fib(15)").unwrap(), 610.into());
+        assert_eq!(compare("fact(n) = if n < 2 then 1 else n * fact(n - 1); fact(10)").unwrap(), 3628800.into());
+        compare("g(n) = if n > 0 then g(n - 1) else nope; g(3)").unwrap_err();
+    }
+
+    #[test]
+    fn deep_recursion() {
+        // the tree-walking evaluator would overflow the native stack before reaching the limit
+        let source = "deep(n) = if n > 0 then deep(n - 1) else 0; deep(5000)";
+        let stmts = Parser::new(source).try_parse_full_many().unwrap();
+        let mut ctxt = Ctxt::default();
+        let err = run_stmts(&stmts, &mut ctxt).unwrap_err();
+        assert_eq!(format!("{:?}", err.kind), format!("{:?}", StackOverflow));
+        assert_eq!(err.spans, vec![44..49, 53..54]);
+        assert_eq!(ctxt.stack_depth, 0);
+
+        // the context can be used again afterwards
+        assert_eq!(run_stmts(&Parser::new("deep(10)").try_parse_full_many().unwrap(), &mut ctxt).unwrap(), 0.into());
+    }
+
+    #[test]
+    fn arguments() {
+        assert_eq!(compare("f(a, b = 2) = a * b; f(6)").unwrap(), 12.into());
+        assert_eq!(compare("f(a, b = a + 1) = a * b; f(3) + f(3, 3)").unwrap(), 21.into());
+        compare("f(a) = a; f()").unwrap_err();
+        compare("f(a) = a; f(1, 2)").unwrap_err();
+        compare("f(a) = a; g(1)").unwrap_err();
+
+        // calls with named arguments are evaluated by the tree-walking evaluator
+        assert_eq!(compare("f(a, b = a + 1) = a * b; s = 0; for i in 0..4 { s += f(b = i, a = 2) }; s").unwrap(), 12.into());
+        compare("f(a, b = 2) = a * b; f(b = 3)").unwrap_err();
+        compare("f(a) = a; f(1, c = 2)").unwrap_err();
+    }
+
+    #[test]
+    fn closures() {
+        assert_eq!(compare("adder(n) = x -> x + n; add = adder(3); add(4)").unwrap(), 7.into());
+        assert_eq!(compare("f(n) = { sq(x) = x^2; sq(n) + 0 }; f(3)").unwrap(), 9.into());
+        assert_eq!(compare("count = 0; bump() = count += 1; bump(); bump(); count").unwrap(), 2.into());
+    }
+
+    #[test]
+    fn return_and_continue() {
+        assert_eq!(compare("f(n) = { for i in 1..n { if i * i > n then return i }; 0 }; f(50)").unwrap(), 8.into());
+        assert_eq!(compare("f(n) = { total = 0; i = 0; while i < n then { i += 1; if i == 3 then continue; total += i }; total }; f(5)").unwrap(), 12.into());
+    }
+
+    #[test]
+    fn indexing() {
+        assert_eq!(compare("list = [1, 2, 3]; list[1] = 7; list").unwrap(), Value::List(vec![1.into(), 7.into(), 3.into()]));
+        assert_eq!(compare("f() = { list = [0, 0, 4]; list[1] += 7; list }; f()").unwrap(), Value::List(vec![0.into(), 7.into(), 4.into()]));
+        compare("list = [1, 2, 3]; list[5]").unwrap_err();
+    }
+
+    #[test]
+    fn function_names() {
+        assert_eq!(compare("f = abs; f(-2)").unwrap().to_string(), "2");
+        assert_eq!(compare("map(abs, [-1])").unwrap().to_string(), "[1]");
+        compare("sin + 1").unwrap_err();
+        compare("f(x) = x * sin; f(2)").unwrap_err();
+    }
+
+    #[test]
+    fn fallbacks() {
+        assert_eq!(compare("sum(n, n, 1, 10)").unwrap(), 55.into());
+        assert_eq!(compare("h(n) = sum(k * n, k, 1, 3); h(2)").unwrap(), 12.into());
+    }
+
+    #[test]
+    fn globals() {
+        assert_eq!(compare("x = 5; f() = x + 1; f()").unwrap(), 6.into());
+        assert_eq!(compare("x = 1; f() = { x += 1; x }; f(); x").unwrap(), 2.into());
+        compare("f() = { y = 1; y }; f(); y").unwrap_err();
+    }
+}
@@ -1,11 +1,66 @@
 //! Functions to construct [`Integer`]s, [`Float`]s, and [`Complex`] numbers from various types.
+//!
+//! [`Float`]s and [`Complex`] numbers are created with the current precision of the thread, which
+//! is [`PRECISION`] bits by default. Evaluating an expression with a
+//! [`Ctxt`](crate::numerical::ctxt::Ctxt) sets the current precision to the precision of the
+//! context for the duration of the evaluation. Use [`with_precision`] to change it manually.
 
 use cas_parser::parser::ast::literal::DIGITS;
 use rug::{ops::Pow, Assign, Complex, Float, Integer};
+use std::cell::Cell;
 
-/// The number of digits of precision to use when computing values.
+/// The default number of bits of precision to use when computing values.
 pub const PRECISION: u32 = 1 << 9;
 
+/// The smallest number of bits of precision that can be used.
+pub const MIN_PRECISION: u32 = 8;
+
+/// The largest number of bits of precision that can be used.
+pub const MAX_PRECISION: u32 = 1 << 20;
+
+thread_local! {
+    /// The number of bits of precision used by [`float`] and [`complex`] on this thread.
+    static CURRENT_PRECISION: Cell<u32> = const { Cell::new(PRECISION) };
+}
+
+/// Returns the number of bits of precision currently used to compute values on this thread.
+pub fn precision() -> u32 {
+    CURRENT_PRECISION.with(Cell::get)
+}
+
+/// Calls the given function with the current precision set to `precision` bits, restoring the
+/// previous precision afterwards.
+///
+/// # Panics
+///
+/// Panics if `precision` is not between [`MIN_PRECISION`] and [`MAX_PRECISION`].
+pub fn with_precision<T>(precision: u32, f: impl FnOnce() -> T) -> T {
+    assert!(
+        (MIN_PRECISION..=MAX_PRECISION).contains(&precision),
+        "precision must be between {} and {} bits",
+        MIN_PRECISION,
+        MAX_PRECISION,
+    );
+
+    /// Restores the previous precision when dropped, even if `f` panics.
+    struct Restore(u32);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT_PRECISION.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(CURRENT_PRECISION.with(|current| current.replace(precision)));
+    f()
+}
+
+/// Sets the current precision of this thread to `precision` bits. The precision that was current
+/// before the enclosing call to [`with_precision`], if any, is still restored when it returns.
+pub(crate) fn set_precision(precision: u32) {
+    CURRENT_PRECISION.with(|current| current.set(precision));
+}
+
 /// Creates an [`Integer`] with the given value.
 pub fn int<T>(n: T) -> Integer
 where
@@ -30,12 +85,12 @@ pub fn float<T>(n: T) -> Float
 where
     Float: Assign<T>,
 {
-    Float::with_val(PRECISION, n)
+    Float::with_val(precision(), n)
 }
 
 /// Creates a [`Float`] from a string slice.
 pub fn float_from_str(s: &str) -> Float {
-    Float::with_val(PRECISION, Float::parse(s).unwrap())
+    Float::with_val(precision(), Float::parse(s).unwrap())
 }
 
 /// Parses a number from a string, with the given radix. The radix must be between 2 and 64,
@@ -60,7 +115,7 @@ pub fn complex<T>(n: T) -> Complex
 where
     Complex: Assign<T>,
 {
-    Complex::with_val(PRECISION, n)
+    Complex::with_val(precision(), n)
 }
 
 #[cfg(test)]
@@ -82,4 +137,15 @@ mod tests {
             assert_eq!(from_str_radix(number, *radix), expected);
         }
     }
+
+    #[test]
+    fn scoped_precision() {
+        assert_eq!(float(1).prec(), PRECISION);
+        let (inner, nested) = with_precision(64, || {
+            (float(1).prec(), with_precision(1024, || complex(1).prec()))
+        });
+        assert_eq!(inner, 64);
+        assert_eq!(nested, (1024, 1024));
+        assert_eq!(float(1).prec(), PRECISION);
+    }
 }
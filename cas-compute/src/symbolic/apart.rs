@@ -0,0 +1,136 @@
+//! Partial fraction decomposition of rational functions.
+//!
+//! The [`apart`] function rewrites a rational function in some variable as a polynomial plus a
+//! sum of proper fractions, one for each power of each factor of the denominator. The denominator
+//! is factored over the rationals into linear and irreducible quadratic factors (see
+//! [`Polynomial::factor`]), so that repeated factors give one fraction per power:
+//!
+//! ```text
+//! (x + 1) / (x^2 (x^2 + 1)) = 1 / x + 1 / x^2 - (x + 1) / (x^2 + 1)
+//! ```
+//!
+//! ```
+//! use cas_compute::symbolic::{apart, expr::Expr};
+//! use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+//!
+//! let parse = |input: &str| Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap());
+//!
+//! // 1 / (x^2 - 1) = 1 / (2 (x - 1)) - 1 / (2 (x + 1))
+//! let result = apart(&parse("1 / (x^2 - 1)"), "x").unwrap();
+//! assert_eq!(result.to_string(), "-1 * 2^-1 * (1 + x)^-1 + 2^-1 * (-1 + x)^-1");
+//! ```
+
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    polynomial::{PartialFraction, Polynomial, RationalFunction},
+    simplify::simplify,
+};
+use crate::primitive::int;
+use rug::ops::Pow;
+use std::sync::Arc;
+
+/// Decomposes the rational function `expr` in the variable `var` into partial fractions.
+///
+/// Returns [`None`] if `expr` is not a rational function of `var` with numeric coefficients.
+pub fn apart(expr: &Expr, var: &str) -> Option<Expr> {
+    let function = RationalFunction::from_expr(&simplify(expr), var)?;
+    let var = Expr::Primary(Primary::Symbol(var.to_string()));
+    let (quotient, fractions) = function.apart();
+
+    let mut terms = fractions.iter()
+        .map(|fraction| fraction_expr(fraction, &var))
+        .collect::<Vec<_>>();
+    if !quotient.is_zero() {
+        terms.push(simplify(&quotient.to_expr(&var)));
+    }
+
+    Some(match terms.len() {
+        0 => Expr::Primary(Primary::Integer(int(0))),
+        _ => Expr::Add(terms.into()).downgrade(),
+    })
+}
+
+/// Converts a partial fraction `n(x) / f(x)^k` into an [`Expr`]. The monic factor `f(x)` is
+/// scaled to have integer coefficients, so that e.g. `1 / (2x + 1)` is not written as
+/// `1/2 (x + 1/2)^-1`.
+fn fraction_expr(fraction: &PartialFraction, var: &Expr) -> Expr {
+    let scale = fraction.factor.coeffs()
+        .iter()
+        .fold(int(1), |lcm, c| lcm.lcm(c.denom()));
+    let factor = fraction.factor.clone() * &Polynomial::constant(scale.clone());
+    let num = fraction.num.clone() * &Polynomial::constant(scale.clone().pow(fraction.power as u32));
+
+    let num = simplify(&num.to_expr(var));
+    let factor = Expr::Exp(
+        Arc::new(simplify(&factor.to_expr(var))),
+        Arc::new(Expr::Primary(Primary::Integer(-int(fraction.power)))),
+    );
+    match num {
+        Expr::Primary(Primary::Integer(n)) if n == 1 => factor,
+        Expr::Mul(factors) => {
+            let mut factors = factors.into_vec();
+            factors.push(factor);
+            Expr::Mul(factors.into())
+        },
+        num => Expr::Mul(vec![num, factor].into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
+    use crate::symbolic::calculus::eval_at;
+    use super::*;
+
+    /// Parses the given expression.
+    fn parse(input: &str) -> Expr {
+        Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap())
+    }
+
+    /// Decomposes `input` into partial fractions in `x`, checks that the result agrees with the
+    /// input at a few points, and returns the number of terms in the result.
+    fn assert_apart(input: &str) -> usize {
+        let expr = parse(input);
+        let result = apart(&expr, "x").unwrap_or_else(|| panic!("could not decompose `{}`", input));
+        for x in [0.3, 1.7, 2.9] {
+            let expected = eval_at(&expr, &[("x", x)]);
+            let actual = eval_at(&result, &[("x", x)]);
+            assert!((expected - actual).abs() < 1e-9, "{} = {}", input, result);
+        }
+        match result {
+            Expr::Add(terms) => terms.len(),
+            _ => 1,
+        }
+    }
+
+    #[test]
+    fn distinct_linear() {
+        assert_eq!(assert_apart("(2x + 3) / (x^2 + 3x + 2)"), 2);
+        assert_eq!(apart(&parse("1 / (2x + 1)"), "x").unwrap(), parse("(2x + 1)^-1"));
+    }
+
+    #[test]
+    fn repeated_linear() {
+        // 1 / (x^2 (x - 1)^3) has five fractions
+        assert_eq!(assert_apart("1 / (x^2 * (x - 1)^3)"), 5);
+    }
+
+    #[test]
+    fn quadratic() {
+        assert_eq!(assert_apart("(x + 1) / (x^2 * (x^2 + 1))"), 3);
+        assert_eq!(assert_apart("1 / (x^4 + 4)"), 2);
+        assert_eq!(assert_apart("(x^2 + 1) / ((x - 1)^3 * (x^2 + x + 1)^2)"), 5);
+    }
+
+    #[test]
+    fn polynomial_part() {
+        assert_eq!(assert_apart("x^3 / (x^2 - 1)"), 3);
+        assert_eq!(apart(&parse("(x^2 - 1) / (x - 1)"), "x").unwrap(), simplify(&parse("x + 1")));
+    }
+
+    #[test]
+    fn not_rational() {
+        assert_eq!(apart(&parse("sin(x) / x"), "x"), None);
+        assert_eq!(apart(&parse("1 / (x - y)"), "x"), None);
+    }
+}
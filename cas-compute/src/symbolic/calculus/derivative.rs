@@ -0,0 +1,192 @@
+//! Symbolic differentiation.
+
+use crate::symbolic::{expr::{Expr, Primary}, simplify::{fraction::make_fraction, simplify}};
+use super::{call, is_euler, num, pow, sym};
+
+/// Returns the derivative of the given expression with respect to the symbol `var`, simplified.
+///
+/// Calls to functions that are not builtin functions and take a single argument are treated as
+/// unknown functions of that argument, and are differentiated using prime notation and the chain
+/// rule. For example, the derivative of `f(x^2)` is `f'(x^2) * 2x`.
+///
+/// Returns [`None`] if the expression contains a call to an unknown function with more than one
+/// argument that depends on `var`, since its derivative cannot be expressed.
+pub fn diff(expr: &Expr, var: &str) -> Option<Expr> {
+    derivative(expr, var).map(|expr| simplify(&expr))
+}
+
+/// Returns the `n`th derivative of the given expression with respect to the symbol `var`,
+/// simplified.
+pub fn diff_n(expr: &Expr, var: &str, n: usize) -> Option<Expr> {
+    (0..n).try_fold(expr.clone(), |expr, _| diff(&expr, var))
+}
+
+/// Computes the derivative of the given expression, without simplifying the result.
+pub(crate) fn derivative(expr: &Expr, var: &str) -> Option<Expr> {
+    if !expr.contains_symbol(var) {
+        return Some(num(0));
+    }
+
+    match expr {
+        Expr::Primary(Primary::Integer(_)) | Expr::Primary(Primary::Float(_)) => Some(num(0)),
+        Expr::Primary(Primary::Symbol(name)) => Some(num((name == var) as i32)),
+        Expr::Primary(Primary::Call(name, args)) => derivative_call(name, args, var),
+        Expr::Add(terms) => terms.iter()
+            .map(|term| derivative(term, var))
+            .collect::<Option<_>>()
+            .map(Expr::Add),
+        Expr::Mul(factors) => {
+            // product rule: (fg)' = f'g + fg'
+            let mut terms = Vec::with_capacity(factors.len());
+            for (i, factor) in factors.iter().enumerate() {
+                if !factor.contains_symbol(var) {
+                    continue;
+                }
+
+                let mut product = factors.to_vec();
+                product[i] = derivative(factor, var)?;
+                terms.push(Expr::Mul(product.into()));
+            }
+            Some(Expr::Add(terms.into()))
+        },
+        Expr::Exp(base, exp) => {
+            let base_dep = base.contains_symbol(var);
+            let exp_dep = exp.contains_symbol(var);
+            if base_dep && !exp_dep {
+                // power rule: (u^n)' = n * u^(n - 1) * u'
+                Some(Expr::Mul(vec![
+                    Expr::clone(exp),
+                    pow(Expr::clone(base), Expr::clone(exp) + num(-1)),
+                    derivative(base, var)?,
+                ].into()))
+            } else if !base_dep && is_euler(base) {
+                // (e^u)' = e^u * u'
+                Some(expr.clone() * derivative(exp, var)?)
+            } else if !base_dep {
+                // (a^u)' = a^u * ln(a) * u'
+                Some(Expr::Mul(vec![
+                    expr.clone(),
+                    call("ln", Expr::clone(base)),
+                    derivative(exp, var)?,
+                ].into()))
+            } else {
+                // (u^v)' = u^v * (v' * ln(u) + v * u' / u)
+                Some(expr.clone() * Expr::Add(vec![
+                    derivative(exp, var)? * call("ln", Expr::clone(base)),
+                    Expr::Mul(vec![
+                        Expr::clone(exp),
+                        derivative(base, var)?,
+                        pow(Expr::clone(base), num(-1)),
+                    ].into()),
+                ].into()))
+            }
+        },
+    }
+}
+
+/// Computes the derivative of a function call, applying the chain rule.
+fn derivative_call(name: &str, args: &[Expr], var: &str) -> Option<Expr> {
+    // functions that can be rewritten in terms of simpler expressions
+    let rewritten = match (name, args) {
+        ("sqrt", [u]) => Some(u.clone().sqrt()),
+        ("cbrt", [u]) => Some(pow(u.clone(), make_fraction(num(1), num(3)))),
+        ("root", [u, n]) => Some(pow(u.clone(), make_fraction(num(1), n.clone()))),
+        ("pow", [u, v]) => Some(pow(u.clone(), v.clone())),
+        ("log", [u]) => Some(make_fraction(call("ln", u.clone()), call("ln", num(10)))),
+        ("log", [u, b]) => Some(make_fraction(call("ln", u.clone()), call("ln", b.clone()))),
+        _ => None,
+    };
+    if let Some(expr) = rewritten {
+        return derivative(&expr, var);
+    }
+
+    let [u] = args else {
+        return None;
+    };
+
+    let one_minus_u_sq = || num(1) + -pow(u.clone(), num(2));
+    let u_sq_plus = |n: i32| pow(u.clone(), num(2)) + num(n);
+    let outer = match name {
+        "exp" => call("exp", u.clone()),
+        "ln" => pow(u.clone(), num(-1)),
+        "sin" => call("cos", u.clone()),
+        "cos" => -call("sin", u.clone()),
+        "tan" => pow(call("cos", u.clone()), num(-2)),
+        "csc" => -(call("csc", u.clone()) * call("cot", u.clone())),
+        "sec" => call("sec", u.clone()) * call("tan", u.clone()),
+        "cot" => -pow(call("sin", u.clone()), num(-2)),
+        "asin" => pow(one_minus_u_sq(), make_fraction(num(-1), num(2))),
+        "acos" => -pow(one_minus_u_sq(), make_fraction(num(-1), num(2))),
+        "atan" => pow(u_sq_plus(1), num(-1)),
+        "sinh" => call("cosh", u.clone()),
+        "cosh" => call("sinh", u.clone()),
+        "tanh" => pow(call("cosh", u.clone()), num(-2)),
+        "asinh" => pow(u_sq_plus(1), make_fraction(num(-1), num(2))),
+        "acosh" => pow(u_sq_plus(-1), make_fraction(num(-1), num(2))),
+        "atanh" => pow(one_minus_u_sq(), num(-1)),
+        "abs" => u.clone() * pow(call("abs", u.clone()), num(-1)),
+
+        // unknown function of one variable; use prime notation
+        _ => Expr::Primary(Primary::Call(format!("{}'", name), args.to_vec())),
+    };
+
+    // chain rule: f(u)' = f'(u) * u'
+    // avoid multiplying by `1` if the argument is the variable itself
+    if u == &sym(var) {
+        Some(outer)
+    } else {
+        Some(outer * derivative(u, var)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
+    use pretty_assertions::assert_eq;
+    use super::*;
+
+    /// Parse the given expression and return the [`Expr`] representation.
+    fn parse_expr(input: &str) -> Expr {
+        let expr = Parser::new(input).try_parse_full::<AstExpr>().unwrap();
+        Expr::from(expr)
+    }
+
+    /// Differentiates `input` with respect to `x` and compares it with `expected`, after
+    /// simplifying both.
+    fn assert_diff(input: &str, expected: &str) {
+        assert_eq!(diff(&parse_expr(input), "x").unwrap(), simplify(&parse_expr(expected)));
+    }
+
+    #[test]
+    fn polynomial() {
+        assert_diff("x^3 + 4x^2 - 7x + 2", "3x^2 + 8x - 7");
+    }
+
+    #[test]
+    fn constant() {
+        assert_diff("y^2 + sin(pi)", "0");
+    }
+
+    #[test]
+    fn chain_rule() {
+        assert_diff("sin(3x)", "3cos(3x)");
+        assert_diff("e^(x^2)", "2x e^(x^2)");
+    }
+
+    #[test]
+    fn product_rule() {
+        assert_diff("x ln(x)", "ln(x) + 1");
+    }
+
+    #[test]
+    fn unknown_function() {
+        assert_diff("f(x)", "f'(x)");
+        assert_diff("y'(x) x", "y''(x) x + y'(x)");
+    }
+
+    #[test]
+    fn second_derivative() {
+        let expr = parse_expr("x^4");
+        assert_eq!(diff_n(&expr, "x", 2).unwrap(), simplify(&parse_expr("12x^2")));
+    }
+}
@@ -0,0 +1,450 @@
+//! Heuristic symbolic integration.
+//!
+//! The integrator tries the following strategies, in order, on each term of the (expanded)
+//! integrand:
+//!
+//! - Terms that do not depend on the variable of integration are constants.
+//! - Constant factors are pulled out of the integral.
+//! - A table of known antiderivatives `F(u)`, combined with the "derivative-divides" form of
+//!   substitution: if the integrand is `g(x) * f(u)` and `g(x) / u'` is constant, then the integral
+//!   is `g(x) / u' * F(u)`.
+//! - Rational functions are decomposed into partial fractions, which are integrated into
+//!   logarithms, powers and arctangents.
+//! - `e^(ax) sin(bx)` and `e^(ax) cos(bx)` are integrated with their closed forms.
+//! - Products of sines and cosines are rewritten as sums with the product-to-sum identities.
+//! - Integration by parts, differentiating powers of the variable, logarithms and inverse
+//!   trigonometric functions.
+//!
+//! Constants of integration are never added.
+
+use crate::primitive::int;
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    polynomial::{PartialFraction, Polynomial, RationalFunction},
+    simplify::{fraction::{make_fraction, rational_expr, sqrt_rational}, simplify},
+};
+use rug::Rational;
+use super::{call, derivative::diff, is_euler, num, pow, sym};
+
+/// The maximum number of nested rewrites (integration by parts, product-to-sum) that may be
+/// applied before the integrator gives up.
+const MAX_DEPTH: usize = 16;
+
+/// Returns an antiderivative of the given expression with respect to the symbol `var`,
+/// simplified. No constant of integration is added.
+///
+/// Returns [`None`] if the integrator could not find an antiderivative.
+pub fn integrate(expr: &Expr, var: &str) -> Option<Expr> {
+    antiderivative(&simplify(expr), var, 0).map(|expr| simplify(&expr))
+}
+
+/// Multiplies all the given factors together, downgrading the result if possible.
+fn product(factors: Vec<Expr>) -> Expr {
+    Expr::Mul(factors.into()).downgrade()
+}
+
+/// Computes an antiderivative of the given simplified expression, without simplifying the result.
+fn antiderivative(expr: &Expr, var: &str, depth: usize) -> Option<Expr> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+
+    if !expr.contains_symbol(var) {
+        return Some(expr.clone() * sym(var));
+    }
+
+    match expr {
+        Expr::Add(terms) => terms.iter()
+            .map(|term| antiderivative(term, var, depth))
+            .collect::<Option<_>>()
+            .map(Expr::Add),
+        Expr::Mul(factors) => {
+            let (constant, dependent): (Vec<_>, Vec<_>) = factors.iter()
+                .cloned()
+                .partition(|factor| !factor.contains_symbol(var));
+            let result = integrate_product(&dependent, var, depth)?;
+            if constant.is_empty() {
+                Some(result)
+            } else {
+                Some(product(constant) * result)
+            }
+        },
+        _ => integrate_product(std::slice::from_ref(expr), var, depth),
+    }
+}
+
+/// Integrates a product of factors that all depend on the variable of integration.
+fn integrate_product(factors: &[Expr], var: &str, depth: usize) -> Option<Expr> {
+    substitution(factors, var)
+        .or_else(|| partial_fractions(factors, var))
+        .or_else(|| exp_trig(factors, var))
+        .or_else(|| product_to_sum(factors, var, depth))
+        .or_else(|| by_parts(factors, var, depth))
+}
+
+/// If the expression is of the form `f(u)`, where `f` is a function with a known antiderivative
+/// `F`, returns `u` and `F(u)`.
+fn table(expr: &Expr, var: &str) -> Option<(Expr, Expr)> {
+    match expr {
+        Expr::Primary(Primary::Symbol(name)) if name == var => {
+            Some((expr.clone(), make_fraction(pow(expr.clone(), num(2)), num(2))))
+        },
+        Expr::Exp(base, exp) => {
+            match (base.contains_symbol(var), exp.contains_symbol(var)) {
+                (true, false) => {
+                    if exp.as_integer().map(|n| n == &-1).unwrap_or(false) {
+                        // u^-1 -> ln(u)
+                        Some((Expr::clone(base), call("ln", Expr::clone(base))))
+                    } else {
+                        // u^n -> u^(n + 1) / (n + 1)
+                        let exp = simplify(&(Expr::clone(exp) + num(1)));
+                        Some((
+                            Expr::clone(base),
+                            make_fraction(pow(Expr::clone(base), exp.clone()), exp),
+                        ))
+                    }
+                },
+                (false, true) => {
+                    if is_euler(base) {
+                        // e^u -> e^u
+                        Some((Expr::clone(exp), expr.clone()))
+                    } else {
+                        // a^u -> a^u / ln(a)
+                        Some((Expr::clone(exp), make_fraction(expr.clone(), call("ln", Expr::clone(base)))))
+                    }
+                },
+                _ => None,
+            }
+        },
+        Expr::Primary(Primary::Call(name, args)) => {
+            let [u] = args.as_slice() else {
+                return None;
+            };
+            let antiderivative = match name.as_str() {
+                "exp" => call("exp", u.clone()),
+                "sqrt" => make_fraction(
+                    pow(u.clone(), make_fraction(num(3), num(2))) * num(2),
+                    num(3),
+                ),
+                "ln" => u.clone() * call("ln", u.clone()) + -u.clone(),
+                "sin" => -call("cos", u.clone()),
+                "cos" => call("sin", u.clone()),
+                "tan" => -call("ln", call("cos", u.clone())),
+                "cot" => call("ln", call("sin", u.clone())),
+                "sinh" => call("cosh", u.clone()),
+                "cosh" => call("sinh", u.clone()),
+                "tanh" => call("ln", call("cosh", u.clone())),
+                _ => return None,
+            };
+            Some((u.clone(), antiderivative))
+        },
+        _ => None,
+    }
+}
+
+/// Integration by substitution, in the form `∫ g(x) f(u) dx = g(x) / u' * F(u)`, where `g(x) / u'`
+/// must be constant.
+fn substitution(factors: &[Expr], var: &str) -> Option<Expr> {
+    factors.iter().enumerate().find_map(|(i, factor)| {
+        let (u, antiderivative) = table(factor, var)?;
+        let du = diff(&u, var)?;
+        if du.as_integer().map(|n| n.is_zero()).unwrap_or(false) {
+            return None;
+        }
+
+        let mut rest = factors.to_vec();
+        rest.remove(i);
+        let ratio = simplify(&(product(rest) * pow(du, num(-1))));
+        if ratio.contains_symbol(var) {
+            None
+        } else {
+            Some(ratio * antiderivative)
+        }
+    })
+}
+
+/// Integrates a rational function by decomposing it into partial fractions.
+///
+/// The polynomial part is integrated term by term, fractions `c / (x - a)^k` with the power rule,
+/// and fractions `(b x + c) / (x^2 + p x + q)` as a logarithm plus an arctangent (or a logarithm,
+/// if the quadratic has real roots). Repeated quadratic factors are not supported.
+fn partial_fractions(factors: &[Expr], var: &str) -> Option<Expr> {
+    let function = RationalFunction::from_expr(&product(factors.to_vec()), var)?;
+    if function.is_polynomial() {
+        return None;
+    }
+
+    let x = sym(var);
+    let (quotient, fractions) = function.apart();
+    let integral = Polynomial::new(
+        std::iter::once(Rational::new())
+            .chain(quotient.coeffs().iter().enumerate().map(|(i, c)| Rational::from(c / (i as u64 + 1))))
+            .collect()
+    );
+    let mut terms = vec![integral.to_expr(&x)];
+    for fraction in fractions {
+        terms.push(integrate_fraction(&fraction, &x)?);
+    }
+    Some(Expr::Add(terms.into()))
+}
+
+/// Integrates a single partial fraction with a linear or non-repeated quadratic factor.
+fn integrate_fraction(fraction: &PartialFraction, x: &Expr) -> Option<Expr> {
+    let PartialFraction { num: numerator, factor, power } = fraction;
+    let factor_expr = factor.to_expr(x);
+    match (factor.degree()?, *power) {
+        // c / (x - a) -> c ln(x - a)
+        (1, 1) => Some(numerator.to_expr(x) * call("ln", factor_expr)),
+        // c / (x - a)^k -> c (x - a)^(1 - k) / (1 - k)
+        (1, k) => {
+            let exp = 1 - k as i32;
+            Some(make_fraction(numerator.to_expr(x) * pow(factor_expr, num(exp)), num(exp)))
+        },
+        (2, 1) => {
+            // (b x + c) / (x^2 + p x + q) = b / 2 (2x + p) / (x^2 + p x + q) + (c - b p / 2) / (x^2 + p x + q)
+            let coeff = |poly: &Polynomial, i: usize| poly.coeffs().get(i).cloned().unwrap_or_default();
+            let (b, c) = (coeff(numerator, 1), coeff(numerator, 0));
+            let (p, q) = (coeff(factor, 1), coeff(factor, 0));
+            let log = rational_expr(&Rational::from(&b / 2)) * call("ln", factor_expr);
+            let rest = c - Rational::from(&b * &p) / 2;
+            if rest == 0 {
+                return Some(log);
+            }
+
+            // 1 / (x^2 + p x + q) = 4 / ((2x + p)^2 + (4q - p^2))
+            let linear = num(2) * x.clone() + rational_expr(&p);
+            let discriminant: Rational = 4 * q - Rational::from(p.square_ref());
+            let root = sqrt_rational(&discriminant.clone().abs());
+            let rest = rational_expr(&rest);
+            let inverse = if discriminant > 0 {
+                // 2 / sqrt(D) atan((2x + p) / sqrt(D))
+                let atan = call("atan", make_fraction(linear, root.clone()));
+                make_fraction(num(2) * rest * atan, root)
+            } else {
+                // 1 / sqrt(-D) (ln(2x + p - sqrt(-D)) - ln(2x + p + sqrt(-D)))
+                let logs = call("ln", linear.clone() + -root.clone()) + -call("ln", linear + root.clone());
+                make_fraction(rest * logs, root)
+            };
+            Some(log + inverse)
+        },
+        _ => None,
+    }
+}
+
+/// Returns the argument of `e^u` or `exp(u)`.
+fn exp_argument(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::Exp(base, exp) if is_euler(base) => Some(exp),
+        Expr::Primary(Primary::Call(name, args)) if name == "exp" && args.len() == 1 => {
+            Some(&args[0])
+        },
+        _ => None,
+    }
+}
+
+/// If the expression is `sin(u)` or `cos(u)`, returns whether it is a sine, and the argument.
+fn sin_cos_argument(expr: &Expr) -> Option<(bool, &Expr)> {
+    match expr {
+        Expr::Primary(Primary::Call(name, args)) if args.len() == 1 => match name.as_str() {
+            "sin" => Some((true, &args[0])),
+            "cos" => Some((false, &args[0])),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `∫ e^(ax) sin(bx) dx = e^(ax) (a sin(bx) - b cos(bx)) / (a^2 + b^2)`
+/// `∫ e^(ax) cos(bx) dx = e^(ax) (a cos(bx) + b sin(bx)) / (a^2 + b^2)`
+///
+/// The arguments of the exponential and trigonometric functions must be linear.
+fn exp_trig(factors: &[Expr], var: &str) -> Option<Expr> {
+    let [first, second] = factors else {
+        return None;
+    };
+    let (exp, trig) = if exp_argument(first).is_some() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let u = exp_argument(exp)?;
+    let (is_sin, w) = sin_cos_argument(trig)?;
+
+    let a = diff(u, var)?;
+    let b = diff(w, var)?;
+    if a.contains_symbol(var) || b.contains_symbol(var) {
+        return None;
+    }
+
+    let denominator = pow(a.clone(), num(2)) + pow(b.clone(), num(2));
+    let numerator = if is_sin {
+        a * call("sin", w.clone()) + -(b * call("cos", w.clone()))
+    } else {
+        a * call("cos", w.clone()) + b * call("sin", w.clone())
+    };
+    Some(make_fraction(exp.clone() * numerator, denominator))
+}
+
+/// Rewrites a product of two sines / cosines as a sum using the product-to-sum identities, then
+/// integrates the result.
+fn product_to_sum(factors: &[Expr], var: &str, depth: usize) -> Option<Expr> {
+    // expand integer powers of sines and cosines into repeated factors
+    let mut expanded = Vec::with_capacity(factors.len());
+    for factor in factors {
+        match factor {
+            Expr::Exp(base, exp) if sin_cos_argument(base).is_some() => {
+                let n = exp.as_integer().and_then(|n| n.to_usize()).filter(|&n| n >= 2)?;
+                expanded.extend(std::iter::repeat_n((**base).clone(), n));
+            },
+            factor => expanded.push(factor.clone()),
+        }
+    }
+
+    let first = expanded.iter().position(|factor| sin_cos_argument(factor).is_some())?;
+    let second = expanded.iter()
+        .skip(first + 1)
+        .position(|factor| sin_cos_argument(factor).is_some())?
+        + first + 1;
+    let (first_sin, a) = sin_cos_argument(&expanded[first])?;
+    let (second_sin, b) = sin_cos_argument(&expanded[second])?;
+    let (a, b) = (a.clone(), b.clone());
+
+    let diff = || a.clone() + -b.clone();
+    let sum = || a.clone() + b.clone();
+    let sum_of_terms = match (first_sin, second_sin) {
+        // sin(a) sin(b) = (cos(a - b) - cos(a + b)) / 2
+        (true, true) => call("cos", diff()) + -call("cos", sum()),
+        // cos(a) cos(b) = (cos(a - b) + cos(a + b)) / 2
+        (false, false) => call("cos", diff()) + call("cos", sum()),
+        // sin(a) cos(b) = (sin(a + b) + sin(a - b)) / 2
+        (true, false) => call("sin", sum()) + call("sin", diff()),
+        // cos(a) sin(b) = (sin(a + b) - sin(a - b)) / 2
+        (false, true) => call("sin", sum()) + -call("sin", diff()),
+    };
+
+    expanded.remove(second);
+    expanded.remove(first);
+    let rewritten = product(expanded) * make_fraction(sum_of_terms, num(2));
+    antiderivative(&simplify(&rewritten), var, depth + 1)
+}
+
+/// Returns true if the expression is the variable, or a positive integer power of it.
+fn is_monomial(expr: &Expr, var: &str) -> bool {
+    match expr {
+        Expr::Primary(Primary::Symbol(name)) => name == var,
+        Expr::Exp(base, exp) => base.as_symbol() == Some(var)
+            && exp.as_integer().map(|n| *n > 0).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Returns true if the expression is a call to a function whose derivative is algebraic, such as
+/// `ln(u)` or `atan(u)`. These are good candidates for `u` in integration by parts.
+fn is_algebraic_derivative(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Primary(Primary::Call(name, args))
+            if args.len() == 1
+                && matches!(name.as_str(), "ln" | "asin" | "acos" | "atan" | "asinh" | "atanh")
+    )
+}
+
+/// Integration by parts: `∫ u dv = uv - ∫ v du`.
+fn by_parts(factors: &[Expr], var: &str, depth: usize) -> Option<Expr> {
+    factors.iter().enumerate().find_map(|(i, u)| {
+        let monomial = is_monomial(u, var);
+        if !monomial && !is_algebraic_derivative(u) {
+            return None;
+        }
+
+        let mut rest = factors.to_vec();
+        rest.remove(i);
+        if rest.is_empty() && monomial {
+            // handled by the table
+            return None;
+        }
+
+        let dv = if rest.is_empty() {
+            Expr::Primary(Primary::Integer(int(1)))
+        } else {
+            product(rest)
+        };
+        let v = simplify(&antiderivative(&dv, var, depth + 1)?);
+        let du = diff(u, var)?;
+        let v_du = antiderivative(&simplify(&(v.clone() * du)), var, depth + 1)?;
+        Some(u.clone() * v + -v_du)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
+    use pretty_assertions::assert_eq;
+    use super::*;
+    use super::super::eval_at;
+
+    /// Parse the given expression and return the [`Expr`] representation.
+    fn parse_expr(input: &str) -> Expr {
+        let expr = Parser::new(input).try_parse_full::<AstExpr>().unwrap();
+        Expr::from(expr)
+    }
+
+    /// Integrates `input` with respect to `x`, and checks that differentiating the result gives
+    /// back the integrand at a few points.
+    fn assert_integrates(input: &str) {
+        let expr = parse_expr(input);
+        let antiderivative = integrate(&expr, "x")
+            .unwrap_or_else(|| panic!("could not integrate `{}`", input));
+        let derivative = diff(&antiderivative, "x").unwrap();
+        for x in [0.3, 1.1, 2.7] {
+            let expected = eval_at(&expr, &[("x", x)]);
+            let actual = eval_at(&derivative, &[("x", x)]);
+            assert!((expected - actual).abs() < 1e-9, "∫ {} = {}", input, antiderivative);
+        }
+    }
+
+    #[test]
+    fn polynomial() {
+        let expr = parse_expr("3x^2 + 2x + 1");
+        assert_eq!(integrate(&expr, "x").unwrap(), simplify(&parse_expr("x^3 + x^2 + x")));
+    }
+
+    #[test]
+    fn reciprocal() {
+        let expr = parse_expr("1/x");
+        assert_eq!(integrate(&expr, "x").unwrap(), parse_expr("ln(x)"));
+    }
+
+    #[test]
+    fn linear_substitution() {
+        assert_integrates("e^(3x)");
+        assert_integrates("cos(2x + 1)");
+    }
+
+    #[test]
+    fn derivative_divides() {
+        assert_integrates("x e^(x^2)");
+        assert_integrates("sin(x) cos(x)");
+    }
+
+    #[test]
+    fn parts() {
+        assert_integrates("x^2 e^x");
+        assert_integrates("x sin(x)");
+    }
+
+    #[test]
+    fn rational() {
+        assert_integrates("1 / (x^2 + 3x + 2)");
+        assert_integrates("(x^3 + 1) / (x * (x + 1)^2)");
+        assert_integrates("1 / (x^2 + 1)");
+        assert_integrates("(2x + 3) / (x^2 + x + 1)");
+        assert_integrates("1 / ((x + 1) (x^2 + 4))");
+    }
+
+    #[test]
+    fn not_integrable() {
+        let expr = parse_expr("e^(x^2)");
+        assert_eq!(integrate(&expr, "x"), None);
+    }
+}
@@ -0,0 +1,69 @@
+//! Symbolic differentiation and integration.
+//!
+//! Both operations work on the [`Expr`] tree and return simplified expressions. Differentiation is
+//! implemented for every expression that can be built from the supported builtin functions, while
+//! integration is heuristic: a table of known antiderivatives is combined with linearity,
+//! substitution ("derivative-divides"), integration by parts and a few trigonometric identities.
+//! If none of these apply, integration fails and returns [`None`].
+//!
+//! ```
+//! use cas_compute::symbolic::{calculus::{diff, integrate}, expr::Expr};
+//! use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+//!
+//! let expr: Expr = Parser::new("3x^2").try_parse_full::<AstExpr>().unwrap().into();
+//! let derivative = diff(&expr, "x").unwrap();
+//! let antiderivative = integrate(&derivative, "x").unwrap();
+//! assert_eq!(antiderivative, expr);
+//! ```
+
+pub mod derivative;
+pub mod integral;
+
+pub use derivative::diff;
+pub use integral::integrate;
+
+use crate::primitive::int;
+use super::expr::{Expr, Primary};
+use std::sync::Arc;
+
+/// Creates an [`Expr`] containing the given integer.
+fn num(n: i32) -> Expr {
+    Expr::Primary(Primary::Integer(int(n)))
+}
+
+/// Creates an [`Expr`] containing the given symbol.
+fn sym(name: &str) -> Expr {
+    Expr::Primary(Primary::Symbol(name.to_string()))
+}
+
+/// Creates an [`Expr`] calling the given function with a single argument.
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::Primary(Primary::Call(name.to_string(), vec![arg]))
+}
+
+/// Creates an [`Expr`] raising `base` to the power of `exp`.
+fn pow(base: Expr, exp: Expr) -> Expr {
+    Expr::Exp(Arc::new(base), Arc::new(exp))
+}
+
+/// Returns true if the expression is the symbol `e`, Euler's number.
+fn is_euler(expr: &Expr) -> bool {
+    expr.as_symbol() == Some("e")
+}
+
+/// Numerically evaluates the given expression, with the given values assigned to symbols.
+#[cfg(test)]
+pub(crate) fn eval_at(expr: &Expr, vars: &[(&str, f64)]) -> f64 {
+    use crate::numerical::{ctxt::Ctxt, eval::Eval, value::Value};
+    use crate::primitive::float;
+    use cas_parser::parser::ast::expr::Expr as AstExpr;
+
+    let mut ctxt = Ctxt::default();
+    for (name, value) in vars {
+        ctxt.add_var(name, Value::Float(float(value)));
+    }
+    match AstExpr::from(expr.clone()).eval(&mut ctxt).unwrap().coerce_float() {
+        Value::Float(value) => value.to_f64(),
+        value => panic!("`{}` evaluated to a non-real value: {:?}", expr, value),
+    }
+}
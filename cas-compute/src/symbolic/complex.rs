@@ -0,0 +1,220 @@
+//! Rectangular form of complex expressions.
+//!
+//! The symbolic engine represents the imaginary unit as the symbol `i`. The functions in this
+//! module split an expression into its real and imaginary parts `a` and `b`, such that the
+//! expression is equal to `a + b*i`. Every symbol other than `i`, and every function call whose
+//! arguments do not contain `i`, is assumed to be real.
+//!
+//! ```
+//! use cas_compute::symbolic::{complex::rectangular, expr::Expr, simplify};
+//! use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+//!
+//! let parse = |input: &str| Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap());
+//!
+//! let result = rectangular(&parse("(1 + 2i) / (3 - i)")).unwrap();
+//! assert_eq!(result, simplify(&parse("1/10 + 7i/10")));
+//! ```
+
+use crate::primitive::int;
+use crate::symbolic::{expr::{Expr, Primary}, simplify::simplify};
+use std::sync::Arc;
+
+/// The largest exponent, in absolute value, of a complex power that will be expanded.
+const MAX_POWER: u32 = 64;
+
+/// Returns the integer `n` as an expression.
+fn num(n: i32) -> Expr {
+    Expr::Primary(Primary::Integer(int(n)))
+}
+
+/// Returns true if the expression is the integer zero.
+fn is_zero(expr: &Expr) -> bool {
+    expr.as_integer().is_some_and(|n| n.is_zero())
+}
+
+/// Adds two expressions, skipping terms that are zero.
+fn add(a: Expr, b: Expr) -> Expr {
+    if is_zero(&a) {
+        b
+    } else if is_zero(&b) {
+        a
+    } else {
+        a + b
+    }
+}
+
+/// Multiplies two expressions, returning zero if either of them is zero.
+fn mul(a: Expr, b: Expr) -> Expr {
+    if is_zero(&a) || is_zero(&b) {
+        num(0)
+    } else {
+        a * b
+    }
+}
+
+/// Multiplies two complex numbers given as real and imaginary parts.
+fn multiply((a, b): (Expr, Expr), (c, d): (Expr, Expr)) -> (Expr, Expr) {
+    // (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+    (
+        add(mul(a.clone(), c.clone()), -mul(b.clone(), d.clone())),
+        add(mul(a, d), mul(b, c)),
+    )
+}
+
+/// Raises a complex number given as real and imaginary parts to a non-negative integer power,
+/// simplifying the intermediate results to keep them small.
+fn power(z: (Expr, Expr), n: u32) -> (Expr, Expr) {
+    let simplified = |(a, b): (Expr, Expr)| (simplify(&a), simplify(&b));
+    let mut result = (num(1), num(0));
+    let mut base = z;
+    let mut n = n;
+    while n > 0 {
+        if n & 1 == 1 {
+            result = simplified(multiply(result, base.clone()));
+        }
+        n >>= 1;
+        if n > 0 {
+            base = simplified(multiply(base.clone(), base));
+        }
+    }
+    result
+}
+
+/// Computes the reciprocal of a complex number given as real and imaginary parts, by multiplying
+/// the numerator and denominator by the conjugate.
+fn reciprocal((a, b): (Expr, Expr)) -> (Expr, Expr) {
+    // 1 / (a + bi) = (a - bi) / (a^2 + b^2)
+    let denominator = Expr::Exp(
+        Arc::new(add(mul(a.clone(), a.clone()), mul(b.clone(), b.clone()))),
+        Arc::new(num(-1)),
+    );
+    (mul(a, denominator.clone()), mul(-b, denominator))
+}
+
+/// Splits the expression into real and imaginary parts, without simplifying them.
+pub(crate) fn split(expr: &Expr) -> Option<(Expr, Expr)> {
+    if !expr.contains_symbol("i") {
+        return Some((expr.clone(), num(0)));
+    }
+
+    match expr {
+        Expr::Primary(Primary::Symbol(_)) => Some((num(0), num(1))),
+        // a call with a complex argument
+        Expr::Primary(_) => None,
+        Expr::Add(terms) => terms.iter()
+            .try_fold((num(0), num(0)), |(a, b), term| {
+                let (c, d) = split(term)?;
+                Some((add(a, c), add(b, d)))
+            }),
+        Expr::Mul(factors) => factors.iter()
+            .try_fold((num(1), num(0)), |z, factor| Some(multiply(z, split(factor)?))),
+        Expr::Exp(base, exp) => {
+            let n = exp.as_integer()?.to_i32()?;
+            if n.unsigned_abs() > MAX_POWER {
+                return None;
+            }
+
+            let z = power(split(base)?, n.unsigned_abs());
+            if n < 0 {
+                Some(reciprocal(z))
+            } else {
+                Some(z)
+            }
+        },
+    }
+}
+
+/// Joins real and imaginary parts into the expression `re + im*i`, without simplifying it.
+pub(crate) fn join(re: Expr, im: Expr) -> Expr {
+    add(re, mul(im, Expr::Primary(Primary::Symbol("i".to_string()))))
+}
+
+/// Returns the real and imaginary parts of the expression, simplified.
+///
+/// Returns [`None`] if the expression cannot be written in rectangular form, such as when `i`
+/// appears in a function argument or in a non-integer power.
+pub fn parts(expr: &Expr) -> Option<(Expr, Expr)> {
+    let (re, im) = split(expr)?;
+    Some((simplify(&re), simplify(&im)))
+}
+
+/// Returns the expression in the rectangular form `a + b*i`, simplified.
+pub fn rectangular(expr: &Expr) -> Option<Expr> {
+    let (re, im) = split(expr)?;
+    Some(simplify(&join(re, im)))
+}
+
+/// Returns the real part of the expression.
+pub fn re(expr: &Expr) -> Option<Expr> {
+    parts(expr).map(|(re, _)| re)
+}
+
+/// Returns the imaginary part of the expression.
+pub fn im(expr: &Expr) -> Option<Expr> {
+    parts(expr).map(|(_, im)| im)
+}
+
+/// Returns the complex conjugate of the expression, in rectangular form.
+pub fn conj(expr: &Expr) -> Option<Expr> {
+    let (re, im) = split(expr)?;
+    Some(simplify(&join(re, -im)))
+}
+
+/// Returns the absolute value (modulus) of the expression, `sqrt(re^2 + im^2)`.
+pub fn abs(expr: &Expr) -> Option<Expr> {
+    let (re, im) = split(expr)?;
+    let modulus = add(mul(re.clone(), re), mul(im.clone(), im));
+    Some(simplify(&Expr::Primary(Primary::Call("sqrt".to_string(), vec![modulus]))))
+}
+
+/// Returns the argument of the expression, `atan2(im, re)`.
+pub fn arg(expr: &Expr) -> Option<Expr> {
+    let (re, im) = parts(expr)?;
+    Some(Expr::Primary(Primary::Call("atan2".to_string(), vec![im, re])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+    use pretty_assertions::assert_eq;
+
+    /// Parses the given expression.
+    fn parse(input: &str) -> Expr {
+        Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap())
+    }
+
+    #[test]
+    fn multiply_binomials() {
+        assert_eq!(rectangular(&parse("(1 + 2i) * (3 - i)")), Some(simplify(&parse("5 + 5i"))));
+    }
+
+    #[test]
+    fn divide_binomials() {
+        assert_eq!(rectangular(&parse("(1 + 2i) / (3 - i)")), Some(simplify(&parse("1/10 + 7i/10"))));
+        assert_eq!(rectangular(&parse("1 / i")), Some(simplify(&parse("-i"))));
+        assert_eq!(rectangular(&parse("x / (1 + i)")), Some(simplify(&parse("x/2 - x*i/2"))));
+    }
+
+    #[test]
+    fn powers() {
+        assert_eq!(rectangular(&parse("(1 + i)^2")), Some(simplify(&parse("2i"))));
+        assert_eq!(rectangular(&parse("(1 + i)^-2")), Some(simplify(&parse("-i/2"))));
+    }
+
+    #[test]
+    fn parts_of_symbolic() {
+        assert_eq!(re(&parse("(x + i)^2")), Some(simplify(&parse("x^2 - 1"))));
+        assert_eq!(im(&parse("(x + i)^2")), Some(simplify(&parse("2x"))));
+        assert_eq!(conj(&parse("x + 3i")), Some(simplify(&parse("x - 3i"))));
+        assert_eq!(abs(&parse("3 + 4i")), Some(simplify(&parse("5"))));
+        assert_eq!(arg(&parse("1 + i")), Some(parse("atan2(1, 1)")));
+    }
+
+    #[test]
+    fn not_rectangular() {
+        assert_eq!(parts(&parse("sin(i)")), None);
+        assert_eq!(parts(&parse("2^i")), None);
+    }
+}
@@ -0,0 +1,169 @@
+//! Solvers for first-order ODEs.
+
+use crate::symbolic::{
+    calculus::{diff, integrate},
+    expr::{Expr, Primary},
+    simplify::{fraction::make_fraction, simplify},
+};
+use super::{constant, divide, exp, is_zero, num, split_linear, sym, Equation, Error, General, Unknown};
+use std::sync::Arc;
+
+/// Integrates the given expression, returning [`Error::Integration`] on failure.
+fn try_integrate(expr: &Expr, var: &str) -> Result<Expr, Error> {
+    integrate(expr, var).ok_or_else(|| Error::Integration(simplify(expr)))
+}
+
+/// Solves the first-order ODE `expr = 0`, where `y(x)` and `y'(x)` have been replaced by symbols.
+pub(super) fn solve(unknown: &Unknown, expr: &Expr) -> Result<General, Error> {
+    let y = unknown.derivative(0);
+    let dy = unknown.derivative(1);
+    let x = &unknown.var;
+
+    // write the equation as `n * y' + m = 0`
+    let (n, m) = split_linear(expr, &dy).ok_or(Error::UnsupportedForm)?;
+    if is_zero(&n) {
+        return Err(Error::NotDifferential);
+    }
+
+    // y' = rhs
+    let rhs = simplify(&divide(-m.clone(), n.clone()));
+    if let Some((p, q)) = split_linear(&rhs, &y) {
+        if !q.contains_symbol(&y) {
+            return linear(unknown, &p, &q);
+        }
+    }
+
+    if let Some((f, g)) = separate(&rhs, x, &y) {
+        return separable(unknown, &f, &g);
+    }
+
+    exact(unknown, &m, &n)
+}
+
+/// Solves the linear ODE `y' = p(x) y + q(x)` using the integrating factor `e^(-∫p dx)`.
+fn linear(unknown: &Unknown, p: &Expr, q: &Expr) -> Result<General, Error> {
+    let x = &unknown.var;
+    let mu_exp = try_integrate(&-p.clone(), x)?;
+    let integrand = simplify(&(exp(mu_exp.clone()) * q.clone()));
+    let integral = try_integrate(&integrand, x)?;
+
+    // y = (∫ mu q dx + C1) / mu
+    let solution = simplify(&((integral + sym(&constant(1))) * exp(-mu_exp)));
+    Ok(General {
+        explicit: Some(solution.clone()),
+        implicit: Equation::new(sym(&unknown.derivative(0)), solution),
+    })
+}
+
+/// Splits `expr` into a product `f(x) g(y)`, if possible.
+fn separate(expr: &Expr, x: &str, y: &str) -> Option<(Expr, Expr)> {
+    if !expr.contains_symbol(x) {
+        return Some((num(1), expr.clone()));
+    }
+
+    match expr {
+        Expr::Mul(factors) => {
+            let mut f = Vec::new();
+            let mut g = Vec::new();
+            for factor in factors {
+                match (factor.contains_symbol(x), factor.contains_symbol(y)) {
+                    (true, true) => {
+                        let (fx, gy) = separate(factor, x, y)?;
+                        f.push(fx);
+                        g.push(gy);
+                    },
+                    (true, false) => f.push(factor.clone()),
+                    _ => g.push(factor.clone()),
+                }
+            }
+            Some((Expr::Mul(f.into()).downgrade(), Expr::Mul(g.into()).downgrade()))
+        },
+        // a^(u(x) + v(y)) = a^u(x) * a^v(y)
+        Expr::Exp(base, exponent) if !base.contains_symbol(x) && !base.contains_symbol(y) => {
+            let Expr::Add(terms) = &**exponent else {
+                return None;
+            };
+            let mut f = Vec::new();
+            let mut g = Vec::new();
+            for term in terms {
+                match (term.contains_symbol(x), term.contains_symbol(y)) {
+                    (true, true) => return None,
+                    (true, false) => f.push(term.clone()),
+                    _ => g.push(term.clone()),
+                }
+            }
+            Some((
+                Expr::Exp(base.clone(), Arc::new(Expr::Add(f.into()).downgrade())),
+                Expr::Exp(base.clone(), Arc::new(Expr::Add(g.into()).downgrade())),
+            ))
+        },
+        _ => None,
+    }
+}
+
+/// Solves the separable ODE `y' = f(x) g(y)` by integrating `∫ 1 / g(y) dy = ∫ f(x) dx + C1`.
+fn separable(unknown: &Unknown, f: &Expr, g: &Expr) -> Result<General, Error> {
+    let y = unknown.derivative(0);
+    let lhs = try_integrate(&make_fraction(num(1), g.clone()), &y)?;
+    let rhs = try_integrate(f, &unknown.var)?;
+    let implicit = Equation::new(lhs.clone(), simplify(&(rhs.clone() + sym(&constant(1)))));
+
+    // try to solve for `y`, if the left-hand side is `c * ln(y)` or `c * y^n`
+    let (coeff, kernel) = match lhs {
+        Expr::Mul(factors) => {
+            let (kernel, coeff): (Vec<_>, Vec<_>) = factors.into_iter()
+                .partition(|factor| factor.contains_symbol(&y));
+            (Expr::Mul(coeff.into()).downgrade(), Expr::Mul(kernel.into()).downgrade())
+        },
+        kernel => (num(1), kernel),
+    };
+    let explicit = match kernel {
+        Expr::Primary(Primary::Call(name, args)) if name == "ln" && args == [sym(&y)] => {
+            // c ln(y) = F(x) + C => y = C1 e^(F(x) / c)
+            let solution = simplify(&(sym(&constant(1)) * exp(divide(rhs, coeff))));
+            return Ok(General {
+                explicit: Some(solution.clone()),
+                implicit: Equation::new(sym(&y), solution),
+            });
+        },
+        Expr::Primary(Primary::Symbol(ref name)) if name == &y => {
+            Some(simplify(&divide(implicit.rhs.clone(), coeff)))
+        },
+        Expr::Exp(base, n) if *base == sym(&y) && !n.contains_symbol(&y) => {
+            // c y^n = F(x) + C => y = ((F(x) + C) / c)^(1/n)
+            Some(simplify(&Expr::Exp(
+                Arc::new(divide(implicit.rhs.clone(), coeff)),
+                Arc::new(make_fraction(num(1), Arc::unwrap_or_clone(n))),
+            )))
+        },
+        _ => None,
+    };
+
+    Ok(General { explicit, implicit })
+}
+
+/// Solves the exact ODE `m(x, y) + n(x, y) y' = 0`, where `∂m/∂y = ∂n/∂x`. The solution is
+/// `ψ(x, y) = C1`, where `∂ψ/∂x = m` and `∂ψ/∂y = n`.
+fn exact(unknown: &Unknown, m: &Expr, n: &Expr) -> Result<General, Error> {
+    let y = unknown.derivative(0);
+    let x = &unknown.var;
+
+    let m_y = diff(m, &y).ok_or(Error::UnsupportedForm)?;
+    let n_x = diff(n, x).ok_or(Error::UnsupportedForm)?;
+    if !is_zero(&simplify(&(m_y + -n_x))) {
+        return Err(Error::UnsupportedForm);
+    }
+
+    // ψ = ∫ m dx + h(y), where h'(y) = n - ∂/∂y ∫ m dx
+    let psi_x = try_integrate(m, x)?;
+    let h_y = simplify(&(n.clone() + -diff(&psi_x, &y).ok_or(Error::UnsupportedForm)?));
+    if h_y.contains_symbol(x) {
+        return Err(Error::UnsupportedForm);
+    }
+    let h = try_integrate(&h_y, &y)?;
+
+    Ok(General {
+        explicit: None,
+        implicit: Equation::new(simplify(&(psi_x + h)), sym(&constant(1))),
+    })
+}
@@ -0,0 +1,668 @@
+//! Symbolic solutions of ordinary differential equations (ODEs).
+//!
+//! The [`dsolve`] function accepts an ODE written in prime notation, such as `y'(x) == x y(x)` or
+//! `y''(x) + 3y'(x) + 2y(x) == 0`, and attempts to find its general solution. The following forms
+//! are currently supported:
+//!
+//! - First-order linear equations, `y' + p(x) y = q(x)`, solved with an integrating factor.
+//! - First-order separable equations, `y' = f(x) g(y)`.
+//! - First-order exact equations, `M(x, y) + N(x, y) y' = 0`, where `∂M/∂y = ∂N/∂x`.
+//! - Second-order linear equations with constant coefficients, `a y'' + b y' + c y = g(x)`. The
+//!   particular solution of non-homogeneous equations is found with variation of parameters.
+//!
+//! The general solution contains the constants of integration `C1` (and `C2` for second-order
+//! equations). If initial conditions are given, such as `y(0) == 1`, the constants are solved for
+//! and substituted into the solution.
+//!
+//! Solutions rely on the [`calculus`](super::calculus) module to integrate; if an integral along
+//! the way cannot be found, solving fails with [`Error::Integration`].
+//!
+//! ```
+//! use cas_compute::symbolic::dsolve::{dsolve, Equation, Solution};
+//! use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+//!
+//! let parse = |input: &str| -> Equation {
+//!     Parser::new(input).try_parse_full::<AstExpr>().unwrap().into()
+//! };
+//!
+//! // y' = y, y(0) = 2
+//! let solution = dsolve(&parse("y'(x) == y(x)"), &[parse("y(0) == 2")]).unwrap();
+//! assert_eq!(solution.to_string(), "y(x) = 2 * e^x");
+//! ```
+
+mod first_order;
+mod second_order;
+
+use crate::primitive::int;
+use crate::symbolic::{
+    calculus::derivative::diff_n,
+    expr::{Expr, Primary},
+    simplify::{fraction::make_fraction, simplify},
+};
+use cas_parser::parser::{ast::expr::Expr as AstExpr, token::op::BinOpKind};
+use std::{fmt, sync::Arc};
+
+/// An equation between two expressions, `lhs = rhs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    /// The left-hand side of the equation.
+    pub lhs: Expr,
+
+    /// The right-hand side of the equation.
+    pub rhs: Expr,
+}
+
+impl Equation {
+    /// Creates a new equation.
+    pub fn new(lhs: Expr, rhs: Expr) -> Self {
+        Self { lhs, rhs }
+    }
+
+    /// Returns the expression `lhs - rhs`, which is zero when the equation holds.
+    pub fn difference(&self) -> Expr {
+        self.lhs.clone() + -self.rhs.clone()
+    }
+}
+
+/// Converts an AST expression into an [`Equation`]. An expression of the form `lhs == rhs` is
+/// split into its two sides; any other expression `expr` is treated as the equation `expr = 0`.
+impl From<AstExpr> for Equation {
+    fn from(expr: AstExpr) -> Self {
+        match expr {
+            AstExpr::Binary(bin) if bin.op.kind == BinOpKind::Eq => {
+                Self::new(Expr::from(*bin.lhs), Expr::from(*bin.rhs))
+            },
+            AstExpr::Paren(paren) => Self::from(paren.into_innermost()),
+            expr => Self::new(Expr::from(expr), num(0)),
+        }
+    }
+}
+
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.lhs, self.rhs)
+    }
+}
+
+/// The solution of an ODE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    /// The unknown function is given explicitly, as in `y(x) = expr`.
+    Explicit {
+        /// The name of the unknown function.
+        func: String,
+
+        /// The name of the independent variable.
+        var: String,
+
+        /// The expression the function is equal to.
+        expr: Expr,
+    },
+
+    /// The solution is given implicitly by an equation relating the independent variable and the
+    /// unknown function. The unknown function is represented by the symbol with its name.
+    Implicit {
+        /// The name of the unknown function.
+        func: String,
+
+        /// The name of the independent variable.
+        var: String,
+
+        /// The equation that the function satisfies.
+        equation: Equation,
+    },
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Explicit { func, var, expr } => write!(f, "{}({}) = {}", func, var, expr),
+            Self::Implicit { equation, .. } => write!(f, "{}", equation),
+        }
+    }
+}
+
+/// An error that can occur while solving an ODE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The equation does not contain the derivative of any function.
+    NotDifferential,
+
+    /// The equation contains more than one unknown function.
+    MultipleFunctions(String, String),
+
+    /// The unknown function must be called with the same single symbol as its argument everywhere,
+    /// as in `y(x)`.
+    InvalidArgument(String),
+
+    /// Only first and second-order equations are supported.
+    UnsupportedOrder(usize),
+
+    /// The equation is not in one of the supported forms.
+    UnsupportedForm,
+
+    /// An integral needed to solve the equation could not be found.
+    Integration(Expr),
+
+    /// An initial condition is not of the form `y(x0) = value` or `y'(x0) = value`.
+    InvalidCondition(Equation),
+
+    /// The number of initial conditions does not match the order of the equation.
+    ConditionCount {
+        /// The order of the equation.
+        expected: usize,
+
+        /// The number of initial conditions given.
+        found: usize,
+    },
+
+    /// The initial conditions do not determine the constants of integration.
+    InconsistentConditions,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotDifferential => write!(f, "the equation does not contain any derivatives"),
+            Self::MultipleFunctions(a, b) => {
+                write!(f, "the equation contains more than one unknown function: `{}` and `{}`", a, b)
+            },
+            Self::InvalidArgument(func) => write!(
+                f,
+                "the unknown function `{}` must be called with the same variable everywhere, as in `{}(x)`",
+                func,
+                func,
+            ),
+            Self::UnsupportedOrder(order) => {
+                write!(f, "cannot solve equations of order {}; only orders 1 and 2 are supported", order)
+            },
+            Self::UnsupportedForm => write!(f, "the equation is not in a supported form"),
+            Self::Integration(expr) => write!(f, "could not integrate `{}`", expr),
+            Self::InvalidCondition(equation) => write!(f, "invalid initial condition `{}`", equation),
+            Self::ConditionCount { expected, found } => {
+                write!(f, "expected {} initial condition(s), found {}", expected, found)
+            },
+            Self::InconsistentConditions => {
+                write!(f, "the initial conditions do not determine the constants of integration")
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The general solution of an ODE, before initial conditions are applied.
+struct General {
+    /// The explicit form of the solution, if one was found.
+    explicit: Option<Expr>,
+
+    /// The solution as an equation in the independent variable and the unknown function (as a
+    /// symbol), which is linear in the constants of integration.
+    implicit: Equation,
+}
+
+/// The unknown function of an ODE, and the independent variable it depends on.
+struct Unknown {
+    /// The name of the unknown function.
+    func: String,
+
+    /// The name of the independent variable.
+    var: String,
+
+    /// The highest derivative of the function that appears in the equation.
+    order: usize,
+}
+
+impl Unknown {
+    /// Returns the name of the symbol that replaces the `n`th derivative of the function.
+    fn derivative(&self, n: usize) -> String {
+        format!("{}{}", self.func, "'".repeat(n))
+    }
+
+    /// Returns the call `func^(n)(var)`.
+    fn call(&self, n: usize) -> Expr {
+        Expr::Primary(Primary::Call(self.derivative(n), vec![sym(&self.var)]))
+    }
+}
+
+/// Returns the name of the `n`th constant of integration.
+fn constant(n: usize) -> String {
+    format!("C{}", n)
+}
+
+/// Creates an [`Expr`] containing the given integer.
+fn num(n: i32) -> Expr {
+    Expr::Primary(Primary::Integer(int(n)))
+}
+
+/// Creates an [`Expr`] containing the given symbol.
+fn sym(name: &str) -> Expr {
+    Expr::Primary(Primary::Symbol(name.to_string()))
+}
+
+/// Creates an [`Expr`] raising `e` to the power of `exp`.
+fn exp(exp: Expr) -> Expr {
+    Expr::Exp(Arc::new(sym("e")), Arc::new(exp))
+}
+
+/// Returns true if the expression is the integer zero.
+fn is_zero(expr: &Expr) -> bool {
+    match expr {
+        Expr::Primary(Primary::Integer(n)) => n.is_zero(),
+        Expr::Primary(Primary::Float(n)) => n.is_zero(),
+        _ => false,
+    }
+}
+
+/// Divides `lhs` by `rhs`, avoiding a division if `rhs` is `1` or `-1`.
+fn divide(lhs: Expr, rhs: Expr) -> Expr {
+    match rhs.as_integer() {
+        Some(n) if *n == 1 => lhs,
+        Some(n) if *n == -1 => -lhs,
+        _ => make_fraction(lhs, rhs),
+    }
+}
+
+/// Splits the given simplified expression into the form `coeff * symbol + rest`, where `coeff`
+/// and `rest` do not contain the symbol. Returns [`None`] if the expression is not linear in the
+/// symbol.
+fn split_linear(expr: &Expr, symbol: &str) -> Option<(Expr, Expr)> {
+    let terms = match expr {
+        Expr::Add(terms) => terms.as_slice(),
+        expr => std::slice::from_ref(expr),
+    };
+
+    let mut coeff = Vec::new();
+    let mut rest = Vec::new();
+    for term in terms {
+        if !term.contains_symbol(symbol) {
+            rest.push(term.clone());
+            continue;
+        }
+
+        match term {
+            Expr::Primary(Primary::Symbol(name)) if name == symbol => coeff.push(num(1)),
+            Expr::Mul(factors) => {
+                let mut dependent = factors.iter()
+                    .enumerate()
+                    .filter(|(_, factor)| factor.contains_symbol(symbol));
+                let (idx, factor) = dependent.next()?;
+                if dependent.next().is_some() || factor.as_symbol() != Some(symbol) {
+                    return None;
+                }
+
+                let mut others = factors.to_vec();
+                others.remove(idx);
+                coeff.push(Expr::Mul(others.into()).downgrade());
+            },
+            _ => return None,
+        }
+    }
+
+    Some((
+        simplify(&Expr::Add(coeff.into()).downgrade()),
+        simplify(&Expr::Add(rest.into()).downgrade()),
+    ))
+}
+
+/// Finds the unknown function in the given equation, and checks that it is always called with the
+/// same variable.
+fn find_unknown(expr: &Expr) -> Result<Unknown, Error> {
+    fn visit(expr: &Expr, found: &mut Option<Unknown>) -> Result<(), Error> {
+        match expr {
+            Expr::Primary(Primary::Call(name, args)) => {
+                let base = name.trim_end_matches('\'');
+                let order = name.len() - base.len();
+                if order > 0 {
+                    let var = match args.as_slice() {
+                        [Expr::Primary(Primary::Symbol(var))] => var,
+                        _ => return Err(Error::InvalidArgument(base.to_string())),
+                    };
+
+                    match found {
+                        Some(unknown) if unknown.func != base => {
+                            return Err(Error::MultipleFunctions(unknown.func.clone(), base.to_string()));
+                        },
+                        Some(unknown) if &unknown.var != var => {
+                            return Err(Error::InvalidArgument(base.to_string()));
+                        },
+                        Some(unknown) => unknown.order = unknown.order.max(order),
+                        None => {
+                            *found = Some(Unknown {
+                                func: base.to_string(),
+                                var: var.clone(),
+                                order,
+                            });
+                        },
+                    }
+                }
+
+                args.iter().try_for_each(|arg| visit(arg, found))
+            },
+            Expr::Primary(_) => Ok(()),
+            Expr::Add(exprs) | Expr::Mul(exprs) => exprs.iter().try_for_each(|expr| visit(expr, found)),
+            Expr::Exp(lhs, rhs) => {
+                visit(lhs, found)?;
+                visit(rhs, found)
+            },
+        }
+    }
+
+    let mut found = None;
+    visit(expr, &mut found)?;
+    found.ok_or(Error::NotDifferential)
+}
+
+/// Returns true if the expression contains a call to the given function, or any of its
+/// derivatives.
+fn calls_function(expr: &Expr, func: &str) -> bool {
+    match expr {
+        Expr::Primary(Primary::Call(name, args)) => {
+            name.trim_end_matches('\'') == func || args.iter().any(|arg| calls_function(arg, func))
+        },
+        Expr::Primary(_) => false,
+        Expr::Add(exprs) | Expr::Mul(exprs) => exprs.iter().any(|expr| calls_function(expr, func)),
+        Expr::Exp(lhs, rhs) => calls_function(lhs, func) || calls_function(rhs, func),
+    }
+}
+
+/// Parses an initial condition of the form `y^(n)(x0) = value`, returning `n`, `x0` and `value`.
+fn parse_condition(unknown: &Unknown, condition: &Equation) -> Result<(usize, Expr, Expr), Error> {
+    let parse_side = |side: &Expr| {
+        if let Expr::Primary(Primary::Call(name, args)) = side {
+            let base = name.trim_end_matches('\'');
+            if base == unknown.func && args.len() == 1 {
+                return Some((name.len() - base.len(), args[0].clone()));
+            }
+        }
+        None
+    };
+
+    let ((n, at), value) = parse_side(&condition.lhs)
+        .map(|parsed| (parsed, condition.rhs.clone()))
+        .or_else(|| parse_side(&condition.rhs).map(|parsed| (parsed, condition.lhs.clone())))
+        .ok_or_else(|| Error::InvalidCondition(condition.clone()))?;
+
+    if n >= unknown.order || at.contains_symbol(&unknown.var) {
+        return Err(Error::InvalidCondition(condition.clone()));
+    }
+
+    Ok((n, at, value))
+}
+
+/// Solves a system of linear equations for the constants of integration. Each given expression is
+/// assumed to be equal to zero, and linear in the constants.
+fn solve_constants(equations: &[Expr]) -> Result<Vec<Expr>, Error> {
+    // split each equation into `a1 * C1 + a2 * C2 + ... = b`
+    let n = equations.len();
+    let mut matrix = Vec::with_capacity(n);
+    let mut rhs = Vec::with_capacity(n);
+    for equation in equations {
+        let mut row = Vec::with_capacity(n);
+        let mut rest = simplify(equation);
+        for i in 1..=n {
+            let (coeff, remaining) = split_linear(&rest, &constant(i))
+                .ok_or(Error::InconsistentConditions)?;
+            row.push(coeff);
+            rest = remaining;
+        }
+        matrix.push(row);
+        rhs.push(-rest);
+    }
+
+    // solve with Cramer's rule
+    let det = |m: &[Vec<Expr>]| match m {
+        [row] => row[0].clone(),
+        [a, b] => a[0].clone() * b[1].clone() + -(a[1].clone() * b[0].clone()),
+        _ => unreachable!("at most two constants of integration"),
+    };
+    let denominator = simplify(&det(&matrix));
+    if is_zero(&denominator) {
+        return Err(Error::InconsistentConditions);
+    }
+
+    Ok((0..n)
+        .map(|col| {
+            let replaced = matrix.iter()
+                .zip(&rhs)
+                .map(|(row, b)| {
+                    let mut row = row.clone();
+                    row[col] = b.clone();
+                    row
+                })
+                .collect::<Vec<_>>();
+            simplify(&divide(det(&replaced), denominator.clone()))
+        })
+        .collect())
+}
+
+/// Applies the given initial conditions to the general solution.
+fn apply_conditions(
+    unknown: &Unknown,
+    general: General,
+    conditions: &[Equation],
+) -> Result<Solution, Error> {
+    let equations = conditions.iter()
+        .map(|condition| {
+            let (n, at, value) = parse_condition(unknown, condition)?;
+            let var = sym(&unknown.var);
+            if n == 0 {
+                let implicit = general.implicit.difference()
+                    .substitute(&sym(&unknown.func), &value)
+                    .substitute(&var, &at);
+                Ok(implicit)
+            } else {
+                let explicit = general.explicit.as_ref().ok_or(Error::UnsupportedForm)?;
+                let derivative = diff_n(explicit, &unknown.var, n).ok_or(Error::UnsupportedForm)?;
+                Ok(derivative.substitute(&var, &at) + -value)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut general = general;
+    if !equations.is_empty() {
+        let values = solve_constants(&equations)?;
+        for (i, value) in values.iter().enumerate() {
+            let constant = sym(&constant(i + 1));
+            general.explicit = general.explicit.map(|expr| simplify(&expr.substitute(&constant, value)));
+            general.implicit = Equation::new(
+                simplify(&general.implicit.lhs.substitute(&constant, value)),
+                simplify(&general.implicit.rhs.substitute(&constant, value)),
+            );
+        }
+    }
+
+    Ok(match general.explicit {
+        Some(expr) => Solution::Explicit {
+            func: unknown.func.clone(),
+            var: unknown.var.clone(),
+            expr,
+        },
+        None => Solution::Implicit {
+            func: unknown.func.clone(),
+            var: unknown.var.clone(),
+            equation: general.implicit,
+        },
+    })
+}
+
+/// Solves the given ordinary differential equation, optionally applying initial conditions.
+///
+/// The unknown function must be written in prime notation, and called with the independent
+/// variable, as in `y(x)`, `y'(x)` and `y''(x)`. The general solution contains the constants of
+/// integration `C1` and `C2`. If initial conditions are given, there must be exactly one for each
+/// constant (i.e., as many as the order of the equation), and they must be of the form
+/// `y(x0) = value` or `y'(x0) = value`.
+///
+/// See the [module-level documentation](self) for the supported forms of equations.
+pub fn dsolve(ode: &Equation, conditions: &[Equation]) -> Result<Solution, Error> {
+    let difference = ode.difference();
+    let unknown = find_unknown(&difference)?;
+    if unknown.order > 2 {
+        return Err(Error::UnsupportedOrder(unknown.order));
+    }
+
+    if !conditions.is_empty() && conditions.len() != unknown.order {
+        return Err(Error::ConditionCount {
+            expected: unknown.order,
+            found: conditions.len(),
+        });
+    }
+
+    // replace `y(x)`, `y'(x)`, ... with symbols, so that the equation can be manipulated
+    // algebraically
+    let mut expr = difference;
+    for n in 0..=unknown.order {
+        expr = expr.substitute(&unknown.call(n), &sym(&unknown.derivative(n)));
+    }
+    if calls_function(&expr, &unknown.func) {
+        // the function is still called with some other argument, as in `y(2x)`
+        return Err(Error::InvalidArgument(unknown.func.clone()));
+    }
+    let expr = simplify(&expr);
+
+    let general = match unknown.order {
+        1 => first_order::solve(&unknown, &expr)?,
+        _ => second_order::solve(&unknown, &expr)?,
+    };
+    apply_conditions(&unknown, general, conditions)
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::Parser;
+    use super::*;
+    use super::super::calculus::eval_at;
+
+    /// Parses the given equation.
+    fn parse(input: &str) -> Equation {
+        Parser::new(input).try_parse_full::<AstExpr>().unwrap().into()
+    }
+
+    /// Solves the given ODE with initial conditions, returning the explicit solution.
+    fn solve_explicit(ode: &str, conditions: &[&str]) -> Expr {
+        let conditions = conditions.iter().map(|c| parse(c)).collect::<Vec<_>>();
+        match dsolve(&parse(ode), &conditions).unwrap() {
+            Solution::Explicit { expr, .. } => expr,
+            solution => panic!("expected explicit solution, got `{}`", solution),
+        }
+    }
+
+    /// Checks that the given expression agrees with `expected(x)` at a few points.
+    fn assert_matches(expr: &Expr, expected: impl Fn(f64) -> f64) {
+        for x in [0.0, 0.4, 1.3, 2.0] {
+            let actual = eval_at(expr, &[("x", x)]);
+            assert!(
+                (actual - expected(x)).abs() < 1e-9 * expected(x).abs().max(1.0),
+                "y({}) = {}, expected {}; solution: {}",
+                x, actual, expected(x), expr,
+            );
+        }
+    }
+
+    #[test]
+    fn exponential_growth() {
+        let expr = solve_explicit("y'(x) == y(x)", &["y(0) == 2"]);
+        assert_matches(&expr, |x| 2.0 * x.exp());
+    }
+
+    #[test]
+    fn general_solution() {
+        let solution = dsolve(&parse("y'(x) == 3y(x)"), &[]).unwrap();
+        let Solution::Explicit { expr, .. } = solution else {
+            panic!("expected explicit solution");
+        };
+        for c in [-1.0, 2.5] {
+            let actual = eval_at(&expr, &[("x", 0.5), ("C1", c)]);
+            assert!((actual - c * 1.5f64.exp()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn first_order_linear() {
+        // integrating factor e^(x^2)
+        let expr = solve_explicit("y'(x) + 2x y(x) == x", &["y(0) == 1"]);
+        assert_matches(&expr, |x| 0.5 + 0.5 * (-x * x).exp());
+    }
+
+    #[test]
+    fn first_order_linear_variable_coefficient() {
+        // x y' + y = x^2, y(1) = 1 => y = x^2 / 3 + 2 / (3x)
+        let expr = solve_explicit("x y'(x) + y(x) == x^2", &["y(1) == 1"]);
+        for x in [0.5, 1.0, 2.0] {
+            let expected = x * x / 3.0 + 2.0 / (3.0 * x);
+            assert!((eval_at(&expr, &[("x", x)]) - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn separable() {
+        // y' = x y^2, y(0) = 1 => y = 2 / (2 - x^2)
+        let expr = solve_explicit("y'(x) == x y(x)^2", &["y(0) == 1"]);
+        assert_matches(&expr, |x| 2.0 / (2.0 - x * x));
+    }
+
+    #[test]
+    fn exact() {
+        // 2xy + (x^2 + 3y^2) y' = 0 => x^2 y + y^3 = C1
+        let solution = dsolve(&parse("2x y(x) + (x^2 + 3y(x)^2) y'(x) == 0"), &[parse("y(1) == 1")])
+            .unwrap();
+        let Solution::Implicit { equation, .. } = solution else {
+            panic!("expected implicit solution");
+        };
+        let at = |x: f64, y: f64| {
+            eval_at(&equation.lhs, &[("x", x), ("y", y)]) - eval_at(&equation.rhs, &[("x", x), ("y", y)])
+        };
+        assert!(at(1.0, 1.0).abs() < 1e-9);
+
+        // (2, y), where 4y + y^3 = 2
+        let y = 0.4734658077291262;
+        assert!(at(2.0, y).abs() < 1e-6);
+    }
+
+    #[test]
+    fn second_order_distinct_roots() {
+        let expr = solve_explicit("y''(x) + 3y'(x) + 2y(x) == 0", &["y(0) == 1", "y'(0) == 0"]);
+        assert_matches(&expr, |x| 2.0 * (-x).exp() - (-2.0 * x).exp());
+    }
+
+    #[test]
+    fn second_order_repeated_root() {
+        let expr = solve_explicit("y''(x) - 4y'(x) + 4y(x) == 0", &["y(0) == 1", "y'(0) == 3"]);
+        assert_matches(&expr, |x| (1.0 + x) * (2.0 * x).exp());
+    }
+
+    #[test]
+    fn second_order_complex_roots() {
+        let expr = solve_explicit("y''(x) + 2y'(x) + 5y(x) == 0", &["y(0) == 0", "y'(0) == 2"]);
+        assert_matches(&expr, |x| (-x).exp() * (2.0 * x).sin());
+    }
+
+    #[test]
+    fn second_order_irrational_roots() {
+        // r^2 - 2 = 0 => y = cosh(sqrt(2) x)
+        let expr = solve_explicit("y''(x) == 2y(x)", &["y(0) == 1", "y'(0) == 0"]);
+        assert_matches(&expr, |x| (2f64.sqrt() * x).cosh());
+    }
+
+    #[test]
+    fn second_order_non_homogeneous() {
+        // y'' + y = x, y(0) = 0, y'(0) = 0 => y = x - sin(x)
+        let expr = solve_explicit("y''(x) + y(x) == x", &["y(0) == 0", "y'(0) == 0"]);
+        assert_matches(&expr, |x| x - x.sin());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(dsolve(&parse("y(x) == x"), &[]), Err(Error::NotDifferential));
+        assert_eq!(dsolve(&parse("y'''(x) == y(x)"), &[]), Err(Error::UnsupportedOrder(3)));
+        assert_eq!(
+            dsolve(&parse("y'(x) == z'(x)"), &[]),
+            Err(Error::MultipleFunctions("y".to_string(), "z".to_string())),
+        );
+        assert_eq!(
+            dsolve(&parse("y''(x) == y(x)"), &[parse("y(0) == 1")]),
+            Err(Error::ConditionCount { expected: 2, found: 1 }),
+        );
+    }
+}
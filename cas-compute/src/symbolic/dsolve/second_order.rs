@@ -0,0 +1,130 @@
+//! Solvers for second-order linear ODEs with constant coefficients.
+
+use crate::symbolic::{
+    calculus::integrate,
+    expr::{Expr, Primary},
+    simplify::{fraction::{as_rational, rational_expr, sqrt_rational}, simplify},
+};
+use rug::Rational;
+use super::{
+    constant,
+    divide,
+    exp,
+    is_zero,
+    num,
+    split_linear,
+    sym,
+    Equation,
+    Error,
+    General,
+    Unknown,
+};
+
+/// Integrates the given expression, returning [`Error::Integration`] on failure.
+fn try_integrate(expr: &Expr, var: &str) -> Result<Expr, Error> {
+    let expr = simplify(expr);
+    integrate(&expr, var).ok_or(Error::Integration(expr))
+}
+
+/// Returns `e^(r x)`, or `1` if `r` is zero.
+fn exp_times(r: &Expr, x: &Expr) -> Expr {
+    if is_zero(r) {
+        num(1)
+    } else {
+        exp(r.clone() * x.clone())
+    }
+}
+
+/// Solves the ODE `expr = 0`, where `y(x)`, `y'(x)` and `y''(x)` have been replaced by symbols.
+///
+/// The equation must have the form `a y'' + b y' + c y + g(x) = 0`, where `a`, `b` and `c` are
+/// rational constants. The homogeneous solution is found from the roots of the characteristic
+/// polynomial `a r^2 + b r + c`, and the particular solution with variation of parameters.
+pub(super) fn solve(unknown: &Unknown, expr: &Expr) -> Result<General, Error> {
+    let x = sym(&unknown.var);
+
+    let (a, rest) = split_linear(expr, &unknown.derivative(2)).ok_or(Error::UnsupportedForm)?;
+    let (b, rest) = split_linear(&rest, &unknown.derivative(1)).ok_or(Error::UnsupportedForm)?;
+    let (c, g) = split_linear(&rest, &unknown.derivative(0)).ok_or(Error::UnsupportedForm)?;
+    let (Some(a), Some(b), Some(c)) = (as_rational(&a), as_rational(&b), as_rational(&c)) else {
+        return Err(Error::UnsupportedForm);
+    };
+    if a == 0 {
+        return Err(Error::UnsupportedForm);
+    }
+    if g.contains_symbol(&unknown.derivative(0)) {
+        return Err(Error::UnsupportedForm);
+    }
+
+    // y'' + (b / a) y' + (c / a) y = h(x)
+    let h = simplify(&divide(-g, rational_expr(&a)));
+    let discriminant = Rational::from(&b * &b) - Rational::from(4 * &a) * &c;
+    let two_a = Rational::from(2 * &a);
+    let alpha = rational_expr(&(Rational::from(-&b) / &two_a));
+
+    let (y1, y2, particular) = if discriminant == 0 {
+        // repeated root r: y1 = e^(rx), y2 = x e^(rx), W = e^(2rx)
+        let y1 = exp_times(&alpha, &x);
+        let y2 = x.clone() * y1.clone();
+        let particular = if is_zero(&h) {
+            None
+        } else {
+            let damped = exp_times(&-alpha.clone(), &x) * h.clone();
+            Some(
+                -(y1.clone() * try_integrate(&(x.clone() * damped.clone()), &unknown.var)?)
+                    + y2.clone() * try_integrate(&damped, &unknown.var)?,
+            )
+        };
+        (y1, y2, particular)
+    } else if discriminant > 0 {
+        // distinct real roots r1, r2: y1 = e^(r1 x), y2 = e^(r2 x), W = (r2 - r1) e^((r1 + r2) x)
+        let offset = simplify(&divide(sqrt_rational(&discriminant), rational_expr(&two_a)));
+        let r1 = simplify(&(alpha.clone() + offset.clone()));
+        let r2 = simplify(&(alpha + -offset.clone()));
+        let y1 = exp_times(&r1, &x);
+        let y2 = exp_times(&r2, &x);
+        let particular = if is_zero(&h) {
+            None
+        } else {
+            let first = try_integrate(&(exp_times(&-r1.clone(), &x) * h.clone()), &unknown.var)?;
+            let second = try_integrate(&(exp_times(&-r2.clone(), &x) * h.clone()), &unknown.var)?;
+
+            // r1 - r2 = 2 offset
+            Some(divide(
+                y1.clone() * first + -(y2.clone() * second),
+                num(2) * offset,
+            ))
+        };
+        (y1, y2, particular)
+    } else {
+        // complex roots α ± βi: y1 = e^(αx) cos(βx), y2 = e^(αx) sin(βx), W = β e^(2αx)
+        let beta = sqrt_rational(&(Rational::from(-&discriminant) / two_a.square()));
+        let beta_x = simplify(&(beta.clone() * x.clone()));
+        let cos = Expr::Primary(Primary::Call("cos".to_string(), vec![beta_x.clone()]));
+        let sin = Expr::Primary(Primary::Call("sin".to_string(), vec![beta_x]));
+        let growth = exp_times(&alpha, &x);
+        let y1 = growth.clone() * cos.clone();
+        let y2 = growth * sin.clone();
+        let particular = if is_zero(&h) {
+            None
+        } else {
+            let damped = exp_times(&-alpha.clone(), &x) * h.clone();
+            let first = try_integrate(&(damped.clone() * sin), &unknown.var)?;
+            let second = try_integrate(&(damped * cos), &unknown.var)?;
+            Some(divide(y2.clone() * second + -(y1.clone() * first), beta))
+        };
+        (y1, y2, particular)
+    };
+
+    let homogeneous = sym(&constant(1)) * y1 + sym(&constant(2)) * y2;
+    let solution = simplify(&match particular {
+        Some(particular) => homogeneous + particular,
+        None => homogeneous,
+    });
+
+    Ok(General {
+        explicit: Some(solution.clone()),
+        implicit: Equation::new(sym(&unknown.func), solution),
+    })
+}
+
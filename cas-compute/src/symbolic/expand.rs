@@ -0,0 +1,114 @@
+//! Expansion of products and powers of sums.
+//!
+//! The simplifier distributes products over sums, but leaves powers of sums such as `(x + 1)^2`
+//! intact, since they are usually simpler than their expansions. The [`expand`] function multiplies
+//! out every product and positive integer power of a sum, then simplifies the result to combine
+//! like terms.
+//!
+//! ```
+//! use cas_compute::symbolic::{expand, expr::Expr, simplify};
+//! use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+//!
+//! let parse = |input: &str| Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap());
+//!
+//! let result = expand(&parse("(x + 1)^2"));
+//! assert_eq!(result, simplify(&parse("x^2 + 2x + 1")));
+//! ```
+
+use crate::symbolic::{expr::{Expr, Primary}, simplify::simplify};
+use std::sync::Arc;
+
+/// The maximum number of terms that may be produced by expanding a single product. Products that
+/// would expand into more terms are left as they are.
+const MAX_TERMS: usize = 1 << 12;
+
+/// Expands all products and positive integer powers of sums in the expression, and simplifies the
+/// result.
+pub fn expand(expr: &Expr) -> Expr {
+    simplify(&expand_terms(&simplify(expr)))
+}
+
+/// Returns the terms of the expression, if it is a sum, or the expression itself otherwise.
+fn terms(expr: Expr) -> Vec<Expr> {
+    match expr {
+        Expr::Add(terms) => terms.into_vec(),
+        expr => vec![expr],
+    }
+}
+
+/// Multiplies out the product of the given factors, if the result has at most [`MAX_TERMS`] terms.
+fn multiply_out(factors: Vec<Expr>) -> Expr {
+    let count = factors.iter()
+        .map(|factor| match factor {
+            Expr::Add(terms) => terms.len(),
+            _ => 1,
+        })
+        .try_fold(1usize, |count, n| count.checked_mul(n).filter(|&count| count <= MAX_TERMS));
+    if count.is_none() {
+        return Expr::Mul(factors.into());
+    }
+
+    let products = factors.into_iter()
+        .fold(vec![Vec::new()], |products, factor| {
+            let terms = terms(factor);
+            products.iter()
+                .flat_map(|product| terms.iter().map(move |term| {
+                    let mut product = product.clone();
+                    product.push(term.clone());
+                    product
+                }))
+                .collect()
+        });
+    Expr::Add(products.into_iter().map(|product| Expr::Mul(product.into()).downgrade()).collect())
+        .downgrade()
+}
+
+/// Expands the given expression, without simplifying the result.
+fn expand_terms(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Primary(Primary::Call(name, args)) => Expr::Primary(Primary::Call(
+            name.clone(),
+            args.iter().map(expand_terms).collect(),
+        )),
+        Expr::Primary(_) => expr.clone(),
+        Expr::Add(terms) => Expr::Add(
+            terms.iter().flat_map(|term| self::terms(expand_terms(term))).collect()
+        ),
+        Expr::Mul(factors) => multiply_out(factors.iter().map(expand_terms).collect()),
+        Expr::Exp(base, exp) => {
+            let base = expand_terms(base);
+            let exp = expand_terms(exp);
+            match (&base, exp.as_integer().and_then(|n| n.to_usize())) {
+                (Expr::Add(_), Some(n)) if n > 1 => multiply_out(vec![base; n]),
+                _ => Expr::Exp(Arc::new(base), Arc::new(exp)),
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
+    use super::*;
+
+    /// Parses the given expression.
+    fn parse(input: &str) -> Expr {
+        Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap())
+    }
+
+    #[test]
+    fn binomial() {
+        assert_eq!(expand(&parse("(x + 1)^3")), simplify(&parse("x^3 + 3x^2 + 3x + 1")));
+    }
+
+    #[test]
+    fn product_of_sums() {
+        assert_eq!(expand(&parse("(x + 1) * (x - 1)")), simplify(&parse("x^2 - 1")));
+        assert_eq!(expand(&parse("(a + b) * (a - b) + b^2")), simplify(&parse("a^2")));
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(expand(&parse("sin((x + 1)^2)")), simplify(&parse("sin(x^2 + 2x + 1)")));
+    }
+}
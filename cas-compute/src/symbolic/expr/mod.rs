@@ -47,8 +47,20 @@
 //!
 //! The [`PartialEq`] and [`Eq`] implementations for [`Expr`] implement **strict equality**, not
 //! semantic equality.
+//!
+//! # Canonical order
+//!
+//! The [`Ord`] implementation for [`Expr`] defines a total order that is consistent with strict
+//! equality. The terms / factors of every sum / product are stored in this order, in an interned
+//! [`Operands`] list that is shared by every strictly equal sum / product. Strictly equal sums and
+//! products therefore point to the same list, so they can be compared and hashed in constant time.
+//! The [`Hash`] implementation is also consistent with strict equality, allowing like terms and
+//! factors to be grouped with a [`HashMap`] rather than by comparing every pair of terms / factors.
+//!
+//! [`HashMap`]: std::collections::HashMap
 
 mod iter;
+mod operands;
 
 use crate::primitive::{float_from_str, from_str_radix, int, int_from_str};
 use cas_parser::parser::{
@@ -56,12 +68,23 @@ use cas_parser::parser::{
     token::op::{BinOpKind, Precedence, UnaryOpKind},
 };
 use iter::ExprIter;
+pub use operands::Operands;
 use rug::{Float, Integer};
-use std::{cmp::Ordering, ops::{Add, AddAssign, Mul, MulAssign, Neg}};
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    iter::{Product, Sum},
+    ops::{Add, AddAssign, Mul, MulAssign, Neg},
+    sync::Arc,
+};
 use super::simplify::fraction::make_fraction;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A single term / factor, such as a number, variable, or function call.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Primary {
     /// An integer, such as `2` or `144`.
     Integer(Integer),
@@ -79,12 +102,17 @@ pub enum Primary {
 /// [`Hash`] is implemented manually to allow hashing [`Primary::Float`]s. This module **must
 /// never** produce non-normal [`Float`]s (such as `NaN` or `Infinity`)! Report any bugs that cause
 /// this to happen.
-impl std::hash::Hash for Primary {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+///
+/// The significand of a [`Float`] is hashed without its trailing zero bits, so that equal
+/// [`Float`]s with different precisions have the same hash.
+impl Hash for Primary {
+    fn hash<H: Hasher>(&self, state: &mut H) {
         match self {
             Self::Integer(int) => int.hash(state),
-            // this must be safe for the `Hash` impl to be valid
-            Self::Float(float) => float.get_significand().unwrap().hash(state),
+            // zero has no significand
+            Self::Float(float) => float.get_significand()
+                .map(|sig| Integer::from(&*sig >> sig.find_one(0).unwrap_or(0)))
+                .hash(state),
             Self::Symbol(sym) => sym.hash(state),
             Self::Call(name, args) => {
                 name.hash(state);
@@ -120,6 +148,44 @@ impl std::fmt::Display for Primary {
 /// any bugs that cause this to happen.
 impl Eq for Primary {}
 
+/// A total order on [`Primary`]s, used to sort terms and factors into a canonical order.
+///
+/// Numbers come first, ordered by value (with an [`Integer`] placed before an equal [`Float`]),
+/// followed by symbols ordered by name, and function calls ordered by name, then by arguments.
+impl Ord for Primary {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank(primary: &Primary) -> u8 {
+            match primary {
+                Primary::Integer(_) | Primary::Float(_) => 0,
+                Primary::Symbol(_) => 1,
+                Primary::Call(..) => 2,
+            }
+        }
+
+        match (self, other) {
+            (Self::Integer(lhs), Self::Integer(rhs)) => lhs.cmp(rhs),
+            (Self::Float(lhs), Self::Float(rhs)) => lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal),
+            (Self::Integer(lhs), Self::Float(rhs)) => lhs.partial_cmp(rhs)
+                .unwrap_or(Ordering::Equal)
+                .then(Ordering::Less),
+            (Self::Float(lhs), Self::Integer(rhs)) => lhs.partial_cmp(rhs)
+                .unwrap_or(Ordering::Equal)
+                .then(Ordering::Greater),
+            (Self::Symbol(lhs), Self::Symbol(rhs)) => lhs.cmp(rhs),
+            (Self::Call(lhs_name, lhs_args), Self::Call(rhs_name, rhs_args)) => {
+                lhs_name.cmp(rhs_name).then_with(|| lhs_args.cmp(rhs_args))
+            },
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for Primary {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 /// Adds two [`Primary`]s together. If both are the **same numeric type**, the numbers are added
 /// together. Otherwise, the two [`Primary`]s are wrapped in an [`Expr::Add`].
 ///
@@ -138,7 +204,7 @@ impl Add<Primary> for Primary {
             (lhs, rhs) => Expr::Add(vec![
                 Expr::Primary(lhs),
                 Expr::Primary(rhs),
-            ]),
+            ].into()),
         }
     }
 }
@@ -162,7 +228,7 @@ impl Mul<Primary> for Primary {
             (lhs, rhs) => Expr::Mul(vec![
                 Expr::Primary(lhs),
                 Expr::Primary(rhs),
-            ]),
+            ].into()),
         }
     }
 }
@@ -175,19 +241,21 @@ impl Mul<Primary> for Primary {
 /// single [`Expr::Add`] node with _three_ children, `x`, `y`, and `z`.
 ///
 /// For more information about this type, see the [module-level documentation](self).
-#[derive(Debug, Clone, Eq, Hash)]
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Expr {
     /// A single term or factor.
     Primary(Primary),
 
-    /// Multiple terms added together.
-    Add(Vec<Expr>),
+    /// Multiple terms added together, stored in canonical order.
+    Add(Operands),
 
-    /// Multiple factors multiplied together.
-    Mul(Vec<Expr>),
+    /// Multiple factors multiplied together, stored in canonical order.
+    Mul(Operands),
 
-    /// An expression raised to a power.
-    Exp(Box<Expr>, Box<Expr>),
+    /// An expression raised to a power. The base and exponent are reference-counted, so that
+    /// cloning a power, or sharing it between expressions, does not copy them.
+    Exp(Arc<Expr>, Arc<Expr>),
 }
 
 impl std::fmt::Display for Expr {
@@ -339,9 +407,9 @@ impl Expr {
     pub fn into_integer_recip(self) -> Option<Integer> {
         if let Self::Exp(base, exp) = self {
             if matches!(*base, Self::Primary(Primary::Integer(_))) {
-                if let Self::Primary(Primary::Integer(exp)) = *exp {
-                    if exp == -1 {
-                        return base.into_integer();
+                if let Self::Primary(Primary::Integer(exp)) = &*exp {
+                    if *exp == -1 {
+                        return Arc::unwrap_or_clone(base).into_integer();
                     }
                 }
             }
@@ -370,20 +438,20 @@ impl Expr {
     /// into the single term / factor, or an [`Expr::Primary`] containing the integer 0 or 1.
     pub(crate) fn downgrade(self) -> Self {
         match self {
-            Self::Add(mut terms) => {
+            Self::Add(terms) => {
                 if terms.is_empty() {
                     Self::Primary(Primary::Integer(int(0)))
                 } else if terms.len() == 1 {
-                    terms.remove(0)
+                    terms[0].clone()
                 } else {
                     Self::Add(terms)
                 }
             },
-            Self::Mul(mut factors) => {
+            Self::Mul(factors) => {
                 if factors.is_empty() {
                     Self::Primary(Primary::Integer(int(1)))
                 } else if factors.len() == 1 {
-                    factors.remove(0)
+                    factors[0].clone()
                 } else {
                     Self::Mul(factors)
                 }
@@ -395,8 +463,8 @@ impl Expr {
     /// Returns the square root of this expression. No simplification is done.
     pub fn sqrt(self) -> Self {
         Self::Exp(
-            Box::new(self),
-            Box::new(make_fraction(
+            Arc::new(self),
+            Arc::new(make_fraction(
                 Self::Primary(Primary::Integer(int(1))),
                 Self::Primary(Primary::Integer(int(2))),
             )),
@@ -408,6 +476,43 @@ impl Expr {
     pub fn post_order_iter(&self) -> ExprIter {
         ExprIter::new(self)
     }
+
+    /// Returns true if the given symbol appears anywhere in the expression.
+    pub fn contains_symbol(&self, symbol: &str) -> bool {
+        match self {
+            Self::Primary(Primary::Symbol(sym)) => sym == symbol,
+            Self::Primary(Primary::Call(_, args)) => args.iter().any(|arg| arg.contains_symbol(symbol)),
+            Self::Primary(_) => false,
+            Self::Add(exprs) | Self::Mul(exprs) => exprs.iter().any(|expr| expr.contains_symbol(symbol)),
+            Self::Exp(lhs, rhs) => lhs.contains_symbol(symbol) || rhs.contains_symbol(symbol),
+        }
+    }
+
+    /// Returns a copy of the expression with every sub-expression that is strictly equal to
+    /// `target` replaced with `replacement`. No simplification is done.
+    pub fn substitute(&self, target: &Expr, replacement: &Expr) -> Expr {
+        if self == target {
+            return replacement.clone();
+        }
+
+        match self {
+            Self::Primary(Primary::Call(name, args)) => Self::Primary(Primary::Call(
+                name.clone(),
+                args.iter().map(|arg| arg.substitute(target, replacement)).collect(),
+            )),
+            Self::Primary(_) => self.clone(),
+            Self::Add(terms) => Self::Add(
+                terms.iter().map(|term| term.substitute(target, replacement)).collect(),
+            ),
+            Self::Mul(factors) => Self::Mul(
+                factors.iter().map(|factor| factor.substitute(target, replacement)).collect(),
+            ),
+            Self::Exp(lhs, rhs) => Self::Exp(
+                Arc::new(lhs.substitute(target, replacement)),
+                Arc::new(rhs.substitute(target, replacement)),
+            ),
+        }
+    }
 }
 
 /// Checks if two expressions are **strictly** equal.
@@ -424,18 +529,96 @@ impl PartialEq for Expr {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Primary(lhs), Self::Primary(rhs)) => lhs == rhs,
-            (Self::Add(lhs), Self::Add(rhs)) | (Self::Mul(lhs), Self::Mul(rhs)) => {
-                lhs.len() == rhs.len()
-                    && lhs.iter().all(|lhs| rhs.contains(lhs))
-            },
+            // strictly equal terms / factors are interned into the same list
+            (Self::Add(lhs), Self::Add(rhs)) | (Self::Mul(lhs), Self::Mul(rhs)) => lhs == rhs,
             (Self::Exp(lhs_base, lhs_exp), Self::Exp(rhs_base, rhs_exp)) => {
-                lhs_base == rhs_base && lhs_exp == rhs_exp
+                (Arc::ptr_eq(lhs_base, rhs_base) || lhs_base == rhs_base)
+                    && (Arc::ptr_eq(lhs_exp, rhs_exp) || lhs_exp == rhs_exp)
             },
             _ => false,
         }
     }
 }
 
+/// [`Hash`] is implemented manually to be consistent with **strict equality**. The terms /
+/// factors of an [`Expr::Add`] or [`Expr::Mul`] are stored in canonical order, and their hash is
+/// computed once, when the [`Operands`] list is created.
+impl Hash for Expr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Primary(primary) => primary.hash(state),
+            Self::Add(exprs) | Self::Mul(exprs) => exprs.hash(state),
+            Self::Exp(base, exp) => {
+                base.hash(state);
+                exp.hash(state);
+            },
+        }
+    }
+}
+
+/// A total order on expressions, used to sort terms and factors into a canonical order. The order
+/// is consistent with **strict equality**: two expressions compare equal if and only if they are
+/// strictly equal.
+///
+/// A power is ordered by its base, then by its exponent, and an expression that is not a power is
+/// ordered as if it were raised to a power lower than any other, so that `x < x^2 < x^3 < y`.
+/// Otherwise, [`Expr::Primary`]s come first (see the [`Ord`] implementation for [`Primary`]),
+/// followed by [`Expr::Mul`]s and [`Expr::Add`]s, which are compared by their factors / terms in
+/// canonical order.
+impl Ord for Expr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank(expr: &Expr) -> u8 {
+            match expr {
+                Expr::Primary(_) => 0,
+                Expr::Mul(_) => 1,
+                Expr::Add(_) => 2,
+                Expr::Exp(..) => 3,
+            }
+        }
+
+        match (self, other) {
+            (Self::Exp(lhs_base, lhs_exp), Self::Exp(rhs_base, rhs_exp)) => {
+                lhs_base.cmp(rhs_base).then_with(|| lhs_exp.cmp(rhs_exp))
+            },
+            (Self::Exp(lhs_base, _), rhs) => (**lhs_base).cmp(rhs).then(Ordering::Greater),
+            (lhs, Self::Exp(rhs_base, _)) => lhs.cmp(rhs_base).then(Ordering::Less),
+            (Self::Primary(lhs), Self::Primary(rhs)) => lhs.cmp(rhs),
+            (Self::Add(lhs), Self::Add(rhs)) | (Self::Mul(lhs), Self::Mul(rhs)) => lhs.cmp(rhs),
+            (lhs, rhs) => rank(lhs).cmp(&rank(rhs)),
+        }
+    }
+}
+
+impl PartialOrd for Expr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns true if the given [`AstExpr`] can be converted into an [`Expr`] using the [`From`]
+/// implementation.
+///
+/// Only numbers, symbols, function calls without named arguments, negation, factorials, and the
+/// `+`, `-`, `*`, `/`, and `^` operators are supported.
+pub fn is_convertible(expr: &AstExpr) -> bool {
+    match expr {
+        AstExpr::Literal(literal) => matches!(
+            literal,
+            Literal::Integer(_) | Literal::Float(_) | Literal::Radix(_) | Literal::Symbol(_)
+        ),
+        AstExpr::Paren(paren) => is_convertible(paren.innermost()),
+        AstExpr::Call(call) => call.named_args.is_empty() && call.args.iter().all(is_convertible),
+        AstExpr::Unary(unary) => matches!(unary.op.kind, UnaryOpKind::Neg | UnaryOpKind::Factorial)
+            && is_convertible(&unary.operand),
+        AstExpr::Binary(bin) => matches!(
+            bin.op.kind,
+            BinOpKind::Exp | BinOpKind::Mul | BinOpKind::Div | BinOpKind::Add | BinOpKind::Sub
+        ) && is_convertible(&bin.lhs) && is_convertible(&bin.rhs),
+        _ => false,
+    }
+}
+
 impl From<AstExpr> for Expr {
     fn from(expr: AstExpr) -> Self {
         match expr {
@@ -453,11 +636,17 @@ impl From<AstExpr> for Expr {
             AstExpr::If(_) => todo!(),
             AstExpr::Loop(_) => todo!(),
             AstExpr::While(_) => todo!(),
+            AstExpr::For(_) => todo!(),
             AstExpr::Break(_) => todo!(),
             AstExpr::Continue(_) => todo!(),
+            AstExpr::Return(_) => todo!(),
+            AstExpr::Lambda(_) => todo!(),
             AstExpr::Call(call) => {
+                // derivatives in prime notation are kept as part of the function name, so that
+                // `f'(x)` and `f(x)` are treated as different functions
+                let name = format!("{}{}", call.name.name, "'".repeat(call.derivatives as usize));
                 let args = call.args.into_iter().map(Self::from).collect();
-                Self::Primary(Primary::Call(call.name.name, args))
+                Self::Primary(Primary::Call(name, args))
             },
             AstExpr::Unary(unary) => {
                 match unary.op.kind {
@@ -465,19 +654,26 @@ impl From<AstExpr> for Expr {
                         // treat this as -1 * rhs
                         Self::from(*unary.operand).neg()
                     },
+                    UnaryOpKind::Factorial => {
+                        // treat this as a call to the `factorial` function
+                        Self::Primary(Primary::Call(
+                            "factorial".to_string(),
+                            vec![Self::from(*unary.operand)],
+                        ))
+                    },
                     _ => todo!(),
                 }
             },
             AstExpr::Binary(bin) => {
                 match bin.op.kind {
                     BinOpKind::Exp => {
-                        Self::Exp(Box::new(Self::from(*bin.lhs)), Box::new(Self::from(*bin.rhs)))
+                        Self::Exp(Arc::new(Self::from(*bin.lhs)), Arc::new(Self::from(*bin.rhs)))
                     },
                     BinOpKind::Mul => {
                         // iteratively flatten binary expressions into factors
                         // because the AST obviously exists, `factors` will never end up as a
                         // `Expr::Mul` with zero factors
-                        let mut factors = Self::Mul(Vec::new());
+                        let mut factors = Vec::new();
                         let mut stack = vec![AstExpr::Binary(bin)];
                         while let Some(bin) = stack.pop() {
                             match bin {
@@ -489,16 +685,16 @@ impl From<AstExpr> for Expr {
                                         // if the generated `MathExpr` is another `MathExpr::Mul`,
                                         // add its factors to the current list of factors instead
                                         // we call this "flattening" the expression
-                                        factors *= Self::from(AstExpr::Binary(bin));
+                                        factors.push(Self::from(AstExpr::Binary(bin)));
                                     }
                                 },
                                 expr => {
                                     // same as above
-                                    factors *= Self::from(expr);
+                                    factors.push(Self::from(expr));
                                 },
                             }
                         }
-                        factors
+                        factors.into_iter().product()
                     },
                     BinOpKind::Div => {
                         // treat this as lhs*rhs^-1
@@ -513,7 +709,7 @@ impl From<AstExpr> for Expr {
                         // iteratively flatten binary expressions into terms
                         // because the AST obviously exists, `terms` will never end up as a
                         // `Expr::Add` with zero terms
-                        let mut terms = Self::Add(Vec::new());
+                        let mut terms = Vec::new();
                         let mut stack = vec![AstExpr::Binary(bin)];
                         while let Some(bin) = stack.pop() {
                             match bin {
@@ -525,16 +721,16 @@ impl From<AstExpr> for Expr {
                                         // if the generated `MathExpr` is another `MathExpr::Add`,
                                         // add its terms to the current list of terms instead
                                         // we call this "flattening" the expression
-                                        terms += Self::from(AstExpr::Binary(bin));
+                                        terms.push(Self::from(AstExpr::Binary(bin)));
                                     }
                                 },
                                 _ => {
                                     // same as above
-                                    terms += Self::from(bin);
+                                    terms.push(Self::from(bin));
                                 },
                             }
                         }
-                        terms
+                        terms.into_iter().sum()
                     },
                     BinOpKind::Sub => {
                         // treat this as lhs + -1 * rhs
@@ -556,8 +752,11 @@ impl From<AstExpr> for Expr {
                     BinOpKind::ApproxNotEq => todo!(),
                     BinOpKind::And => todo!(),
                     BinOpKind::Or => todo!(),
+                    BinOpKind::RangeExclusive => todo!(),
+                    BinOpKind::RangeInclusive => todo!(),
                 }
             },
+            AstExpr::Index(_) => todo!(),
             AstExpr::Assign(_) => todo!(),
         }
     }
@@ -585,9 +784,10 @@ impl From<Expr> for AstExpr {
                     span: 0..0,
                 })),
                 Primary::Call(name, args) => AstExpr::Call(Call {
-                    name: LitSym { name, span: 0..0 },
-                    derivatives: 0,
+                    derivatives: (name.len() - name.trim_end_matches('\'').len()) as u8,
+                    name: LitSym { name: name.trim_end_matches('\'').to_string(), span: 0..0 },
                     args: args.into_iter().map(Self::from).collect(),
+                    named_args: Vec::new(),
                     span: 0..0,
                     paren_span: 0..0,
                 }),
@@ -627,13 +827,13 @@ impl From<Expr> for AstExpr {
                 expr
             },
             Expr::Exp(lhs, rhs) => AstExpr::Binary(Binary {
-                lhs: Box::new(Self::from(*lhs)),
+                lhs: Box::new(Self::from(Arc::unwrap_or_clone(lhs))),
                 op: BinOp {
                     kind: BinOpKind::Exp,
                     implicit: false,
                     span: 0..0,
                 },
-                rhs: Box::new(Self::from(*rhs)),
+                rhs: Box::new(Self::from(Arc::unwrap_or_clone(rhs))),
                 span: 0..0,
             }),
         }
@@ -649,56 +849,44 @@ impl Add for Expr {
     fn add(self, rhs: Self) -> Self::Output {
         match (self, rhs) {
             (Self::Primary(lhs), Self::Primary(rhs)) => lhs + rhs,
-            (Self::Add(mut terms), Self::Add(rhs_terms)) => {
+            (Self::Add(terms), Self::Add(rhs_terms)) => {
+                let mut terms = terms.into_vec();
                 terms.extend(rhs_terms);
-                Self::Add(terms)
+                Self::Add(terms.into())
             },
-            (Self::Add(mut terms), other) | (other, Self::Add(mut terms)) => {
+            (Self::Add(terms), other) | (other, Self::Add(terms)) => {
+                let mut terms = terms.into_vec();
                 terms.push(other);
-                Self::Add(terms)
+                Self::Add(terms.into())
             },
-            (lhs, rhs) => Self::Add(vec![lhs, rhs]),
+            (lhs, rhs) => Self::Add(vec![lhs, rhs].into()),
         }
     }
 }
 
-/// Adds two [`Expr`]s together. The behavior is the same as [`Add`], except we can reuse the
-/// allocated memory of `self` if possible.
+/// Adds two [`Expr`]s together. The behavior is the same as [`Add`].
 impl AddAssign for Expr {
     fn add_assign(&mut self, rhs: Self) {
-        match (self, rhs) {
-            (Self::Primary(Primary::Integer(lhs)), Self::Primary(Primary::Integer(rhs))) => {
-                *lhs += rhs;
-            },
-            (Self::Primary(Primary::Float(lhs)), Self::Primary(Primary::Float(rhs))) => {
-                *lhs += rhs;
-            },
-            (Self::Add(terms), Self::Add(rhs_terms)) => {
-                terms.extend(rhs_terms);
-            },
-            (Self::Add(terms), other) => {
-                terms.push(other);
-            },
-            (other, Self::Add(mut terms)) => {
-                // SAFETY: same as (lhs, rhs) branch
-                unsafe {
-                    let owned = std::ptr::read(other);
-                    // TODO: doesn't this panic on oom? this could be unsound if it does
-                    terms.push(owned);
-                    std::ptr::write(other, Self::Add(terms));
-                }
-            },
-            (lhs, rhs) => {
-                // SAFETY: we're essentially moving `lhs` into a new `Self::Add` variant, and
-                // reassigning the new `Self::Add` to `lhs`. nothing is dropped, since `owned` and
-                // `rhs` are moved into `Self::Add`, and neither `write` nor `read` drop their
-                // arguments
-                unsafe {
-                    let owned = std::ptr::read(lhs);
-                    std::ptr::write(lhs, Self::Add(vec![owned, rhs]));
-                }
-            },
+        let lhs = std::mem::replace(self, Self::Primary(Primary::Integer(Integer::new())));
+        *self = lhs + rhs;
+    }
+}
+
+/// Adds all the [`Expr`]s together into a single [`Expr::Add`], flattening any [`Expr::Add`]s into
+/// the list of terms. No other simplification is done.
+///
+/// This should be preferred over adding the [`Expr`]s one at a time, which creates a new list of
+/// terms for every addition.
+impl Sum for Expr {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut terms = Vec::new();
+        for expr in iter {
+            match expr {
+                Self::Add(inner) => terms.extend(inner),
+                expr => terms.push(expr),
+            }
         }
+        Self::Add(terms.into())
     }
 }
 
@@ -711,50 +899,44 @@ impl Mul for Expr {
     fn mul(self, rhs: Self) -> Self {
         match (self, rhs) {
             (Self::Primary(lhs), Self::Primary(rhs)) => lhs * rhs,
-            (Self::Mul(mut factors), Self::Mul(other)) => {
+            (Self::Mul(factors), Self::Mul(other)) => {
+                let mut factors = factors.into_vec();
                 factors.extend(other);
-                Self::Mul(factors)
+                Self::Mul(factors.into())
             },
-            (Self::Mul(mut factors), other) | (other, Self::Mul(mut factors)) => {
+            (Self::Mul(factors), other) | (other, Self::Mul(factors)) => {
+                let mut factors = factors.into_vec();
                 factors.push(other);
-                Self::Mul(factors)
+                Self::Mul(factors.into())
             },
-            (lhs, rhs) => Self::Mul(vec![lhs, rhs]),
+            (lhs, rhs) => Self::Mul(vec![lhs, rhs].into()),
         }
     }
 }
 
+/// Multiplies two [`Expr`]s together. The behavior is the same as [`Mul`].
 impl MulAssign for Expr {
     fn mul_assign(&mut self, rhs: Self) {
-        match (self, rhs) {
-            (Self::Primary(Primary::Integer(lhs)), Self::Primary(Primary::Integer(rhs))) => {
-                *lhs *= rhs;
-            },
-            (Self::Primary(Primary::Float(lhs)), Self::Primary(Primary::Float(rhs))) => {
-                *lhs *= rhs;
-            },
-            (Self::Mul(factors), Self::Mul(rhs_factors)) => {
-                factors.extend(rhs_factors);
-            },
-            (Self::Mul(factors), other) => {
-                factors.push(other);
-            },
-            (other, Self::Mul(mut factors)) => {
-                // SAFETY: see [`AddAssign`] implementation
-                unsafe {
-                    let owned = std::ptr::read(other);
-                    factors.push(owned);
-                    std::ptr::write(other, Self::Mul(factors));
-                }
-            },
-            (lhs, rhs) => {
-                // SAFETY: see [`AddAssign`] implementation
-                unsafe {
-                    let owned = std::ptr::read(lhs);
-                    std::ptr::write(lhs, Self::Mul(vec![owned, rhs]));
-                }
-            },
+        let lhs = std::mem::replace(self, Self::Primary(Primary::Integer(Integer::new())));
+        *self = lhs * rhs;
+    }
+}
+
+/// Multiplies all the [`Expr`]s together into a single [`Expr::Mul`], flattening any
+/// [`Expr::Mul`]s into the list of factors. No other simplification is done.
+///
+/// This should be preferred over multiplying the [`Expr`]s one at a time, which creates a new list
+/// of factors for every multiplication.
+impl Product for Expr {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut factors = Vec::new();
+        for expr in iter {
+            match expr {
+                Self::Mul(inner) => factors.extend(inner),
+                expr => factors.push(expr),
+            }
         }
+        Self::Mul(factors.into())
     }
 }
 
@@ -772,13 +954,9 @@ impl Neg for Expr {
     }
 }
 
-/// NOTE: The output of `pretty_assertions` for failing tests is horrifically ugly, mainly because
-/// we're utilizing strict equality here. Strict equality allows different orderings of terms and
-/// factors, but `pretty_assertions` doesn't care about order. If a test fails and we put the
-/// expected terms and factors in a different order than the generated terms and factors, the
-/// output will be a mess. Just a forewarning!
 #[cfg(test)]
 mod tests {
+    use crate::primitive::with_precision;
     use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
     use pretty_assertions::assert_eq;
     use super::*;
@@ -805,12 +983,65 @@ mod tests {
         assert_ne!(a, b);
     }
 
+    #[test]
+    fn strict_equality_hash() {
+        use std::collections::HashSet;
+
+        let set = HashSet::from([parse_expr("x^2 * y + 3z")]);
+        assert!(set.contains(&parse_expr("3z + y * x^2")));
+        assert!(!set.contains(&parse_expr("3z + y * x^3")));
+    }
+
+    #[test]
+    fn interned_operands() {
+        // strictly equal sums share the same list of terms
+        let (Expr::Add(a), Expr::Add(b)) = (parse_expr("x^2 * y + 3z"), parse_expr("3z + y * x^2"))
+        else {
+            panic!("expected sums");
+        };
+        assert!(std::ptr::eq(a.as_slice(), b.as_slice()));
+
+        // comparing and hashing does not walk the whole tree, which has 2^100 leaves here
+        let nest = |leaf: &str| (0..100).fold(parse_expr(leaf), |expr, n| {
+            Expr::Add(vec![expr.clone() * Expr::Primary(Primary::Integer(int(n))), expr].into())
+        });
+        assert!(nest("x") == nest("x"));
+        assert!(nest("x") != nest("y"));
+        assert!(std::collections::HashSet::from([nest("x")]).contains(&nest("x")));
+    }
+
+    #[test]
+    fn canonical_order() {
+        let mut exprs = ["y", "x^2", "2", "x", "sin(x)", "x * y", "x^3", "1.5"]
+            .into_iter()
+            .map(parse_expr)
+            .collect::<Vec<_>>();
+        exprs.sort();
+
+        let expected = ["1.5", "2", "x", "x^2", "x^3", "y", "sin(x)", "x * y"]
+            .into_iter()
+            .map(parse_expr)
+            .collect::<Vec<_>>();
+        assert_eq!(exprs, expected);
+
+        // the order agrees with strict equality
+        assert_eq!(parse_expr("x + y * z").cmp(&parse_expr("z * y + x")), Ordering::Equal);
+        assert_ne!(parse_expr("2").cmp(&parse_expr("2.0")), Ordering::Equal);
+        assert_ne!(parse_expr("x + 2").cmp(&parse_expr("x + 3")), Ordering::Equal);
+
+        // equal floats with different precisions are interned into the same list
+        let low = with_precision(64, || parse_expr("x + 0.5"));
+        let high = with_precision(1024, || parse_expr("0.5 + x"));
+        assert_eq!(low, high);
+        assert_eq!(low.cmp(&high), Ordering::Equal);
+    }
+
     #[test]
     fn simple_expr() {
         let expr = parse_expr("x^2 + 5x + 6");
 
-        // NOTE: the order of the terms and factors is not guaranteed, but the output is still
-        // semantically correct
+        // NOTE: the terms and factors can be listed in any order, since they are sorted into
+        // canonical order
         assert_eq!(expr, Expr::Add(vec![
             // 6
             Expr::Primary(Primary::Integer(int(6))),
@@ -818,13 +1049,13 @@ mod tests {
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Integer(int(5))),
-            ]),
+            ].into()),
             // + x^2
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol(String::from("x")))),
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                Arc::new(Expr::Primary(Primary::Symbol(String::from("x")))),
+                Arc::new(Expr::Primary(Primary::Integer(int(2)))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -833,22 +1064,22 @@ mod tests {
         assert_eq!(expr, Expr::Mul(vec![
             // y^-3
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol(String::from("y")))),
-                Box::new(Expr::Primary(Primary::Integer(int(-3)))),
+                Arc::new(Expr::Primary(Primary::Symbol(String::from("y")))),
+                Arc::new(Expr::Primary(Primary::Integer(int(-3)))),
             ),
             // * x^2
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol(String::from("x")))),
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                Arc::new(Expr::Primary(Primary::Symbol(String::from("x")))),
+                Arc::new(Expr::Primary(Primary::Integer(int(2)))),
             ),
             // * -2
             Expr::Primary(Primary::Integer(int(-2))),
             // / 5
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Integer(int(5)))),
-                Box::new(Expr::Primary(Primary::Integer(int(-1)))),
+                Arc::new(Expr::Primary(Primary::Integer(int(5)))),
+                Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -859,37 +1090,37 @@ mod tests {
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Integer(int(3))),
-            ]),
+            ].into()),
             // + -1 * (x + t) * y
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("y"))),
                 Expr::Add(vec![
                     Expr::Primary(Primary::Symbol(String::from("t"))),
                     Expr::Primary(Primary::Symbol(String::from("x"))),
-                ]),
+                ].into()),
                 Expr::Primary(Primary::Integer(int(-1))),
-            ]),
+            ].into()),
             // + -1 * z * a^(1/5/6) * b
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("b"))),
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("a")))),
-                    Box::new(Expr::Mul(vec![
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("a")))),
+                    Arc::new(Expr::Mul(vec![
                         Expr::Primary(Primary::Integer(int(1))),
                         Expr::Exp(
-                            Box::new(Expr::Primary(Primary::Integer(int(5)))),
-                            Box::new(Expr::Primary(Primary::Integer(int(-1)))),
+                            Arc::new(Expr::Primary(Primary::Integer(int(5)))),
+                            Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
                         ),
                         Expr::Exp(
-                            Box::new(Expr::Primary(Primary::Integer(int(6)))),
-                            Box::new(Expr::Primary(Primary::Integer(int(-1)))),
+                            Arc::new(Expr::Primary(Primary::Integer(int(6)))),
+                            Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
                         ),
-                    ])),
+                    ].into())),
                 ),
                 Expr::Primary(Primary::Symbol(String::from("z"))),
                 Expr::Primary(Primary::Integer(int(-1))),
-            ]),
-        ]));
+            ].into()),
+        ].into()));
     }
 
     #[test]
@@ -899,69 +1130,68 @@ mod tests {
             // 4 * x * y^2
             Expr::Mul(vec![
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("y")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("y")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
                 ),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Integer(int(4))),
-            ]),
+            ].into()),
             // + 2 * x^2 * y
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("y"))),
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("x")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("x")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
                 ),
                 Expr::Primary(Primary::Integer(int(2))),
-            ]),
+            ].into()),
             // + 3 * x^2 * y
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("y"))),
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("x")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("x")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
                 ),
                 Expr::Primary(Primary::Integer(int(3))),
-            ]),
+            ].into()),
             // + -1 * 16 * x * y
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("y"))),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Integer(int(16))),
                 Expr::Primary(Primary::Integer(int(-1))),
-            ]),
+            ].into()),
             // + -1 * 13 * x * y
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("y"))),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Integer(int(13))),
                 Expr::Primary(Primary::Integer(int(-1))),
-            ]),
+            ].into()),
             // + -1 * 11 * x * y^2
             Expr::Mul(vec![
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("y")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("y")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
                 ),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Integer(int(11))),
                 Expr::Primary(Primary::Integer(int(-1))),
-            ]),
-        ]));
+            ].into()),
+        ].into()));
     }
 
     #[test]
     fn fmt_expr() {
         let expr = parse_expr("8a^73b sqrt(2634*a*b)");
 
-        // the order switches around a bit because of the way we're traversing the tree
-        // but the output is still semantically correct
-        assert_eq!(expr.to_string(), "sqrt(b * a * 2634) * b * a^73 * 8");
+        // the terms and factors are printed in canonical order
+        assert_eq!(expr.to_string(), "8 * a^73 * b * sqrt(2634 * a * b)");
     }
 
     #[test]
     fn fmt_expr_2() {
         let expr = parse_expr("(((((((((a) b) c) d) e + f) g) h) i) j)");
-        assert_eq!(expr.to_string(), "j * i * h * g * (f + e * d * c * b * a)");
+        assert_eq!(expr.to_string(), "g * h * i * j * (f + a * b * c * d * e)");
     }
 }
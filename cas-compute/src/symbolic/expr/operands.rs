@@ -0,0 +1,211 @@
+use once_cell::sync::Lazy;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, Mutex, Weak},
+};
+use super::Expr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The terms of an [`Expr::Add`], or the factors of an [`Expr::Mul`].
+///
+/// The operands are sorted into canonical order (see the [`Ord`] implementation for [`Expr`]) when
+/// the list is created, and the list is **interned**: every list of strictly equal operands that
+/// is alive at the same time is stored once, and shared between all the expressions that contain
+/// it. This means that two lists are strictly equal if and only if they point to the same
+/// allocation, so comparing and hashing them takes constant time.
+///
+/// The list is immutable. To add or remove operands, convert it into a [`Vec`] with
+/// [`Operands::into_vec`], and build a new list from the result.
+#[derive(Clone)]
+pub struct Operands(Arc<Node>);
+
+/// An interned list of operands.
+struct Node {
+    /// The operands, in canonical order.
+    exprs: Box<[Expr]>,
+
+    /// The hash of the operands, computed once when the list is created.
+    hash: u64,
+}
+
+impl Node {
+    /// Returns true if this node stores the given operands.
+    fn matches(&self, hash: u64, exprs: &[Expr]) -> bool {
+        self.hash == hash && *self.exprs == *exprs
+    }
+}
+
+/// Every interned list of operands that may still be alive, grouped by hash.
+///
+/// The interner only holds weak references, so it does not keep unused lists alive. Dead entries
+/// are removed from a bucket when a new list is interned into it, and from the whole table when it
+/// grows past twice its size after the last sweep.
+struct Interner {
+    buckets: HashMap<u64, Vec<Weak<Node>>>,
+    sweep_at: usize,
+}
+
+/// The minimum number of buckets in the interner before it is swept.
+const MIN_SWEEP: usize = 1 << 10;
+
+static INTERNER: Lazy<Mutex<Interner>> = Lazy::new(|| Mutex::new(Interner {
+    buckets: HashMap::new(),
+    sweep_at: MIN_SWEEP,
+}));
+
+impl Interner {
+    /// Returns the interned list of the given operands, interning them if needed.
+    fn intern(&mut self, hash: u64, exprs: Vec<Expr>) -> Arc<Node> {
+        let bucket = self.buckets.entry(hash).or_default();
+        bucket.retain(|node| node.strong_count() > 0);
+        if let Some(node) = bucket.iter()
+            .filter_map(Weak::upgrade)
+            .find(|node| node.matches(hash, &exprs))
+        {
+            return node;
+        }
+
+        let node = Arc::new(Node { exprs: exprs.into_boxed_slice(), hash });
+        bucket.push(Arc::downgrade(&node));
+
+        if self.buckets.len() >= self.sweep_at {
+            self.buckets.retain(|_, bucket| {
+                bucket.retain(|node| node.strong_count() > 0);
+                !bucket.is_empty()
+            });
+            self.sweep_at = MIN_SWEEP.max(self.buckets.len() * 2);
+        }
+
+        node
+    }
+}
+
+impl Operands {
+    /// Sorts the given operands into canonical order, and returns the interned list of them.
+    pub fn new(mut exprs: Vec<Expr>) -> Self {
+        exprs.sort();
+
+        let mut hasher = DefaultHasher::new();
+        exprs.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let node = INTERNER.lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .intern(hash, exprs);
+        Self(node)
+    }
+
+    /// Returns the operands as a slice, in canonical order.
+    pub fn as_slice(&self) -> &[Expr] {
+        &self.0.exprs
+    }
+
+    /// Returns the operands in a [`Vec`], in canonical order. The operands are only cloned if the
+    /// list is shared with another expression.
+    pub fn into_vec(self) -> Vec<Expr> {
+        match Arc::try_unwrap(self.0) {
+            Ok(node) => node.exprs.into_vec(),
+            Err(node) => node.exprs.to_vec(),
+        }
+    }
+}
+
+impl Deref for Operands {
+    type Target = [Expr];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl std::fmt::Debug for Operands {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl From<Vec<Expr>> for Operands {
+    fn from(exprs: Vec<Expr>) -> Self {
+        Self::new(exprs)
+    }
+}
+
+impl FromIterator<Expr> for Operands {
+    fn from_iter<I: IntoIterator<Item = Expr>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Operands {
+    type Item = Expr;
+    type IntoIter = std::vec::IntoIter<Expr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Operands {
+    type Item = &'a Expr;
+    type IntoIter = std::slice::Iter<'a, Expr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Two lists of operands are strictly equal if and only if they are the same interned list.
+impl PartialEq for Operands {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Operands {}
+
+impl Hash for Operands {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.hash);
+    }
+}
+
+/// Lists of operands are ordered lexicographically, in canonical order.
+///
+/// Two lists compare equal if and only if they are the same interned list, in agreement with the
+/// [`PartialEq`] implementation. Distinct lists whose operands compare equal (which should only
+/// happen if an operand is a `NaN`) are ordered by address.
+impl Ord for Operands {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self == other {
+            return Ordering::Equal;
+        }
+
+        self.as_slice().cmp(other.as_slice())
+            .then_with(|| Arc::as_ptr(&self.0).cmp(&Arc::as_ptr(&other.0)))
+    }
+}
+
+impl PartialOrd for Operands {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Operands {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Operands {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<Expr>::deserialize(deserializer).map(Self::new)
+    }
+}
@@ -34,7 +34,7 @@
 //!     Expr::Primary(Primary::Symbol("x".to_string())),
 //!     Expr::Primary(Primary::Symbol("y".to_string())),
 //!     Expr::Primary(Primary::Symbol("z".to_string())),
-//! ]));
+//! ].into()));
 //! ```
 //!
 //! # Simplification
@@ -69,15 +69,25 @@
 //! assert_eq!(simplified, Expr::Mul(vec![
 //!     Expr::Primary(Primary::Integer(int(3))),
 //!     Expr::Primary(Primary::Symbol("x".to_string())),
-//! ]));
+//! ].into()));
 //! ```
 //!
 //! For more information, see the [`simplify`] module.
 
+pub mod apart;
+pub mod calculus;
+pub mod complex;
+pub mod dsolve;
+pub mod expand;
 pub mod expr;
+pub mod polynomial;
+pub mod series;
 pub mod simplify;
 pub mod step_collector;
 
+pub use apart::apart;
+pub use complex::rectangular;
+pub use expand::expand;
 pub use expr::Expr;
 pub use simplify::{simplify, simplify_with, simplify_with_steps};
 pub use step_collector::StepCollector;
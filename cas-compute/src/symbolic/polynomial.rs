@@ -0,0 +1,689 @@
+//! Univariate polynomials with rational coefficients.
+//!
+//! A [`Polynomial`] can be extracted from an [`Expr`] that is a polynomial in some variable, with
+//! numeric coefficients, using [`Polynomial::from_expr`]. Polynomials can be added, multiplied and
+//! divided exactly, and their rational roots can be found with the rational root theorem.
+//!
+//! A [`RationalFunction`] is the quotient of two polynomials, and can be extracted from any
+//! [`Expr`] built out of sums, products and integer powers of polynomials.
+
+use crate::primitive::int;
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    simplify::fraction::{as_rational, rational_expr},
+};
+use rug::{Integer, Rational};
+use std::{ops::{Add, Mul, Neg, Sub}, sync::Arc};
+
+/// A polynomial with rational coefficients, in a single variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// The coefficients of the polynomial, where the coefficient at index `i` is the coefficient
+    /// of `x^i`. The last coefficient, if there is one, is never zero.
+    coeffs: Vec<Rational>,
+}
+
+impl Polynomial {
+    /// Creates a polynomial with the given coefficients, where the coefficient at index `i` is the
+    /// coefficient of `x^i`.
+    pub fn new(coeffs: Vec<Rational>) -> Self {
+        let mut poly = Self { coeffs };
+        poly.trim();
+        poly
+    }
+
+    /// Creates the zero polynomial.
+    pub fn zero() -> Self {
+        Self { coeffs: Vec::new() }
+    }
+
+    /// Creates a constant polynomial.
+    pub fn constant(c: impl Into<Rational>) -> Self {
+        Self::new(vec![c.into()])
+    }
+
+    /// Creates the polynomial `x`.
+    pub fn x() -> Self {
+        Self::new(vec![Rational::new(), Rational::from(1)])
+    }
+
+    /// Removes trailing zero coefficients.
+    fn trim(&mut self) {
+        while self.coeffs.last().is_some_and(|c| *c == 0) {
+            self.coeffs.pop();
+        }
+    }
+
+    /// Returns the coefficients of the polynomial, starting with the constant term.
+    pub fn coeffs(&self) -> &[Rational] {
+        &self.coeffs
+    }
+
+    /// Returns true if this is the zero polynomial.
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// Returns the degree of the polynomial, or [`None`] for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    /// Returns the leading coefficient of the polynomial, which is zero for the zero polynomial.
+    pub fn leading(&self) -> Rational {
+        self.coeffs.last().cloned().unwrap_or_default()
+    }
+
+    /// Evaluates the polynomial at the given point, using Horner's method.
+    pub fn eval(&self, x: &Rational) -> Rational {
+        self.coeffs.iter()
+            .rev()
+            .fold(Rational::new(), |acc, c| acc * x + c)
+    }
+
+    /// Returns the derivative of the polynomial.
+    pub fn derivative(&self) -> Self {
+        Self::new(
+            self.coeffs.iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| Rational::from(c * i as u64))
+                .collect()
+        )
+    }
+
+    /// Returns the polynomial `p(x + c)`.
+    pub fn shift(&self, c: &Rational) -> Self {
+        let linear = Self::new(vec![c.clone(), Rational::from(1)]);
+        self.coeffs.iter()
+            .rev()
+            .fold(Self::zero(), |acc, coeff| acc * &linear + Self::constant(coeff.clone()))
+    }
+
+    /// Raises the polynomial to the given power.
+    pub fn pow(&self, exp: u32) -> Self {
+        (0..exp).fold(Self::constant(1), |acc, _| acc * self)
+    }
+
+    /// Divides this polynomial by `divisor`, returning the quotient and remainder.
+    ///
+    /// Panics if `divisor` is the zero polynomial.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let divisor_degree = divisor.degree().expect("division by zero polynomial");
+        let leading = divisor.leading();
+
+        let mut remainder = self.clone();
+        let mut quotient = vec![Rational::new(); self.coeffs.len().saturating_sub(divisor_degree)];
+        while let Some(degree) = remainder.degree().filter(|&degree| degree >= divisor_degree) {
+            let shift = degree - divisor_degree;
+            let factor = remainder.leading() / &leading;
+            for (i, c) in divisor.coeffs.iter().enumerate() {
+                remainder.coeffs[i + shift] -= Rational::from(c * &factor);
+            }
+            quotient[shift] = factor;
+            remainder.trim();
+        }
+
+        (Self::new(quotient), remainder)
+    }
+
+    /// Returns the remainder of dividing this polynomial by `divisor`.
+    ///
+    /// Panics if `divisor` is the zero polynomial.
+    pub fn rem(&self, divisor: &Self) -> Self {
+        self.div_rem(divisor).1
+    }
+
+    /// Returns the polynomial scaled so that its leading coefficient is one. The zero polynomial
+    /// is returned unchanged.
+    pub fn monic(&self) -> Self {
+        let leading = self.leading();
+        if leading == 0 {
+            return self.clone();
+        }
+        Self { coeffs: self.coeffs.iter().map(|c| Rational::from(c / &leading)).collect() }
+    }
+
+    /// Returns the monic greatest common divisor of two polynomials, using the Euclidean
+    /// algorithm. The greatest common divisor of two zero polynomials is zero.
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = a.rem(&b);
+            a = b;
+            b = r;
+        }
+        a.monic()
+    }
+
+    /// Returns the monic greatest common divisor `g` of two polynomials, along with polynomials
+    /// `s` and `t` such that `s * self + t * other = g`, using the extended Euclidean algorithm.
+    pub fn ext_gcd(&self, other: &Self) -> (Self, Self, Self) {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        let (mut s0, mut s1) = (Self::constant(1), Self::zero());
+        let (mut t0, mut t1) = (Self::zero(), Self::constant(1));
+        while !b.is_zero() {
+            let (q, r) = a.div_rem(&b);
+            a = b;
+            b = r;
+            let s = s0 - q.clone() * &s1;
+            s0 = s1;
+            s1 = s;
+            let t = t0 - q * &t1;
+            t0 = t1;
+            t1 = t;
+        }
+
+        let leading = a.leading();
+        if leading == 0 {
+            return (a, s0, t0);
+        }
+        let scale = Self::constant(leading.recip());
+        (a.monic(), s0 * &scale, t0 * &scale)
+    }
+
+    /// Returns the square-free factorization of the polynomial, using Yun's algorithm.
+    ///
+    /// The result is a list of pairwise coprime, square-free, monic factors `f_i` with distinct
+    /// multiplicities `m_i`, such that the polynomial is `c Π f_i^m_i` for its leading
+    /// coefficient `c`. Constant polynomials have no factors.
+    pub fn square_free(&self) -> Vec<(Self, usize)> {
+        let mut factors = Vec::new();
+        if self.degree().is_none_or(|degree| degree == 0) {
+            return factors;
+        }
+
+        let monic = self.monic();
+        let derivative = monic.derivative();
+        let b = monic.gcd(&derivative);
+        let mut c = monic.div_rem(&b).0;
+        let mut d = derivative.div_rem(&b).0 - c.derivative();
+        let mut multiplicity = 1;
+        while c.degree().is_some_and(|degree| degree > 0) {
+            let a = c.gcd(&d);
+            c = c.div_rem(&a).0;
+            d = d.div_rem(&a).0 - c.derivative();
+            if a.degree().is_some_and(|degree| degree > 0) {
+                factors.push((a, multiplicity));
+            }
+            multiplicity += 1;
+        }
+        factors
+    }
+
+    /// Factors the polynomial over the rationals, returning its leading coefficient and a list of
+    /// pairwise coprime, monic factors along with their multiplicities.
+    ///
+    /// Linear factors are found from the rational roots of the polynomial, and quadratic factors
+    /// with [Kronecker's method](https://en.wikipedia.org/wiki/Factorization_of_polynomials#Kronecker's_method).
+    /// Each factor is irreducible, except that a factor of degree four or more is left unsplit if
+    /// it has no quadratic factor that can be found this way.
+    pub fn factor(&self) -> (Rational, Vec<(Self, usize)>) {
+        let mut factors = Vec::new();
+        for (square_free, multiplicity) in self.square_free() {
+            let mut rest = square_free;
+            for (root, _) in rest.rational_roots() {
+                let linear = Self::new(vec![-root, Rational::from(1)]);
+                rest = rest.div_rem(&linear).0;
+                factors.push((linear, multiplicity));
+            }
+
+            let mut pending = vec![rest.monic()];
+            while let Some(poly) = pending.pop() {
+                match poly.degree() {
+                    Some(0) | None => (),
+                    Some(1..=3) => factors.push((poly, multiplicity)),
+                    Some(_) => match poly.quadratic_factor() {
+                        Some(quadratic) => {
+                            pending.push(poly.div_rem(&quadratic).0.monic());
+                            pending.push(quadratic);
+                        },
+                        None => factors.push((poly, multiplicity)),
+                    },
+                }
+            }
+        }
+
+        factors.sort_by(|(a, m), (b, n)| a.degree().cmp(&b.degree())
+            .then_with(|| a.coeffs.iter().rev().cmp(b.coeffs.iter().rev()))
+            .then_with(|| m.cmp(n)));
+        (self.leading(), factors)
+    }
+
+    /// Searches for a monic quadratic factor of a polynomial with no rational roots, using
+    /// Kronecker's method.
+    ///
+    /// A quadratic factor with integer coefficients of the primitive integer polynomial
+    /// `p` is determined by its values at `x = 0, 1, -1`, each of which must divide the
+    /// corresponding value of `p`. Each combination of divisors is interpolated and tried.
+    fn quadratic_factor(&self) -> Option<Self> {
+        let lcm = self.coeffs.iter().fold(int(1), |lcm, c| lcm.lcm(c.denom()));
+        let integer = Self {
+            coeffs: self.coeffs.iter().map(|c| Rational::from(c * &lcm)).collect(),
+        };
+        let values = [0, 1, -1].map(|x| integer.eval(&Rational::from(x)).into_numer_denom().0);
+        let [at_zero, at_one, at_minus_one] = values.each_ref().map(divisors);
+        let (at_zero, at_one, at_minus_one) = (at_zero?, at_one?, at_minus_one?);
+        if at_zero.len() * at_one.len() * at_minus_one.len() > MAX_QUADRATIC_CANDIDATES {
+            return None;
+        }
+
+        // `q` and `-q` are both factors if either is, so the constant term `c = q(0)` can be
+        // taken to be positive; it is nonzero since `x = 0` is not a root
+        for c in &at_zero {
+            for d1 in at_one.iter().flat_map(|d| [d.clone(), -d.clone()]) {
+                for d2 in at_minus_one.iter().flat_map(|d| [d.clone(), -d.clone()]) {
+                    // q(x) = a x^2 + b x + c, where q(1) = d1 and q(-1) = d2
+                    let sum = Integer::from(&d1 + &d2);
+                    if sum.is_odd() {
+                        continue;
+                    }
+                    let a = sum / 2 - c;
+                    if a == 0 {
+                        continue;
+                    }
+                    let b = (d1.clone() - &d2) / 2;
+                    let candidate = Self::new(vec![Rational::from(c), Rational::from(b), Rational::from(a)]);
+                    if integer.rem(&candidate).is_zero() {
+                        return Some(candidate.monic());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the distinct rational roots of the polynomial, in increasing order, along with
+    /// their multiplicities.
+    ///
+    /// Candidates are found with the rational root theorem. If the coefficients have very large
+    /// numerators or denominators, only the root `x = 0` is looked for.
+    pub fn rational_roots(&self) -> Vec<(Rational, usize)> {
+        let mut roots = Vec::new();
+        let mut poly = self.clone();
+        if poly.degree().is_none_or(|degree| degree == 0) {
+            return roots;
+        }
+
+        // `x = 0` is a root if the constant term is zero
+        let zeros = poly.coeffs.iter().take_while(|c| **c == 0).count();
+        if zeros > 0 {
+            roots.push((Rational::new(), zeros));
+            poly = Self::new(poly.coeffs[zeros..].to_vec());
+        }
+
+        // scale to integer coefficients; roots are of the form `±p / q`, where `p` divides the
+        // constant term and `q` divides the leading coefficient
+        let lcm = poly.coeffs.iter().fold(int(1), |lcm, c| lcm.lcm(c.denom()));
+        let integer_coeffs = poly.coeffs.iter()
+            .map(|c| c.numer() * Integer::from(&lcm / c.denom()))
+            .collect::<Vec<_>>();
+        let constant = integer_coeffs.first().cloned().unwrap_or_default();
+        let leading = integer_coeffs.last().cloned().unwrap_or_default();
+        let (Some(numerators), Some(denominators)) = (divisors(&constant), divisors(&leading)) else {
+            return roots;
+        };
+
+        for p in &numerators {
+            for q in &denominators {
+                for candidate in [Rational::from((p.clone(), q.clone())), -Rational::from((p.clone(), q.clone()))] {
+                    if roots.iter().any(|(root, _)| *root == candidate) {
+                        continue;
+                    }
+
+                    // divide out the root as many times as possible
+                    let factor = Self::new(vec![-candidate.clone(), Rational::from(1)]);
+                    let mut multiplicity = 0;
+                    while poly.degree().is_some_and(|degree| degree > 0) && poly.eval(&candidate) == 0 {
+                        poly = poly.div_rem(&factor).0;
+                        multiplicity += 1;
+                    }
+                    if multiplicity > 0 {
+                        roots.push((candidate, multiplicity));
+                    }
+                }
+            }
+        }
+
+        roots.sort_by(|(a, _), (b, _)| a.cmp(b));
+        roots
+    }
+
+    /// Extracts a polynomial in the given variable from the expression. Returns [`None`] if the
+    /// expression is not a polynomial with numeric coefficients.
+    pub fn from_expr(expr: &Expr, var: &str) -> Option<Self> {
+        match expr {
+            Expr::Primary(Primary::Symbol(name)) if name == var => Some(Self::x()),
+            Expr::Add(terms) => terms.iter()
+                .try_fold(Self::zero(), |sum, term| Some(sum + Self::from_expr(term, var)?)),
+            Expr::Mul(factors) => factors.iter()
+                .try_fold(Self::constant(1), |product, factor| {
+                    Some(product * Self::from_expr(factor, var)?)
+                }),
+            Expr::Exp(base, exp) if base.contains_symbol(var) => {
+                let exp = exp.as_integer()?.to_u32()?;
+                Some(Self::from_expr(base, var)?.pow(exp))
+            },
+            expr => as_rational(expr).map(Self::constant),
+        }
+    }
+
+    /// Converts the polynomial into an [`Expr`], substituting the given expression for the
+    /// variable. The result is not simplified.
+    pub fn to_expr(&self, var: &Expr) -> Expr {
+        let terms = self.coeffs.iter()
+            .enumerate()
+            .filter(|(_, c)| **c != 0)
+            .map(|(i, c)| match i {
+                0 => rational_expr(c),
+                1 => rational_expr(c) * var.clone(),
+                _ => rational_expr(c) * Expr::Exp(
+                    Arc::new(var.clone()),
+                    Arc::new(Expr::Primary(Primary::Integer(int(i)))),
+                ),
+            })
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            Expr::Primary(Primary::Integer(int(0)))
+        } else {
+            Expr::Add(terms.into()).downgrade()
+        }
+    }
+}
+
+/// The maximum number of candidate quadratic factors tried by [`Polynomial::quadratic_factor`],
+/// divided by four.
+const MAX_QUADRATIC_CANDIDATES: usize = 1 << 16;
+
+/// The largest integer whose divisors are enumerated by [`divisors`].
+const MAX_DIVISORS_OF: u64 = 1 << 40;
+
+/// Returns the positive divisors of the given integer, which must be nonzero, or [`None`] if the
+/// integer is too large to find its divisors by trial division.
+fn divisors(n: &Integer) -> Option<Vec<Integer>> {
+    let n = n.clone().abs();
+    if n > MAX_DIVISORS_OF {
+        return None;
+    }
+
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+    let mut d = int(1);
+    while Integer::from(&d * &d) <= n {
+        if n.is_divisible(&d) {
+            let pair = Integer::from(&n / &d);
+            if pair != d {
+                large.push(pair);
+            }
+            small.push(d.clone());
+        }
+        d += 1;
+    }
+    small.extend(large.into_iter().rev());
+    Some(small)
+}
+
+impl Add for Polynomial {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (mut long, short) = if self.coeffs.len() >= rhs.coeffs.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        for (i, c) in short.coeffs.into_iter().enumerate() {
+            long.coeffs[i] += c;
+        }
+        long.trim();
+        long
+    }
+}
+
+impl Neg for Polynomial {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { coeffs: self.coeffs.into_iter().map(|c| -c).collect() }
+    }
+}
+
+impl Sub for Polynomial {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul<&Polynomial> for Polynomial {
+    type Output = Self;
+
+    fn mul(self, rhs: &Self) -> Self {
+        if self.is_zero() || rhs.is_zero() {
+            return Self::zero();
+        }
+
+        let mut coeffs = vec![Rational::new(); self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] += Rational::from(a * b);
+            }
+        }
+        Self::new(coeffs)
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        self * &rhs
+    }
+}
+
+/// The quotient of two polynomials in the same variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RationalFunction {
+    /// The numerator.
+    pub num: Polynomial,
+
+    /// The denominator, which is never the zero polynomial.
+    pub den: Polynomial,
+}
+
+impl RationalFunction {
+    /// Extracts a rational function in the given variable from the expression. Returns [`None`]
+    /// if the expression is not a rational function with numeric coefficients.
+    pub fn from_expr(expr: &Expr, var: &str) -> Option<Self> {
+        match expr {
+            Expr::Add(terms) => terms.iter()
+                .try_fold(Self::from(Polynomial::zero()), |sum, term| {
+                    let term = Self::from_expr(term, var)?;
+                    Some(Self {
+                        num: sum.num * &term.den + term.num * &sum.den,
+                        den: sum.den * term.den,
+                    })
+                }),
+            Expr::Mul(factors) => factors.iter()
+                .try_fold(Self::from(Polynomial::constant(1)), |product, factor| {
+                    let factor = Self::from_expr(factor, var)?;
+                    Some(Self {
+                        num: product.num * factor.num,
+                        den: product.den * factor.den,
+                    })
+                }),
+            Expr::Exp(base, exp) if base.contains_symbol(var) => {
+                let exp = exp.as_integer()?.to_i32()?;
+                let base = Self::from_expr(base, var)?;
+                if exp >= 0 {
+                    Some(Self { num: base.num.pow(exp as u32), den: base.den.pow(exp as u32) })
+                } else if base.num.is_zero() {
+                    None
+                } else {
+                    Some(Self { num: base.den.pow(exp.unsigned_abs()), den: base.num.pow(exp.unsigned_abs()) })
+                }
+            },
+            expr => Polynomial::from_expr(expr, var).map(Self::from),
+        }
+    }
+
+    /// Returns true if the rational function is a polynomial, i.e. its denominator is constant.
+    pub fn is_polynomial(&self) -> bool {
+        self.den.degree() == Some(0)
+    }
+
+    /// Decomposes the rational function into partial fractions over the rationals.
+    ///
+    /// Returns the polynomial part of the rational function, and a list of proper fractions
+    /// `n(x) / f(x)^k`, one for each power `k` of each factor `f` of the denominator, as found by
+    /// [`Polynomial::factor`]. Fractions whose numerator is zero are omitted.
+    pub fn apart(&self) -> (Polynomial, Vec<PartialFraction>) {
+        // cancel common factors first, so that every factor of the denominator is a pole
+        let common = self.num.gcd(&self.den);
+        let (num, den) = if common.is_zero() {
+            (self.num.clone(), self.den.clone())
+        } else {
+            (self.num.div_rem(&common).0, self.den.div_rem(&common).0)
+        };
+
+        let (quotient, remainder) = num.div_rem(&den);
+        let (leading, factors) = den.factor();
+
+        // remainder / den = remainder / (leading Π f_i^m_i); split off one factor at a time with
+        // the extended Euclidean algorithm
+        let mut remainder = remainder * &Polynomial::constant(leading.recip());
+        let mut rest = factors.iter()
+            .fold(Polynomial::constant(1), |product, (factor, multiplicity)| {
+                product * &factor.pow(*multiplicity as u32)
+            });
+        let mut fractions = Vec::new();
+        for (factor, multiplicity) in factors {
+            let power = factor.pow(multiplicity as u32);
+            let others = rest.div_rem(&power).0;
+
+            // remainder / (power * others) = a / power + b / others, where
+            // s * power + t * others = 1, a = remainder * t mod power, b = (remainder - a * others) / power
+            let (_, _, t) = power.ext_gcd(&others);
+            let a = (remainder.clone() * &t).rem(&power);
+            remainder = (remainder - a.clone() * &others).div_rem(&power).0;
+            rest = others;
+
+            // write a / f^m as Σ_k c_k / f^k, by expanding `a` in powers of `f`
+            let mut a = a;
+            let mut terms = Vec::new();
+            for power in (1..=multiplicity).rev() {
+                let (q, c) = a.div_rem(&factor);
+                if !c.is_zero() {
+                    terms.push(PartialFraction { num: c, factor: factor.clone(), power });
+                }
+                a = q;
+            }
+            fractions.extend(terms.into_iter().rev());
+        }
+
+        (quotient, fractions)
+    }
+}
+
+/// A proper fraction `num(x) / factor(x)^power`, as produced by [`RationalFunction::apart`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialFraction {
+    /// The numerator, whose degree is less than that of the factor.
+    pub num: Polynomial,
+
+    /// The factor of the denominator, which is monic and has no repeated factors.
+    pub factor: Polynomial,
+
+    /// The power to which the factor is raised.
+    pub power: usize,
+}
+
+impl From<Polynomial> for RationalFunction {
+    fn from(num: Polynomial) -> Self {
+        Self { num, den: Polynomial::constant(1) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
+    use super::*;
+
+    /// Parses the given expression and extracts a polynomial in `x`.
+    fn poly(input: &str) -> Polynomial {
+        let expr = Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap());
+        Polynomial::from_expr(&expr, "x").unwrap()
+    }
+
+    /// Creates a polynomial from integer coefficients.
+    fn from_ints(coeffs: &[i32]) -> Polynomial {
+        Polynomial::new(coeffs.iter().map(|&c| Rational::from(c)).collect())
+    }
+
+    #[test]
+    fn extract() {
+        assert_eq!(poly("(x + 1)^2 - 1"), from_ints(&[0, 2, 1]));
+        assert_eq!(poly("3x^3 / 2"), Polynomial::new(vec![
+            Rational::new(),
+            Rational::new(),
+            Rational::new(),
+            Rational::from((3, 2)),
+        ]));
+        assert!(Polynomial::from_expr(&Expr::from(Parser::new("x^x").try_parse_full::<AstExpr>().unwrap()), "x").is_none());
+    }
+
+    #[test]
+    fn division() {
+        let (q, r) = poly("x^3 - 2x + 5").div_rem(&poly("x - 1"));
+        assert_eq!(q, from_ints(&[-1, 1, 1]));
+        assert_eq!(r, from_ints(&[4]));
+    }
+
+    #[test]
+    fn roots() {
+        // 2 (x - 1/2) (x + 3)^2 x
+        let p = poly("(2x - 1) (x + 3)^2 x");
+        assert_eq!(p.rational_roots(), vec![
+            (Rational::from(-3), 2),
+            (Rational::new(), 1),
+            (Rational::from((1, 2)), 1),
+        ]);
+        assert!(poly("x^2 + 1").rational_roots().is_empty());
+    }
+
+    #[test]
+    fn gcd() {
+        assert_eq!(poly("(x - 1) (x + 2)^2").gcd(&poly("3 (x + 2) (x + 5)")), from_ints(&[2, 1]));
+
+        let (a, b) = (poly("x^2 + 1"), poly("x - 3"));
+        let (g, s, t) = a.ext_gcd(&b);
+        assert_eq!(g, from_ints(&[1]));
+        assert_eq!(s * &a + t * &b, g);
+    }
+
+    #[test]
+    fn square_free() {
+        // 2 (x - 1) (x + 2)^2 (x^2 + 1)^2
+        let p = poly("2 (x - 1) (x + 2)^2 (x^2 + 1)^2");
+        assert_eq!(p.square_free(), vec![
+            (from_ints(&[-1, 1]), 1),
+            (poly("(x + 2) (x^2 + 1)"), 2),
+        ]);
+    }
+
+    #[test]
+    fn factor() {
+        let (leading, factors) = poly("3 (2x - 1) (x^2 + 1)^2 (x^2 - 2)").factor();
+        assert_eq!(leading, 6);
+        assert_eq!(factors, vec![
+            (Polynomial::new(vec![Rational::from((-1, 2)), Rational::from(1)]), 1),
+            (from_ints(&[-2, 0, 1]), 1),
+            (from_ints(&[1, 0, 1]), 2),
+        ]);
+
+        // x^4 + 4 = (x^2 - 2x + 2) (x^2 + 2x + 2) has no rational roots
+        let (_, factors) = poly("x^4 + 4").factor();
+        assert_eq!(factors, vec![(from_ints(&[2, -2, 1]), 1), (from_ints(&[2, 2, 1]), 1)]);
+    }
+}
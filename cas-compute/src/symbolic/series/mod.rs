@@ -0,0 +1,179 @@
+//! Closed forms of sums and products.
+//!
+//! The [`sum`] function finds a closed form for the sum `Σ_{k=a}^{b} f(k)`, and the [`product`]
+//! function finds a closed form for the product `Π_{k=a}^{b} f(k)`. The upper bound of a sum may be
+//! the symbol [`INFINITY`], in which case the sum is an infinite series.
+//!
+//! Both functions return [`None`] if they do not recognize the form of the summand. The following
+//! forms are supported:
+//!
+//! - Sums:
+//!   - Polynomials in `k`, using Faulhaber's formula.
+//!   - Rational functions in `k` whose denominators have rational roots that differ by integers,
+//!     which telescope after partial fraction decomposition (e.g. `1 / (k (k + 1))`).
+//!   - Geometric series `c r^k`.
+//!   - Pairs of terms that telescope, such as `f(k) - f(k + 1)`.
+//!   - Some well-known infinite series, such as `Σ 1 / k^2 = π^2 / 6`, `Σ x^k / k! = e^x`, and
+//!     `Σ (-1)^(k + 1) / k = ln(2)`.
+//! - Products:
+//!   - Constants and powers with a constant base, which become sums in the exponent.
+//!   - Polynomials in `k` with rational roots, which become quotients of factorials.
+//!
+//! ```
+//! use cas_compute::symbolic::{expr::{Expr, Primary}, series::sum, simplify};
+//! use cas_parser::parser::{ast::Expr as AstExpr, Parser};
+//!
+//! let parse = |input: &str| Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap());
+//!
+//! // Σ_{k=1}^{n} k = n (n + 1) / 2
+//! let result = sum(&parse("k"), "k", &parse("1"), &parse("n")).unwrap();
+//! assert_eq!(result, simplify(&parse("n / 2 + n^2 / 2")));
+//! ```
+
+mod product;
+mod sum;
+
+pub use product::product;
+pub use sum::sum;
+
+use crate::primitive::int;
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    simplify::fraction::make_fraction,
+};
+use std::sync::Arc;
+
+/// The name of the symbol representing positive infinity, which can be used as the upper bound of
+/// a sum.
+pub const INFINITY: &str = "inf";
+
+/// The maximum number of terms to compute explicitly when adjusting the lower bound of a
+/// well-known series.
+const MAX_EXPLICIT_TERMS: i64 = 1 << 8;
+
+/// Returns true if the expression is the symbol [`INFINITY`].
+pub fn is_infinite(expr: &Expr) -> bool {
+    expr.as_symbol() == Some(INFINITY)
+}
+
+/// Creates an integer [`Expr`].
+fn num(n: i64) -> Expr {
+    Expr::Primary(Primary::Integer(int(n)))
+}
+
+/// Creates a symbol [`Expr`].
+fn sym(name: &str) -> Expr {
+    Expr::Primary(Primary::Symbol(name.to_string()))
+}
+
+/// Creates a function call [`Expr`] with a single argument.
+fn call(name: &str, arg: Expr) -> Expr {
+    Expr::Primary(Primary::Call(name.to_string(), vec![arg]))
+}
+
+/// Creates an [`Expr`] raising `base` to the power of `exp`.
+fn pow(base: Expr, exp: Expr) -> Expr {
+    Expr::Exp(Arc::new(base), Arc::new(exp))
+}
+
+/// Creates an [`Expr`] dividing `lhs` by `rhs`.
+fn divide(lhs: Expr, rhs: Expr) -> Expr {
+    make_fraction(lhs, rhs)
+}
+
+/// Splits the expression into a product `c * f(k)`, where `c` does not depend on `var`.
+fn split_constant(expr: &Expr, var: &str) -> (Expr, Expr) {
+    match expr {
+        Expr::Mul(factors) => {
+            let (dependent, constant): (Vec<_>, Vec<_>) = factors.iter()
+                .cloned()
+                .partition(|factor| factor.contains_symbol(var));
+            (Expr::Mul(constant.into()).downgrade(), Expr::Mul(dependent.into()).downgrade())
+        },
+        expr if expr.contains_symbol(var) => (num(1), expr.clone()),
+        expr => (expr.clone(), num(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_parser::parser::{ast::expr::Expr as AstExpr, Parser};
+    use crate::symbolic::{calculus::eval_at, simplify};
+    use super::*;
+
+    /// Parses the given expression.
+    fn parse(input: &str) -> Expr {
+        Expr::from(Parser::new(input).try_parse_full::<AstExpr>().unwrap())
+    }
+
+    /// Computes the sum of `expr` over `k`, and evaluates it numerically with the given values.
+    fn sum_at(expr: &str, lower: &str, upper: &str, vars: &[(&str, f64)]) -> f64 {
+        let result = sum(&parse(expr), "k", &parse(lower), &parse(upper))
+            .unwrap_or_else(|| panic!("no closed form for `{}`", expr));
+        eval_at(&result, vars)
+    }
+
+    /// Computes the product of `expr` over `k`, and evaluates it numerically with the given
+    /// values.
+    fn product_at(expr: &str, lower: &str, upper: &str, vars: &[(&str, f64)]) -> f64 {
+        let result = product(&parse(expr), "k", &parse(lower), &parse(upper))
+            .unwrap_or_else(|| panic!("no closed form for `{}`", expr));
+        eval_at(&result, vars)
+    }
+
+    /// Asserts that two floating-point numbers are close.
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn faulhaber() {
+        assert_eq!(
+            sum(&parse("k^2"), "k", &parse("1"), &parse("n")).unwrap(),
+            simplify(&parse("n / 6 + n^2 / 2 + n^3 / 3")),
+        );
+        assert_close(sum_at("3k^3 - k + 2", "4", "20", &[]), 132_022.0);
+        assert_close(sum_at("k", "m", "n", &[("m", 3.0), ("n", 10.0)]), 52.0);
+    }
+
+    #[test]
+    fn rational_telescoping() {
+        assert_eq!(sum(&parse("1 / (k * (k + 1))"), "k", &parse("1"), &parse("inf")).unwrap(), num(1));
+        assert_close(sum_at("1 / (k * (k + 1) * (k + 2))", "1", "inf", &[]), 0.25);
+        assert_close(sum_at("1 / (k^2 - 1)", "2", "n", &[("n", 10.0)]), 0.75 - 0.5 / 10.0 - 0.5 / 11.0);
+        assert!(sum(&parse("1 / k"), "k", &parse("1"), &parse("inf")).is_none());
+    }
+
+    #[test]
+    fn geometric() {
+        assert_eq!(sum(&parse("(1/2)^k"), "k", &parse("0"), &parse("inf")).unwrap(), num(2));
+        assert_close(sum_at("3 * 2^(2k + 1)", "0", "5", &[]), 8190.0);
+        assert_close(sum_at("x^k", "1", "n", &[("x", 3.0), ("n", 4.0)]), 120.0);
+        assert!(sum(&parse("2^k"), "k", &parse("0"), &parse("inf")).is_none());
+    }
+
+    #[test]
+    fn known_series() {
+        let pi = std::f64::consts::PI;
+        assert_close(sum_at("1 / k^2", "1", "inf", &[]), pi * pi / 6.0);
+        assert_close(sum_at("1 / k^4", "2", "inf", &[]), pi.powi(4) / 90.0 - 1.0);
+        assert_close(sum_at("x^k / k!", "0", "inf", &[("x", 2.0)]), 2f64.exp());
+        assert_close(sum_at("(-1)^(k + 1) / k", "1", "inf", &[]), 2f64.ln());
+        assert_close(sum_at("(-1)^k / (2k + 1)", "0", "inf", &[]), pi / 4.0);
+    }
+
+    #[test]
+    fn pairwise_telescoping() {
+        assert_close(sum_at("sqrt(k + 1) - sqrt(k)", "1", "n", &[("n", 99.0)]), 9.0);
+        assert_close(sum_at("ln(k + 2) - ln(k)", "1", "n", &[("n", 5.0)]), 21f64.ln());
+    }
+
+    #[test]
+    fn products() {
+        assert_close(product_at("k", "1", "n", &[("n", 6.0)]), 720.0);
+        assert_close(product_at("2^k", "1", "4", &[]), 1024.0);
+        assert_close(product_at("3 k^2", "1", "3", &[]), 27.0 * 36.0);
+        assert_close(product_at("(k + 1) / k", "1", "n", &[("n", 9.0)]), 10.0);
+        assert_close(product_at("(k^2 - 1/4)", "1", "3", &[]), 0.75 * 3.75 * 8.75);
+    }
+}
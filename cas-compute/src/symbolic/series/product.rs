@@ -0,0 +1,120 @@
+//! Closed forms of products.
+
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    polynomial::{Polynomial, RationalFunction},
+    simplify::{fraction::{as_rational, rational_expr}, simplify},
+};
+use rug::{Integer, Rational};
+use super::{call, divide, is_infinite, num, pow, sum, MAX_EXPLICIT_TERMS};
+
+/// Returns the bound as an integer, if it is one.
+fn as_integer(bound: &Expr) -> Option<Integer> {
+    as_rational(bound)
+        .filter(|n| *n.denom() == 1)
+        .map(|n| n.into_numer_denom().0)
+}
+
+/// Computes the product `Π_{k=lower}^{upper} expr`, where `k` is the variable `var`. Infinite
+/// products are not supported.
+///
+/// Returns [`None`] if a closed form could not be found.
+pub fn product(expr: &Expr, var: &str, lower: &Expr, upper: &Expr) -> Option<Expr> {
+    if lower.contains_symbol(var) || upper.contains_symbol(var) || is_infinite(lower) || is_infinite(upper) {
+        return None;
+    }
+
+    product_simplified(&simplify(expr), var, lower, upper).map(|result| simplify(&result))
+}
+
+/// Computes the product of an expression that has already been simplified.
+fn product_simplified(expr: &Expr, var: &str, lower: &Expr, upper: &Expr) -> Option<Expr> {
+    if !expr.contains_symbol(var) {
+        // Π_{k=a}^{b} c = c^(b - a + 1)
+        let count = upper.clone() + -lower.clone() + num(1);
+        return Some(pow(expr.clone(), count));
+    }
+
+    match expr {
+        Expr::Mul(factors) => factors.iter()
+            .map(|factor| product_simplified(factor, var, lower, upper))
+            .collect::<Option<_>>()
+            .map(Expr::Mul),
+        // Π q^f(k) = q^(Σ f(k))
+        Expr::Exp(base, exp) if !base.contains_symbol(var) => {
+            Some(pow(Expr::clone(base), sum(exp, var, lower, upper)?))
+        },
+        // Π f(k)^p = (Π f(k))^p
+        Expr::Exp(base, exp) if !exp.contains_symbol(var) => {
+            Some(pow(product_simplified(base, var, lower, upper)?, Expr::clone(exp)))
+        },
+        // Π p(k) / q(k) = Π p(k) / Π q(k)
+        expr => {
+            let f = RationalFunction::from_expr(expr, var)?;
+            Some(divide(polynomial(&f.num, lower, upper)?, polynomial(&f.den, lower, upper)?))
+        },
+    }
+}
+
+/// Computes the product of a polynomial that splits into linear factors over the rationals.
+fn polynomial(poly: &Polynomial, lower: &Expr, upper: &Expr) -> Option<Expr> {
+    let roots = poly.rational_roots();
+    if Some(roots.iter().map(|(_, multiplicity)| multiplicity).sum()) != poly.degree() {
+        return None;
+    }
+
+    // p(k) = c Π (k - root_i)^m_i
+    let count = upper.clone() + -lower.clone() + num(1);
+    let mut factors = vec![pow(rational_expr(&poly.leading()), count)];
+    for (root, multiplicity) in roots {
+        let linear = linear(&-root, lower, upper)?;
+        factors.push(pow(linear, num(multiplicity as i64)));
+    }
+    Some(Expr::Mul(factors.into()))
+}
+
+/// Computes the product `Π_{k=a}^{b} (k + c)`.
+///
+/// If the bounds are integers, the product is computed exactly. Otherwise, it is written as
+/// `(b + c)! / (a + c - 1)!` if `c` is an integer, or `Γ(b + c + 1) / Γ(a + c)` otherwise.
+fn linear(c: &Rational, lower: &Expr, upper: &Expr) -> Option<Expr> {
+    let (a, b) = (as_integer(lower), as_integer(upper));
+    if let (Some(a), Some(b)) = (&a, &b) {
+        let count = Integer::from(b - a).to_i64()?;
+        if count <= MAX_EXPLICIT_TERMS {
+            let mut product = Rational::from(1);
+            let mut k = a.clone();
+            while k <= *b {
+                product *= Rational::from(&k + c);
+                k += 1;
+            }
+            return Some(rational_expr(&product));
+        }
+    }
+
+    let integer = *c.denom() == 1;
+
+    // the numerator, (b + c)! or Γ(b + c + 1)
+    let top = if integer {
+        call("factorial", upper.clone() + rational_expr(c))
+    } else {
+        call("gamma", upper.clone() + rational_expr(&Rational::from(c + 1)))
+    };
+
+    // the denominator, (a + c - 1)! or Γ(a + c), computed exactly if possible
+    let start = Rational::from(c - 1);
+    let bottom = match a {
+        Some(a) if integer => {
+            let n = Integer::from(&a + start.numer()).to_u32()?;
+            if n > MAX_EXPLICIT_TERMS as u32 {
+                call("factorial", rational_expr(&Rational::from(n)))
+            } else {
+                Expr::Primary(Primary::Integer(Integer::from(Integer::factorial(n))))
+            }
+        },
+        _ if integer => call("factorial", lower.clone() + rational_expr(&start)),
+        _ => call("gamma", lower.clone() + rational_expr(c)),
+    };
+
+    Some(divide(top, bottom))
+}
@@ -0,0 +1,437 @@
+//! Closed forms of sums.
+
+use crate::symbolic::{
+    expr::{Expr, Primary},
+    polynomial::{Polynomial, RationalFunction},
+    simplify::{fraction::{as_rational, rational_expr}, simplify},
+};
+use rug::{Integer, Rational};
+use super::{
+    call,
+    divide,
+    is_infinite,
+    num,
+    pow,
+    split_constant,
+    sym,
+    MAX_EXPLICIT_TERMS,
+};
+
+/// The index variable and bounds of a sum.
+struct Bounds<'a> {
+    /// The index variable.
+    var: &'a str,
+
+    /// The lower bound, inclusive.
+    lower: &'a Expr,
+
+    /// The upper bound, inclusive. This may be [`super::INFINITY`].
+    upper: &'a Expr,
+}
+
+impl Bounds<'_> {
+    /// Returns true if the sum is an infinite series.
+    fn infinite(&self) -> bool {
+        is_infinite(self.upper)
+    }
+
+    /// Returns the lower bound as an integer, if it is one.
+    fn lower_integer(&self) -> Option<Integer> {
+        as_rational(self.lower)
+            .filter(|n| *n.denom() == 1)
+            .map(|n| n.into_numer_denom().0)
+    }
+
+    /// Substitutes `lower + offset` for the index variable in the expression.
+    fn at_lower(&self, expr: &Expr, offset: &Rational) -> Expr {
+        expr.substitute(&sym(self.var), &(self.lower.clone() + rational_expr(offset)))
+    }
+
+    /// Substitutes `upper + offset` for the index variable in the expression.
+    fn at_upper(&self, expr: &Expr, offset: &Rational) -> Expr {
+        expr.substitute(&sym(self.var), &(self.upper.clone() + rational_expr(offset)))
+    }
+}
+
+/// Returns true if the expression is zero.
+fn is_zero(expr: &Expr) -> bool {
+    as_rational(expr).is_some_and(|n| n == 0)
+}
+
+/// Evaluates the polynomial at the given expression, exactly if the expression is a number.
+fn eval_poly(poly: &Polynomial, at: &Expr) -> Expr {
+    match as_rational(at) {
+        Some(at) => rational_expr(&poly.eval(&at)),
+        None => poly.to_expr(at),
+    }
+}
+
+/// Computes the sum `Σ_{k=lower}^{upper} expr`, where `k` is the variable `var`. The upper bound
+/// can be the symbol [`super::INFINITY`] to compute an infinite series.
+///
+/// Returns [`None`] if a closed form could not be found, or if the infinite series diverges.
+pub fn sum(expr: &Expr, var: &str, lower: &Expr, upper: &Expr) -> Option<Expr> {
+    if lower.contains_symbol(var) || upper.contains_symbol(var) || is_infinite(lower) {
+        return None;
+    }
+
+    let bounds = Bounds { var, lower, upper };
+    sum_simplified(&simplify(expr), &bounds).map(|result| simplify(&result))
+}
+
+/// Computes the sum of an expression that has already been simplified.
+fn sum_simplified(expr: &Expr, bounds: &Bounds) -> Option<Expr> {
+    if !expr.contains_symbol(bounds.var) {
+        if bounds.infinite() {
+            return is_zero(expr).then(|| num(0));
+        }
+
+        // Σ_{k=a}^{b} c = c (b - a + 1)
+        let count = bounds.upper.clone() + -bounds.lower.clone() + num(1);
+        return Some(expr.clone() * count);
+    }
+
+    if let Some(result) = RationalFunction::from_expr(expr, bounds.var)
+        .and_then(|f| rational(&f, bounds))
+    {
+        return Some(result);
+    }
+
+    if let Expr::Add(terms) = expr {
+        // sum each term separately, or look for pairs of terms that telescope
+        return terms.iter()
+            .map(|term| sum_simplified(term, bounds))
+            .collect::<Option<_>>()
+            .map(Expr::Add)
+            .or_else(|| telescoping(terms, bounds));
+    }
+
+    let (constant, dependent) = split_constant(expr, bounds.var);
+    let result = RationalFunction::from_expr(&dependent, bounds.var)
+        .and_then(|f| rational(&f, bounds))
+        .or_else(|| exponential(&dependent, bounds))?;
+    Some(constant * result)
+}
+
+/// Computes the sum of a rational function.
+///
+/// The polynomial part of the function is summed with Faulhaber's formula. The remaining proper
+/// fraction is decomposed into partial fractions `A_i / (k + c_i)`, which telescope if the `c_i`
+/// differ by integers and the `A_i` sum to zero.
+fn rational(f: &RationalFunction, bounds: &Bounds) -> Option<Expr> {
+    let (quotient, remainder) = f.num.div_rem(&f.den);
+    let mut terms = Vec::new();
+    if !quotient.is_zero() {
+        if bounds.infinite() {
+            return None;
+        }
+        terms.push(faulhaber(&quotient, bounds));
+    }
+    if !remainder.is_zero() {
+        terms.push(partial_fractions(&remainder, &f.den, bounds)?);
+    }
+    Some(Expr::Add(terms.into()).downgrade())
+}
+
+/// Returns the Bernoulli numbers `B_0` through `B_n`, using the convention `B_1 = -1/2`.
+fn bernoulli(n: u32) -> Vec<Rational> {
+    let mut numbers: Vec<Rational> = Vec::with_capacity(n as usize + 1);
+    for m in 0..=n {
+        // Σ_{j=0}^{m} C(m + 1, j) B_j = 0 for m > 0
+        let sum = numbers.iter()
+            .enumerate()
+            .fold(Rational::new(), |sum, (j, b)| {
+                sum + Rational::from(Integer::from(m + 1).binomial(j as u32)) * b
+            });
+        numbers.push(if m == 0 {
+            Rational::from(1)
+        } else {
+            -sum / Rational::from(m + 1)
+        });
+    }
+    numbers
+}
+
+/// Returns the polynomial `F_p(n) = Σ_{k=0}^{n-1} k^p`, using Faulhaber's formula:
+///
+/// ```text
+/// F_p(n) = 1 / (p + 1) Σ_{j=0}^{p} C(p + 1, j) B_j n^(p + 1 - j)
+/// ```
+fn power_sum(p: u32, bernoulli: &[Rational]) -> Polynomial {
+    let mut coeffs = vec![Rational::new(); p as usize + 2];
+    for (j, b) in bernoulli.iter().enumerate().take(p as usize + 1) {
+        let binomial = Integer::from(p + 1).binomial(j as u32);
+        coeffs[p as usize + 1 - j] = Rational::from(binomial) * b / Rational::from(p + 1);
+    }
+    Polynomial::new(coeffs)
+}
+
+/// Sums a polynomial over a finite range using Faulhaber's formula.
+fn faulhaber(poly: &Polynomial, bounds: &Bounds) -> Expr {
+    let degree = poly.degree().unwrap_or(0) as u32;
+    let bernoulli = bernoulli(degree);
+
+    // G(n) = Σ_{k=0}^{n-1} poly(k), so Σ_{k=a}^{b} poly(k) = G(b + 1) - G(a)
+    let g = poly.coeffs()
+        .iter()
+        .enumerate()
+        .fold(Polynomial::zero(), |g, (p, c)| {
+            g + power_sum(p as u32, &bernoulli) * Polynomial::constant(c.clone())
+        });
+    eval_poly(&g.shift(&Rational::from(1)), bounds.upper) + -eval_poly(&g, bounds.lower)
+}
+
+/// Sums the proper fraction `num / den` by decomposing it into partial fractions that telescope.
+fn partial_fractions(num: &Polynomial, den: &Polynomial, bounds: &Bounds) -> Option<Expr> {
+    let roots = den.rational_roots();
+    if roots.iter().any(|(_, multiplicity)| *multiplicity > 1) || Some(roots.len()) != den.degree() {
+        return None;
+    }
+
+    // num / den = Σ A_i / (k + c_i), where c_i = -root_i and A_i = num(root_i) / den'(root_i)
+    let derivative = den.derivative();
+    let fractions = roots.into_iter()
+        .map(|(root, _)| {
+            let residue = num.eval(&root) / derivative.eval(&root);
+            (-root, residue)
+        })
+        .collect::<Vec<_>>();
+    if fractions.iter().fold(Rational::new(), |sum, (_, a)| sum + a) != 0 {
+        return None;
+    }
+
+    // the sum must not contain a pole
+    if let Some(lower) = bounds.lower_integer() {
+        let pole = fractions.iter().any(|(c, _)| {
+            let at = Rational::from(c + &lower);
+            *at.denom() == 1 && at <= 0
+        });
+        if pole {
+            return None;
+        }
+    }
+
+    // with c_i = c_0 + s_i, and since Σ A_i = 0, the sum telescopes to
+    // Σ_i A_i (Σ_{j=1}^{s_i} 1 / (b + j + c_0) - Σ_{j=0}^{s_i - 1} 1 / (a + j + c_0))
+    let c0 = fractions.iter().map(|(c, _)| c).min()?.clone();
+    let mut terms = Vec::new();
+    for (c, a) in &fractions {
+        let shift = Rational::from(c - &c0);
+        if *shift.denom() != 1 || shift > MAX_EXPLICIT_TERMS {
+            return None;
+        }
+
+        let steps = shift.numer().to_i64()?;
+        for j in 0..steps {
+            let offset = Rational::from(&c0 + j);
+            terms.push(divide(
+                rational_expr(&-a.clone()),
+                bounds.lower.clone() + rational_expr(&offset),
+            ));
+        }
+        if !bounds.infinite() {
+            for j in 1..=steps {
+                let offset = Rational::from(&c0 + j);
+                terms.push(divide(
+                    rational_expr(a),
+                    bounds.upper.clone() + rational_expr(&offset),
+                ));
+            }
+        }
+    }
+
+    Some(Expr::Add(terms.into()).downgrade())
+}
+
+/// Returns true if the expression is `factorial(var)^-1`.
+fn is_reciprocal_factorial(expr: &Expr, var: &str) -> bool {
+    match expr {
+        Expr::Exp(base, exp) => {
+            exp.as_integer().is_some_and(|exp| *exp == -1)
+                && matches!(&**base, Expr::Primary(Primary::Call(name, args))
+                    if name == "factorial" && args.len() == 1 && args[0].as_symbol() == Some(var))
+        },
+        _ => false,
+    }
+}
+
+/// Returns `n` if the expression is `var^(-2n)` for a positive integer `n`.
+fn even_reciprocal_power(expr: &Expr, var: &str) -> Option<u32> {
+    let Expr::Exp(base, exp) = expr else {
+        return None;
+    };
+    let exp = exp.as_integer()?.to_i32()?;
+    (base.as_symbol() == Some(var) && exp < 0 && exp % 2 == 0)
+        .then(|| exp.unsigned_abs() / 2)
+}
+
+/// Returns `ζ(2n) = (-1)^(n + 1) B_{2n} (2π)^(2n) / (2 (2n)!)`.
+fn zeta_even(n: u32) -> Expr {
+    let bernoulli = bernoulli(2 * n);
+    let factorial = Integer::from(Integer::factorial(2 * n));
+    let coeff = bernoulli[2 * n as usize].clone().abs()
+        * Rational::from(Integer::from(1) << (2 * n - 1))
+        / Rational::from(factorial);
+    rational_expr(&coeff) * pow(sym("pi"), num(2 * n as i64))
+}
+
+/// Computes the sum of a product of exponential factors `q^(α k + β)`, optionally multiplied by
+/// one of a few factors for which the infinite series is well-known:
+///
+/// - `Σ_{k=0}^{∞} r^k / k! = e^r`
+/// - `Σ_{k=1}^{∞} r^k / k = -ln(1 - r)`, for `-1 <= r < 1`
+/// - `Σ_{k=0}^{∞} (-1)^k / (2k + 1) = π / 4`
+/// - `Σ_{k=1}^{∞} 1 / k^(2n) = ζ(2n)`
+fn exponential(expr: &Expr, bounds: &Bounds) -> Option<Expr> {
+    let factors = match expr {
+        Expr::Mul(factors) => factors.to_vec(),
+        expr => vec![expr.clone()],
+    };
+
+    // q^(α k + β) = (q^α)^k q^β
+    let mut ratio = Vec::new();
+    let mut coeff = Vec::new();
+    let mut rest = Vec::new();
+    for factor in factors {
+        match &factor {
+            Expr::Exp(base, exp) if !base.contains_symbol(bounds.var) => {
+                let linear = Polynomial::from_expr(exp, bounds.var)
+                    .filter(|poly| poly.degree().is_none_or(|degree| degree <= 1))?;
+                let coeffs = linear.coeffs();
+                let alpha = coeffs.get(1).cloned().unwrap_or_default();
+                let beta = coeffs.first().cloned().unwrap_or_default();
+                ratio.push(pow(Expr::clone(base), rational_expr(&alpha)));
+                coeff.push(pow(Expr::clone(base), rational_expr(&beta)));
+            },
+            _ => rest.push(factor),
+        }
+    }
+    let ratio = simplify(&Expr::Mul(ratio.into()).downgrade());
+    let coeff = Expr::Mul(coeff.into()).downgrade();
+    let ratio_value = as_rational(&ratio);
+
+    let result = match rest.as_slice() {
+        [] => geometric(&ratio, ratio_value.as_ref(), bounds)?,
+        [factor] if bounds.infinite() => {
+            let (start, value) = if is_reciprocal_factorial(factor, bounds.var) {
+                (0, pow(sym("e"), ratio.clone()))
+            } else if let Some(n) = even_reciprocal_power(factor, bounds.var) {
+                if ratio_value.as_ref().is_none_or(|r| *r != 1) {
+                    return None;
+                }
+                (1, zeta_even(n))
+            } else if *factor == pow(sym(bounds.var), num(-1)) {
+                let r = ratio_value.as_ref()?;
+                if *r < -1 || *r >= 1 {
+                    return None;
+                }
+                let one_minus_r = rational_expr(&(Rational::from(1) - r));
+                (1, -call("ln", one_minus_r))
+            } else if let Expr::Exp(base, exp) = factor {
+                let odd = Polynomial::new(vec![Rational::from(1), Rational::from(2)]);
+                let leibniz = exp.as_integer().is_some_and(|exp| *exp == -1)
+                    && Polynomial::from_expr(base, bounds.var).is_some_and(|base| base == odd)
+                    && ratio_value.as_ref().is_some_and(|r| *r == -1);
+                if !leibniz {
+                    return None;
+                }
+                (0, divide(sym("pi"), num(4)))
+            } else {
+                return None;
+            };
+
+            // subtract the terms before the lower bound
+            let lower = bounds.lower_integer()?;
+            let skipped = Integer::from(&lower - start).to_i64()?;
+            if !(0..=MAX_EXPLICIT_TERMS).contains(&skipped) {
+                return None;
+            }
+            let term = pow(ratio.clone(), sym(bounds.var)) * factor.clone();
+            let initial = (start..start + skipped)
+                .map(|k| -term.substitute(&sym(bounds.var), &num(k)))
+                .collect::<Vec<_>>();
+            value + Expr::Add(initial.into()).downgrade()
+        },
+        _ => return None,
+    };
+
+    Some(coeff * result)
+}
+
+/// Computes the geometric series `Σ r^k`.
+fn geometric(ratio: &Expr, ratio_value: Option<&Rational>, bounds: &Bounds) -> Option<Expr> {
+    if ratio_value.is_some_and(|r| *r == 1) {
+        return if bounds.infinite() {
+            None
+        } else {
+            Some(bounds.upper.clone() + -bounds.lower.clone() + num(1))
+        };
+    }
+
+    let first = pow(ratio.clone(), bounds.lower.clone());
+    if bounds.infinite() {
+        // Σ_{k=a}^{∞} r^k = r^a / (1 - r), for |r| < 1
+        let r = ratio_value?;
+        if *r.as_abs() >= 1 {
+            return None;
+        }
+        Some(divide(first, num(1) + -ratio.clone()))
+    } else {
+        // Σ_{k=a}^{b} r^k = (r^(b + 1) - r^a) / (r - 1)
+        let last = pow(ratio.clone(), bounds.upper.clone() + num(1));
+        Some(divide(last + -first, ratio.clone() + num(-1)))
+    }
+}
+
+/// Looks for a pair of terms of the form `f(k) - f(k + m)` for small `m`, which telescopes to
+/// `Σ_{j=0}^{m-1} f(a + j) - Σ_{j=1}^{m} f(b + j)`. The remaining terms are summed separately.
+fn telescoping(terms: &[Expr], bounds: &Bounds) -> Option<Expr> {
+    let var = sym(bounds.var);
+    for (i, f) in terms.iter().enumerate() {
+        // an infinite series only telescopes if `f(k) -> 0`
+        if bounds.infinite() {
+            let vanishes = RationalFunction::from_expr(f, bounds.var)
+                .is_some_and(|f| f.num.degree().is_none_or(|num| Some(num) < f.den.degree()));
+            if !vanishes {
+                continue;
+            }
+        }
+
+        for (j, g) in terms.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+
+            for m in 1..=3 {
+                let shifted = f.substitute(&var, &(var.clone() + num(m)));
+                if !is_zero(&simplify(&(shifted + g.clone()))) {
+                    continue;
+                }
+
+                let rest = terms.iter()
+                    .enumerate()
+                    .filter(|(index, _)| *index != i && *index != j)
+                    .map(|(_, term)| term.clone())
+                    .collect::<Vec<_>>();
+                let rest = if rest.is_empty() {
+                    num(0)
+                } else {
+                    sum_simplified(&simplify(&Expr::Add(rest.into()).downgrade()), bounds)?
+                };
+
+                let mut result = vec![rest];
+                for offset in 0..m {
+                    result.push(bounds.at_lower(f, &Rational::from(offset)));
+                }
+                if !bounds.infinite() {
+                    for offset in 1..=m {
+                        result.push(-bounds.at_upper(f, &Rational::from(offset)));
+                    }
+                }
+                return Some(Expr::Add(result.into()));
+            }
+        }
+    }
+
+    None
+}
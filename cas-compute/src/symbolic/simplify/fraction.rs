@@ -3,7 +3,8 @@
 use crate::approx::approximate_rational;
 use crate::primitive::int;
 use crate::symbolic::expr::{Expr, Primary};
-use rug::Integer;
+use rug::{ops::Pow, Integer, Rational};
+use std::sync::Arc;
 
 /// Create an [`Expr`] representing a fraction with the given numerator and denominator.
 ///
@@ -12,8 +13,8 @@ use rug::Integer;
 pub(crate) fn make_fraction(numerator: Expr, denominator: Expr) -> Expr {
     numerator *
         Expr::Exp(
-            Box::new(denominator),
-            Box::new(Expr::Primary(Primary::Integer(int(-1)))),
+            Arc::new(denominator),
+            Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
         )
 }
 
@@ -145,7 +146,12 @@ pub(crate) fn extract_explicit_frac(expr: &mut Expr) -> Option<(Integer, Integer
             *expr = Expr::Primary(Primary::Integer(int(1)));
             Some(rational.into_numer_denom())
         },
-        Expr::Mul(factors) => extract_integer_fraction(factors, false, true),
+        Expr::Mul(factors) => {
+            let mut new_factors = factors.to_vec();
+            let fraction = extract_integer_fraction(&mut new_factors, false, true);
+            *factors = new_factors.into();
+            fraction
+        },
         Expr::Exp(..) => {
             if expr.is_integer_recip() {
                 let denominator = std::mem::replace(expr, Expr::Primary(Primary::Integer(int(1))))
@@ -159,3 +165,51 @@ pub(crate) fn extract_explicit_frac(expr: &mut Expr) -> Option<(Integer, Integer
         _ => None,
     }
 }
+
+/// Returns the exact rational value of the given expression, if it is a number.
+pub(crate) fn as_rational(expr: &Expr) -> Option<Rational> {
+    match expr {
+        Expr::Primary(Primary::Integer(n)) => Some(Rational::from(n)),
+        Expr::Primary(Primary::Float(n)) => Some(approximate_rational(n)),
+        Expr::Primary(_) => None,
+        Expr::Add(terms) => terms.iter()
+            .try_fold(Rational::new(), |sum, term| Some(sum + as_rational(term)?)),
+        Expr::Mul(factors) => factors.iter()
+            .try_fold(Rational::from(1), |product, factor| Some(product * as_rational(factor)?)),
+        Expr::Exp(base, exp) => {
+            let base = as_rational(base)?;
+            let exp = exp.as_integer()?.to_i32()?;
+            if base == 0 && exp < 0 {
+                None
+            } else {
+                Some(base.pow(exp))
+            }
+        },
+    }
+}
+
+/// Converts a rational number into an [`Expr`].
+pub(crate) fn rational_expr(n: &Rational) -> Expr {
+    if *n.denom() == 1 {
+        Expr::Primary(Primary::Integer(n.numer().clone()))
+    } else {
+        make_fraction(
+            Expr::Primary(Primary::Integer(n.numer().clone())),
+            Expr::Primary(Primary::Integer(n.denom().clone())),
+        )
+    }
+}
+
+/// Returns the square root of the given non-negative rational number. The result is exact if the
+/// number is a perfect square, otherwise it is written as `sqrt(p q) / q`.
+pub(crate) fn sqrt_rational(n: &Rational) -> Expr {
+    let (numer, denom) = n.clone().into_numer_denom();
+    let product = numer * &denom;
+    if product.is_perfect_square() {
+        rational_expr(&Rational::from((product.sqrt(), denom)))
+    } else if denom == 1 {
+        Expr::Primary(Primary::Integer(product)).sqrt()
+    } else {
+        make_fraction(Expr::Primary(Primary::Integer(product)).sqrt(), Expr::Primary(Primary::Integer(denom)))
+    }
+}
@@ -28,6 +28,7 @@ use crate::primitive::{float, int};
 use crate::symbolic::StepCollector;
 use step::Step;
 use super::expr::{Expr, Primary};
+use std::sync::Arc;
 
 /// The default complexity heuristic function.
 ///
@@ -45,10 +46,11 @@ pub fn default_complexity(expr: &Expr) -> usize {
             Expr::Primary(primary) => {
                 match primary {
                     Primary::Integer(num) => int(num.abs_ref())
-                        .to_usize().unwrap(),
+                        .to_usize().unwrap_or(usize::MAX),
                     Primary::Float(num) => float(num.abs_ref())
-                        .to_integer().unwrap()
-                        .to_usize().unwrap(),
+                        .to_integer()
+                        .and_then(|num| num.to_usize())
+                        .unwrap_or(usize::MAX),
                     Primary::Symbol(sym) => sym.len(),
                     Primary::Call(name, args) => name.len() + args.len(),
                 }
@@ -57,7 +59,7 @@ pub fn default_complexity(expr: &Expr) -> usize {
             Expr::Mul(factors) => 2 + factors.len(),
             Expr::Exp(_, _) => 1,
         })
-        .sum()
+        .fold(0, usize::saturating_add)
 }
 
 /// Base implementation of the simplification algorithm.
@@ -101,33 +103,33 @@ where
                 return (expr, changed_at_least_once);
             },
             Expr::Add(ref terms) => {
-                let mut output = Expr::Add(Vec::new());
+                let mut output = Vec::with_capacity(terms.len());
                 for term in terms {
                     let result = inner_simplify_with(term, complexity, step_collector);
-                    output += result.0;
+                    output.push(result.0);
 
                     // use |= instead of = to not reset these variables to false if already true
                     changed_in_this_pass |= result.1;
                     changed_at_least_once |= result.1;
                 }
-                expr = output;
+                expr = output.into_iter().sum::<Expr>().downgrade();
             },
-            Expr::Mul(ref mut factors) => {
-                let mut output = Expr::Mul(Vec::new());
-                for factor in factors.iter_mut() {
+            Expr::Mul(ref factors) => {
+                let mut output = Vec::with_capacity(factors.len());
+                for factor in factors {
                     let result = inner_simplify_with(factor, complexity, step_collector);
-                    output *= result.0;
+                    output.push(result.0);
                     changed_in_this_pass |= result.1;
                     changed_at_least_once |= result.1;
                 }
-                expr = output;
+                expr = output.into_iter().product::<Expr>().downgrade();
             },
             Expr::Exp(ref mut lhs, ref mut rhs) => {
                 let result_l = inner_simplify_with(lhs, complexity, step_collector);
                 let result_r = inner_simplify_with(rhs, complexity, step_collector);
 
-                *lhs = Box::new(result_l.0);
-                *rhs = Box::new(result_r.0);
+                *lhs = Arc::new(result_l.0);
+                *rhs = Arc::new(result_r.0);
                 changed_in_this_pass |= result_l.1 || result_r.1;
                 changed_at_least_once |= result_l.1 || result_r.1;
             },
@@ -194,7 +196,7 @@ mod tests {
         assert_eq!(simplified_expr, Expr::Mul(vec![
             Expr::Primary(Primary::Symbol(String::from("a"))),
             Expr::Primary(Primary::Integer(int(3))),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -218,7 +220,7 @@ mod tests {
                 -Expr::Primary(Primary::Symbol(String::from("pi"))),
                 Expr::Primary(Primary::Integer(int(3))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -228,9 +230,9 @@ mod tests {
             Expr::Mul(vec![
                 Expr::Primary(Primary::Symbol(String::from("m"))),
                 Expr::Primary(Primary::Integer(int(-30))),
-            ]),
+            ].into()),
             Expr::Primary(Primary::Integer(int(33))),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -241,33 +243,33 @@ mod tests {
         assert_eq!(simplified_expr, Expr::Add(vec![
             Expr::Mul(vec![
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("x")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(3)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("x")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(3)))),
                 ),
                 Expr::Primary(Primary::Symbol(String::from("y"))),
-            ]),
+            ].into()),
             Expr::Mul(vec![
                 Expr::Primary(Primary::Integer(int(20))),
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("y")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("y")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
                 ),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
-            ]),
+            ].into()),
             Expr::Mul(vec![
                 Expr::Primary(Primary::Integer(int(5))),
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Symbol(String::from("x")))),
-                    Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Primary(Primary::Symbol(String::from("x")))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
                 ),
                 Expr::Primary(Primary::Symbol(String::from("y"))),
-            ]),
+            ].into()),
             Expr::Mul(vec![
                 Expr::Primary(Primary::Integer(int(-27))),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
                 Expr::Primary(Primary::Symbol(String::from("y"))),
-            ]),
-        ]));
+            ].into()),
+        ].into()));
     }
 
     #[test]
@@ -276,7 +278,7 @@ mod tests {
         assert_eq!(simplified_expr, Expr::Mul(vec![
             Expr::Primary(Primary::Integer(int(3))),
             Expr::Primary(Primary::Symbol(String::from("x"))),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -286,9 +288,9 @@ mod tests {
             Expr::Mul(vec![
                 Expr::Primary(Primary::Float(float_from_str("5.15"))),
                 Expr::Primary(Primary::Symbol(String::from("x"))),
-            ]),
+            ].into()),
             Expr::Primary(Primary::Float(float_from_str("11.33449"))),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -303,7 +305,7 @@ mod tests {
                 Expr::Primary(Primary::Integer(int(1133449))),
                 Expr::Primary(Primary::Integer(int(100000))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -321,8 +323,23 @@ mod tests {
                 // :)
                 Expr::Primary(Primary::Float(float_from_str("5.51"))),
                 Expr::Primary(Primary::Symbol(String::from("y"))),
-            ]),
-        ]));
+            ].into()),
+        ].into()));
+    }
+
+    #[test]
+    fn combine_many_like_terms() {
+        // 600 terms, made of 20 distinct monomials with 30 copies each
+        let input = (0..600)
+            .map(|i| format!("x^{} * y^{}", i % 4, i % 5))
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let simplified_expr = simplify_str(&input);
+        let expected = simplify_str(&(0..4)
+            .flat_map(|i| (0..5).map(move |j| format!("30x^{} * y^{}", i, j)))
+            .collect::<Vec<_>>()
+            .join(" + "));
+        assert_eq!(simplified_expr, expected);
     }
 
     #[test]
@@ -343,22 +360,22 @@ mod tests {
         let simplified_expr = simplify_str("a * b * a^3 * c^2 * d^2 * a^2 * b^4 * d^2");
         assert_eq!(simplified_expr, Expr::Mul(vec![
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("d".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(4)))),
+                Arc::new(Expr::Primary(Primary::Symbol("d".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(4)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("b".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(5)))),
+                Arc::new(Expr::Primary(Primary::Symbol("b".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(5)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("a".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(6)))),
+                Arc::new(Expr::Primary(Primary::Symbol("a".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(6)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("c".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                Arc::new(Expr::Primary(Primary::Symbol("c".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(2)))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -366,21 +383,21 @@ mod tests {
         let simplified_expr = simplify_str("(a + 1 + b) * (b + a) * (b + a + 1) * (a + b)");
         assert_eq!(simplified_expr, Expr::Mul(vec![
             Expr::Exp(
-                Box::new(Expr::Add(vec![
+                Arc::new(Expr::Add(vec![
                     Expr::Primary(Primary::Symbol("a".to_string())),
                     Expr::Primary(Primary::Symbol("b".to_string())),
                     Expr::Primary(Primary::Integer(int(1))),
-                ])),
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                ].into())),
+                Arc::new(Expr::Primary(Primary::Integer(int(2)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Add(vec![
+                Arc::new(Expr::Add(vec![
                     Expr::Primary(Primary::Symbol("a".to_string())),
                     Expr::Primary(Primary::Symbol("b".to_string())),
-                ])),
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                ].into())),
+                Arc::new(Expr::Primary(Primary::Integer(int(2)))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -406,22 +423,22 @@ mod tests {
         let simplified_expr = simplify_str("3p^-5q^9r^7/(12p^-2q*r^2)");
         assert_eq!(simplified_expr, Expr::Mul(vec![
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("r".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(5)))),
+                Arc::new(Expr::Primary(Primary::Symbol("r".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(5)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("q".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(8)))),
+                Arc::new(Expr::Primary(Primary::Symbol("q".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(8)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("p".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(-3)))),
+                Arc::new(Expr::Primary(Primary::Symbol("p".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(-3)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Integer(int(4)))),
-                Box::new(Expr::Primary(Primary::Integer(int(-1)))),
+                Arc::new(Expr::Primary(Primary::Integer(int(4)))),
+                Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -431,28 +448,29 @@ mod tests {
 
         // sqrt(2)/4 + sqrt(6)/4
         assert_eq!(simplified_expr, Expr::Add(vec![
-            // sqrt(2)/4 = 2^(-3/2)
-            // the result is a denominator that is not rationalized
-            // TODO: rationalize the denominator
-            Expr::Exp(
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
-                Box::new(make_fraction(
-                    Expr::Primary(Primary::Integer(int(-3))),
-                    Expr::Primary(Primary::Integer(int(2))),
-                )),
+            // sqrt(2)/4
+            make_fraction(
+                Expr::Exp(
+                    Arc::new(Expr::Primary(Primary::Integer(int(2)))),
+                    Arc::new(Expr::Exp(
+                        Arc::new(Expr::Primary(Primary::Integer(int(2)))),
+                        Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
+                    )),
+                ),
+                Expr::Primary(Primary::Integer(int(4))),
             ),
             // sqrt(6)/4
             make_fraction(
                 Expr::Exp(
-                    Box::new(Expr::Primary(Primary::Integer(int(6)))),
-                    Box::new(Expr::Exp(
-                        Box::new(Expr::Primary(Primary::Integer(int(2)))),
-                        Box::new(Expr::Primary(Primary::Integer(int(-1)))),
+                    Arc::new(Expr::Primary(Primary::Integer(int(6)))),
+                    Arc::new(Expr::Exp(
+                        Arc::new(Expr::Primary(Primary::Integer(int(2)))),
+                        Arc::new(Expr::Primary(Primary::Integer(int(-1)))),
                     )),
                 ),
                 Expr::Primary(Primary::Integer(int(4))),
             ),
-        ]));
+        ].into()));
     }
 
     #[test]
@@ -465,7 +483,7 @@ mod tests {
                 Expr::Primary(Primary::Symbol("x".to_string())),
             ),
             Expr::Primary(Primary::Integer(int(2))),
-        ]));
+        ].into()));
         assert!(steps.contains(&Step::DistributiveProperty));
     }
 
@@ -475,15 +493,15 @@ mod tests {
         let (simplified_expr, steps) = simplify_str_steps("x^2 * (1 + x + y/x^2)");
         assert_eq!(simplified_expr, Expr::Add(vec![
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("x".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(2)))),
+                Arc::new(Expr::Primary(Primary::Symbol("x".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(2)))),
             ),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("x".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(3)))),
+                Arc::new(Expr::Primary(Primary::Symbol("x".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(3)))),
             ),
             Expr::Primary(Primary::Symbol("y".to_string())),
-        ]));
+        ].into()));
         assert!(steps.contains(&Step::DistributiveProperty));
     }
 
@@ -528,9 +546,22 @@ mod tests {
             Expr::Mul(vec![
                 Expr::Primary(Primary::Integer(int(3))),
                 Expr::Primary(Primary::Symbol("i".to_string())),
-            ]),
+            ].into()),
             Expr::Primary(Primary::Integer(int(1))),
-        ]));
+        ].into()));
+    }
+
+    #[test]
+    fn complex_division() {
+        let (simplified_expr, steps) = simplify_str_steps("(1+2i)/(3-i)");
+        assert_eq!(simplified_expr, simplify_str("1/10 + 7i/10"));
+        assert!(steps.contains(&Step::ComplexDivision));
+    }
+
+    #[test]
+    fn complex_parts() {
+        assert_eq!(simplify_str("re((1+2i)*(3-i)) + im((1+i)^2)"), Expr::Primary(Primary::Integer(int(7))));
+        assert_eq!(simplify_str("abs(3+4i) + conj(2-i)"), simplify_str("7+i"));
     }
 
     #[test]
@@ -545,8 +576,8 @@ mod tests {
 
         // -sqrt(2)/2 = -2^(1/2)/2 = -2^(-1/2)
         assert_eq!(simplified_expr, -Expr::Exp(
-            Box::new(Expr::Primary(Primary::Integer(int(2)))),
-            Box::new(make_fraction(
+            Arc::new(Expr::Primary(Primary::Integer(int(2)))),
+            Arc::new(make_fraction(
                 Expr::Primary(Primary::Integer(int(-1))),
                 Expr::Primary(Primary::Integer(int(2))),
             )),
@@ -655,8 +686,8 @@ mod tests {
         assert_eq!(simplified_expr, Expr::Mul(vec![
             Expr::Primary(Primary::Integer(int(8))),
             Expr::Exp(
-                Box::new(Expr::Primary(Primary::Symbol("a".to_string()))),
-                Box::new(Expr::Primary(Primary::Integer(int(73)))),
+                Arc::new(Expr::Primary(Primary::Symbol("a".to_string()))),
+                Arc::new(Expr::Primary(Primary::Integer(int(73)))),
             ),
             Expr::Primary(Primary::Symbol("b".to_string())),
             Expr::Primary(Primary::Call(
@@ -666,9 +697,9 @@ mod tests {
                         Expr::Primary(Primary::Integer(int(2634))),
                         Expr::Primary(Primary::Symbol("a".to_string())),
                         Expr::Primary(Primary::Symbol("b".to_string())),
-                    ]),
+                    ].into()),
                 ],
             )),
-        ]));
+        ].into()));
     }
 }
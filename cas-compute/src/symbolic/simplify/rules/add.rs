@@ -6,6 +6,7 @@ use crate::symbolic::{
     simplify::{fraction::{extract_explicit_frac, make_fraction, extract_fractional}, rules::do_add, step::Step},
     step_collector::StepCollector,
 };
+use std::collections::HashMap;
 
 /// Extension of the `+=` implementation for [`Expr`] to also support adding fractions.
 fn add_assign(lhs: &mut Expr, rhs: Expr) {
@@ -51,7 +52,7 @@ pub fn add_zero(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Op
         if new_terms.len() == terms.len() {
             None
         } else {
-            Some(Expr::Add(new_terms).downgrade())
+            Some(Expr::Add(new_terms.into()).downgrade())
         }
     })?;
 
@@ -68,9 +69,6 @@ pub fn add_zero(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Op
 /// etc.
 pub fn combine_like_terms(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
     let opt = do_add(expr, |terms| {
-        let mut new_terms = terms.to_vec();
-        let mut current_term_idx = 0;
-
         /// Utility function to extract the rational coefficient and factors of an expression. If
         /// the expression is not [`Expr::Mul`], the coefficient is 1.
         ///
@@ -85,13 +83,13 @@ pub fn combine_like_terms(expr: &Expr, step_collector: &mut dyn StepCollector<St
                     (expr.clone(), Expr::Primary(Primary::Integer(int(1))))
                 },
                 Expr::Mul(factors) => {
-                    let mut factors = factors.clone();
+                    let mut factors = factors.to_vec();
                     let fraction = extract_fractional(&mut factors)
                         .unwrap_or(Expr::Primary(Primary::Integer(int(1))));
 
                     (
                         fraction,
-                        Expr::Mul(factors).downgrade(),
+                        Expr::Mul(factors.into()).downgrade(),
                     )
                 },
                 Expr::Exp(..) => {
@@ -105,40 +103,36 @@ pub fn combine_like_terms(expr: &Expr, step_collector: &mut dyn StepCollector<St
             }
         }
 
-        // this is O(n^2) worst case, due to scanning the whole vec for each term
-        // TODO: optimize with hashing?
-        while current_term_idx < new_terms.len() {
-            let (mut current_term_coeff, current_term_factors) = get_coeff(&new_terms[current_term_idx]);
-
-            // look at every term after `current_term`
-            let mut next_term_idx = current_term_idx + 1;
-            while next_term_idx < new_terms.len() {
-                let (next_term_coeff, next_term_factors) = get_coeff(&new_terms[next_term_idx]);
-
+        // group the terms by their factors, keeping the groups in order of first appearance
+        let mut groups: Vec<(Expr, Expr)> = Vec::with_capacity(terms.len());
+        let mut indices: HashMap<Expr, usize> = HashMap::with_capacity(terms.len());
+        for term in terms {
+            let (coeff, factors) = get_coeff(term);
+            match indices.get(&factors) {
                 // factors must be strictly equal
-                if current_term_factors == next_term_factors {
-                    // if so, apply a*n + a*m = (n+m)*a
-                    add_assign(&mut current_term_coeff, next_term_coeff);
-                    new_terms.swap_remove(next_term_idx);
-                } else {
-                    next_term_idx += 1;
-                }
-            }
-
-            if current_term_coeff.as_integer().map(|n| n == &1).unwrap_or(false) {
-                new_terms[current_term_idx] = current_term_factors;
-            } else {
-                new_terms[current_term_idx] =
-                    current_term_coeff * current_term_factors;
+                // if so, apply a*n + a*m = (n+m)*a
+                Some(&idx) => add_assign(&mut groups[idx].0, coeff),
+                None => {
+                    indices.insert(factors.clone(), groups.len());
+                    groups.push((coeff, factors));
+                },
             }
-
-            current_term_idx += 1;
         }
 
+        let new_terms = groups.into_iter()
+            .map(|(coeff, factors)| {
+                if coeff.as_integer().map(|n| n == &1).unwrap_or(false) {
+                    factors
+                } else {
+                    coeff * factors
+                }
+            })
+            .collect::<Vec<_>>();
+
         if new_terms.len() == terms.len() {
             None
         } else {
-            Some(Expr::Add(new_terms).downgrade())
+            Some(Expr::Add(new_terms.into()).downgrade())
         }
     })?;
 
@@ -5,6 +5,7 @@ use crate::symbolic::{
     simplify::{rules::{do_multiply, do_power}, step::Step},
     step_collector::StepCollector,
 };
+use std::sync::Arc;
 
 /// `a*(b+c) = a*b + a*c`
 pub fn distributive_property(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
@@ -27,10 +28,10 @@ pub fn distributive_property(expr: &Expr, step_collector: &mut dyn StepCollector
 
         let new_terms = add_factor_terms.into_iter()
             .map(|term| {
-                Expr::Mul(factors_to_distribute.clone()) * term
+                Expr::Mul(factors_to_distribute.clone().into()) * term
             })
             .collect::<Vec<_>>();
-        Some(Expr::Add(new_terms))
+        Some(Expr::Add(new_terms.into()))
     })?;
 
     // keep the step collection logic outside of the closure to make it implement `Fn`
@@ -44,12 +45,12 @@ pub fn distribute_power(expr: &Expr, step_collector: &mut dyn StepCollector<Step
         if let Expr::Mul(factors) = lhs {
             let new_factors = factors.iter()
                 .map(|factor| Expr::Exp(
-                    Box::new(factor.clone()),
-                    Box::new(rhs.clone()),
+                    Arc::new(factor.clone()),
+                    Arc::new(rhs.clone()),
                 ))
                 .collect::<Vec<_>>();
 
-            return Some(Expr::Mul(new_factors));
+            return Some(Expr::Mul(new_factors.into()));
         }
 
         None
@@ -2,8 +2,9 @@
 
 use crate::primitive::int;
 use crate::symbolic::{
+    complex::{join, split},
     expr::{Expr, Primary},
-    simplify::{rules::do_power, step::Step},
+    simplify::{rules::{do_call, do_power}, step::Step},
     step_collector::StepCollector,
 };
 
@@ -72,12 +73,117 @@ pub fn i_pow_3(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Opt
     Some(opt)
 }
 
+/// `(a+bi)^n = c+di`, `n > 0`
+pub fn power_complex(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_power(expr, |lhs, rhs| {
+        if matches!(lhs, Expr::Add(_)) && lhs.contains_symbol("i") && rhs.as_integer()?.is_positive() {
+            let (re, im) = split(expr)?;
+            Some(join(re, im))
+        } else {
+            None
+        }
+    })?;
+
+    step_collector.push(Step::ComplexPower);
+    Some(opt)
+}
+
+/// `1/(a+bi) = (a-bi)/(a^2+b^2)`
+pub fn divide_complex(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_power(expr, |lhs, rhs| {
+        if lhs.contains_symbol("i") && rhs.as_integer()?.is_negative() {
+            let (re, im) = split(expr)?;
+            Some(join(re, im))
+        } else {
+            None
+        }
+    })?;
+
+    step_collector.push(Step::ComplexDivision);
+    Some(opt)
+}
+
+/// `re(a+bi) = a`
+pub fn re(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_call(expr, "re", |args| {
+        let [z] = args else { return None };
+        split(z).map(|(re, _)| re)
+    })?;
+
+    step_collector.push(Step::Re);
+    Some(opt)
+}
+
+/// `im(a+bi) = b`
+pub fn im(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_call(expr, "im", |args| {
+        let [z] = args else { return None };
+        split(z).map(|(_, im)| im)
+    })?;
+
+    step_collector.push(Step::Im);
+    Some(opt)
+}
+
+/// `conj(a+bi) = a-bi`
+pub fn conj(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_call(expr, "conj", |args| {
+        let [z] = args else { return None };
+        split(z).map(|(re, im)| join(re, -im))
+    })?;
+
+    step_collector.push(Step::Conj);
+    Some(opt)
+}
+
+/// `abs(a+bi) = sqrt(a^2+b^2)`, `b != 0`
+pub fn abs(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_call(expr, "abs", |args| {
+        let [z] = args else { return None };
+        if !z.contains_symbol("i") {
+            return None;
+        }
+        let (re, im) = split(z)?;
+        Some(Expr::Primary(Primary::Call(
+            "sqrt".to_string(),
+            vec![re.clone() * re + im.clone() * im],
+        )))
+    })?;
+
+    step_collector.push(Step::Abs);
+    Some(opt)
+}
+
+/// `arg(a+bi) = atan2(b, a)`, `b != 0`
+pub fn arg(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_call(expr, "arg", |args| {
+        let [z] = args else { return None };
+        if !z.contains_symbol("i") {
+            return None;
+        }
+        let (re, im) = split(z)?;
+        Some(Expr::Primary(Primary::Call("atan2".to_string(), vec![im, re])))
+    })?;
+
+    step_collector.push(Step::Arg);
+    Some(opt)
+}
+
 /// Applies all imaginary unit rules.
 ///
-/// All imaginary unit rules will reduce the complexity of the expression.
+/// The `i^n` rules will reduce the complexity of the expression. The remaining rules put complex
+/// expressions into the rectangular form `a+bi`, which may increase their complexity, but allows
+/// like terms to be combined.
 pub fn all(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
     i_pow_0(expr, step_collector)
         .or_else(|| i_pow_1(expr, step_collector))
         .or_else(|| i_pow_2(expr, step_collector))
         .or_else(|| i_pow_3(expr, step_collector))
+        .or_else(|| power_complex(expr, step_collector))
+        .or_else(|| divide_complex(expr, step_collector))
+        .or_else(|| re(expr, step_collector))
+        .or_else(|| im(expr, step_collector))
+        .or_else(|| conj(expr, step_collector))
+        .or_else(|| abs(expr, step_collector))
+        .or_else(|| arg(expr, step_collector))
 }
@@ -7,6 +7,7 @@ use crate::symbolic::{
     simplify::{fraction::{extract_integer_fraction, make_fraction}, rules::do_multiply, step::Step},
     step_collector::StepCollector,
 };
+use std::{collections::HashMap, sync::Arc};
 
 /// `0*a = 0`
 /// `a*0 = 0`
@@ -41,7 +42,7 @@ pub fn multiply_one(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -
         if new_factors.len() == factors.len() {
             None
         } else {
-            Some(Expr::Mul(new_factors).downgrade())
+            Some(Expr::Mul(new_factors.into()).downgrade())
         }
     })?;
 
@@ -67,7 +68,7 @@ pub fn reduce_numerical_fraction(expr: &Expr, step_collector: &mut dyn StepColle
         }
 
         // insert the reduced fraction back into the factors
-        Some(Expr::Mul(new_factors) * make_fraction(
+        Some(Expr::Mul(new_factors.into()) * make_fraction(
             Expr::Primary(Primary::Integer(numerator / &gcd)),
             Expr::Primary(Primary::Integer(denominator / &gcd)),
         ))
@@ -84,9 +85,6 @@ pub fn reduce_numerical_fraction(expr: &Expr, step_collector: &mut dyn StepColle
 /// etc.
 pub fn combine_like_factors(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
     let opt = do_multiply(expr, |factors| {
-        let mut new_factors = factors.to_vec();
-        let mut current_factor_idx = 0;
-
         /// Utility function to extract the base and exponent of an expression. If the expression
         /// is not [`Expr::Exp`], the exponent is `1`.
         ///
@@ -94,55 +92,78 @@ pub fn combine_like_factors(expr: &Expr, step_collector: &mut dyn StepCollector<
         /// - `a` -> `(a, 1)`
         fn get_exp(expr: &Expr) -> (Expr, Expr) {
             match expr {
-                Expr::Exp(lhs, rhs) => (*lhs.clone(), *rhs.clone()),
+                Expr::Exp(lhs, rhs) => (Expr::clone(lhs), Expr::clone(rhs)),
                 expr => (expr.clone(), Expr::Primary(Primary::Integer(int(1)))),
             }
         }
 
-        // this is O(n^2) worst case, due to scanning the whole vec for each factor
-        // TODO: optimize with hashing?
-        while current_factor_idx < new_factors.len() {
-            let (mut current_factor, mut current_factor_exp) = get_exp(&new_factors[current_factor_idx]);
-
-            // look at every factor after `current_factor`
-            let mut next_factor_idx = current_factor_idx + 1;
-            while next_factor_idx < new_factors.len() {
-                let (next_factor, next_factor_exp) = get_exp(&new_factors[next_factor_idx]);
-
-                if current_factor_exp == next_factor_exp
-                    && (current_factor.is_integer() && next_factor.is_integer()
-                        || current_factor.is_float() && next_factor.is_float()) {
-                    // degrees must be strictly equal
-                    // if they are, apply a^c*b^c = (a*b)^c
-                    current_factor *= next_factor;
-                    new_factors.swap_remove(next_factor_idx);
-                } else if current_factor == next_factor {
-                    // bases must be strictly equal
-                    // if they are, apply a^b*a^c = a^(b+c)
-                    current_factor_exp += next_factor_exp;
-                    new_factors.swap_remove(next_factor_idx);
-                } else {
-                    next_factor_idx += 1;
+        /// Returns the key used to find numbers raised to the same power as the given factor, so
+        /// that integers and floats are kept separate.
+        fn exp_key(base: &Expr, exp: &Expr) -> Option<(bool, Expr)> {
+            (base.is_integer() || base.is_float()).then(|| (base.is_float(), exp.clone()))
+        }
+
+        // each factor is combined with the first group of factors that it matches, found by
+        // looking up its base, or if it is a number, its exponent
+        let mut groups: Vec<(Expr, Expr)> = Vec::with_capacity(factors.len());
+        let mut by_base: HashMap<Expr, usize> = HashMap::new();
+        let mut by_exp: HashMap<(bool, Expr), usize> = HashMap::new();
+        for factor in factors {
+            let (base, exp) = get_exp(factor);
+            let key = exp_key(&base, &exp);
+            let idx = [
+                key.as_ref().and_then(|key| by_exp.get(key)),
+                by_base.get(&base),
+            ]
+                .into_iter()
+                .flatten()
+                .min()
+                .copied();
+
+            let Some(idx) = idx else {
+                by_base.insert(base.clone(), groups.len());
+                if let Some(key) = key {
+                    by_exp.insert(key, groups.len());
                 }
+                groups.push((base, exp));
+                continue;
+            };
+
+            // remove the group's old keys; they are re-inserted below after combining
+            let (group_base, group_exp) = &mut groups[idx];
+            if let Some(key) = exp_key(group_base, group_exp) {
+                by_exp.remove(&key);
             }
+            by_base.remove(group_base);
 
-            // after all combining, update the current factor
-            if current_factor_exp.as_integer().map(|n| n == &1).unwrap_or(false) {
-                new_factors[current_factor_idx] = current_factor;
+            if key.is_some() && *group_exp == exp && group_base.is_float() == base.is_float() {
+                // exponents are strictly equal, apply a^c*b^c = (a*b)^c
+                *group_base *= base;
             } else {
-                new_factors[current_factor_idx] = Expr::Exp(
-                    Box::new(current_factor),
-                    Box::new(current_factor_exp),
-                );
+                // bases are strictly equal, apply a^b*a^c = a^(b+c)
+                *group_exp += exp;
             }
 
-            current_factor_idx += 1;
+            by_base.entry(group_base.clone()).or_insert(idx);
+            if let Some(key) = exp_key(group_base, group_exp) {
+                by_exp.entry(key).or_insert(idx);
+            }
         }
 
+        let new_factors = groups.into_iter()
+            .map(|(base, exp)| {
+                if exp.as_integer().map(|n| n == &1).unwrap_or(false) {
+                    base
+                } else {
+                    Expr::Exp(Arc::new(base), Arc::new(exp))
+                }
+            })
+            .collect::<Vec<_>>();
+
         if new_factors.len() == factors.len() {
             None
         } else {
-            Some(Expr::Mul(new_factors).downgrade())
+            Some(Expr::Mul(new_factors.into()).downgrade())
         }
     })?;
 
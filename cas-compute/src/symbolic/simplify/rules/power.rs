@@ -7,6 +7,7 @@ use crate::symbolic::{
     step_collector::StepCollector,
 };
 use rug::ops::Pow;
+use std::sync::Arc;
 
 /// `a^0 = 1`
 ///
@@ -75,8 +76,8 @@ pub fn power_power(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) ->
     let opt = do_power(expr, |lhs, rhs| {
         if let Expr::Exp(base, exponent) = lhs {
             return Some(Expr::Exp(
-                Box::new(*base.clone()),
-                Box::new(*exponent.clone() * rhs.clone()),
+                base.clone(),
+                Arc::new(Expr::clone(exponent) * rhs.clone()),
             ));
         }
 
@@ -87,6 +88,40 @@ pub fn power_power(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) ->
     Some(opt)
 }
 
+/// `e^(b*ln(a)) = a^b`
+///
+/// This includes the case `e^ln(a) = a`, where `b = 1`.
+pub fn power_log(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<Expr> {
+    let opt = do_power(expr, |lhs, rhs| {
+        if lhs.as_symbol()? != "e" {
+            return None;
+        }
+
+        let is_ln = |expr: &Expr| matches!(
+            expr,
+            Expr::Primary(Primary::Call(name, args)) if name == "ln" && args.len() == 1
+        );
+        let (ln, others) = match rhs {
+            Expr::Mul(factors) => {
+                let idx = factors.iter().position(is_ln)?;
+                let mut others = factors.to_vec();
+                let ln = others.remove(idx);
+                (ln, Expr::Mul(others.into()).downgrade())
+            },
+            rhs if is_ln(rhs) => (rhs.clone(), Expr::Primary(Primary::Integer(int(1)))),
+            _ => return None,
+        };
+        let Expr::Primary(Primary::Call(_, mut args)) = ln else {
+            unreachable!();
+        };
+
+        Some(Expr::Exp(Arc::new(args.remove(0)), Arc::new(others)))
+    })?;
+
+    step_collector.push(Step::PowerLog);
+    Some(opt)
+}
+
 /// Simplifies integer powers.
 pub fn integer(expr: &Expr, _: &mut dyn StepCollector<Step>) -> Option<Expr> {
     do_power(expr, |lhs, rhs| {
@@ -109,5 +144,6 @@ pub fn all(expr: &Expr, step_collector: &mut dyn StepCollector<Step>) -> Option<
         .or_else(|| power_one_left(expr, step_collector))
         .or_else(|| power_one(expr, step_collector))
         .or_else(|| power_power(expr, step_collector))
+        .or_else(|| power_log(expr, step_collector))
         .or_else(|| integer(expr, step_collector))
 }
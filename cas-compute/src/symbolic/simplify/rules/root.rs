@@ -7,7 +7,7 @@ use crate::symbolic::{
     step_collector::StepCollector,
 };
 use rug::Integer;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 /// Returns the prime factorization of the given integer.
 fn prime_factorization(mut n: Integer) -> HashMap<Integer, usize> {
@@ -35,7 +35,7 @@ fn prime_factorization(mut n: Integer) -> HashMap<Integer, usize> {
 /// remain inside the root.
 fn do_root(expr: &Expr, root: usize) -> Option<Expr> {
     let factors = if let Expr::Mul(factors) = expr {
-        factors.clone()
+        factors.to_vec()
     } else {
         vec![expr.clone()]
     };
@@ -55,7 +55,7 @@ fn do_root(expr: &Expr, root: usize) -> Option<Expr> {
 
                 // extract numerical exponent
                 Expr::Exp(left, right) if right.is_integer() => {
-                    *counts.entry(*left).or_insert(0) += right.as_integer().unwrap().to_usize().unwrap();
+                    *counts.entry(Arc::unwrap_or_clone(left)).or_insert(0) += right.as_integer().unwrap().to_usize().unwrap();
                 },
 
                 _ => {
@@ -74,8 +74,8 @@ fn do_root(expr: &Expr, root: usize) -> Option<Expr> {
                 None
             } else {
                 Some(Expr::Exp(
-                    Box::new(factor.clone()),
-                    Box::new(Expr::Primary(Primary::Integer(Integer::from(count / root))))
+                    Arc::new(factor.clone()),
+                    Arc::new(Expr::Primary(Primary::Integer(Integer::from(count / root))))
                 ))
             }
         })
@@ -87,8 +87,8 @@ fn do_root(expr: &Expr, root: usize) -> Option<Expr> {
                 None
             } else {
                 Some(Expr::Exp(
-                    Box::new(factor),
-                    Box::new(Expr::Primary(Primary::Integer(Integer::from(count % root))))
+                    Arc::new(factor),
+                    Arc::new(Expr::Primary(Primary::Integer(Integer::from(count % root))))
                 ))
             }
         })
@@ -99,18 +99,21 @@ fn do_root(expr: &Expr, root: usize) -> Option<Expr> {
         None
     } else if inside_factors.is_empty() {
         // everything was pulled out of the root; the root / call is gone
-        Some(Expr::Mul(outside_factors))
+        Some(Expr::Mul(outside_factors.into()))
     } else {
         // call needs to be rebuilt with the new arguments
         let call = match root {
-            2 => Primary::Call("sqrt".to_string(), vec![Expr::Mul(inside_factors)]),
-            3 => Primary::Call("cbrt".to_string(), vec![Expr::Mul(inside_factors)]),
+            2 => Primary::Call("sqrt".to_string(), vec![Expr::Mul(inside_factors.into())]),
+            3 => Primary::Call("cbrt".to_string(), vec![Expr::Mul(inside_factors.into())]),
             n => Primary::Call(
                 "root".to_string(),
-                vec![Expr::Mul(inside_factors), Expr::Primary(Primary::Integer(Integer::from(n)))],
+                vec![
+                    Expr::Mul(inside_factors.into()),
+                    Expr::Primary(Primary::Integer(Integer::from(n))),
+                ],
             ),
         };
-        Some(Expr::Mul(outside_factors) * Expr::Primary(call))
+        Some(Expr::Mul(outside_factors.into()) * Expr::Primary(call))
     }
 }
 
@@ -27,8 +27,13 @@ fn simplify_trig(arg: Expr, table: &HashMap<&Expr, table::TrigOut>) -> Option<Ex
         simplify::simplify(&raw)
     };
 
-    // expect the result to be a fraction
+    // expect the result to be a fraction, with no other factors left over (such as a symbol)
     let (numerator, denominator) = extract_explicit_frac(&mut expr)?;
+    if let Expr::Mul(factors) = &expr {
+        if !factors.is_empty() {
+            return None;
+        }
+    }
 
     // turn the fraction into a normalized `Expr`
     let fraction = {
@@ -44,6 +44,9 @@ pub enum Step {
     /// `a^b^c = a^(b*c)`
     PowerPower,
 
+    /// `e^(b*ln(a)) = a^b`
+    PowerLog,
+
     /// `a*(b+c) = a*b + a*c`
     DistributiveProperty,
 
@@ -62,6 +65,27 @@ pub enum Step {
     /// `i^(4n+3) = -i`
     I3,
 
+    /// `(a+bi)^n = c+di`, `n > 0`
+    ComplexPower,
+
+    /// `1/(a+bi) = (a-bi)/(a^2+b^2)`
+    ComplexDivision,
+
+    /// `re(a+bi) = a`
+    Re,
+
+    /// `im(a+bi) = b`
+    Im,
+
+    /// `conj(a+bi) = a-bi`
+    Conj,
+
+    /// `abs(a+bi) = sqrt(a^2+b^2)`
+    Abs,
+
+    /// `arg(a+bi) = atan2(b, a)`
+    Arg,
+
     /// `sin(x)` identity
     Sin,
 
@@ -1,4 +1,4 @@
-use cas_compute::numerical::{ctxt::Ctxt, eval::Eval, value::Value};
+use cas_compute::numerical::{ctxt::Ctxt, fast::FastFn};
 use super::{analyzed::{AnalyzedExpr, Variable}, GraphOptions, GraphPoint};
 
 /// Evaluate the given expression and returns the points to draw.
@@ -15,11 +15,14 @@ use super::{analyzed::{AnalyzedExpr, Variable}, GraphOptions, GraphPoint};
 /// However, when the slope of the expression does start varying, the step size must be smaller.
 /// For example, if the step size is too large, we could end up skipping past a relative minimum /
 /// maximum of the expression, which would be extremely obvious.
+///
+/// The expression is compiled to a [`FastFn`] first, since double precision is plenty for drawing
+/// it, and evaluating it at high precision at every point would be much slower.
 pub(crate) fn evaluate_expr(
     analyzed: &AnalyzedExpr,
     options: GraphOptions,
 ) -> Vec<GraphPoint<f64>> {
-    let mut ctxt = Ctxt::default();
+    let f = FastFn::compile(&analyzed.expr, &[analyzed.independent.as_str()], &Ctxt::default());
     let mut points = Vec::new();
 
     let mut last_point = None;
@@ -48,11 +51,11 @@ pub(crate) fn evaluate_expr(
     let mut last_slope: Option<f64> = None;
 
     while current_trace <= bounds.1 {
-        ctxt.add_var(analyzed.independent.as_str(), current_trace.into());
-        if let Ok(Value::Float(float)) = analyzed.expr.eval(&mut ctxt).map(|v| v.coerce_float()) {
+        let value = f.eval(&[current_trace]);
+        if !value.is_nan() {
             let point = match analyzed.independent {
-                Variable::X => GraphPoint(current_trace, float.to_f64()),
-                Variable::Y => GraphPoint(float.to_f64(), current_trace),
+                Variable::X => GraphPoint(current_trace, value),
+                Variable::Y => GraphPoint(value, current_trace),
                 Variable::Theta => todo!("polar coordinates"),
             };
             points.push(point);
@@ -56,6 +56,33 @@
 //! # }
 //! ```
 //!
+//! The expression above is the quadratic that best fits the points. Instead of typing it by hand,
+//! it can be computed with [`Fit`] and added with [`Graph::add_expr()`]:
+//!
+//! ```no_run
+//! use cas_compute::funcs::regression::{Fit, Model};
+//! use cas_compute::numerical::value::Value;
+//! use cas_graph::Graph;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let points = [(-5.0, 5.0), (-4.0, 4.0), (-3.0, 1.0), (-2.0, 0.5), (-1.0, 4.0)];
+//! let (xs, ys) = points.iter()
+//!     .map(|&(x, y)| (Value::from(x), Value::from(y)))
+//!     .unzip();
+//! let fit = Fit::new(Model::Polynomial(2), xs, ys).unwrap();
+//!
+//! let mut graph = Graph::new();
+//! graph.add_expr(fit.expr());
+//! for point in points {
+//!     graph.add_point(point);
+//! }
+//! let surface = graph.center_on_points().draw()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`Fit`]: cas_compute::funcs::regression::Fit
+//!
 //! Output:
 //!
 //! <img src="https://raw.githubusercontent.com/ElectrifyPro/cas-rs/dev/cas-graph/img/output.png" width="500" height="500"/>
@@ -1,6 +1,6 @@
 use crate::{
     parser::{
-        ast::{expr::Expr, helper::ParenDelimited, literal::{Literal, LitSym}},
+        ast::{expr::Expr, helper::ParenDelimited, index::Index, literal::{Literal, LitSym}},
         error::{kind::{CompoundAssignmentInHeader, InvalidAssignmentLhs, InvalidCompoundAssignmentLhs}, Error},
         fmt::Latex,
         garbage::Garbage,
@@ -142,13 +142,16 @@ impl Latex for FuncHeader {
     }
 }
 
-/// An assignment target, such as `x` or `f(x)`.
+/// An assignment target, such as `x`, `l[0]`, or `f(x)`.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum AssignTarget {
     /// A symbol, such as `x`.
     Symbol(LitSym),
 
+    /// An index into a list stored in a symbol, such as `l[0]` or `l[0][1]`.
+    Index(Index),
+
     /// A function, such as `f(x)`.
     Func(FuncHeader),
 }
@@ -158,16 +161,20 @@ impl AssignTarget {
     pub fn span(&self) -> Range<usize> {
         match self {
             AssignTarget::Symbol(symbol) => symbol.span.clone(),
+            AssignTarget::Index(index) => index.span(),
             AssignTarget::Func(func) => func.span(),
         }
     }
 
     /// Tries to convert a general [`Expr`] into an [`AssignTarget`]. This is used when parsing
-    /// assignment expressions, such as `x = 1` or `f(x) = x^2`.
+    /// assignment expressions, such as `x = 1`, `l[0] = 1`, or `f(x) = x^2`.
     pub fn try_from_with_op(expr: Expr, op: &AssignOp) -> ParseResult<Self> {
         let op_span = op.span.clone();
         match expr {
             Expr::Literal(Literal::Symbol(symbol)) => ParseResult::Ok(AssignTarget::Symbol(symbol)),
+            Expr::Index(index) if matches!(index.base(), Expr::Literal(Literal::Symbol(_))) => {
+                ParseResult::Ok(AssignTarget::Index(index))
+            },
             Expr::Call(call) => {
                 let spans = vec![call.span.clone(), op_span.clone()];
                 let error = if op.is_compound() {
@@ -209,6 +216,7 @@ impl std::fmt::Display for AssignTarget {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AssignTarget::Symbol(symbol) => write!(f, "{}", symbol),
+            AssignTarget::Index(index) => write!(f, "{}", index),
             AssignTarget::Func(func) => write!(f, "{}", func),
         }
     }
@@ -218,6 +226,7 @@ impl Latex for AssignTarget {
     fn fmt_latex(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             AssignTarget::Symbol(symbol) => symbol.fmt_latex(f),
+            AssignTarget::Index(index) => index.fmt_latex(f),
             AssignTarget::Func(func) => func.fmt_latex(f),
         }
     }
@@ -291,6 +300,9 @@ impl<'source> Parse<'source> for Assign {
                 //     f(5)
                 // }
                 state.allow_loop_control = false;
+
+                // but `return` is allowed, and exits this function
+                state.allow_return = true;
             }).forward_errors(recoverable_errors)?
         } else {
             input.try_parse::<Expr>().forward_errors(recoverable_errors)?
@@ -11,11 +11,18 @@ use crate::parser::{
     Parser,
     ParseResult,
 };
+use crate::tokenizer::{Token, TokenKind};
 use std::{fmt, ops::Range};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Returns true if the given token is the opening brace of the body of a `for` loop, which must not
+/// be parsed as the right-hand side of an implicit multiplication with the iterable of the loop.
+fn begins_for_body(input: &Parser, token: &Token) -> bool {
+    input.state.in_for_iterable && token.kind == TokenKind::OpenCurly
+}
+
 /// A binary operator, including assignment.
 #[derive(Debug, Clone, PartialEq)]
 enum BinOpExt {
@@ -168,7 +175,7 @@ impl Binary {
                 // parse implicit multiplication, as that would be confusing
                 input_ahead.advance_past_non_significant_whitespace();
                 if let Some(token) = input_ahead.current_token() {
-                    if token.kind.is_significant_whitespace() {
+                    if token.kind.is_significant_whitespace() || begins_for_body(input, token) {
                         break;
                     }
                 }
@@ -259,7 +266,7 @@ impl Binary {
                 // do not continue if there is significant whitespace after `lhs`
                 input_ahead.advance_past_non_significant_whitespace();
                 if let Some(token) = input_ahead.current_token() {
-                    if token.kind.is_significant_whitespace() {
+                    if token.kind.is_significant_whitespace() || begins_for_body(input, token) {
                         break;
                     }
                 }
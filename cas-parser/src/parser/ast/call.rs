@@ -1,8 +1,8 @@
 use crate::parser::{
     ast::{expr::Expr, helper::ParenDelimited, literal::LitSym},
-    error::{kind::TooManyDerivatives, Error},
+    error::{kind::{PositionalAfterNamedArgument, TooManyDerivatives}, Error},
     fmt::{Latex, fmt_pow},
-    token::Quote,
+    token::{Assign, Quote},
     Parse,
     Parser,
 };
@@ -11,7 +11,76 @@ use std::{fmt, ops::Range};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-/// A function call, such as `func(x, -40)`.
+/// A named argument of a function call, such as `k = 4` in `f(n, k = 4)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NamedArg {
+    /// The name of the parameter the argument is given to.
+    pub name: LitSym,
+
+    /// The value of the argument.
+    pub value: Expr,
+
+    /// The region of the source code that this argument was parsed from.
+    pub span: Range<usize>,
+}
+
+impl NamedArg {
+    /// Returns the span of the named argument.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl<'source> Parse<'source> for NamedArg {
+    fn std_parse(
+        input: &mut Parser<'source>,
+        recoverable_errors: &mut Vec<Error>
+    ) -> Result<Self, Vec<Error>> {
+        let name = input.try_parse::<LitSym>().forward_errors(recoverable_errors)?;
+        input.try_parse::<Assign>().forward_errors(recoverable_errors)?;
+        let value = input.try_parse::<Expr>().forward_errors(recoverable_errors)?;
+        let span = name.span.start..value.span().end;
+        Ok(Self { name, value, span })
+    }
+}
+
+impl std::fmt::Display for NamedArg {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} = {}", self.name, self.value)
+    }
+}
+
+impl Latex for NamedArg {
+    fn fmt_latex(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = {}", self.name.as_display(), self.value.as_display())
+    }
+}
+
+/// An argument of a function call, which is either positional or named.
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    /// A positional argument, such as `n` in `f(n, k = 4)`.
+    Positional(Expr),
+
+    /// A named argument, such as `k = 4` in `f(n, k = 4)`.
+    Named(NamedArg),
+}
+
+impl<'source> Parse<'source> for Arg {
+    fn std_parse(
+        input: &mut Parser<'source>,
+        recoverable_errors: &mut Vec<Error>
+    ) -> Result<Self, Vec<Error>> {
+        // `k = 4` is a named argument, rather than an assignment to `k`
+        if let Ok(named) = input.try_parse::<NamedArg>().forward_errors(recoverable_errors) {
+            return Ok(Arg::Named(named));
+        }
+        input.try_parse().map(Arg::Positional).forward_errors(recoverable_errors)
+    }
+}
+
+/// A function call, such as `func(x, -40)` or `ncr(n = 5, k = 2)`.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Call {
@@ -21,9 +90,12 @@ pub struct Call {
     /// The number of derivatives to take before calling the function.
     pub derivatives: u8,
 
-    /// The arguments to the function.
+    /// The positional arguments to the function.
     pub args: Vec<Expr>,
 
+    /// The named arguments to the function, which follow the positional arguments.
+    pub named_args: Vec<NamedArg>,
+
     /// The region of the source code that this function call was parsed from.
     pub span: Range<usize>,
 
@@ -37,6 +109,12 @@ impl Call {
         self.span.clone()
     }
 
+    /// Returns an iterator over the values of the positional arguments, followed by the values of
+    /// the named arguments.
+    pub fn arg_values(&self) -> impl DoubleEndedIterator<Item = &Expr> {
+        self.args.iter().chain(self.named_args.iter().map(|arg| &arg.value))
+    }
+
     /// Returns a set of two spans, where the first is the span of the function name (with the
     /// opening parenthesis) and the second is the span of the closing parenthesis.
     pub fn outer_span(&self) -> [Range<usize>; 2] {
@@ -75,14 +153,37 @@ impl<'source> Parse<'source> for Call {
             ));
         }
 
-        let surrounded = input.try_parse::<ParenDelimited<_>>().forward_errors(recoverable_errors)?;
+        let surrounded = input.try_parse::<ParenDelimited<Arg>>()
+            .forward_errors(recoverable_errors)?;
+
+        // named arguments must come after all positional arguments, for example:
+        //
+        // f(k = 4, 5)
+        //          ^ positional argument after named argument
+        let mut args = Vec::new();
+        let mut named_args = Vec::<NamedArg>::new();
+        for arg in surrounded.value.values {
+            match arg {
+                Arg::Positional(expr) => {
+                    if let Some(named) = named_args.last() {
+                        recoverable_errors.push(Error::new(
+                            vec![expr.span(), named.span()],
+                            PositionalAfterNamedArgument,
+                        ));
+                    }
+                    args.push(expr);
+                },
+                Arg::Named(named) => named_args.push(named),
+            }
+        }
 
         // use `name` here before it is moved into the struct
         let span = name.span.start..surrounded.close.span.end;
         Ok(Self {
             name,
             derivatives: derivatives as u8,
-            args: surrounded.value.values,
+            args,
+            named_args,
             span,
             paren_span: surrounded.open.span.start..surrounded.close.span.end,
         })
@@ -96,12 +197,17 @@ impl std::fmt::Display for Call {
             write!(f, "'")?;
         }
         write!(f, "(")?;
-        if let Some((last, args)) = self.args.split_last() {
-            for arg in args {
-                arg.fmt(f)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
                 write!(f, ", ")?;
             }
-            last.fmt(f)?;
+            arg.fmt(f)?;
+        }
+        for (i, arg) in self.named_args.iter().enumerate() {
+            if i > 0 || !self.args.is_empty() {
+                write!(f, ", ")?;
+            }
+            arg.fmt(f)?;
         }
         write!(f, ")")
     }
@@ -170,12 +276,17 @@ impl Latex for Call {
                         }
                     },
                     Self::Cbrt | Self::Sqrt | Self::Abs | Self::Other => {
-                        if let Some((last, args)) = call.args.split_last() {
-                            for arg in args {
-                                arg.fmt_latex(f)?;
+                        for (i, arg) in call.args.iter().enumerate() {
+                            if i > 0 {
+                                write!(f, ", ")?;
+                            }
+                            arg.fmt_latex(f)?;
+                        }
+                        for (i, arg) in call.named_args.iter().enumerate() {
+                            if i > 0 || !call.args.is_empty() {
                                 write!(f, ", ")?;
                             }
-                            last.fmt_latex(f)?;
+                            arg.fmt_latex(f)?;
                         }
                     },
                 }
@@ -5,10 +5,14 @@ use crate::{
             binary::Binary,
             block::Block,
             call::Call,
+            for_expr::For,
             if_expr::If,
+            index::Index,
+            lambda::Lambda,
             literal::Literal,
             loop_expr::{Break, Continue, Loop},
             paren::Paren,
+            return_expr::Return,
             unary::Unary,
             while_expr::While,
         },
@@ -53,15 +57,24 @@ pub enum Expr {
     /// A while loop expression, as in `while x > 0 then { ... }`.
     While(While),
 
+    /// A for loop expression, as in `for i in 0..10 { ... }`.
+    For(For),
+
     /// A break expression, used to exit a loop, optionally with a value.
     Break(Break),
 
     /// A continue expression, used to skip the rest of a loop iteration.
     Continue(Continue),
 
+    /// A return expression, used to exit a function, optionally with a value.
+    Return(Return),
+
     /// A function call, such as `abs(-1)`.
     Call(Call),
 
+    /// An index into a list, such as `l[0]` or `l[1:3]`.
+    Index(Index),
+
     /// A unary operation, such as `-1` or `!true`.
     Unary(Unary),
 
@@ -70,6 +83,9 @@ pub enum Expr {
 
     /// An assignment of a variable or function, such as `x = 1` or `f(x) = x^2`.
     Assign(Assign),
+
+    /// An anonymous function, such as `x -> x^2`.
+    Lambda(Lambda),
 }
 
 impl Expr {
@@ -82,12 +98,16 @@ impl Expr {
             Expr::If(if_expr) => if_expr.span(),
             Expr::Loop(loop_expr) => loop_expr.span(),
             Expr::While(while_expr) => while_expr.span(),
+            Expr::For(for_expr) => for_expr.span(),
             Expr::Break(break_expr) => break_expr.span(),
             Expr::Continue(continue_expr) => continue_expr.span(),
+            Expr::Return(return_expr) => return_expr.span(),
             Expr::Call(call) => call.span(),
+            Expr::Index(index) => index.span(),
             Expr::Unary(unary) => unary.span(),
             Expr::Binary(binary) => binary.span(),
             Expr::Assign(assign) => assign.span(),
+            Expr::Lambda(lambda) => lambda.span(),
         }
     }
 
@@ -117,6 +137,7 @@ impl<'source> Parse<'source> for Expr {
             return Err(vec![input.error(kind::UnclosedParenthesis { opening: false })]);
         }
 
+        let _ = return_if_ok!(input.try_parse().map(Self::Lambda).forward_errors(recoverable_errors));
         let _ = return_if_ok!(input.try_parse().map(Self::Assign).forward_errors(recoverable_errors));
         let lhs = Unary::parse_or_lower(input, recoverable_errors)?;
         Ok(Binary::parse_expr(input, recoverable_errors, lhs, Precedence::Any)?.0)
@@ -132,12 +153,16 @@ impl std::fmt::Display for Expr {
             Expr::If(if_expr) => if_expr.fmt(f),
             Expr::Loop(loop_expr) => loop_expr.fmt(f),
             Expr::While(while_expr) => while_expr.fmt(f),
+            Expr::For(for_expr) => for_expr.fmt(f),
             Expr::Break(break_expr) => break_expr.fmt(f),
             Expr::Continue(continue_expr) => continue_expr.fmt(f),
+            Expr::Return(return_expr) => return_expr.fmt(f),
             Expr::Call(call) => call.fmt(f),
+            Expr::Index(index) => index.fmt(f),
             Expr::Unary(unary) => unary.fmt(f),
             Expr::Binary(binary) => binary.fmt(f),
             Expr::Assign(assign) => assign.fmt(f),
+            Expr::Lambda(lambda) => lambda.fmt(f),
         }
     }
 }
@@ -151,12 +176,16 @@ impl Latex for Expr {
             Expr::If(if_expr) => if_expr.fmt_latex(f),
             Expr::Loop(loop_expr) => loop_expr.fmt_latex(f),
             Expr::While(while_expr) => while_expr.fmt_latex(f),
+            Expr::For(for_expr) => for_expr.fmt_latex(f),
             Expr::Break(break_expr) => break_expr.fmt_latex(f),
             Expr::Continue(continue_expr) => continue_expr.fmt_latex(f),
+            Expr::Return(return_expr) => return_expr.fmt_latex(f),
             Expr::Call(call) => call.fmt_latex(f),
+            Expr::Index(index) => index.fmt_latex(f),
             Expr::Unary(unary) => unary.fmt_latex(f),
             Expr::Binary(binary) => binary.fmt_latex(f),
             Expr::Assign(assign) => assign.fmt_latex(f),
+            Expr::Lambda(lambda) => lambda.fmt_latex(f),
         }
     }
 }
@@ -187,12 +216,18 @@ pub enum Primary {
     /// A while loop expression, as in `while x > 0 then { ... }`.
     While(While),
 
+    /// A for loop expression, as in `for i in 0..10 { ... }`.
+    For(For),
+
     /// A break expression, used to exit a loop, optionally with a value.
     Break(Break),
 
     /// A continue expression, used to skip the rest of a loop iteration.
     Continue(Continue),
 
+    /// A return expression, used to exit a function, optionally with a value.
+    Return(Return),
+
     /// A function call, such as `abs(-1)`.
     Call(Call),
 }
@@ -207,23 +242,59 @@ impl Primary {
             Primary::If(if_expr) => if_expr.span(),
             Primary::Loop(loop_expr) => loop_expr.span(),
             Primary::While(while_expr) => while_expr.span(),
+            Primary::For(for_expr) => for_expr.span(),
             Primary::Break(break_expr) => break_expr.span(),
             Primary::Continue(continue_expr) => continue_expr.span(),
+            Primary::Return(return_expr) => return_expr.span(),
             Primary::Call(call) => call.span(),
         }
     }
 }
 
+impl Primary {
+    /// Attempts to parse a primary expression that begins with a keyword, such as an `if`
+    /// expression or a loop.
+    ///
+    /// This is a separate function so that the results of these attempts do not take up space in
+    /// the stack frame of [`Primary::std_parse`], which is part of the recursion when parsing
+    /// deeply nested expressions.
+    fn parse_keyword_expr(
+        input: &mut Parser,
+        recoverable_errors: &mut Vec<Error>
+    ) -> Option<Self> {
+        if let Ok(primary) = input.try_parse().map(Self::If).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        if let Ok(primary) = input.try_parse().map(Self::Loop).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        if let Ok(primary) = input.try_parse().map(Self::While).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        if let Ok(primary) = input.try_parse().map(Self::For).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        if let Ok(primary) = input.try_parse().map(Self::Break).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        if let Ok(primary) = input.try_parse().map(Self::Continue).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        if let Ok(primary) = input.try_parse().map(Self::Return).forward_errors(recoverable_errors) {
+            return Some(primary);
+        }
+        None
+    }
+}
+
 impl<'source> Parse<'source> for Primary {
     fn std_parse(
         input: &mut Parser<'source>,
         recoverable_errors: &mut Vec<Error>
     ) -> Result<Self, Vec<Error>> {
-        let _ = return_if_ok!(input.try_parse().map(Self::If).forward_errors(recoverable_errors));
-        let _ = return_if_ok!(input.try_parse().map(Self::Loop).forward_errors(recoverable_errors));
-        let _ = return_if_ok!(input.try_parse().map(Self::While).forward_errors(recoverable_errors));
-        let _ = return_if_ok!(input.try_parse().map(Self::Break).forward_errors(recoverable_errors));
-        let _ = return_if_ok!(input.try_parse().map(Self::Continue).forward_errors(recoverable_errors));
+        if let Some(primary) = Self::parse_keyword_expr(input, recoverable_errors) {
+            return Ok(primary);
+        }
         // function calls can overlap with literals, so we need to try parsing a function call
         // first
         let _ = return_if_ok!(input.try_parse().map(Self::Call).forward_errors(recoverable_errors));
@@ -242,8 +313,10 @@ impl From<Primary> for Expr {
             Primary::If(if_expr) => Self::If(if_expr),
             Primary::Loop(loop_expr) => Self::Loop(loop_expr),
             Primary::While(while_expr) => Self::While(while_expr),
+            Primary::For(for_expr) => Self::For(for_expr),
             Primary::Break(break_expr) => Self::Break(break_expr),
             Primary::Continue(continue_expr) => Self::Continue(continue_expr),
+            Primary::Return(return_expr) => Self::Return(return_expr),
             Primary::Call(call) => Self::Call(call),
         }
     }
@@ -0,0 +1,91 @@
+use crate::parser::{
+    ast::{expr::Expr, literal::LitSym},
+    error::Error,
+    fmt::Latex,
+    keyword::{For as ForToken, In},
+    Parse,
+    Parser,
+};
+use std::{fmt, ops::Range};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A `for` loop expression, such as `for i in 0..10 { ... }`. The loop body is executed once for
+/// each value of the iterable, such as a range or a list, with the loop variable set to that value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct For {
+    /// The variable that is assigned each value of the iterable.
+    pub variable: LitSym,
+
+    /// The expression producing the values to iterate over.
+    pub iterable: Box<Expr>,
+
+    /// The body of the loop.
+    pub body: Box<Expr>,
+
+    /// The region of the source code that this expression was parsed from.
+    pub span: Range<usize>,
+
+    /// The span of the `for` keyword.
+    pub for_span: Range<usize>,
+
+    /// The span of the `in` keyword.
+    pub in_span: Range<usize>,
+}
+
+impl For {
+    /// Returns the span of the `for` loop expression.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl<'source> Parse<'source> for For {
+    fn std_parse(
+        input: &mut Parser<'source>,
+        recoverable_errors: &mut Vec<Error>
+    ) -> Result<Self, Vec<Error>> {
+        let for_token = input.try_parse::<ForToken>().forward_errors(recoverable_errors)?;
+        let variable = input.try_parse::<LitSym>().forward_errors(recoverable_errors)?;
+        let in_token = input.try_parse::<In>().forward_errors(recoverable_errors)?;
+
+        // the body usually begins with a block, which must not be parsed as part of the iterable
+        let iterable = input.try_parse_with_state::<_, Expr>(|state| {
+            state.in_for_iterable = true;
+        }).forward_errors(recoverable_errors)?;
+        let body = input.try_parse_with_state::<_, Expr>(|state| {
+            state.allow_loop_control = true;
+            state.in_for_iterable = false;
+        }).forward_errors(recoverable_errors)?;
+        let span = for_token.span.start..body.span().end;
+
+        Ok(Self {
+            variable,
+            iterable: Box::new(iterable),
+            body: Box::new(body),
+            span,
+            for_span: for_token.span,
+            in_span: in_token.span,
+        })
+    }
+}
+
+impl std::fmt::Display for For {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "for {} in {} {}", self.variable, self.iterable, self.body)
+    }
+}
+
+impl Latex for For {
+    fn fmt_latex(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\\text{{for }}")?;
+        self.variable.fmt_latex(f)?;
+        write!(f, "\\text{{ in }}")?;
+        self.iterable.fmt_latex(f)?;
+        write!(f, " ")?;
+        self.body.fmt_latex(f)?;
+        Ok(())
+    }
+}
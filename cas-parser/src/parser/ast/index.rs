@@ -0,0 +1,186 @@
+use crate::{
+    parser::{
+        ast::{expr::Expr, helper::Surrounded},
+        error::Error,
+        fmt::Latex,
+        token::{CloseSquare, Colon, OpenSquare},
+        Parse,
+        Parser,
+    },
+    tokenizer::TokenKind,
+};
+use std::{fmt, ops::Range};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The part of an [`Index`] expression between the square brackets.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IndexKind {
+    /// A single index, such as `0` in `l[0]`.
+    Single(Box<Expr>),
+
+    /// A slice, such as `1:3` in `l[1:3]`. Either bound can be omitted, as in `l[:3]` or `l[1:]`.
+    Slice {
+        /// The inclusive start of the slice.
+        start: Option<Box<Expr>>,
+
+        /// The exclusive end of the slice.
+        end: Option<Box<Expr>>,
+    },
+}
+
+impl IndexKind {
+    /// Returns the expressions in the index, from left to right.
+    pub fn exprs(&self) -> Vec<&Expr> {
+        match self {
+            IndexKind::Single(index) => vec![index],
+            IndexKind::Slice { start, end } => start.iter().chain(end.iter())
+                .map(|expr| &**expr)
+                .collect(),
+        }
+    }
+}
+
+impl<'source> Parse<'source> for IndexKind {
+    fn std_parse(
+        input: &mut Parser<'source>,
+        recoverable_errors: &mut Vec<Error>
+    ) -> Result<Self, Vec<Error>> {
+        let start = if input.clone().try_parse::<Colon>().is_ok() {
+            None
+        } else {
+            Some(Box::new(input.try_parse::<Expr>().forward_errors(recoverable_errors)?))
+        };
+
+        match start {
+            Some(start) if !input.clone().try_parse::<Colon>().is_ok() => Ok(IndexKind::Single(start)),
+            start => {
+                input.try_parse::<Colon>().forward_errors(recoverable_errors)?;
+                let end = if input.clone().try_parse::<CloseSquare>().is_ok() {
+                    None
+                } else {
+                    Some(Box::new(input.try_parse::<Expr>().forward_errors(recoverable_errors)?))
+                };
+                Ok(IndexKind::Slice { start, end })
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexKind::Single(index) => index.fmt(f),
+            IndexKind::Slice { start, end } => {
+                if let Some(start) = start {
+                    start.fmt(f)?;
+                }
+                write!(f, ":")?;
+                if let Some(end) = end {
+                    end.fmt(f)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Latex for IndexKind {
+    fn fmt_latex(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexKind::Single(index) => index.fmt_latex(f),
+            IndexKind::Slice { start, end } => {
+                if let Some(start) = start {
+                    start.fmt_latex(f)?;
+                }
+                write!(f, ":")?;
+                if let Some(end) = end {
+                    end.fmt_latex(f)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/// An index into a list, such as `l[0]`, or a slice of a list, such as `l[1:3]`.
+///
+/// Indices start at `0`. Negative indices count from the end of the list, so `l[-1]` is the last
+/// element of `l`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Index {
+    /// The expression being indexed.
+    pub target: Box<Expr>,
+
+    /// The index or slice.
+    pub index: IndexKind,
+
+    /// The region of the source code that this index expression was parsed from.
+    pub span: Range<usize>,
+
+    /// The region of the source code that the square brackets and their contents were parsed from.
+    pub bracket_span: Range<usize>,
+}
+
+impl Index {
+    /// Returns the span of the index expression.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the innermost expression being indexed. For example, this is `l` in `l[0][1]`.
+    pub fn base(&self) -> &Expr {
+        let mut base = &self.target;
+        while let Expr::Index(index) = base.as_ref() {
+            base = &index.target;
+        }
+        base
+    }
+
+    /// Parses any number of indices following the given expression, such as `[0]` and `[1:]` in
+    /// `l[0][1:]`.
+    ///
+    /// The opening square bracket must immediately follow the expression, with no whitespace in
+    /// between. This way, `x [1, 2]` is still parsed as the implicit multiplication of `x` and
+    /// a list.
+    pub fn parse_postfix(
+        input: &mut Parser,
+        recoverable_errors: &mut Vec<Error>,
+        mut target: Expr,
+    ) -> Result<Expr, Vec<Error>> {
+        while input.current_token().is_some_and(|token| token.kind == TokenKind::OpenSquare) {
+            let Ok(surrounded) = input.try_parse::<Surrounded<OpenSquare, IndexKind>>()
+                .forward_errors(recoverable_errors) else {
+                break;
+            };
+
+            let span = target.span().start..surrounded.close.span.end;
+            target = Expr::Index(Self {
+                target: Box::new(target),
+                index: surrounded.value,
+                span,
+                bracket_span: surrounded.open.span.start..surrounded.close.span.end,
+            });
+        }
+
+        Ok(target)
+    }
+}
+
+impl std::fmt::Display for Index {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]", self.target, self.index)
+    }
+}
+
+impl Latex for Index {
+    fn fmt_latex(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.target.fmt_latex(f)?;
+        write!(f, "\\left[")?;
+        self.index.fmt_latex(f)?;
+        write!(f, "\\right]")
+    }
+}
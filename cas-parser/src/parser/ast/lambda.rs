@@ -0,0 +1,115 @@
+use crate::parser::{
+    ast::{assign::Param, expr::Expr, helper::ParenDelimited, literal::LitSym},
+    error::Error,
+    fmt::Latex,
+    token::Arrow,
+    Parse,
+    Parser,
+};
+use std::{fmt, ops::Range};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// An anonymous function, such as `x -> x^2` or `(x, y = 1) -> x^y`.
+///
+/// A single parameter with no default value can be written on its own. Otherwise, the parameters
+/// must be surrounded by parentheses, in the same form as the parameters of a function header. The
+/// body of the lambda extends as far to the right as possible.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Lambda {
+    /// The parameters of the lambda.
+    pub params: Vec<Param>,
+
+    /// The body of the lambda.
+    pub body: Box<Expr>,
+
+    /// The region of the source code that this lambda was parsed from.
+    pub span: Range<usize>,
+
+    /// The span of the `->` arrow.
+    pub arrow_span: Range<usize>,
+}
+
+impl Lambda {
+    /// Returns the span of the lambda.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl<'source> Parse<'source> for Lambda {
+    fn std_parse(
+        input: &mut Parser<'source>,
+        recoverable_errors: &mut Vec<Error>
+    ) -> Result<Self, Vec<Error>> {
+        let (params, start) = match input.try_parse::<LitSym>().forward_errors(recoverable_errors) {
+            Ok(symbol) => {
+                let start = symbol.span.start;
+                (vec![Param::Symbol(symbol)], start)
+            },
+            Err(_) => {
+                let surrounded = input.try_parse::<ParenDelimited<_>>()
+                    .forward_errors(recoverable_errors)?;
+                (surrounded.value.values, surrounded.open.span.start)
+            },
+        };
+        let arrow = input.try_parse::<Arrow>().forward_errors(recoverable_errors)?;
+
+        // like a function definition, the body of a lambda cannot break out of a surrounding loop,
+        // but can return from the lambda
+        let body = input.try_parse_with_state::<_, Expr>(|state| {
+            state.allow_loop_control = false;
+            state.allow_return = true;
+        }).forward_errors(recoverable_errors)?;
+
+        let span = start..body.span().end;
+        Ok(Self {
+            params,
+            body: Box::new(body),
+            span,
+            arrow_span: arrow.span,
+        })
+    }
+}
+
+impl std::fmt::Display for Lambda {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.params.as_slice() {
+            [Param::Symbol(symbol)] => write!(f, "{}", symbol)?,
+            params => {
+                write!(f, "(")?;
+                if let Some((last, rest)) = params.split_last() {
+                    for param in rest {
+                        write!(f, "{}, ", param)?;
+                    }
+                    write!(f, "{}", last)?;
+                }
+                write!(f, ")")?;
+            },
+        }
+        write!(f, " -> {}", self.body)
+    }
+}
+
+impl Latex for Lambda {
+    fn fmt_latex(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.params.as_slice() {
+            [Param::Symbol(symbol)] => symbol.fmt_latex(f)?,
+            params => {
+                write!(f, "\\left(")?;
+                if let Some((last, rest)) = params.split_last() {
+                    for param in rest {
+                        param.fmt_latex(f)?;
+                        write!(f, ", ")?;
+                    }
+                    last.fmt_latex(f)?;
+                }
+                write!(f, "\\right)")?;
+            },
+        }
+        write!(f, " \\mapsto ")?;
+        self.body.fmt_latex(f)
+    }
+}
@@ -3,11 +3,15 @@ pub mod binary;
 pub mod block;
 pub mod call;
 pub mod expr;
+pub mod for_expr;
 pub mod helper;
 pub mod if_expr;
+pub mod index;
+pub mod lambda;
 pub mod literal;
 pub mod loop_expr;
 pub mod paren;
+pub mod return_expr;
 pub mod stmt;
 pub mod unary;
 pub mod while_expr;
@@ -17,10 +21,14 @@ pub use binary::Binary;
 pub use block::Block;
 pub use call::Call;
 pub use expr::{Expr, Primary};
+pub use for_expr::For;
 pub use if_expr::If;
+pub use index::{Index, IndexKind};
+pub use lambda::Lambda;
 pub use literal::{Literal, LitFloat, LitInt, LitRadix, LitSym, LitUnit};
 pub use loop_expr::Loop;
 pub use paren::Paren;
+pub use return_expr::Return;
 pub use stmt::Stmt;
 pub use unary::Unary;
 pub use while_expr::While;
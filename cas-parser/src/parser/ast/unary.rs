@@ -1,6 +1,6 @@
 use crate::{
     parser::{
-        ast::{binary::Binary, expr::{Expr, Primary}},
+        ast::{binary::Binary, expr::{Expr, Primary}, index::Index},
         error::{kind, Error},
         fmt::Latex,
         token::op::{Associativity, UnaryOp},
@@ -74,19 +74,29 @@ impl Unary {
     ///
     /// By the nature of left-associative operators, we must parse the operand first. This can
     /// result in enormous backtracking if the operator is not present. To avoid this, this
-    /// function returns the parsed operand as an [`Primary`] if it does determine that there
-    /// is no operator present.
+    /// function returns the parsed operand as an [`Primary`] (or an [`Index`] into one) if it does
+    /// determine that there is no operator present.
     pub fn parse_left_or_operand(input: &mut Parser, recoverable_errors: &mut Vec<Error>) -> Result<Expr, Vec<Error>> {
         let operand = input.try_parse::<Primary>().forward_errors(recoverable_errors)?;
+        Self::complete_left(input, recoverable_errors, operand.into())
+    }
+
+    /// After parsing the operand of a potential left-associative unary expression, parses any
+    /// indices and left-associative operators that follow it.
+    ///
+    /// This is kept separate from [`Unary::parse_left_or_operand`] to keep its stack frame small,
+    /// since it is part of the recursion when parsing deeply nested expressions.
+    fn complete_left(input: &mut Parser, recoverable_errors: &mut Vec<Error>, operand: Expr) -> Result<Expr, Vec<Error>> {
+        let operand = Index::parse_postfix(input, recoverable_errors, operand)?;
         let start_span = operand.span().start;
 
         // one operator must be present
         let op = match try_parse_unary_op(input, Associativity::Left) {
             Ok(op) => op,
-            Err(_) => return Ok(operand.into()),
+            Err(_) => return Ok(operand),
         };
         let mut result = Self {
-            operand: Box::new(operand.into()),
+            operand: Box::new(operand),
             op,
             span: start_span..input.prev_token().unwrap().span.end,
         };
@@ -192,7 +192,7 @@ pub struct UnclosedParenthesis {
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
 #[error(
     message = "invalid left-hand-side of assignment operator",
-    labels = ["(1) this expression should be a symbol or function header...", "(2) ...to work with this assignment operator"],
+    labels = ["(1) this expression should be a symbol, list index, or function header...", "(2) ...to work with this assignment operator"],
     help = if self.is_call {
         "(1) looks like a function *call*, not a function *header*"
     } else {
@@ -208,7 +208,7 @@ pub struct InvalidAssignmentLhs {
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
 #[error(
     message = "invalid left-hand-side of compound assignment operator",
-    labels = ["(1) this expression must be a symbol...", "(2) ...to work with this compound assignment operator"],
+    labels = ["(1) this expression must be a symbol or list index...", "(2) ...to work with this compound assignment operator"],
     help = "use the standard assignment operator (`=`) instead",
 )]
 pub struct InvalidCompoundAssignmentLhs;
@@ -234,6 +234,15 @@ pub struct TooManyDerivatives {
     pub derivatives: usize,
 }
 
+/// A positional argument was given after a named argument in a function call.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "positional argument given after a named argument",
+    labels = ["this positional argument", "comes after this named argument"],
+    help = "move the positional argument before the named arguments, such as `k = 4` in `f(n, k = 4)`",
+)]
+pub struct PositionalAfterNamedArgument;
+
 /// Missing `then` or `else` keyword in an `if` expression.
 #[derive(Debug, Clone, ErrorKind, PartialEq)]
 #[error(
@@ -273,3 +282,12 @@ pub struct BreakOutsideLoop;
     help = "`break` or `continue` can only be used within a `loop` block",
 )]
 pub struct ContinueOutsideLoop;
+
+/// Cannot use `return` outside of a function.
+#[derive(Debug, Clone, ErrorKind, PartialEq)]
+#[error(
+    message = "cannot use `return` keyword here",
+    labels = [""],
+    help = "`return` can only be used within the body of a function or lambda",
+)]
+pub struct ReturnOutsideFunction;
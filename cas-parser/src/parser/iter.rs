@@ -78,6 +78,13 @@ impl<'a> Iterator for ExprIter<'a> {
                     self.stack.push(&while_expr.body);
                     self.stack.push(&while_expr.condition);
                 },
+                Expr::For(for_expr) => {
+                    if self.is_last_visited(&for_expr.body) {
+                        return self.visit();
+                    }
+                    self.stack.push(&for_expr.body);
+                    self.stack.push(&for_expr.iterable);
+                },
                 Expr::Break(break_expr) => {
                     if let Some(value) = &break_expr.value {
                         if self.is_last_visited(value) {
@@ -89,14 +96,35 @@ impl<'a> Iterator for ExprIter<'a> {
                     }
                 },
                 Expr::Continue(_) => return self.visit(),
-                Expr::Call(call) => {
-                    if call.args.is_empty() || self.is_last_visited(call.args.last().unwrap()) {
+                Expr::Return(return_expr) => {
+                    if let Some(value) = &return_expr.value {
+                        if self.is_last_visited(value) {
+                            return self.visit();
+                        }
+                        self.stack.push(value);
+                    } else {
                         return self.visit();
                     }
-                    for arg in call.args.iter().rev() {
+                },
+                Expr::Call(call) => {
+                    match call.arg_values().next_back() {
+                        Some(last) if !self.is_last_visited(last) => (),
+                        _ => return self.visit(),
+                    }
+                    for arg in call.arg_values().rev() {
                         self.stack.push(arg);
                     }
                 },
+                Expr::Index(index) => {
+                    let mut children = vec![&*index.target];
+                    children.extend(index.index.exprs());
+                    if self.is_last_visited(children[children.len() - 1]) {
+                        return self.visit();
+                    }
+                    for child in children.into_iter().rev() {
+                        self.stack.push(child);
+                    }
+                },
                 Expr::Unary(unary) => {
                     if self.is_last_visited(&unary.operand) {
                         return self.visit();
@@ -116,6 +144,12 @@ impl<'a> Iterator for ExprIter<'a> {
                     }
                     self.stack.push(&assign.value);
                 },
+                Expr::Lambda(lambda) => {
+                    if self.is_last_visited(&lambda.body) {
+                        return self.visit();
+                    }
+                    self.stack.push(&lambda.body);
+                },
             }
         }
     }
@@ -57,12 +57,51 @@ macro_rules! keywords {
     };
 }
 
+/// The `in` keyword of a `for` loop, such as in `for i in 0..10`.
+///
+/// `in` is only a keyword in this position, and is lexed as a [`TokenKind::Name`]. Elsewhere, it
+/// is an ordinary name, so that it can still be used as a variable, such as in `in = 2.54`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct In<'source> {
+    pub lexeme: &'source str,
+    pub span: Range<usize>,
+}
+
+impl<'source> Parse<'source> for In<'source> {
+    fn std_parse(
+        input: &mut Parser<'source>,
+        _: &mut Vec<Error>
+    ) -> Result<Self, Vec<Error>> {
+        let token = input.next_token().map_err(|e| vec![e])?;
+
+        if token.kind == TokenKind::Name && token.lexeme == "in" {
+            Ok(Self {
+                lexeme: token.lexeme,
+                span: token.span,
+            })
+        } else {
+            Err(vec![Error::new(vec![token.span], kind::UnexpectedToken {
+                expected: &[TokenKind::Keyword],
+                found: token.kind,
+            })])
+        }
+    }
+}
+
+impl Garbage for In<'_> {
+    fn garbage() -> Self {
+        Self { lexeme: "", span: 0..0 }
+    }
+}
+
 keywords!(
     (If, if)
     (Then, then)
     (Else, else)
     (Loop, loop)
     (While, while)
+    (For, for)
     (Break, break)
     (Continue, continue)
+    (Return, return)
 );
@@ -20,6 +20,14 @@ pub struct ParserState {
     /// Whether loop control expressions are allowed in the current context. This is used to
     /// determine if a `break` or `continue` expression is valid.
     pub allow_loop_control: bool,
+
+    /// Whether `return` expressions are allowed in the current context, which is the case inside
+    /// the body of a function definition or lambda.
+    pub allow_return: bool,
+
+    /// Whether the parser is parsing the iterable of a `for` loop. A block cannot be implicitly
+    /// multiplied here, since it begins the body of the loop.
+    pub in_for_iterable: bool,
 }
 
 /// A high-level parser for the language. This is the type to use to parse an arbitrary piece of
@@ -1679,6 +1687,7 @@ mod tests {
                                     span: 18..19,
                                 })),
                             ],
+                            named_args: vec![],
                             span: 12..20,
                             paren_span: 17..20,
                         }),
@@ -1946,6 +1955,7 @@ mod tests {
                     span: 2..3,
                 })),
             ],
+            named_args: vec![],
             span: 0..4,
             paren_span: 1..4,
         }));
@@ -1972,6 +1982,7 @@ mod tests {
                     span: 22..23,
                 })),
             ],
+            named_args: vec![],
             span: 0..25,
             paren_span: 16..25,
         }));
@@ -2053,6 +2064,243 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn index_and_slice() {
+        let mut parser = Parser::new("l[0][1:]");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        assert_eq!(expr, Expr::Index(Index {
+            target: Box::new(Expr::Index(Index {
+                target: Box::new(Expr::Literal(Literal::Symbol(LitSym {
+                    name: "l".to_string(),
+                    span: 0..1,
+                }))),
+                index: IndexKind::Single(Box::new(Expr::Literal(Literal::Integer(LitInt {
+                    value: "0".to_string(),
+                    span: 2..3,
+                })))),
+                span: 0..4,
+                bracket_span: 1..4,
+            })),
+            index: IndexKind::Slice {
+                start: Some(Box::new(Expr::Literal(Literal::Integer(LitInt {
+                    value: "1".to_string(),
+                    span: 5..6,
+                })))),
+                end: None,
+            },
+            span: 0..8,
+            bracket_span: 4..8,
+        }));
+    }
+
+    #[test]
+    fn slice_without_bounds() {
+        let mut parser = Parser::new("[1, 2, 3][:]");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        let Expr::Index(index) = expr else {
+            panic!("expected an index expression");
+        };
+        assert_eq!(index.index, IndexKind::Slice { start: None, end: None });
+        assert_eq!(index.bracket_span, 9..12);
+    }
+
+    #[test]
+    fn index_requires_adjacent_bracket() {
+        // with whitespace, this is implicit multiplication by a list
+        let mut parser = Parser::new("x [1]");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+        assert!(matches!(expr, Expr::Binary(Binary { op: BinOp { implicit: true, .. }, .. })));
+    }
+
+    #[test]
+    fn assign_to_index() {
+        let mut parser = Parser::new("l[-1] += 5");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        assert_eq!(expr, Expr::Assign(Assign {
+            target: AssignTarget::Index(Index {
+                target: Box::new(Expr::Literal(Literal::Symbol(LitSym {
+                    name: "l".to_string(),
+                    span: 0..1,
+                }))),
+                index: IndexKind::Single(Box::new(Expr::Unary(Unary {
+                    operand: Box::new(Expr::Literal(Literal::Integer(LitInt {
+                        value: "1".to_string(),
+                        span: 3..4,
+                    }))),
+                    op: UnaryOp {
+                        kind: UnaryOpKind::Neg,
+                        span: 2..3,
+                    },
+                    span: 2..4,
+                }))),
+                span: 0..5,
+                bracket_span: 1..5,
+            }),
+            op: AssignOp {
+                kind: AssignOpKind::Add,
+                span: 6..8,
+            },
+            value: Box::new(Expr::Literal(Literal::Integer(LitInt {
+                value: "5".to_string(),
+                span: 9..10,
+            }))),
+            span: 0..10,
+        }));
+    }
+
+    #[test]
+    fn assign_to_index_of_call() {
+        let mut parser = Parser::new("f(x)[0] = 1");
+        assert!(parser.try_parse_full::<Expr>().is_err());
+    }
+
+    #[test]
+    fn lambda() {
+        let mut parser = Parser::new("x -> x^2");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        assert_eq!(expr, Expr::Lambda(Lambda {
+            params: vec![
+                Param::Symbol(LitSym {
+                    name: "x".to_string(),
+                    span: 0..1,
+                }),
+            ],
+            body: Box::new(Expr::Binary(Binary {
+                lhs: Box::new(Expr::Literal(Literal::Symbol(LitSym {
+                    name: "x".to_string(),
+                    span: 5..6,
+                }))),
+                op: BinOp {
+                    kind: BinOpKind::Exp,
+                    implicit: false,
+                    span: 6..7,
+                },
+                rhs: Box::new(Expr::Literal(Literal::Integer(LitInt {
+                    value: "2".to_string(),
+                    span: 7..8,
+                }))),
+                span: 5..8,
+            })),
+            span: 0..8,
+            arrow_span: 2..4,
+        }));
+    }
+
+    #[test]
+    fn lambda_with_params() {
+        let mut parser = Parser::new("(x, y = 1) -> x + y");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        let Expr::Lambda(lambda) = expr else {
+            panic!("expected a lambda, got {:?}", expr);
+        };
+        assert_eq!(lambda.params.len(), 2);
+        assert!(matches!(lambda.params[1], Param::Default(..)));
+        assert_eq!(lambda.span, 0..19);
+        assert!(matches!(*lambda.body, Expr::Binary(_)));
+    }
+
+    #[test]
+    fn lambda_as_argument() {
+        let mut parser = Parser::new("map(x -> x + 1, [1, 2, 3])");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        let Expr::Call(call) = expr else {
+            panic!("expected a call, got {:?}", expr);
+        };
+        assert_eq!(call.args.len(), 2);
+        assert!(matches!(call.args[0], Expr::Lambda(_)));
+    }
+
+    #[test]
+    fn named_arguments() {
+        let mut parser = Parser::new("f(2, k = 4, m = n = 1)");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        let Expr::Call(call) = expr else {
+            panic!("expected a call, got {:?}", expr);
+        };
+        assert_eq!(call.args.len(), 1);
+        assert_eq!(call.named_args.len(), 2);
+        assert_eq!(call.named_args[0].name.name, "k");
+        assert_eq!(call.named_args[0].span, 5..10);
+        assert!(matches!(call.named_args[1].value, Expr::Assign(_)));
+        assert_eq!(call.to_string(), "f(2, k = 4, m = n = 1)");
+    }
+
+    #[test]
+    fn positional_after_named_argument() {
+        let mut parser = Parser::new("f(k = 4, 5)");
+        assert!(parser.try_parse_full::<Expr>().is_err());
+    }
+
+    #[test]
+    fn lambda_loop_control() {
+        let mut parser = Parser::new("loop { f = x -> break x }");
+        assert!(parser.try_parse_full::<Expr>().is_err());
+    }
+
+    #[test]
+    fn range() {
+        let mut parser = Parser::new("0..n + 1");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        let Expr::Binary(binary) = expr else {
+            panic!("expected a range, got {:?}", expr);
+        };
+        assert_eq!(binary.op.kind, BinOpKind::RangeExclusive);
+        assert_eq!(binary.op.span, 1..3);
+        assert!(matches!(*binary.rhs, Expr::Binary(_)));
+        assert_eq!(binary.to_string(), "0..n+1");
+    }
+
+    #[test]
+    fn for_loop() {
+        let mut parser = Parser::new("for i in 1..=2n { x += i; break }");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+
+        let Expr::For(for_expr) = expr else {
+            panic!("expected a for loop, got {:?}", expr);
+        };
+        assert_eq!(for_expr.variable.name, "i");
+        assert_eq!(for_expr.iterable.to_string(), "1..=2n");
+        assert!(matches!(*for_expr.body, Expr::Block(_)));
+        assert_eq!(for_expr.span, 0..33);
+        assert_eq!(for_expr.in_span, 6..8);
+    }
+
+    #[test]
+    fn in_as_name() {
+        // `in` is only a keyword in a `for` loop, so it can still be used as a name
+        let mut parser = Parser::new("in = 2.54");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+        assert!(matches!(expr, Expr::Assign(_)));
+
+        let mut parser = Parser::new("for x in 0..3in { x }");
+        let expr = parser.try_parse_full::<Expr>().unwrap();
+        let Expr::For(for_expr) = expr else {
+            panic!("expected a for loop, got {:?}", expr);
+        };
+        assert_eq!(for_expr.iterable.to_string(), "0..3in");
+        assert_eq!(for_expr.in_span, 6..8);
+    }
+
+    #[test]
+    fn return_expr() {
+        let mut parser = Parser::new("f(x) = { if x < 0 then return 0; x }");
+        assert!(parser.try_parse_full::<Expr>().is_ok());
+
+        let mut parser = Parser::new("g = x -> return x");
+        assert!(parser.try_parse_full::<Expr>().is_ok());
+
+        let mut parser = Parser::new("for i in [1, 2] { return i }");
+        assert!(parser.try_parse_full::<Expr>().is_err());
+    }
+
     #[test]
     fn source_code() {
         let mut parser = Parser::new("x = 5;
@@ -102,6 +102,10 @@ token_kinds!(
     CloseSquare
     Quote
     Semicolon
+    Colon
+    Arrow
+    RangeExclusive
+    RangeInclusive
     Int
     Float
     Boolean
@@ -38,6 +38,9 @@ pub enum Precedence {
     /// `|=`, `>>=`, and `<<=`).
     Assign,
 
+    /// Precedence of ranges (`..` and `..=`).
+    Range,
+
     /// Precedence of logical or (`or`).
     Or,
 
@@ -224,6 +227,8 @@ pub enum BinOpKind {
     ApproxNotEq,
     And,
     Or,
+    RangeExclusive,
+    RangeInclusive,
 }
 
 impl BinOpKind {
@@ -240,6 +245,7 @@ impl BinOpKind {
                 | Self::Eq | Self::NotEq | Self::ApproxEq | Self::ApproxNotEq => Precedence::Compare,
             Self::And => Precedence::And,
             Self::Or => Precedence::Or,
+            Self::RangeExclusive | Self::RangeInclusive => Precedence::Range,
         }
     }
 
@@ -253,7 +259,8 @@ impl BinOpKind {
                 | Self::BitAnd | Self::BitOr
                 | Self::Greater | Self::GreaterEq | Self::Less | Self::LessEq
                 | Self::Eq | Self::NotEq | Self::ApproxEq | Self::ApproxNotEq
-                | Self::And | Self::Or => Associativity::Left,
+                | Self::And | Self::Or
+                | Self::RangeExclusive | Self::RangeInclusive => Associativity::Left,
         }
     }
 }
@@ -311,6 +318,8 @@ impl<'source> Parse<'source> for BinOp {
             TokenKind::ApproxNotEq => Ok(BinOpKind::ApproxNotEq),
             TokenKind::And => Ok(BinOpKind::And),
             TokenKind::Or => Ok(BinOpKind::Or),
+            TokenKind::RangeExclusive => Ok(BinOpKind::RangeExclusive),
+            TokenKind::RangeInclusive => Ok(BinOpKind::RangeInclusive),
             _ => Err(vec![Error::new(
                 vec![token.span.clone()],
                 kind::UnexpectedToken {
@@ -361,6 +370,8 @@ impl std::fmt::Display for BinOp {
             BinOpKind::ApproxNotEq => write!(f, "~!="),
             BinOpKind::And => write!(f, "&&"),
             BinOpKind::Or => write!(f, "||"),
+            BinOpKind::RangeExclusive => write!(f, ".."),
+            BinOpKind::RangeInclusive => write!(f, "..="),
         }
     }
 }
@@ -392,6 +403,8 @@ impl Latex for BinOp {
             BinOpKind::ApproxNotEq => write!(f, "\\not\\approx "),
             BinOpKind::And => write!(f, "\\wedge "),
             BinOpKind::Or => write!(f, "\\vee "),
+            BinOpKind::RangeExclusive => write!(f, ".."),
+            BinOpKind::RangeInclusive => write!(f, "..="),
         }
     }
 }
@@ -12,11 +12,32 @@ pub fn tokenize(input: &str) -> Lexer<TokenKind> {
 /// to backtrack in case of an error.
 pub fn tokenize_complete(input: &str) -> Box<[Token]> {
     let mut lexer = tokenize(input);
+    let mut offset = 0;
     let mut tokens = Vec::new();
 
     while let Some(Ok(kind)) = lexer.next() {
+        let span = lexer.span().start + offset..lexer.span().end + offset;
+
+        // an integer directly followed by a range operator, such as the `0` in `0..10`, is lexed
+        // as a number with the first `.` of the operator as its decimal point
+        //
+        // give the `.` back by lexing the rest of the input again, starting from the `.`
+        if matches!(kind, TokenKind::Int | TokenKind::Float)
+            && lexer.slice().ends_with('.')
+            && lexer.remainder().starts_with('.') {
+            let end = span.end - 1;
+            tokens.push(Token {
+                span: span.start..end,
+                kind: TokenKind::Int,
+                lexeme: &input[span.start..end],
+            });
+            lexer = tokenize(&input[end..]);
+            offset = end;
+            continue;
+        }
+
         tokens.push(Token {
-            span: lexer.span(),
+            span,
             kind,
             lexeme: lexer.slice(),
         });
@@ -80,4 +101,42 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn range_after_integer() {
+        let tokens = tokenize_complete("0..10 1..=n");
+        let tokens = tokens.iter()
+            .map(|token| (token.kind, token.lexeme, token.span.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, [
+            (TokenKind::Int, "0", 0..1),
+            (TokenKind::RangeExclusive, "..", 1..3),
+            (TokenKind::Int, "10", 3..5),
+            (TokenKind::Whitespace, " ", 5..6),
+            (TokenKind::Int, "1", 6..7),
+            (TokenKind::RangeInclusive, "..=", 7..10),
+            (TokenKind::Name, "n", 10..11),
+        ]);
+    }
+
+    #[test]
+    fn range_with_floats() {
+        let tokens = tokenize_complete("1.5..3 0..1. 2.5..=1.5");
+        let tokens = tokens.iter()
+            .map(|token| (token.kind, token.lexeme, token.span.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, [
+            (TokenKind::Float, "1.5", 0..3),
+            (TokenKind::RangeExclusive, "..", 3..5),
+            (TokenKind::Int, "3", 5..6),
+            (TokenKind::Whitespace, " ", 6..7),
+            (TokenKind::Int, "0", 7..8),
+            (TokenKind::RangeExclusive, "..", 8..10),
+            (TokenKind::Float, "1.", 10..12),
+            (TokenKind::Whitespace, " ", 12..13),
+            (TokenKind::Float, "2.5", 13..16),
+            (TokenKind::RangeInclusive, "..=", 16..19),
+            (TokenKind::Float, "1.5", 19..22),
+        ]);
+    }
 }
@@ -133,7 +133,7 @@ pub enum TokenKind {
     #[regex(r"([a-zA-Z_]+|atan2)")] // TODO: includes horrible hard-coded test for atan2
     Name,
 
-    #[regex(r"(if|then|else|loop|while|break|continue)")]
+    #[regex(r"(if|then|else|loop|while|for|break|continue|return)")]
     Keyword,
 
     #[token(",")]
@@ -163,6 +163,18 @@ pub enum TokenKind {
     #[token(";")]
     Semicolon,
 
+    #[token(":")]
+    Colon,
+
+    #[token("->")]
+    Arrow,
+
+    #[token("..")]
+    RangeExclusive,
+
+    #[token("..=")]
+    RangeInclusive,
+
     #[regex(r"[0-9]+\.?")]
     Int,
 
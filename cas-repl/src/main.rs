@@ -1,6 +1,6 @@
 mod error;
 
-use cas_compute::numerical::{ctxt::Ctxt, eval::eval_stmts, fmt::{FormatOptionsBuilder, NumberFormat, Scientific, Separator}, value::Value};
+use cas_compute::numerical::{ctxt::Ctxt, fmt::{FormatOptionsBuilder, NumberFormat, Scientific, Separator}, value::Value, vm::run_stmts};
 use cas_parser::parser::Parser;
 use error::Error;
 use rustyline::{error::ReadlineError, DefaultEditor};
@@ -9,24 +9,27 @@ use std::{fs::File, io::{self, BufReader, IsTerminal, Read}};
 /// Parses and evaluates the given input string, returning the results of both operations.
 fn parse_eval(input: &str, ctxt: &mut Ctxt) -> Result<Value, Error> {
     let ast = Parser::new(input).try_parse_full_many()?;
-    let res = eval_stmts(&ast, ctxt)?;
+    let res = run_stmts(&ast, ctxt)?;
     Ok(res)
 }
 
 /// Reads from the provided file or stdin and parses / evaluates the input, printing the success or
 /// failure.
 fn read_eval(input: &str, ctxt: &mut Ctxt) {
-    // let fmt = FormatOptions {
-    let fmt = FormatOptionsBuilder::new()
-        .number(NumberFormat::Auto)
-        .scientific(Scientific::Times)
-        .precision(Some(150))
-        .separators(Separator::Never)
-        .build();
-
     match parse_eval(input, ctxt) {
         Ok(Value::Unit) => (), // intentionally print nothing
-        Ok(res) => println!("{}", res.fmt(fmt)),
+        Ok(res) => {
+            // show the digits that fit in the precision of the context, trimming off a few that
+            // may be affected by rounding errors
+            let digits = (ctxt.precision() as f64 * std::f64::consts::LOG10_2) as usize;
+            let fmt = FormatOptionsBuilder::new()
+                .number(NumberFormat::Auto)
+                .scientific(Scientific::Times)
+                .precision(Some(digits.saturating_sub(4).max(1)))
+                .separators(Separator::Never)
+                .build();
+            println!("{}", res.fmt(fmt));
+        },
         Err(err) => err.report_to_stderr(input),
     }
 }